@@ -0,0 +1,72 @@
+//! Micro-benchmark for concurrent tool lookups in the ToolRegistry.
+//!
+//! Run with `cargo bench --bench tool_registry`.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use indubitably_rust_agent_sdk::tools::registry::{Tool, ToolRegistry};
+
+const TOOL_COUNT: usize = 64;
+const LOOKUPS_PER_TASK: usize = 200_000;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    let registry = ToolRegistry::new();
+    for i in 0..TOOL_COUNT {
+        let tool = Tool::new(
+            &format!("tool_{}", i),
+            "A benchmark tool",
+            Arc::new(Ok),
+        );
+        registry.register(tool).await.unwrap();
+    }
+
+    for tasks in [1, 4, 16] {
+        let start = Instant::now();
+        let mut handles = Vec::new();
+        for task in 0..tasks {
+            let registry = registry.clone();
+            handles.push(tokio::spawn(async move {
+                let mut found = 0usize;
+                for i in 0..LOOKUPS_PER_TASK {
+                    let name = format!("tool_{}", (i + task) % TOOL_COUNT);
+                    if registry.get(&name).await.is_some() {
+                        found += 1;
+                    }
+                }
+                found
+            }));
+        }
+
+        // A concurrent writer exercises the copy-on-write path during lookups
+        let writer = {
+            let registry = registry.clone();
+            tokio::spawn(async move {
+                for i in 0..100 {
+                    let tool = Tool::new("churn", "A churning tool", Arc::new(Ok));
+                    registry.register(tool).await.unwrap();
+                    if i % 2 == 0 {
+                        registry.unregister("churn").await.unwrap();
+                    }
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+
+        let mut total = 0usize;
+        for handle in handles {
+            total += handle.await.unwrap();
+        }
+        writer.await.unwrap();
+
+        let elapsed = start.elapsed();
+        println!(
+            "tasks=<{}>, lookups=<{}>, elapsed_ms=<{}>, lookups_per_sec=<{:.0}> | tool registry lookup throughput",
+            tasks,
+            total,
+            elapsed.as_millis(),
+            total as f64 / elapsed.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Procedural macros for the Indubitably Rust agent SDK.
+//!
+//! This crate provides the `#[tool]` attribute, which turns a function with
+//! typed arguments into an agent tool. It is re-exported by the SDK as
+//! `indubitably_rust_agent_sdk::tools::tool` and should not be used directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Expr, ExprLit, FnArg, GenericArgument, Ident, ItemFn, Lit, LitStr, Meta, Pat, PathArguments, ReturnType,
+    Token, Type,
+};
+
+/// The tool name and description given to the attribute, if any.
+#[derive(Default)]
+struct ToolArgs {
+    name: Option<LitStr>,
+    description: Option<LitStr>,
+}
+
+impl Parse for ToolArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = ToolArgs::default();
+        if input.peek(LitStr) {
+            args.name = Some(input.parse()?);
+            if input.parse::<Option<Token![,]>>()?.is_some() && input.peek(LitStr) {
+                args.description = Some(input.parse()?);
+            }
+            return Ok(args);
+        }
+        for meta in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let Meta::NameValue(pair) = meta else {
+                return Err(syn::Error::new_spanned(meta, "expected `name = \"...\"` or `description = \"...\"`"));
+            };
+            let Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) = pair.value else {
+                return Err(syn::Error::new_spanned(pair.value, "expected a string literal"));
+            };
+            if pair.path.is_ident("name") {
+                args.name = Some(value);
+            } else if pair.path.is_ident("description") {
+                args.description = Some(value);
+            } else {
+                return Err(syn::Error::new_spanned(pair.path, "unknown `tool` argument"));
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Define an agent tool from a function with typed arguments.
+///
+/// The function is kept as written, and a `create_<name>_tool()` function is
+/// added next to it that returns the `Tool`. The tool's input schema is built
+/// from the argument names and types: strings, numbers, booleans, vectors,
+/// maps and `Option`s, which mark optional arguments. The description is the
+/// first paragraph of the function's doc comment, and arguments are
+/// described by a `# Arguments` section with lines such as
+/// `` * `city` - The city to look up ``. The name and description can also
+/// be given as `#[tool(name = "...", description = "...")]`.
+///
+/// The function may be `async`, and may return a `Result` whose error is
+/// reported as a failed tool call, or any serializable value.
+#[proc_macro_attribute]
+pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as ToolArgs);
+    let function = syn::parse_macro_input!(item as ItemFn);
+    expand(args, function).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(args: ToolArgs, function: ItemFn) -> syn::Result<TokenStream2> {
+    let sdk = quote!(::indubitably_rust_agent_sdk);
+    let signature = &function.sig;
+    let ident = &signature.ident;
+    if !signature.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(&signature.generics, "tool functions cannot be generic"));
+    }
+    let (summary, argument_docs) = parse_docs(&function.attrs);
+    let name = args.name.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+    let description = args.description.unwrap_or_else(|| LitStr::new(&summary, Span::call_site()));
+
+    let mut extractions = Vec::new();
+    let mut call_args = Vec::new();
+    let mut properties = Vec::new();
+    let mut required = Vec::new();
+    for input in &signature.inputs {
+        let FnArg::Typed(typed) = input else {
+            return Err(syn::Error::new_spanned(input, "tool functions cannot take `self`"));
+        };
+        let Pat::Ident(pattern) = typed.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(&typed.pat, "tool arguments must be plain names"));
+        };
+        let argument = pattern.ident.to_string();
+        let argument = argument.trim_start_matches("r#").to_string();
+        let local = format_ident!("__{}", pattern.ident);
+        let (owned, borrowed) = owned_type(&typed.ty);
+        let message = format!("Invalid '{}': {{}}", argument);
+        extractions.push(quote! {
+            let #local: #owned = #sdk::__private::serde_json::from_value(
+                input.get(#argument).cloned().unwrap_or(#sdk::__private::serde_json::Value::Null),
+            )
+            .map_err(|e| #sdk::types::IndubitablyError::ToolError(
+                #sdk::types::ToolError::InvalidInput(format!(#message, e)),
+            ))?;
+        });
+        call_args.push(if borrowed { quote!(&#local) } else { quote!(#local) });
+
+        let mut schema = type_schema(&owned);
+        if let Some(doc) = argument_docs.iter().find(|(name, _)| *name == argument).map(|(_, doc)| doc) {
+            schema = quote!({
+                let mut schema = #schema;
+                schema["description"] = #sdk::__private::serde_json::Value::String(#doc.to_string());
+                schema
+            });
+        }
+        properties.push(quote!(properties.insert(#argument.to_string(), #schema);));
+        if option_inner(&owned).is_none() {
+            required.push(argument);
+        }
+    }
+
+    let call = if signature.asyncness.is_some() {
+        quote!(#sdk::tools::decorator::block_on_tool(async move { #ident(#(#call_args),*).await })?)
+    } else {
+        quote!(#ident(#(#call_args),*))
+    };
+    let output = if returns_result(&signature.output) {
+        quote! {
+            #call.map_err(|e| #sdk::types::IndubitablyError::ToolError(
+                #sdk::types::ToolError::ExecutionFailed(e.to_string()),
+            ))?
+        }
+    } else {
+        call
+    };
+    let vis = &function.vis;
+    let constructor = format_ident!("create_{}_tool", ident.to_string().trim_start_matches("r#"));
+    let constructor_doc = format!("Create the `{}` tool.", name.value());
+
+    Ok(quote! {
+        #function
+
+        #[doc = #constructor_doc]
+        #vis fn #constructor() -> #sdk::tools::Tool {
+            let function = |input: #sdk::__private::serde_json::Value| {
+                #(#extractions)*
+                let output = #output;
+                #sdk::__private::serde_json::to_value(output).map_err(|e| #sdk::types::IndubitablyError::ToolError(
+                    #sdk::types::ToolError::InvalidOutput(e.to_string()),
+                ))
+            };
+            let mut properties = #sdk::__private::serde_json::Map::new();
+            #(#properties)*
+            let schema = #sdk::__private::serde_json::json!({
+                "type": "object",
+                "properties": properties,
+                "required": [#(#required),*],
+            });
+            #sdk::tools::Tool::new(#name, #description, ::std::sync::Arc::new(function))
+                .with_metadata(#sdk::tools::ToolMetadata::new().with_input_schema(schema))
+        }
+    })
+}
+
+/// Split a doc comment into its first paragraph and the entries of its `# Arguments` section.
+fn parse_docs(attrs: &[Attribute]) -> (String, Vec<(String, String)>) {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(pair) => match &pair.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(text), .. }) => Some(text.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let summary = lines
+        .iter()
+        .take_while(|line| !line.is_empty() && !line.starts_with('#'))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut arguments = Vec::new();
+    let mut in_arguments = false;
+    for line in &lines {
+        if line.starts_with('#') {
+            in_arguments = line.trim_start_matches('#').trim().eq_ignore_ascii_case("arguments");
+            continue;
+        }
+        let entry = line.strip_prefix('*').or_else(|| line.strip_prefix('-'));
+        if let (true, Some(entry)) = (in_arguments, entry) {
+            let entry = entry.trim();
+            if let Some((name, doc)) = entry.split_once(" - ").or_else(|| entry.split_once(':')) {
+                arguments.push((name.trim().trim_matches('`').to_string(), doc.trim().to_string()));
+            }
+        }
+    }
+    (summary, arguments)
+}
+
+/// Get the type an argument is deserialized into, and whether the function takes it by reference.
+fn owned_type(ty: &Type) -> (Type, bool) {
+    let Type::Reference(reference) = ty else {
+        return (ty.clone(), false);
+    };
+    let owned = match reference.elem.as_ref() {
+        Type::Path(path) if path.path.is_ident("str") => syn::parse_quote!(::std::string::String),
+        Type::Slice(slice) => {
+            let element = &slice.elem;
+            syn::parse_quote!(::std::vec::Vec<#element>)
+        }
+        other => other.clone(),
+    };
+    (owned, true)
+}
+
+/// Get the last path segment of a type and its generic arguments.
+fn last_segment(ty: &Type) -> Option<(&Ident, Vec<&Type>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let arguments = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments
+            .args
+            .iter()
+            .filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Some((&segment.ident, arguments))
+}
+
+/// Get the inner type of an `Option`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    match last_segment(ty)? {
+        (ident, arguments) if ident == "Option" && arguments.len() == 1 => Some(arguments[0]),
+        _ => None,
+    }
+}
+
+/// Check whether a function returns a `Result`.
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => last_segment(ty).is_some_and(|(ident, _)| ident.to_string().ends_with("Result")),
+        ReturnType::Default => false,
+    }
+}
+
+/// Build the JSON schema of an argument type.
+fn type_schema(ty: &Type) -> TokenStream2 {
+    let sdk = quote!(::indubitably_rust_agent_sdk);
+    let json = |schema: TokenStream2| quote!(#sdk::__private::serde_json::json!(#schema));
+    if let Type::Array(array) = ty {
+        let items = type_schema(&array.elem);
+        return json(quote!({ "type": "array", "items": #items }));
+    }
+    if let Type::Tuple(tuple) = ty {
+        if tuple.elems.is_empty() {
+            return json(quote!({ "type": "null" }));
+        }
+    }
+    let Some((ident, arguments)) = last_segment(ty) else {
+        return json(quote!({}));
+    };
+    match (ident.to_string().as_str(), arguments.as_slice()) {
+        ("String" | "str" | "char" | "PathBuf", _) => json(quote!({ "type": "string" })),
+        ("bool", _) => json(quote!({ "type": "boolean" })),
+        ("i8" | "i16" | "i32" | "i64" | "i128" | "isize", _) => json(quote!({ "type": "integer" })),
+        ("u8" | "u16" | "u32" | "u64" | "u128" | "usize", _) => json(quote!({ "type": "integer", "minimum": 0 })),
+        ("f32" | "f64", _) => json(quote!({ "type": "number" })),
+        ("Option" | "Box", [inner]) => type_schema(inner),
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => {
+            let items = type_schema(inner);
+            json(quote!({ "type": "array", "items": #items }))
+        }
+        ("HashMap" | "BTreeMap", [_, value]) => {
+            let values = type_schema(value);
+            json(quote!({ "type": "object", "additionalProperties": #values }))
+        }
+        ("Map", _) => json(quote!({ "type": "object" })),
+        _ => json(quote!({})),
+    }
+}
@@ -3,17 +3,61 @@
 //! This module provides the core Agent struct that orchestrates
 //! conversations, tool execution, and model interactions.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::types::{Messages, Message, ToolSpec, IndubitablyResult};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::types::{AudioContent, Citation, ContentBlock, DocumentContent, ImageContent, IndubitablyError, MessageRole, Messages, Message, Session, ToolSpec, ToolUse, ToolResult, ToolResultContent, StreamEvent, ConversationError, HookError, IndubitablyResult, ModelError, ToolError};
 use crate::models::Model;
+use crate::media::transcription::{Transcriber, TRANSCRIPTION_METADATA_KEY};
+use crate::models::tokenizer::TokenizerRegistry;
+use crate::models::rate_limit::{RateLimitPermit, RateLimiter};
 use super::state::AgentState;
-use super::result::AgentResult;
+use super::result::{AgentResult, StopReason, ToolCallRecord};
 use super::conversation_manager::{ConversationManager, ConversationManagerConfig};
-use crate::tools::registry::ToolRegistry;
+use super::limits::MemoryLimits;
+use super::degraded::{DegradedModeHandler, DegradedResponseKind};
+use super::budget::{BudgetStatus, BudgetUsage, ConversationBudget};
+use super::clarification::ClarificationPolicy;
+use super::editing::{find_user_turn, last_user_turn, ConversationFork, EditOptions};
+use super::redaction::SecretRedactor;
+use super::guardrail::{AppealRoute, GuardrailDecision, GuardrailLog, GuardrailPolicy, GuardrailStage, GUARDRAIL_BLOCKED_RESPONSE};
+use super::debug_bundle::{scrub, version_info, DebugBundle, DEFAULT_DEBUG_EVENT_CAPACITY};
+use super::experiments::{labels_of, Assignment, Experiments};
+use super::compression::ContextCompressor;
+use super::run_options::RunOptions;
+use super::interrupt::{Interrupt, InterruptDecision, INTERRUPT_APPROVED_MESSAGE};
+use super::liveness::{LivenessConfig, RunPhase, RunProbe, Watchdog};
+use super::validation::{validate_config, ConfigReport};
+use super::transcript::HistorySource;
+use super::retention::ExpiredContentIndex;
+use super::snapshot::{AgentSnapshot, AGENT_SNAPSHOT_VERSION};
+use super::post_process::{PostProcessor, PostProcessorChain};
+use super::plan::{ExecutionPlan, PlannedToolCall, DEFAULT_MAX_PLAN_ROUNDS, PLANNED_TOOL_RESULT};
+use crate::event_loop::EventLoop;
+use crate::tools::executor::{ToolExecutionResult, ToolExecutor};
+use crate::tools::repair::{malformed_tool_calls, retry_message, DEFAULT_MAX_ARGUMENT_RETRIES};
+use crate::tools::registry::{ToolFilter, ToolRegistry};
+use crate::tools::render::RendererRegistry;
+use crate::tools::image_generation::take_images;
+use crate::tools::selector::ToolSelector;
+use crate::tools::workspace::{Workspace, WorkspaceConfig};
+use crate::telemetry::events::{
+    EventBus, EventRecorder, HookSubscriber, LifecycleEvent, LifecycleEventKind, MetricsSubscriber, TracingSubscriber,
+};
+use crate::telemetry::Metrics;
+use crate::telemetry::sink::{MetricEvent, MetricsSink};
+use crate::telemetry::timeline::{SpanCategory, Timeline};
+use crate::hooks::{HookRegistry, Subscription, TypedEvent};
+use crate::session::user_memory::UserMemory;
+use crate::models::model::{ModelResponse, ModelUsage};
+
+pub use crate::telemetry::events::{METRIC_RESPONSES_DEGRADED, METRIC_RESPONSES_NORMAL};
 
 /// Configuration for an agent.
 pub struct AgentConfig {
@@ -27,6 +71,52 @@ pub struct AgentConfig {
     pub tools: Vec<ToolSpec>,
     /// The conversation manager configuration.
     pub conversation_config: ConversationManagerConfig,
+    /// The memory limits for the agent.
+    pub memory_limits: MemoryLimits,
+    /// The handler invoked when the model is unavailable.
+    pub degraded_mode_handler: Option<Arc<dyn DegradedModeHandler>>,
+    /// The token and cost budget for the conversation.
+    pub budget: Option<ConversationBudget>,
+    /// The policy deciding when to stop and ask the user for clarification.
+    pub clarification_policy: Option<ClarificationPolicy>,
+    /// Whether tools that write, send or delete are refused.
+    pub read_only: bool,
+    /// The stage choosing which tool specs to send each turn; all are sent when unset.
+    pub tool_selector: Option<Arc<dyn ToolSelector>>,
+    /// The filter choosing which registered tools the agent sees and may call; all are when unset.
+    pub tool_filter: Option<ToolFilter>,
+    /// The guardrail masking secrets in model responses and tool inputs.
+    pub secret_redactor: Option<Arc<SecretRedactor>>,
+    /// The policy blocking or flagging user messages and model answers.
+    pub guardrail_policy: Option<Arc<GuardrailPolicy>>,
+    /// The experiments sessions are assigned to with `Agent::join_experiments`.
+    pub experiments: Option<Arc<Experiments>>,
+    /// The memory of durable facts about users, shared across their sessions.
+    pub user_memory: Option<Arc<UserMemory>>,
+    /// The stage compressing retrieved documents and old turns before each model call.
+    pub compressor: Option<Arc<ContextCompressor>>,
+    /// The tokenizers estimating request size and unreported usage.
+    pub tokenizers: Option<Arc<TokenizerRegistry>>,
+    /// The model's context window in tokens; requests estimated above it are refused.
+    pub context_window: Option<usize>,
+    /// The number of times per run the model is asked to re-emit tool calls whose arguments are not valid JSON.
+    pub max_tool_argument_retries: usize,
+    /// The sink receiving typed cycle, latency and token metrics from the event loop.
+    pub metrics_sink: Option<Arc<dyn MetricsSink>>,
+    /// Where each run gets a scoped working directory for its tools, if anywhere.
+    pub workspace: Option<WorkspaceConfig>,
+    /// The limiter scheduling model calls, usually shared with other agents.
+    pub rate_limiter: Option<RateLimiter>,
+    /// The processors rewriting each final answer before it is returned and stored.
+    pub post_processors: PostProcessorChain,
+    /// The service turning voice input from `Agent::run_with_audio` into text.
+    pub transcriber: Option<Arc<dyn Transcriber>>,
+    /// When long runs send heartbeats and when the watchdog aborts them; runs are unwatched when unset.
+    pub liveness: Option<LivenessConfig>,
+    /// The conversation the agent starts in, seeded into its conversation manager before the first run.
+    pub history: Option<HistorySource>,
+    /// The renderers whose UI hints are attached to `toolCompleted` stream events.
+    pub result_renderers: Option<RendererRegistry>,
     /// Additional configuration options.
     pub options: HashMap<String, Value>,
 }
@@ -39,6 +129,29 @@ impl Default for AgentConfig {
             model: None,
             tools: Vec::new(),
             conversation_config: ConversationManagerConfig::default(),
+            memory_limits: MemoryLimits::default(),
+            degraded_mode_handler: None,
+            budget: None,
+            clarification_policy: None,
+            read_only: false,
+            tool_selector: None,
+            tool_filter: None,
+            secret_redactor: None,
+            guardrail_policy: None,
+            experiments: None,
+            user_memory: None,
+            compressor: None,
+            tokenizers: None,
+            context_window: None,
+            max_tool_argument_retries: DEFAULT_MAX_ARGUMENT_RETRIES,
+            metrics_sink: None,
+            workspace: None,
+            rate_limiter: None,
+            post_processors: PostProcessorChain::new(),
+            transcriber: None,
+            liveness: None,
+            history: None,
+            result_renderers: None,
             options: HashMap::new(),
         }
     }
@@ -80,6 +193,144 @@ impl AgentConfig {
         self
     }
 
+    /// Set the memory limits.
+    pub fn with_memory_limits(mut self, limits: MemoryLimits) -> Self {
+        self.memory_limits = limits;
+        self
+    }
+
+    /// Set the handler invoked when the model is unavailable.
+    pub fn with_degraded_mode_handler(mut self, handler: Arc<dyn DegradedModeHandler>) -> Self {
+        self.degraded_mode_handler = Some(handler);
+        self
+    }
+
+    /// Set the conversation budget.
+    pub fn with_budget(mut self, budget: ConversationBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set the clarification policy.
+    pub fn with_clarification_policy(mut self, policy: ClarificationPolicy) -> Self {
+        self.clarification_policy = Some(policy);
+        self
+    }
+
+    /// Refuse every tool flagged as writing, sending or deleting.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Set the stage choosing which tool specs to send each turn.
+    pub fn with_tool_selector(mut self, selector: Arc<dyn ToolSelector>) -> Self {
+        self.tool_selector = Some(selector);
+        self
+    }
+
+    /// Expose only the tools a filter lets through, such as those in a tenant's namespaces.
+    pub fn with_tool_filter(mut self, filter: ToolFilter) -> Self {
+        self.tool_filter = Some(filter);
+        self
+    }
+
+    /// Set the guardrail masking secrets in model responses and tool inputs.
+    pub fn with_secret_redactor(mut self, redactor: SecretRedactor) -> Self {
+        self.secret_redactor = Some(Arc::new(redactor));
+        self
+    }
+
+    /// Set the policy blocking or flagging user messages and model answers.
+    pub fn with_guardrail_policy(mut self, policy: GuardrailPolicy) -> Self {
+        self.guardrail_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Set the experiments sessions are assigned to.
+    pub fn with_experiments(mut self, experiments: Experiments) -> Self {
+        self.experiments = Some(Arc::new(experiments));
+        self
+    }
+
+    /// Set the memory of durable facts about users.
+    pub fn with_user_memory(mut self, memory: UserMemory) -> Self {
+        self.user_memory = Some(Arc::new(memory));
+        self
+    }
+
+    /// Set the stage compressing retrieved documents and old turns before each model call.
+    pub fn with_compressor(mut self, compressor: ContextCompressor) -> Self {
+        self.compressor = Some(Arc::new(compressor));
+        self
+    }
+
+    /// Set the tokenizers estimating request size and unreported usage.
+    pub fn with_tokenizers(mut self, tokenizers: TokenizerRegistry) -> Self {
+        self.tokenizers = Some(Arc::new(tokenizers));
+        self
+    }
+
+    /// Set the model's context window in tokens.
+    pub fn with_context_window(mut self, tokens: usize) -> Self {
+        self.context_window = Some(tokens);
+        self
+    }
+
+    /// Set the number of times per run the model may re-emit tool calls with malformed arguments.
+    pub fn with_max_tool_argument_retries(mut self, retries: usize) -> Self {
+        self.max_tool_argument_retries = retries;
+        self
+    }
+
+    /// Set the sink receiving typed metrics from the event loop.
+    pub fn with_metrics_sink(mut self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.metrics_sink = Some(sink);
+        self
+    }
+
+    /// Give each run a scoped working directory for its tools.
+    pub fn with_workspace(mut self, workspace: WorkspaceConfig) -> Self {
+        self.workspace = Some(workspace);
+        self
+    }
+
+    /// Schedule model calls with a limiter, usually shared with other agents.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Add a processor rewriting each final answer, after those already added.
+    pub fn with_post_processor(mut self, processor: Arc<dyn PostProcessor>) -> Self {
+        self.post_processors.push(processor);
+        self
+    }
+
+    /// Set the service turning voice input into text.
+    pub fn with_transcriber(mut self, transcriber: Arc<dyn Transcriber>) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
+
+    /// Send heartbeats from long runs and abort runs past a hard ceiling.
+    pub fn with_liveness(mut self, liveness: LivenessConfig) -> Self {
+        self.liveness = Some(liveness);
+        self
+    }
+
+    /// Start the agent in an existing conversation, given as messages or a transcript path.
+    pub fn with_history(mut self, history: impl Into<HistorySource>) -> Self {
+        self.history = Some(history.into());
+        self
+    }
+
+    /// Attach the UI hints of result renderers to `toolCompleted` stream events.
+    pub fn with_result_renderers(mut self, renderers: RendererRegistry) -> Self {
+        self.result_renderers = Some(renderers);
+        self
+    }
+
     /// Add a configuration option.
     pub fn with_option(mut self, key: &str, value: Value) -> Self {
         self.options.insert(key.to_string(), value);
@@ -87,12 +338,110 @@ impl AgentConfig {
     }
 }
 
+/// What the tools of one run see beyond their inputs.
+struct ToolScope<'a> {
+    /// The tool specs offered to the model, when a selector narrowed them.
+    offered: Option<&'a [ToolSpec]>,
+    /// The run's working directory.
+    working_directory: Option<&'a Path>,
+}
+
+/// What tool outputs of one run carry beyond their text.
+#[derive(Default)]
+struct ToolOutputs {
+    /// The sources cited by tool outputs.
+    citations: Vec<Citation>,
+    /// The images generated by tools.
+    images: Vec<ImageContent>,
+}
+
+/// What the model calls of one run are made with.
+struct RunInputs {
+    /// The text of the user message.
+    message: String,
+    /// The conversation history when the run started.
+    history: Messages,
+    /// The tool specs offered to the model.
+    tool_specs: Vec<ToolSpec>,
+    /// The system prompt of the run.
+    system_prompt: String,
+    /// Whether a selector narrowed the tool specs.
+    selected: bool,
+    /// The run's working directory.
+    working_directory: Option<PathBuf>,
+}
+
+impl RunInputs {
+    /// What the tools of the run see beyond their inputs.
+    fn scope(&self) -> ToolScope<'_> {
+        ToolScope {
+            offered: self.selected.then_some(self.tool_specs.as_slice()),
+            working_directory: self.working_directory.as_deref(),
+        }
+    }
+}
+
+/// What one run accumulates while it calls the model and tools.
+struct RunState {
+    timeline: Timeline,
+    event_loop: EventLoop,
+    /// The tool calls and results of this run, which the history does not include yet.
+    turn: Messages,
+    tool_outputs: ToolOutputs,
+    tool_calls: Vec<ToolCallRecord>,
+    usage: Option<ModelUsage>,
+    degraded: Option<DegradedResponseKind>,
+    interrupt: Option<Interrupt>,
+    /// The guardrails that blocked the run, if any.
+    blocked_by: Vec<String>,
+    truncated: bool,
+    argument_retries: usize,
+}
+
+impl RunState {
+    fn new(timeline: Timeline, event_loop: EventLoop, blocked_by: Vec<String>) -> Self {
+        Self {
+            timeline,
+            event_loop,
+            turn: Messages::new(),
+            tool_outputs: ToolOutputs::default(),
+            tool_calls: Vec::new(),
+            usage: None,
+            degraded: None,
+            interrupt: None,
+            blocked_by,
+            truncated: false,
+            argument_retries: 0,
+        }
+    }
+}
+
 /// The main Agent struct that orchestrates conversations and tool execution.
 pub struct Agent {
     config: AgentConfig,
     state: AgentState,
     conversation_manager: Box<dyn ConversationManager>,
     tool_registry: Arc<ToolRegistry>,
+    tool_executor: ToolExecutor,
+    events: EventBus,
+    metrics: MetricsSubscriber,
+    hooks: HookRegistry,
+    budget_usage: BudgetUsage,
+    budget_warned: bool,
+    forks: Vec<ConversationFork>,
+    stream_events: Option<UnboundedSender<StreamEvent>>,
+    experiment_labels: BTreeMap<String, String>,
+    user_id: Option<String>,
+    session_id: Option<String>,
+    guardrail_log: GuardrailLog,
+    recent_events: EventRecorder,
+    run_options: RunOptions,
+    pending_interrupt: Option<Interrupt>,
+    run_probe: RunProbe,
+    /// The configured history, seeded into the conversation manager at the start of the first run.
+    pending_history: Option<Messages>,
+    /// Traces of the messages aged out of the conversation.
+    expired_content: ExpiredContentIndex,
 }
 
 impl Agent {
@@ -102,29 +451,88 @@ impl Agent {
         let state = AgentState::new();
         let conversation_manager = Box::new(super::conversation_manager::NullConversationManager::new());
         let tool_registry = Arc::new(ToolRegistry::new());
+        let (events, metrics, hooks, recent_events) = Self::default_event_bus();
 
         Ok(Self {
             config,
             state,
             conversation_manager,
             tool_registry,
+            tool_executor: ToolExecutor::new(),
+            events,
+            metrics,
+            hooks,
+            budget_usage: BudgetUsage::default(),
+            budget_warned: false,
+            forks: Vec::new(),
+            stream_events: None,
+            experiment_labels: BTreeMap::new(),
+            user_id: None,
+            session_id: None,
+            guardrail_log: GuardrailLog::new(),
+            recent_events,
+            run_options: RunOptions::default(),
+            pending_interrupt: None,
+            run_probe: RunProbe::default(),
+            pending_history: None,
+            expired_content: ExpiredContentIndex::new(),
         })
     }
 
     /// Create a new agent with the given configuration.
     pub fn with_config(config: AgentConfig) -> IndubitablyResult<Self> {
-        let state = AgentState::new();
-        let conversation_manager = Box::new(super::conversation_manager::NullConversationManager::new());
+        let state = AgentState::new().with_max_size_bytes(config.memory_limits.max_state_bytes);
+        let pending_history = config.history.as_ref().map(HistorySource::load).transpose()?;
+        // A seeded conversation needs a manager that keeps it
+        let conversation_manager: Box<dyn ConversationManager> = if pending_history.is_some() {
+            Box::new(super::conversation_manager::SlidingWindowConversationManager::new(
+                config.conversation_config.max_messages,
+            ))
+        } else {
+            Box::new(super::conversation_manager::NullConversationManager::new())
+        };
         let tool_registry = Arc::new(ToolRegistry::new());
+        let (events, metrics, hooks, recent_events) = Self::default_event_bus();
 
         Ok(Self {
             config,
             state,
             conversation_manager,
             tool_registry,
+            tool_executor: ToolExecutor::new(),
+            events,
+            metrics,
+            hooks,
+            budget_usage: BudgetUsage::default(),
+            budget_warned: false,
+            forks: Vec::new(),
+            stream_events: None,
+            experiment_labels: BTreeMap::new(),
+            user_id: None,
+            session_id: None,
+            guardrail_log: GuardrailLog::new(),
+            recent_events,
+            run_options: RunOptions::default(),
+            pending_interrupt: None,
+            run_probe: RunProbe::default(),
+            pending_history,
+            expired_content: ExpiredContentIndex::new(),
         })
     }
 
+    /// Create the event bus with the default tracing, metrics, hook and recent event subscribers.
+    fn default_event_bus() -> (EventBus, MetricsSubscriber, HookRegistry, EventRecorder) {
+        let events = EventBus::new();
+        let metrics = MetricsSubscriber::new();
+        let hooks = HookRegistry::new();
+        let recent_events = EventRecorder::new().with_capacity(DEFAULT_DEBUG_EVENT_CAPACITY);
+        events.subscribe(Arc::new(TracingSubscriber));
+        events.subscribe(Arc::new(metrics.clone()));
+        events.subscribe(Arc::new(HookSubscriber::new(hooks.clone())));
+        events.subscribe(Arc::new(recent_events.clone()));
+        (events, metrics, hooks, recent_events)
+    }
+
     /// Create a new agent with a specific model.
     pub fn with_model(model: Box<dyn Model>) -> IndubitablyResult<Self> {
         let mut config = AgentConfig::new();
@@ -134,141 +542,1657 @@ impl Agent {
 
     /// Run the agent with a message.
     pub async fn run(&mut self, message: &str) -> IndubitablyResult<AgentResult> {
-        let user_message = Message::user(message);
-        
+        let user_message = Message::user(message).with_id(&uuid::Uuid::new_v4().to_string());
+        self.run_message(user_message, None).await
+    }
+
+    /// Run the agent with a message and per-run options.
+    pub async fn run_with_options(&mut self, message: &str, options: RunOptions) -> IndubitablyResult<AgentResult> {
+        self.run_options = options;
+        let result = self.run(message).await;
+        self.run_options = RunOptions::default();
+        result
+    }
+
+    /// Run the agent with a user message of mixed content, such as text with images or documents.
+    ///
+    /// Providers without vision or document support ignore the blocks they cannot send.
+    pub async fn run_with_content(&mut self, content: Vec<ContentBlock>) -> IndubitablyResult<AgentResult> {
+        if content.is_empty() {
+            return Err(IndubitablyError::ValidationError("A message needs at least one content block".to_string()));
+        }
+        let user_message = Message::new(MessageRole::User, content).with_id(&uuid::Uuid::new_v4().to_string());
+        self.run_message(user_message, None).await
+    }
+
+    /// Run the agent with a message and an image file.
+    pub async fn run_with_image(&mut self, message: &str, path: impl AsRef<Path>) -> IndubitablyResult<AgentResult> {
+        let image = ImageContent::from_path(path)?;
+        self.run_with_content(vec![ContentBlock::from_text(message), ContentBlock::from_image(image)]).await
+    }
+
+    /// Run the agent with a message and image data in PNG, JPEG, GIF or WebP format.
+    pub async fn run_with_image_bytes(&mut self, message: &str, bytes: &[u8]) -> IndubitablyResult<AgentResult> {
+        let image = ImageContent::from_bytes(bytes)?;
+        self.run_with_content(vec![ContentBlock::from_text(message), ContentBlock::from_image(image)]).await
+    }
+
+    /// Run the agent with a message and a document file, such as a PDF.
+    pub async fn run_with_document(&mut self, message: &str, path: impl AsRef<Path>) -> IndubitablyResult<AgentResult> {
+        let document = DocumentContent::from_path(path)?;
+        self.run_with_content(vec![ContentBlock::from_text(message), ContentBlock::from_document(document)]).await
+    }
+
+    /// Run the agent with voice input, transcribed into the text of the user message.
+    ///
+    /// The transcript's language and duration are kept in the message metadata.
+    pub async fn run_with_audio(&mut self, audio: &AudioContent) -> IndubitablyResult<AgentResult> {
+        let transcriber = self.config.transcriber.clone().ok_or_else(|| {
+            IndubitablyError::ConfigurationError("Running with audio needs a transcriber".to_string())
+        })?;
+        let transcription = transcriber.transcribe(audio).await?;
+        if transcription.text.trim().is_empty() {
+            return Err(IndubitablyError::ValidationError("The audio contains no speech".to_string()));
+        }
+        let mut user_message = Message::user(&transcription.text).with_id(&uuid::Uuid::new_v4().to_string());
+        user_message.metadata.get_or_insert_with(HashMap::new).insert(
+            TRANSCRIPTION_METADATA_KEY.to_string(),
+            serde_json::json!({ "language": transcription.language, "duration": transcription.duration }),
+        );
+        self.run_message(user_message, None).await
+    }
+
+    /// Wait for a model call slot in the rate limiter, if one is set.
+    async fn acquire_model_slot(&self) -> IndubitablyResult<Option<RateLimitPermit>> {
+        match self.config.rate_limiter {
+            Some(ref limiter) => {
+                Ok(Some(limiter.acquire_for_session(self.run_options.priority, self.session_id.as_deref()).await?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Preview the tool calls the agent would make for a message without running them.
+    ///
+    /// The model sees the conversation as usual, but each tool call is recorded
+    /// and answered with a placeholder result instead of being executed. The
+    /// conversation, budget and metrics are left untouched.
+    pub async fn plan(&self, message: &str) -> IndubitablyResult<ExecutionPlan> {
+        let mut plan = ExecutionPlan::new(message);
+        let Some(ref model) = self.config.model else {
+            plan.complete = true;
+            return Ok(plan);
+        };
+
+        let mut request = self.conversation_manager.get_context().await?;
+        request.push(Message::user(message));
+        let tool_specs = self.turn_tool_specs(message).await;
+        let system_prompt = self.system_prompt_with_profile().await;
+
+        for round in 1..=DEFAULT_MAX_PLAN_ROUNDS {
+            let _slot = self.acquire_model_slot().await?;
+            let mut response = model
+                .generate(&request, Some(&tool_specs), Some(&system_prompt))
+                .await?;
+            self.redact_secrets(&mut response).await;
+            if !response.has_tool_uses() {
+                plan.final_response = Some(response.content);
+                plan.complete = true;
+                break;
+            }
+
+            let mut results = Vec::with_capacity(response.tool_uses.len());
+            for tool_use in &response.tool_uses {
+                let tool = self.tool_registry.get(&tool_use.name).await;
+                plan.steps.push(PlannedToolCall {
+                    step: plan.steps.len() + 1,
+                    round,
+                    tool_use_id: tool_use.tool_use_id.clone(),
+                    name: tool_use.name.clone(),
+                    input: tool_use.input.clone().unwrap_or_else(|| Value::Object(Default::default())),
+                    rationale: response.content.clone(),
+                    registered: tool.is_some(),
+                    mutating: tool.is_some_and(|tool| tool.metadata.is_mutating()),
+                });
+                results.push(ToolResult::new(&tool_use.tool_use_id, vec![ToolResultContent::text(PLANNED_TOOL_RESULT)]));
+            }
+            request.push(Message::assistant_with_tool_uses(&response.content, response.tool_uses));
+            request.push(Message::tool_results(results));
+        }
+
+        tracing::debug!(
+            "agent=<{}>, steps=<{}>, complete=<{}> | planned tool calls without executing them",
+            self.config.name,
+            plan.steps.len(),
+            plan.complete
+        );
+        Ok(plan)
+    }
+
+    /// Run the agent with a prepared user message, under the watchdog if liveness is configured.
+    async fn run_message(
+        &mut self,
+        user_message: Message,
+        approved: Option<Interrupt>,
+    ) -> IndubitablyResult<AgentResult> {
+        let probe = self.run_probe.clone();
+        probe.start(user_message.id());
+        let result = match self.config.liveness {
+            Some(config) => {
+                let watchdog = Watchdog {
+                    config,
+                    probe: probe.clone(),
+                    events: self.events.clone(),
+                    source: self.config.name.clone(),
+                    context: self.event_context(),
+                };
+                watchdog.watch(self.execute_run(user_message, approved)).await
+            }
+            None => self.execute_run(user_message, approved).await,
+        };
+        probe.finish();
+        result
+    }
+
+    /// Run the agent with a prepared user message, first running the calls of an approved interrupt.
+    async fn execute_run(
+        &mut self,
+        user_message: Message,
+        approved: Option<Interrupt>,
+    ) -> IndubitablyResult<AgentResult> {
+        let timeline = Timeline::new(&self.config.name);
+        let message = user_message.all_text();
+        // A hook failure outside a run, such as on feedback, does not abort the next one
+        self.hooks.take_abort();
+        // A new message answers the last interrupt, and its held calls only run if it was approved
+        if let Some(pending) = self.pending_interrupt.take() {
+            if approved.is_none() {
+                tracing::debug!(
+                    "agent=<{}>, interrupt=<{}>, held_calls=<{}> | new message declined the pending interrupt",
+                    self.config.name,
+                    pending.id,
+                    pending.tool_uses.len()
+                );
+            }
+        }
+        if let Some(history) = self.pending_history.take() {
+            self.conversation_manager.seed_from(history).await?;
+        }
+        self.age_out_expired(&message).await?;
+        // Tool invocation limits apply per run
+        self.tool_executor.begin_run();
+        self.publish(LifecycleEventKind::RunStarted, serde_json::json!({
+            "message_id": user_message.id(),
+        }))
+        .await;
+        self.check_hook_abort().await?;
+
+        // Dropped without being finished if the run fails, which applies the failure cleanup policy
+        let workspace = match self.config.workspace {
+            Some(ref config) => Some(Workspace::create(config, &uuid::Uuid::new_v4().simple().to_string())?),
+            None => None,
+        };
+
+        // A blocked message never reaches the model or the conversation
+        let blocked_by = self.apply_guardrail(&message, GuardrailStage::Input).await;
+        let input_blocked = !blocked_by.is_empty();
+
         // Add the message to the conversation
-        self.conversation_manager.add_message(user_message.clone()).await?;
-        
+        if !input_blocked {
+            self.add_within_memory_limits(user_message.clone()).await?;
+        }
+
         // Get the conversation history
         let history = self.conversation_manager.get_context().await?;
-        
-        // Generate a response using the model
-        let response = if let Some(ref model) = self.config.model {
-            let model_response = model.generate(
-                &history,
-                Some(&self.config.tools),
-                Some(&self.config.system_prompt),
-            ).await?;
-            
-            Message::assistant(&model_response.content)
+        let tool_specs = self.turn_tool_specs(&message).await;
+        let inputs = RunInputs {
+            system_prompt: self.system_prompt_with_profile().await,
+            message,
+            history,
+            tool_specs,
+            selected: self.config.tool_selector.is_some(),
+            working_directory: workspace.as_ref().map(|workspace| workspace.path().to_path_buf()),
+        };
+
+        let mut event_loop = EventLoop::new().with_label("agent", &self.config.name);
+        if let Some(ref sink) = self.config.metrics_sink {
+            event_loop = event_loop.with_metrics_sink(Arc::clone(sink));
+        }
+        let mut run = RunState::new(timeline, event_loop, blocked_by);
+        let response = if input_blocked {
+            Message::assistant(GUARDRAIL_BLOCKED_RESPONSE)
         } else {
-            // If no model is configured, return a placeholder response
-            Message::assistant("I'm a placeholder agent. Please configure a model to get real responses.")
+            // The approved calls run as if the model had just asked for them
+            if let Some(interrupt) = approved {
+                let tool_use_message = Message::assistant_with_tool_uses("", interrupt.tool_uses.clone());
+                self.run_tool_calls(tool_use_message, &interrupt.tool_uses, &HashMap::new(), &inputs, &mut run)
+                    .await?;
+            }
+            self.call_model_until_answered(&inputs, &mut run).await?
         };
-        
+        self.finish_run(user_message, response, inputs, run, workspace, input_blocked).await
+    }
+
+    /// Call the model until it answers without asking for tools, running the tools it asks for.
+    async fn call_model_until_answered(
+        &mut self,
+        inputs: &RunInputs,
+        run: &mut RunState,
+    ) -> IndubitablyResult<Message> {
+        loop {
+            let Some(ref model) = self.config.model else {
+                // If no model is configured, return a placeholder response
+                return Ok(Message::assistant(
+                    "I'm a placeholder agent. Please configure a model to get real responses.",
+                ));
+            };
+
+            let mut request = inputs.history.clone();
+            request.extend(run.turn.iter().cloned());
+            run.event_loop.cycle(&request).await?;
+            self.run_probe.cycle(run.event_loop.iteration_count());
+
+            // Drop low-information text from retrieved documents and old turns
+            let mut tokens_saved = 0;
+            if let Some(ref compressor) = self.config.compressor {
+                let (compressed, stats) = compressor.compress(&request);
+                request = compressed;
+                tokens_saved = stats.tokens_saved();
+            }
+
+            // Ask the model to wrap up once the conversation nears its budget
+            if let Some(ref budget) = self.config.budget {
+                if budget.status(&self.budget_usage) != BudgetStatus::WithinBudget {
+                    request.push(Message::system(&budget.wrap_up_note(&self.budget_usage)));
+                }
+            }
+
+            // Generate a response using the model, falling back to degraded mode
+            self.run_probe.enter(RunPhase::ModelCall);
+            let model_started = Instant::now();
+            let estimated_tokens =
+                self.estimate_request_tokens(model.as_ref(), &request, &inputs.tool_specs, &inputs.system_prompt);
+            let generated = match (estimated_tokens, self.config.context_window) {
+                (Some(estimated), Some(window)) if estimated > window => Err(ModelError::ContextWindowOverflow(format!(
+                    "Request is estimated at {} tokens but the context window is {}",
+                    estimated, window
+                ))
+                .into()),
+                // The slot is held until the model call returns
+                _ => match self.acquire_model_slot().await {
+                    Ok(slot) => {
+                        if let Some(ref slot) = slot {
+                            run.event_loop.record(MetricEvent::RateLimitWait {
+                                session: self.session_id.clone(),
+                                duration: slot.waited(),
+                            });
+                        }
+                        model.generate(&request, Some(&inputs.tool_specs), Some(&inputs.system_prompt)).await
+                    }
+                    Err(e) => Err(e),
+                },
+            };
+            run.event_loop.record(MetricEvent::ModelLatency {
+                model_id: model.model_id().to_string(),
+                duration: model_started.elapsed(),
+            });
+            let usage = generated.as_ref().ok().and_then(|response| response.usage.as_ref());
+            run.timeline.record(SpanCategory::Model, "model.generate", model_started, serde_json::json!({
+                "round": run.event_loop.iteration_count(),
+                "ok": generated.is_ok(),
+                "input_tokens": usage.map(|usage| usage.input_tokens),
+                "output_tokens": usage.map(|usage| usage.output_tokens),
+                "reasoning_tokens": usage.map(|usage| usage.reasoning_tokens),
+                "compression_tokens_saved": tokens_saved,
+            }));
+
+            let model_response = match generated {
+                Ok(mut model_response) => {
+                    self.redact_secrets(&mut model_response).await;
+                    if model_response.usage.is_none() {
+                        self.estimate_usage(model.as_ref(), &mut model_response, estimated_tokens);
+                    }
+                    let usage = model_response.usage.as_ref();
+                    if let Some(usage) = usage {
+                        run.event_loop.record(MetricEvent::TokensUsed {
+                            model_id: model.model_id().to_string(),
+                            input: usage.input_tokens.into(),
+                            output: usage.output_tokens.into(),
+                            reasoning: usage.reasoning_tokens.into(),
+                        });
+                    }
+                    self.publish(LifecycleEventKind::ModelCallCompleted, serde_json::json!({
+                        "input_tokens": usage.map(|usage| usage.input_tokens).unwrap_or(0),
+                        "output_tokens": usage.map(|usage| usage.output_tokens).unwrap_or(0),
+                        "reasoning_tokens": usage.map(|usage| usage.reasoning_tokens).unwrap_or(0),
+                        "compression_tokens_saved": tokens_saved,
+                        "tool_uses": model_response.tool_uses.len(),
+                    }))
+                    .await;
+                    model_response
+                }
+                Err(error) => {
+                    self.publish(LifecycleEventKind::ModelCallFailed, serde_json::json!({
+                        "error": error.to_string(),
+                        "degraded": self.config.degraded_mode_handler.is_some(),
+                    }))
+                    .await;
+                    let Some(ref handler) = self.config.degraded_mode_handler else {
+                        self.publish(LifecycleEventKind::RunCompleted, serde_json::json!({
+                            "outcome": "failed",
+                        }))
+                        .await;
+                        return Err(error);
+                    };
+                    let fallback = handler.handle(&inputs.message, &inputs.history, &error).await?;
+                    run.degraded = Some(fallback.kind);
+                    return Ok(Message::assistant(&fallback.text));
+                }
+            };
+            self.check_hook_abort().await?;
+
+            if let Some(ref usage) = model_response.usage {
+                self.track_budget(usage).await;
+                run.usage.get_or_insert_with(ModelUsage::default).accumulate(usage);
+            }
+
+            // Withhold a blocked answer along with any tool calls it makes
+            run.blocked_by = self.apply_guardrail(&model_response.content, GuardrailStage::Output).await;
+            if !run.blocked_by.is_empty() {
+                return Ok(Message::assistant(GUARDRAIL_BLOCKED_RESPONSE));
+            }
+
+            if !model_response.has_tool_uses() {
+                run.truncated = ["finish_reason", "stop_reason"].iter().any(|key| {
+                    matches!(
+                        model_response.metadata.get(*key).and_then(Value::as_str),
+                        Some("length" | "max_tokens" | "MAX_TOKENS")
+                    )
+                });
+                let mut citations = model_response.citations;
+                citations.append(&mut run.tool_outputs.citations);
+                let content = self.config.post_processors.apply(&model_response.content).await;
+                return Ok(Message::assistant(&content)
+                    .with_citations(citations)
+                    .with_reasoning(model_response.reasoning));
+            }
+
+            // Ask the model to re-emit tool calls whose arguments could not be parsed or repaired
+            let malformed = malformed_tool_calls(&model_response.metadata);
+            if !malformed.is_empty() {
+                run.argument_retries += 1;
+                tracing::warn!(
+                    "agent=<{}>, malformed=<{}>, retries=<{}> | model emitted tool calls with malformed arguments",
+                    self.config.name,
+                    malformed.len(),
+                    run.argument_retries
+                );
+                if run.argument_retries > self.config.max_tool_argument_retries {
+                    self.publish(LifecycleEventKind::RunCompleted, serde_json::json!({
+                        "outcome": "failed",
+                    }))
+                    .await;
+                    return Err(ToolError::InvalidInput(format!(
+                        "Model emitted malformed tool arguments after {} retries",
+                        self.config.max_tool_argument_retries
+                    ))
+                    .into());
+                }
+            }
+
+            // Stop and ask the user before running risky or uncertain tool calls
+            let clarification = self
+                .config
+                .clarification_policy
+                .as_ref()
+                .and_then(|policy| policy.evaluate(&model_response));
+            if let Some(clarification) = clarification {
+                let question = Message::assistant(&clarification.question);
+                run.interrupt = Some(clarification);
+                return Ok(question);
+            }
+
+            let tool_use_message = Message::assistant_with_tool_uses(
+                &model_response.content,
+                model_response.tool_uses.clone(),
+            )
+            .with_reasoning(model_response.reasoning.clone());
+            self.run_tool_calls(tool_use_message, &model_response.tool_uses, &malformed, inputs, run).await?;
+        }
+    }
+
+    /// Run the tool calls of one model turn, adding the calls and their results to the conversation.
+    async fn run_tool_calls(
+        &mut self,
+        tool_use_message: Message,
+        tool_uses: &[ToolUse],
+        malformed: &HashMap<String, String>,
+        inputs: &RunInputs,
+        run: &mut RunState,
+    ) -> IndubitablyResult<()> {
+        self.emit(StreamEvent::tool_plan_started(&uuid::Uuid::new_v4().to_string(), tool_uses.len()));
+        let scope = inputs.scope();
+        let results = self
+            .execute_tools(tool_uses, malformed, &scope, &mut run.tool_outputs, &mut run.timeline, &run.event_loop)
+            .await;
+        for (tool_use, result) in tool_uses.iter().zip(&results) {
+            run.tool_calls.push(ToolCallRecord {
+                tool_use_id: tool_use.tool_use_id.clone(),
+                name: tool_use.name.clone(),
+                input: tool_use.input.clone().unwrap_or(Value::Null),
+                output: result
+                    .content
+                    .iter()
+                    .filter_map(|content| content.text.as_deref())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                is_error: result.is_error == Some(true),
+                duration_us: run
+                    .timeline
+                    .spans
+                    .iter()
+                    .rev()
+                    .find(|span| {
+                        span.category == SpanCategory::Tool && span.args["tool_use_id"] == tool_use.tool_use_id.as_str()
+                    })
+                    .map(|span| span.duration_us),
+            });
+        }
+        for message in [tool_use_message, Message::tool_results(results)] {
+            self.add_within_memory_limits(message.clone()).await?;
+            run.turn.push(message);
+        }
+        self.check_hook_abort().await
+    }
+
+    /// Record the response of a run and build its result.
+    async fn finish_run(
+        &mut self,
+        user_message: Message,
+        response: Message,
+        inputs: RunInputs,
+        run: RunState,
+        workspace: Option<Workspace>,
+        input_blocked: bool,
+    ) -> IndubitablyResult<AgentResult> {
+        let RunState {
+            mut timeline,
+            mut event_loop,
+            tool_outputs,
+            tool_calls,
+            usage,
+            degraded,
+            interrupt,
+            blocked_by,
+            truncated,
+            ..
+        } = run;
+        event_loop.finish_cycle();
+        self.run_probe.enter(RunPhase::Finishing);
+
         // Add the response to the conversation
-        self.conversation_manager.add_message(response.clone()).await?;
-        
+        let response = response.with_id(&uuid::Uuid::new_v4().to_string());
+        if !input_blocked {
+            self.add_within_memory_limits(response.clone()).await?;
+        }
+        if degraded.is_none() && blocked_by.is_empty() {
+            self.remember_user_facts(&[user_message, response.clone()]).await;
+        }
+
+        let outcome = match (&degraded, &interrupt) {
+            _ if !blocked_by.is_empty() => "blocked",
+            (Some(_), _) => "degraded",
+            (None, Some(_)) => "interrupted",
+            (None, None) => "answered",
+        };
+        self.publish(LifecycleEventKind::RunCompleted, serde_json::json!({
+            "outcome": outcome,
+            "message_id": response.id(),
+            "degraded_kind": degraded.as_ref().map(|kind| kind.as_str()),
+            "interrupt_reason": interrupt.as_ref().map(|interrupt| format!("{:?}", interrupt.reason)),
+        }))
+        .await;
+
         // Create the result
         let result = AgentResult::new(
             self.config.name.clone(),
-            history.clone(),
+            inputs.history.clone(),
             response.clone(),
             response.all_text(),
-            history,
-            self.config.tools.clone(),
-        );
-        
+            inputs.history,
+            inputs.tool_specs,
+        )
+        .with_citations(response.citations().into_iter().cloned().collect())
+        .with_tool_calls(tool_calls)
+        .with_images(tool_outputs.images)
+        .with_stop_reason(match (&degraded, &interrupt) {
+            _ if !blocked_by.is_empty() => StopReason::GuardrailBlocked,
+            (Some(_), _) => StopReason::Degraded,
+            (None, Some(_)) => StopReason::Interrupted,
+            (None, None) if truncated => StopReason::MaxTokens,
+            (None, None) => StopReason::EndTurn,
+        });
+        let result = match usage {
+            Some(usage) => result.with_usage(usage),
+            None => result,
+        };
+        let result = match workspace {
+            Some(workspace) => result.with_artifacts(workspace.finish(true)),
+            None => result,
+        };
+        timeline.record_from_origin(SpanCategory::Run, "agent.run", serde_json::json!({"outcome": outcome}));
+        let result = result.with_timeline(timeline);
+
+        let result = match degraded {
+            Some(kind) => result
+                .with_metadata("degraded", Value::Bool(true))
+                .with_metadata("degraded_kind", Value::String(kind.as_str().to_string())),
+            None => result,
+        };
+
+        self.pending_interrupt = interrupt.clone();
+        let result = match interrupt {
+            Some(interrupt) => result.with_interrupt(interrupt),
+            None => result,
+        };
+
+        let result = if blocked_by.is_empty() {
+            result
+        } else {
+            result.with_metadata("guardrail_decisions", serde_json::json!(blocked_by))
+        };
+
         Ok(result)
     }
 
-    /// Run the agent with a message and get a streaming response.
-    pub async fn run_streaming(&mut self, message: &str) -> IndubitablyResult<AgentResult> {
-        // For now, just call the regular run method
-        // TODO: Implement actual streaming
-        self.run(message).await
+    /// Discard the last assistant turn and generate a new one.
+    pub async fn regenerate_last(&mut self) -> IndubitablyResult<AgentResult> {
+        self.regenerate_last_with(EditOptions::default()).await
     }
 
-    /// Add a tool to the agent.
-    pub async fn add_tool(&mut self, tool: crate::tools::registry::Tool) -> IndubitablyResult<()> {
-        self.tool_registry.register(tool).await?;
-        Ok(())
+    /// Discard the last assistant turn and generate a new one, with options.
+    pub async fn regenerate_last_with(&mut self, options: EditOptions) -> IndubitablyResult<AgentResult> {
+        let messages = self.conversation_manager.get_context().await?;
+        let index = last_user_turn(&messages).ok_or_else(|| {
+            ConversationError::MessageNotFound("No user message to regenerate from".to_string())
+        })?;
+        let user_message = messages[index].clone();
+
+        self.rewind(messages, index, options).await?;
+        self.run_message(user_message, None).await
     }
 
-    /// Set the conversation manager.
-    pub fn with_conversation_manager(mut self, manager: Box<dyn ConversationManager>) -> Self {
-        self.conversation_manager = manager;
-        self
+    /// Replace the text of a user message and generate a new turn from it.
+    ///
+    /// Everything after the edited message is discarded.
+    pub async fn edit_user_message(&mut self, message_id: &str, new_text: &str) -> IndubitablyResult<AgentResult> {
+        self.edit_user_message_with(message_id, new_text, EditOptions::default()).await
     }
 
-    /// Get the agent's configuration.
-    pub fn config(&self) -> &AgentConfig {
-        &self.config
+    /// Replace the text of a user message and generate a new turn from it, with options.
+    pub async fn edit_user_message_with(
+        &mut self,
+        message_id: &str,
+        new_text: &str,
+        options: EditOptions,
+    ) -> IndubitablyResult<AgentResult> {
+        let messages = self.conversation_manager.get_context().await?;
+        let index = find_user_turn(&messages, message_id).ok_or_else(|| {
+            ConversationError::MessageNotFound(format!("No user message with id '{}'", message_id))
+        })?;
+
+        self.rewind(messages, index, options).await?;
+        self.run_message(Message::user(new_text).with_id(message_id), None).await
     }
 
-    /// Get the agent's state.
-    pub fn state(&self) -> &AgentState {
-        &self.state
+    /// Truncate the conversation to the messages before `index`, forking it first if requested.
+    async fn rewind(&mut self, messages: Messages, index: usize, options: EditOptions) -> IndubitablyResult<()> {
+        if options.fork {
+            self.forks.push(ConversationFork::new(messages));
+        }
+
+        self.conversation_manager.truncate(index).await
     }
 
-    /// Get the agent's state as mutable.
-    pub fn state_mut(&mut self) -> &mut AgentState {
-        &mut self.state
+    /// Assign a session to its experiment variants and apply them to this agent.
+    ///
+    /// The assignments are recorded in the session metadata, which the caller
+    /// persists, and every later lifecycle event carries them as labels.
+    pub fn join_experiments(&mut self, session: &mut Session) -> IndubitablyResult<Vec<Assignment>> {
+        let Some(experiments) = self.config.experiments.clone() else {
+            return Ok(Vec::new());
+        };
+        let assignments = experiments.assign_session(session)?;
+        for assignment in &assignments {
+            let variant = &assignment.variant;
+            tracing::debug!(
+                "session_id=<{}>, experiment=<{}>, variant=<{}> | applying experiment variant",
+                session.id,
+                assignment.experiment,
+                variant.name
+            );
+            if let Some(ref system_prompt) = variant.system_prompt {
+                self.config.system_prompt = system_prompt.clone();
+            }
+            if let Some(ref mut model) = self.config.model {
+                variant.apply_to_model_config(model.config_mut());
+            }
+            self.config.options.extend(variant.options.clone());
+        }
+        self.experiment_labels = labels_of(&assignments);
+        Ok(assignments)
     }
 
-    /// Get the conversation history.
-    pub async fn get_history(&self) -> IndubitablyResult<Messages> {
-        self.conversation_manager.get_context().await
+    /// Get the guardrail decisions made in this agent's runs.
+    pub fn guardrail_log(&self) -> &GuardrailLog {
+        &self.guardrail_log
     }
 
-    /// Clear the conversation history.
-    pub async fn clear_history(&mut self) -> IndubitablyResult<()> {
-        self.conversation_manager.clear().await?;
-        Ok(())
+    /// Record the guardrail decisions in the session metadata, which the caller persists.
+    pub fn save_guardrail_decisions(&self, session: &mut Session) -> IndubitablyResult<()> {
+        self.guardrail_log.save_to(session)
     }
-}
 
-impl Default for Agent {
-    fn default() -> Self {
-        Self::new().expect("Failed to create default agent")
+    /// Restore the guardrail decisions recorded in a resumed session.
+    pub fn restore_guardrail_decisions(&mut self, session: &Session) -> IndubitablyResult<()> {
+        self.guardrail_log = GuardrailLog::from_session(session)?;
+        Ok(())
     }
-}
 
-/// A builder for creating agents with a fluent interface.
-pub struct AgentBuilder {
-    config: AgentConfig,
-}
+    /// Appeal a guardrail block, re-evaluating it under another policy or routing it to human review.
+    pub async fn appeal(&mut self, decision_id: &str, route: AppealRoute) -> IndubitablyResult<GuardrailDecision> {
+        let decision = self.guardrail_log.appeal(decision_id, route)?;
+        self.publish_appeal(&decision).await;
+        Ok(decision)
+    }
 
-impl AgentBuilder {
-    /// Create a new agent builder.
-    pub fn new() -> Self {
-        Self {
-            config: AgentConfig::new(),
-        }
+    /// Decide an appeal waiting for human review.
+    pub async fn resolve_appeal(
+        &mut self,
+        decision_id: &str,
+        upheld: bool,
+        reviewer: &str,
+        note: Option<&str>,
+    ) -> IndubitablyResult<GuardrailDecision> {
+        let decision = self.guardrail_log.resolve_review(decision_id, upheld, reviewer, note)?;
+        self.publish_appeal(&decision).await;
+        Ok(decision)
     }
 
-    /// Set the agent name.
-    pub fn name(mut self, name: &str) -> Self {
-        self.config.name = name.to_string();
-        self
+    async fn publish_appeal(&self, decision: &GuardrailDecision) {
+        let appeal = decision.appeal.as_ref();
+        self.publish(LifecycleEventKind::GuardrailAppealed, serde_json::json!({
+            "decision_id": decision.id,
+            "rule_id": decision.rule_id,
+            "status": decision.status,
+            "policy": appeal.and_then(|appeal| appeal.policy.as_deref()),
+            "reviewer": appeal.and_then(|appeal| appeal.reviewer.as_deref()),
+        }))
+        .await;
     }
 
-    /// Set the system prompt.
-    pub fn system_prompt(mut self, prompt: &str) -> Self {
-        self.config.system_prompt = prompt.to_string();
-        self
+    /// Set the user this agent talks to, whose memory profile is used and extended.
+    pub fn set_user(&mut self, user_id: &str, session_id: Option<&str>) {
+        self.user_id = Some(user_id.to_string());
+        self.session_id = session_id.map(str::to_string);
     }
 
-    /// Set the model.
-    pub fn model(mut self, model: Box<dyn Model>) -> Self {
-        self.config.model = Some(model);
-        self
+    /// Get the user this agent talks to, if set.
+    pub fn user_id(&self) -> Option<&str> {
+        self.user_id.as_deref()
     }
 
-    /// Add a tool specification.
-    pub fn tool(mut self, tool: ToolSpec) -> Self {
-        self.config.tools.push(tool);
-        self
+    /// Get the experiment variants this agent runs under, keyed by experiment name.
+    pub fn experiment_labels(&self) -> &BTreeMap<String, String> {
+        &self.experiment_labels
     }
 
-    /// Set the conversation manager configuration.
-    pub fn conversation_config(mut self, config: ConversationManagerConfig) -> Self {
-        self.config.conversation_config = config;
-        self
+    /// Get the fields added to every lifecycle event from this agent.
+    fn event_context(&self) -> serde_json::Map<String, Value> {
+        let mut fields = serde_json::Map::new();
+        fields.insert("agent".to_string(), Value::String(self.config.name.clone()));
+        if !self.experiment_labels.is_empty() {
+            fields.insert("experiments".to_string(), serde_json::json!(self.experiment_labels));
+        }
+        if let Some(ref user_id) = self.user_id {
+            fields.insert("user_id".to_string(), Value::String(user_id.clone()));
+        }
+        fields
+    }
+
+    /// Publish a lifecycle event from this agent.
+    async fn publish(&self, kind: LifecycleEventKind, data: Value) {
+        let mut data = data;
+        if let Value::Object(ref mut fields) = data {
+            fields.extend(self.event_context());
+        }
+        self.events.publish(LifecycleEvent::new(kind, &self.config.name, data)).await;
+    }
+
+    /// Fail the run if a hook with the abort failure policy failed since the last check.
+    async fn check_hook_abort(&self) -> IndubitablyResult<()> {
+        let Some(abort) = self.hooks.take_abort() else {
+            return Ok(());
+        };
+        self.publish(LifecycleEventKind::RunCompleted, serde_json::json!({
+            "outcome": "aborted",
+            "hook": abort.hook,
+        }))
+        .await;
+        Err(HookError::ExecutionFailed(abort.to_string()).into())
+    }
+
+    /// Send a stream event to the subscriber, if any.
+    fn emit(&self, event: StreamEvent) {
+        if let Some(ref sender) = self.stream_events {
+            // A dropped receiver just means nobody is listening anymore.
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Get the conversation forks taken by edits and regenerations.
+    pub fn forks(&self) -> &[ConversationFork] {
+        &self.forks
+    }
+
+    /// Get the specifications of the configured and registered tools visible to the agent.
+    pub(crate) async fn tool_specs(&self) -> Vec<ToolSpec> {
+        // Configured specs have no namespace or tags
+        let mut specs = if self.tool_filters().all(|filter| filter.matches_parts(None, &[])) {
+            self.config.tools.clone()
+        } else {
+            Vec::new()
+        };
+        for tool in self.tool_registry.list_tools().await {
+            let visible = self.tool_filters().all(|filter| filter.matches(&tool));
+            if visible && !specs.iter().any(|spec| spec.name == tool.name) {
+                specs.push(tool.spec());
+            }
+        }
+        specs
+    }
+
+    /// Get the agent's tool filter and the current run's, which both apply.
+    fn tool_filters(&self) -> impl Iterator<Item = &ToolFilter> {
+        self.config.tool_filter.iter().chain(self.run_options.tool_filter.iter())
+    }
+
+    /// Check whether the agent's tool filters hide a registered tool.
+    async fn is_tool_hidden(&self, tool_name: &str) -> bool {
+        match self.tool_registry.get(tool_name).await {
+            Some(tool) => !self.tool_filters().all(|filter| filter.matches(&tool)),
+            None => false,
+        }
+    }
+
+    /// Mask secrets the model echoed in its response text and tool inputs.
+    async fn redact_secrets(&self, response: &mut ModelResponse) {
+        let Some(ref redactor) = self.config.secret_redactor else {
+            return;
+        };
+        let redaction = redactor.redact(&response.content);
+        let in_response = redaction.is_redacted();
+        let mut labels = redaction.labels;
+        response.content = redaction.text;
+        let mut tools = Vec::new();
+        for tool_use in &mut response.tool_uses {
+            let Some(ref mut input) = tool_use.input else {
+                continue;
+            };
+            let masked = redactor.redact_value(input);
+            if !masked.is_empty() {
+                tools.push(tool_use.name.clone());
+                labels.extend(masked);
+            }
+        }
+        if labels.is_empty() {
+            return;
+        }
+
+        labels.sort();
+        labels.dedup();
+        self.publish(LifecycleEventKind::SecretLeakBlocked, serde_json::json!({
+            "labels": labels,
+            "in_response": in_response,
+            "tools": tools,
+        }))
+        .await;
+    }
+
+    /// Evaluate content against the guardrail policy and log the decisions.
+    ///
+    /// Returns the IDs of the blocking decisions, empty when the content may pass.
+    async fn apply_guardrail(&mut self, content: &str, stage: GuardrailStage) -> Vec<String> {
+        let Some(policy) = self.config.guardrail_policy.clone() else {
+            return Vec::new();
+        };
+        let decisions = policy.evaluate(content, stage);
+        let blocks: Vec<&GuardrailDecision> = decisions.iter().filter(|decision| decision.is_block()).collect();
+        let blocked_by: Vec<String> = blocks.iter().map(|decision| decision.id.clone()).collect();
+        if !blocks.is_empty() {
+            self.publish(LifecycleEventKind::GuardrailBlocked, serde_json::json!({
+                "stage": stage,
+                "policy": policy.name(),
+                "decisions": blocks
+                    .iter()
+                    .map(|decision| serde_json::json!({
+                        "decision_id": decision.id,
+                        "rule_id": decision.rule_id,
+                        "span": { "start": decision.span.start, "end": decision.span.end },
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+            .await;
+        }
+        self.guardrail_log.record(decisions);
+        blocked_by
+    }
+
+    /// Get the tool specs to send for a turn, narrowed by the tool selector when one is set.
+    async fn turn_tool_specs(&self, query: &str) -> Vec<ToolSpec> {
+        let specs = self.tool_specs().await;
+        let Some(ref selector) = self.config.tool_selector else {
+            return specs;
+        };
+        match selector.select(query, &specs).await {
+            Ok(selected) => {
+                tracing::debug!(
+                    "available=<{}>, selected=<{}> | selected tool specs for turn",
+                    specs.len(),
+                    selected.len()
+                );
+                selected
+            }
+            Err(e) => {
+                tracing::warn!("error=<{}> | tool selection failed, sending all tool specs", e);
+                specs
+            }
+        }
+    }
+
+    /// Strip expired messages from the conversation and report expired content the query touches.
+    async fn age_out_expired(&mut self, query: &str) -> IndubitablyResult<()> {
+        let now = chrono::Utc::now();
+        let messages = self.conversation_manager.get_context().await?;
+        if messages.iter().any(|message| message.is_expired_at(now)) {
+            let (messages, expired) = self.expired_content.age_out(messages, now);
+            self.conversation_manager.clear().await?;
+            for message in messages {
+                self.conversation_manager.add_message(message).await?;
+            }
+            tracing::debug!("count=<{}> | aged expired messages out of the conversation", expired);
+        }
+
+        let relevant: Vec<Value> = self
+            .expired_content
+            .relevant_to(query)
+            .into_iter()
+            .map(|(trace, matching_terms)| {
+                serde_json::json!({
+                    "message_id": trace.message_id,
+                    "expired_at": trace.expired_at.to_rfc3339(),
+                    "matching_terms": matching_terms,
+                })
+            })
+            .collect();
+        for data in relevant {
+            self.publish(LifecycleEventKind::ExpiredContentRelevant, data).await;
+        }
+        Ok(())
+    }
+
+    /// Execute the tools requested by the model and collect their results and citations.
+    ///
+    /// The tools run concurrently through the executor, up to its concurrency
+    /// limit, and their results keep the order of the tool calls.
+    async fn execute_tools(
+        &self,
+        tool_uses: &[ToolUse],
+        malformed: &HashMap<String, String>,
+        scope: &ToolScope<'_>,
+        outputs: &mut ToolOutputs,
+        timeline: &mut Timeline,
+        event_loop: &EventLoop,
+    ) -> Vec<ToolResult> {
+        let tools_started = Instant::now();
+        // Calls that are answered without running hold their result here
+        let mut answered = Vec::with_capacity(tool_uses.len());
+        let mut calls = Vec::new();
+        for tool_use in tool_uses {
+            self.emit(StreamEvent::tool_executing(&tool_use.tool_use_id, &tool_use.name));
+            self.publish(LifecycleEventKind::ToolStarted, serde_json::json!({
+                "tool_use_id": tool_use.tool_use_id,
+                "name": tool_use.name,
+                "selected": scope.offered.map(|specs| specs.iter().any(|spec| spec.name == tool_use.name)),
+            }))
+            .await;
+            self.run_probe.tool_started(&tool_use.name);
+            answered.push(if let Some(error) = malformed.get(&tool_use.tool_use_id) {
+                Some(ToolResult::error(&tool_use.tool_use_id, &retry_message(&tool_use.name, error)))
+            } else if self.is_tool_hidden(&tool_use.name).await {
+                tracing::info!(
+                    "tool_name=<{}>, tool_use_id=<{}> | refused tool hidden by the tool filter",
+                    tool_use.name,
+                    tool_use.tool_use_id
+                );
+                let error = ToolError::ToolNotFound(tool_use.name.clone()).to_string();
+                Some(ToolResult::error(&tool_use.tool_use_id, &error))
+            } else if let Some(refusal) = self.read_only_refusal(&tool_use.name).await {
+                tracing::info!(
+                    "tool_name=<{}>, tool_use_id=<{}> | refused mutating tool in read-only mode",
+                    tool_use.name,
+                    tool_use.tool_use_id
+                );
+                Some(ToolResult::error(&tool_use.tool_use_id, &refusal.to_string()))
+            } else {
+                let input = tool_use.input.clone().unwrap_or_else(|| Value::Object(Default::default()));
+                calls.push((tool_use.name.clone(), input));
+                None
+            });
+        }
+
+        let mut executed = self
+            .tool_executor
+            .execute_parallel_by_name_in(calls, &self.tool_registry, scope.working_directory)
+            .await
+            .into_iter();
+        let mut results = Vec::with_capacity(tool_uses.len());
+        for (tool_use, answer) in tool_uses.iter().zip(answered) {
+            let (result, duration) = match answer {
+                Some(result) => (result, Duration::ZERO),
+                None => {
+                    let execution = executed.next().unwrap_or_else(|| {
+                        Err(IndubitablyError::ToolError(ToolError::ExecutionFailed("Tool was not run".to_string())))
+                    });
+                    let duration = execution
+                        .as_ref()
+                        .map(|execution| Duration::from_millis(execution.execution_time_ms))
+                        .unwrap_or_default();
+                    (self.tool_result(tool_use, execution, outputs), duration)
+                }
+            };
+            self.run_probe.tool_finished(&tool_use.name);
+
+            let summary = result
+                .content
+                .first()
+                .and_then(|content| content.text.as_deref())
+                .map(summarize)
+                .unwrap_or_default();
+            let is_error = result.is_error == Some(true);
+            event_loop.record(MetricEvent::ToolLatency {
+                tool_name: tool_use.name.clone(),
+                duration,
+                is_error,
+            });
+            timeline.record(SpanCategory::Tool, &tool_use.name, tools_started, serde_json::json!({
+                "tool_use_id": tool_use.tool_use_id,
+                "is_error": is_error,
+            }));
+            let mut completed = StreamEvent::tool_completed(&tool_use.tool_use_id, &tool_use.name, &summary, is_error);
+            if let Some(hint) = self.ui_hint(tool_use, &result) {
+                completed = completed.with_ui_hint(hint);
+            }
+            self.emit(completed);
+            self.publish(LifecycleEventKind::ToolCompleted, serde_json::json!({
+                "tool_use_id": tool_use.tool_use_id,
+                "name": tool_use.name,
+                "is_error": is_error,
+            }))
+            .await;
+            results.push(result);
+        }
+        results
+    }
+
+    /// Get the UI hint of the tool's result renderer for a successful result, when events are streamed.
+    fn ui_hint(&self, tool_use: &ToolUse, result: &ToolResult) -> Option<Value> {
+        let renderers = self.config.result_renderers.as_ref()?;
+        if self.stream_events.is_none() || result.is_error == Some(true) {
+            return None;
+        }
+        let text = result.content.first()?.text.as_deref()?;
+        let input = tool_use.input.clone().unwrap_or(Value::Null);
+        let output = serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()));
+        renderers.render_with(&tool_use.name, &input, &output).map(|rendered| rendered.hint)
+    }
+
+    /// Convert the outcome of a tool execution into a tool result.
+    fn tool_result(
+        &self,
+        tool_use: &ToolUse,
+        executed: IndubitablyResult<ToolExecutionResult>,
+        outputs: &mut ToolOutputs,
+    ) -> ToolResult {
+        match executed {
+            Ok(mut execution) if execution.is_success() => {
+                outputs.citations.extend(Citation::from_tool_output(&execution.output));
+                outputs.images.extend(take_images(&mut execution.output));
+                let output = self.config.memory_limits.truncate_tool_result(execution.output);
+                let text = match output {
+                    Value::String(text) => text,
+                    other => other.to_string(),
+                };
+                ToolResult::new(&tool_use.tool_use_id, vec![ToolResultContent::text(&text)])
+            }
+            Ok(execution) => ToolResult::error(
+                &tool_use.tool_use_id,
+                execution.error().unwrap_or("Tool execution failed"),
+            ),
+            Err(e) => ToolResult::error(&tool_use.tool_use_id, &e.to_string()),
+        }
+    }
+
+    /// Build the refusal returned to the model for a mutating tool in read-only mode.
+    async fn read_only_refusal(&self, tool_name: &str) -> Option<Value> {
+        if !self.config.read_only {
+            return None;
+        }
+        let tool = self.tool_registry.get(tool_name).await?;
+        if !tool.metadata.is_mutating() {
+            return None;
+        }
+        let effects: Vec<&str> = tool.metadata.effects.iter().map(|effect| effect.as_str()).collect();
+        Some(serde_json::json!({
+            "status": "refused",
+            "reason": "read_only",
+            "tool": tool_name,
+            "effects": effects,
+            "message": format!(
+                "Tool '{}' was not run because the agent is in read-only mode and the tool can {}. Continue without it or tell the user what you would have done.",
+                tool_name,
+                effects.join(", ")
+            ),
+        }))
+    }
+
+    /// Get the system prompt with the current user's memory profile appended.
+    async fn system_prompt_with_profile(&self) -> String {
+        let (Some(memory), Some(user_id)) = (self.config.user_memory.as_ref(), self.user_id.as_deref()) else {
+            return self.config.system_prompt.clone();
+        };
+        match memory.profile(user_id).await {
+            Ok(Some(profile)) if self.config.system_prompt.is_empty() => profile,
+            Ok(Some(profile)) => format!("{}\n\n{}", self.config.system_prompt, profile),
+            Ok(None) => self.config.system_prompt.clone(),
+            Err(e) => {
+                tracing::warn!("user_id=<{}>, error=<{}> | failed to load user memory profile", user_id, e);
+                self.config.system_prompt.clone()
+            }
+        }
+    }
+
+    /// Extract durable facts about the current user from a finished turn.
+    async fn remember_user_facts(&self, turn: &[Message]) {
+        let (Some(memory), Some(user_id), Some(model)) =
+            (self.config.user_memory.as_ref(), self.user_id.as_deref(), self.config.model.as_ref())
+        else {
+            return;
+        };
+        match memory.extract(model.as_ref(), user_id, self.session_id.as_deref(), turn).await {
+            Ok(added) if !added.is_empty() => {
+                tracing::debug!("user_id=<{}>, count=<{}> | remembered user facts", user_id, added.len());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("user_id=<{}>, error=<{}> | failed to extract user facts", user_id, e),
+        }
+    }
+
+    /// Estimate the input tokens of a request when tokenizers are configured.
+    fn estimate_request_tokens(
+        &self,
+        model: &dyn Model,
+        request: &Messages,
+        tool_specs: &[ToolSpec],
+        system_prompt: &str,
+    ) -> Option<usize> {
+        let tokenizers = self.config.tokenizers.as_ref()?;
+        let tokenizer = tokenizers.resolve(model.model_id());
+        let specs = serde_json::to_string(tool_specs).unwrap_or_default();
+        Some(
+            tokenizer.count_message_tokens(request)
+                + tokenizer.count_tokens(system_prompt)
+                + tokenizer.count_tokens(&specs),
+        )
+    }
+
+    /// Fill in usage the provider did not report from tokenizer estimates.
+    fn estimate_usage(&self, model: &dyn Model, response: &mut ModelResponse, input_tokens: Option<usize>) {
+        let (Some(tokenizers), Some(input_tokens)) = (self.config.tokenizers.as_ref(), input_tokens) else {
+            return;
+        };
+        let tokenizer = tokenizers.resolve(model.model_id());
+        let mut output_tokens = tokenizer.count_tokens(&response.content);
+        for tool_use in &response.tool_uses {
+            output_tokens += tokenizer.count_tokens(&tool_use.name);
+            output_tokens += tool_use.input.as_ref().map_or(0, |input| tokenizer.count_tokens(&input.to_string()));
+        }
+        let reasoning_tokens: usize = response
+            .reasoning
+            .iter()
+            .map(|block| tokenizer.count_tokens(&block.reasoning_text.text))
+            .sum();
+        let output_tokens = (output_tokens + reasoning_tokens) as u32;
+        response.usage = Some(ModelUsage {
+            input_tokens: input_tokens as u32,
+            output_tokens,
+            total_tokens: input_tokens as u32 + output_tokens,
+            reasoning_tokens: reasoning_tokens as u32,
+        });
+        response.metadata.insert("usage_estimated".to_string(), serde_json::json!(true));
+    }
+
+    /// Add model usage to the budget and emit a warning event when it nears the limit.
+    async fn track_budget(&mut self, usage: &ModelUsage) {
+        let Some(ref budget) = self.config.budget else {
+            return;
+        };
+        budget.record(&mut self.budget_usage, usage);
+
+        let status = budget.status(&self.budget_usage);
+        if status == BudgetStatus::WithinBudget || self.budget_warned {
+            return;
+        }
+        self.budget_warned = true;
+
+        let data = serde_json::json!({
+            "input_tokens": self.budget_usage.input_tokens,
+            "output_tokens": self.budget_usage.output_tokens,
+            "reasoning_tokens": self.budget_usage.reasoning_tokens,
+            "cost": self.budget_usage.cost,
+            "fraction_used": budget.fraction_used(&self.budget_usage),
+            "exhausted": status == BudgetStatus::Exhausted,
+        });
+        self.publish(LifecycleEventKind::BudgetWarning, data).await;
+    }
+
+    /// Add a message to the stored conversation, trimming it to the configured memory limits.
+    ///
+    /// The limits are checked before the conversation changes, so a message refused
+    /// with the `Error` strategy never enters the history.
+    async fn add_within_memory_limits(&mut self, message: Message) -> IndubitablyResult<()> {
+        if self.config.memory_limits.max_conversation_bytes.is_none() {
+            return self.conversation_manager.add_message(message).await;
+        }
+
+        let mut messages = self.conversation_manager.get_context().await?;
+        messages.push(message.clone());
+        if self.config.memory_limits.enforce_conversation(&mut messages)? == 0 {
+            return self.conversation_manager.add_message(message).await;
+        }
+        self.conversation_manager.clear().await?;
+        for message in messages {
+            self.conversation_manager.add_message(message).await?;
+        }
+        Ok(())
+    }
+
+    /// Run the agent with a message and get a streaming response.
+    pub async fn run_streaming(&mut self, message: &str) -> IndubitablyResult<AgentResult> {
+        // For now, just call the regular run method
+        // TODO: Implement actual streaming
+        self.run(message).await
+    }
+
+    /// Add a tool to the agent.
+    pub async fn add_tool(&mut self, tool: crate::tools::registry::Tool) -> IndubitablyResult<()> {
+        self.tool_registry.register(tool).await?;
+        Ok(())
+    }
+
+    /// Append a segment to the system prompt, separated by a blank line.
+    pub fn append_system_prompt(&mut self, segment: &str) {
+        let segment = segment.trim();
+        if segment.is_empty() {
+            return;
+        }
+        if !self.config.system_prompt.is_empty() {
+            self.config.system_prompt.push_str("\n\n");
+        }
+        self.config.system_prompt.push_str(segment);
+    }
+
+    /// Set the conversation manager.
+    pub fn with_conversation_manager(mut self, manager: Box<dyn ConversationManager>) -> Self {
+        self.conversation_manager = manager;
+        self
+    }
+
+    /// Send tool-call boundary markers to the given channel while running.
+    pub fn with_stream_event_sender(mut self, sender: UnboundedSender<StreamEvent>) -> Self {
+        self.stream_events = Some(sender);
+        self
+    }
+
+    /// Set the executor used to run tools requested by the model.
+    pub fn with_tool_executor(mut self, executor: ToolExecutor) -> Self {
+        self.tool_executor = executor;
+        self
+    }
+
+    /// Get the agent's configuration.
+    pub fn config(&self) -> &AgentConfig {
+        &self.config
+    }
+
+    /// Get the agent's state.
+    pub fn state(&self) -> &AgentState {
+        &self.state
+    }
+
+    /// Get the agent's state as mutable.
+    pub fn state_mut(&mut self) -> &mut AgentState {
+        &mut self.state
+    }
+
+    /// Get a snapshot of the agent's metrics.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.snapshot()
+    }
+
+    /// Get the bus the agent publishes lifecycle events to.
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Get the agent's tool registry, for example to serve its tools with `MCPServer`.
+    pub fn tool_registry(&self) -> Arc<ToolRegistry> {
+        Arc::clone(&self.tool_registry)
+    }
+
+    /// Get the agent's hook registry.
+    pub fn hooks(&self) -> &HookRegistry {
+        &self.hooks
+    }
+
+    /// Call a handler with each event of a type, such as `agent.on(|event: ToolExecuted| ...)`.
+    ///
+    /// The handler is removed when the returned subscription is dropped.
+    pub async fn on<E, F>(&self, handler: F) -> Subscription
+    where
+        E: TypedEvent,
+        F: Fn(E) + Send + Sync + 'static,
+    {
+        self.hooks.subscribe(handler).await
+    }
+
+    /// Get the usage counted against the conversation budget.
+    pub fn budget_usage(&self) -> &BudgetUsage {
+        &self.budget_usage
+    }
+
+    /// Get the conversation history.
+    pub async fn get_history(&self) -> IndubitablyResult<Messages> {
+        if let Some(ref history) = self.pending_history {
+            return Ok(history.clone());
+        }
+        self.conversation_manager.get_context().await
+    }
+
+    /// Get the interrupt of the last run, if it is awaiting an answer from the user.
+    pub fn pending_interrupt(&self) -> Option<&Interrupt> {
+        self.pending_interrupt.as_ref()
+    }
+
+    /// Answer the pending interrupt and continue the run it paused.
+    ///
+    /// Approving runs the held tool calls before the model is called again. Declining sends the
+    /// reply as the next message instead, and the held calls never run.
+    pub async fn resume(&mut self, decision: InterruptDecision) -> IndubitablyResult<AgentResult> {
+        let Some(interrupt) = self.pending_interrupt.clone() else {
+            return Err(IndubitablyError::ValidationError("No interrupt is awaiting an answer".to_string()));
+        };
+        match decision {
+            InterruptDecision::Approve => {
+                let user_message = Message::user(INTERRUPT_APPROVED_MESSAGE).with_id(&uuid::Uuid::new_v4().to_string());
+                self.run_message(user_message, Some(interrupt)).await
+            }
+            InterruptDecision::Decline(reply) => self.run(&reply).await,
+        }
+    }
+
+    /// Approve the pending interrupt, running the tool calls it held back.
+    pub async fn approve_pending(&mut self) -> IndubitablyResult<AgentResult> {
+        self.resume(InterruptDecision::Approve).await
+    }
+
+    /// Capture the runtime state of the agent, such as to move the conversation to another process.
+    pub async fn snapshot(&self) -> IndubitablyResult<AgentSnapshot> {
+        Ok(AgentSnapshot {
+            version: AGENT_SNAPSHOT_VERSION,
+            agent_name: self.config.name.clone(),
+            taken_at: chrono::Utc::now(),
+            conversation: self.conversation_manager.get_context().await?,
+            state: self.state.clone(),
+            pending_interrupt: self.pending_interrupt.clone(),
+            budget_usage: self.budget_usage,
+            budget_warned: self.budget_warned,
+            guardrail_log: self.guardrail_log.clone(),
+            user_id: self.user_id.clone(),
+            session_id: self.session_id.clone(),
+            experiment_labels: self.experiment_labels.clone(),
+            forks: self.forks.clone(),
+            tool_cache: self.config.tool_selector.as_ref().and_then(|selector| selector.export_cache()),
+        })
+    }
+
+    /// Replace the runtime state of the agent with a snapshot.
+    ///
+    /// The agent keeps its own configuration, model and tools, and the state keeps this agent's size limit.
+    pub async fn restore(&mut self, snapshot: AgentSnapshot) -> IndubitablyResult<()> {
+        snapshot.check_version()?;
+        if let (Some(selector), Some(cache)) = (&self.config.tool_selector, snapshot.tool_cache) {
+            selector.import_cache(cache)?;
+        }
+        let messages = snapshot.conversation.len();
+        self.conversation_manager.clear().await?;
+        for message in snapshot.conversation {
+            self.conversation_manager.add_message(message).await?;
+        }
+        self.state = snapshot.state.with_max_size_bytes(self.state.max_size_bytes());
+        self.pending_interrupt = snapshot.pending_interrupt;
+        self.pending_history = None;
+        self.budget_usage = snapshot.budget_usage;
+        self.budget_warned = snapshot.budget_warned;
+        self.guardrail_log = snapshot.guardrail_log;
+        self.user_id = snapshot.user_id;
+        self.session_id = snapshot.session_id;
+        self.experiment_labels = snapshot.experiment_labels;
+        self.forks = snapshot.forks;
+        tracing::debug!(
+            "agent=<{}>, source_agent=<{}>, messages=<{}> | restored agent snapshot",
+            self.config.name,
+            snapshot.agent_name,
+            messages
+        );
+        Ok(())
+    }
+
+    /// Export a ZIP archive of the conversation, configuration, tool specs, recent events and SDK version for a bug report.
+    ///
+    /// Secrets are removed with the agent's secret redactor, the default
+    /// credential patterns and by masking fields named like credentials.
+    pub async fn export_debug_bundle(&self) -> IndubitablyResult<Vec<u8>> {
+        let redactor = self
+            .config
+            .secret_redactor
+            .as_deref()
+            .cloned()
+            .unwrap_or_default()
+            .with_default_patterns();
+        let config = serde_json::json!({
+            "name": self.config.name,
+            "system_prompt": self.config.system_prompt,
+            "model": self.config.model.as_ref().map(|model| model.config()),
+            "budget": self.config.budget,
+            "read_only": self.config.read_only,
+            "context_window": self.config.context_window,
+            "max_tool_argument_retries": self.config.max_tool_argument_retries,
+            "guardrail_policy": self.config.guardrail_policy.as_ref().map(|policy| policy.name()),
+            "degraded_mode_handler": self.config.degraded_mode_handler.is_some(),
+            "clarification_policy": self.config.clarification_policy.is_some(),
+            "tool_selector": self.config.tool_selector.is_some(),
+            "secret_redactor": self.config.secret_redactor.is_some(),
+            "experiments": self.config.experiments.is_some(),
+            "user_memory": self.config.user_memory.is_some(),
+            "compressor": self.config.compressor.is_some(),
+            "tokenizers": self.config.tokenizers.is_some(),
+            "metrics_sink": self.config.metrics_sink.is_some(),
+            "workspace": self.config.workspace.is_some(),
+            "post_processors": self.config.post_processors.names(),
+            "options": self.config.options,
+        });
+        let events = self.recent_events.events();
+        let bundle = DebugBundle {
+            version: version_info(),
+            transcript: scrub(serde_json::to_value(self.get_history().await?)?, &redactor),
+            config: scrub(config, &redactor),
+            tool_specs: scrub(serde_json::to_value(self.tool_specs().await)?, &redactor),
+            events: scrub(serde_json::to_value(&events)?, &redactor),
+        };
+        tracing::debug!(
+            "agent=<{}>, events=<{}> | exported debug bundle",
+            self.config.name,
+            events.len()
+        );
+        bundle.to_zip()
+    }
+
+    /// Clear the conversation history.
+    pub async fn clear_history(&mut self) -> IndubitablyResult<()> {
+        self.conversation_manager.clear().await?;
+        Ok(())
+    }
+}
+
+impl Default for Agent {
+    fn default() -> Self {
+        Self::new().expect("Failed to create default agent")
+    }
+}
+
+/// The maximum length of a tool summary in stream markers, in characters.
+const TOOL_SUMMARY_MAX_CHARS: usize = 80;
+
+/// Shorten tool output to a one-line summary for stream markers.
+fn summarize(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    let mut summary: String = line.chars().take(TOOL_SUMMARY_MAX_CHARS).collect();
+    if summary.len() < text.len() {
+        summary.push('…');
+    }
+    summary
+}
+
+/// A builder for creating agents with a fluent interface.
+pub struct AgentBuilder {
+    config: AgentConfig,
+    require_model: bool,
+}
+
+impl AgentBuilder {
+    /// Create a new agent builder.
+    pub fn new() -> Self {
+        Self {
+            config: AgentConfig::new(),
+            require_model: false,
+        }
+    }
+
+    /// Set the agent name.
+    pub fn name(mut self, name: &str) -> Self {
+        self.config.name = name.to_string();
+        self
+    }
+
+    /// Set the system prompt.
+    pub fn system_prompt(mut self, prompt: &str) -> Self {
+        self.config.system_prompt = prompt.to_string();
+        self
+    }
+
+    /// Set the model.
+    pub fn model(mut self, model: Box<dyn Model>) -> Self {
+        self.config.model = Some(model);
+        self
+    }
+
+    /// Add a tool specification.
+    pub fn tool(mut self, tool: ToolSpec) -> Self {
+        self.config.tools.push(tool);
+        self
+    }
+
+    /// Set the conversation manager configuration.
+    pub fn conversation_config(mut self, config: ConversationManagerConfig) -> Self {
+        self.config.conversation_config = config;
+        self
+    }
+
+    /// Set the memory limits.
+    pub fn memory_limits(mut self, limits: MemoryLimits) -> Self {
+        self.config.memory_limits = limits;
+        self
+    }
+
+    /// Set the handler invoked when the model is unavailable.
+    pub fn degraded_mode_handler(mut self, handler: Arc<dyn DegradedModeHandler>) -> Self {
+        self.config.degraded_mode_handler = Some(handler);
+        self
+    }
+
+    /// Set the conversation budget.
+    pub fn budget(mut self, budget: ConversationBudget) -> Self {
+        self.config.budget = Some(budget);
+        self
+    }
+
+    /// Set the clarification policy.
+    pub fn clarification_policy(mut self, policy: ClarificationPolicy) -> Self {
+        self.config.clarification_policy = Some(policy);
+        self
+    }
+
+    /// Refuse every tool flagged as writing, sending or deleting.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.config.read_only = read_only;
+        self
+    }
+
+    /// Set the stage choosing which tool specs to send each turn.
+    pub fn tool_selector(mut self, selector: Arc<dyn ToolSelector>) -> Self {
+        self.config.tool_selector = Some(selector);
+        self
+    }
+
+    /// Expose only the tools a filter lets through, such as those in a tenant's namespaces.
+    pub fn tool_filter(mut self, filter: ToolFilter) -> Self {
+        self.config.tool_filter = Some(filter);
+        self
+    }
+
+    /// Set the guardrail masking secrets in model responses and tool inputs.
+    pub fn secret_redactor(mut self, redactor: SecretRedactor) -> Self {
+        self.config.secret_redactor = Some(Arc::new(redactor));
+        self
+    }
+
+    /// Set the policy blocking or flagging user messages and model answers.
+    pub fn guardrail_policy(mut self, policy: GuardrailPolicy) -> Self {
+        self.config.guardrail_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Set the experiments sessions are assigned to.
+    pub fn experiments(mut self, experiments: Experiments) -> Self {
+        self.config.experiments = Some(Arc::new(experiments));
+        self
+    }
+
+    /// Set the memory of durable facts about users.
+    pub fn user_memory(mut self, memory: UserMemory) -> Self {
+        self.config.user_memory = Some(Arc::new(memory));
+        self
+    }
+
+    /// Set the stage compressing retrieved documents and old turns before each model call.
+    pub fn compressor(mut self, compressor: ContextCompressor) -> Self {
+        self.config.compressor = Some(Arc::new(compressor));
+        self
+    }
+
+    /// Set the tokenizers estimating request size and unreported usage.
+    pub fn tokenizers(mut self, tokenizers: TokenizerRegistry) -> Self {
+        self.config.tokenizers = Some(Arc::new(tokenizers));
+        self
+    }
+
+    /// Set the model's context window in tokens.
+    pub fn context_window(mut self, tokens: usize) -> Self {
+        self.config.context_window = Some(tokens);
+        self
+    }
+
+    /// Set the number of times per run the model may re-emit tool calls with malformed arguments.
+    pub fn max_tool_argument_retries(mut self, retries: usize) -> Self {
+        self.config.max_tool_argument_retries = retries;
+        self
+    }
+
+    /// Set the sink receiving typed metrics from the event loop.
+    pub fn metrics_sink(mut self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.config.metrics_sink = Some(sink);
+        self
+    }
+
+    /// Give each run a scoped working directory for its tools.
+    pub fn workspace(mut self, workspace: WorkspaceConfig) -> Self {
+        self.config.workspace = Some(workspace);
+        self
+    }
+
+    /// Schedule model calls with a limiter, usually shared with other agents.
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.config.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Add a processor rewriting each final answer.
+    pub fn post_processor(mut self, processor: Arc<dyn PostProcessor>) -> Self {
+        self.config.post_processors.push(processor);
+        self
+    }
+
+    /// Set the service turning voice input into text.
+    pub fn transcriber(mut self, transcriber: Arc<dyn Transcriber>) -> Self {
+        self.config.transcriber = Some(transcriber);
+        self
+    }
+
+    /// Send heartbeats from long runs and abort runs past a hard ceiling.
+    pub fn liveness(mut self, liveness: LivenessConfig) -> Self {
+        self.config.liveness = Some(liveness);
+        self
+    }
+
+    /// Attach the UI hints of result renderers to `toolCompleted` stream events.
+    pub fn result_renderers(mut self, renderers: RendererRegistry) -> Self {
+        self.config.result_renderers = Some(renderers);
+        self
+    }
+
+    /// Start the agent in an existing conversation, given as messages or a transcript path.
+    ///
+    /// Transcripts may hold OpenAI chat messages or the SDK's own messages as
+    /// JSON or JSONL; see `load_transcript`.
+    pub fn history(mut self, history: impl Into<HistorySource>) -> Self {
+        self.config.history = Some(history.into());
+        self
+    }
+
+    /// Refuse to build an agent without a model instead of answering with a placeholder.
+    pub fn require_model(mut self) -> Self {
+        self.require_model = true;
+        self
+    }
+
+    /// Check the configuration without building the agent.
+    pub fn validate(&self) -> ConfigReport {
+        validate_config(&self.config, self.require_model)
     }
 
     /// Build the agent.
+    ///
+    /// Fails with `IndubitablyError::InvalidConfiguration` carrying the full
+    /// report if the configuration has errors; warnings are logged.
     pub fn build(self) -> IndubitablyResult<Agent> {
+        let report = self.validate();
+        if report.has_errors() {
+            return Err(IndubitablyError::InvalidConfiguration(report));
+        }
+        for issue in report.warnings() {
+            tracing::warn!("field=<{}> | {}", issue.field, issue.message);
+        }
         Agent::with_config(self.config)
     }
 }
@@ -290,6 +2214,7 @@ pub trait ToolCaller: Send + Sync {
 mod tests {
     use super::*;
     use crate::agent::conversation_manager::SlidingWindowConversationManager;
+    use crate::hooks::BUDGET_WARNING_EVENT;
 
     #[tokio::test]
     async fn test_agent_creation() {
@@ -355,6 +2280,706 @@ mod tests {
         assert_eq!(history.len(), 2); // User message + agent response
     }
 
+    #[tokio::test]
+    async fn test_agent_memory_limits() {
+        let message = Message::user("Hello").with_id(&uuid::Uuid::new_v4().to_string());
+        let max_bytes = crate::agent::limits::message_size_bytes(&message) * 3;
+        let limits = MemoryLimits::new().with_max_conversation_bytes(max_bytes);
+        let mut agent = AgentBuilder::new()
+            .memory_limits(limits)
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+
+        for _ in 0..5 {
+            agent.run("Hello").await.unwrap();
+        }
+
+        let history = agent.get_history().await.unwrap();
+        assert!(crate::agent::limits::conversation_size_bytes(&history) <= max_bytes);
+        assert!(!history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_agent_memory_limit_error_keeps_history() {
+        use crate::agent::limits::TruncationStrategy;
+
+        let limits = MemoryLimits::new().with_max_conversation_bytes(1_000).with_strategy(TruncationStrategy::Error);
+        let mut agent = AgentBuilder::new()
+            .memory_limits(limits)
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+        agent.run("Hello").await.unwrap();
+        let before = agent.get_history().await.unwrap();
+
+        assert!(agent.run(&"x".repeat(2_000)).await.is_err());
+        assert_eq!(agent.get_history().await.unwrap(), before);
+    }
+
+    #[tokio::test]
+    async fn test_agent_memory_limit_covers_tool_results() {
+        use crate::agent::limits::TruncationStrategy;
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::Tool;
+        use crate::types::ToolUse;
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("").with_tool_use(ToolUse::new("dump", "call-1").with_input(serde_json::json!({}))),
+            ModelResponse::new("Done."),
+        ]);
+        let limits = MemoryLimits::new().with_max_conversation_bytes(1_000).with_strategy(TruncationStrategy::Error);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .memory_limits(limits)
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+        agent
+            .add_tool(Tool::new("dump", "Dump the table", Arc::new(|_| Ok(serde_json::json!("x".repeat(2_000))))))
+            .await
+            .unwrap();
+
+        assert!(agent.run("Dump the table").await.is_err());
+        let history = agent.get_history().await.unwrap();
+        assert!(crate::agent::limits::conversation_size_bytes(&history) <= 1_000);
+        assert!(!serde_json::to_string(&history).unwrap().contains(&"x".repeat(2_000)));
+    }
+
+    #[tokio::test]
+    async fn test_agent_degraded_mode() {
+        use crate::agent::degraded::CannedResponseHandler;
+        use crate::models::model::MockModel;
+        use crate::testing::chaos::{ChaosConfig, ChaosInjector};
+
+        let injector = ChaosInjector::new(ChaosConfig::new().with_throttling(1.0));
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(injector.wrap_model(Box::new(MockModel::new()))))
+            .degraded_mode_handler(Arc::new(CannedResponseHandler::new("Back soon.")))
+            .build()
+            .unwrap();
+
+        let result = agent.run("Hello").await.unwrap();
+
+        assert_eq!(result.response(), "Back soon.");
+        assert_eq!(result.get_metadata("degraded_kind"), Some(&Value::String("canned".to_string())));
+        assert_eq!(agent.metrics().get(METRIC_RESPONSES_DEGRADED), Some(1.0));
+        assert_eq!(agent.metrics().get(METRIC_RESPONSES_NORMAL), None);
+    }
+
+    #[tokio::test]
+    async fn test_agent_budget_warning() {
+        use crate::agent::budget::ConversationBudget;
+        use crate::models::model::MockModel;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // The mock model reports 25 tokens per response.
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(MockModel::new()))
+            .budget(ConversationBudget::new().with_max_tokens(60))
+            .build()
+            .unwrap();
+
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&warnings);
+        agent
+            .hooks()
+            .register_hook(BUDGET_WARNING_EVENT, Box::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }))
+            .await;
+
+        agent.run("one").await.unwrap();
+        assert_eq!(warnings.load(Ordering::SeqCst), 0);
+        agent.run("two").await.unwrap();
+        agent.run("three").await.unwrap();
+
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+        assert_eq!(agent.budget_usage().total_tokens(), 75);
+    }
+
+    #[tokio::test]
+    async fn test_agent_run_workspace_returns_artifacts() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::Tool;
+        use crate::tools::workspace::current_working_directory;
+        use crate::types::ToolUse;
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("").with_tool_use(ToolUse::new("write_report", "call-1").with_input(serde_json::json!({}))),
+            ModelResponse::new("Report written."),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .workspace(WorkspaceConfig::temp())
+            .build()
+            .unwrap();
+        agent
+            .add_tool(Tool::new("write_report", "Write a report", Arc::new(|_| {
+                let directory = current_working_directory().expect("run has a working directory");
+                std::fs::write(directory.join("report.csv"), "city,forecast\nParis,sunny\n")?;
+                Ok(serde_json::json!("written"))
+            })))
+            .await
+            .unwrap();
+
+        let result = agent.run("Write the weather report").await.unwrap();
+        assert_eq!(result.artifacts.len(), 1);
+        assert_eq!(result.artifacts[0].name, "report.csv");
+        assert_eq!(result.artifacts[0].media_type, "text/csv");
+        assert_eq!(result.artifacts[0].content.as_deref(), Some(&b"city,forecast\nParis,sunny\n"[..]));
+        assert!(!result.artifacts[0].path.parent().unwrap().exists());
+    }
+
+    #[tokio::test]
+    async fn test_agent_executes_tool_calls() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::Tool;
+        use crate::types::ToolUse;
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("Let me check.")
+                .with_tool_use(ToolUse::new("lookup", "call-1").with_input(serde_json::json!({"city": "Paris"}))),
+            ModelResponse::new("It is sunny in Paris."),
+        ]);
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let sink = crate::telemetry::RegistryMetricsSink::new();
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .metrics_sink(Arc::new(sink.clone()))
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)))
+            .with_stream_event_sender(sender);
+        agent
+            .add_tool(Tool::new("lookup", "Look up the weather", Arc::new(|_| Ok(serde_json::json!({
+                "forecast": "sunny",
+                "citations": [{"sourceId": "weather-service"}]
+            })))))
+            .await
+            .unwrap();
+
+        let recorder = crate::telemetry::EventRecorder::new();
+        agent.events().subscribe(Arc::new(recorder.clone()));
+
+        let result = agent.run("Weather in Paris?").await.unwrap();
+        assert_eq!(result.response(), "It is sunny in Paris.");
+        assert_eq!(
+            recorder.kinds(),
+            vec![
+                LifecycleEventKind::RunStarted,
+                LifecycleEventKind::ModelCallCompleted,
+                LifecycleEventKind::ToolStarted,
+                LifecycleEventKind::ToolCompleted,
+                LifecycleEventKind::ModelCallCompleted,
+                LifecycleEventKind::RunCompleted,
+            ]
+        );
+        assert_eq!(agent.metrics().get(METRIC_RESPONSES_NORMAL), Some(1.0));
+        assert_eq!(agent.metrics().get(crate::telemetry::events::METRIC_TOOL_CALLS), Some(1.0));
+        let sunk = sink.snapshot();
+        assert_eq!(sunk.get("agent.cycle.duration.count"), Some(2.0));
+        assert_eq!(sunk.get("agent.model.latency.count"), Some(2.0));
+        assert_eq!(sunk.get("agent.tools.latency.count"), Some(1.0));
+        assert!(result.interrupt.is_none());
+        assert_eq!(result.citations()[0].source_id, "weather-service");
+        assert_eq!(result.stop_reason, StopReason::EndTurn);
+        assert_eq!(result.tool_calls[0].input, serde_json::json!({"city": "Paris"}));
+        assert!(result.tool_calls[0].duration_us.is_some());
+        let trace: Value = serde_json::from_str(&result.to_trace_json().unwrap()).unwrap();
+        let spans: Vec<&str> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .filter_map(|event| event["name"].as_str())
+            .collect();
+        assert_eq!(spans, vec!["model.generate", "lookup", "model.generate", "agent.run"]);
+
+        let history = agent.get_history().await.unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[1].tool_uses()[0].tool_use_id, "call-1");
+
+        let markers: Vec<_> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert_eq!(markers.len(), 3);
+        assert_eq!(markers[1].tool_marker.as_ref().unwrap().name.as_deref(), Some("lookup"));
+        assert_eq!(markers[2].tool_marker.as_ref().unwrap().id, "call-1");
+        assert!(history[2].tool_result_blocks()[0].content[0].text.as_deref().unwrap().contains("sunny"));
+    }
+
+    #[tokio::test]
+    async fn test_agent_retries_malformed_tool_arguments() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::Tool;
+        use crate::tools::repair::MALFORMED_TOOL_CALLS_METADATA_KEY;
+        use crate::types::ToolUse;
+
+        let malformed = || {
+            let mut response = ModelResponse::new("").with_tool_use(ToolUse::new("lookup", "call-1"));
+            response.metadata.insert(
+                MALFORMED_TOOL_CALLS_METADATA_KEY.to_string(),
+                serde_json::json!({"call-1": "EOF while parsing a string"}),
+            );
+            response
+        };
+        let model = MockModel::new().with_responses(vec![
+            malformed(),
+            ModelResponse::new("")
+                .with_tool_use(ToolUse::new("lookup", "call-2").with_input(serde_json::json!({"city": "Paris"}))),
+            ModelResponse::new("It is sunny in Paris."),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+        agent
+            .add_tool(Tool::new("lookup", "Look up the weather", Arc::new(|_| Ok(serde_json::json!("sunny")))))
+            .await
+            .unwrap();
+
+        let result = agent.run("Weather in Paris?").await.unwrap();
+        assert_eq!(result.response(), "It is sunny in Paris.");
+        let history = agent.get_history().await.unwrap();
+        let retry = &history[2].tool_result_blocks()[0];
+        assert_eq!(retry.is_error, Some(true));
+        assert!(retry.content[0].text.as_deref().unwrap().contains("not valid JSON"));
+
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(MockModel::new().with_responses(vec![malformed(), malformed()])))
+            .max_tool_argument_retries(1)
+            .build()
+            .unwrap();
+        assert!(agent.run("Weather in Paris?").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_agent_asks_before_high_impact_tool() {
+        use crate::agent::clarification::ClarificationPolicy;
+        use crate::agent::interrupt::InterruptReason;
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::Tool;
+        use crate::types::ToolUse;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("").with_tool_use(ToolUse::new("drop_table", "call-1")),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .clarification_policy(ClarificationPolicy::new().with_high_impact_tool("drop_table"))
+            .build()
+            .unwrap();
+
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&ran);
+        agent
+            .add_tool(Tool::new("drop_table", "Drop a table", Arc::new(move |_| {
+                flag.store(true, Ordering::SeqCst);
+                Ok(Value::Null)
+            })))
+            .await
+            .unwrap();
+
+        let result = agent.run("Clean up the database").await.unwrap();
+
+        let interrupt = result.interrupt.as_ref().unwrap();
+        assert_eq!(interrupt.reason, InterruptReason::HighImpactTool);
+        assert_eq!(result.response(), interrupt.question);
+        assert!(!ran.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_agent_resumes_approved_interrupt() {
+        use crate::agent::clarification::ClarificationPolicy;
+        use crate::agent::interrupt::InterruptDecision;
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::Tool;
+        use crate::types::ToolUse;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("").with_tool_use(ToolUse::new("drop_table", "call-1")),
+            ModelResponse::new("The table is gone."),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .clarification_policy(ClarificationPolicy::new().with_high_impact_tool("drop_table"))
+            .build()
+            .unwrap();
+
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&ran);
+        agent
+            .add_tool(Tool::new("drop_table", "Drop a table", Arc::new(move |_| {
+                flag.store(true, Ordering::SeqCst);
+                Ok(Value::Null)
+            })))
+            .await
+            .unwrap();
+
+        assert!(agent.resume(InterruptDecision::Approve).await.is_err());
+        agent.run("Clean up the database").await.unwrap();
+        let result = agent.approve_pending().await.unwrap();
+
+        assert!(ran.load(Ordering::SeqCst));
+        assert_eq!(result.response(), "The table is gone.");
+        assert_eq!(result.tool_calls.len(), 1);
+        assert!(agent.pending_interrupt().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_agent_read_only_refuses_mutating_tools() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::{Tool, ToolEffect, ToolMetadata};
+        use crate::types::ToolUse;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("")
+                .with_tool_use(ToolUse::new("send_email", "call-1"))
+                .with_tool_use(ToolUse::new("lookup", "call-2")),
+            ModelResponse::new("I could not send the email."),
+        ]);
+        let mut agent = Agent::with_config(AgentConfig::new().with_model(Box::new(model)).read_only(true))
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+
+        let sent = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&sent);
+        agent
+            .add_tool(
+                Tool::new("send_email", "Send an email", Arc::new(move |_| {
+                    flag.store(true, Ordering::SeqCst);
+                    Ok(Value::Null)
+                }))
+                .with_metadata(ToolMetadata::new().with_effect(ToolEffect::Send)),
+            )
+            .await
+            .unwrap();
+        agent
+            .add_tool(Tool::new("lookup", "Look up a contact", Arc::new(|_| Ok(serde_json::json!("found")))))
+            .await
+            .unwrap();
+
+        let result = agent.run("Email the team").await.unwrap();
+        assert_eq!(result.response(), "I could not send the email.");
+        assert!(!sent.load(Ordering::SeqCst));
+
+        let history = agent.get_history().await.unwrap();
+        let results = history[2].tool_result_blocks();
+        assert_eq!(results[0].is_error, Some(true));
+        let refusal: Value = serde_json::from_str(results[0].content[0].text.as_deref().unwrap()).unwrap();
+        assert_eq!(refusal["reason"], "read_only");
+        assert_eq!(refusal["effects"], serde_json::json!(["send"]));
+        assert_eq!(results[1].content[0].text.as_deref(), Some("found"));
+    }
+
+    #[tokio::test]
+    async fn test_agent_tool_filters_scope_visible_tools() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::{Tool, ToolFilter, ToolMetadata};
+        use crate::types::ToolUse;
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("")
+                .with_tool_use(ToolUse::new("read", "call-1"))
+                .with_tool_use(ToolUse::new("lookup", "call-2")),
+            ModelResponse::new("Found the contact."),
+        ]);
+        let filter = ToolFilter::new().without_tag("dangerous");
+        let mut agent = Agent::with_config(AgentConfig::new().with_model(Box::new(model)).with_tool_filter(filter))
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+        for (name, metadata) in [
+            ("read", ToolMetadata::new().with_namespace("fs")),
+            ("wipe", ToolMetadata::new().with_namespace("fs").with_tag("dangerous")),
+            ("lookup", ToolMetadata::new().with_namespace("crm")),
+        ] {
+            let tool = Tool::new(name, name, Arc::new(|_| Ok(serde_json::json!("found")))).with_metadata(metadata);
+            agent.add_tool(tool).await.unwrap();
+        }
+        let mut visible: Vec<String> = agent.tool_specs().await.into_iter().map(|spec| spec.name).collect();
+        visible.sort();
+        assert_eq!(visible, ["lookup", "read"]);
+
+        let options = RunOptions::new().with_tool_filter(ToolFilter::new().with_namespace("crm"));
+        let result = agent.run_with_options("Find Ada", options).await.unwrap();
+        assert_eq!(result.response(), "Found the contact.");
+        let history = agent.get_history().await.unwrap();
+        let results = history[2].tool_result_blocks();
+        assert_eq!(results[0].is_error, Some(true));
+        assert_eq!(results[1].content[0].text.as_deref(), Some("found"));
+    }
+
+    #[tokio::test]
+    async fn test_agent_plan_does_not_execute_tools() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::{Tool, ToolEffect, ToolMetadata};
+        use crate::types::ToolUse;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("First find the stale invoices.")
+                .with_tool_use(ToolUse::new("list_invoices", "call-1").with_input(serde_json::json!({"status": "stale"}))),
+            ModelResponse::new("Now delete them.")
+                .with_tool_use(ToolUse::new("delete_invoice", "call-2").with_input(serde_json::json!({"id": 7}))),
+            ModelResponse::new("The stale invoices would be deleted."),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&ran);
+        agent
+            .add_tool(
+                Tool::new("delete_invoice", "Delete an invoice", Arc::new(move |_| {
+                    flag.store(true, Ordering::SeqCst);
+                    Ok(Value::Null)
+                }))
+                .with_metadata(ToolMetadata::new().with_effect(ToolEffect::Delete)),
+            )
+            .await
+            .unwrap();
+
+        let plan = agent.plan("Clean up stale invoices").await.unwrap();
+
+        assert!(plan.complete);
+        assert_eq!(plan.tool_names(), vec!["list_invoices", "delete_invoice"]);
+        assert_eq!(plan.steps[1].input, serde_json::json!({"id": 7}));
+        assert_eq!(plan.steps[1].rationale, "Now delete them.");
+        assert!(!plan.steps[0].registered);
+        assert!(plan.has_mutations());
+        assert_eq!(plan.final_response.as_deref(), Some("The stale invoices would be deleted."));
+        assert!(!ran.load(Ordering::SeqCst));
+        assert!(agent.get_history().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_agent_tool_selection() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::telemetry::events::{METRIC_TOOL_SELECTION_HITS, METRIC_TOOL_SELECTION_MISSES};
+        use crate::tools::registry::Tool;
+        use crate::tools::selector::KeywordToolSelector;
+        use crate::types::ToolUse;
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("")
+                .with_tool_use(ToolUse::new("get_weather", "call-1"))
+                .with_tool_use(ToolUse::new("read_file", "call-2")),
+            ModelResponse::new("Sunny."),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .tool_selector(Arc::new(KeywordToolSelector::new().with_top_k(1)))
+            .build()
+            .unwrap();
+        for (name, description) in [
+            ("send_email", "Send an email"),
+            ("get_weather", "Get the weather forecast"),
+            ("read_file", "Read a file"),
+        ] {
+            agent
+                .add_tool(Tool::new(name, description, Arc::new(|_| Ok(Value::Null))))
+                .await
+                .unwrap();
+        }
+
+        let result = agent.run("What is the weather today?").await.unwrap();
+
+        let offered: Vec<&str> = result.available_tools().iter().map(|spec| spec.name.as_str()).collect();
+        assert_eq!(offered, vec!["get_weather"]);
+        assert_eq!(agent.metrics().get(METRIC_TOOL_SELECTION_HITS), Some(1.0));
+        assert_eq!(agent.metrics().get(METRIC_TOOL_SELECTION_MISSES), Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_agent_redacts_echoed_secrets() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::tools::registry::Tool;
+        use crate::types::ToolUse;
+        use std::sync::Mutex;
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("").with_tool_use(
+                ToolUse::new("send_email", "call-1").with_input(serde_json::json!({"body": "key: sk-live-0123456789abcdef"})),
+            ),
+            ModelResponse::new("I emailed sk-live-0123456789abcdef to ops."),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .secret_redactor(SecretRedactor::new().with_secret("model_api_key", "sk-live-0123456789abcdef"))
+            .build()
+            .unwrap();
+        let received = Arc::new(Mutex::new(Value::Null));
+        let sink = Arc::clone(&received);
+        agent
+            .add_tool(Tool::new("send_email", "Send an email", Arc::new(move |input| {
+                *sink.lock().unwrap() = input;
+                Ok(Value::Null)
+            })))
+            .await
+            .unwrap();
+        let recorder = crate::telemetry::EventRecorder::new();
+        agent.events().subscribe(Arc::new(recorder.clone()));
+
+        let result = agent.run("Send the key to ops").await.unwrap();
+
+        assert_eq!(result.response(), "I emailed [REDACTED:model_api_key] to ops.");
+        assert_eq!(received.lock().unwrap()["body"], "key: [REDACTED:model_api_key]");
+        let blocked: Vec<_> = recorder
+            .events()
+            .into_iter()
+            .filter(|event| event.kind == LifecycleEventKind::SecretLeakBlocked)
+            .collect();
+        assert_eq!(blocked.len(), 2);
+        assert_eq!(blocked[0].get("tools"), Some(&serde_json::json!(["send_email"])));
+        assert_eq!(blocked[1].get("in_response"), Some(&Value::Bool(true)));
+    }
+
+    #[tokio::test]
+    async fn test_hook_failure_policies() {
+        use crate::hooks::{HookFailurePolicy, HookOptions};
+        use crate::models::model::{MockModel, ModelResponse};
+
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("Hi"), ModelResponse::new("Hi again")]);
+        let mut agent = Agent::with_model(Box::new(model)).unwrap();
+        agent
+            .hooks()
+            .register_hook("run_started", Box::new(|_| panic!("broken audit hook")))
+            .await;
+        assert_eq!(agent.run("Hello").await.unwrap().response(), "Hi");
+
+        agent
+            .hooks()
+            .register_hook_with_options(
+                "model_call_completed",
+                Box::new(|_| Err("quota hook refused".into())),
+                HookOptions::new().with_name("quota").with_failure_policy(HookFailurePolicy::Abort),
+            )
+            .await;
+        let error = agent.run("Hello again").await.unwrap_err();
+        assert!(error.to_string().contains("Hook 'quota' failed on 'model_call_completed': quota hook refused"));
+        let health = agent.hooks().health().await;
+        assert_eq!((health[0].name.as_str(), health[0].failures), ("quota", 1));
+        assert_eq!((health[1].name.as_str(), health[1].panics), ("run_started#1", 2));
+    }
+
+    #[tokio::test]
+    async fn test_export_debug_bundle() {
+        use crate::agent::debug_bundle::REDACTED_FIELD;
+        use crate::agent::DebugBundle;
+        use crate::models::model::{MockModel, ModelResponse};
+
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("Your key is sk-live-0123456789abcdef.")]);
+        let config = AgentConfig::new()
+            .with_model(Box::new(model))
+            .with_tool(ToolSpec::new("search", "Search the web"))
+            .with_secret_redactor(SecretRedactor::new().with_secret("model_api_key", "sk-live-0123456789abcdef"))
+            .with_option("api_key", Value::String("opaque".to_string()));
+        let mut agent = Agent::with_config(config)
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+        agent.run("My key is sk-live-0123456789abcdef, what is it?").await.unwrap();
+
+        let bundle = DebugBundle::from_zip(&agent.export_debug_bundle().await.unwrap()).unwrap();
+        let transcript = bundle.transcript.to_string();
+        assert!(!transcript.contains("sk-live-0123456789abcdef"));
+        assert!(transcript.contains("My key is [REDACTED:model_api_key], what is it?"));
+        assert_eq!(bundle.config["options"]["api_key"], REDACTED_FIELD);
+        assert_eq!(bundle.config["secret_redactor"], true);
+        assert_eq!(bundle.tool_specs[0]["name"], "search");
+        let kinds: Vec<&str> = bundle.events.as_array().unwrap().iter().filter_map(|event| event["kind"].as_str()).collect();
+        assert_eq!(kinds.first(), Some(&"run_started"));
+        assert_eq!(kinds.last(), Some(&"run_completed"));
+        assert_eq!(bundle.version["version"], crate::VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_agent_guardrail_blocks_and_appeals() {
+        use crate::agent::guardrail::{DecisionStatus, GuardrailAction, GUARDRAIL_DECISIONS_METADATA_KEY};
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::telemetry::events::METRIC_GUARDRAIL_BLOCKS;
+        use crate::types::{SessionAgent, SessionType};
+
+        let policy = GuardrailPolicy::new("strict")
+            .with_rule("self_harm", r"(?i)\boverdose\b", GuardrailAction::Block)
+            .unwrap();
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("Call a doctor about any overdose.")]);
+        let mut agent = AgentBuilder::new().model(Box::new(model)).guardrail_policy(policy).build().unwrap();
+        let recorder = crate::telemetry::EventRecorder::new();
+        agent.events().subscribe(Arc::new(recorder.clone()));
+
+        let result = agent.run("What is a safe dose of ibuprofen?").await.unwrap();
+        assert_eq!(result.response(), GUARDRAIL_BLOCKED_RESPONSE);
+        assert_eq!(result.stop_reason, StopReason::GuardrailBlocked);
+        let decision = agent.guardrail_log().decisions()[0].clone();
+        assert_eq!(decision.stage, GuardrailStage::Output);
+        assert_eq!(decision.span.text, "overdose");
+        assert_eq!(result.metadata["guardrail_decisions"], serde_json::json!([decision.id]));
+        assert_eq!(agent.metrics().get(METRIC_GUARDRAIL_BLOCKS), Some(1.0));
+
+        let appealed = agent.appeal(&decision.id, AppealRoute::HumanReview).await.unwrap();
+        assert_eq!(appealed.status, DecisionStatus::PendingReview);
+        let resolved = agent.resolve_appeal(&decision.id, false, "reviewer-7", Some("medical advice")).await.unwrap();
+        assert_eq!(resolved.status, DecisionStatus::Overturned);
+        assert!(recorder.events().iter().any(|event| event.kind == LifecycleEventKind::GuardrailAppealed
+            && event.get("status") == Some(&serde_json::json!("overturned"))));
+
+        let mut session = Session::new("session-1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        agent.save_guardrail_decisions(&mut session).unwrap();
+        let recorded = &session.metadata.as_ref().unwrap()[GUARDRAIL_DECISIONS_METADATA_KEY][0];
+        assert_eq!(recorded["rule_id"], "self_harm");
+        assert_eq!(recorded["appeal"]["reviewer"], "reviewer-7");
+        assert!(!recorded.to_string().contains("overdose"));
+
+        let input = agent.run("How much causes an overdose?").await.unwrap();
+        assert_eq!(input.stop_reason, StopReason::GuardrailBlocked);
+        assert_eq!(agent.guardrail_log().decisions()[1].stage, GuardrailStage::Input);
+    }
+
+    #[tokio::test]
+    async fn test_agent_regenerate_and_edit() {
+        use crate::models::model::{MockModel, ModelResponse};
+
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("Paris."),
+            ModelResponse::new("The capital is Paris."),
+            ModelResponse::new("Berlin."),
+        ]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+
+        agent.run("Capital of France?").await.unwrap();
+        let message_id = agent.get_history().await.unwrap()[0].id().unwrap().to_string();
+
+        let result = agent.regenerate_last().await.unwrap();
+        assert_eq!(result.response(), "The capital is Paris.");
+        assert_eq!(agent.get_history().await.unwrap().len(), 2);
+
+        let result = agent
+            .edit_user_message_with(&message_id, "Capital of Germany?", EditOptions::new().with_fork(true))
+            .await
+            .unwrap();
+        assert_eq!(result.response(), "Berlin.");
+
+        let history = agent.get_history().await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].all_text(), "Capital of Germany?");
+        assert_eq!(history[0].id(), Some(message_id.as_str()));
+        assert_eq!(agent.forks().len(), 1);
+        assert_eq!(agent.forks()[0].messages[1].all_text(), "The capital is Paris.");
+
+        assert!(agent.edit_user_message("missing", "text").await.is_err());
+    }
+
     #[tokio::test]
     async fn test_agent_clear_conversation() {
         let mut agent = Agent::new().unwrap();
@@ -373,4 +2998,241 @@ mod tests {
         let history = history.unwrap();
         assert_eq!(history.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_agent_joins_experiments() {
+        use crate::agent::experiments::{Experiment, Variant, EXPERIMENTS_METADATA_KEY};
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::telemetry::metrics::labeled_name;
+        use crate::types::{SessionAgent, SessionType};
+
+        let experiments = Experiments::new().with_experiment(
+            Experiment::new("prompt_v2").with_variant(
+                Variant::new("concise").with_system_prompt("Be concise.").with_model_id("small-model"),
+            ),
+        );
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(MockModel::new().with_responses(vec![ModelResponse::new("Hi.")])))
+            .experiments(experiments)
+            .build()
+            .unwrap();
+        let mut session = Session::new("session-1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+
+        let assignments = agent.join_experiments(&mut session).unwrap();
+        assert_eq!(assignments[0].variant.name, "concise");
+        assert_eq!(agent.config.system_prompt, "Be concise.");
+        assert_eq!(agent.config.model.as_ref().unwrap().model_id(), "small-model");
+        assert_eq!(session.metadata.as_ref().unwrap()[EXPERIMENTS_METADATA_KEY]["prompt_v2"], "concise");
+
+        agent.run("Hello").await.unwrap();
+        let labels = agent.experiment_labels().clone();
+        assert_eq!(agent.metrics().get(METRIC_RESPONSES_NORMAL), Some(1.0));
+        assert_eq!(agent.metrics().get(&labeled_name(METRIC_RESPONSES_NORMAL, &labels)), Some(1.0));
+        assert_eq!(labeled_name(METRIC_RESPONSES_NORMAL, &labels), "agent.responses.normal{prompt_v2=\"concise\"}");
+    }
+
+    #[tokio::test]
+    async fn test_run_with_options_waits_for_rate_limiter() {
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::models::rate_limit::Priority;
+
+        let limiter = RateLimiter::new(1);
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("Batch done")]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .rate_limiter(limiter.clone())
+            .build()
+            .unwrap();
+
+        let held = limiter.acquire(Priority::Interactive).await.unwrap();
+        let run = tokio::spawn(async move {
+            agent
+                .run_with_options("Summarize the archive", RunOptions::new().with_priority(Priority::Background))
+                .await
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert_eq!(limiter.queued(), 1);
+        assert!(!run.is_finished());
+
+        drop(held);
+        assert_eq!(run.await.unwrap().unwrap().response(), "Batch done");
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[cfg(all(feature = "anthropic", feature = "bedrock", feature = "openai-compatible"))]
+    #[tokio::test]
+    async fn test_run_with_image_reaches_provider_requests() {
+        use crate::models::anthropic::messages_request_body;
+        use crate::models::bedrock_converse::converse_request_body;
+        use crate::models::model::{MockModel, ModelConfig, ModelResponse};
+        use crate::models::openai_compat::chat_request_body;
+
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("A red square.")]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert!(agent.run_with_image_bytes("Describe it", b"not an image").await.is_err());
+        assert!(agent.run_with_content(Vec::new()).await.is_err());
+        let result = agent.run_with_image_bytes("Describe it", png).await.unwrap();
+        assert_eq!(result.response(), "A red square.");
+
+        let history = agent.get_history().await.unwrap();
+        let image = history[0].content[1].image.as_ref().unwrap();
+        assert_eq!(image.source.media_type, "image/png");
+        assert_eq!(history[0].all_text(), "Describe it");
+
+        let config = ModelConfig::new("vision");
+        let data = image.base64_data().unwrap();
+        let openai = chat_request_body(&config, &history, None, None);
+        assert_eq!(openai["messages"][0]["content"][0]["text"], "Describe it");
+        assert_eq!(
+            openai["messages"][0]["content"][1]["image_url"]["url"],
+            format!("data:image/png;base64,{}", data)
+        );
+        let anthropic = messages_request_body(&config, &history, None, None);
+        assert_eq!(anthropic["messages"][0]["content"][1]["source"]["data"], data);
+        let converse = converse_request_body(&config, &history, None, None);
+        assert_eq!(converse["messages"][0]["content"][1]["image"]["format"], "png");
+    }
+
+    #[tokio::test]
+    async fn test_run_with_audio_sends_transcript() {
+        use crate::media::transcription::Transcription;
+        use crate::models::model::{MockModel, ModelResponse};
+
+        struct FixedTranscriber;
+
+        #[async_trait]
+        impl Transcriber for FixedTranscriber {
+            async fn transcribe(&self, _audio: &AudioContent) -> IndubitablyResult<Transcription> {
+                let mut transcription = Transcription::new("What time is it in Oslo?");
+                transcription.language = Some("en".to_string());
+                Ok(transcription)
+            }
+        }
+
+        let audio = AudioContent::base64("UklGRg==", "audio/wav");
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("It is noon.")]);
+        let mut agent = AgentBuilder::new().model(Box::new(model)).build().unwrap();
+        assert!(agent.run_with_audio(&audio).await.is_err());
+
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("It is noon.")]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .transcriber(Arc::new(FixedTranscriber))
+            .build()
+            .unwrap()
+            .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)));
+        let result = agent.run_with_audio(&audio).await.unwrap();
+        assert_eq!(result.response(), "It is noon.");
+
+        let history = agent.get_history().await.unwrap();
+        assert_eq!(history[0].all_text(), "What time is it in Oslo?");
+        let metadata = history[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata[TRANSCRIPTION_METADATA_KEY]["language"], "en");
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_aborts_hung_run_with_diagnostics() {
+        use std::time::Duration;
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::models::rate_limit::Priority;
+        use crate::telemetry::events::EventRecorder;
+
+        // The only model slot is held elsewhere, so the run waits forever
+        let limiter = RateLimiter::new(1);
+        let _held = limiter.acquire(Priority::Interactive).await.unwrap();
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("Never sent.")]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .rate_limiter(limiter)
+            .liveness(
+                LivenessConfig::new()
+                    .with_heartbeat_after(Duration::from_millis(10))
+                    .with_heartbeat_interval(Duration::from_millis(10))
+                    .with_hard_ceiling(Duration::from_millis(60)),
+            )
+            .build()
+            .unwrap();
+        let recorder = EventRecorder::new();
+        agent.events().subscribe(Arc::new(recorder.clone()));
+
+        let error = agent.run("Hello").await.unwrap_err();
+        assert!(matches!(
+            error,
+            IndubitablyError::EventLoopError(crate::types::EventLoopError::WatchdogTimeout(_))
+        ));
+
+        let events = recorder.events();
+        let heartbeat = events.iter().find(|event| event.kind == LifecycleEventKind::RunHeartbeat).unwrap();
+        assert_eq!(heartbeat.get("phase").and_then(Value::as_str), Some("model_call"));
+        assert_eq!(heartbeat.get("agent").and_then(Value::as_str), Some(agent.config.name.as_str()));
+        let aborted = events.iter().find(|event| event.kind == LifecycleEventKind::RunAborted).unwrap();
+        assert_eq!(aborted.get("cycle").and_then(Value::as_u64), Some(1));
+        assert_eq!(aborted.get("model_calls").and_then(Value::as_u64), Some(1));
+        assert!(agent.run_probe.diagnostics().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restores_conversation_in_another_agent() {
+        use crate::agent::clarification::ClarificationPolicy;
+        use crate::models::model::{MockModel, ModelResponse};
+        use crate::types::ToolUse;
+
+        let build = |responses: Vec<ModelResponse>| {
+            AgentBuilder::new()
+                .model(Box::new(MockModel::new().with_responses(responses)))
+                .clarification_policy(ClarificationPolicy::new().with_high_impact_tool("drop_table"))
+                .build()
+                .unwrap()
+                .with_conversation_manager(Box::new(SlidingWindowConversationManager::new(100)))
+        };
+        let mut agent = build(vec![ModelResponse::new("").with_tool_use(ToolUse::new("drop_table", "call-1"))]);
+        let result = agent.run("Clean up the database").await.unwrap();
+        let interrupt_id = result.interrupt.as_ref().unwrap().id.clone();
+        agent.state_mut().set_metadata("ticket", serde_json::json!("OPS-12"));
+        agent.set_user("user-7", Some("session-3"));
+
+        let blob = agent.snapshot().await.unwrap().to_bytes().unwrap();
+        let snapshot = AgentSnapshot::from_bytes(&blob).unwrap();
+        let mut migrated = build(vec![ModelResponse::new("Cancelled.")]);
+        migrated.restore(snapshot).await.unwrap();
+
+        assert_eq!(migrated.get_history().await.unwrap(), agent.get_history().await.unwrap());
+        assert_eq!(migrated.pending_interrupt().unwrap().id, interrupt_id);
+        assert_eq!(migrated.state().get_metadata("ticket"), Some(&serde_json::json!("OPS-12")));
+        assert_eq!(migrated.user_id(), Some("user-7"));
+
+        // Answering the interrupt clears it
+        migrated.run("No, keep the tables").await.unwrap();
+        assert!(migrated.pending_interrupt().is_none());
+
+        let mut future = agent.snapshot().await.unwrap();
+        future.version = AGENT_SNAPSHOT_VERSION + 1;
+        assert!(migrated.restore(future).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_history_seeds_the_first_run() {
+        use crate::models::model::MockModel;
+
+        let history = vec![Message::user("My name is Ada."), Message::assistant("Nice to meet you, Ada.")];
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(MockModel::new().with_responses(vec![ModelResponse::new("Your name is Ada.")])))
+            .history(history.clone())
+            .build()
+            .unwrap();
+        assert_eq!(agent.get_history().await.unwrap(), history);
+
+        agent.run("What is my name?").await.unwrap();
+        let seeded = agent.get_history().await.unwrap();
+        assert_eq!(seeded.len(), 4);
+        assert_eq!(seeded[..2], history[..]);
+
+        let report = AgentBuilder::new().history(vec![Message::assistant("Hello")]).validate();
+        assert!(report.errors().any(|issue| issue.field == "history"));
+    }
 }
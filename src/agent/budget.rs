@@ -0,0 +1,195 @@
+//! Conversation budgets for the SDK.
+//! 
+//! This module provides `ConversationBudget`, which tracks token and cost
+//! usage for a conversation. Once usage crosses the warning threshold the
+//! agent injects a system note asking the model to wrap up concisely and
+//! emits a hook event, so budgets end conversations gracefully rather than
+//! cutting off an answer midway.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::model::ModelUsage;
+
+/// The default note injected when a conversation nears its budget.
+pub const DEFAULT_WRAP_UP_NOTE: &str = "This conversation has used {percent}% of its budget. \
+Wrap up concisely: finish the current task, avoid starting new work and keep the remaining answers short.";
+
+/// Accumulated usage for a conversation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    /// The number of input tokens used.
+    pub input_tokens: u64,
+    /// The number of output tokens used.
+    pub output_tokens: u64,
+    /// The number of output tokens spent on reasoning.
+    #[serde(default)]
+    pub reasoning_tokens: u64,
+    /// The estimated cost in US dollars.
+    pub cost: f64,
+    /// The part of the estimated cost spent on reasoning.
+    #[serde(default)]
+    pub reasoning_cost: f64,
+}
+
+impl BudgetUsage {
+    /// Get the total number of tokens used.
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Where a conversation stands relative to its budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetStatus {
+    /// Usage is below the warning threshold.
+    WithinBudget,
+    /// Usage is at or above the warning threshold, as a fraction of the budget.
+    Warning(f64),
+    /// The budget has been used up.
+    Exhausted,
+}
+
+/// A token and cost budget for a conversation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationBudget {
+    /// The maximum number of tokens, if bounded.
+    pub max_tokens: Option<u64>,
+    /// The maximum cost in US dollars, if bounded.
+    pub max_cost: Option<f64>,
+    /// The price per 1,000 input tokens in US dollars.
+    pub input_cost_per_1k: f64,
+    /// The price per 1,000 output tokens in US dollars.
+    pub output_cost_per_1k: f64,
+    /// The fraction of the budget at which to start warning.
+    pub warning_threshold: f64,
+    /// The note injected for the model; `{percent}` is replaced with the usage.
+    pub wrap_up_note: String,
+}
+
+impl Default for ConversationBudget {
+    fn default() -> Self {
+        Self {
+            max_tokens: None,
+            max_cost: None,
+            input_cost_per_1k: 0.0,
+            output_cost_per_1k: 0.0,
+            warning_threshold: 0.8,
+            wrap_up_note: DEFAULT_WRAP_UP_NOTE.to_string(),
+        }
+    }
+}
+
+impl ConversationBudget {
+    /// Create a new unbounded budget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the maximum cost and the token prices used to estimate it.
+    pub fn with_max_cost(mut self, max_cost: f64, input_cost_per_1k: f64, output_cost_per_1k: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self.input_cost_per_1k = input_cost_per_1k;
+        self.output_cost_per_1k = output_cost_per_1k;
+        self
+    }
+
+    /// Set the fraction of the budget at which to start warning.
+    pub fn with_warning_threshold(mut self, warning_threshold: f64) -> Self {
+        self.warning_threshold = warning_threshold;
+        self
+    }
+
+    /// Set the note injected for the model.
+    pub fn with_wrap_up_note(mut self, note: &str) -> Self {
+        self.wrap_up_note = note.to_string();
+        self
+    }
+
+    /// Add the usage reported by a model response to the running total.
+    pub fn record(&self, total: &mut BudgetUsage, usage: &ModelUsage) {
+        total.input_tokens += u64::from(usage.input_tokens);
+        total.output_tokens += u64::from(usage.output_tokens);
+        total.reasoning_tokens += u64::from(usage.reasoning_tokens);
+        total.reasoning_cost += f64::from(usage.reasoning_tokens) / 1000.0 * self.output_cost_per_1k;
+        total.cost += f64::from(usage.input_tokens) / 1000.0 * self.input_cost_per_1k
+            + f64::from(usage.output_tokens) / 1000.0 * self.output_cost_per_1k;
+    }
+
+    /// Get the fraction of the budget used, taking the larger of tokens and cost.
+    pub fn fraction_used(&self, usage: &BudgetUsage) -> f64 {
+        let tokens = self
+            .max_tokens
+            .map(|max| usage.total_tokens() as f64 / max.max(1) as f64)
+            .unwrap_or(0.0);
+        let cost = self
+            .max_cost
+            .filter(|max| *max > 0.0)
+            .map(|max| usage.cost / max)
+            .unwrap_or(0.0);
+        tokens.max(cost)
+    }
+
+    /// Get the budget status for the given usage.
+    pub fn status(&self, usage: &BudgetUsage) -> BudgetStatus {
+        let fraction = self.fraction_used(usage);
+        if fraction >= 1.0 {
+            BudgetStatus::Exhausted
+        } else if fraction >= self.warning_threshold {
+            BudgetStatus::Warning(fraction)
+        } else {
+            BudgetStatus::WithinBudget
+        }
+    }
+
+    /// Render the wrap-up note for the given usage.
+    pub fn wrap_up_note(&self, usage: &BudgetUsage) -> String {
+        let percent = (self.fraction_used(usage) * 100.0).round() as u64;
+        self.wrap_up_note.replace("{percent}", &percent.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input_tokens: u32, output_tokens: u32) -> ModelUsage {
+        ModelUsage {
+            input_tokens,
+            output_tokens,
+            total_tokens: input_tokens + output_tokens,
+            reasoning_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn test_budget_status_by_tokens() {
+        let budget = ConversationBudget::new().with_max_tokens(1000);
+        let mut total = BudgetUsage::default();
+
+        budget.record(&mut total, &usage(400, 100));
+        assert_eq!(budget.status(&total), BudgetStatus::WithinBudget);
+
+        budget.record(&mut total, &usage(300, 50));
+        assert!(matches!(budget.status(&total), BudgetStatus::Warning(f) if (f - 0.85).abs() < 1e-9));
+        assert!(budget.wrap_up_note(&total).contains("85%"));
+
+        budget.record(&mut total, &usage(150, 0));
+        assert_eq!(budget.status(&total), BudgetStatus::Exhausted);
+    }
+
+    #[test]
+    fn test_budget_status_by_cost() {
+        let budget = ConversationBudget::new().with_max_cost(0.10, 0.01, 0.03);
+        let mut total = BudgetUsage::default();
+
+        budget.record(&mut total, &usage(2000, 2500));
+        assert!((total.cost - 0.095).abs() < 1e-9);
+        assert!(matches!(budget.status(&total), BudgetStatus::Warning(_)));
+    }
+}
@@ -0,0 +1,207 @@
+//! Stop-and-ask clarification policy for the SDK.
+//! 
+//! This module provides `ClarificationPolicy`, which inspects each model
+//! response before its tool calls run. When the model asks for a
+//! high-impact tool, signals low confidence, or, when enabled, plans two
+//! different calls on the same target, the agent interrupts the run and
+//! returns a clarification question to the user instead of proceeding.
+
+use std::collections::HashSet;
+
+use crate::models::model::ModelResponse;
+use super::interrupt::{Interrupt, InterruptReason};
+
+/// Phrases that signal the model is unsure, matched case-insensitively.
+pub const DEFAULT_UNCERTAINTY_MARKERS: &[&str] = &[
+    "[unsure]",
+    "i'm unsure",
+    "i am unsure",
+    "i'm not sure",
+    "i am not sure",
+];
+
+/// Input fields naming what a tool call acts on, checked in order for conflicting plans.
+pub const DEFAULT_CONFLICT_TARGET_KEYS: &[&str] = &["path", "file_path", "file", "resource", "url", "key", "id"];
+
+/// A policy deciding when the agent should stop and ask the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClarificationPolicy {
+    /// Tools that always require confirmation before running.
+    pub high_impact_tools: HashSet<String>,
+    /// Lowercase phrases that mark a response as low confidence.
+    pub uncertainty_markers: Vec<String>,
+    /// Whether to ask when the same tool is planned twice on one target with different inputs.
+    pub ask_on_conflicting_plans: bool,
+    /// Input fields naming what a tool call acts on, such as a path or resource ID.
+    pub conflict_target_keys: Vec<String>,
+}
+
+impl Default for ClarificationPolicy {
+    fn default() -> Self {
+        Self {
+            high_impact_tools: HashSet::new(),
+            uncertainty_markers: DEFAULT_UNCERTAINTY_MARKERS.iter().map(|m| m.to_string()).collect(),
+            ask_on_conflicting_plans: false,
+            conflict_target_keys: DEFAULT_CONFLICT_TARGET_KEYS.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+}
+
+impl ClarificationPolicy {
+    /// Create a new policy with the default uncertainty markers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require confirmation before running the named tool.
+    pub fn with_high_impact_tool(mut self, name: &str) -> Self {
+        self.high_impact_tools.insert(name.to_string());
+        self
+    }
+
+    /// Add a phrase that marks a response as low confidence.
+    pub fn with_uncertainty_marker(mut self, marker: &str) -> Self {
+        self.uncertainty_markers.push(marker.to_lowercase());
+        self
+    }
+
+    /// Enable or disable asking on conflicting tool plans.
+    ///
+    /// Two calls conflict when they run the same tool on the same target,
+    /// such as one path, with different inputs. Calls without a target never conflict.
+    pub fn with_ask_on_conflicting_plans(mut self, enable: bool) -> Self {
+        self.ask_on_conflicting_plans = enable;
+        self
+    }
+
+    /// Add an input field naming what a tool call acts on.
+    pub fn with_conflict_target_key(mut self, key: &str) -> Self {
+        self.conflict_target_keys.push(key.to_string());
+        self
+    }
+
+    /// Decide whether a response with tool calls should interrupt the run.
+    pub fn evaluate(&self, response: &ModelResponse) -> Option<Interrupt> {
+        if response.tool_uses.is_empty() {
+            return None;
+        }
+
+        let content = response.content.to_lowercase();
+        if self.uncertainty_markers.iter().any(|marker| content.contains(marker.as_str())) {
+            let question = if response.content.trim().is_empty() {
+                "I'm not confident about how to proceed. Could you clarify what you would like me to do?".to_string()
+            } else {
+                format!(
+                    "{}\n\nBefore I continue, could you clarify what you would like me to do?",
+                    response.content.trim()
+                )
+            };
+            return Some(Interrupt::new(InterruptReason::LowConfidence, &question, response.tool_uses.clone()));
+        }
+
+        if self.ask_on_conflicting_plans {
+            for (index, tool_use) in response.tool_uses.iter().enumerate() {
+                let Some(target) = self.target(&tool_use.input) else {
+                    continue;
+                };
+                let conflict = response.tool_uses[index + 1..].iter().find(|other| {
+                    other.name == tool_use.name
+                        && other.input != tool_use.input
+                        && self.target(&other.input) == Some(target)
+                });
+                if let Some(other) = conflict {
+                    let question = format!(
+                        "I was about to run '{}' on {} in two different ways ({} and {}). Which one did you mean?",
+                        tool_use.name,
+                        target,
+                        describe_input(&tool_use.input),
+                        describe_input(&other.input)
+                    );
+                    return Some(Interrupt::new(
+                        InterruptReason::ConflictingToolPlans,
+                        &question,
+                        response.tool_uses.clone(),
+                    ));
+                }
+            }
+        }
+
+        let high_impact = response
+            .tool_uses
+            .iter()
+            .find(|tool_use| self.high_impact_tools.contains(&tool_use.name))?;
+        let question = format!(
+            "I'm about to run '{}' with {}. Should I go ahead?",
+            high_impact.name,
+            describe_input(&high_impact.input)
+        );
+        Some(Interrupt::new(InterruptReason::HighImpactTool, &question, response.tool_uses.clone()))
+    }
+
+    /// Get the target a tool call acts on, from the first target key in its input.
+    fn target<'a>(&self, input: &'a Option<serde_json::Value>) -> Option<&'a serde_json::Value> {
+        let input = input.as_ref()?;
+        self.conflict_target_keys.iter().find_map(|key| input.get(key))
+    }
+}
+
+fn describe_input(input: &Option<serde_json::Value>) -> String {
+    match input {
+        Some(value) => value.to_string(),
+        None => "no input".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolUse;
+    use serde_json::json;
+
+    fn call(name: &str, input: serde_json::Value) -> ToolUse {
+        ToolUse::new(name, &uuid::Uuid::new_v4().to_string()).with_input(input)
+    }
+
+    #[test]
+    fn test_high_impact_tool_interrupts() {
+        let policy = ClarificationPolicy::new().with_high_impact_tool("delete_file");
+
+        let safe = ModelResponse::new("").with_tool_use(call("read_file", json!({"path": "a"})));
+        assert!(policy.evaluate(&safe).is_none());
+
+        let risky = ModelResponse::new("").with_tool_use(call("delete_file", json!({"path": "a"})));
+        let interrupt = policy.evaluate(&risky).unwrap();
+        assert_eq!(interrupt.reason, InterruptReason::HighImpactTool);
+        assert!(interrupt.question.contains("delete_file"));
+    }
+
+    #[test]
+    fn test_low_confidence_interrupts() {
+        let policy = ClarificationPolicy::new();
+        let response = ModelResponse::new("I'm not sure which account you mean.")
+            .with_tool_use(call("lookup", json!({})));
+
+        let interrupt = policy.evaluate(&response).unwrap();
+        assert_eq!(interrupt.reason, InterruptReason::LowConfidence);
+        assert!(interrupt.question.starts_with("I'm not sure which account"));
+    }
+
+    #[test]
+    fn test_conflicting_plans_interrupt() {
+        let policy = ClarificationPolicy::new().with_ask_on_conflicting_plans(true);
+        let batch = ModelResponse::new("")
+            .with_tool_use(call("write_file", json!({"path": "a.txt", "content": "one"})))
+            .with_tool_use(call("write_file", json!({"path": "b.txt", "content": "two"})))
+            .with_tool_use(call("send_email", json!({"to": "a@example.com"})))
+            .with_tool_use(call("send_email", json!({"to": "b@example.com"})));
+        assert!(policy.evaluate(&batch).is_none());
+
+        let response = ModelResponse::new("")
+            .with_tool_use(call("write_file", json!({"path": "a.txt", "content": "one"})))
+            .with_tool_use(call("write_file", json!({"path": "a.txt", "content": "two"})));
+        let interrupt = policy.evaluate(&response).unwrap();
+        assert_eq!(interrupt.reason, InterruptReason::ConflictingToolPlans);
+        assert_eq!(interrupt.tool_uses.len(), 2);
+        assert!(ClarificationPolicy::new().evaluate(&response).is_none());
+    }
+}
@@ -0,0 +1,342 @@
+//! Context compression for the SDK.
+//! 
+//! This module provides `ContextCompressor`, an optional stage that shrinks
+//! long contexts before they are sent to the model. In the spirit of
+//! LLMLingua, it scores each sentence of retrieved documents and old turns
+//! by the self-information of its words, estimated from word frequencies
+//! across the context, and drops the least informative sentences until the
+//! configured share of tokens remains. Filler words are then dropped from
+//! text still over its target. Recent turns and system messages are never
+//! compressed.
+//! 
+//! Compression applies only to the request; the stored history keeps the
+//! original messages.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::models::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::types::{MessageRole, Messages};
+
+/// Words carrying little information, dropped first when compressing within sentences.
+const FILLER_WORDS: &[&str] = &[
+    "a", "an", "the", "and", "or", "but", "of", "to", "in", "on", "at", "by", "for", "with", "as", "is", "are",
+    "was", "were", "be", "been", "being", "that", "this", "these", "those", "it", "its", "very", "really", "just",
+    "quite", "also", "so", "then", "there", "which", "who", "whom", "has", "have", "had", "do", "does", "did",
+];
+
+/// Configuration for context compression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionConfig {
+    /// The share of tokens to keep in compressed text, between 0 and 1.
+    pub ratio: f64,
+    /// The number of most recent messages left uncompressed.
+    pub keep_recent_messages: usize,
+    /// The minimum number of tokens a text needs before it is compressed.
+    pub min_tokens: usize,
+    /// Whether tool results, such as retrieved documents, are compressed.
+    pub compress_tool_results: bool,
+    /// Whether the text of old turns is compressed.
+    pub compress_old_turns: bool,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            ratio: 0.5,
+            keep_recent_messages: 4,
+            min_tokens: 64,
+            compress_tool_results: true,
+            compress_old_turns: true,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Create the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the share of tokens to keep, clamped between 0 and 1.
+    pub fn with_ratio(mut self, ratio: f64) -> Self {
+        self.ratio = ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the number of most recent messages left uncompressed.
+    pub fn with_keep_recent_messages(mut self, keep_recent_messages: usize) -> Self {
+        self.keep_recent_messages = keep_recent_messages;
+        self
+    }
+
+    /// Set the minimum number of tokens a text needs before it is compressed.
+    pub fn with_min_tokens(mut self, min_tokens: usize) -> Self {
+        self.min_tokens = min_tokens;
+        self
+    }
+
+    /// Set whether tool results are compressed.
+    pub fn with_compress_tool_results(mut self, compress_tool_results: bool) -> Self {
+        self.compress_tool_results = compress_tool_results;
+        self
+    }
+
+    /// Set whether the text of old turns is compressed.
+    pub fn with_compress_old_turns(mut self, compress_old_turns: bool) -> Self {
+        self.compress_old_turns = compress_old_turns;
+        self
+    }
+}
+
+/// The effect of compressing a context.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// The tokens in the compressible text before compression.
+    pub original_tokens: usize,
+    /// The tokens in the compressible text after compression.
+    pub compressed_tokens: usize,
+}
+
+impl CompressionStats {
+    /// Get the number of tokens removed.
+    pub fn tokens_saved(&self) -> usize {
+        self.original_tokens.saturating_sub(self.compressed_tokens)
+    }
+
+    /// Get the share of tokens kept, or 1 when nothing was compressible.
+    pub fn ratio(&self) -> f64 {
+        if self.original_tokens == 0 {
+            return 1.0;
+        }
+        self.compressed_tokens as f64 / self.original_tokens as f64
+    }
+}
+
+/// Word frequencies across the compressible text of a context.
+struct WordStats {
+    counts: HashMap<String, usize>,
+    total: usize,
+}
+
+impl WordStats {
+    fn new<'a>(texts: impl Iterator<Item = &'a str>) -> Self {
+        let mut counts = HashMap::new();
+        let mut total = 0;
+        for word in texts.flat_map(words) {
+            *counts.entry(word).or_insert(0) += 1;
+            total += 1;
+        }
+        Self { counts, total }
+    }
+
+    /// Get the self-information of a word in bits, treating filler words as uninformative.
+    fn information(&self, word: &str, filler: &HashSet<&str>) -> f64 {
+        if filler.contains(word) {
+            return 0.0;
+        }
+        let count = self.counts.get(word).copied().unwrap_or(0);
+        ((self.total + self.counts.len()) as f64 / (count + 1) as f64).log2()
+    }
+}
+
+/// Split text into lowercase words.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Split text into sentences, keeping their terminators.
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let boundary = c == '\n'
+            || (matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|(_, next)| next.is_whitespace()));
+        if boundary {
+            let end = index + c.len_utf8();
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+/// A stage dropping low-information sentences and words from long contexts.
+#[derive(Clone)]
+pub struct ContextCompressor {
+    config: CompressionConfig,
+    tokenizer: Arc<dyn Tokenizer>,
+}
+
+impl std::fmt::Debug for ContextCompressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextCompressor")
+            .field("config", &self.config)
+            .field("tokenizer", &self.tokenizer.name())
+            .finish()
+    }
+}
+
+impl ContextCompressor {
+    /// Create a compressor counting tokens with the heuristic tokenizer.
+    pub fn new(config: CompressionConfig) -> Self {
+        Self {
+            config,
+            tokenizer: Arc::new(HeuristicTokenizer::default()),
+        }
+    }
+
+    /// Set the tokenizer used to count tokens.
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Get the compressor's configuration.
+    pub fn config(&self) -> &CompressionConfig {
+        &self.config
+    }
+
+    /// Compress the retrieved documents and old turns of a context.
+    pub fn compress(&self, messages: &Messages) -> (Messages, CompressionStats) {
+        let recent_start = messages.len().saturating_sub(self.config.keep_recent_messages);
+        let mut compressed = messages.clone();
+
+        // Collect the compressible text as (message, block, tool result content) positions
+        let mut targets = Vec::new();
+        for (m, message) in compressed.iter().enumerate() {
+            if message.role == MessageRole::System {
+                continue;
+            }
+            for (b, block) in message.content.iter().enumerate() {
+                if self.config.compress_tool_results {
+                    if let Some(ref result) = block.tool_result {
+                        targets.extend((0..result.content.len()).map(|c| (m, b, Some(c))));
+                    }
+                }
+                if self.config.compress_old_turns && m < recent_start && block.text.is_some() {
+                    targets.push((m, b, None));
+                }
+            }
+        }
+
+        let text_at = |messages: &Messages, (m, b, c): (usize, usize, Option<usize>)| -> Option<String> {
+            let block = &messages[m].content[b];
+            match c {
+                Some(c) => block.tool_result.as_ref()?.content[c].text.clone(),
+                None => block.text.clone(),
+            }
+        };
+        let texts: Vec<(usize, String)> = targets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, target)| text_at(&compressed, *target).map(|text| (i, text)))
+            .filter(|(_, text)| self.tokenizer.count_tokens(text) >= self.config.min_tokens)
+            .collect();
+        let stats = WordStats::new(texts.iter().map(|(_, text)| text.as_str()));
+
+        let mut totals = CompressionStats::default();
+        for (i, text) in texts {
+            let shorter = self.compress_text(&text, &stats);
+            totals.original_tokens += self.tokenizer.count_tokens(&text);
+            totals.compressed_tokens += self.tokenizer.count_tokens(&shorter);
+            let (m, b, c) = targets[i];
+            let block = &mut compressed[m].content[b];
+            match c {
+                Some(c) => {
+                    if let Some(ref mut result) = block.tool_result {
+                        result.content[c].text = Some(shorter);
+                    }
+                }
+                None => block.text = Some(shorter),
+            }
+        }
+        (compressed, totals)
+    }
+
+    /// Keep the most informative sentences of a text, then drop filler words if still over target.
+    fn compress_text(&self, text: &str, stats: &WordStats) -> String {
+        let filler: HashSet<&str> = FILLER_WORDS.iter().copied().collect();
+        let target = (self.tokenizer.count_tokens(text) as f64 * self.config.ratio).ceil() as usize;
+        let sentences = sentences(text);
+        let score = |sentence: &str| {
+            let infos: Vec<f64> = words(sentence).map(|word| stats.information(&word, &filler)).collect();
+            if infos.is_empty() { 0.0 } else { infos.iter().sum::<f64>() / infos.len() as f64 }
+        };
+
+        let mut ranked: Vec<(f64, usize)> = sentences.iter().enumerate().map(|(i, sentence)| (score(sentence), i)).collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        let mut keep = vec![false; sentences.len()];
+        let mut used = 0;
+        for (_, i) in ranked {
+            let tokens = self.tokenizer.count_tokens(sentences[i]);
+            if used == 0 || used + tokens <= target {
+                keep[i] = true;
+                used += tokens;
+            }
+        }
+        let kept = sentences
+            .iter()
+            .zip(keep)
+            .filter_map(|(sentence, keep)| keep.then_some(*sentence))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if used <= target {
+            return kept;
+        }
+        kept.split_whitespace()
+            .filter(|word| {
+                let bare = word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+                !filler.contains(bare.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Message, ToolResult, ToolResultContent};
+
+    #[test]
+    fn test_compressor_drops_repetitive_sentences() {
+        let document = [
+            "The report is ready.",
+            "The report is ready.",
+            "The report is ready.",
+            "Quarterly revenue rose 14 percent to 2.3 billion dollars in Norway.",
+            "The report is ready.",
+        ]
+        .join(" ");
+        let messages = vec![
+            Message::user(&document),
+            Message::tool_results(vec![ToolResult::new("t1", vec![ToolResultContent::text(&document)])]),
+            Message::user(&document),
+        ];
+        let compressor = ContextCompressor::new(
+            CompressionConfig::new().with_ratio(0.5).with_keep_recent_messages(1).with_min_tokens(10),
+        );
+
+        let (compressed, stats) = compressor.compress(&messages);
+
+        let old_turn = compressed[0].text().unwrap();
+        assert!(old_turn.contains("Quarterly revenue rose 14 percent"));
+        assert!(old_turn.len() < document.len());
+        let result = compressed[1].tool_result_blocks()[0].content[0].text.clone().unwrap();
+        assert!(result.contains("Norway"));
+        assert_eq!(compressed[2].text().unwrap(), document);
+        assert!(stats.tokens_saved() > 0);
+        assert!(stats.ratio() <= 0.6);
+    }
+}
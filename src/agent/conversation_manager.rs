@@ -3,8 +3,12 @@
 //! This module provides functionality for managing conversation
 //! context, history, and memory for agents.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 
+use super::transcript::validate_role_order;
+use crate::models::tokenizer::Tokenizer;
 use crate::types::{Messages, Message, IndubitablyResult};
 
 /// Configuration for conversation managers.
@@ -70,11 +74,39 @@ pub trait ConversationManager: Send + Sync {
     /// Clear the conversation history.
     async fn clear(&mut self) -> IndubitablyResult<()>;
     
+    /// Keep the first `len` messages of the context and drop the rest, such as to rewind a turn.
+    ///
+    /// The default rebuilds the conversation from the kept messages, so
+    /// managers that can drop messages in place should override it.
+    async fn truncate(&mut self, len: usize) -> IndubitablyResult<()> {
+        let messages = self.get_context().await?;
+        if len >= messages.len() {
+            return Ok(());
+        }
+        self.clear().await?;
+        for message in messages.into_iter().take(len) {
+            self.add_message(message).await?;
+        }
+        Ok(())
+    }
+    
     /// Clear the conversation history (alias for clear).
     async fn clear_history(&mut self) -> IndubitablyResult<()> {
         self.clear().await
     }
     
+    /// Replace the conversation with messages from elsewhere, such as an imported transcript.
+    ///
+    /// Fails without changing the conversation if the messages are out of role order.
+    async fn seed_from(&mut self, messages: Messages) -> IndubitablyResult<()> {
+        validate_role_order(&messages)?;
+        self.clear().await?;
+        for message in messages {
+            self.add_message(message).await?;
+        }
+        Ok(())
+    }
+    
     /// Get the number of messages in the conversation.
     async fn message_count(&self) -> IndubitablyResult<usize>;
     
@@ -123,10 +155,22 @@ impl Default for NullConversationManager {
     }
 }
 
+/// Drop the oldest messages until the rest fit the token limit, keeping at least one.
+fn trim_to_token_limit(messages: &mut Messages, limit: &Option<(usize, Arc<dyn Tokenizer>)>) {
+    let Some((max_tokens, tokenizer)) = limit else {
+        return;
+    };
+    while messages.len() > 1 && tokenizer.count_message_tokens(messages) > *max_tokens {
+        messages.remove(0);
+    }
+}
+
 /// A conversation manager that maintains a sliding window of messages.
 pub struct SlidingWindowConversationManager {
     /// The maximum number of messages to keep.
     max_messages: usize,
+    /// The maximum number of tokens to keep and the tokenizer counting them.
+    token_limit: Option<(usize, Arc<dyn Tokenizer>)>,
     /// The messages in the conversation.
     messages: Messages,
 }
@@ -136,6 +180,7 @@ impl SlidingWindowConversationManager {
     pub fn new(max_messages: usize) -> Self {
         Self {
             max_messages,
+            token_limit: None,
             messages: Vec::new(),
         }
     }
@@ -151,6 +196,12 @@ impl SlidingWindowConversationManager {
         self
     }
     
+    /// Also drop the oldest messages once the window exceeds a number of tokens.
+    pub fn with_token_limit(mut self, max_tokens: usize, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.token_limit = Some((max_tokens, tokenizer));
+        self
+    }
+    
     /// Get the maximum number of messages.
     pub fn max_messages(&self) -> usize {
         self.max_messages
@@ -170,6 +221,7 @@ impl ConversationManager for SlidingWindowConversationManager {
         if self.messages.len() > self.max_messages {
             self.messages.remove(0);
         }
+        trim_to_token_limit(&mut self.messages, &self.token_limit);
         
         Ok(())
     }
@@ -179,6 +231,11 @@ impl ConversationManager for SlidingWindowConversationManager {
         Ok(())
     }
     
+    async fn truncate(&mut self, len: usize) -> IndubitablyResult<()> {
+        self.messages.truncate(len);
+        Ok(())
+    }
+    
     async fn message_count(&self) -> IndubitablyResult<usize> {
         Ok(self.messages.len())
     }
@@ -198,6 +255,8 @@ impl Default for SlidingWindowConversationManager {
 pub struct SummarizingConversationManager {
     /// The maximum number of recent messages to keep.
     max_recent_messages: usize,
+    /// The maximum number of recent tokens to keep and the tokenizer counting them.
+    token_limit: Option<(usize, Arc<dyn Tokenizer>)>,
     /// The recent messages to keep in full.
     recent_messages: Messages,
     /// A summary of older messages.
@@ -211,6 +270,7 @@ impl SummarizingConversationManager {
     pub fn new(max_recent_messages: usize) -> Self {
         Self {
             max_recent_messages,
+            token_limit: None,
             recent_messages: Vec::new(),
             summary: None,
             summary_model: None,
@@ -228,6 +288,12 @@ impl SummarizingConversationManager {
         self
     }
     
+    /// Also drop the oldest recent messages once they exceed a number of tokens.
+    pub fn with_token_limit(mut self, max_tokens: usize, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.token_limit = Some((max_tokens, tokenizer));
+        self
+    }
+    
     /// Set the model to use for summarization.
     pub fn with_summary_model(mut self, model: &str) -> Self {
         self.summary_model = Some(model.to_string());
@@ -261,6 +327,7 @@ impl ConversationManager for SummarizingConversationManager {
             // In a full implementation, this would trigger summarization
             self.recent_messages.remove(0);
         }
+        trim_to_token_limit(&mut self.recent_messages, &self.token_limit);
         
         Ok(())
     }
@@ -271,6 +338,16 @@ impl ConversationManager for SummarizingConversationManager {
         Ok(())
     }
     
+    async fn truncate(&mut self, len: usize) -> IndubitablyResult<()> {
+        // The summary comes first in the context
+        match (self.summary.is_some(), len) {
+            (true, 0) => self.clear().await?,
+            (true, len) => self.recent_messages.truncate(len - 1),
+            (false, len) => self.recent_messages.truncate(len),
+        }
+        Ok(())
+    }
+    
     async fn message_count(&self) -> IndubitablyResult<usize> {
         let summary_count = if self.summary.is_some() { 1 } else { 0 };
         Ok(self.recent_messages.len() + summary_count)
@@ -307,6 +384,49 @@ mod tests {
         assert!(manager.is_empty().await.unwrap());
     }
 
+    /// A manager implementing only the required methods.
+    struct ListConversationManager(Messages);
+
+    #[async_trait]
+    impl ConversationManager for ListConversationManager {
+        async fn get_context(&self) -> IndubitablyResult<Messages> {
+            Ok(self.0.clone())
+        }
+
+        async fn add_message(&mut self, message: Message) -> IndubitablyResult<()> {
+            self.0.push(message);
+            Ok(())
+        }
+
+        async fn clear(&mut self) -> IndubitablyResult<()> {
+            self.0.clear();
+            Ok(())
+        }
+
+        async fn message_count(&self) -> IndubitablyResult<usize> {
+            Ok(self.0.len())
+        }
+
+        async fn is_empty(&self) -> IndubitablyResult<bool> {
+            Ok(self.0.is_empty())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_truncate() {
+        let mut manager = ListConversationManager(vec![
+            Message::user("Hello"),
+            Message::assistant("Hi!"),
+            Message::user("How are you?"),
+        ]);
+
+        manager.truncate(5).await.unwrap();
+        assert_eq!(manager.message_count().await.unwrap(), 3);
+        manager.truncate(1).await.unwrap();
+        assert_eq!(manager.message_count().await.unwrap(), 1);
+        assert_eq!(manager.get_context().await.unwrap()[0].text(), Some("Hello"));
+    }
+
     #[tokio::test]
     async fn test_sliding_window_conversation_manager() {
         let mut manager = SlidingWindowConversationManager::new(3);
@@ -327,6 +447,11 @@ mod tests {
         
         assert_eq!(manager.message_count().await.unwrap(), 3);
         
+        // Truncate to the first message of the window
+        manager.truncate(1).await.unwrap();
+        assert_eq!(manager.get_context().await.unwrap()[0].text(), Some("Hi!"));
+        assert_eq!(manager.message_count().await.unwrap(), 1);
+        
         // Clear conversation
         manager.clear().await.unwrap();
         assert_eq!(manager.message_count().await.unwrap(), 0);
@@ -347,6 +472,12 @@ mod tests {
         assert_eq!(manager.message_count().await.unwrap(), 2);
         assert!(!manager.is_empty().await.unwrap());
         
+        // The summary counts as the first message of the context
+        manager.summary = Some("Greetings".to_string());
+        manager.truncate(2).await.unwrap();
+        assert_eq!(manager.message_count().await.unwrap(), 2);
+        assert_eq!(manager.get_context().await.unwrap()[1].text(), Some("Hello"));
+        
         // Clear conversation
         manager.clear().await.unwrap();
         assert_eq!(manager.message_count().await.unwrap(), 0);
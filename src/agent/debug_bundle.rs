@@ -0,0 +1,179 @@
+//! Debug bundles for bug reports.
+//! 
+//! This module provides `DebugBundle`, a self-contained snapshot of an
+//! agent produced by `Agent::export_debug_bundle`: the conversation
+//! transcript, the configuration, the tool specs, the most recent lifecycle
+//! events and the SDK version. Secrets are removed before anything is
+//! written. Fields named like credentials are masked whole, and every string
+//! is passed through the agent's `SecretRedactor` with the default patterns.
+//! The bundle is a ZIP archive of one JSON file per section, so it can be
+//! attached to an issue as is.
+
+use serde_json::{json, Value};
+
+use super::redaction::SecretRedactor;
+use crate::docs::zip::{ZipArchive, ZipWriter};
+use crate::types::{DocumentError, IndubitablyError, IndubitablyResult};
+
+/// The number of recent lifecycle events an agent keeps for debug bundles.
+pub const DEFAULT_DEBUG_EVENT_CAPACITY: usize = 200;
+
+/// The mask replacing fields named like credentials.
+pub const REDACTED_FIELD: &str = "[REDACTED]";
+
+/// Field name fragments marking a field as a credential.
+const SECRET_FIELD_MARKERS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "api-key",
+    "secret",
+    "password",
+    "authorization",
+    "credential",
+    "private_key",
+];
+
+/// Check whether a field name marks a credential.
+fn is_secret_field(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "token" || name.ends_with("_token") || SECRET_FIELD_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// Remove secrets from a JSON value.
+///
+/// Fields named like credentials are replaced with `REDACTED_FIELD`, and
+/// secrets the redactor finds in the remaining strings are masked.
+pub fn scrub(mut value: Value, redactor: &SecretRedactor) -> Value {
+    fn mask_fields(value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (name, field) in fields.iter_mut() {
+                    // Nulls and flags, such as whether a secret redactor is set, hold no secret
+                    if is_secret_field(name) && !field.is_null() && !field.is_boolean() {
+                        *field = Value::String(REDACTED_FIELD.to_string());
+                    } else {
+                        mask_fields(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(mask_fields),
+            _ => {}
+        }
+    }
+    mask_fields(&mut value);
+    redactor.redact_value(&mut value);
+    value
+}
+
+/// Describe the SDK build and platform.
+pub fn version_info() -> Value {
+    json!({
+        "sdk": env!("CARGO_PKG_NAME"),
+        "version": crate::VERSION,
+        "features": {
+            "hf-tokenizers": cfg!(feature = "hf-tokenizers"),
+            "ocr": cfg!(feature = "ocr"),
+            "wasmtime-cli": cfg!(feature = "wasmtime-cli"),
+        },
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "created_at": chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// A snapshot of an agent for bug reports, with secrets removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugBundle {
+    /// The SDK version and platform, from `version_info`.
+    pub version: Value,
+    /// The conversation messages.
+    pub transcript: Value,
+    /// The agent and model configuration.
+    pub config: Value,
+    /// The tool specs offered to the model.
+    pub tool_specs: Value,
+    /// The most recent lifecycle events, oldest first.
+    pub events: Value,
+}
+
+impl DebugBundle {
+    /// The archive entry of each section, in order.
+    const ENTRIES: [&'static str; 5] = ["version.json", "transcript.json", "config.json", "tools.json", "events.json"];
+
+    fn sections(&self) -> [&Value; 5] {
+        [&self.version, &self.transcript, &self.config, &self.tool_specs, &self.events]
+    }
+
+    /// Write the bundle as a ZIP archive with one pretty-printed JSON file per section.
+    pub fn to_zip(&self) -> IndubitablyResult<Vec<u8>> {
+        let mut writer = ZipWriter::new();
+        for (name, section) in Self::ENTRIES.iter().zip(self.sections()) {
+            writer.add(name, serde_json::to_string_pretty(section)?.as_bytes())?;
+        }
+        Ok(writer.finish())
+    }
+
+    /// Read a bundle written by `to_zip`.
+    pub fn from_zip(data: &[u8]) -> IndubitablyResult<Self> {
+        let archive = ZipArchive::new(data)?;
+        let section = |name: &str| -> IndubitablyResult<Value> {
+            let text = archive.read_string(name)?.ok_or_else(|| {
+                IndubitablyError::DocumentError(DocumentError::Malformed(format!(
+                    "Debug bundle has no '{}' entry",
+                    name
+                )))
+            })?;
+            Ok(serde_json::from_str(&text)?)
+        };
+        let [version, transcript, config, tool_specs, events] = Self::ENTRIES;
+        Ok(Self {
+            version: section(version)?,
+            transcript: section(transcript)?,
+            config: section(config)?,
+            tool_specs: section(tool_specs)?,
+            events: section(events)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub_and_zip_round_trip() {
+        let redactor = SecretRedactor::new().with_default_patterns();
+        let config = scrub(
+            json!({
+                "max_tokens": 1024,
+                "refresh_token": "opaque",
+                "headers": [{ "Authorization": "Bearer abc" }],
+                "extra": { "api_key": null, "note": "key sk-proj-abcdefghijklmnopqrstuvwx" },
+            }),
+            &redactor,
+        );
+        assert_eq!(config["max_tokens"], 1024);
+        assert_eq!(config["refresh_token"], REDACTED_FIELD);
+        assert_eq!(config["headers"][0]["Authorization"], REDACTED_FIELD);
+        assert!(config["extra"]["api_key"].is_null());
+        assert_eq!(config["extra"]["note"], "key [REDACTED:api_key]");
+
+        let bundle = DebugBundle {
+            version: version_info(),
+            transcript: json!([]),
+            config,
+            tool_specs: json!([{ "name": "search" }]),
+            events: json!([]),
+        };
+        let archive = bundle.to_zip().unwrap();
+        let entries: Vec<String> = ZipArchive::new(&archive)
+            .unwrap()
+            .entries()
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect();
+        assert_eq!(entries, DebugBundle::ENTRIES);
+        assert_eq!(DebugBundle::from_zip(&archive).unwrap(), bundle);
+        assert_eq!(bundle.version["version"], crate::VERSION);
+    }
+}
@@ -0,0 +1,318 @@
+//! Degraded mode handling for the SDK.
+//! 
+//! This module provides the `DegradedModeHandler` trait, invoked when the
+//! model cannot be reached, and built-in handlers that answer with a canned
+//! message, queue the request for later processing, or answer from a set of
+//! FAQ entries without calling the model.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{IndubitablyError, IndubitablyResult, Messages};
+
+/// The kind of response produced in degraded mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradedResponseKind {
+    /// A fixed apology message.
+    Canned,
+    /// The request was queued for later processing.
+    Queued,
+    /// The answer came from a FAQ or memory search.
+    Faq,
+}
+
+impl DegradedResponseKind {
+    /// Get the kind as a string, as used in metric names.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DegradedResponseKind::Canned => "canned",
+            DegradedResponseKind::Queued => "queued",
+            DegradedResponseKind::Faq => "faq",
+        }
+    }
+}
+
+/// A response produced without the model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegradedResponse {
+    /// The text returned to the user.
+    pub text: String,
+    /// How the response was produced.
+    pub kind: DegradedResponseKind,
+}
+
+impl DegradedResponse {
+    /// Create a new degraded response.
+    pub fn new(text: impl Into<String>, kind: DegradedResponseKind) -> Self {
+        Self {
+            text: text.into(),
+            kind,
+        }
+    }
+}
+
+/// A handler invoked when all model attempts for a request have failed.
+#[async_trait]
+pub trait DegradedModeHandler: Send + Sync {
+    /// Produce a response for the user message without the model.
+    async fn handle(
+        &self,
+        message: &str,
+        history: &Messages,
+        error: &IndubitablyError,
+    ) -> IndubitablyResult<DegradedResponse>;
+}
+
+/// The default apology returned in degraded mode.
+pub const DEFAULT_DEGRADED_MESSAGE: &str =
+    "I'm sorry, I'm unable to answer right now. Please try again in a few minutes.";
+
+/// A handler that always returns the same message.
+#[derive(Debug, Clone)]
+pub struct CannedResponseHandler {
+    message: String,
+}
+
+impl CannedResponseHandler {
+    /// Create a new canned response handler.
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Default for CannedResponseHandler {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEGRADED_MESSAGE)
+    }
+}
+
+#[async_trait]
+impl DegradedModeHandler for CannedResponseHandler {
+    async fn handle(
+        &self,
+        _message: &str,
+        _history: &Messages,
+        _error: &IndubitablyError,
+    ) -> IndubitablyResult<DegradedResponse> {
+        Ok(DegradedResponse::new(self.message.clone(), DegradedResponseKind::Canned))
+    }
+}
+
+/// A request queued while the model was unavailable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedRequest {
+    /// The unique identifier of the queued request.
+    pub id: String,
+    /// The user message.
+    pub message: String,
+    /// The conversation history at the time of the request.
+    pub history: Messages,
+    /// The error that caused the request to be queued.
+    pub error: String,
+    /// When the request was queued.
+    pub queued_at: DateTime<Utc>,
+}
+
+/// A handler that queues requests for later processing.
+#[derive(Debug, Clone)]
+pub struct QueueingHandler {
+    queue: Arc<Mutex<VecDeque<QueuedRequest>>>,
+    message: String,
+}
+
+impl QueueingHandler {
+    /// Create a new queueing handler.
+    pub fn new() -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            message: "I'm unable to answer right now. Your request has been queued and will be answered as soon as possible.".to_string(),
+        }
+    }
+
+    /// Set the message returned when a request is queued.
+    pub fn with_message(mut self, message: &str) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    /// Get the number of queued requests.
+    pub fn len(&self) -> usize {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove and return all queued requests, oldest first.
+    pub fn drain(&self) -> Vec<QueuedRequest> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).drain(..).collect()
+    }
+}
+
+impl Default for QueueingHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl DegradedModeHandler for QueueingHandler {
+    async fn handle(
+        &self,
+        message: &str,
+        history: &Messages,
+        error: &IndubitablyError,
+    ) -> IndubitablyResult<DegradedResponse> {
+        let request = QueuedRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            message: message.to_string(),
+            history: history.clone(),
+            error: error.to_string(),
+            queued_at: Utc::now(),
+        };
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).push_back(request);
+        Ok(DegradedResponse::new(self.message.clone(), DegradedResponseKind::Queued))
+    }
+}
+
+/// A question and answer pair used in degraded mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaqEntry {
+    /// The question.
+    pub question: String,
+    /// The answer.
+    pub answer: String,
+}
+
+/// A handler that answers from FAQ or memory entries by keyword overlap.
+#[derive(Debug, Clone)]
+pub struct FaqHandler {
+    entries: Vec<FaqEntry>,
+    min_score: f64,
+    fallback: String,
+}
+
+impl FaqHandler {
+    /// Create a new FAQ handler with no entries.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            min_score: 0.3,
+            fallback: DEFAULT_DEGRADED_MESSAGE.to_string(),
+        }
+    }
+
+    /// Add an entry.
+    pub fn with_entry(mut self, question: &str, answer: &str) -> Self {
+        self.entries.push(FaqEntry {
+            question: question.to_string(),
+            answer: answer.to_string(),
+        });
+        self
+    }
+
+    /// Set the minimum keyword overlap, between 0 and 1, for an entry to match.
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Set the message returned when no entry matches.
+    pub fn with_fallback(mut self, fallback: &str) -> Self {
+        self.fallback = fallback.to_string();
+        self
+    }
+
+    /// Find the best matching entry for a message.
+    pub fn search(&self, message: &str) -> Option<&FaqEntry> {
+        let query = keywords(message);
+        if query.is_empty() {
+            return None;
+        }
+
+        self.entries
+            .iter()
+            .map(|entry| {
+                let question = keywords(&entry.question);
+                let overlap = query.intersection(&question).count() as f64;
+                (entry, overlap / query.union(&question).count() as f64)
+            })
+            .filter(|(_, score)| *score >= self.min_score)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entry, _)| entry)
+    }
+}
+
+impl Default for FaqHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split text into lowercase keywords, ignoring very short words.
+fn keywords(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[async_trait]
+impl DegradedModeHandler for FaqHandler {
+    async fn handle(
+        &self,
+        message: &str,
+        _history: &Messages,
+        _error: &IndubitablyError,
+    ) -> IndubitablyResult<DegradedResponse> {
+        Ok(match self.search(message) {
+            Some(entry) => DegradedResponse::new(entry.answer.clone(), DegradedResponseKind::Faq),
+            None => DegradedResponse::new(self.fallback.clone(), DegradedResponseKind::Canned),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_down() -> IndubitablyError {
+        IndubitablyError::NetworkError("connection refused".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_faq_handler_matches_closest_entry() {
+        let handler = FaqHandler::new()
+            .with_entry("What are your opening hours?", "We are open 9am to 5pm.")
+            .with_entry("How do I reset my password?", "Use the 'Forgot password' link.");
+
+        let response = handler.handle("how can I reset my password", &Vec::new(), &model_down()).await.unwrap();
+        assert_eq!(response.kind, DegradedResponseKind::Faq);
+        assert_eq!(response.text, "Use the 'Forgot password' link.");
+
+        let response = handler.handle("tell me a joke", &Vec::new(), &model_down()).await.unwrap();
+        assert_eq!(response.kind, DegradedResponseKind::Canned);
+    }
+
+    #[tokio::test]
+    async fn test_queueing_handler() {
+        let handler = QueueingHandler::new();
+        let response = handler.handle("Summarize my inbox", &Vec::new(), &model_down()).await.unwrap();
+
+        assert_eq!(response.kind, DegradedResponseKind::Queued);
+        assert_eq!(handler.len(), 1);
+
+        let queued = handler.drain();
+        assert_eq!(queued[0].message, "Summarize my inbox");
+        assert!(queued[0].error.contains("connection refused"));
+        assert!(handler.is_empty());
+    }
+}
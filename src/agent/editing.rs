@@ -0,0 +1,94 @@
+//! Message editing and regeneration for the SDK.
+//! 
+//! This module provides the types used by `Agent::regenerate_last` and
+//! `Agent::edit_user_message`, which rewind the conversation to a user turn
+//! and produce a new assistant turn from there.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ConversationDiff, Message, Messages, MessagesDiff};
+
+/// Options for editing or regenerating a turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EditOptions {
+    /// Whether to keep the conversation as it was before the edit as a fork.
+    pub fork: bool,
+}
+
+impl EditOptions {
+    /// Create new edit options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the conversation as it was before the edit as a fork.
+    pub fn with_fork(mut self, fork: bool) -> Self {
+        self.fork = fork;
+        self
+    }
+}
+
+/// A copy of the conversation taken before it was rewound.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationFork {
+    /// The unique identifier of the fork.
+    pub id: String,
+    /// The messages of the conversation at the time of the fork.
+    pub messages: Messages,
+    /// When the fork was taken.
+    pub created_at: DateTime<Utc>,
+}
+
+impl ConversationFork {
+    /// Create a new fork of the given messages.
+    pub fn new(messages: Messages) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            messages,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Compute the changes from this fork to another version of the conversation.
+    pub fn diff(&self, current: &[Message]) -> ConversationDiff {
+        self.messages.diff(current)
+    }
+}
+
+/// Find the index of the last user turn in a conversation.
+///
+/// Messages carrying tool results are skipped, so rewinding to the returned
+/// index never separates a tool call from its result.
+pub fn last_user_turn(messages: &Messages) -> Option<usize> {
+    messages.iter().rposition(|message| message.is_user_turn())
+}
+
+/// Find the index of the user turn with the given message identifier.
+pub fn find_user_turn(messages: &Messages, message_id: &str) -> Option<usize> {
+    messages
+        .iter()
+        .position(|message| message.is_user_turn() && message.id() == Some(message_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Message, ToolResult, ToolUse};
+
+    #[test]
+    fn test_user_turns_skip_tool_results() {
+        let messages = vec![
+            Message::user("first").with_id("m1"),
+            Message::assistant("ok"),
+            Message::user("second").with_id("m2"),
+            Message::assistant_with_tool_uses("", vec![ToolUse::new("lookup", "call-1")]),
+            Message::tool_results(vec![ToolResult::error("call-1", "failed")]),
+            Message::assistant("done"),
+        ];
+
+        assert_eq!(last_user_turn(&messages), Some(2));
+        assert_eq!(find_user_turn(&messages, "m1"), Some(0));
+        assert_eq!(find_user_turn(&messages, "missing"), None);
+    }
+}
@@ -0,0 +1,666 @@
+//! Run-level experiments for the SDK.
+//! 
+//! This module provides `Experiments`, which assigns each session to a
+//! variant of every configured experiment. A variant can change the system
+//! prompt, the model and its sampling parameters, or set agent options.
+//! Assignment hashes the experiment name with the session ID, so a session
+//! always lands in the same bucket; the result is recorded in the session
+//! metadata under `experiments` and attached to the agent's lifecycle
+//! events so metrics can be broken down by variant.
+//! 
+//! `ExperimentOutcomes` subscribes to those events and collects, per
+//! variant, how runs ended and how users rated them, together with task
+//! success assertions recorded by the application or the eval harness.
+//! `report` compares every variant with its experiment's control, the
+//! first variant, using a chi-square test for rates and a bootstrap of
+//! the difference in means for scores.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::crypto::sha256;
+use crate::models::ModelConfig;
+use crate::telemetry::events::{EventSubscriber, LifecycleEvent, LifecycleEventKind};
+use crate::types::{IndubitablyError, IndubitablyResult, Session};
+
+/// The session metadata key holding experiment assignments.
+pub const EXPERIMENTS_METADATA_KEY: &str = "experiments";
+
+/// The p-value below which a difference between variants is called significant.
+pub const DEFAULT_SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// The number of resamples drawn when bootstrapping a difference in mean scores.
+pub const BOOTSTRAP_RESAMPLES: usize = 2000;
+
+/// The metric for the share of runs answered normally.
+pub const OUTCOME_ANSWERED_RATE: &str = "answered_rate";
+
+/// The metric for the share of feedback that is positive.
+pub const OUTCOME_THUMBS_UP_RATE: &str = "thumbs_up_rate";
+
+/// The metric for the share of task success assertions that passed.
+pub const OUTCOME_SUCCESS_RATE: &str = "success_rate";
+
+/// The metric for the mean of recorded scores.
+pub const OUTCOME_MEAN_SCORE: &str = "mean_score";
+
+/// One arm of an experiment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Variant {
+    /// The variant name, used in metadata and metric labels.
+    pub name: String,
+    /// The relative share of sessions assigned to this variant.
+    pub weight: u32,
+    /// The system prompt used instead of the agent's own.
+    pub system_prompt: Option<String>,
+    /// The model ID used instead of the configured one.
+    pub model_id: Option<String>,
+    /// The temperature used instead of the configured one.
+    pub temperature: Option<f32>,
+    /// The maximum tokens used instead of the configured value.
+    pub max_tokens: Option<u32>,
+    /// Agent options set for this variant.
+    pub options: HashMap<String, Value>,
+}
+
+impl Variant {
+    /// Create a variant with weight 1 that changes nothing.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            weight: 1,
+            system_prompt: None,
+            model_id: None,
+            temperature: None,
+            max_tokens: None,
+            options: HashMap::new(),
+        }
+    }
+
+    /// Set the relative share of sessions assigned to this variant.
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set the system prompt.
+    pub fn with_system_prompt(mut self, system_prompt: &str) -> Self {
+        self.system_prompt = Some(system_prompt.to_string());
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = Some(model_id.to_string());
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set an agent option.
+    pub fn with_option(mut self, key: &str, value: Value) -> Self {
+        self.options.insert(key.to_string(), value);
+        self
+    }
+
+    /// Override the model ID and sampling parameters this variant sets.
+    pub fn apply_to_model_config(&self, config: &mut ModelConfig) {
+        if let Some(ref model_id) = self.model_id {
+            config.model_id = model_id.clone();
+        }
+        if let Some(temperature) = self.temperature {
+            config.temperature = Some(temperature);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            config.max_tokens = Some(max_tokens);
+        }
+    }
+}
+
+/// A named experiment with weighted variants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Experiment {
+    /// The experiment name, used in metadata and metric labels.
+    pub name: String,
+    /// The variants sessions are split across.
+    pub variants: Vec<Variant>,
+}
+
+impl Experiment {
+    /// Create an experiment with no variants.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            variants: Vec::new(),
+        }
+    }
+
+    /// Add a variant.
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variants.push(variant);
+        self
+    }
+
+    /// Get a variant by name.
+    pub fn variant(&self, name: &str) -> Option<&Variant> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
+
+    /// Pick the variant for a session by hashing the experiment name with the session ID.
+    pub fn assign(&self, session_id: &str) -> Option<&Variant> {
+        let total: u64 = self.variants.iter().map(|variant| variant.weight as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let digest = sha256(format!("{}:{}", self.name, session_id).as_bytes());
+        let mut bucket = u64::from_be_bytes(digest[..8].try_into().expect("digest has 8 bytes")) % total;
+        for variant in &self.variants {
+            if bucket < variant.weight as u64 {
+                return Some(variant);
+            }
+            bucket -= variant.weight as u64;
+        }
+        None
+    }
+}
+
+/// The variant a session was assigned for one experiment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    /// The experiment name.
+    pub experiment: String,
+    /// The assigned variant.
+    pub variant: Variant,
+}
+
+/// The set of experiments an agent takes part in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Experiments {
+    /// The experiments, applied in order.
+    pub experiments: Vec<Experiment>,
+}
+
+impl Experiments {
+    /// Create an empty experiment set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an experiment.
+    pub fn with_experiment(mut self, experiment: Experiment) -> Self {
+        self.experiments.push(experiment);
+        self
+    }
+
+    /// Check whether no experiments are configured.
+    pub fn is_empty(&self) -> bool {
+        self.experiments.is_empty()
+    }
+
+    /// Assign a session ID to a variant of every experiment.
+    pub fn assign(&self, session_id: &str) -> Vec<Assignment> {
+        self.experiments
+            .iter()
+            .filter_map(|experiment| {
+                experiment.assign(session_id).map(|variant| Assignment {
+                    experiment: experiment.name.clone(),
+                    variant: variant.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Assign a session and record the assignments in its metadata.
+    ///
+    /// Assignments already recorded in the session are kept, so a session
+    /// stays in its variant even if weights change later. A recorded
+    /// variant that no longer exists is an error.
+    pub fn assign_session(&self, session: &mut Session) -> IndubitablyResult<Vec<Assignment>> {
+        let recorded: BTreeMap<String, String> = session
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(EXPERIMENTS_METADATA_KEY))
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default();
+
+        let mut assignments = Vec::with_capacity(self.experiments.len());
+        for experiment in &self.experiments {
+            let variant = match recorded.get(&experiment.name) {
+                Some(name) => experiment.variant(name).ok_or_else(|| {
+                    IndubitablyError::ValidationError(format!(
+                        "Session '{}' is recorded in unknown variant '{}' of experiment '{}'",
+                        session.id, name, experiment.name
+                    ))
+                })?,
+                None => match experiment.assign(&session.id) {
+                    Some(variant) => variant,
+                    None => continue,
+                },
+            };
+            assignments.push(Assignment {
+                experiment: experiment.name.clone(),
+                variant: variant.clone(),
+            });
+        }
+
+        let mut labels = recorded;
+        labels.extend(labels_of(&assignments));
+        session.add_metadata(EXPERIMENTS_METADATA_KEY, serde_json::to_value(labels)?);
+        Ok(assignments)
+    }
+}
+
+/// Map each experiment name to its assigned variant name, for metadata and metric labels.
+pub fn labels_of(assignments: &[Assignment]) -> BTreeMap<String, String> {
+    assignments
+        .iter()
+        .map(|assignment| (assignment.experiment.clone(), assignment.variant.name.clone()))
+        .collect()
+}
+
+/// The outcomes collected for one variant of an experiment.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VariantOutcomes {
+    /// The completed runs.
+    pub runs: u64,
+    /// The runs the model answered normally, rather than degraded or interrupted.
+    pub answered: u64,
+    /// The positive ratings.
+    pub thumbs_up: u64,
+    /// The negative ratings.
+    pub thumbs_down: u64,
+    /// The task success assertions that passed.
+    pub assertions_passed: u64,
+    /// The task success assertions that failed.
+    pub assertions_failed: u64,
+    /// The recorded quality scores, such as eval answer scores.
+    pub scores: Vec<f64>,
+}
+
+impl VariantOutcomes {
+    /// Get the hits and misses behind a rate metric, such as `OUTCOME_SUCCESS_RATE`.
+    pub fn rate_counts(&self, metric: &str) -> Option<(u64, u64)> {
+        match metric {
+            OUTCOME_ANSWERED_RATE => Some((self.answered, self.runs.saturating_sub(self.answered))),
+            OUTCOME_THUMBS_UP_RATE => Some((self.thumbs_up, self.thumbs_down)),
+            OUTCOME_SUCCESS_RATE => Some((self.assertions_passed, self.assertions_failed)),
+            _ => None,
+        }
+    }
+}
+
+/// Collects outcome metrics per experiment variant from lifecycle events.
+///
+/// Subscribe it to an agent's event bus; clones share the same outcomes.
+#[derive(Debug, Clone, Default)]
+pub struct ExperimentOutcomes {
+    outcomes: Arc<Mutex<BTreeMap<String, BTreeMap<String, VariantOutcomes>>>>,
+}
+
+impl ExperimentOutcomes {
+    /// Create a collector with no outcomes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record whether a task success assertion passed, under the given experiment labels.
+    pub fn record_assertion(&self, labels: &BTreeMap<String, String>, passed: bool) {
+        self.update(labels, |outcomes| match passed {
+            true => outcomes.assertions_passed += 1,
+            false => outcomes.assertions_failed += 1,
+        });
+    }
+
+    /// Record a quality score, under the given experiment labels.
+    pub fn record_score(&self, labels: &BTreeMap<String, String>, score: f64) {
+        self.update(labels, |outcomes| outcomes.scores.push(score));
+    }
+
+    /// Get the outcomes of one variant.
+    pub fn variant(&self, experiment: &str, variant: &str) -> Option<VariantOutcomes> {
+        self.snapshot().get(experiment)?.get(variant).cloned()
+    }
+
+    /// Get a copy of the outcomes, keyed by experiment and then variant name.
+    pub fn snapshot(&self) -> BTreeMap<String, BTreeMap<String, VariantOutcomes>> {
+        self.outcomes.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn update(&self, labels: &BTreeMap<String, String>, mut apply: impl FnMut(&mut VariantOutcomes)) {
+        let mut outcomes = self.outcomes.lock().unwrap_or_else(|e| e.into_inner());
+        for (experiment, variant) in labels {
+            apply(outcomes.entry(experiment.clone()).or_default().entry(variant.clone()).or_default());
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for ExperimentOutcomes {
+    fn name(&self) -> &str {
+        "experiment_outcomes"
+    }
+
+    async fn on_event(&self, event: &LifecycleEvent) -> IndubitablyResult<()> {
+        let Some(labels) = event
+            .get(EXPERIMENTS_METADATA_KEY)
+            .and_then(|value| serde_json::from_value::<BTreeMap<String, String>>(value.clone()).ok())
+        else {
+            return Ok(());
+        };
+        match event.kind {
+            LifecycleEventKind::RunCompleted => {
+                let answered = event.get("outcome").and_then(Value::as_str) == Some("answered");
+                self.update(&labels, |outcomes| {
+                    outcomes.runs += 1;
+                    outcomes.answered += answered as u64;
+                });
+            }
+            LifecycleEventKind::FeedbackReceived => match event.get("rating").and_then(Value::as_str) {
+                Some("thumbs_up") => self.update(&labels, |outcomes| outcomes.thumbs_up += 1),
+                Some("thumbs_down") => self.update(&labels, |outcomes| outcomes.thumbs_down += 1),
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// The statistical test behind a comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignificanceTest {
+    /// Pearson's chi-square test on the 2x2 table of successes and failures.
+    ChiSquare,
+    /// A bootstrap of the difference in means.
+    Bootstrap,
+}
+
+/// One outcome metric of a variant compared with the control.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricComparison {
+    /// The metric, such as `OUTCOME_THUMBS_UP_RATE`.
+    pub metric: String,
+    /// The variant compared with the control.
+    pub variant: String,
+    /// The metric's value for the control.
+    pub control_value: f64,
+    /// The metric's value for the variant.
+    pub variant_value: f64,
+    /// The observations behind the control's value.
+    pub control_samples: u64,
+    /// The observations behind the variant's value.
+    pub variant_samples: u64,
+    /// The test used.
+    pub test: SignificanceTest,
+    /// The test statistic: chi-square, or the observed difference in means for the bootstrap.
+    pub statistic: f64,
+    /// The two-sided p-value.
+    pub p_value: f64,
+}
+
+impl MetricComparison {
+    /// Get the variant's value minus the control's.
+    pub fn difference(&self) -> f64 {
+        self.variant_value - self.control_value
+    }
+
+    /// Check whether the difference is significant at the given level.
+    pub fn is_significant(&self, level: f64) -> bool {
+        self.p_value < level
+    }
+}
+
+/// The comparisons of every variant of one experiment with its control.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    /// The experiment name.
+    pub experiment: String,
+    /// The control variant, the experiment's first.
+    pub control: String,
+    /// The collected outcomes, keyed by variant name.
+    pub outcomes: BTreeMap<String, VariantOutcomes>,
+    /// The metric comparisons, for metrics both arms have observations of.
+    pub comparisons: Vec<MetricComparison>,
+}
+
+impl ExperimentReport {
+    /// Get the comparisons significant at `DEFAULT_SIGNIFICANCE_LEVEL`.
+    pub fn significant(&self) -> Vec<&MetricComparison> {
+        self.comparisons
+            .iter()
+            .filter(|comparison| comparison.is_significant(DEFAULT_SIGNIFICANCE_LEVEL))
+            .collect()
+    }
+}
+
+/// Compare the collected outcomes of every variant with its experiment's control.
+///
+/// Experiments without a variant or without outcomes are left out. Rates are
+/// compared with a chi-square test and mean scores with a bootstrap, which is
+/// seeded so the same outcomes always give the same report.
+pub fn report(experiments: &Experiments, outcomes: &ExperimentOutcomes) -> Vec<ExperimentReport> {
+    let collected = outcomes.snapshot();
+    let mut reports = Vec::new();
+    for experiment in &experiments.experiments {
+        let (Some(control), Some(observed)) = (experiment.variants.first(), collected.get(&experiment.name)) else {
+            continue;
+        };
+        let empty = VariantOutcomes::default();
+        let control_outcomes = observed.get(&control.name).unwrap_or(&empty);
+        let mut comparisons = Vec::new();
+        for variant in &experiment.variants[1..] {
+            let variant_outcomes = observed.get(&variant.name).unwrap_or(&empty);
+            comparisons.extend(compare_variant(&variant.name, control_outcomes, variant_outcomes));
+        }
+        reports.push(ExperimentReport {
+            experiment: experiment.name.clone(),
+            control: control.name.clone(),
+            outcomes: observed.clone(),
+            comparisons,
+        });
+    }
+    reports
+}
+
+/// Compare each metric both arms have observations of.
+fn compare_variant(variant: &str, control: &VariantOutcomes, treatment: &VariantOutcomes) -> Vec<MetricComparison> {
+    let mut comparisons = Vec::new();
+    for metric in [OUTCOME_ANSWERED_RATE, OUTCOME_THUMBS_UP_RATE, OUTCOME_SUCCESS_RATE] {
+        let (Some((control_hits, control_misses)), Some((variant_hits, variant_misses))) =
+            (control.rate_counts(metric), treatment.rate_counts(metric))
+        else {
+            continue;
+        };
+        let (control_samples, variant_samples) = (control_hits + control_misses, variant_hits + variant_misses);
+        if control_samples == 0 || variant_samples == 0 {
+            continue;
+        }
+        let statistic = chi_square_2x2(control_hits, control_misses, variant_hits, variant_misses);
+        comparisons.push(MetricComparison {
+            metric: metric.to_string(),
+            variant: variant.to_string(),
+            control_value: control_hits as f64 / control_samples as f64,
+            variant_value: variant_hits as f64 / variant_samples as f64,
+            control_samples,
+            variant_samples,
+            test: SignificanceTest::ChiSquare,
+            statistic,
+            p_value: erfc((statistic / 2.0).sqrt()),
+        });
+    }
+    if !control.scores.is_empty() && !treatment.scores.is_empty() {
+        let (difference, p_value) = bootstrap_difference(&control.scores, &treatment.scores);
+        comparisons.push(MetricComparison {
+            metric: OUTCOME_MEAN_SCORE.to_string(),
+            variant: variant.to_string(),
+            control_value: mean(&control.scores),
+            variant_value: mean(&treatment.scores),
+            control_samples: control.scores.len() as u64,
+            variant_samples: treatment.scores.len() as u64,
+            test: SignificanceTest::Bootstrap,
+            statistic: difference,
+            p_value,
+        });
+    }
+    comparisons
+}
+
+/// Pearson's chi-square statistic for a 2x2 table, 0 when a row or column is empty.
+fn chi_square_2x2(a: u64, b: u64, c: u64, d: u64) -> f64 {
+    let (a, b, c, d) = (a as f64, b as f64, c as f64, d as f64);
+    let margins = (a + b) * (c + d) * (a + c) * (b + d);
+    if margins == 0.0 {
+        return 0.0;
+    }
+    (a + b + c + d) * (a * d - b * c).powi(2) / margins
+}
+
+/// The coefficients of the Chebyshev fit used by `erfc`, lowest power first.
+const ERFC_COEFFICIENTS: [f64; 10] = [
+    -1.26551223, 1.00002368, 0.37409196, 0.09678418, -0.18628806, 0.27886807, -1.13520398, 1.48851587, -0.82215223,
+    0.17087277,
+];
+
+/// The complementary error function, accurate to about 1e-7.
+///
+/// With one degree of freedom, the chi-square p-value is `erfc(sqrt(x / 2))`.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = ERFC_COEFFICIENTS.iter().rev().fold(0.0, |acc, coefficient| acc * t + coefficient);
+    let value = t * (-z * z + poly).exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+/// Bootstrap the difference in means, returning it with its two-sided p-value.
+fn bootstrap_difference(control: &[f64], variant: &[f64]) -> (f64, f64) {
+    let observed = mean(variant) - mean(control);
+    let mut rng = SplitMix64(0x5eed);
+    let resample_mean = |values: &[f64], rng: &mut SplitMix64| {
+        (0..values.len()).map(|_| values[rng.below(values.len())]).sum::<f64>() / values.len() as f64
+    };
+    let (mut at_most_zero, mut at_least_zero) = (0usize, 0usize);
+    for _ in 0..BOOTSTRAP_RESAMPLES {
+        let difference = resample_mean(variant, &mut rng) - resample_mean(control, &mut rng);
+        at_most_zero += (difference <= 0.0) as usize;
+        at_least_zero += (difference >= 0.0) as usize;
+    }
+    let tail = at_most_zero.min(at_least_zero) as f64 / BOOTSTRAP_RESAMPLES as f64;
+    (observed, (2.0 * tail).min(1.0))
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// A small seeded generator, so bootstrap reports are reproducible.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SessionAgent, SessionType};
+
+    #[test]
+    fn test_experiments_assignment() {
+        let experiments = Experiments::new().with_experiment(
+            Experiment::new("prompt_v2")
+                .with_variant(Variant::new("control"))
+                .with_variant(Variant::new("concise").with_system_prompt("Be concise.").with_weight(3)),
+        );
+
+        let first = experiments.assign("session-1");
+        assert_eq!(first, experiments.assign("session-1"));
+        let concise = (0..400)
+            .filter(|i| experiments.assign(&format!("session-{}", i))[0].variant.name == "concise")
+            .count();
+        assert!((260..340).contains(&concise), "concise share was {}", concise);
+
+        let mut session = Session::new("session-1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        let assigned = experiments.assign_session(&mut session).unwrap();
+        assert_eq!(assigned, first);
+        let recorded = &session.metadata.as_ref().unwrap()[EXPERIMENTS_METADATA_KEY];
+        assert_eq!(recorded["prompt_v2"], first[0].variant.name.as_str());
+
+        // A recorded assignment sticks even when the weights change.
+        session.add_metadata(EXPERIMENTS_METADATA_KEY, serde_json::json!({"prompt_v2": "control"}));
+        let reweighted = Experiments::new().with_experiment(
+            Experiment::new("prompt_v2")
+                .with_variant(Variant::new("control").with_weight(0))
+                .with_variant(Variant::new("concise")),
+        );
+        assert_eq!(reweighted.assign_session(&mut session).unwrap()[0].variant.name, "control");
+
+        session.add_metadata(EXPERIMENTS_METADATA_KEY, serde_json::json!({"prompt_v2": "removed"}));
+        assert!(reweighted.assign_session(&mut session).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_experiment_outcomes_report_chi_square() {
+        use crate::telemetry::events::EventBus;
+        use std::sync::Arc;
+
+        let experiments = Experiments::new().with_experiment(
+            Experiment::new("prompt_v2").with_variant(Variant::new("control")).with_variant(Variant::new("concise")),
+        );
+        let outcomes = ExperimentOutcomes::new();
+        let events = EventBus::new();
+        events.subscribe(Arc::new(outcomes.clone()));
+        let publish = |kind, variant: &str, data: Value| {
+            let mut data = data;
+            data[EXPERIMENTS_METADATA_KEY] = serde_json::json!({"prompt_v2": variant});
+            events.publish(LifecycleEvent::new(kind, "agent", data))
+        };
+        // Control: 10 of 40 ratings positive; concise: 20 of 40.
+        for i in 0..40 {
+            let control = if i < 10 { "thumbs_up" } else { "thumbs_down" };
+            let concise = if i < 20 { "thumbs_up" } else { "thumbs_down" };
+            publish(LifecycleEventKind::FeedbackReceived, "control", serde_json::json!({"rating": control})).await;
+            publish(LifecycleEventKind::FeedbackReceived, "concise", serde_json::json!({"rating": concise})).await;
+        }
+        publish(LifecycleEventKind::RunCompleted, "concise", serde_json::json!({"outcome": "answered"})).await;
+        events.publish(LifecycleEvent::new(LifecycleEventKind::RunCompleted, "agent", serde_json::json!({}))).await;
+
+        let concise = outcomes.variant("prompt_v2", "concise").unwrap();
+        assert_eq!((concise.runs, concise.answered, concise.thumbs_up), (1, 1, 20));
+
+        let reports = report(&experiments, &outcomes);
+        // The control has no runs, so only the ratings are compared.
+        assert_eq!(reports[0].comparisons.len(), 1);
+        let rating = &reports[0].comparisons[0];
+        assert_eq!(rating.metric, OUTCOME_THUMBS_UP_RATE);
+        assert_eq!(rating.test, SignificanceTest::ChiSquare);
+        assert!((rating.statistic - 16.0 / 3.0).abs() < 1e-9);
+        assert!((rating.p_value - 0.0209).abs() < 1e-3, "p-value was {}", rating.p_value);
+        assert!((erfc(0.0) - 1.0).abs() < 1e-7 && (erfc(-1.0) + erfc(1.0) - 2.0).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,424 @@
+//! Content guardrails with decision logging and appeals for the SDK.
+//! 
+//! This module provides `GuardrailPolicy`, a set of rules matched against
+//! user input and model output. Each match becomes a `GuardrailDecision`
+//! naming the rule, the matched span and the action taken. Decisions are kept
+//! in a `GuardrailLog`, which is recorded in the session metadata under
+//! `guardrail_decisions` so every block can be traced.
+//! 
+//! Session metadata is neither encrypted nor anonymized, so a recorded
+//! decision holds only the span offsets and a SHA-256 hash of the evaluated
+//! content. The flagged text itself stays in memory for the life of the log.
+//! 
+//! A blocked decision can be appealed: re-evaluated under a stricter or
+//! looser policy, or routed to human review and resolved by a reviewer.
+//! Re-evaluating a decision loaded from a session takes the content from the
+//! caller, checked against the recorded hash.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{hex, sha256};
+use crate::types::{IndubitablyError, IndubitablyResult, Session};
+
+/// The session metadata key holding guardrail decisions.
+pub const GUARDRAIL_DECISIONS_METADATA_KEY: &str = "guardrail_decisions";
+
+/// The answer returned in place of blocked input or output.
+pub const GUARDRAIL_BLOCKED_RESPONSE: &str = "I can't help with that request.";
+
+/// What a guardrail does with matching content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailAction {
+    /// The content is withheld.
+    Block,
+    /// The content passes but the match is recorded.
+    Flag,
+}
+
+/// Where in a run content was evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailStage {
+    /// The user's message, before it reaches the model.
+    Input,
+    /// The model's response, before it reaches the user.
+    Output,
+}
+
+/// A rule matching content by regular expression.
+#[derive(Debug, Clone)]
+pub struct GuardrailRule {
+    /// The rule ID recorded in decisions.
+    pub id: String,
+    /// The action taken on a match.
+    pub action: GuardrailAction,
+    pattern: Regex,
+}
+
+/// A named set of guardrail rules.
+#[derive(Debug, Clone)]
+pub struct GuardrailPolicy {
+    name: String,
+    rules: Vec<GuardrailRule>,
+}
+
+impl GuardrailPolicy {
+    /// Create a policy with no rules.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Add a rule matching a regular expression.
+    pub fn with_rule(mut self, id: &str, pattern: &str, action: GuardrailAction) -> IndubitablyResult<Self> {
+        let pattern = Regex::new(pattern).map_err(|e| {
+            IndubitablyError::ValidationError(format!("Invalid guardrail pattern for rule '{}': {}", id, e))
+        })?;
+        self.rules.push(GuardrailRule {
+            id: id.to_string(),
+            action,
+            pattern,
+        });
+        Ok(self)
+    }
+
+    /// Get the policy name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the rules in evaluation order.
+    pub fn rules(&self) -> &[GuardrailRule] {
+        &self.rules
+    }
+
+    /// Evaluate content, returning a decision for the first match of each rule.
+    pub fn evaluate(&self, content: &str, stage: GuardrailStage) -> Vec<GuardrailDecision> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let found = rule.pattern.find(content)?;
+                Some(GuardrailDecision {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    policy: self.name.clone(),
+                    rule_id: rule.id.clone(),
+                    stage,
+                    action: rule.action,
+                    span: MatchedSpan {
+                        start: found.start(),
+                        end: found.end(),
+                        text: found.as_str().to_string(),
+                    },
+                    content_sha256: hex::encode(&sha256(content.as_bytes())),
+                    content: content.to_string(),
+                    status: DecisionStatus::Active,
+                    appeal: None,
+                    created_at: Utc::now(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The part of the content a rule matched, as byte offsets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchedSpan {
+    /// The offset of the first matched byte.
+    pub start: usize,
+    /// The offset after the last matched byte.
+    pub end: usize,
+    /// The matched text, held in memory only and never recorded.
+    #[serde(skip)]
+    pub text: String,
+}
+
+/// Where a decision stands in the appeal workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionStatus {
+    /// The decision stands and has not been appealed.
+    Active,
+    /// The decision is waiting for a human reviewer.
+    PendingReview,
+    /// The decision was confirmed on appeal.
+    Upheld,
+    /// The decision was reversed on appeal.
+    Overturned,
+}
+
+/// How an appeal is decided.
+#[derive(Debug, Clone)]
+pub enum AppealRoute {
+    /// Re-evaluate the content under another policy; it stays blocked if that policy blocks it.
+    ///
+    /// The content must still be held by the log, so this fails for decisions loaded from a session.
+    Reevaluate(GuardrailPolicy),
+    /// Re-evaluate content supplied by the caller, which must match the recorded hash.
+    ReevaluateContent(GuardrailPolicy, String),
+    /// Hold the decision for a human reviewer.
+    HumanReview,
+}
+
+/// The record of an appeal against a decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppealRecord {
+    /// The policy the content was re-evaluated under, or `None` for human review.
+    pub policy: Option<String>,
+    /// When the appeal was made.
+    pub requested_at: DateTime<Utc>,
+    /// When the appeal was decided.
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// The reviewer who decided a human review.
+    pub reviewer: Option<String>,
+    /// The reviewer's note.
+    pub note: Option<String>,
+}
+
+/// A guardrail match and the action taken on it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardrailDecision {
+    /// The unique identifier of the decision.
+    pub id: String,
+    /// The policy that matched.
+    pub policy: String,
+    /// The rule that matched.
+    pub rule_id: String,
+    /// Where in the run the content was evaluated.
+    pub stage: GuardrailStage,
+    /// The action taken.
+    pub action: GuardrailAction,
+    /// The matched part of the content.
+    pub span: MatchedSpan,
+    /// The hex SHA-256 hash of the evaluated content.
+    pub content_sha256: String,
+    /// The evaluated content, held in memory only so the decision can be re-evaluated.
+    #[serde(skip)]
+    pub content: String,
+    /// Where the decision stands in the appeal workflow.
+    pub status: DecisionStatus,
+    /// The appeal against the decision, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub appeal: Option<AppealRecord>,
+    /// When the decision was made.
+    pub created_at: DateTime<Utc>,
+}
+
+impl GuardrailDecision {
+    /// Check whether the decision blocked content.
+    pub fn is_block(&self) -> bool {
+        self.action == GuardrailAction::Block
+    }
+}
+
+/// The guardrail decisions of a session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GuardrailLog {
+    decisions: Vec<GuardrailDecision>,
+}
+
+impl GuardrailLog {
+    /// Create an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the decisions recorded in a session's metadata.
+    pub fn from_session(session: &Session) -> IndubitablyResult<Self> {
+        let Some(recorded) = session
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(GUARDRAIL_DECISIONS_METADATA_KEY))
+        else {
+            return Ok(Self::new());
+        };
+        Ok(Self {
+            decisions: serde_json::from_value(recorded.clone())?,
+        })
+    }
+
+    /// Record the decisions in a session's metadata, replacing earlier ones.
+    pub fn save_to(&self, session: &mut Session) -> IndubitablyResult<()> {
+        session.add_metadata(GUARDRAIL_DECISIONS_METADATA_KEY, serde_json::to_value(&self.decisions)?);
+        Ok(())
+    }
+
+    /// Add decisions to the log.
+    pub fn record(&mut self, decisions: Vec<GuardrailDecision>) {
+        for decision in &decisions {
+            tracing::info!(
+                "decision_id=<{}>, policy=<{}>, rule_id=<{}>, stage=<{:?}>, action=<{:?}>, span=<{}..{}> | guardrail matched content",
+                decision.id,
+                decision.policy,
+                decision.rule_id,
+                decision.stage,
+                decision.action,
+                decision.span.start,
+                decision.span.end
+            );
+        }
+        self.decisions.extend(decisions);
+    }
+
+    /// Get the decisions in the order they were made.
+    pub fn decisions(&self) -> &[GuardrailDecision] {
+        &self.decisions
+    }
+
+    /// Get a decision by ID.
+    pub fn get(&self, decision_id: &str) -> Option<&GuardrailDecision> {
+        self.decisions.iter().find(|decision| decision.id == decision_id)
+    }
+
+    /// Appeal a block, re-evaluating it or routing it to human review.
+    ///
+    /// Only active blocks can be appealed.
+    pub fn appeal(&mut self, decision_id: &str, route: AppealRoute) -> IndubitablyResult<GuardrailDecision> {
+        let decision = self.decision_mut(decision_id)?;
+        if !decision.is_block() || decision.status != DecisionStatus::Active {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Guardrail decision '{}' is not an active block",
+                decision_id
+            )));
+        }
+        let now = Utc::now();
+        let (policy, content) = match route {
+            AppealRoute::Reevaluate(_) if decision.content.is_empty() => {
+                return Err(IndubitablyError::ValidationError(format!(
+                    "Guardrail decision '{}' no longer holds its content; supply it to re-evaluate",
+                    decision_id
+                )));
+            }
+            AppealRoute::Reevaluate(policy) => (Some(policy), decision.content.clone()),
+            AppealRoute::ReevaluateContent(policy, content) => {
+                if hex::encode(&sha256(content.as_bytes())) != decision.content_sha256 {
+                    return Err(IndubitablyError::ValidationError(format!(
+                        "Content does not match guardrail decision '{}'",
+                        decision_id
+                    )));
+                }
+                (Some(policy), content)
+            }
+            AppealRoute::HumanReview => (None, String::new()),
+        };
+        if let Some(policy) = policy {
+            let blocked = policy.evaluate(&content, decision.stage).iter().any(GuardrailDecision::is_block);
+            decision.status = if blocked { DecisionStatus::Upheld } else { DecisionStatus::Overturned };
+            decision.appeal = Some(AppealRecord {
+                policy: Some(policy.name().to_string()),
+                requested_at: now,
+                resolved_at: Some(now),
+                reviewer: None,
+                note: None,
+            });
+        } else {
+            decision.status = DecisionStatus::PendingReview;
+            decision.appeal = Some(AppealRecord {
+                policy: None,
+                requested_at: now,
+                resolved_at: None,
+                reviewer: None,
+                note: None,
+            });
+        }
+        tracing::info!(
+            "decision_id=<{}>, status=<{:?}> | guardrail decision appealed",
+            decision.id,
+            decision.status
+        );
+        Ok(decision.clone())
+    }
+
+    /// Decide an appeal waiting for human review.
+    pub fn resolve_review(
+        &mut self,
+        decision_id: &str,
+        upheld: bool,
+        reviewer: &str,
+        note: Option<&str>,
+    ) -> IndubitablyResult<GuardrailDecision> {
+        let decision = self.decision_mut(decision_id)?;
+        let (DecisionStatus::PendingReview, Some(appeal)) = (decision.status, decision.appeal.as_mut()) else {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Guardrail decision '{}' is not waiting for review",
+                decision_id
+            )));
+        };
+        appeal.resolved_at = Some(Utc::now());
+        appeal.reviewer = Some(reviewer.to_string());
+        appeal.note = note.map(str::to_string);
+        decision.status = if upheld { DecisionStatus::Upheld } else { DecisionStatus::Overturned };
+        Ok(decision.clone())
+    }
+
+    fn decision_mut(&mut self, decision_id: &str) -> IndubitablyResult<&mut GuardrailDecision> {
+        self.decisions
+            .iter_mut()
+            .find(|decision| decision.id == decision_id)
+            .ok_or_else(|| IndubitablyError::ValidationError(format!("Unknown guardrail decision '{}'", decision_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SessionAgent, SessionType};
+
+    #[test]
+    fn test_guardrail_decisions_and_appeals() {
+        let strict = GuardrailPolicy::new("strict")
+            .with_rule("weapons", r"(?i)\bexplosives?\b", GuardrailAction::Block)
+            .unwrap()
+            .with_rule("competitor", r"(?i)\bacme\b", GuardrailAction::Flag)
+            .unwrap();
+        let decisions = strict.evaluate("How do ACME mines store explosives safely?", GuardrailStage::Input);
+        assert_eq!(decisions.len(), 2);
+        assert_eq!(decisions[0].rule_id, "weapons");
+        assert_eq!(decisions[0].span.text, "explosives");
+        assert_eq!(decisions[0].span.start, 24);
+        assert!(!decisions[1].is_block());
+
+        let mut log = GuardrailLog::new();
+        log.record(decisions.clone());
+        let blocked = decisions[0].id.clone();
+        assert!(log.appeal(&decisions[1].id, AppealRoute::HumanReview).is_err());
+
+        let looser = GuardrailPolicy::new("mining").with_rule("weapons", r"(?i)\bbuild\b.*\bexplosives?\b", GuardrailAction::Block).unwrap();
+        let appealed = log.appeal(&blocked, AppealRoute::Reevaluate(looser)).unwrap();
+        assert_eq!(appealed.status, DecisionStatus::Overturned);
+        assert_eq!(appealed.appeal.unwrap().policy.as_deref(), Some("mining"));
+        assert!(log.appeal(&blocked, AppealRoute::HumanReview).is_err());
+
+        let mut second = GuardrailLog::new();
+        second.record(strict.evaluate("explosives", GuardrailStage::Output));
+        let id = second.decisions()[0].id.clone();
+        assert!(second.resolve_review(&id, true, "ops", None).is_err());
+        assert_eq!(second.appeal(&id, AppealRoute::HumanReview).unwrap().status, DecisionStatus::PendingReview);
+        let resolved = second.resolve_review(&id, true, "ops@example.com", Some("policy applies")).unwrap();
+        assert_eq!(resolved.status, DecisionStatus::Upheld);
+        assert_eq!(resolved.appeal.unwrap().reviewer.as_deref(), Some("ops@example.com"));
+
+        let mut session = Session::new("session-1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        log.save_to(&mut session).unwrap();
+        let recorded = &session.metadata.as_ref().unwrap()[GUARDRAIL_DECISIONS_METADATA_KEY];
+        assert_eq!(recorded[0]["rule_id"], "weapons");
+        assert_eq!(recorded[0]["span"], serde_json::json!({"start": 24, "end": 34}));
+        assert!(!recorded.to_string().to_lowercase().contains("explosives"));
+
+        let mut third = GuardrailLog::new();
+        third.record(strict.evaluate("Where are explosives sold?", GuardrailStage::Input));
+        third.save_to(&mut session).unwrap();
+        let mut restored = GuardrailLog::from_session(&session).unwrap();
+        let id = restored.decisions()[0].id.clone();
+        assert!(restored.decisions()[0].content.is_empty());
+        assert!(restored.appeal(&id, AppealRoute::Reevaluate(strict.clone())).is_err());
+        let forged = AppealRoute::ReevaluateContent(strict.clone(), "Where are fireworks sold?".to_string());
+        assert!(restored.appeal(&id, forged).is_err());
+        let supplied = AppealRoute::ReevaluateContent(strict, "Where are explosives sold?".to_string());
+        assert_eq!(restored.appeal(&id, supplied).unwrap().status, DecisionStatus::Upheld);
+    }
+}
@@ -0,0 +1,63 @@
+//! Interrupts for the SDK.
+//! 
+//! This module defines `Interrupt`, returned in an `AgentResult` when the
+//! agent pauses a run to hand control back to the user instead of
+//! proceeding on its own.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::ToolUse;
+
+/// The message recorded in the conversation when the user approves an interrupt.
+pub const INTERRUPT_APPROVED_MESSAGE: &str = "Approved, go ahead.";
+
+/// Why the agent interrupted a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptReason {
+    /// The model asked to run a tool marked as high impact.
+    HighImpactTool,
+    /// The model signalled that it is unsure how to proceed.
+    LowConfidence,
+    /// The model planned conflicting tool calls.
+    ConflictingToolPlans,
+}
+
+/// The user's answer to an interrupt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptDecision {
+    /// Run the held tool calls and continue.
+    Approve,
+    /// Do not run the held tool calls, and reply with this message instead.
+    Decline(String),
+}
+
+/// A pause in a run that needs input from the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interrupt {
+    /// The unique identifier of the interrupt.
+    pub id: String,
+    /// Why the run was interrupted.
+    pub reason: InterruptReason,
+    /// The question returned to the user.
+    pub question: String,
+    /// The tool calls that were held back.
+    pub tool_uses: Vec<ToolUse>,
+    /// When the interrupt was raised.
+    pub created_at: DateTime<Utc>,
+}
+
+impl Interrupt {
+    /// Create a new interrupt.
+    pub fn new(reason: InterruptReason, question: &str, tool_uses: Vec<ToolUse>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            reason,
+            question: question.to_string(),
+            tool_uses,
+            created_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Memory limits for long-lived agents.
+//! 
+//! This module provides configuration for capping the size of the stored
+//! transcript, the tool results retained in context, and the agent state,
+//! so that long-running services do not grow without bound.
+
+use serde_json::Value;
+
+use crate::types::{Message, Messages, IndubitablyResult, IndubitablyError, ConversationError};
+use crate::tools::executor::truncate_output;
+
+/// How to handle a transcript that exceeds its configured size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncationStrategy {
+    /// Drop the oldest messages until the transcript fits.
+    DropOldest,
+    /// Fail with an explicit error.
+    Error,
+}
+
+/// Memory limits for an agent.
+///
+/// All limits are disabled by default.
+#[derive(Debug, Clone)]
+pub struct MemoryLimits {
+    /// The maximum serialized size of the stored conversation in bytes.
+    pub max_conversation_bytes: Option<usize>,
+    /// The maximum serialized size of a tool result retained in context in bytes.
+    pub max_tool_result_bytes: Option<usize>,
+    /// The maximum serialized size of the agent state in bytes.
+    ///
+    /// The agent keeps its transcript in the conversation manager, under
+    /// `max_conversation_bytes`; this limit applies to state metadata set
+    /// with `AgentState::try_set_metadata`.
+    pub max_state_bytes: Option<usize>,
+    /// How to handle a conversation that exceeds its limit.
+    pub strategy: TruncationStrategy,
+}
+
+impl Default for MemoryLimits {
+    fn default() -> Self {
+        Self {
+            max_conversation_bytes: None,
+            max_tool_result_bytes: None,
+            max_state_bytes: None,
+            strategy: TruncationStrategy::DropOldest,
+        }
+    }
+}
+
+impl MemoryLimits {
+    /// Create new memory limits with all limits disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum conversation size in bytes.
+    pub fn with_max_conversation_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_conversation_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set the maximum tool result size in bytes.
+    pub fn with_max_tool_result_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_tool_result_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set the maximum agent state size in bytes.
+    pub fn with_max_state_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_state_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set the truncation strategy.
+    pub fn with_strategy(mut self, strategy: TruncationStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Check if any limit is configured.
+    pub fn is_bounded(&self) -> bool {
+        self.max_conversation_bytes.is_some()
+            || self.max_tool_result_bytes.is_some()
+            || self.max_state_bytes.is_some()
+    }
+
+    /// Enforce the conversation limit on the given messages.
+    ///
+    /// Returns the number of messages dropped. The most recent message is
+    /// never dropped; if it alone exceeds the limit an error is returned.
+    pub fn enforce_conversation(&self, messages: &mut Messages) -> IndubitablyResult<usize> {
+        let max_bytes = match self.max_conversation_bytes {
+            Some(max_bytes) => max_bytes,
+            None => return Ok(0),
+        };
+
+        let mut total = conversation_size_bytes(messages);
+        if total <= max_bytes {
+            return Ok(0);
+        }
+
+        if self.strategy == TruncationStrategy::Error {
+            return Err(IndubitablyError::ConversationError(ConversationError::ContextOverflow(
+                format!("conversation is {} bytes, limit is {} bytes", total, max_bytes),
+            )));
+        }
+
+        let mut dropped = 0;
+        while total > max_bytes && messages.len() > 1 {
+            total -= message_size_bytes(&messages.remove(0));
+            dropped += 1;
+        }
+
+        if total > max_bytes {
+            return Err(IndubitablyError::ConversationError(ConversationError::ContextOverflow(
+                format!("latest message is {} bytes, limit is {} bytes", total, max_bytes),
+            )));
+        }
+
+        tracing::warn!(
+            "dropped_messages=<{}>, max_bytes=<{}> | conversation exceeded memory limit",
+            dropped,
+            max_bytes
+        );
+        Ok(dropped)
+    }
+
+    /// Truncate a tool result to the configured size for retention in context.
+    pub fn truncate_tool_result(&self, output: Value) -> Value {
+        match self.max_tool_result_bytes {
+            Some(max_bytes) => truncate_output(output, max_bytes).0,
+            None => output,
+        }
+    }
+}
+
+/// Get the serialized size of a message in bytes.
+pub fn message_size_bytes(message: &Message) -> usize {
+    serde_json::to_vec(message).map(|bytes| bytes.len()).unwrap_or(0)
+}
+
+/// Get the serialized size of a conversation in bytes.
+pub fn conversation_size_bytes(messages: &Messages) -> usize {
+    messages.iter().map(message_size_bytes).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unbounded_by_default() {
+        let limits = MemoryLimits::new();
+        let mut messages = vec![Message::user("Hello"); 100];
+
+        assert!(!limits.is_bounded());
+        assert_eq!(limits.enforce_conversation(&mut messages).unwrap(), 0);
+        assert_eq!(messages.len(), 100);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let message_size = message_size_bytes(&Message::user("Hello"));
+        let limits = MemoryLimits::new().with_max_conversation_bytes(message_size * 2);
+        let mut messages = vec![Message::user("Hello"); 5];
+
+        assert_eq!(limits.enforce_conversation(&mut messages).unwrap(), 3);
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn test_error_strategy() {
+        let limits = MemoryLimits::new()
+            .with_max_conversation_bytes(10)
+            .with_strategy(TruncationStrategy::Error);
+        let mut messages = vec![Message::user("Hello")];
+
+        assert!(limits.enforce_conversation(&mut messages).is_err());
+        assert_eq!(messages.len(), 1);
+    }
+
+    #[test]
+    fn test_truncate_tool_result() {
+        let limits = MemoryLimits::new().with_max_tool_result_bytes(16);
+        let output = limits.truncate_tool_result(json!("a".repeat(100)));
+
+        let text = output.as_str().unwrap();
+        assert!(text.starts_with("aaaaaaaaaaaaaaaa"));
+        assert!(text.contains("truncated"));
+    }
+}
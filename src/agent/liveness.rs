@@ -0,0 +1,300 @@
+//! Heartbeats and a watchdog for long-running agent runs.
+//! 
+//! A run that outlives `LivenessConfig::heartbeat_after` publishes a
+//! `RunHeartbeat` event every `heartbeat_interval`, so hooks and webhooks can
+//! tell a slow run from a hung one. A run still going at the hard ceiling is
+//! aborted and a `RunAborted` event carries a dump of what it was doing.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::telemetry::events::{EventBus, LifecycleEvent, LifecycleEventKind};
+use crate::types::{EventLoopError, IndubitablyResult};
+
+/// The default time a run may take before it sends heartbeats.
+pub const DEFAULT_HEARTBEAT_AFTER: Duration = Duration::from_secs(30);
+
+/// The default time between heartbeats.
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// When long runs send heartbeats and when they are aborted.
+///
+/// Runs are never aborted unless a hard ceiling is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivenessConfig {
+    /// How long a run may take before it sends its first heartbeat.
+    pub heartbeat_after: Duration,
+    /// The time between heartbeats.
+    pub heartbeat_interval: Duration,
+    /// How long a run may take before the watchdog aborts it.
+    pub hard_ceiling: Option<Duration>,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_after: DEFAULT_HEARTBEAT_AFTER,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
+            hard_ceiling: None,
+        }
+    }
+}
+
+impl LivenessConfig {
+    /// Create a configuration with the default heartbeat timing and no hard ceiling.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long a run may take before it sends its first heartbeat.
+    pub fn with_heartbeat_after(mut self, after: Duration) -> Self {
+        self.heartbeat_after = after;
+        self
+    }
+
+    /// Set the time between heartbeats.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    /// Set how long a run may take before the watchdog aborts it.
+    pub fn with_hard_ceiling(mut self, ceiling: Duration) -> Self {
+        self.hard_ceiling = Some(ceiling);
+        self
+    }
+}
+
+/// The step a run is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunPhase {
+    /// Applying guardrails and loading the conversation.
+    Preparing,
+    /// Waiting for the model.
+    ModelCall,
+    /// Running the tools the model asked for.
+    ToolCall,
+    /// Storing the answer and building the result.
+    Finishing,
+}
+
+/// A snapshot of what a run was doing, sent with heartbeats and aborts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunDiagnostics {
+    /// The ID of the user message the run answers.
+    pub message_id: Option<String>,
+    /// The time since the run started in milliseconds.
+    pub elapsed_ms: u64,
+    /// The event loop cycle the run is in.
+    pub cycle: usize,
+    /// The step the run is in.
+    pub phase: RunPhase,
+    /// The time spent in the current step in milliseconds.
+    pub phase_elapsed_ms: u64,
+    /// The number of model calls started.
+    pub model_calls: usize,
+    /// The number of tool calls started.
+    pub tool_calls: usize,
+    /// The tools running right now.
+    pub active_tools: Vec<String>,
+}
+
+impl RunDiagnostics {
+    /// Get the diagnostics as a JSON object.
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Debug)]
+struct Progress {
+    message_id: Option<String>,
+    started: Instant,
+    cycle: usize,
+    phase: RunPhase,
+    phase_started: Instant,
+    model_calls: usize,
+    tool_calls: usize,
+    active_tools: Vec<String>,
+}
+
+/// The progress of the current run, shared with the watchdog while the run holds the agent.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RunProbe {
+    progress: Arc<Mutex<Option<Progress>>>,
+}
+
+impl RunProbe {
+    fn update(&self, update: impl FnOnce(&mut Progress)) {
+        if let Some(progress) = self.progress.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            update(progress);
+        }
+    }
+
+    /// Start tracking a run.
+    pub(crate) fn start(&self, message_id: Option<&str>) {
+        let now = Instant::now();
+        *self.progress.lock().unwrap_or_else(|e| e.into_inner()) = Some(Progress {
+            message_id: message_id.map(str::to_string),
+            started: now,
+            cycle: 0,
+            phase: RunPhase::Preparing,
+            phase_started: now,
+            model_calls: 0,
+            tool_calls: 0,
+            active_tools: Vec::new(),
+        });
+    }
+
+    /// Record the start of an event loop cycle.
+    pub(crate) fn cycle(&self, cycle: usize) {
+        self.update(|progress| progress.cycle = cycle);
+    }
+
+    /// Record that the run moved to another step.
+    pub(crate) fn enter(&self, phase: RunPhase) {
+        self.update(|progress| {
+            if phase == RunPhase::ModelCall {
+                progress.model_calls += 1;
+            }
+            progress.phase = phase;
+            progress.phase_started = Instant::now();
+        });
+    }
+
+    /// Record that a tool started running.
+    pub(crate) fn tool_started(&self, name: &str) {
+        self.enter(RunPhase::ToolCall);
+        self.update(|progress| {
+            progress.tool_calls += 1;
+            progress.active_tools.push(name.to_string());
+        });
+    }
+
+    /// Record that a tool finished running.
+    pub(crate) fn tool_finished(&self, name: &str) {
+        self.update(|progress| {
+            if let Some(index) = progress.active_tools.iter().position(|tool| tool == name) {
+                progress.active_tools.remove(index);
+            }
+        });
+    }
+
+    /// Stop tracking the run.
+    pub(crate) fn finish(&self) {
+        *self.progress.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Get a snapshot of the run, if one is being tracked.
+    pub(crate) fn diagnostics(&self) -> Option<RunDiagnostics> {
+        let progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        progress.as_ref().map(|progress| RunDiagnostics {
+            message_id: progress.message_id.clone(),
+            elapsed_ms: progress.started.elapsed().as_millis() as u64,
+            cycle: progress.cycle,
+            phase: progress.phase,
+            phase_elapsed_ms: progress.phase_started.elapsed().as_millis() as u64,
+            model_calls: progress.model_calls,
+            tool_calls: progress.tool_calls,
+            active_tools: progress.active_tools.clone(),
+        })
+    }
+}
+
+/// Watches a run, publishing heartbeats and aborting it past the hard ceiling.
+pub(crate) struct Watchdog {
+    pub(crate) config: LivenessConfig,
+    pub(crate) probe: RunProbe,
+    pub(crate) events: EventBus,
+    /// The agent name events are published under.
+    pub(crate) source: String,
+    /// Fields added to every event, such as the agent name and experiment labels.
+    pub(crate) context: Map<String, Value>,
+}
+
+impl Watchdog {
+    /// Drive the run to completion, or drop it once it exceeds the hard ceiling.
+    pub(crate) async fn watch<T>(self, run: impl Future<Output = IndubitablyResult<T>>) -> IndubitablyResult<T> {
+        let started = tokio::time::Instant::now();
+        let deadline = self.config.hard_ceiling.map(|ceiling| started + ceiling);
+        let mut next_heartbeat = started + self.config.heartbeat_after;
+        tokio::pin!(run);
+        loop {
+            let wake = deadline.map_or(next_heartbeat, |deadline| deadline.min(next_heartbeat));
+            tokio::select! {
+                result = &mut run => return result,
+                _ = tokio::time::sleep_until(wake) => {}
+            }
+            let diagnostics = self.probe.diagnostics();
+            let data = diagnostics.as_ref().map(RunDiagnostics::to_json).unwrap_or_else(|| serde_json::json!({}));
+            match deadline {
+                Some(deadline) if tokio::time::Instant::now() >= deadline => {
+                    let ceiling = self.config.hard_ceiling.unwrap_or_default();
+                    let mut data = data;
+                    data["ceiling_ms"] = serde_json::json!(ceiling.as_millis() as u64);
+                    self.publish(LifecycleEventKind::RunAborted, data.clone()).await;
+                    self.publish(LifecycleEventKind::RunCompleted, serde_json::json!({"outcome": "timed_out"})).await;
+                    self.probe.finish();
+                    return Err(EventLoopError::WatchdogTimeout(format!(
+                        "run exceeded its hard ceiling of {}ms: {}",
+                        ceiling.as_millis(),
+                        data
+                    ))
+                    .into());
+                }
+                _ => {
+                    self.publish(LifecycleEventKind::RunHeartbeat, data).await;
+                    next_heartbeat += self.config.heartbeat_interval;
+                }
+            }
+        }
+    }
+
+    async fn publish(&self, kind: LifecycleEventKind, data: Value) {
+        let mut data = data;
+        if let Value::Object(ref mut fields) = data {
+            fields.extend(self.context.clone());
+        }
+        self.events.publish(LifecycleEvent::new(kind, &self.source, data)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watchdog_sends_heartbeats_then_aborts() {
+        let bus = EventBus::new();
+        let recorder = crate::telemetry::events::EventRecorder::new();
+        bus.subscribe(Arc::new(recorder.clone()));
+        let probe = RunProbe::default();
+        probe.start(Some("m1"));
+        probe.enter(RunPhase::ModelCall);
+        let watchdog = Watchdog {
+            config: LivenessConfig::new()
+                .with_heartbeat_after(Duration::from_millis(10))
+                .with_heartbeat_interval(Duration::from_millis(10))
+                .with_hard_ceiling(Duration::from_millis(55)),
+            probe: probe.clone(),
+            events: bus,
+            source: "worker".to_string(),
+            context: Map::new(),
+        };
+
+        let result = watchdog.watch(std::future::pending::<IndubitablyResult<()>>()).await;
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("hard ceiling of 55ms"));
+        assert!(error.contains("\"phase\":\"model_call\""));
+        let kinds = recorder.kinds();
+        assert!(kinds.iter().filter(|kind| **kind == LifecycleEventKind::RunHeartbeat).count() >= 3);
+        assert_eq!(kinds[kinds.len() - 2..], [LifecycleEventKind::RunAborted, LifecycleEventKind::RunCompleted]);
+        assert!(probe.diagnostics().is_none());
+    }
+}
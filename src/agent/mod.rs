@@ -7,11 +7,57 @@ pub mod agent;
 pub mod state;
 pub mod result;
 pub mod conversation_manager;
+pub mod limits;
+pub mod degraded;
+pub mod budget;
+pub mod interrupt;
+pub mod clarification;
+pub mod editing;
+pub mod plan;
+pub mod redaction;
+pub mod guardrail;
+pub mod experiments;
+pub mod compression;
+pub mod debug_bundle;
+pub mod run_options;
+pub mod post_process;
+pub mod snapshot;
+pub mod liveness;
+pub mod validation;
+pub mod transcript;
+pub mod retention;
 
 pub use agent::Agent;
 pub use state::AgentState;
-pub use result::AgentResult;
+pub use result::{AgentResult, StopReason, ToolCallRecord, AGENT_RESULT_SCHEMA_VERSION};
 pub use conversation_manager::{ConversationManager, ConversationManagerConfig};
+pub use limits::{MemoryLimits, TruncationStrategy};
+pub use degraded::{DegradedModeHandler, DegradedResponse, DegradedResponseKind};
+pub use budget::{BudgetStatus, BudgetUsage, ConversationBudget};
+pub use interrupt::{Interrupt, InterruptDecision, InterruptReason, INTERRUPT_APPROVED_MESSAGE};
+pub use clarification::ClarificationPolicy;
+pub use editing::{ConversationFork, EditOptions};
+pub use plan::{ExecutionPlan, PlannedToolCall};
+pub use redaction::{Redaction, SecretRedactor};
+pub use guardrail::{
+    AppealRecord, AppealRoute, DecisionStatus, GuardrailAction, GuardrailDecision, GuardrailLog, GuardrailPolicy,
+    GuardrailStage, MatchedSpan,
+};
+pub use experiments::{
+    Assignment, Experiment, ExperimentOutcomes, ExperimentReport, Experiments, MetricComparison, SignificanceTest,
+    Variant, VariantOutcomes,
+};
+pub use compression::{CompressionConfig, CompressionStats, ContextCompressor};
+pub use debug_bundle::DebugBundle;
+pub use run_options::RunOptions;
+pub use post_process::{
+    LinkValidator, MarkdownNormalizer, PostProcessor, PostProcessorChain, ProfanityFilter, TemplateWrapper,
+};
+pub use snapshot::{AgentSnapshot, AGENT_SNAPSHOT_VERSION};
+pub use liveness::{LivenessConfig, RunDiagnostics, RunPhase};
+pub use validation::{validate_config, ConfigIssue, ConfigReport, ConfigSeverity};
+pub use transcript::{load_transcript, messages_from_openai, parse_transcript, validate_role_order, HistorySource};
+pub use retention::{redact_expired, ExpiredContentIndex, ExpiredTrace, DEFAULT_MIN_MATCHING_TERMS};
 
 // Re-export commonly used types
 pub use agent::{AgentBuilder, ToolCaller};
@@ -0,0 +1,114 @@
+//! Dry-run planning for the SDK.
+//! 
+//! This module provides `ExecutionPlan`, the result of `Agent::plan`. A
+//! dry run drives the model loop as usual but intercepts every tool call:
+//! instead of executing, the agent records the call and tells the model it
+//! was planned, so operators can review the full sequence of tool calls and
+//! the model's reasoning before approving a real run.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// The default maximum number of model calls in a dry run.
+pub const DEFAULT_MAX_PLAN_ROUNDS: usize = 10;
+
+/// The tool result text returned to the model for an intercepted call.
+pub const PLANNED_TOOL_RESULT: &str =
+    "Dry run: this tool call was recorded but not executed. Assume it succeeds and continue with the next step.";
+
+/// A tool call the model proposed during a dry run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedToolCall {
+    /// The position of the call in the plan, starting at 1.
+    pub step: usize,
+    /// The model call that proposed the tool call, starting at 1.
+    pub round: usize,
+    /// The identifier the model assigned to the call.
+    pub tool_use_id: String,
+    /// The name of the tool.
+    pub name: String,
+    /// The arguments the model would pass.
+    pub input: Value,
+    /// The text the model produced alongside the call.
+    pub rationale: String,
+    /// Whether a tool with this name is registered.
+    pub registered: bool,
+    /// Whether the tool declares write, send or delete effects.
+    pub mutating: bool,
+}
+
+/// The tool calls an agent would make for a message, without side effects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    /// The message that was planned.
+    pub request: String,
+    /// The proposed tool calls in order.
+    pub steps: Vec<PlannedToolCall>,
+    /// The answer the model gave after its last proposed call, if any.
+    pub final_response: Option<String>,
+    /// Whether the model finished before the round limit.
+    pub complete: bool,
+}
+
+impl ExecutionPlan {
+    /// Create an empty plan for a message.
+    pub fn new(request: &str) -> Self {
+        Self {
+            request: request.to_string(),
+            steps: Vec::new(),
+            final_response: None,
+            complete: false,
+        }
+    }
+
+    /// Check whether the model proposed no tool calls.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Get the names of the proposed tools in order.
+    pub fn tool_names(&self) -> Vec<&str> {
+        self.steps.iter().map(|step| step.name.as_str()).collect()
+    }
+
+    /// Check whether any proposed call would write, send or delete.
+    pub fn has_mutations(&self) -> bool {
+        self.steps.iter().any(|step| step.mutating)
+    }
+
+    /// Serialize the plan to a JSON string.
+    pub fn to_json(&self) -> IndubitablyResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| IndubitablyError::InternalError(format!("Failed to serialize execution plan: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_execution_plan() {
+        let mut plan = ExecutionPlan::new("Archive old invoices");
+        assert!(plan.is_empty());
+
+        plan.steps.push(PlannedToolCall {
+            step: 1,
+            round: 1,
+            tool_use_id: "call-1".to_string(),
+            name: "list_invoices".to_string(),
+            input: json!({"before": "2025-01-01"}),
+            rationale: "First find the invoices.".to_string(),
+            registered: true,
+            mutating: false,
+        });
+        assert_eq!(plan.tool_names(), vec!["list_invoices"]);
+        assert!(!plan.has_mutations());
+
+        let parsed: ExecutionPlan = serde_json::from_str(&plan.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, plan);
+    }
+}
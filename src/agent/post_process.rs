@@ -0,0 +1,426 @@
+//! Post-processing of final answers.
+//! 
+//! This module provides the `PostProcessor` trait and `PostProcessorChain`,
+//! which an agent runs over the text of each final answer before it is
+//! returned and stored in the conversation. The chain is set with
+//! `AgentConfig::with_post_processor`. Built-in processors normalize
+//! Markdown, strip dead links, mask profanity and wrap answers in a
+//! template. A processor that fails is skipped and the text it was given is
+//! passed on, so post-processing never fails a run.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use regex::{Captures, Regex};
+
+use crate::models::http::{HttpClient, HttpRequest};
+use crate::types::IndubitablyResult;
+
+/// The placeholder replaced by the answer in a `TemplateWrapper` template.
+pub const RESPONSE_PLACEHOLDER: &str = "{{response}}";
+
+/// The words masked by `ProfanityFilter::with_default_words`.
+pub const DEFAULT_PROFANITY: &[&str] = &[
+    "asshole", "bastard", "bitch", "bullshit", "crap", "damn", "dick", "fuck", "fucking", "piss", "shit",
+];
+
+/// Rewrites the text of final answers.
+#[async_trait]
+pub trait PostProcessor: Send + Sync {
+    /// Get the processor's name, used in logs.
+    fn name(&self) -> &str;
+
+    /// Rewrite an answer.
+    async fn process(&self, text: &str) -> IndubitablyResult<String>;
+}
+
+/// The processors an agent runs over each final answer, in order.
+#[derive(Clone, Default)]
+pub struct PostProcessorChain {
+    processors: Vec<Arc<dyn PostProcessor>>,
+}
+
+impl std::fmt::Debug for PostProcessorChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl PostProcessorChain {
+    /// Create an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a processor to the end of the chain.
+    pub fn with(mut self, processor: Arc<dyn PostProcessor>) -> Self {
+        self.push(processor);
+        self
+    }
+
+    /// Add a processor to the end of the chain.
+    pub fn push(&mut self, processor: Arc<dyn PostProcessor>) {
+        self.processors.push(processor);
+    }
+
+    /// Get the names of the processors, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.processors.iter().map(|processor| processor.name()).collect()
+    }
+
+    /// Check whether the chain has no processors.
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Pass an answer through each processor in order, skipping those that fail.
+    pub async fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for processor in &self.processors {
+            match processor.process(&text).await {
+                Ok(processed) => text = processed,
+                Err(e) => tracing::warn!(
+                    "processor=<{}>, error=<{}> | post-processor failed, skipping it",
+                    processor.name(),
+                    e
+                ),
+            }
+        }
+        text
+    }
+}
+
+/// Tidies Markdown: trailing whitespace, runs of blank lines, bullet markers and unclosed code fences.
+///
+/// Code blocks are left untouched apart from closing a fence the model left open.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownNormalizer;
+
+impl MarkdownNormalizer {
+    /// Create a normalizer.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Normalize Markdown text.
+    pub fn normalize(&self, text: &str) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut fence: Option<&str> = None;
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            let marker = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker));
+            if let Some(open) = fence {
+                if marker == Some(open) {
+                    fence = None;
+                }
+                lines.push(line.to_string());
+                continue;
+            }
+            if marker.is_some() {
+                fence = marker;
+                lines.push(line.trim_end().to_string());
+                continue;
+            }
+
+            let line = line.trim_end();
+            if line.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+                continue;
+            }
+            let indent = &line[..line.len() - trimmed.trim_end().len()];
+            match trimmed.strip_prefix("* ").or_else(|| trimmed.strip_prefix("+ ")) {
+                Some(item) => lines.push(format!("{}- {}", indent, item)),
+                None => lines.push(line.to_string()),
+            }
+        }
+        if let Some(open) = fence {
+            lines.push(open.to_string());
+        }
+        while lines.last().is_some_and(|last| last.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+}
+
+#[async_trait]
+impl PostProcessor for MarkdownNormalizer {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    async fn process(&self, text: &str) -> IndubitablyResult<String> {
+        Ok(self.normalize(text))
+    }
+}
+
+/// Removes links that are dead or point outside the allowed domains.
+///
+/// Markdown links to dead targets are replaced by their text, and dead bare
+/// URLs are removed. Links are checked with a HEAD request when a client is
+/// set, and are dead if the request fails or answers with a 4xx or 5xx status.
+pub struct LinkValidator {
+    client: Option<Arc<dyn HttpClient>>,
+    allowed_domains: Vec<String>,
+    markdown_link: Regex,
+    bare_url: Regex,
+}
+
+impl std::fmt::Debug for LinkValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LinkValidator")
+            .field("checks_links", &self.client.is_some())
+            .field("allowed_domains", &self.allowed_domains)
+            .finish()
+    }
+}
+
+impl Default for LinkValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinkValidator {
+    /// Create a validator that keeps every link.
+    pub fn new() -> Self {
+        Self {
+            client: None,
+            allowed_domains: Vec::new(),
+            markdown_link: Regex::new(r"\[([^\]]*)\]\((https?://[^\s)]+)\)").expect("markdown link pattern is valid"),
+            bare_url: Regex::new(r"https?://[^\s<>()\[\]]+").expect("URL pattern is valid"),
+        }
+    }
+
+    /// Check each link with a HEAD request through the given client.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Keep only links to the given domains and their subdomains.
+    pub fn with_allowed_domains(mut self, domains: &[&str]) -> Self {
+        self.allowed_domains = domains.iter().map(|domain| domain.to_lowercase()).collect();
+        self
+    }
+
+    fn is_allowed(&self, url: &str) -> bool {
+        if self.allowed_domains.is_empty() {
+            return true;
+        }
+        let host = url
+            .split("://")
+            .nth(1)
+            .and_then(|rest| rest.split(['/', ':', '?', '#']).next())
+            .unwrap_or_default()
+            .to_lowercase();
+        self.allowed_domains
+            .iter()
+            .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+    }
+
+    /// Check whether a link is live.
+    pub async fn is_live(&self, url: &str) -> bool {
+        if !self.is_allowed(url) {
+            return false;
+        }
+        match self.client {
+            Some(ref client) => match client.send(HttpRequest::new("HEAD", url)).await {
+                Ok(response) => response.status < 400,
+                Err(_) => false,
+            },
+            None => true,
+        }
+    }
+}
+
+/// Split the punctuation ending a sentence off a bare URL.
+fn split_trailing_punctuation(url: &str) -> (&str, &str) {
+    let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"']);
+    (trimmed, &url[trimmed.len()..])
+}
+
+#[async_trait]
+impl PostProcessor for LinkValidator {
+    fn name(&self) -> &str {
+        "links"
+    }
+
+    async fn process(&self, text: &str) -> IndubitablyResult<String> {
+        let mut urls: Vec<String> = self
+            .markdown_link
+            .captures_iter(text)
+            .map(|captures| captures[2].to_string())
+            .chain(self.bare_url.find_iter(text).map(|found| split_trailing_punctuation(found.as_str()).0.to_string()))
+            .collect();
+        urls.sort();
+        urls.dedup();
+        let mut live: HashMap<String, bool> = HashMap::new();
+        for url in urls {
+            let is_live = self.is_live(&url).await;
+            if !is_live {
+                tracing::debug!("url=<{}> | removing dead link from answer", url);
+            }
+            live.insert(url, is_live);
+        }
+
+        let is_live = |url: &str| live.get(url).copied().unwrap_or(true);
+        let text = self.markdown_link.replace_all(text, |captures: &Captures| {
+            if is_live(&captures[2]) {
+                captures[0].to_string()
+            } else {
+                captures[1].to_string()
+            }
+        });
+        let text = self.bare_url.replace_all(&text, |captures: &Captures| {
+            let (url, punctuation) = split_trailing_punctuation(&captures[0]);
+            if is_live(url) {
+                captures[0].to_string()
+            } else {
+                punctuation.to_string()
+            }
+        });
+        Ok(text.into_owned())
+    }
+}
+
+/// Masks profane words, keeping their first letter.
+#[derive(Debug, Clone)]
+pub struct ProfanityFilter {
+    pattern: Option<Regex>,
+}
+
+impl ProfanityFilter {
+    /// Create a filter masking the given words, matched whole and ignoring case.
+    pub fn new(words: &[&str]) -> Self {
+        let words: Vec<String> = words.iter().filter(|word| !word.is_empty()).map(|word| regex::escape(word)).collect();
+        let pattern = (!words.is_empty())
+            .then(|| Regex::new(&format!(r"(?i)\b(?:{})\b", words.join("|"))).expect("escaped words form a valid pattern"));
+        Self { pattern }
+    }
+
+    /// Create a filter masking the words in `DEFAULT_PROFANITY`.
+    pub fn with_default_words() -> Self {
+        Self::new(DEFAULT_PROFANITY)
+    }
+
+    /// Mask the profane words in text.
+    pub fn mask(&self, text: &str) -> String {
+        let Some(ref pattern) = self.pattern else {
+            return text.to_string();
+        };
+        pattern
+            .replace_all(text, |captures: &Captures| {
+                let mut chars = captures[0].chars();
+                let first = chars.next().map(String::from).unwrap_or_default();
+                first + &"*".repeat(chars.count())
+            })
+            .into_owned()
+    }
+}
+
+#[async_trait]
+impl PostProcessor for ProfanityFilter {
+    fn name(&self) -> &str {
+        "profanity"
+    }
+
+    async fn process(&self, text: &str) -> IndubitablyResult<String> {
+        Ok(self.mask(text))
+    }
+}
+
+/// Wraps answers in a template, replacing `{{response}}` and `{{name}}` variables.
+#[derive(Debug, Clone)]
+pub struct TemplateWrapper {
+    template: String,
+    variables: Vec<(String, String)>,
+}
+
+impl TemplateWrapper {
+    /// Create a wrapper with a template containing `RESPONSE_PLACEHOLDER`.
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+            variables: Vec::new(),
+        }
+    }
+
+    /// Set a variable substituted for `{{name}}`.
+    pub fn with_variable(mut self, name: &str, value: &str) -> Self {
+        self.variables.push((format!("{{{{{}}}}}", name), value.to_string()));
+        self
+    }
+
+    /// Wrap an answer.
+    pub fn wrap(&self, text: &str) -> String {
+        // Variables are substituted before the answer so text in the answer is never expanded
+        let template = self
+            .variables
+            .iter()
+            .fold(self.template.clone(), |template, (placeholder, value)| template.replace(placeholder, value));
+        template.replace(RESPONSE_PLACEHOLDER, text)
+    }
+}
+
+#[async_trait]
+impl PostProcessor for TemplateWrapper {
+    fn name(&self) -> &str {
+        "template"
+    }
+
+    async fn process(&self, text: &str) -> IndubitablyResult<String> {
+        Ok(self.wrap(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::models::http::HttpResponse;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    /// Answers 404 for URLs containing "missing" and 200 otherwise.
+    struct LinkChecker;
+
+    #[async_trait]
+    impl HttpClient for LinkChecker {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert_eq!(request.method, "HEAD");
+            let status = if request.url.contains("missing") { 404 } else { 200 };
+            Ok(HttpResponse::new(status, Vec::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_processors_rewrite_final_answers() {
+        let markdown = MarkdownNormalizer::new().normalize("# Steps  \n\n\n\n* one\n  + two\n```rust\n*  keep  \n");
+        assert_eq!(markdown, "# Steps\n\n- one\n  - two\n```rust\n*  keep  \n```");
+
+        let links = LinkValidator::new().with_client(Arc::new(LinkChecker));
+        let text = links
+            .process("See [the docs](https://docs.rs/ok) and [old notes](https://example.com/missing), or https://example.com/missing.")
+            .await
+            .unwrap();
+        assert_eq!(text, "See [the docs](https://docs.rs/ok) and old notes, or .");
+        let allowlisted = LinkValidator::new().with_allowed_domains(&["docs.rs"]);
+        assert!(allowlisted.is_live("https://api.docs.rs/crate").await);
+        assert!(!allowlisted.is_live("https://docs.rs.evil.com").await);
+
+        assert_eq!(ProfanityFilter::with_default_words().mask("Damn, that shitty crap."), "D***, that shitty c***.");
+        let template = TemplateWrapper::new("{{agent}}: {{response}}").with_variable("agent", "Support");
+        assert_eq!(template.wrap("Use {{agent}}"), "Support: Use {{agent}}");
+
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("* Damn, restart it.\n\n\n")]);
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .post_processor(Arc::new(MarkdownNormalizer::new()))
+            .post_processor(Arc::new(ProfanityFilter::with_default_words()))
+            .post_processor(Arc::new(TemplateWrapper::new("{{response}}\n\n_Sent by the support bot_")))
+            .build()
+            .unwrap();
+        let result = agent.run("My router is down").await.unwrap();
+        assert_eq!(result.response, "- D***, restart it.\n\n_Sent by the support bot_");
+    }
+}
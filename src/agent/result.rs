@@ -2,10 +2,100 @@
 //! 
 //! This module defines the result structures returned by agents
 //! after processing messages and executing tools.
+//! 
+//! Results are persisted and read by other services, so their JSON form is
+//! a documented, versioned schema rather than a mirror of the struct:
+//! `to_json` writes the current `AGENT_RESULT_SCHEMA_VERSION`, `from_json`
+//! reads every version up to it and ignores fields it does not know, and
+//! `json_schema` exports the JSON Schema of the current version. Fields are
+//! only ever added within a version; renaming or removing one bumps it.
+
+use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::models::model::ModelUsage;
+use crate::telemetry::timeline::{Timeline, TimelineSpan};
+use crate::tools::workspace::WorkspaceFile;
+use crate::types::{Citation, ImageContent, IndubitablyError, IndubitablyResult, Message, Messages, ToolSpec};
+use super::interrupt::Interrupt;
+
+/// The version of the JSON schema written by `AgentResult::to_json`.
+pub const AGENT_RESULT_SCHEMA_VERSION: u32 = 1;
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The model finished its answer.
+    #[default]
+    EndTurn,
+    /// The model hit its output token limit.
+    MaxTokens,
+    /// The run paused for input from the user.
+    Interrupted,
+    /// The model was unavailable and a fallback answered.
+    Degraded,
+    /// A guardrail blocked the user's message or the model's answer.
+    GuardrailBlocked,
+}
+
+/// A tool call made during a run, with its outcome.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    /// The tool use ID assigned by the model.
+    pub tool_use_id: String,
+    /// The tool name.
+    pub name: String,
+    /// The input the model passed.
+    #[serde(default)]
+    pub input: Value,
+    /// The text returned to the model.
+    #[serde(default)]
+    pub output: String,
+    /// Whether the tool returned an error.
+    #[serde(default)]
+    pub is_error: bool,
+    /// How long the call took, in microseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_us: Option<u64>,
+}
 
-use crate::types::{Message, Messages, ToolSpec};
+/// The persisted form of an `AgentResult`.
+#[derive(Serialize, Deserialize)]
+struct AgentResultDocument {
+    schema_version: u32,
+    agent_id: String,
+    created_at: DateTime<Utc>,
+    response: String,
+    response_message: Message,
+    #[serde(default)]
+    stop_reason: StopReason,
+    #[serde(default)]
+    usage: Option<ModelUsage>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCallRecord>,
+    #[serde(default)]
+    artifacts: Vec<WorkspaceFile>,
+    #[serde(default)]
+    images: Vec<ImageContent>,
+    #[serde(default)]
+    citations: Vec<Citation>,
+    #[serde(default)]
+    interrupt: Option<Interrupt>,
+    #[serde(default)]
+    conversation_context: Messages,
+    #[serde(default)]
+    messages: Messages,
+    #[serde(default)]
+    available_tools: Vec<ToolSpec>,
+    #[serde(default)]
+    metadata: HashMap<String, Value>,
+    #[serde(default)]
+    timeline: Vec<TimelineSpan>,
+}
 
 /// The result of an agent's processing.
 #[derive(Debug, Clone)]
@@ -26,6 +116,22 @@ pub struct AgentResult {
     pub created_at: DateTime<Utc>,
     /// Additional metadata for the result.
     pub metadata: std::collections::HashMap<String, serde_json::Value>,
+    /// The interrupt that paused the run, if any.
+    pub interrupt: Option<Interrupt>,
+    /// The sources supporting the response.
+    pub citations: Vec<Citation>,
+    /// The timed model calls and tool executions of the run.
+    pub timeline: Timeline,
+    /// The tool calls made during the run, in order.
+    pub tool_calls: Vec<ToolCallRecord>,
+    /// The tokens used by all model calls of the run.
+    pub usage: Option<ModelUsage>,
+    /// Why the run stopped.
+    pub stop_reason: StopReason,
+    /// The files the run created or changed in its working directory.
+    pub artifacts: Vec<WorkspaceFile>,
+    /// The images generated by tools during the run.
+    pub images: Vec<ImageContent>,
 }
 
 impl AgentResult {
@@ -47,6 +153,14 @@ impl AgentResult {
             available_tools,
             created_at: Utc::now(),
             metadata: std::collections::HashMap::new(),
+            interrupt: None,
+            citations: Vec::new(),
+            timeline: Timeline::default(),
+            tool_calls: Vec::new(),
+            usage: None,
+            stop_reason: StopReason::default(),
+            artifacts: Vec::new(),
+            images: Vec::new(),
         }
     }
 
@@ -91,6 +205,202 @@ impl AgentResult {
         self
     }
 
+    /// Set the interrupt that paused the run.
+    pub fn with_interrupt(mut self, interrupt: Interrupt) -> Self {
+        self.interrupt = Some(interrupt);
+        self
+    }
+
+    /// Check if the run was interrupted and needs input from the user.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupt.is_some()
+    }
+
+    /// Set the sources supporting the response.
+    pub fn with_citations(mut self, citations: Vec<Citation>) -> Self {
+        self.citations = citations;
+        self
+    }
+
+    /// Get the sources supporting the response.
+    pub fn citations(&self) -> &[Citation] {
+        &self.citations
+    }
+
+    /// Set the timeline of the run.
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    /// Set the tool calls made during the run.
+    pub fn with_tool_calls(mut self, tool_calls: Vec<ToolCallRecord>) -> Self {
+        self.tool_calls = tool_calls;
+        self
+    }
+
+    /// Set the tokens used by the run.
+    pub fn with_usage(mut self, usage: ModelUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Set why the run stopped.
+    pub fn with_stop_reason(mut self, stop_reason: StopReason) -> Self {
+        self.stop_reason = stop_reason;
+        self
+    }
+
+    /// Set the files the run created or changed.
+    pub fn with_artifacts(mut self, artifacts: Vec<WorkspaceFile>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    /// Set the images generated by tools during the run.
+    pub fn with_images(mut self, images: Vec<ImageContent>) -> Self {
+        self.images = images;
+        self
+    }
+
+    /// Serialize the result in the current versioned JSON schema.
+    pub fn to_json(&self) -> IndubitablyResult<String> {
+        let document = AgentResultDocument {
+            schema_version: AGENT_RESULT_SCHEMA_VERSION,
+            agent_id: self.agent_id.clone(),
+            created_at: self.created_at,
+            response: self.response.clone(),
+            response_message: self.response_message.clone(),
+            stop_reason: self.stop_reason,
+            usage: self.usage.clone(),
+            tool_calls: self.tool_calls.clone(),
+            artifacts: self.artifacts.clone(),
+            images: self.images.clone(),
+            citations: self.citations.clone(),
+            interrupt: self.interrupt.clone(),
+            conversation_context: self.conversation_context.clone(),
+            messages: self.messages.clone(),
+            available_tools: self.available_tools.clone(),
+            metadata: self.metadata.clone(),
+            timeline: self.timeline.spans.clone(),
+        };
+        Ok(serde_json::to_string(&document)?)
+    }
+
+    /// Read a result written by `to_json` with this or an earlier schema version.
+    pub fn from_json(json: &str) -> IndubitablyResult<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let version = value.get("schema_version").and_then(Value::as_u64).ok_or_else(|| {
+            IndubitablyError::ValidationError("Agent result JSON has no schema_version".to_string())
+        })?;
+        if version == 0 || version > u64::from(AGENT_RESULT_SCHEMA_VERSION) {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Agent result schema version {} is not supported; this SDK reads versions 1 to {}",
+                version, AGENT_RESULT_SCHEMA_VERSION
+            )));
+        }
+
+        let document: AgentResultDocument = serde_json::from_value(value)?;
+        let mut timeline = Timeline::new(&document.agent_id);
+        timeline.started_at = document.created_at;
+        timeline.spans = document.timeline;
+        Ok(Self {
+            agent_id: document.agent_id,
+            conversation_context: document.conversation_context,
+            response_message: document.response_message,
+            response: document.response,
+            messages: document.messages,
+            available_tools: document.available_tools,
+            created_at: document.created_at,
+            metadata: document.metadata,
+            interrupt: document.interrupt,
+            citations: document.citations,
+            timeline,
+            tool_calls: document.tool_calls,
+            usage: document.usage,
+            stop_reason: document.stop_reason,
+            artifacts: document.artifacts,
+            images: document.images,
+        })
+    }
+
+    /// Get the JSON Schema of the current result version.
+    ///
+    /// Messages, tool specs, citations and interrupts follow the SDK's own
+    /// serde representations and are described as objects.
+    pub fn json_schema() -> Value {
+        let object = json!({ "type": "object" });
+        let objects = json!({ "type": "array", "items": { "type": "object" } });
+        let count = json!({ "type": "integer", "minimum": 0 });
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": format!("https://indubitably.ai/schemas/agent-result/v{}.json", AGENT_RESULT_SCHEMA_VERSION),
+            "title": "AgentResult",
+            "type": "object",
+            "required": ["schema_version", "agent_id", "created_at", "response", "response_message"],
+            "properties": {
+                "schema_version": { "const": AGENT_RESULT_SCHEMA_VERSION },
+                "agent_id": { "type": "string" },
+                "created_at": { "type": "string", "format": "date-time" },
+                "response": { "type": "string" },
+                "response_message": object,
+                "stop_reason": { "enum": ["end_turn", "max_tokens", "interrupted", "degraded"] },
+                "usage": {
+                    "type": ["object", "null"],
+                    "required": ["input_tokens", "output_tokens", "total_tokens"],
+                    "properties": {
+                        "input_tokens": count,
+                        "output_tokens": count,
+                        "total_tokens": count,
+                        "reasoning_tokens": count,
+                    },
+                },
+                "tool_calls": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["tool_use_id", "name"],
+                        "properties": {
+                            "tool_use_id": { "type": "string" },
+                            "name": { "type": "string" },
+                            "input": {},
+                            "output": { "type": "string" },
+                            "is_error": { "type": "boolean" },
+                            "duration_us": count,
+                        },
+                    },
+                },
+                "artifacts": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "path", "size_bytes", "media_type"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "path": { "type": "string" },
+                            "size_bytes": count,
+                            "media_type": { "type": "string" },
+                            "content": { "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } },
+                        },
+                    },
+                },
+                "images": objects,
+                "citations": objects,
+                "interrupt": { "type": ["object", "null"] },
+                "conversation_context": objects,
+                "messages": objects,
+                "available_tools": objects,
+                "metadata": object,
+                "timeline": objects,
+            },
+        })
+    }
+
+    /// Export the run timeline as Chrome trace-event JSON for Perfetto or `chrome://tracing`.
+    pub fn to_trace_json(&self) -> IndubitablyResult<String> {
+        self.timeline.to_trace_json()
+    }
+
     /// Get metadata by key.
     pub fn get_metadata(&self, key: &str) -> Option<&serde_json::Value> {
         self.metadata.get(key)
@@ -123,6 +433,14 @@ impl Default for AgentResult {
             available_tools: Vec::new(),
             created_at: Utc::now(),
             metadata: std::collections::HashMap::new(),
+            interrupt: None,
+            citations: Vec::new(),
+            timeline: Timeline::default(),
+            tool_calls: Vec::new(),
+            usage: None,
+            stop_reason: StopReason::default(),
+            artifacts: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -179,6 +497,40 @@ mod tests {
         assert_eq!(result.tool_count(), 0);
     }
 
+    #[test]
+    fn test_agent_result_json_compatibility() {
+        // A result written by schema version 1; it must keep reading as-is
+        let v1 = r#"{
+            "schema_version": 1,
+            "agent_id": "support",
+            "created_at": "2026-01-05T10:00:00Z",
+            "response": "It is sunny.",
+            "response_message": {"role": "assistant", "content": [{"text": "It is sunny."}]},
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 30, "output_tokens": 8, "total_tokens": 38},
+            "tool_calls": [{"tool_use_id": "call-1", "name": "lookup", "input": {"city": "Paris"}, "output": "sunny", "is_error": false, "duration_us": 1200}],
+            "added_in_a_later_release": true
+        }"#;
+        let result = AgentResult::from_json(v1).unwrap();
+        assert_eq!(result.agent_id(), "support");
+        assert_eq!(result.response_message().role, MessageRole::Assistant);
+        assert_eq!(result.usage.as_ref().map(|usage| usage.total_tokens), Some(38));
+        assert_eq!(result.tool_calls[0].input, serde_json::json!({"city": "Paris"}));
+        assert!(result.messages().is_empty());
+
+        let reread = AgentResult::from_json(&result.with_stop_reason(StopReason::MaxTokens).to_json().unwrap()).unwrap();
+        assert_eq!(reread.stop_reason, StopReason::MaxTokens);
+        assert_eq!(reread.tool_calls[0].duration_us, Some(1200));
+        assert_eq!(reread.created_at().to_rfc3339(), "2026-01-05T10:00:00+00:00");
+
+        let document: Value = serde_json::from_str(&reread.to_json().unwrap()).unwrap();
+        let schema = AgentResult::json_schema();
+        for key in document.as_object().unwrap().keys() {
+            assert!(schema["properties"].get(key).is_some(), "{} is missing from the schema", key);
+        }
+        assert!(AgentResult::from_json(&v1.replace("\"schema_version\": 1", "\"schema_version\": 2")).is_err());
+    }
+
     #[test]
     fn test_agent_result_conversation() {
         let conversation_context = vec![Message::user("Hello"), Message::assistant("Hi!")];
@@ -0,0 +1,194 @@
+//! Message retention for the SDK.
+//! 
+//! Messages can carry a TTL, set with `Message::with_ttl`, for content that
+//! should not stay in a conversation for long, such as uploaded documents.
+//! Before each run the agent strips the content of expired messages from the
+//! active context. Each expired message keeps its role and tool call ids with
+//! a placeholder in place of its content, so the turn order stays valid for
+//! providers. Persisted copies are removed by the session `RetentionJanitor`.
+//! 
+//! The agent does not keep what it removed. It keeps keyed hashes of the
+//! terms in it, which are enough to notice when a new query touches the same
+//! subject and emit an `expired_content_relevant` event, so applications can
+//! ask the user to share the content again.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::crypto::hmac_sha256;
+use crate::types::{
+    ContentBlock, Message, Messages, ToolResultContent, EXPIRED_CONTENT_PLACEHOLDER, MESSAGE_EXPIRED_AT_KEY,
+    MESSAGE_EXPIRES_AT_KEY,
+};
+
+/// The number of query terms that must match an expired message for it to count as relevant.
+pub const DEFAULT_MIN_MATCHING_TERMS: usize = 2;
+
+/// The shortest word counted as a term; shorter words are too common to signal relevance.
+const MIN_TERM_LEN: usize = 4;
+
+/// Strip the content of an expired message, keeping its role, metadata and tool call ids.
+pub fn redact_expired(message: &Message, now: DateTime<Utc>) -> Message {
+    let mut content = Vec::new();
+    for block in &message.content {
+        if let Some(ref tool_use) = block.tool_use {
+            let mut tool_use = tool_use.clone();
+            tool_use.input = Some(Value::Object(Default::default()));
+            content.push(ContentBlock {
+                tool_use: Some(tool_use),
+                ..Default::default()
+            });
+        } else if let Some(ref result) = block.tool_result {
+            let mut result = result.clone();
+            result.content = vec![ToolResultContent::text(EXPIRED_CONTENT_PLACEHOLDER)];
+            content.push(ContentBlock {
+                tool_result: Some(result),
+                ..Default::default()
+            });
+        }
+    }
+    // Tool results must come alone, so only other messages get a text placeholder
+    if content.iter().all(|block| block.tool_result.is_none()) {
+        content.insert(0, ContentBlock::from_text(EXPIRED_CONTENT_PLACEHOLDER));
+    }
+
+    let mut metadata = message.metadata.clone().unwrap_or_default();
+    metadata.remove(MESSAGE_EXPIRES_AT_KEY);
+    metadata.insert(MESSAGE_EXPIRED_AT_KEY.to_string(), Value::String(now.to_rfc3339()));
+    Message {
+        role: message.role.clone(),
+        content,
+        metadata: Some(metadata),
+    }
+}
+
+/// What is remembered about an expired message: keyed term hashes, never its content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiredTrace {
+    /// The ID of the expired message, if it had one.
+    pub message_id: Option<String>,
+    /// When the message's content was removed.
+    pub expired_at: DateTime<Utc>,
+    terms: HashSet<[u8; 32]>,
+}
+
+/// Remembers the messages aged out of a conversation to spot queries that touch them.
+#[derive(Debug, Clone)]
+pub struct ExpiredContentIndex {
+    /// The key the term hashes are made with, so they cannot be looked up in a dictionary.
+    key: [u8; 16],
+    traces: Vec<ExpiredTrace>,
+    min_matching_terms: usize,
+}
+
+impl Default for ExpiredContentIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpiredContentIndex {
+    /// Create an empty index with a fresh hashing key.
+    pub fn new() -> Self {
+        Self {
+            key: *uuid::Uuid::new_v4().as_bytes(),
+            traces: Vec::new(),
+            min_matching_terms: DEFAULT_MIN_MATCHING_TERMS,
+        }
+    }
+
+    /// Set how many query terms must match an expired message for it to count as relevant.
+    pub fn with_min_matching_terms(mut self, min_matching_terms: usize) -> Self {
+        self.min_matching_terms = min_matching_terms.max(1);
+        self
+    }
+
+    /// Get the traces of the messages aged out so far.
+    pub fn traces(&self) -> &[ExpiredTrace] {
+        &self.traces
+    }
+
+    /// Strip the content of the messages expired at `now`, remembering their terms.
+    ///
+    /// Returns the messages with expired ones redacted and the number redacted.
+    pub fn age_out(&mut self, messages: Messages, now: DateTime<Utc>) -> (Messages, usize) {
+        let mut expired = 0;
+        let messages = messages
+            .into_iter()
+            .map(|message| {
+                if !message.is_expired_at(now) {
+                    return message;
+                }
+                expired += 1;
+                let mut text = vec![message.all_text()];
+                for result in message.tool_result_blocks() {
+                    text.extend(result.content.iter().filter_map(|content| content.text.clone()));
+                }
+                self.traces.push(ExpiredTrace {
+                    message_id: message.id().map(str::to_string),
+                    expired_at: now,
+                    terms: self.hash_terms(&text.join(" ")),
+                });
+                redact_expired(&message, now)
+            })
+            .collect();
+        (messages, expired)
+    }
+
+    /// Find the expired messages a query shares enough terms with, and how many terms each shares.
+    pub fn relevant_to(&self, query: &str) -> Vec<(&ExpiredTrace, usize)> {
+        let terms = self.hash_terms(query);
+        self.traces
+            .iter()
+            .map(|trace| (trace, trace.terms.intersection(&terms).count()))
+            .filter(|(_, matching)| *matching >= self.min_matching_terms)
+            .collect()
+    }
+
+    fn hash_terms(&self, text: &str) -> HashSet<[u8; 32]> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.chars().count() >= MIN_TERM_LEN)
+            .map(|word| hmac_sha256(&self.key, word.to_lowercase().as_bytes()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ToolResult, ToolUse};
+    use chrono::Duration;
+
+    #[test]
+    fn test_age_out_redacts_expired_messages_and_spots_relevant_queries() {
+        let now = Utc::now();
+        let messages = vec![
+            Message::user("Here is my lease agreement for the Maple Street apartment.")
+                .with_id("m1")
+                .with_expires_at(now - Duration::minutes(1)),
+            Message::assistant_with_tool_uses("", vec![ToolUse::new("read_pdf", "call-1")])
+                .with_expires_at(now - Duration::minutes(1)),
+            Message::tool_results(vec![ToolResult::new("call-1", vec![ToolResultContent::text("Rent: 1200")])])
+                .with_expires_at(now - Duration::minutes(1)),
+            Message::assistant("The lease runs for a year.").with_expires_at(now + Duration::hours(1)),
+        ];
+        let mut index = ExpiredContentIndex::new();
+        let (aged, expired) = index.age_out(messages.clone(), now);
+
+        assert_eq!(expired, 3);
+        assert_eq!(aged[0].text(), Some(EXPIRED_CONTENT_PLACEHOLDER));
+        assert_eq!(aged[0].id(), Some("m1"));
+        assert!(aged[0].expires_at().is_none());
+        assert_eq!(aged[1].tool_uses()[0].tool_use_id, "call-1");
+        assert_eq!(aged[2].tool_result_blocks()[0].content[0].text.as_deref(), Some(EXPIRED_CONTENT_PLACEHOLDER));
+        assert_eq!(aged[3], messages[3]);
+        crate::agent::validate_role_order(&aged).unwrap();
+
+        let relevant = index.relevant_to("When does the Maple Street lease end?");
+        assert_eq!(relevant.len(), 1);
+        assert_eq!(relevant[0].0.message_id.as_deref(), Some("m1"));
+        assert!(index.relevant_to("What is the weather like?").is_empty());
+    }
+}
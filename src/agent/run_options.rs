@@ -0,0 +1,40 @@
+//! Per-run options for agents.
+//! 
+//! This module provides `RunOptions`, passed to `Agent::run_with_options`
+//! to control how a single run is carried out, such as the priority its
+//! model calls are scheduled with by a shared `RateLimiter` or the tools a
+//! multi-tenant server lets the request's tenant see.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::rate_limit::Priority;
+use crate::tools::registry::ToolFilter;
+
+/// Options for a single run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunOptions {
+    /// The priority of the run's model calls in the agent's rate limiter.
+    pub priority: Priority,
+    /// The filter narrowing the tools visible during the run, on top of the agent's own.
+    #[serde(default)]
+    pub tool_filter: Option<ToolFilter>,
+}
+
+impl RunOptions {
+    /// Create new run options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the priority of the run's model calls.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Narrow the tools visible during the run, such as to the requesting tenant's namespaces.
+    pub fn with_tool_filter(mut self, filter: ToolFilter) -> Self {
+        self.tool_filter = Some(filter);
+        self
+    }
+}
@@ -0,0 +1,92 @@
+//! Snapshots of an agent's runtime state.
+//! 
+//! This module provides `AgentSnapshot`, produced by `Agent::snapshot` and
+//! applied with `Agent::restore`, so a conversation can move between
+//! processes, such as during a deploy, without losing context. A snapshot
+//! holds the conversation, the `AgentState`, the interrupt awaiting an
+//! answer, the budget usage, the guardrail decisions, the user and
+//! experiment assignment, the conversation forks and the tool selector's
+//! cache. Configuration, models and tools are not captured; the restoring
+//! process builds its agent as usual and then restores the snapshot into it.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::budget::BudgetUsage;
+use super::editing::ConversationFork;
+use super::guardrail::GuardrailLog;
+use super::interrupt::Interrupt;
+use super::state::AgentState;
+use crate::types::{IndubitablyError, IndubitablyResult, Messages};
+
+/// The version of the snapshot format written by this SDK.
+pub const AGENT_SNAPSHOT_VERSION: u32 = 1;
+
+/// The runtime state of an agent, serializable for moving a conversation between processes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSnapshot {
+    /// The snapshot format version.
+    pub version: u32,
+    /// The name of the agent the snapshot was taken from.
+    pub agent_name: String,
+    /// When the snapshot was taken.
+    pub taken_at: DateTime<Utc>,
+    /// The conversation messages.
+    pub conversation: Messages,
+    /// The agent state.
+    pub state: AgentState,
+    /// The interrupt of the last run, awaiting an answer from the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_interrupt: Option<Interrupt>,
+    /// The usage counted against the conversation budget.
+    #[serde(default)]
+    pub budget_usage: BudgetUsage,
+    /// Whether the budget warning was already raised.
+    #[serde(default)]
+    pub budget_warned: bool,
+    /// The guardrail decisions made in the agent's runs.
+    #[serde(default)]
+    pub guardrail_log: GuardrailLog,
+    /// The user the agent talks to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// The user's session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// The experiment variants the agent runs under, keyed by experiment name.
+    #[serde(default)]
+    pub experiment_labels: BTreeMap<String, String>,
+    /// The copies of the conversation taken before it was rewound.
+    #[serde(default)]
+    pub forks: Vec<ConversationFork>,
+    /// The tool selector's cache, such as tool embeddings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_cache: Option<serde_json::Value>,
+}
+
+impl AgentSnapshot {
+    /// Serialize the snapshot as JSON.
+    pub fn to_bytes(&self) -> IndubitablyResult<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Read a snapshot written by `to_bytes`, rejecting newer formats.
+    pub fn from_bytes(data: &[u8]) -> IndubitablyResult<Self> {
+        let snapshot: Self = serde_json::from_slice(data)?;
+        snapshot.check_version()?;
+        Ok(snapshot)
+    }
+
+    /// Fail if the snapshot was written in a newer format than this SDK reads.
+    pub fn check_version(&self) -> IndubitablyResult<()> {
+        if self.version > AGENT_SNAPSHOT_VERSION {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Agent snapshot version {} is newer than the supported version {}",
+                self.version, AGENT_SNAPSHOT_VERSION
+            )));
+        }
+        Ok(())
+    }
+}
@@ -5,11 +5,14 @@
 
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-use crate::types::{Message, Messages};
+use crate::types::{Message, Messages, IndubitablyResult, IndubitablyError};
 
 /// The internal state of an agent.
-#[derive(Debug, Clone)]
+///
+/// The size limit and dirty flag are not serialized; they belong to the agent holding the state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentState {
     /// The messages in the conversation.
     messages: Messages,
@@ -19,6 +22,12 @@ pub struct AgentState {
     updated_at: DateTime<Utc>,
     /// Additional metadata for the agent.
     metadata: HashMap<String, serde_json::Value>,
+    /// The maximum serialized size of the state in bytes, if bounded.
+    #[serde(skip)]
+    max_size_bytes: Option<usize>,
+    /// Whether the state changed since it was last marked persisted.
+    #[serde(skip)]
+    dirty: bool,
 }
 
 impl AgentState {
@@ -30,13 +39,60 @@ impl AgentState {
             created_at: now,
             updated_at: now,
             metadata: HashMap::new(),
+            max_size_bytes: None,
+            dirty: false,
         }
     }
 
+    /// Set the maximum serialized size of the state in bytes.
+    pub fn with_max_size_bytes(mut self, max_size_bytes: Option<usize>) -> Self {
+        self.max_size_bytes = max_size_bytes;
+        self
+    }
+
+    /// Get the maximum serialized size of the state in bytes.
+    pub fn max_size_bytes(&self) -> Option<usize> {
+        self.max_size_bytes
+    }
+
+    /// Get the serialized size of the messages and metadata in bytes.
+    pub fn size_bytes(&self) -> usize {
+        let messages = super::limits::conversation_size_bytes(&self.messages);
+        let metadata = serde_json::to_vec(&self.metadata).map(|bytes| bytes.len()).unwrap_or(0);
+        messages + metadata
+    }
+
+    /// Fail if adding `additional_bytes` would exceed the size limit.
+    fn check_size(&self, additional_bytes: usize) -> IndubitablyResult<()> {
+        if let Some(max_size_bytes) = self.max_size_bytes {
+            let size = self.size_bytes() + additional_bytes;
+            if size > max_size_bytes {
+                return Err(IndubitablyError::MemoryLimitExceeded(format!(
+                    "agent state would be {} bytes, limit is {} bytes",
+                    size, max_size_bytes
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set metadata by key, failing if it would exceed the size limit.
+    pub fn try_set_metadata(&mut self, key: &str, value: serde_json::Value) -> IndubitablyResult<()> {
+        let existing = self
+            .metadata
+            .get(key)
+            .map(|v| v.to_string().len() + key.len())
+            .unwrap_or(0);
+        let additional = (value.to_string().len() + key.len()).saturating_sub(existing);
+        self.check_size(additional)?;
+        self.set_metadata(key, value);
+        Ok(())
+    }
+
     /// Add a message to the state.
     pub fn add_message(&mut self, message: Message) {
         self.messages.push(message);
-        self.updated_at = Utc::now();
+        self.touch();
     }
 
     /// Get all messages.
@@ -62,7 +118,7 @@ impl AgentState {
     /// Clear all messages.
     pub fn clear_messages(&mut self) {
         self.messages.clear();
-        self.updated_at = Utc::now();
+        self.touch();
     }
 
     /// Get the creation time.
@@ -83,14 +139,14 @@ impl AgentState {
     /// Set metadata by key.
     pub fn set_metadata(&mut self, key: &str, value: serde_json::Value) {
         self.metadata.insert(key.to_string(), value);
-        self.updated_at = Utc::now();
+        self.touch();
     }
 
     /// Remove metadata by key.
     pub fn remove_metadata(&mut self, key: &str) -> Option<serde_json::Value> {
         let result = self.metadata.remove(key);
         if result.is_some() {
-            self.updated_at = Utc::now();
+            self.touch();
         }
         result
     }
@@ -99,6 +155,23 @@ impl AgentState {
     pub fn metadata(&self) -> &HashMap<String, serde_json::Value> {
         &self.metadata
     }
+
+    /// Check whether the state changed since it was last marked persisted.
+    ///
+    /// Persistence layers check this to skip writing unchanged state.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Mark the state as persisted.
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+        self.dirty = true;
+    }
 }
 
 impl Default for AgentState {
@@ -148,9 +221,26 @@ mod tests {
             Some(&serde_json::Value::String("test_value".to_string()))
         );
         
+        assert!(state.is_dirty());
+        state.mark_clean();
+
         let removed = state.remove_metadata("test_key");
         assert_eq!(removed, Some(serde_json::Value::String("test_value".to_string())));
         assert!(state.get_metadata("test_key").is_none());
+        assert!(state.is_dirty());
+        state.mark_clean();
+        state.remove_metadata("test_key");
+        assert!(!state.is_dirty());
+    }
+
+    #[test]
+    fn test_agent_state_size_limit() {
+        let mut state = AgentState::new().with_max_size_bytes(Some(64));
+
+        assert!(state.try_set_metadata("small", serde_json::json!(1)).is_ok());
+        assert!(state.try_set_metadata("large", serde_json::json!("x".repeat(128))).is_err());
+        assert!(state.get_metadata("large").is_none());
+        assert!(state.size_bytes() <= 64);
     }
 
     #[test]
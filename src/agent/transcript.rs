@@ -0,0 +1,281 @@
+//! Conversation seeding from external transcripts for the SDK.
+//! 
+//! This module loads a conversation recorded elsewhere so that an agent can
+//! start in the middle of it, for example after migrating from another
+//! stack. Transcripts are JSON or JSONL files holding OpenAI chat messages,
+//! OpenAI fine-tuning records (`{"messages": [...]}` per line), or the SDK's
+//! own `Message` objects, one per line. Before a history is used its role
+//! ordering is checked, so that a transcript providers would reject fails at
+//! startup rather than on the first model call.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::types::{
+    ConversationError, IndubitablyError, IndubitablyResult, Message, MessageRole, Messages, ToolResult,
+    ToolResultContent, ToolUse,
+};
+
+/// Where a seeded conversation comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistorySource {
+    /// Messages already in memory.
+    Messages(Messages),
+    /// A transcript file.
+    Path(PathBuf),
+}
+
+impl HistorySource {
+    /// Load the messages, reading the transcript file if needed, and check their role ordering.
+    pub fn load(&self) -> IndubitablyResult<Messages> {
+        let messages = match self {
+            Self::Messages(messages) => messages.clone(),
+            Self::Path(path) => load_transcript(path)?,
+        };
+        validate_role_order(&messages)?;
+        Ok(messages)
+    }
+}
+
+impl From<Messages> for HistorySource {
+    fn from(messages: Messages) -> Self {
+        Self::Messages(messages)
+    }
+}
+
+impl From<PathBuf> for HistorySource {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for HistorySource {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<&str> for HistorySource {
+    fn from(path: &str) -> Self {
+        Self::Path(PathBuf::from(path))
+    }
+}
+
+fn invalid(message: String) -> IndubitablyError {
+    ConversationError::InvalidHistory(message).into()
+}
+
+/// Read a transcript file in any supported format.
+pub fn load_transcript(path: impl AsRef<Path>) -> IndubitablyResult<Messages> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| invalid(format!("Failed to read transcript '{}': {}", path.display(), e)))?;
+    let messages = parse_transcript(&text)?;
+    tracing::debug!("path=<{}>, messages=<{}> | loaded transcript", path.display(), messages.len());
+    Ok(messages)
+}
+
+/// Parse a transcript given as a JSON array or as JSONL.
+pub fn parse_transcript(text: &str) -> IndubitablyResult<Messages> {
+    let trimmed = text.trim();
+    let entries: Vec<Value> = if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed).map_err(|e| invalid(format!("Invalid transcript JSON: {}", e)))?
+    } else {
+        trimmed
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| invalid(format!("Invalid transcript line {}: {}", index + 1, e)))
+            })
+            .collect::<IndubitablyResult<_>>()?
+    };
+
+    let mut messages = Vec::new();
+    let mut openai = Vec::new();
+    for entry in entries {
+        // A fine-tuning record holds a whole conversation
+        if let Some(Value::Array(records)) = entry.get("messages") {
+            openai.extend(records.iter().cloned());
+        } else if is_openai_message(&entry) {
+            openai.push(entry);
+        } else {
+            messages.extend(messages_from_openai(&std::mem::take(&mut openai))?);
+            let message = serde_json::from_value(entry).map_err(|e| invalid(format!("Invalid message: {}", e)))?;
+            messages.push(message);
+        }
+    }
+    messages.extend(messages_from_openai(&openai)?);
+    Ok(messages)
+}
+
+/// Check whether a JSON message is in the OpenAI chat format rather than the SDK's.
+fn is_openai_message(entry: &Value) -> bool {
+    entry.get("tool_calls").is_some()
+        || entry.get("tool_call_id").is_some()
+        || matches!(entry.get("content"), Some(Value::String(_)) | Some(Value::Null))
+}
+
+/// Convert OpenAI chat messages, grouping consecutive tool messages into one tool result message.
+pub fn messages_from_openai(entries: &[Value]) -> IndubitablyResult<Messages> {
+    let mut messages: Messages = Vec::new();
+    let mut results: Vec<ToolResult> = Vec::new();
+    for entry in entries {
+        let role = entry.get("role").and_then(Value::as_str).unwrap_or_default();
+        let text = openai_text(entry.get("content"));
+        if role == "tool" {
+            let tool_call_id = entry
+                .get("tool_call_id")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid("A tool message has no tool_call_id".to_string()))?;
+            results.push(ToolResult::new(tool_call_id, vec![ToolResultContent::text(&text)]));
+            continue;
+        }
+        if !results.is_empty() {
+            messages.push(Message::tool_results(std::mem::take(&mut results)));
+        }
+        messages.push(match role {
+            "system" | "developer" => Message::system(&text),
+            "user" => Message::user(&text),
+            "assistant" => {
+                let tool_uses = entry
+                    .get("tool_calls")
+                    .and_then(Value::as_array)
+                    .map(|calls| calls.iter().map(openai_tool_use).collect::<IndubitablyResult<Vec<_>>>())
+                    .transpose()?
+                    .unwrap_or_default();
+                if tool_uses.is_empty() {
+                    Message::assistant(&text)
+                } else {
+                    Message::assistant_with_tool_uses(&text, tool_uses)
+                }
+            }
+            other => return Err(invalid(format!("Unsupported message role '{}'", other))),
+        });
+    }
+    if !results.is_empty() {
+        messages.push(Message::tool_results(results));
+    }
+    Ok(messages)
+}
+
+/// Get the text of OpenAI message content, given as a string or as text parts.
+fn openai_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn openai_tool_use(call: &Value) -> IndubitablyResult<ToolUse> {
+    let id = call.get("id").and_then(Value::as_str).unwrap_or_default();
+    let name = call.pointer("/function/name").and_then(Value::as_str).unwrap_or_default();
+    if id.is_empty() || name.is_empty() {
+        return Err(invalid("A tool call has no id or function name".to_string()));
+    }
+    let input = match call.pointer("/function/arguments") {
+        Some(Value::String(arguments)) if arguments.trim().is_empty() => Value::Object(Default::default()),
+        Some(Value::String(arguments)) => serde_json::from_str(arguments)
+            .map_err(|e| invalid(format!("Tool call '{}' has invalid arguments: {}", id, e)))?,
+        Some(arguments) => arguments.clone(),
+        None => Value::Object(Default::default()),
+    };
+    Ok(ToolUse::new(name, id).with_input(input))
+}
+
+/// Check that a history alternates between user and assistant turns as providers require.
+///
+/// System messages may only come first, the first turn must be the user's,
+/// and every tool call must be answered by the next message, which holds
+/// only tool results.
+pub fn validate_role_order(messages: &Messages) -> IndubitablyResult<()> {
+    let mut previous: Option<&Message> = None;
+    let mut pending: HashSet<&str> = HashSet::new();
+    for (index, message) in messages.iter().enumerate() {
+        let results: Vec<&ToolResult> = message.tool_result_blocks();
+        match message.role {
+            MessageRole::System => {
+                if previous.is_some_and(|previous| previous.role != MessageRole::System) {
+                    return Err(invalid(format!(
+                        "Message {} is a system message after the conversation started",
+                        index
+                    )));
+                }
+                continue;
+            }
+            _ if !pending.is_empty() => {
+                let answered: HashSet<&str> = results.iter().map(|result| result.tool_use_id.as_str()).collect();
+                if answered != pending || results.len() != message.content.len() {
+                    return Err(invalid(format!(
+                        "Message {} does not answer the tool calls of the message before it",
+                        index
+                    )));
+                }
+                pending.clear();
+            }
+            _ if !results.is_empty() => {
+                return Err(invalid(format!("Message {} has tool results without a tool call before it", index)));
+            }
+            MessageRole::Assistant if !previous.is_some_and(|previous| previous.role != MessageRole::System) => {
+                return Err(invalid(format!("Message {} is an assistant turn before any user turn", index)));
+            }
+            _ => {}
+        }
+        let is_assistant = message.role == MessageRole::Assistant;
+        if let Some(previous) = previous.filter(|previous| previous.role != MessageRole::System) {
+            if (previous.role == MessageRole::Assistant) == is_assistant {
+                return Err(invalid(format!("Message {} repeats the role of the message before it", index)));
+            }
+        }
+        if is_assistant {
+            pending = message.tool_uses().into_iter().map(|tool_use| tool_use.tool_use_id.as_str()).collect();
+        }
+        previous = Some(message);
+    }
+    if !pending.is_empty() {
+        return Err(invalid("The last message has tool calls without results".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_openai_transcript_and_validate_roles() {
+        let text = r#"
+{"role": "system", "content": "Be brief."}
+{"role": "user", "content": "Weather in Oslo?"}
+{"role": "assistant", "content": null, "tool_calls": [{"id": "call_1", "type": "function", "function": {"name": "weather", "arguments": "{\"city\":\"Oslo\"}"}}]}
+{"role": "tool", "tool_call_id": "call_1", "content": "Sunny"}
+{"role": "assistant", "content": [{"type": "text", "text": "It is sunny."}]}
+"#;
+        let messages = parse_transcript(text).unwrap();
+        assert_eq!(messages.len(), 5);
+        assert_eq!(messages[2].tool_uses()[0].input.as_ref().unwrap()["city"], "Oslo");
+        assert_eq!(messages[3].tool_result_blocks()[0].tool_use_id, "call_1");
+        assert_eq!(messages[4].text(), Some("It is sunny."));
+        validate_role_order(&messages).unwrap();
+
+        // The SDK's own messages, one per line, round-trip as well
+        let jsonl: String = messages.iter().map(|m| serde_json::to_string(m).unwrap() + "\n").collect();
+        assert_eq!(parse_transcript(&jsonl).unwrap(), messages);
+
+        let record = serde_json::json!({"messages": [{"role": "user", "content": "Hi"}]}).to_string();
+        assert_eq!(parse_transcript(&record).unwrap(), vec![Message::user("Hi")]);
+
+        let unanswered = messages[..3].to_vec();
+        assert!(validate_role_order(&unanswered).is_err());
+        assert!(validate_role_order(&vec![Message::assistant("Hello")]).is_err());
+        assert!(validate_role_order(&vec![Message::user("A"), Message::user("B")]).is_err());
+        assert!(validate_role_order(&vec![Message::user("A"), Message::system("Late")]).is_err());
+    }
+}
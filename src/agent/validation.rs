@@ -0,0 +1,361 @@
+//! Configuration validation for the SDK.
+//! 
+//! This module checks a whole `AgentConfig` before an agent is built: the
+//! model, the tool specs and their schemas, the conversation settings against
+//! the model's context window, and options that contradict each other.
+//! `AgentBuilder::build` refuses a configuration with errors and returns the
+//! `ConfigReport` listing them, so that mistakes surface at startup instead
+//! of as opaque failures in the middle of a run. Warnings are logged.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::agent::AgentConfig;
+use crate::tools::strict::to_strict_schema;
+
+/// The longest tool name model providers accept.
+pub const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// How serious a configuration issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSeverity {
+    /// The agent cannot work as configured.
+    Error,
+    /// The agent works, but probably not as intended.
+    Warning,
+}
+
+/// One problem found in a configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    /// How serious the issue is.
+    pub severity: ConfigSeverity,
+    /// The setting at fault, such as `tools[2].input_schema`.
+    pub field: String,
+    /// What is wrong and how to fix it.
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            ConfigSeverity::Error => "error",
+            ConfigSeverity::Warning => "warning",
+        };
+        write!(f, "{} in {}: {}", severity, self.field, self.message)
+    }
+}
+
+/// The errors and warnings found in an agent configuration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigReport {
+    /// The issues in the order they were found.
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    /// Create an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an error.
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.push(ConfigSeverity::Error, field, message.into());
+    }
+
+    /// Record a warning.
+    pub fn warning(&mut self, field: &str, message: impl Into<String>) {
+        self.push(ConfigSeverity::Warning, field, message.into());
+    }
+
+    fn push(&mut self, severity: ConfigSeverity, field: &str, message: String) {
+        self.issues.push(ConfigIssue {
+            severity,
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    /// Get the errors.
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|issue| issue.severity == ConfigSeverity::Error)
+    }
+
+    /// Get the warnings.
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|issue| issue.severity == ConfigSeverity::Warning)
+    }
+
+    /// Check whether any error was found.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Check whether the report is free of errors and warnings.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.errors().count();
+        write!(f, "{} error(s), {} warning(s)", errors, self.issues.len() - errors)?;
+        for issue in &self.issues {
+            write!(f, "\n  {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check an agent configuration; `require_model` makes a missing model an error.
+pub fn validate_config(config: &AgentConfig, require_model: bool) -> ConfigReport {
+    let mut report = ConfigReport::new();
+    check_model(config, require_model, &mut report);
+    check_tools(config, &mut report);
+    check_conversation(config, &mut report);
+    check_conflicts(config, &mut report);
+    report
+}
+
+fn check_model(config: &AgentConfig, require_model: bool, report: &mut ConfigReport) {
+    let Some(ref model) = config.model else {
+        let message = "no model is configured, so the agent answers with a placeholder; set one with `model`";
+        if require_model {
+            report.error("model", message);
+        } else {
+            report.warning("model", message);
+        }
+        return;
+    };
+    if model.model_id().trim().is_empty() {
+        report.error("model.model_id", "the model ID is empty");
+    }
+    if let Some(temperature) = model.temperature() {
+        if !(0.0..=2.0).contains(&temperature) {
+            report.error("model.temperature", format!("temperature {} is outside 0 to 2", temperature));
+        }
+    }
+    if model.max_tokens() == Some(0) {
+        report.error("model.max_tokens", "the model may not produce any output tokens");
+    }
+}
+
+fn check_tools(config: &AgentConfig, report: &mut ConfigReport) {
+    let mut names = HashSet::new();
+    for (index, tool) in config.tools.iter().enumerate() {
+        let field = format!("tools[{}]", index);
+        let valid_name = !tool.name.is_empty()
+            && tool.name.len() <= MAX_TOOL_NAME_LEN
+            && tool.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            report.error(
+                &format!("{}.name", field),
+                format!(
+                    "tool name '{}' must be 1 to {} letters, digits, '_' or '-'",
+                    tool.name, MAX_TOOL_NAME_LEN
+                ),
+            );
+        }
+        if !names.insert(tool.name.as_str()) {
+            report.error(&format!("{}.name", field), format!("tool '{}' is registered twice", tool.name));
+        }
+        if tool.description.trim().is_empty() {
+            report.warning(
+                &format!("{}.description", field),
+                format!("tool '{}' has no description, so the model cannot tell when to use it", tool.name),
+            );
+        }
+        if let Some(ref schema) = tool.input_schema {
+            check_input_schema(schema, &format!("{}.input_schema", field), report);
+        }
+        if config.model.as_ref().is_some_and(|model| model.config().strict_tools) {
+            let strict = to_strict_schema(tool.input_schema.as_ref());
+            if !strict.is_strict() {
+                report.warning(
+                    &format!("{}.input_schema", field),
+                    format!("tool '{}' is sent without strict mode: {}", tool.name, strict.violations.join("; ")),
+                );
+            }
+        }
+    }
+}
+
+/// Check that a tool input schema describes an object with well-formed properties.
+fn check_input_schema(schema: &Value, field: &str, report: &mut ConfigReport) {
+    // A null or empty schema is what tools without parameters carry
+    let object = match schema {
+        Value::Null => return,
+        Value::Object(object) if object.is_empty() => return,
+        Value::Object(object) => object,
+        _ => {
+            report.error(field, "the input schema must be a JSON object");
+            return;
+        }
+    };
+    match object.get("type").and_then(Value::as_str) {
+        Some("object") => {}
+        Some(other) => report.error(field, format!("the input schema has type '{}' but must be 'object'", other)),
+        None => report.error(field, "the input schema has no \"type\": \"object\""),
+    }
+    let properties = match object.get("properties") {
+        None => None,
+        Some(Value::Object(properties)) => Some(properties),
+        Some(_) => {
+            report.error(&format!("{}.properties", field), "properties must be an object");
+            None
+        }
+    };
+    for (name, property) in properties.into_iter().flatten() {
+        if !property.is_object() && !property.is_boolean() {
+            report.error(&format!("{}.properties.{}", field, name), "each property must be a schema object");
+        }
+    }
+    match object.get("required") {
+        None => {}
+        Some(Value::Array(required)) => {
+            for name in required {
+                match name.as_str() {
+                    Some(name) if properties.is_some_and(|properties| !properties.contains_key(name)) => {
+                        report.warning(
+                            &format!("{}.required", field),
+                            format!("required property '{}' is not declared in properties", name),
+                        );
+                    }
+                    Some(_) => {}
+                    None => report.error(&format!("{}.required", field), "required must list property names"),
+                }
+            }
+        }
+        Some(_) => report.error(&format!("{}.required", field), "required must be an array"),
+    }
+}
+
+fn check_conversation(config: &AgentConfig, report: &mut ConfigReport) {
+    let conversation = &config.conversation_config;
+    if conversation.max_messages == 0 {
+        report.error("conversation_config.max_messages", "the conversation may not keep any messages");
+    }
+    if conversation.enable_summarization && conversation.summary_model.is_none() {
+        report.warning(
+            "conversation_config.summary_model",
+            "summarization is enabled without a summary model, so the agent's model is used",
+        );
+    }
+    if let Some(Err(e)) = config.history.as_ref().map(|history| history.load()) {
+        report.error("history", e.to_string());
+    }
+
+    let output_tokens = config.model.as_ref().and_then(|model| model.max_tokens()).map(|tokens| tokens as usize);
+    if let Some(window) = config.context_window {
+        if window == 0 {
+            report.error("context_window", "the context window is 0 tokens");
+        } else if let Some(output_tokens) = output_tokens.filter(|&tokens| tokens >= window) {
+            report.error(
+                "context_window",
+                format!(
+                    "the model's max_tokens of {} leaves no room for input in a {} token window",
+                    output_tokens, window
+                ),
+            );
+        }
+        if let Some(max_tokens) = config.budget.as_ref().and_then(|budget| budget.max_tokens) {
+            if (max_tokens as usize) < window / 2 {
+                report.warning(
+                    "budget.max_tokens",
+                    format!(
+                        "the budget of {} tokens runs out before one full {} token context is sent",
+                        max_tokens, window
+                    ),
+                );
+            }
+        }
+    }
+
+    if let Some(ref budget) = config.budget {
+        if budget.max_tokens == Some(0) || budget.max_cost.is_some_and(|cost| cost <= 0.0) {
+            report.error("budget", "the budget is used up before the first model call");
+        }
+        if budget.max_cost.is_some() && budget.input_cost_per_1k == 0.0 && budget.output_cost_per_1k == 0.0 {
+            report.warning("budget.max_cost", "a cost limit is set but token prices are 0, so it never applies");
+        }
+        if !(0.0..=1.0).contains(&budget.warning_threshold) {
+            report.error("budget.warning_threshold", "the warning threshold must be between 0 and 1");
+        }
+    }
+}
+
+fn check_conflicts(config: &AgentConfig, report: &mut ConfigReport) {
+    if config.tool_selector.is_some() && config.tools.is_empty() {
+        report.warning("tool_selector", "a tool selector is set but the agent has no tool specs to select from");
+    }
+    if let Some(ref liveness) = config.liveness {
+        if liveness.heartbeat_interval.is_zero() {
+            report.error("liveness.heartbeat_interval", "the heartbeat interval must be greater than 0");
+        }
+        if liveness.hard_ceiling.is_some_and(|ceiling| ceiling <= liveness.heartbeat_after) {
+            report.warning("liveness.hard_ceiling", "runs are aborted before their first heartbeat is sent");
+        }
+    }
+    if config.read_only && config.workspace.is_some() {
+        report.warning("workspace", "read-only agents refuse writing tools, so the workspace stays empty");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::ConversationBudget;
+    use crate::models::model::{MockModel, ModelConfig};
+    use crate::types::ToolSpec;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_config_reports_errors_and_warnings() {
+        let mut config = AgentConfig::new();
+        assert!(!validate_config(&config, false).has_errors());
+        assert!(validate_config(&config, true).has_errors());
+
+        config.model = Some(Box::new(MockModel::with_config(ModelConfig::new("mock").with_max_tokens(8192))));
+        config.context_window = Some(8000);
+        config.tools = vec![
+            ToolSpec::new("search", "Search the web").with_input_schema(json!({
+                "type": "object",
+                "properties": {"query": {"type": "string"}},
+                "required": ["query", "limit"],
+            })),
+            ToolSpec::new("search", "Search again"),
+            ToolSpec::new("bad name", "").with_input_schema(json!({"type": "string"})),
+        ];
+        config.budget = Some(ConversationBudget::default().with_max_tokens(0));
+
+        let report = validate_config(&config, true);
+        let fields: Vec<(ConfigSeverity, &str)> =
+            report.issues.iter().map(|issue| (issue.severity, issue.field.as_str())).collect();
+        assert_eq!(
+            fields,
+            vec![
+                (ConfigSeverity::Warning, "tools[0].input_schema.required"),
+                (ConfigSeverity::Error, "tools[1].name"),
+                (ConfigSeverity::Error, "tools[2].name"),
+                (ConfigSeverity::Warning, "tools[2].description"),
+                (ConfigSeverity::Error, "tools[2].input_schema"),
+                (ConfigSeverity::Error, "context_window"),
+                (ConfigSeverity::Warning, "budget.max_tokens"),
+                (ConfigSeverity::Error, "budget"),
+            ]
+        );
+        assert!(report.to_string().starts_with("5 error(s), 3 warning(s)\n  warning in tools[0]"));
+
+        let built = crate::agent::AgentBuilder::new().require_model().build();
+        let Err(crate::types::IndubitablyError::InvalidConfiguration(report)) = built else {
+            panic!("expected an invalid configuration");
+        };
+        assert!(report.has_errors());
+    }
+}
@@ -4,14 +4,19 @@
 //! with the Indubitably Rust Agent SDK, including chat functionality and tool management.
 
 use clap::{Parser, Subcommand};
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio;
 
 use indubitably_rust_agent_sdk::{
     agent::AgentBuilder,
-    models::{BedrockModel, OpenAIModel, AnthropicModel, OllamaModel},
+    models::{OllamaModel, Model},
+    models::{format_side_by_side, ModelComparison, ModelPrice, DEFAULT_COLUMN_WIDTH},
+    tools::patch::APPLY_PATCH_TOOL_NAME,
     tools::registry::ToolRegistry,
-    types::IndubitablyResult,
+    tools::{DiffRenderer, RendererRegistry},
+    types::{IndubitablyError, IndubitablyResult, ModelError},
 };
 
 #[derive(Parser)]
@@ -30,7 +35,7 @@ enum Commands {
         /// The message to send to the agent
         message: String,
         
-        /// The model to use (bedrock, openai, anthropic, ollama)
+        /// The model to use (bedrock, openai, anthropic, ollama, grok)
         #[arg(short, long, default_value = "bedrock")]
         model: String,
         
@@ -43,6 +48,28 @@ enum Commands {
         verbose: bool,
     },
     
+    /// Send a prompt to several models at once and show their responses side by side
+    Compare {
+        /// The prompt to send to every model
+        prompt: String,
+
+        /// A model to compare (bedrock, openai, anthropic, ollama, grok); repeat for each model
+        #[arg(short = 'm', long = "model", required = true)]
+        models: Vec<String>,
+
+        /// The system prompt sent to every model
+        #[arg(short, long)]
+        system_prompt: Option<String>,
+
+        /// The price of a model's tokens in US dollars, as MODEL=INPUT_PER_1K,OUTPUT_PER_1K
+        #[arg(short = 'p', long = "price")]
+        prices: Vec<String>,
+
+        /// The width of each response column
+        #[arg(short, long, default_value_t = DEFAULT_COLUMN_WIDTH)]
+        width: usize,
+    },
+
     /// List available tools
     Tools {
         /// Show detailed tool information
@@ -65,6 +92,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Chat { message, model, system_prompt, verbose } => {
             chat_command(message, model, system_prompt, verbose).await?;
         }
+        Commands::Compare { prompt, models, system_prompt, prices, width } => {
+            compare_command(prompt, models, system_prompt, prices, width).await?;
+        }
         Commands::Tools { detailed } => {
             tools_command(detailed).await?;
         }
@@ -90,36 +120,11 @@ async fn chat_command(
     }
     
     // Create the appropriate model
-    let model_box: Box<dyn indubitably_rust_agent_sdk::models::Model> = match model.to_lowercase().as_str() {
-        "bedrock" => {
-            if verbose {
-                println!("Using Amazon Bedrock model");
-            }
-            Box::new(BedrockModel::new())
-        }
-        "openai" => {
-            if verbose {
-                println!("Using OpenAI model");
-            }
-            Box::new(OpenAIModel::new())
-        }
-        "anthropic" => {
-            if verbose {
-                println!("Using Anthropic Claude model");
-            }
-            Box::new(AnthropicModel::new())
-        }
-        "ollama" => {
-            if verbose {
-                println!("Using Ollama model");
-            }
-            Box::new(OllamaModel::new())
-        }
-        _ => {
-            eprintln!("Unknown model: {}. Using Bedrock as default.", model);
-            Box::new(BedrockModel::new())
-        }
-    };
+    let (model_box, description) = create_model(&model)
+        .map_err(|e| IndubitablyError::ModelError(ModelError::InvalidConfiguration(e)))?;
+    if verbose {
+        println!("Using {} model", description);
+    }
     
     // Build the agent
     let mut agent_builder = AgentBuilder::new().model(model_box);
@@ -142,11 +147,78 @@ async fn chat_command(
         println!("Response received in {} messages", result.messages.len());
     }
     
+    // Show tool outputs as tables, image links or diffs rather than raw JSON
+    let renderers = cli_renderers();
+    for call in &result.tool_calls {
+        let rendered = renderers.render_text(&call.name, &call.input, &call.output);
+        println!("Tool {} ({}):\n{}", call.name, rendered.kind, rendered.text);
+    }
+    
     println!("Agent: {}", result.response);
     
     Ok(())
 }
 
+/// Create a model from its provider name, with a description of it.
+///
+/// The CLI has no TLS client, so it can only reach servers over plain HTTP,
+/// such as a local Ollama. Hosted providers fail here, before any request is
+/// made, rather than on their first call.
+fn create_model(name: &str) -> Result<(Box<dyn Model>, &'static str), String> {
+    let hosted = match name.to_lowercase().as_str() {
+        "ollama" => return Ok((Box::new(OllamaModel::new()), "Ollama")),
+        "bedrock" => "Amazon Bedrock",
+        "openai" => "OpenAI",
+        "anthropic" => "Anthropic Claude",
+        "grok" => "xAI Grok",
+        _ => return Err(format!("Unknown model: {}", name)),
+    };
+    Err(format!(
+        "No HTTP transport configured for {}: this CLI can only reach plain HTTP servers such as Ollama",
+        hosted
+    ))
+}
+
+/// Parse a price given as MODEL=INPUT_PER_1K,OUTPUT_PER_1K.
+fn parse_price(price: &str) -> Option<(String, ModelPrice)> {
+    let (label, costs) = price.split_once('=')?;
+    let (input, output) = costs.split_once(',')?;
+    Some((label.trim().to_string(), ModelPrice::new(input.trim().parse().ok()?, output.trim().parse().ok()?)))
+}
+
+async fn compare_command(
+    prompt: String,
+    models: Vec<String>,
+    system_prompt: Option<String>,
+    prices: Vec<String>,
+    width: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut comparison = ModelComparison::new();
+    for name in &models {
+        let (model, _) = create_model(name)?;
+        comparison = comparison.with_model(name, Arc::from(model));
+    }
+    for price in &prices {
+        let (label, price) =
+            parse_price(price).ok_or_else(|| format!("Invalid price '{}', expected MODEL=INPUT,OUTPUT", price))?;
+        comparison = comparison.with_price(&label, price);
+    }
+    if let Some(prompt) = system_prompt {
+        comparison = comparison.with_system_prompt(&prompt);
+    }
+
+    let results = comparison.run(&prompt).await;
+    println!("{}", format_side_by_side(&results, width));
+    Ok(())
+}
+
+/// Get the renderers the CLI shows tool outputs with.
+fn cli_renderers() -> RendererRegistry {
+    let color = std::io::stdout().is_terminal();
+    RendererRegistry::with_defaults()
+        .with_renderer(APPLY_PATCH_TOOL_NAME, Arc::new(DiffRenderer::new().with_color(color)))
+}
+
 async fn tools_command(detailed: bool) -> IndubitablyResult<()> {
     let registry = ToolRegistry::new();
     
@@ -188,6 +260,7 @@ fn help_command() {
     println!();
     println!("Commands:");
     println!("  chat     Start a chat session with an agent");
+    println!("  compare  Send a prompt to several models and compare their responses");
     println!("  tools    List available tools");
     println!("  version  Show version information");
     println!("  help     Show this help message");
@@ -196,6 +269,7 @@ fn help_command() {
     println!("  indubitably-cli chat \"Hello, how are you?\"");
     println!("  indubitably-cli chat -m openai \"What's the weather like?\"");
     println!("  indubitably-cli chat -m openai -s \"You are a helpful assistant\" \"Tell me a joke\"");
+    println!("  indubitably-cli compare -m openai -m anthropic -m ollama \"Explain recursion\"");
     println!("  indubitably-cli tools --detailed");
 }
 
@@ -210,6 +284,27 @@ mod tests {
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn test_compare_parsing() {
+        let args = vec!["indubitably-cli", "compare", "-m", "openai", "-m", "ollama", "-p", "openai=0.15,0.6", "Hi"];
+        let Commands::Compare { prompt, models, prices, .. } = Cli::try_parse_from(args).unwrap().command else {
+            panic!("expected the compare command");
+        };
+        assert_eq!(prompt, "Hi");
+        assert_eq!(models, ["openai", "ollama"]);
+        assert_eq!(parse_price(&prices[0]), Some(("openai".to_string(), ModelPrice::new(0.15, 0.6))));
+    }
+
+    #[test]
+    fn test_create_model_requires_a_transport() {
+        assert_eq!(create_model("Ollama").unwrap().1, "Ollama");
+        for name in ["bedrock", "openai", "anthropic", "grok"] {
+            let error = create_model(name).err().unwrap();
+            assert!(error.starts_with("No HTTP transport configured"), "{}", error);
+        }
+        assert_eq!(create_model("gpt").err().as_deref(), Some("Unknown model: gpt"));
+    }
+
     #[test]
     fn test_version_command() {
         // This is a simple test that just ensures the function doesn't panic
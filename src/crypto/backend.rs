@@ -0,0 +1,137 @@
+//! Pluggable signature and encryption primitives for the SDK.
+//! 
+//! Ed25519 verification, RSA signing and ChaCha20-Poly1305 need audited,
+//! constant-time implementations, so the SDK does not implement them itself.
+//! Applications supply them through these traits, typically backed by the
+//! `ed25519-dalek`, `rsa` and `chacha20poly1305` crates. Components that need
+//! a primitive fail closed until one is configured: signed artifacts are
+//! refused, service-account tokens cannot be minted and sessions cannot be
+//! encrypted or decrypted.
+
+use super::hex;
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// The length of an Ed25519 public key in bytes.
+pub const ED25519_PUBLIC_KEY_LEN: usize = 32;
+
+/// The length of a ChaCha20-Poly1305 key in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// The length of a ChaCha20-Poly1305 nonce in bytes.
+pub const NONCE_LEN: usize = 12;
+
+/// An Ed25519 public key, as allow-listed in a trust store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ed25519PublicKey {
+    bytes: [u8; ED25519_PUBLIC_KEY_LEN],
+}
+
+impl Ed25519PublicKey {
+    /// Create a key from its 32-byte encoding.
+    pub fn from_bytes(bytes: [u8; ED25519_PUBLIC_KEY_LEN]) -> Self {
+        Self { bytes }
+    }
+
+    /// Create a key from a hex string.
+    pub fn from_hex(text: &str) -> IndubitablyResult<Self> {
+        let bytes = hex::decode(text)?;
+        let bytes = bytes.try_into().map_err(|_| {
+            IndubitablyError::ValidationError(format!("Ed25519 public key must be {} bytes", ED25519_PUBLIC_KEY_LEN))
+        })?;
+        Ok(Self { bytes })
+    }
+
+    /// Get the 32-byte encoding of the key.
+    pub fn to_bytes(&self) -> [u8; ED25519_PUBLIC_KEY_LEN] {
+        self.bytes
+    }
+
+    /// Get the hex encoding of the key.
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.bytes)
+    }
+}
+
+/// Verifies Ed25519 signatures.
+pub trait Ed25519Verifier: Send + Sync {
+    /// Check a detached signature over a message; malformed keys and signatures do not verify.
+    fn verify(&self, key: &Ed25519PublicKey, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// Signs messages with RSASSA-PKCS1-v1_5 over SHA-256, as RS256 JWTs use.
+pub trait RsaSigner: Send + Sync {
+    /// Sign a message with a PEM-encoded PKCS#8 or PKCS#1 private key.
+    fn sign_sha256(&self, private_key_pem: &str, message: &[u8]) -> IndubitablyResult<Vec<u8>>;
+}
+
+/// Encrypts with ChaCha20-Poly1305 (RFC 8439), returning the ciphertext followed by its 16-byte tag.
+pub trait Aead: Send + Sync {
+    /// Encrypt and authenticate a plaintext with associated data.
+    fn seal(&self, key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8>;
+
+    /// Decrypt a sealed message, or `None` if it fails authentication.
+    fn open(&self, key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Stand-ins for the pluggable primitives in tests. They are not secure.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use crate::crypto::hmac::{constant_time_eq, hmac_sha256};
+
+    /// "Signs" with an HMAC keyed by the public key, so anyone can forge signatures.
+    pub(crate) struct InsecureSigner;
+
+    impl InsecureSigner {
+        pub(crate) fn sign(key: &Ed25519PublicKey, message: &[u8]) -> Vec<u8> {
+            hmac_sha256(&key.to_bytes(), message).to_vec()
+        }
+    }
+
+    impl Ed25519Verifier for InsecureSigner {
+        fn verify(&self, key: &Ed25519PublicKey, message: &[u8], signature: &[u8]) -> bool {
+            constant_time_eq(&Self::sign(key, message), signature)
+        }
+    }
+
+    impl RsaSigner for InsecureSigner {
+        fn sign_sha256(&self, private_key_pem: &str, message: &[u8]) -> IndubitablyResult<Vec<u8>> {
+            Ok(hmac_sha256(private_key_pem.as_bytes(), message).to_vec())
+        }
+    }
+
+    /// XORs with an HMAC keystream and appends an HMAC tag.
+    pub(crate) struct InsecureAead;
+
+    impl InsecureAead {
+        fn keystream(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], len: usize) -> Vec<u8> {
+            (0..len.div_ceil(32) as u32)
+                .flat_map(|block| hmac_sha256(key, &[&nonce[..], &block.to_be_bytes()].concat()))
+                .take(len)
+                .collect()
+        }
+
+        fn tag(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+            hmac_sha256(key, &[&nonce[..], aad, ciphertext].concat())
+        }
+    }
+
+    impl Aead for InsecureAead {
+        fn seal(&self, key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+            let stream = Self::keystream(key, nonce, plaintext.len());
+            let mut sealed: Vec<u8> = plaintext.iter().zip(stream).map(|(byte, key)| byte ^ key).collect();
+            let tag = Self::tag(key, nonce, aad, &sealed);
+            sealed.extend_from_slice(&tag[..16]);
+            sealed
+        }
+
+        fn open(&self, key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+            let (ciphertext, tag) = sealed.split_at(sealed.len().checked_sub(16)?);
+            if !constant_time_eq(&Self::tag(key, nonce, aad, ciphertext)[..16], tag) {
+                return None;
+            }
+            let stream = Self::keystream(key, nonce, ciphertext.len());
+            Some(ciphertext.iter().zip(stream).map(|(byte, key)| byte ^ key).collect())
+        }
+    }
+}
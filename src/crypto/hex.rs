@@ -0,0 +1,55 @@
+//! Hex encoding for the SDK.
+//! 
+//! This module provides lowercase hex encoding and decoding for digests,
+//! keys and signatures.
+
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// Encode bytes as lowercase hex.
+pub fn encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    out
+}
+
+/// Decode a hex string, accepting either case.
+pub fn decode(text: &str) -> IndubitablyResult<Vec<u8>> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return Err(IndubitablyError::ValidationError("Hex string has odd length".to_string()));
+    }
+
+    fn nibble(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| match (nibble(pair[0]), nibble(pair[1])) {
+            (Some(high), Some(low)) => Ok(high << 4 | low),
+            _ => Err(IndubitablyError::ValidationError("Invalid hex digit".to_string())),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(encode(&[0x00, 0xab, 0xff]), "00abff");
+        assert_eq!(decode("00ABff").unwrap(), vec![0x00, 0xab, 0xff]);
+        assert!(decode("abc").is_err());
+        assert!(decode("zz").is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! Cryptographic primitives for the SDK.
+//! 
+//! This module provides the small set of primitives the SDK needs for
+//! integrity checks and request signing: SHA-256 and SHA-512 digests,
+//! HMAC-SHA256, CRC-32 checksums, and hex and base64 encoding. Signatures and
+//! encryption at rest (Ed25519, RSA and ChaCha20-Poly1305) are not implemented
+//! here; applications plug in vetted implementations through the traits in
+//! `backend`.
+
+pub mod sha2;
+pub mod hmac;
+pub mod hex;
+pub mod base64;
+pub mod crc32;
+pub mod backend;
+
+pub use sha2::{sha256, sha512, Sha256, Sha512};
+pub use hmac::hmac_sha256;
+pub use crc32::crc32;
+pub use backend::{Aead, Ed25519PublicKey, Ed25519Verifier, RsaSigner};
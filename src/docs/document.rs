@@ -0,0 +1,350 @@
+//! Parsed documents and their chunks.
+//! 
+//! This module defines `ParsedDocument`, the text of a document split into
+//! pages and blocks with their positions, and `DocumentChunk`, a piece of
+//! one page sized for retrieval. Chunks never span pages, so every chunk can
+//! be cited by page and bounding box.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Citation, DocumentContent, DocumentType};
+
+/// A rectangle on a page.
+///
+/// Coordinates are in the units of the source: PDF points with the origin at
+/// the bottom-left of the page, or image pixels with the origin at the
+/// top-left for OCR results.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    /// The left edge.
+    pub x: f32,
+    /// The bottom edge for PDF coordinates, the top edge for image coordinates.
+    pub y: f32,
+    /// The width.
+    pub width: f32,
+    /// The height.
+    pub height: f32,
+}
+
+impl BoundingBox {
+    /// Create a new bounding box.
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Get the smallest box containing both boxes.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        BoundingBox {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+/// A run of text on a page, such as a line or paragraph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextBlock {
+    /// The text.
+    pub text: String,
+    /// Where the text is on the page, when the format records positions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<BoundingBox>,
+    /// A non-geometric location, such as a spreadsheet cell range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl TextBlock {
+    /// Create a block of text.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            bbox: None,
+            location: None,
+        }
+    }
+
+    /// Set where the text is on the page.
+    pub fn with_bbox(mut self, bbox: BoundingBox) -> Self {
+        self.bbox = Some(bbox);
+        self
+    }
+
+    /// Set the non-geometric location of the text.
+    pub fn with_location(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+}
+
+/// A page of a document: a PDF page, a slide, or a spreadsheet sheet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentPage {
+    /// The page number, starting at 1.
+    pub number: u32,
+    /// The page title, such as a sheet name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The page width, when the format records it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f32>,
+    /// The page height, when the format records it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f32>,
+    /// The text blocks in reading order.
+    pub blocks: Vec<TextBlock>,
+}
+
+impl DocumentPage {
+    /// Create an empty page.
+    pub fn new(number: u32) -> Self {
+        Self {
+            number,
+            title: None,
+            width: None,
+            height: None,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Get the text of the page, one block per line.
+    pub fn text(&self) -> String {
+        self.blocks.iter().map(|block| block.text.as_str()).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// How documents are split into chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkOptions {
+    /// The most characters in a chunk; longer blocks are split at whitespace.
+    pub max_chars: usize,
+    /// The number of trailing blocks of a chunk repeated at the start of the next one on the same page.
+    pub overlap_blocks: usize,
+}
+
+impl ChunkOptions {
+    /// Create the default chunking options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the most characters in a chunk.
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars.max(1);
+        self
+    }
+
+    /// Set the number of blocks repeated between neighbouring chunks.
+    pub fn with_overlap_blocks(mut self, overlap_blocks: usize) -> Self {
+        self.overlap_blocks = overlap_blocks;
+        self
+    }
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            max_chars: 1500,
+            overlap_blocks: 1,
+        }
+    }
+}
+
+/// A piece of one page of a document, sized for retrieval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentChunk {
+    /// The chunk ID, unique within the document.
+    pub id: String,
+    /// The name of the document.
+    pub document: String,
+    /// The position of the chunk in the document, starting at 0.
+    pub index: usize,
+    /// The text of the chunk.
+    pub text: String,
+    /// The page the chunk is on.
+    pub page: u32,
+    /// The box around the chunk's blocks, when the format records positions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<BoundingBox>,
+    /// The location of the chunk's first block, such as a cell range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl DocumentChunk {
+    /// Create a citation pointing at this chunk's page.
+    pub fn citation(&self) -> Citation {
+        let snippet: String = self.text.chars().take(200).collect();
+        Citation::new(&self.id)
+            .with_uri(&format!("{}#page={}", self.document, self.page))
+            .with_title(&self.document)
+            .with_snippet(&snippet)
+    }
+
+    /// Convert the chunk to document content for a message.
+    pub fn to_document_content(&self) -> DocumentContent {
+        DocumentContent::text(&self.text)
+    }
+}
+
+/// The text of a document, split into pages and blocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedDocument {
+    /// The document name, usually its file name.
+    pub name: String,
+    /// The kind of document.
+    pub document_type: DocumentType,
+    /// The media type of the source.
+    pub media_type: String,
+    /// The pages in order.
+    pub pages: Vec<DocumentPage>,
+}
+
+impl ParsedDocument {
+    /// Get the text of the document, pages separated by blank lines.
+    pub fn text(&self) -> String {
+        self.pages.iter().map(DocumentPage::text).collect::<Vec<_>>().join("\n\n")
+    }
+
+    /// Convert the document to text content for a message, marking where each page starts.
+    pub fn to_document_content(&self) -> DocumentContent {
+        let text = self
+            .pages
+            .iter()
+            .map(|page| match page.title {
+                Some(ref title) => format!("[Page {}: {}]\n{}", page.number, title, page.text()),
+                None => format!("[Page {}]\n{}", page.number, page.text()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        DocumentContent::text(&text)
+    }
+
+    /// Split the document into chunks that never span pages.
+    pub fn chunks(&self, options: &ChunkOptions) -> Vec<DocumentChunk> {
+        let mut chunks = Vec::new();
+        for page in &self.pages {
+            let pieces: Vec<TextBlock> = page
+                .blocks
+                .iter()
+                .filter(|block| !block.text.trim().is_empty())
+                .flat_map(|block| split_block(block, options.max_chars))
+                .collect();
+            let mut current: Vec<TextBlock> = Vec::new();
+            for piece in pieces {
+                if !current.is_empty() && chunk_len(&current) + 1 + piece.text.chars().count() > options.max_chars {
+                    self.push_chunk(&mut chunks, page.number, &current);
+                    current.drain(..current.len() - options.overlap_blocks.min(current.len()));
+                    // Drop the overlap when it leaves no room for the next block
+                    while !current.is_empty() && chunk_len(&current) + 1 + piece.text.chars().count() > options.max_chars {
+                        current.remove(0);
+                    }
+                }
+                current.push(piece);
+            }
+            if !current.is_empty() {
+                self.push_chunk(&mut chunks, page.number, &current);
+            }
+        }
+        chunks
+    }
+
+    fn push_chunk(&self, chunks: &mut Vec<DocumentChunk>, page: u32, blocks: &[TextBlock]) {
+        let index = chunks.len();
+        chunks.push(DocumentChunk {
+            id: format!("{}#{}", self.name, index),
+            document: self.name.clone(),
+            index,
+            text: blocks.iter().map(|block| block.text.as_str()).collect::<Vec<_>>().join("\n"),
+            page,
+            bbox: blocks
+                .iter()
+                .filter_map(|block| block.bbox)
+                .reduce(|union, bbox| union.union(&bbox)),
+            location: blocks.iter().find_map(|block| block.location.clone()),
+        });
+    }
+}
+
+fn chunk_len(blocks: &[TextBlock]) -> usize {
+    blocks.iter().map(|block| block.text.chars().count()).sum::<usize>() + blocks.len().saturating_sub(1)
+}
+
+/// Split a block longer than `max_chars` at whitespace, keeping its position on every piece.
+fn split_block(block: &TextBlock, max_chars: usize) -> Vec<TextBlock> {
+    if block.text.chars().count() <= max_chars {
+        return vec![block.clone()];
+    }
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for word in block.text.split_whitespace() {
+        let mut word = word.to_string();
+        // Words longer than a whole chunk are cut
+        while word.chars().count() > max_chars {
+            let head: String = word.chars().take(max_chars).collect();
+            word = word.chars().skip(max_chars).collect();
+            if !current.is_empty() {
+                pieces.push(std::mem::take(&mut current));
+            }
+            pieces.push(head);
+        }
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            pieces.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+        .into_iter()
+        .map(|text| TextBlock {
+            text,
+            ..block.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_stay_on_their_page() {
+        let mut first = DocumentPage::new(1);
+        first.blocks = vec![
+            TextBlock::new("Revenue grew in every region.").with_bbox(BoundingBox::new(72.0, 700.0, 200.0, 12.0)),
+            TextBlock::new("Costs fell by four percent.").with_bbox(BoundingBox::new(72.0, 684.0, 180.0, 12.0)),
+            TextBlock::new("Headcount was flat.").with_bbox(BoundingBox::new(72.0, 668.0, 120.0, 12.0)),
+        ];
+        let mut second = DocumentPage::new(2);
+        second.blocks = vec![TextBlock::new("word ".repeat(30)).with_location("A1")];
+        let document = ParsedDocument {
+            name: "report.pdf".to_string(),
+            document_type: DocumentType::Pdf,
+            media_type: "application/pdf".to_string(),
+            pages: vec![first, second],
+        };
+
+        let chunks = document.chunks(&ChunkOptions::new().with_max_chars(60).with_overlap_blocks(1));
+        assert_eq!(chunks[0].text, "Revenue grew in every region.\nCosts fell by four percent.");
+        assert_eq!(chunks[0].bbox, Some(BoundingBox::new(72.0, 684.0, 200.0, 28.0)));
+        assert_eq!(chunks[1].text, "Costs fell by four percent.\nHeadcount was flat.");
+        assert!(chunks[2..].iter().all(|chunk| chunk.page == 2 && chunk.text.len() <= 60));
+        assert_eq!(chunks[2].location.as_deref(), Some("A1"));
+        assert_eq!(chunks.iter().filter(|chunk| chunk.page == 2).map(|chunk| chunk.text.split(' ').count()).sum::<usize>(), 30);
+
+        let citation = chunks[1].citation();
+        assert_eq!(citation.source_id, "report.pdf#1");
+        assert_eq!(citation.uri.as_deref(), Some("report.pdf#page=1"));
+        let content = document.to_document_content();
+        assert!(content.source.data.text.unwrap().starts_with("[Page 1]\nRevenue grew"));
+    }
+}
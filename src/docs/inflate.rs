@@ -0,0 +1,349 @@
+//! DEFLATE decompression for document parsing.
+//! 
+//! PDF streams and Office archives are compressed with DEFLATE, wrapped in a
+//! zlib header for PDF `FlateDecode` streams. This is a small decoder in the
+//! style of zlib's `puff`: it favours clarity over speed, which is fine for
+//! the document sizes the parsers handle. Output is capped, so a small
+//! decompression bomb cannot exhaust memory.
+
+use crate::types::{DocumentError, IndubitablyError, IndubitablyResult};
+
+const MAX_BITS: usize = 15;
+
+/// The most bytes `inflate` and `zlib_decompress` produce before failing.
+pub const MAX_INFLATED_BYTES: usize = 128 * 1024 * 1024;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn malformed(message: &str) -> IndubitablyError {
+    IndubitablyError::DocumentError(DocumentError::Malformed(format!("Invalid DEFLATE data: {}", message)))
+}
+
+/// Reads bits least significant first, as DEFLATE packs them.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            bit_buffer: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn bits(&mut self, count: u32) -> IndubitablyResult<u32> {
+        while self.bit_count < count {
+            let byte = *self.data.get(self.position).ok_or_else(|| malformed("unexpected end of data"))?;
+            self.position += 1;
+            self.bit_buffer |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buffer & ((1u32 << count) - 1);
+        self.bit_buffer >>= count;
+        self.bit_count -= count;
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        self.bit_buffer = 0;
+        self.bit_count = 0;
+    }
+}
+
+/// A canonical Huffman code, stored as code counts per length and symbols in code order.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        counts[0] = 0;
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader<'_>) -> IndubitablyResult<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for length in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(malformed("invalid Huffman code"))
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (Huffman::new(&lengths), Huffman::new(&[5u8; 30]))
+}
+
+fn dynamic_codes(reader: &mut BitReader<'_>) -> IndubitablyResult<(Huffman, Huffman)> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(malformed("too many codes"));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *index
+                    .checked_sub(1)
+                    .and_then(|previous| lengths.get(previous))
+                    .ok_or_else(|| malformed("repeat with no previous length"))?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if index + repeat > lengths.len() {
+            return Err(malformed("too many code lengths"));
+        }
+        lengths[index..index + repeat].fill(value);
+        index += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(malformed("no end-of-block code"));
+    }
+    Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+}
+
+fn too_large(max_output: usize) -> IndubitablyError {
+    malformed(&format!("output exceeds {} bytes", max_output))
+}
+
+fn inflate_block(
+    reader: &mut BitReader<'_>,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+    max_output: usize,
+) -> IndubitablyResult<()> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 if output.len() >= max_output => return Err(too_large(max_output)),
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASE.len() {
+                    return Err(malformed("invalid length code"));
+                }
+                let length = LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                let index = distances.decode(reader)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(malformed("invalid distance code"));
+                }
+                let distance = DISTANCE_BASE[index] as usize + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                if distance > output.len() {
+                    return Err(malformed("distance too far back"));
+                }
+                if output.len() + length > max_output {
+                    return Err(too_large(max_output));
+                }
+                let start = output.len() - distance;
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+        }
+    }
+}
+
+/// Decompress raw DEFLATE data, up to `MAX_INFLATED_BYTES`.
+pub fn inflate(data: &[u8]) -> IndubitablyResult<Vec<u8>> {
+    inflate_with_limit(data, MAX_INFLATED_BYTES)
+}
+
+/// Decompress raw DEFLATE data, failing once the output would exceed `max_output` bytes.
+pub fn inflate_with_limit(data: &[u8], max_output: usize) -> IndubitablyResult<Vec<u8>> {
+    let mut reader = BitReader::new(data);
+    let mut output = Vec::with_capacity(data.len().saturating_mul(4).min(max_output));
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align_to_byte();
+                let header = reader
+                    .data
+                    .get(reader.position..reader.position + 4)
+                    .ok_or_else(|| malformed("truncated stored block"))?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if length != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err(malformed("stored block length mismatch"));
+                }
+                let start = reader.position + 4;
+                let block = reader
+                    .data
+                    .get(start..start + length)
+                    .ok_or_else(|| malformed("truncated stored block"))?;
+                if output.len() + block.len() > max_output {
+                    return Err(too_large(max_output));
+                }
+                output.extend_from_slice(block);
+                reader.position = start + length;
+            }
+            1 => {
+                let (literals, distances) = fixed_codes();
+                inflate_block(&mut reader, &mut output, &literals, &distances, max_output)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances, max_output)?;
+            }
+            _ => return Err(malformed("invalid block type")),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Decompress zlib-wrapped DEFLATE data, as used by PDF `FlateDecode` streams.
+///
+/// The Adler-32 trailer is not checked, since many PDF writers get it wrong.
+pub fn zlib_decompress(data: &[u8]) -> IndubitablyResult<Vec<u8>> {
+    match data {
+        [cmf, flg, rest @ ..] if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 => {
+            inflate(rest)
+        }
+        _ => Err(malformed("missing zlib header")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inflate_block_types() {
+        // Stored, fixed Huffman and dynamic Huffman blocks from zlib
+        let stored = [0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o'];
+        assert_eq!(inflate(&stored).unwrap(), b"hello");
+
+        let fixed = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00];
+        assert_eq!(inflate(&fixed).unwrap(), b"hello hello hello");
+
+        let text = "the quick brown fox jumps over the lazy dog. ".repeat(20)
+            + &"Pack my box with five dozen liquor jugs! 0123456789 ".repeat(3);
+        let dynamic = zlib_decompress(&DYNAMIC_ZLIB).unwrap();
+        assert_eq!(String::from_utf8(dynamic).unwrap(), text);
+
+        assert!(inflate(&[0x07]).is_err());
+        assert!(zlib_decompress(b"not zlib").is_err());
+    }
+
+    #[test]
+    fn test_inflate_limits_output() {
+        // A fixed Huffman block of 'a' followed by 258-byte copies, 1 KiB inflating to over 64 KiB
+        let mut writer = BitWriter::default();
+        writer.bits(0b1, 1);
+        writer.bits(0b01, 2);
+        writer.code(0x30 + b'a' as u32, 8);
+        for _ in 0..300 {
+            writer.code(0b11000101, 8); // length code 285, 258 bytes
+            writer.code(0, 5); // distance code 0, 1 byte back
+        }
+        writer.code(0, 7); // end of block
+        let bomb = writer.finish();
+        assert!(bomb.len() < 1024);
+
+        assert_eq!(inflate(&bomb).unwrap().len(), 1 + 300 * 258);
+        let error = inflate_with_limit(&bomb, 64 * 1024).unwrap_err();
+        assert!(error.to_string().contains("exceeds 65536 bytes"), "{}", error);
+    }
+
+    /// Writes DEFLATE bits, with Huffman codes most significant bit first.
+    #[derive(Default)]
+    struct BitWriter {
+        bytes: Vec<u8>,
+        bit: u32,
+    }
+
+    impl BitWriter {
+        fn bits(&mut self, value: u32, count: u32) {
+            for i in 0..count {
+                if self.bit == 0 {
+                    self.bytes.push(0);
+                }
+                *self.bytes.last_mut().unwrap() |= ((value >> i & 1) as u8) << self.bit;
+                self.bit = (self.bit + 1) % 8;
+            }
+        }
+
+        fn code(&mut self, code: u32, length: u32) {
+            let reversed = (0..length).fold(0, |acc, i| acc << 1 | (code >> i & 1));
+            self.bits(reversed, length);
+        }
+
+        fn finish(self) -> Vec<u8> {
+            self.bytes
+        }
+    }
+
+    const DYNAMIC_ZLIB: [u8; 110] = [
+        0x78, 0xda, 0xed, 0xcb, 0x5b, 0x12, 0x44, 0x30, 0x14, 0x84, 0xe1, 0xad, 0xb4, 0x0d,
+        0x4c, 0x61, 0xc6, 0x6d, 0x17, 0xb6, 0x80, 0x09, 0x32, 0x83, 0x43, 0x24, 0x42, 0x56,
+        0xef, 0x94, 0x35, 0x28, 0x4f, 0x79, 0xec, 0xfa, 0xbf, 0xd6, 0xbd, 0xc0, 0x62, 0x64,
+        0xf3, 0x47, 0xad, 0xc8, 0x4e, 0x68, 0x69, 0xc7, 0xcf, 0x8c, 0xf3, 0x0a, 0xda, 0x84,
+        0x82, 0xe6, 0x3c, 0x54, 0xee, 0xc0, 0x97, 0xba, 0xd7, 0xb5, 0x3c, 0xf6, 0xd8, 0xe3,
+        0x5b, 0x71, 0x59, 0xb1, 0x1b, 0x0f, 0xd4, 0x8c, 0xac, 0xd4, 0x3d, 0x5a, 0xb9, 0x09,
+        0x4e, 0x4e, 0x4c, 0x18, 0xe4, 0x62, 0x48, 0xf1, 0xb7, 0x5b, 0x03, 0x84, 0x51, 0xfc,
+        0xfe, 0x24, 0x69, 0x96, 0x17, 0x8f, 0x7d, 0x4e, 0x42, 0x29, 0x78, 0x21,
+    ];
+}
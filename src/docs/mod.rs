@@ -0,0 +1,26 @@
+//! Document parsing for the SDK.
+//! 
+//! This module turns PDF, Word, Excel, PowerPoint and text documents into
+//! pages of text blocks with page numbers and bounding boxes, and splits them
+//! into chunks for retrieval, which a `Retriever` ranks against queries, or
+//! converts them to `DocumentContent` for messages. Decompression and
+//! archive reading are implemented in-crate; OCR of images and scanned pages
+//! through Tesseract needs the `ocr` feature.
+
+pub mod inflate;
+pub mod zip;
+pub mod document;
+pub mod pdf;
+pub mod office;
+pub mod parser;
+pub mod retrieval;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+
+pub use document::{BoundingBox, ChunkOptions, DocumentChunk, DocumentPage, ParsedDocument, TextBlock};
+pub use parser::DocumentParser;
+pub use retrieval::{
+    create_search_documents_tool, EmbeddingRetriever, Retriever, ScoredChunk, SEARCH_DOCUMENTS_TOOL_NAME,
+};
+#[cfg(feature = "ocr")]
+pub use ocr::TesseractOcr;
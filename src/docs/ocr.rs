@@ -0,0 +1,126 @@
+//! Optical character recognition with Tesseract.
+//! 
+//! This module runs the `tesseract` command-line tool on images and reads its
+//! TSV output, grouping recognized words into lines with pixel bounding boxes.
+//! It is compiled with the `ocr` feature and needs Tesseract installed.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::document::{BoundingBox, TextBlock};
+use crate::types::{DocumentError, IndubitablyError, IndubitablyResult};
+
+/// The lowest word confidence, out of 100, kept in OCR output.
+const MIN_WORD_CONFIDENCE: f32 = 30.0;
+
+fn ocr_error(message: impl std::fmt::Display) -> IndubitablyError {
+    IndubitablyError::DocumentError(DocumentError::OcrFailed(message.to_string()))
+}
+
+/// Recognizes text in images with the Tesseract command-line tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TesseractOcr {
+    binary: PathBuf,
+    language: String,
+}
+
+impl TesseractOcr {
+    /// Use `tesseract` from the `PATH` with English.
+    pub fn new() -> Self {
+        Self {
+            binary: PathBuf::from("tesseract"),
+            language: "eng".to_string(),
+        }
+    }
+
+    /// Set the path of the Tesseract binary.
+    pub fn with_binary(mut self, binary: impl Into<PathBuf>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+
+    /// Set the Tesseract languages, such as `eng` or `eng+deu`.
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+
+    /// Recognize the text lines of an image.
+    pub fn recognize(&self, image: &[u8]) -> IndubitablyResult<Vec<TextBlock>> {
+        let path = std::env::temp_dir().join(format!("indubitably-ocr-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&path, image).map_err(ocr_error)?;
+        let output = Command::new(&self.binary)
+            .arg(&path)
+            .arg("stdout")
+            .args(["-l", &self.language, "tsv"])
+            .output();
+        let _ = std::fs::remove_file(&path);
+
+        let output = output.map_err(|e| ocr_error(format!("could not run {}: {}", self.binary.display(), e)))?;
+        if !output.status.success() {
+            return Err(ocr_error(String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        let blocks = parse_tsv(&String::from_utf8_lossy(&output.stdout));
+        tracing::debug!("language=<{}>, lines=<{}> | recognized image text", self.language, blocks.len());
+        Ok(blocks)
+    }
+}
+
+impl Default for TesseractOcr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Group the words of Tesseract TSV output into lines.
+///
+/// Words below `MIN_WORD_CONFIDENCE` are dropped. Boxes are in pixels from
+/// the top-left of the image.
+pub fn parse_tsv(tsv: &str) -> Vec<TextBlock> {
+    let mut blocks: Vec<TextBlock> = Vec::new();
+    let mut current_line = None;
+    for row in tsv.lines().skip(1) {
+        let columns: Vec<&str> = row.split('\t').collect();
+        if columns.len() < 12 || columns[0] != "5" {
+            continue;
+        }
+        let number = |index: usize| columns[index].trim().parse::<f32>().unwrap_or(-1.0);
+        let text = columns[11].trim();
+        if text.is_empty() || number(10) < MIN_WORD_CONFIDENCE {
+            continue;
+        }
+        let bbox = BoundingBox::new(number(6), number(7), number(8), number(9));
+        let line = (columns[1], columns[2], columns[3], columns[4]);
+        match blocks.last_mut() {
+            Some(block) if current_line == Some(line) => {
+                block.text.push(' ');
+                block.text.push_str(text);
+                block.bbox = block.bbox.map(|union| union.union(&bbox));
+            }
+            _ => {
+                blocks.push(TextBlock::new(text).with_bbox(bbox));
+                current_line = Some(line);
+            }
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tesseract_tsv() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                   4\t1\t1\t1\t1\t0\t10\t20\t200\t30\t-1\t\n\
+                   5\t1\t1\t1\t1\t1\t10\t20\t80\t30\t96.5\tInvoice\n\
+                   5\t1\t1\t1\t1\t2\t100\t22\t110\t28\t91.0\t#2024-17\n\
+                   5\t1\t1\t1\t2\t1\t10\t60\t40\t30\t12.0\t~~\n\
+                   5\t1\t1\t1\t3\t1\t10\t100\t60\t30\t88.0\tTotal\n";
+        let blocks = parse_tsv(tsv);
+        let lines: Vec<&str> = blocks.iter().map(|block| block.text.as_str()).collect();
+        assert_eq!(lines, vec!["Invoice #2024-17", "Total"]);
+        assert_eq!(blocks[0].bbox, Some(BoundingBox::new(10.0, 20.0, 200.0, 30.0)));
+    }
+}
@@ -0,0 +1,376 @@
+//! Office document parsing.
+//! 
+//! This module extracts text from Office Open XML documents: DOCX paragraphs,
+//! XLSX rows and PPTX slides. Word documents do not record page layout, so
+//! DOCX pages are split only at explicit and last-rendered page breaks. Each
+//! spreadsheet sheet and each slide becomes a page.
+
+use std::collections::HashMap;
+
+use super::document::{DocumentPage, TextBlock};
+use super::zip::ZipArchive;
+use crate::types::{DocumentError, IndubitablyError, IndubitablyResult};
+
+/// A piece of an XML document.
+#[derive(Debug, Clone, PartialEq)]
+enum XmlEvent<'a> {
+    /// An opening or self-closing tag with its raw attribute text.
+    Start { name: &'a str, attributes: &'a str, empty: bool },
+    /// A closing tag.
+    End(&'a str),
+    /// Text between tags, with entities decoded.
+    Text(String),
+}
+
+/// Split XML into tags and text, skipping declarations and comments.
+///
+/// This is not a validating parser; it is enough for the machine-written XML
+/// inside Office documents.
+fn xml_events(xml: &str) -> Vec<XmlEvent<'_>> {
+    let mut events = Vec::new();
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            events.push(XmlEvent::Text(unescape(rest)));
+            break;
+        };
+        if start > 0 {
+            events.push(XmlEvent::Text(unescape(&rest[..start])));
+        }
+        rest = &rest[start..];
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            events.push(XmlEvent::Text(cdata[..end].to_string()));
+            rest = cdata.get(end + 3..).unwrap_or_default();
+            continue;
+        }
+        let terminator = if rest.starts_with("<!--") { "-->" } else { ">" };
+        let Some(end) = rest.find(terminator) else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + terminator.len()..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            events.push(XmlEvent::End(name.trim()));
+            continue;
+        }
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        events.push(XmlEvent::Start { name, attributes, empty });
+    }
+    events
+}
+
+/// Get an attribute value from raw attribute text.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(equals) = rest.find('=') {
+        let key = rest[..equals].trim();
+        let value = rest[equals + 1..].trim_start();
+        let quote = value.chars().next()?;
+        let end = value[1..].find(quote)? + 1;
+        if key == name {
+            return Some(unescape(&value[1..end]));
+        }
+        rest = &value[end + 1..];
+    }
+    None
+}
+
+/// Decode the predefined XML entities and character references.
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let decoded = match &rest[1..end] {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(character) => {
+                output.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn missing_part(part: &str) -> IndubitablyError {
+    IndubitablyError::DocumentError(DocumentError::Malformed(format!("Missing document part '{}'", part)))
+}
+
+/// Extract the pages of a Word document.
+pub fn parse_docx(archive: &ZipArchive<'_>) -> IndubitablyResult<Vec<DocumentPage>> {
+    let xml = archive
+        .read_string("word/document.xml")?
+        .ok_or_else(|| missing_part("word/document.xml"))?;
+    let mut pages = vec![DocumentPage::new(1)];
+    let mut paragraph = String::new();
+    let mut in_text = false;
+    for event in xml_events(&xml) {
+        match event {
+            XmlEvent::Start { name: "w:t", empty: false, .. } => in_text = true,
+            XmlEvent::End("w:t") => in_text = false,
+            XmlEvent::Text(text) if in_text => paragraph.push_str(&text),
+            XmlEvent::Start { name: "w:tab", .. } => paragraph.push('\t'),
+            XmlEvent::Start { name: "w:br", attributes, .. } if attribute(attributes, "w:type").as_deref() != Some("page") => {
+                paragraph.push('\n')
+            }
+            XmlEvent::Start { name: "w:br" | "w:lastRenderedPageBreak", .. } => {
+                let text = std::mem::take(&mut paragraph);
+                let page = pages.last_mut().expect("pages start non-empty");
+                if !text.trim().is_empty() {
+                    page.blocks.push(TextBlock::new(text.trim()));
+                }
+                // A break before any text on the page starts nothing new
+                if !page.blocks.is_empty() {
+                    pages.push(DocumentPage::new(pages.len() as u32 + 1));
+                }
+            }
+            XmlEvent::End("w:p") => {
+                let text = std::mem::take(&mut paragraph);
+                if !text.trim().is_empty() {
+                    pages.last_mut().expect("pages start non-empty").blocks.push(TextBlock::new(text.trim()));
+                }
+            }
+            _ => {}
+        }
+    }
+    if pages.len() > 1 && pages.last().is_some_and(|page| page.blocks.is_empty()) {
+        pages.pop();
+    }
+    Ok(pages)
+}
+
+/// Resolve the part targets of a relationships file by relationship ID.
+fn relationships(archive: &ZipArchive<'_>, path: &str, base: &str) -> IndubitablyResult<HashMap<String, String>> {
+    let xml = archive.read_string(path)?.unwrap_or_default();
+    Ok(xml_events(&xml)
+        .into_iter()
+        .filter_map(|event| match event {
+            XmlEvent::Start { name: "Relationship", attributes, .. } => {
+                let target = attribute(attributes, "Target")?;
+                let target = match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => format!("{}/{}", base, target),
+                };
+                Some((attribute(attributes, "Id")?, target))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+/// Extract the sheets of a spreadsheet, one page per sheet and one block per row.
+///
+/// Cells are separated by tabs, and each row block records its cell range.
+pub fn parse_xlsx(archive: &ZipArchive<'_>) -> IndubitablyResult<Vec<DocumentPage>> {
+    let shared_strings: Vec<String> = {
+        let xml = archive.read_string("xl/sharedStrings.xml")?.unwrap_or_default();
+        let mut strings = Vec::new();
+        let mut in_text = false;
+        for event in xml_events(&xml) {
+            match event {
+                XmlEvent::Start { name: "si", .. } => strings.push(String::new()),
+                XmlEvent::Start { name: "t", empty: false, .. } => in_text = true,
+                XmlEvent::End("t") => in_text = false,
+                XmlEvent::Text(text) if in_text => {
+                    if let Some(string) = strings.last_mut() {
+                        string.push_str(&text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        strings
+    };
+    let targets = relationships(archive, "xl/_rels/workbook.xml.rels", "xl")?;
+    let workbook = archive
+        .read_string("xl/workbook.xml")?
+        .ok_or_else(|| missing_part("xl/workbook.xml"))?;
+
+    let mut pages = Vec::new();
+    for event in xml_events(&workbook) {
+        let XmlEvent::Start { name: "sheet", attributes, .. } = event else {
+            continue;
+        };
+        let Some(target) = attribute(attributes, "r:id").and_then(|id| targets.get(&id)) else {
+            continue;
+        };
+        let Some(sheet) = archive.read_string(target)? else {
+            continue;
+        };
+        let mut page = DocumentPage::new(pages.len() as u32 + 1);
+        page.title = attribute(attributes, "name");
+
+        let mut cells: Vec<(String, String)> = Vec::new();
+        let mut cell: Option<(String, String)> = None;
+        let mut value = String::new();
+        let mut in_value = false;
+        for event in xml_events(&sheet) {
+            match event {
+                XmlEvent::Start { name: "row", .. } => cells.clear(),
+                XmlEvent::Start { name: "c", attributes, empty } => {
+                    value.clear();
+                    cell = (!empty).then(|| {
+                        (attribute(attributes, "r").unwrap_or_default(), attribute(attributes, "t").unwrap_or_default())
+                    });
+                }
+                XmlEvent::Start { name: "v" | "t", empty: false, .. } => in_value = true,
+                XmlEvent::End("v" | "t") => in_value = false,
+                XmlEvent::Text(text) if in_value => value.push_str(&text),
+                XmlEvent::End("c") => {
+                    if let Some((reference, kind)) = cell.take() {
+                        let text = match kind.as_str() {
+                            "s" => value.trim().parse::<usize>().ok().and_then(|index| shared_strings.get(index).cloned()),
+                            "b" => Some(if value.trim() == "1" { "TRUE" } else { "FALSE" }.to_string()),
+                            _ => Some(value.clone()),
+                        };
+                        if let Some(text) = text.filter(|text| !text.is_empty()) {
+                            cells.push((reference, text));
+                        }
+                    }
+                }
+                XmlEvent::End("row") if !cells.is_empty() => {
+                    let text = cells.iter().map(|(_, text)| text.as_str()).collect::<Vec<_>>().join("\t");
+                    let first = &cells[0].0;
+                    let last = &cells[cells.len() - 1].0;
+                    let location = if first == last { first.clone() } else { format!("{}:{}", first, last) };
+                    page.blocks.push(TextBlock::new(text).with_location(&location));
+                    cells.clear();
+                }
+                _ => {}
+            }
+        }
+        tracing::debug!(
+            "sheet=<{}>, rows=<{}> | parsed spreadsheet sheet",
+            page.title.as_deref().unwrap_or_default(),
+            page.blocks.len()
+        );
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+/// Extract the slides of a presentation, one page per slide and one block per paragraph.
+pub fn parse_pptx(archive: &ZipArchive<'_>) -> IndubitablyResult<Vec<DocumentPage>> {
+    let targets = relationships(archive, "ppt/_rels/presentation.xml.rels", "ppt")?;
+    let presentation = archive
+        .read_string("ppt/presentation.xml")?
+        .ok_or_else(|| missing_part("ppt/presentation.xml"))?;
+
+    let mut pages = Vec::new();
+    for event in xml_events(&presentation) {
+        let XmlEvent::Start { name: "p:sldId", attributes, .. } = event else {
+            continue;
+        };
+        let Some(slide) = attribute(attributes, "r:id")
+            .and_then(|id| targets.get(&id))
+            .map(|target| archive.read_string(target))
+            .transpose()?
+            .flatten()
+        else {
+            continue;
+        };
+        let mut page = DocumentPage::new(pages.len() as u32 + 1);
+        let mut paragraph = String::new();
+        let mut in_text = false;
+        for event in xml_events(&slide) {
+            match event {
+                XmlEvent::Start { name: "a:t", empty: false, .. } => in_text = true,
+                XmlEvent::End("a:t") => in_text = false,
+                XmlEvent::Text(text) if in_text => paragraph.push_str(&text),
+                XmlEvent::Start { name: "a:br", .. } => paragraph.push('\n'),
+                XmlEvent::End("a:p") => {
+                    let text = std::mem::take(&mut paragraph);
+                    if !text.trim().is_empty() {
+                        page.blocks.push(TextBlock::new(text.trim()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        page.title = page.blocks.first().map(|block| block.text.clone());
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docs::zip::ZipWriter;
+
+    /// Build a ZIP archive of stored entries.
+    fn archive(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new();
+        for (name, content) in entries {
+            writer.add(name, content.as_bytes()).unwrap();
+        }
+        writer.finish()
+    }
+
+    #[test]
+    fn test_parse_docx_and_xlsx() {
+        let docx = archive(&[(
+            "word/document.xml",
+            r#"<?xml version="1.0"?><w:document><w:body>
+                <w:p><w:r><w:t>Q3 &amp; Q4</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve">review</w:t></w:r></w:p>
+                <w:p><w:r><w:br w:type="page"/><w:t>Appendix</w:t></w:r></w:p>
+            </w:body></w:document>"#,
+        )]);
+        let pages = parse_docx(&ZipArchive::new(&docx).unwrap()).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].text(), "Q3 & Q4\treview");
+        assert_eq!(pages[1].text(), "Appendix");
+
+        let xlsx = archive(&[
+            ("xl/workbook.xml", r#"<workbook><sheets><sheet name="Sales" sheetId="1" r:id="rId1"/></sheets></workbook>"#),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            ),
+            ("xl/sharedStrings.xml", r#"<sst><si><t>Region</t></si><si><r><t>Nor</t></r><r><t>th</t></r></si></sst>"#),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData>
+                    <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="inlineStr"><is><t>Total</t></is></c></row>
+                    <row r="2"><c r="A2" t="s"><v>1</v></c><c r="B2"><v>42.5</v></c></row>
+                </sheetData></worksheet>"#,
+            ),
+        ]);
+        let pages = parse_xlsx(&ZipArchive::new(&xlsx).unwrap()).unwrap();
+        assert_eq!(pages[0].title.as_deref(), Some("Sales"));
+        assert_eq!(pages[0].text(), "Region\tTotal\nNorth\t42.5");
+        assert_eq!(pages[0].blocks[1].location.as_deref(), Some("A2:B2"));
+    }
+}
@@ -0,0 +1,213 @@
+//! Document format detection and parsing.
+//! 
+//! `DocumentParser` recognizes a document by its leading bytes, falling back
+//! to the file extension for text formats, and hands it to the matching
+//! parser. With the `ocr` feature and an OCR engine configured, images and
+//! PDF pages without a text layer are recognized with Tesseract.
+
+use std::path::Path;
+
+use super::document::{DocumentPage, ParsedDocument, TextBlock};
+use super::office::{parse_docx, parse_pptx, parse_xlsx};
+use super::pdf::parse_pdf;
+use super::zip::ZipArchive;
+#[cfg(feature = "ocr")]
+use super::ocr::TesseractOcr;
+use crate::tools::workspace::media_type_for;
+use crate::types::{DocumentError, DocumentType, IndubitablyError, IndubitablyResult};
+
+/// The leading bytes of the image formats Tesseract reads.
+const IMAGE_SIGNATURES: &[&[u8]] = &[b"\x89PNG", b"\xff\xd8\xff", b"II*\0", b"MM\0*", b"BM", b"GIF8"];
+
+/// Parses documents into pages of text.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentParser {
+    #[cfg(feature = "ocr")]
+    ocr: Option<TesseractOcr>,
+}
+
+impl DocumentParser {
+    /// Create a parser without OCR.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recognize images and scanned PDF pages with the given OCR engine.
+    #[cfg(feature = "ocr")]
+    pub fn with_ocr(mut self, ocr: TesseractOcr) -> Self {
+        self.ocr = Some(ocr);
+        self
+    }
+
+    /// Read and parse a file, naming the document after the file.
+    pub fn parse_file(&self, path: impl AsRef<Path>) -> IndubitablyResult<ParsedDocument> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|e| {
+            IndubitablyError::DocumentError(DocumentError::Malformed(format!("Cannot read {}: {}", path.display(), e)))
+        })?;
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        self.parse(&data, &name)
+    }
+
+    /// Parse a document, using `name` for its file extension and in chunk IDs and citations.
+    pub fn parse(&self, data: &[u8], name: &str) -> IndubitablyResult<ParsedDocument> {
+        let (document_type, media_type, pages) = if data.starts_with(b"%PDF") {
+            (DocumentType::Pdf, "application/pdf", self.pdf_pages(data)?)
+        } else if data.starts_with(b"PK\x03\x04") {
+            let archive = ZipArchive::new(data)?;
+            if archive.contains("word/document.xml") {
+                let media_type = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+                (DocumentType::Word, media_type, parse_docx(&archive)?)
+            } else if archive.contains("xl/workbook.xml") {
+                let media_type = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+                (DocumentType::Excel, media_type, parse_xlsx(&archive)?)
+            } else if archive.contains("ppt/presentation.xml") {
+                let media_type = "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+                (DocumentType::Powerpoint, media_type, parse_pptx(&archive)?)
+            } else {
+                return Err(unsupported(name));
+            }
+        } else if IMAGE_SIGNATURES.iter().any(|signature| data.starts_with(signature)) {
+            (DocumentType::Text, media_type_for(Path::new(name)), self.image_pages(data, name)?)
+        } else {
+            let text = std::str::from_utf8(data).map_err(|_| unsupported(name))?;
+            let document_type = text_type(name);
+            let pages = match document_type {
+                DocumentType::Csv => csv_pages(text),
+                _ => paragraph_pages(text),
+            };
+            (document_type, media_type_for(Path::new(name)), pages)
+        };
+
+        tracing::debug!(
+            "name=<{}>, media_type=<{}>, pages=<{}> | parsed document",
+            name,
+            media_type,
+            pages.len()
+        );
+        Ok(ParsedDocument {
+            name: name.to_string(),
+            document_type,
+            media_type: media_type.to_string(),
+            pages,
+        })
+    }
+
+    fn pdf_pages(&self, data: &[u8]) -> IndubitablyResult<Vec<DocumentPage>> {
+        let pages = parse_pdf(data)?;
+        #[cfg(feature = "ocr")]
+        if let Some(ref ocr) = self.ocr {
+            return pages
+                .into_iter()
+                .map(|pdf_page| {
+                    let mut page = pdf_page.page;
+                    if page.blocks.is_empty() {
+                        // OCR boxes are in image pixels, not PDF points
+                        for image in &pdf_page.jpeg_images {
+                            page.blocks.extend(ocr.recognize(image)?);
+                        }
+                    }
+                    Ok(page)
+                })
+                .collect();
+        }
+        Ok(pages.into_iter().map(|pdf_page| pdf_page.page).collect())
+    }
+
+    #[cfg(feature = "ocr")]
+    fn image_pages(&self, data: &[u8], name: &str) -> IndubitablyResult<Vec<DocumentPage>> {
+        let ocr = self.ocr.as_ref().ok_or_else(|| {
+            IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(format!(
+                "{} is an image; configure OCR to read it",
+                name
+            )))
+        })?;
+        let mut page = DocumentPage::new(1);
+        page.blocks = ocr.recognize(data)?;
+        Ok(vec![page])
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    fn image_pages(&self, _data: &[u8], name: &str) -> IndubitablyResult<Vec<DocumentPage>> {
+        Err(IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(format!(
+            "{} is an image; reading it requires the `ocr` feature",
+            name
+        ))))
+    }
+}
+
+fn unsupported(name: &str) -> IndubitablyError {
+    IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(name.to_string()))
+}
+
+/// Get the document type of a text file from its extension.
+fn text_type(name: &str) -> DocumentType {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "md" | "markdown" => DocumentType::Markdown,
+        "html" | "htm" => DocumentType::Html,
+        "csv" => DocumentType::Csv,
+        "json" => DocumentType::Json,
+        "xml" => DocumentType::Xml,
+        _ => DocumentType::Text,
+    }
+}
+
+/// Split text into one page of paragraph blocks.
+fn paragraph_pages(text: &str) -> Vec<DocumentPage> {
+    let mut page = DocumentPage::new(1);
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in text.lines().chain(std::iter::once("")) {
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                page.blocks.push(TextBlock::new(paragraph.join("\n")));
+                paragraph.clear();
+            }
+        } else {
+            paragraph.push(line.trim_end());
+        }
+    }
+    vec![page]
+}
+
+/// Split CSV into one page with a block per row, located by row number.
+fn csv_pages(text: &str) -> Vec<DocumentPage> {
+    let mut page = DocumentPage::new(1);
+    for (index, line) in text.lines().enumerate() {
+        if !line.trim().is_empty() {
+            page.blocks.push(TextBlock::new(line).with_location(&format!("row {}", index + 1)));
+        }
+    }
+    vec![page]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_detects_formats() {
+        let parser = DocumentParser::new();
+        let notes = parser.parse(b"# Notes\n\nFirst paragraph\ncontinues.\n\n\nSecond.", "notes.md").unwrap();
+        assert_eq!(notes.document_type, DocumentType::Markdown);
+        assert_eq!(notes.media_type, "text/markdown");
+        assert_eq!(notes.pages[0].blocks.len(), 3);
+        assert_eq!(notes.pages[0].blocks[1].text, "First paragraph\ncontinues.");
+
+        let table = parser.parse(b"region,total\nnorth,42\n", "sales.csv").unwrap();
+        assert_eq!(table.pages[0].blocks[1].location.as_deref(), Some("row 2"));
+
+        let pdf = b"%PDF-1.4\n1 0 obj << /Type /Page /Contents 2 0 R >> endobj\n\
+                    2 0 obj << /Length 31 >>\nstream\nBT /F1 12 Tf (Hello PDF) Tj ET\nendstream endobj";
+        let document = parser.parse(pdf, "hello.pdf").unwrap();
+        assert_eq!(document.document_type, DocumentType::Pdf);
+        assert_eq!(document.text(), "Hello PDF");
+
+        assert!(parser.parse(b"PK\x03\x04 not an office file", "archive.zip").is_err());
+        assert!(parser.parse(b"\x89PNG\r\n\x1a\n", "scan.png").is_err());
+    }
+}
@@ -0,0 +1,1030 @@
+//! PDF text extraction.
+//! 
+//! This module reads the objects of a PDF by scanning for them rather than
+//! trusting the cross-reference table, which also recovers damaged files. It
+//! walks the page tree, decodes `FlateDecode` content streams and object
+//! streams, maps glyph codes to text through `ToUnicode` CMaps, and groups
+//! shown text into lines with bounding boxes in PDF points. Glyph widths are
+//! estimated from the font size, so boxes are approximate. Scanned pages have
+//! no text; their JPEG images are returned for OCR.
+
+use std::collections::{HashMap, HashSet};
+
+use regex::bytes::Regex;
+
+use super::document::{BoundingBox, DocumentPage, TextBlock};
+use super::inflate::{inflate, zlib_decompress};
+use crate::types::{DocumentError, IndubitablyError, IndubitablyResult};
+
+/// How deeply form XObjects may nest before they are ignored.
+const MAX_FORM_DEPTH: usize = 8;
+
+/// The average glyph width as a fraction of the font size, used to estimate text extents.
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+
+fn malformed(message: &str) -> IndubitablyError {
+    IndubitablyError::DocumentError(DocumentError::Malformed(format!("Invalid PDF: {}", message)))
+}
+
+/// A PDF object, or an operator in a content stream.
+#[derive(Debug, Clone, PartialEq)]
+enum Object {
+    Null,
+    Bool(bool),
+    Number(f32),
+    Name(String),
+    String(Vec<u8>),
+    Array(Vec<Object>),
+    Dictionary(HashMap<String, Object>),
+    Reference(u32),
+    Operator(String),
+}
+
+impl Object {
+    fn as_number(&self) -> Option<f32> {
+        match self {
+            Object::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&[Object]> {
+        match self {
+            Object::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn as_dictionary(&self) -> Option<&HashMap<String, Object>> {
+        match self {
+            Object::Dictionary(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Object> {
+        self.as_dictionary()?.get(key)
+    }
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b'\x0c' | b'\0')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(byte, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+/// Reads objects and operators from PDF syntax.
+struct Lexer<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8], position: usize) -> Self {
+        Self { data, position }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if is_whitespace(byte) {
+                self.position += 1;
+            } else if byte == b'%' {
+                while self.peek().is_some_and(|byte| byte != b'\n' && byte != b'\r') {
+                    self.position += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn regular_word(&mut self) -> &'a [u8] {
+        let start = self.position;
+        while self.peek().is_some_and(|byte| !is_whitespace(byte) && !is_delimiter(byte)) {
+            self.position += 1;
+        }
+        &self.data[start..self.position]
+    }
+
+    /// Read the next object or operator, or `None` at the end of the data.
+    fn next(&mut self) -> Option<Object> {
+        self.skip_whitespace();
+        let byte = self.peek()?;
+        match byte {
+            b'/' => {
+                self.position += 1;
+                Some(Object::Name(decode_name(self.regular_word())))
+            }
+            b'(' => Some(Object::String(self.literal_string())),
+            b'<' if self.data.get(self.position + 1) == Some(&b'<') => {
+                self.position += 2;
+                let mut entries = HashMap::new();
+                loop {
+                    match self.next()? {
+                        Object::Operator(operator) if operator == ">>" => break,
+                        Object::Name(key) => {
+                            let value = self.next()?;
+                            entries.insert(key, value);
+                        }
+                        _ => {}
+                    }
+                }
+                Some(Object::Dictionary(entries))
+            }
+            b'<' => Some(Object::String(self.hex_string())),
+            b'>' if self.data.get(self.position + 1) == Some(&b'>') => {
+                self.position += 2;
+                Some(Object::Operator(">>".to_string()))
+            }
+            b'[' => {
+                self.position += 1;
+                let mut items = Vec::new();
+                loop {
+                    match self.next()? {
+                        Object::Operator(operator) if operator == "]" => break,
+                        item => items.push(item),
+                    }
+                }
+                Some(Object::Array(items))
+            }
+            b']' | b'>' | b')' | b'{' | b'}' => {
+                self.position += 1;
+                Some(Object::Operator((byte as char).to_string()))
+            }
+            b'0'..=b'9' | b'+' | b'-' | b'.' => {
+                let word = self.regular_word();
+                let number = std::str::from_utf8(word).ok()?.parse::<f32>().unwrap_or(0.0);
+                if word.iter().all(u8::is_ascii_digit) {
+                    if let Some(reference) = self.reference_after(number as u32) {
+                        return Some(reference);
+                    }
+                }
+                Some(Object::Number(number))
+            }
+            _ => {
+                let word = self.regular_word();
+                if word.is_empty() {
+                    self.position += 1;
+                    return self.next();
+                }
+                Some(match word {
+                    b"true" => Object::Bool(true),
+                    b"false" => Object::Bool(false),
+                    b"null" => Object::Null,
+                    _ => Object::Operator(String::from_utf8_lossy(word).into_owned()),
+                })
+            }
+        }
+    }
+
+    /// Read `G R` after an object number, restoring the position when they are not there.
+    fn reference_after(&mut self, number: u32) -> Option<Object> {
+        let start = self.position;
+        self.skip_whitespace();
+        let generation = self.regular_word();
+        if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) {
+            self.skip_whitespace();
+            if self.regular_word() == b"R" {
+                return Some(Object::Reference(number));
+            }
+        }
+        self.position = start;
+        None
+    }
+
+    fn literal_string(&mut self) -> Vec<u8> {
+        self.position += 1;
+        let mut output = Vec::new();
+        let mut depth = 1;
+        while let Some(byte) = self.peek() {
+            self.position += 1;
+            match byte {
+                b'(' => {
+                    depth += 1;
+                    output.push(byte);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                    output.push(byte);
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else {
+                        break;
+                    };
+                    self.position += 1;
+                    match escaped {
+                        b'n' => output.push(b'\n'),
+                        b'r' => output.push(b'\r'),
+                        b't' => output.push(b'\t'),
+                        b'b' => output.push(0x08),
+                        b'f' => output.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(digit @ b'0'..=b'7') => {
+                                        value = value * 8 + (digit - b'0') as u32;
+                                        self.position += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            output.push(value as u8);
+                        }
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.position += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => output.push(other),
+                    }
+                }
+                _ => output.push(byte),
+            }
+        }
+        output
+    }
+
+    fn hex_string(&mut self) -> Vec<u8> {
+        self.position += 1;
+        let mut digits = Vec::new();
+        while let Some(byte) = self.peek() {
+            self.position += 1;
+            if byte == b'>' {
+                break;
+            }
+            if let Some(digit) = (byte as char).to_digit(16) {
+                digits.push(digit as u8);
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect()
+    }
+
+    /// Skip the binary data of an inline image, up to and including `EI`.
+    fn skip_inline_image(&mut self) {
+        while self.position + 2 < self.data.len() {
+            let window = &self.data[self.position..];
+            if is_whitespace(window[0])
+                && window[1] == b'E'
+                && window[2] == b'I'
+                && window.get(3).is_none_or(|&byte| is_whitespace(byte) || is_delimiter(byte))
+            {
+                self.position += 3;
+                return;
+            }
+            self.position += 1;
+        }
+        self.position = self.data.len();
+    }
+}
+
+/// Decode `#xx` escapes in a name.
+fn decode_name(raw: &[u8]) -> String {
+    let mut output = Vec::with_capacity(raw.len());
+    let mut index = 0;
+    while index < raw.len() {
+        if raw[index] == b'#' && index + 2 < raw.len() {
+            if let Ok(byte) = u8::from_str_radix(&String::from_utf8_lossy(&raw[index + 1..index + 3]), 16) {
+                output.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        output.push(raw[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// A mapping from glyph codes to text, read from a `ToUnicode` CMap.
+#[derive(Debug, Clone, Default)]
+struct CMap {
+    code_length: usize,
+    map: HashMap<u32, String>,
+}
+
+fn utf16_text(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes.chunks(2).map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])).collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |value, &byte| value << 8 | byte as u32)
+}
+
+impl CMap {
+    fn parse(data: &[u8]) -> Self {
+        let mut cmap = CMap {
+            code_length: 1,
+            map: HashMap::new(),
+        };
+        let mut lexer = Lexer::new(data, 0);
+        let mut operands = Vec::new();
+        while let Some(object) = lexer.next() {
+            let Object::Operator(operator) = object else {
+                operands.push(object);
+                continue;
+            };
+            match operator.as_str() {
+                "endcodespacerange" => {
+                    if let Some(Object::String(low)) = operands.first() {
+                        cmap.code_length = low.len().max(1);
+                    }
+                }
+                "endbfchar" => {
+                    for pair in operands.chunks(2) {
+                        if let [Object::String(source), Object::String(target)] = pair {
+                            cmap.map.insert(code_value(source), utf16_text(target));
+                        }
+                    }
+                }
+                "endbfrange" => {
+                    for range in operands.chunks(3) {
+                        let [Object::String(low), Object::String(high), target] = range else {
+                            continue;
+                        };
+                        let (low, high) = (code_value(low), code_value(high));
+                        for (offset, code) in (low..=high.min(low + 0xffff)).enumerate() {
+                            let text = match target {
+                                Object::String(start) if !start.is_empty() => {
+                                    let mut bytes = start.clone();
+                                    let last = bytes.len() - 1;
+                                    bytes[last] = bytes[last].wrapping_add(offset as u8);
+                                    utf16_text(&bytes)
+                                }
+                                Object::Array(targets) => match targets.get(offset) {
+                                    Some(Object::String(bytes)) => utf16_text(bytes),
+                                    _ => continue,
+                                },
+                                _ => continue,
+                            };
+                            cmap.map.insert(code, text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            operands.clear();
+        }
+        cmap
+    }
+}
+
+/// Map a Windows-1252 byte to a character, covering the printable specials.
+fn win_ansi(byte: u8) -> char {
+    match byte {
+        0x80 => '€',
+        0x85 => '…',
+        0x91 => '‘',
+        0x92 => '’',
+        0x93 => '“',
+        0x94 => '”',
+        0x95 => '•',
+        0x96 => '–',
+        0x97 => '—',
+        0x99 => '™',
+        _ => byte as char,
+    }
+}
+
+/// A font's glyph code decoding.
+#[derive(Debug, Clone, Default)]
+struct Font {
+    cmap: Option<CMap>,
+    two_byte: bool,
+}
+
+impl Font {
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self.cmap {
+            Some(ref cmap) => bytes
+                .chunks(cmap.code_length)
+                .map(|code| cmap.map.get(&code_value(code)).cloned().unwrap_or_default())
+                .collect(),
+            // Without a CMap two-byte codes are glyph IDs with no known text
+            None if self.two_byte => String::new(),
+            None => bytes
+                .iter()
+                .filter(|&&byte| byte >= 0x20 || byte == b'\t')
+                .map(|&byte| win_ansi(byte))
+                .collect(),
+        }
+    }
+}
+
+type Matrix = [f32; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+fn multiply(first: &Matrix, second: &Matrix) -> Matrix {
+    [
+        first[0] * second[0] + first[1] * second[2],
+        first[0] * second[1] + first[1] * second[3],
+        first[2] * second[0] + first[3] * second[2],
+        first[2] * second[1] + first[3] * second[3],
+        first[4] * second[0] + first[5] * second[2] + second[4],
+        first[4] * second[1] + first[5] * second[3] + second[5],
+    ]
+}
+
+fn matrix_from(operands: &[Object]) -> Option<Matrix> {
+    let values: Vec<f32> = operands.iter().filter_map(Object::as_number).collect();
+    values.try_into().ok()
+}
+
+/// A line of text being assembled from text-showing operators.
+struct Line {
+    text: String,
+    x: f32,
+    y: f32,
+    end: f32,
+    size: f32,
+}
+
+/// The state of a content stream interpretation.
+struct TextState {
+    ctm: Matrix,
+    stack: Vec<Matrix>,
+    text_matrix: Matrix,
+    line_matrix: Matrix,
+    font: Font,
+    font_size: f32,
+    leading: f32,
+    line: Option<Line>,
+    blocks: Vec<TextBlock>,
+}
+
+impl TextState {
+    fn new() -> Self {
+        Self {
+            ctm: IDENTITY,
+            stack: Vec::new(),
+            text_matrix: IDENTITY,
+            line_matrix: IDENTITY,
+            font: Font::default(),
+            font_size: 0.0,
+            leading: 0.0,
+            line: None,
+            blocks: Vec::new(),
+        }
+    }
+
+    fn move_line(&mut self, tx: f32, ty: f32) {
+        self.line_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, tx, ty], &self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+
+    fn show(&mut self, bytes: &[u8]) {
+        let text = self.font.decode(bytes);
+        let glyphs = if self.font.two_byte && self.font.cmap.is_none() { bytes.len() / 2 } else { text.chars().count() };
+        let rendering = multiply(&self.text_matrix, &self.ctm);
+        let size = self.font_size * rendering[2].hypot(rendering[3]);
+        let (x, y) = (rendering[4], rendering[5]);
+        let width = glyphs as f32 * AVERAGE_GLYPH_WIDTH * size;
+
+        if !text.trim().is_empty() {
+            match self.line {
+                Some(ref mut line) if (line.y - y).abs() < line.size.max(size) * 0.5 => {
+                    if x - line.end > line.size.max(size) * 0.2 && !line.text.ends_with(' ') && !text.starts_with(' ') {
+                        line.text.push(' ');
+                    }
+                    line.text.push_str(&text);
+                    line.end = line.end.max(x + width);
+                    line.size = line.size.max(size);
+                }
+                _ => {
+                    self.finish_line();
+                    self.line = Some(Line {
+                        text,
+                        x,
+                        y,
+                        end: x + width,
+                        size,
+                    });
+                }
+            }
+        }
+        self.advance(glyphs as f32 * AVERAGE_GLYPH_WIDTH * self.font_size);
+    }
+
+    fn advance(&mut self, distance: f32) {
+        self.text_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, distance, 0.0], &self.text_matrix);
+    }
+
+    fn finish_line(&mut self) {
+        if let Some(line) = self.line.take() {
+            let text = line.text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                // The box runs from a typical descender depth below the baseline
+                let bbox = BoundingBox::new(line.x, line.y - line.size * 0.2, line.end - line.x, line.size);
+                self.blocks.push(TextBlock::new(text).with_bbox(bbox));
+            }
+        }
+    }
+}
+
+/// A page of a PDF with the images that may need OCR.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfPage {
+    /// The extracted page.
+    pub page: DocumentPage,
+    /// The page's JPEG images, in drawing order.
+    pub jpeg_images: Vec<Vec<u8>>,
+}
+
+/// The objects of a PDF file.
+struct PdfFile {
+    objects: HashMap<u32, Object>,
+    streams: HashMap<u32, Vec<u8>>,
+}
+
+impl PdfFile {
+    fn parse(data: &[u8]) -> IndubitablyResult<Self> {
+        if !data.starts_with(b"%PDF") && !data[..data.len().min(1024)].windows(4).any(|window| window == b"%PDF") {
+            return Err(malformed("missing %PDF header"));
+        }
+        let object_header = Regex::new(r"(\d+)\s+\d+\s+obj\b").expect("valid object header pattern");
+        let mut file = PdfFile {
+            objects: HashMap::new(),
+            streams: HashMap::new(),
+        };
+        let mut position = 0;
+        while let Some(header) = object_header.captures_at(data, position) {
+            let whole = header.get(0).expect("match has a whole group");
+            position = whole.end();
+            let Some(number) = std::str::from_utf8(&header[1]).ok().and_then(|number| number.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let mut lexer = Lexer::new(data, whole.end());
+            let Some(object) = lexer.next() else {
+                break;
+            };
+            lexer.skip_whitespace();
+            if data[lexer.position..].starts_with(b"stream") {
+                let mut start = lexer.position + 6;
+                if data.get(start) == Some(&b'\r') {
+                    start += 1;
+                }
+                if data.get(start) == Some(&b'\n') {
+                    start += 1;
+                }
+                // Trust the declared length only when `endstream` follows it
+                let declared = object
+                    .get("Length")
+                    .and_then(Object::as_number)
+                    .map(|length| start + length as usize)
+                    .filter(|&end| {
+                        let mut after = Lexer::new(data, end.min(data.len()));
+                        after.skip_whitespace();
+                        end <= data.len() && data[after.position..].starts_with(b"endstream")
+                    });
+                let end = declared
+                    .or_else(|| {
+                        find(&data[start..], b"endstream").map(|offset| {
+                            let mut end = start + offset;
+                            while end > start && matches!(data[end - 1], b'\r' | b'\n') {
+                                end -= 1;
+                            }
+                            end
+                        })
+                    })
+                    .unwrap_or(data.len());
+                file.streams.insert(number, data[start..end].to_vec());
+                position = end;
+            } else {
+                position = lexer.position;
+            }
+            file.objects.insert(number, object);
+        }
+        if file.objects.is_empty() {
+            return Err(malformed("no objects found"));
+        }
+        if find(data, b"/Encrypt").is_some() {
+            return Err(IndubitablyError::DocumentError(DocumentError::Encrypted(
+                "PDF is encrypted".to_string(),
+            )));
+        }
+        file.unpack_object_streams();
+        Ok(file)
+    }
+
+    /// Add the objects stored in compressed object streams.
+    fn unpack_object_streams(&mut self) {
+        let object_streams: Vec<u32> = self
+            .objects
+            .iter()
+            .filter(|(_, object)| object.get("Type").and_then(Object::as_name) == Some("ObjStm"))
+            .map(|(&number, _)| number)
+            .collect();
+        for number in object_streams {
+            let Some(data) = self.decoded_stream(number) else {
+                continue;
+            };
+            let object = &self.objects[&number];
+            let count = object.get("N").and_then(Object::as_number).unwrap_or(0.0) as usize;
+            let first = object.get("First").and_then(Object::as_number).unwrap_or(0.0) as usize;
+            let mut header = Lexer::new(&data, 0);
+            let mut entries = Vec::with_capacity(count);
+            for _ in 0..count {
+                match (header.next(), header.next()) {
+                    (Some(Object::Number(number)), Some(Object::Number(offset))) => {
+                        entries.push((number as u32, first + offset as usize))
+                    }
+                    _ => break,
+                }
+            }
+            for (number, offset) in entries {
+                if let Some(object) = Lexer::new(&data, offset).next() {
+                    self.objects.entry(number).or_insert(object);
+                }
+            }
+        }
+    }
+
+    fn resolve<'a>(&'a self, object: &'a Object) -> &'a Object {
+        let mut object = object;
+        for _ in 0..32 {
+            match object {
+                Object::Reference(number) => match self.objects.get(number) {
+                    Some(target) => object = target,
+                    None => return &Object::Null,
+                },
+                _ => return object,
+            }
+        }
+        &Object::Null
+    }
+
+    fn get<'a>(&'a self, object: &'a Object, key: &str) -> Option<&'a Object> {
+        self.resolve(object).get(key).map(|value| self.resolve(value))
+    }
+
+    /// Decode the stream of an object, or `None` when its filters are not supported.
+    fn decoded_stream(&self, number: u32) -> Option<Vec<u8>> {
+        let raw = self.streams.get(&number)?;
+        let object = self.objects.get(&number)?;
+        let filters: Vec<&str> = match self.get(object, "Filter") {
+            Some(Object::Name(name)) => vec![name.as_str()],
+            Some(Object::Array(names)) => names.iter().filter_map(|name| self.resolve(name).as_name()).collect(),
+            _ => Vec::new(),
+        };
+        let mut data = raw.clone();
+        for filter in filters {
+            data = match filter {
+                // Both stop at MAX_INFLATED_BYTES, so a bomb fails instead of exhausting memory
+                "FlateDecode" | "Fl" => match zlib_decompress(&data).or_else(|_| inflate(&data)) {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        tracing::warn!("object=<{}>, error=<{}> | failed to decode pdf stream", number, e);
+                        return None;
+                    }
+                },
+                "ASCIIHexDecode" | "AHx" => {
+                    let mut hex = b"<".to_vec();
+                    hex.extend_from_slice(&data);
+                    Lexer::new(&hex, 0).hex_string()
+                }
+                filter => {
+                    tracing::debug!("object=<{}>, filter=<{}> | skipping pdf stream with unsupported filter", number, filter);
+                    return None;
+                }
+            };
+        }
+        Some(data)
+    }
+
+    fn stream_of(&self, object: &Object) -> Option<Vec<u8>> {
+        match object {
+            Object::Reference(number) => self.decoded_stream(*number),
+            _ => None,
+        }
+    }
+
+    /// Collect the page objects in page tree order, with inherited resources and media box.
+    fn pages(&self) -> Vec<(Object, Object, Option<Object>)> {
+        let root = self
+            .objects
+            .values()
+            .find(|object| object.get("Type").and_then(Object::as_name) == Some("Catalog"))
+            .and_then(|catalog| catalog.get("Pages").cloned());
+        let mut pages = Vec::new();
+        match root {
+            Some(root) => {
+                let mut visited = HashSet::new();
+                self.walk_pages(&root, &Object::Null, None, &mut visited, &mut pages);
+            }
+            None => {
+                let mut numbers: Vec<&u32> = self
+                    .objects
+                    .iter()
+                    .filter(|(_, object)| object.get("Type").and_then(Object::as_name) == Some("Page"))
+                    .map(|(number, _)| number)
+                    .collect();
+                numbers.sort();
+                for number in numbers {
+                    let page = Object::Reference(*number);
+                    let resources = self.get(&page, "Resources").cloned().unwrap_or(Object::Null);
+                    let media_box = self.get(&page, "MediaBox").cloned();
+                    pages.push((page, resources, media_box));
+                }
+            }
+        }
+        pages
+    }
+
+    fn walk_pages(
+        &self,
+        node: &Object,
+        resources: &Object,
+        media_box: Option<&Object>,
+        visited: &mut HashSet<u32>,
+        pages: &mut Vec<(Object, Object, Option<Object>)>,
+    ) {
+        if let Object::Reference(number) = node {
+            if !visited.insert(*number) {
+                return;
+            }
+        }
+        let resources = self.get(node, "Resources").unwrap_or(resources);
+        let media_box = self.get(node, "MediaBox").or(media_box);
+        match self.get(node, "Kids").and_then(Object::as_array) {
+            Some(kids) => {
+                for kid in kids {
+                    self.walk_pages(kid, resources, media_box, visited, pages);
+                }
+            }
+            None => pages.push((node.clone(), resources.clone(), media_box.cloned())),
+        }
+    }
+
+    fn fonts(&self, resources: &Object) -> HashMap<String, Font> {
+        let Some(fonts) = self.get(resources, "Font").and_then(Object::as_dictionary) else {
+            return HashMap::new();
+        };
+        fonts
+            .iter()
+            .map(|(name, font)| {
+                let cmap = self
+                    .resolve(font)
+                    .get("ToUnicode")
+                    .and_then(|to_unicode| self.stream_of(to_unicode))
+                    .map(|data| CMap::parse(&data));
+                let two_byte = self.get(font, "Subtype").and_then(Object::as_name) == Some("Type0");
+                (name.clone(), Font { cmap, two_byte })
+            })
+            .collect()
+    }
+
+    /// Interpret a content stream, adding its text to `state` and its JPEG images to `images`.
+    fn interpret(&self, content: &[u8], resources: &Object, state: &mut TextState, images: &mut Vec<Vec<u8>>, depth: usize) {
+        let fonts = self.fonts(resources);
+        let x_objects = self.get(resources, "XObject").and_then(Object::as_dictionary);
+        let mut lexer = Lexer::new(content, 0);
+        let mut operands: Vec<Object> = Vec::new();
+        while let Some(object) = lexer.next() {
+            let Object::Operator(operator) = object else {
+                operands.push(object);
+                continue;
+            };
+            let number = |index: usize| operands.get(index).and_then(Object::as_number).unwrap_or(0.0);
+            match operator.as_str() {
+                "q" => state.stack.push(state.ctm),
+                "Q" => state.ctm = state.stack.pop().unwrap_or(IDENTITY),
+                "cm" => {
+                    if let Some(matrix) = matrix_from(&operands) {
+                        state.ctm = multiply(&matrix, &state.ctm);
+                    }
+                }
+                "BT" => {
+                    state.text_matrix = IDENTITY;
+                    state.line_matrix = IDENTITY;
+                }
+                "ET" => {}
+                "Tf" => {
+                    if let Some(name) = operands.first().and_then(Object::as_name) {
+                        state.font = fonts.get(name).cloned().unwrap_or_default();
+                    }
+                    state.font_size = number(1);
+                }
+                "TL" => state.leading = number(0),
+                "Td" => state.move_line(number(0), number(1)),
+                "TD" => {
+                    state.leading = -number(1);
+                    state.move_line(number(0), number(1));
+                }
+                "Tm" => {
+                    if let Some(matrix) = matrix_from(&operands) {
+                        state.text_matrix = matrix;
+                        state.line_matrix = matrix;
+                    }
+                }
+                "T*" => state.move_line(0.0, -state.leading),
+                "Tj" | "'" | "\"" => {
+                    if operator != "Tj" {
+                        state.move_line(0.0, -state.leading);
+                    }
+                    if let Some(Object::String(bytes)) = operands.last() {
+                        state.show(bytes);
+                    }
+                }
+                "TJ" => {
+                    for item in operands.first().and_then(Object::as_array).unwrap_or_default() {
+                        match item {
+                            Object::String(bytes) => state.show(bytes),
+                            Object::Number(adjustment) => {
+                                // Large negative adjustments are how many writers space words
+                                if *adjustment < -200.0 {
+                                    if let Some(ref mut line) = state.line {
+                                        if !line.text.ends_with(' ') {
+                                            line.text.push(' ');
+                                        }
+                                    }
+                                }
+                                state.advance(-adjustment / 1000.0 * state.font_size);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                "Do" => {
+                    let target = operands
+                        .first()
+                        .and_then(Object::as_name)
+                        .and_then(|name| x_objects?.get(name));
+                    if let Some(target) = target {
+                        self.draw_x_object(target, resources, state, images, depth);
+                    }
+                }
+                "ID" => lexer.skip_inline_image(),
+                _ => {}
+            }
+            operands.clear();
+        }
+    }
+
+    fn draw_x_object(
+        &self,
+        target: &Object,
+        resources: &Object,
+        state: &mut TextState,
+        images: &mut Vec<Vec<u8>>,
+        depth: usize,
+    ) {
+        let Object::Reference(number) = target else {
+            return;
+        };
+        match self.get(target, "Subtype").and_then(Object::as_name) {
+            Some("Image") => {
+                let jpeg = match self.get(target, "Filter") {
+                    Some(Object::Name(name)) => name == "DCTDecode",
+                    Some(Object::Array(names)) => names.len() == 1 && names[0].as_name() == Some("DCTDecode"),
+                    _ => false,
+                };
+                if jpeg {
+                    if let Some(data) = self.streams.get(number) {
+                        images.push(data.clone());
+                    }
+                }
+            }
+            Some("Form") if depth < MAX_FORM_DEPTH => {
+                let Some(content) = self.decoded_stream(*number) else {
+                    return;
+                };
+                let form_resources = self.get(target, "Resources").unwrap_or(resources);
+                let saved = state.ctm;
+                if let Some(matrix) = self.get(target, "Matrix").and_then(Object::as_array).and_then(matrix_from) {
+                    state.ctm = multiply(&matrix, &state.ctm);
+                }
+                self.interpret(&content, form_resources, state, images, depth + 1);
+                state.ctm = saved;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Extract the text of every page of a PDF.
+pub fn parse_pdf(data: &[u8]) -> IndubitablyResult<Vec<PdfPage>> {
+    let file = PdfFile::parse(data)?;
+    let mut pages = Vec::new();
+    for (index, (page, resources, media_box)) in file.pages().into_iter().enumerate() {
+        let mut content = Vec::new();
+        match file.resolve(&page).get("Contents") {
+            Some(Object::Array(parts)) => {
+                for part in parts {
+                    content.extend(file.stream_of(part).unwrap_or_default());
+                    content.push(b'\n');
+                }
+            }
+            Some(contents) => content = file.stream_of(contents).unwrap_or_default(),
+            None => {}
+        }
+
+        let mut state = TextState::new();
+        let mut images = Vec::new();
+        file.interpret(&content, &resources, &mut state, &mut images, 0);
+        state.finish_line();
+
+        let mut document_page = DocumentPage::new(index as u32 + 1);
+        if let Some([x0, y0, x1, y1]) = media_box
+            .as_ref()
+            .and_then(|media_box| file.resolve(media_box).as_array())
+            .and_then(|values| <[f32; 4]>::try_from(values.iter().filter_map(Object::as_number).collect::<Vec<_>>()).ok())
+        {
+            document_page.width = Some((x1 - x0).abs());
+            document_page.height = Some((y1 - y0).abs());
+        }
+        document_page.blocks = state.blocks;
+        pages.push(PdfPage {
+            page: document_page,
+            jpeg_images: images,
+        });
+    }
+    if pages.is_empty() {
+        return Err(malformed("no pages found"));
+    }
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a PDF whose objects are given in order, numbered from 1.
+    fn pdf(objects: &[String]) -> Vec<u8> {
+        let mut data = b"%PDF-1.7\n".to_vec();
+        for (index, object) in objects.iter().enumerate() {
+            data.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+        }
+        data.extend_from_slice(b"trailer\n<< /Root 1 0 R >>\n%%EOF\n");
+        data
+    }
+
+    fn stream(dictionary: &str, content: &str) -> String {
+        format!("<< {} /Length {} >>\nstream\n{}\nendstream", dictionary, content.len(), content)
+    }
+
+    #[test]
+    fn test_parse_pdf_pages_and_positions() {
+        let cmap = "/CIDInit /ProcSet findresource begin 1 begincodespacerange <0000> <FFFF> endcodespacerange \
+                    2 beginbfchar <0001> <0048> <0002> <0069> endbfchar 1 beginbfrange <0003> <0004> <0041> endbfrange end";
+        let data = pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [4 0 R 3 0 R] /Count 2 /MediaBox [0 0 612 792] /Resources << /Font << /F1 5 0 R /F2 6 0 R >> >> >>"
+                .to_string(),
+            "<< /Type /Page /Parent 2 0 R /Contents 7 0 R >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /Contents [8 0 R 9 0 R] >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            "<< /Type /Font /Subtype /Type0 /ToUnicode 10 0 R >>".to_string(),
+            stream("", "BT /F1 12 Tf 72 700 Td (Second page) Tj ET"),
+            stream("", "BT /F1 10 Tf 1 0 0 1 72 720 Tm [(Quarterly)-300(results)] TJ 0 -14 Td (\\(draft\\) \\223ok\\224) Tj ET"),
+            stream("", "q 2 0 0 2 0 0 cm BT /F2 10 Tf 36 300 Td <00010002> Tj <00030004> Tj ET Q"),
+            stream("", cmap),
+        ]);
+
+        let pages = parse_pdf(&data).unwrap();
+        assert_eq!(pages.len(), 2);
+        let first = &pages[0].page;
+        assert_eq!(first.width, Some(612.0));
+        let lines: Vec<&str> = first.blocks.iter().map(|block| block.text.as_str()).collect();
+        assert_eq!(lines, vec!["Quarterly results", "(draft) “ok”", "HiAB"]);
+        let bbox = first.blocks[0].bbox.unwrap();
+        assert_eq!((bbox.x, bbox.y, bbox.height), (72.0, 718.0, 10.0));
+        let scaled = first.blocks[2].bbox.unwrap();
+        assert_eq!((scaled.x, scaled.height), (72.0, 20.0));
+        assert_eq!(pages[1].page.text(), "Second page");
+
+        assert!(parse_pdf(b"not a pdf").is_err());
+        let mut encrypted = pdf(&["<< /Type /Catalog >>".to_string(), "<< /Filter /Standard >>".to_string()]);
+        encrypted.extend_from_slice(b"trailer << /Encrypt 2 0 R >>");
+        assert!(matches!(
+            parse_pdf(&encrypted),
+            Err(IndubitablyError::DocumentError(DocumentError::Encrypted(_)))
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+//! Retrieval of document chunks for grounded answers.
+//! 
+//! This module provides the `Retriever` trait, which finds the document
+//! chunks most similar to a query with their similarity scores, and
+//! `EmbeddingRetriever`, an in-memory index that embeds chunks with an
+//! `Embedder` and ranks them by cosine similarity. The `search_documents`
+//! tool exposes a retriever to an agent, citing every chunk it returns.
+
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::document::DocumentChunk;
+use crate::tools::registry::{Tool, ToolMetadata};
+use crate::tools::selector::{cosine_similarity, Embedder};
+use crate::types::{IndubitablyError, IndubitablyResult, ToolError};
+
+/// The name of the document search tool.
+pub const SEARCH_DOCUMENTS_TOOL_NAME: &str = "search_documents";
+
+/// The default number of chunks the document search tool returns.
+pub const DEFAULT_SEARCH_TOP_K: usize = 5;
+
+/// A chunk found for a query, with its similarity to the query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredChunk {
+    /// The chunk.
+    pub chunk: DocumentChunk,
+    /// The similarity to the query; higher is closer.
+    pub score: f32,
+}
+
+/// Finds the document chunks most relevant to a query.
+#[async_trait]
+pub trait Retriever: Send + Sync {
+    /// Get up to `top_k` chunks for a query, highest score first.
+    async fn retrieve(&self, query: &str, top_k: usize) -> IndubitablyResult<Vec<ScoredChunk>>;
+}
+
+/// An in-memory index ranking chunks by the cosine similarity of their embeddings.
+pub struct EmbeddingRetriever {
+    embedder: Arc<dyn Embedder>,
+    entries: RwLock<Vec<(DocumentChunk, Vec<f32>)>>,
+}
+
+impl EmbeddingRetriever {
+    /// Create an empty index using the embedder.
+    pub fn new(embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            embedder,
+            entries: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Embed chunks and add them to the index.
+    pub async fn add_chunks(&self, chunks: Vec<DocumentChunk>) -> IndubitablyResult<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        let texts: Vec<String> = chunks.iter().map(|chunk| chunk.text.clone()).collect();
+        let vectors = self.embedder.embed(&texts).await?;
+        if vectors.len() != chunks.len() {
+            return Err(IndubitablyError::InternalError(format!(
+                "Embedder returned {} vectors for {} chunks",
+                vectors.len(),
+                chunks.len()
+            )));
+        }
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.extend(chunks.into_iter().zip(vectors));
+        tracing::debug!("chunks=<{}> | indexed document chunks", entries.len());
+        Ok(())
+    }
+
+    /// Get the number of indexed chunks.
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl std::fmt::Debug for EmbeddingRetriever {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddingRetriever").field("chunks", &self.len()).finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Retriever for EmbeddingRetriever {
+    async fn retrieve(&self, query: &str, top_k: usize) -> IndubitablyResult<Vec<ScoredChunk>> {
+        let query_vector = self.embedder.embed(&[query.to_string()]).await?.into_iter().next().unwrap_or_default();
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        let mut scored: Vec<ScoredChunk> = entries
+            .iter()
+            .map(|(chunk, vector)| ScoredChunk {
+                chunk: chunk.clone(),
+                score: cosine_similarity(&query_vector, vector),
+            })
+            .collect();
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scored.truncate(top_k);
+        Ok(scored)
+    }
+}
+
+/// Create the `search_documents` tool, which retrieves chunks for a query.
+///
+/// The output carries a citation for every returned chunk, which the agent
+/// attaches to its answer.
+pub fn create_search_documents_tool(retriever: Arc<dyn Retriever>) -> Tool {
+    let function = move |input: Value| {
+        let retriever = Arc::clone(&retriever);
+        async move {
+            let query = input.get("query").and_then(Value::as_str).ok_or_else(|| {
+                IndubitablyError::ToolError(ToolError::InvalidInput("Expected 'query' string".to_string()))
+            })?;
+            let top_k = input.get("top_k").and_then(Value::as_u64).map_or(DEFAULT_SEARCH_TOP_K, |top_k| top_k as usize);
+            let chunks = retriever.retrieve(query, top_k).await?;
+            let results: Vec<Value> = chunks
+                .iter()
+                .map(|scored| {
+                    json!({
+                        "id": scored.chunk.id,
+                        "document": scored.chunk.document,
+                        "page": scored.chunk.page,
+                        "text": scored.chunk.text,
+                        "score": scored.score,
+                    })
+                })
+                .collect();
+            let citations: Vec<_> = chunks.iter().map(|scored| scored.chunk.citation()).collect();
+            Ok(json!({ "results": results, "citations": citations }))
+        }
+    };
+    Tool::new_async(SEARCH_DOCUMENTS_TOOL_NAME, "Search the indexed documents for passages about a query", function)
+        .with_metadata(ToolMetadata::new().with_input_schema(json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "What to search for"},
+                "top_k": {"type": "integer", "minimum": 1, "description": "The most passages to return"}
+            },
+            "required": ["query"]
+        })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Citation;
+
+    struct WordEmbedder;
+
+    #[async_trait]
+    impl Embedder for WordEmbedder {
+        async fn embed(&self, texts: &[String]) -> IndubitablyResult<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    ["refund", "shipping"].iter().map(|w| text.matches(w).count() as f32).collect()
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_documents_tool_cites_chunks() {
+        let retriever = Arc::new(EmbeddingRetriever::new(Arc::new(WordEmbedder)));
+        let chunk = |id: &str, text: &str| DocumentChunk {
+            id: id.to_string(),
+            document: "policy.pdf".to_string(),
+            index: 0,
+            text: text.to_string(),
+            page: 2,
+            bbox: None,
+            location: None,
+        };
+        retriever
+            .add_chunks(vec![chunk("refunds", "Refunds within 30 days."), chunk("shipping", "Shipping takes 5 days.")])
+            .await
+            .unwrap();
+        let tool = create_search_documents_tool(retriever);
+
+        let output = tool.execute_async(json!({ "query": "refund window", "top_k": 1 })).await.unwrap();
+
+        assert_eq!(output["results"][0]["id"], "refunds");
+        let citations = Citation::from_tool_output(&output);
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].source_id, "refunds");
+        assert_eq!(citations[0].uri.as_deref(), Some("policy.pdf#page=2"));
+    }
+}
@@ -0,0 +1,213 @@
+//! ZIP archive reading and writing.
+//! 
+//! Office documents are ZIP archives of XML parts. This reader covers what
+//! they need: the central directory, and stored or deflated entries. The
+//! writer stores entries uncompressed, which is enough for debug bundles.
+//! ZIP64, encryption and multi-disk archives are not supported.
+
+use chrono::{Datelike, Timelike, Utc};
+
+use super::inflate::{inflate_with_limit, MAX_INFLATED_BYTES};
+use crate::crypto::crc32::crc32;
+use crate::types::{DocumentError, IndubitablyError, IndubitablyResult};
+
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4b50;
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+
+fn malformed(message: &str) -> IndubitablyError {
+    IndubitablyError::DocumentError(DocumentError::Malformed(format!("Invalid ZIP archive: {}", message)))
+}
+
+fn u16_at(data: &[u8], offset: usize) -> IndubitablyResult<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| malformed("unexpected end of data"))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> IndubitablyResult<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| malformed("unexpected end of data"))
+}
+
+/// An entry in a ZIP archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    /// The path of the entry within the archive.
+    pub name: String,
+    /// The compression method: 0 for stored, 8 for deflated.
+    pub method: u16,
+    /// The compressed size in bytes.
+    pub compressed_size: u32,
+    /// The uncompressed size in bytes.
+    pub size: u32,
+    local_header_offset: u32,
+}
+
+/// A ZIP archive held in memory.
+#[derive(Debug)]
+pub struct ZipArchive<'a> {
+    data: &'a [u8],
+    entries: Vec<ZipEntry>,
+}
+
+impl<'a> ZipArchive<'a> {
+    /// Read the central directory of an archive.
+    pub fn new(data: &'a [u8]) -> IndubitablyResult<Self> {
+        // The end record sits in the last 22 bytes plus an optional comment of up to 64 KiB
+        let search_start = data.len().saturating_sub(22 + u16::MAX as usize);
+        let end = (search_start..data.len().saturating_sub(21))
+            .rev()
+            .find(|&offset| u32_at(data, offset).ok() == Some(END_OF_CENTRAL_DIRECTORY))
+            .ok_or_else(|| malformed("no end of central directory record"))?;
+        let count = u16_at(data, end + 10)? as usize;
+        let mut offset = u32_at(data, end + 16)? as usize;
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            if u32_at(data, offset)? != CENTRAL_DIRECTORY_HEADER {
+                return Err(malformed("bad central directory header"));
+            }
+            let name_length = u16_at(data, offset + 28)? as usize;
+            let extra_length = u16_at(data, offset + 30)? as usize;
+            let comment_length = u16_at(data, offset + 32)? as usize;
+            let name = data
+                .get(offset + 46..offset + 46 + name_length)
+                .ok_or_else(|| malformed("truncated entry name"))?;
+            entries.push(ZipEntry {
+                name: String::from_utf8_lossy(name).into_owned(),
+                method: u16_at(data, offset + 10)?,
+                compressed_size: u32_at(data, offset + 20)?,
+                size: u32_at(data, offset + 24)?,
+                local_header_offset: u32_at(data, offset + 42)?,
+            });
+            offset += 46 + name_length + extra_length + comment_length;
+        }
+        Ok(Self { data, entries })
+    }
+
+    /// Get the entries of the archive in directory order.
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    /// Check whether the archive has an entry with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|entry| entry.name == name)
+    }
+
+    /// Read and decompress an entry, or `None` when the archive has no such entry.
+    pub fn read(&self, name: &str) -> IndubitablyResult<Option<Vec<u8>>> {
+        let Some(entry) = self.entries.iter().find(|entry| entry.name == name) else {
+            return Ok(None);
+        };
+        let header = entry.local_header_offset as usize;
+        if u32_at(self.data, header)? != LOCAL_FILE_HEADER {
+            return Err(malformed("bad local file header"));
+        }
+        let start = header + 30 + u16_at(self.data, header + 26)? as usize + u16_at(self.data, header + 28)? as usize;
+        let compressed = self
+            .data
+            .get(start..start + entry.compressed_size as usize)
+            .ok_or_else(|| malformed("truncated entry data"))?;
+        match entry.method {
+            0 => Ok(Some(compressed.to_vec())),
+            // The recorded size bounds the output, so an entry cannot inflate beyond what it declares
+            8 => inflate_with_limit(compressed, (entry.size as usize).min(MAX_INFLATED_BYTES)).map(Some),
+            method => Err(IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(format!(
+                "ZIP compression method {} in entry '{}'",
+                method, entry.name
+            )))),
+        }
+    }
+
+    /// Read an entry as UTF-8 text.
+    pub fn read_string(&self, name: &str) -> IndubitablyResult<Option<String>> {
+        Ok(self.read(name)?.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+}
+
+/// A writer building a ZIP archive of stored entries in memory.
+#[derive(Debug, Clone)]
+pub struct ZipWriter {
+    data: Vec<u8>,
+    directory: Vec<u8>,
+    count: u16,
+    time: u16,
+    date: u16,
+}
+
+impl Default for ZipWriter {
+    fn default() -> Self {
+        // Entries are stamped with the time the writer was created, in MS-DOS format
+        let now = Utc::now();
+        Self {
+            data: Vec::new(),
+            directory: Vec::new(),
+            count: 0,
+            time: ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16,
+            date: (((now.year().clamp(1980, 2107) - 1980) as u32) << 9 | (now.month() << 5) | now.day()) as u16,
+        }
+    }
+}
+
+impl ZipWriter {
+    /// Create a writer for an empty archive.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry with the given path and contents.
+    pub fn add(&mut self, name: &str, contents: &[u8]) -> IndubitablyResult<()> {
+        let too_large = |what: &str| {
+            IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(format!(
+                "{} exceeds the limits of ZIP without ZIP64",
+                what
+            )))
+        };
+        let size = u32::try_from(contents.len()).map_err(|_| too_large("Entry size"))?;
+        let offset = u32::try_from(self.data.len()).map_err(|_| too_large("Archive size"))?;
+        let name_length = u16::try_from(name.len()).map_err(|_| too_large("Entry name"))?;
+        let count = self.count.checked_add(1).ok_or_else(|| too_large("Entry count"))?;
+
+        // Version 2.0, UTF-8 names, stored
+        let mut fields = Vec::with_capacity(26);
+        for value in [20u16, 0x0800, 0, self.time, self.date] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [crc32(contents), size, size] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        fields.extend_from_slice(&name_length.to_le_bytes());
+
+        self.data.extend_from_slice(&LOCAL_FILE_HEADER.to_le_bytes());
+        self.data.extend_from_slice(&fields);
+        self.data.extend_from_slice(&[0, 0]);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(contents);
+
+        self.directory.extend_from_slice(&CENTRAL_DIRECTORY_HEADER.to_le_bytes());
+        self.directory.extend_from_slice(&20u16.to_le_bytes());
+        self.directory.extend_from_slice(&fields);
+        self.directory.extend_from_slice(&[0; 12]);
+        self.directory.extend_from_slice(&offset.to_le_bytes());
+        self.directory.extend_from_slice(name.as_bytes());
+        self.count = count;
+        Ok(())
+    }
+
+    /// Finish the archive and return its bytes.
+    pub fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        self.data.extend_from_slice(&self.directory);
+        self.data.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        self.data.extend_from_slice(&[0, 0, 0, 0]);
+        self.data.extend_from_slice(&self.count.to_le_bytes());
+        self.data.extend_from_slice(&self.count.to_le_bytes());
+        self.data.extend_from_slice(&(self.directory.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(&directory_offset.to_le_bytes());
+        self.data.extend_from_slice(&[0, 0]);
+        self.data
+    }
+}
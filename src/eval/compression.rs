@@ -0,0 +1,187 @@
+//! Compression evaluation for the SDK.
+//! 
+//! This module measures what context compression costs in answer quality.
+//! Each case is answered twice, once with its full context and once with
+//! the context compressed, and both answers are scored against the
+//! expected answer by word-overlap F1. The report sets the tokens saved
+//! against the change in quality, so a compression ratio can be tuned on
+//! a representative set of cases before it is enabled.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::compression::ContextCompressor;
+use crate::models::Model;
+use crate::types::{IndubitablyResult, Message, Messages};
+
+/// A question to answer from a context, with its expected answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionCase {
+    /// The case identifier.
+    pub id: String,
+    /// The documents and earlier turns the question is answered from.
+    pub context: Messages,
+    /// The question asked after the context.
+    pub question: String,
+    /// The expected answer.
+    pub expected: String,
+}
+
+impl CompressionCase {
+    /// Create a case.
+    pub fn new(id: &str, context: Messages, question: &str, expected: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            context,
+            question: question.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+}
+
+/// The outcome of one case.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionCaseResult {
+    /// The case identifier.
+    pub id: String,
+    /// The compressible tokens before compression.
+    pub original_tokens: usize,
+    /// The compressible tokens after compression.
+    pub compressed_tokens: usize,
+    /// The F1 score of the answer given the full context.
+    pub full_quality: f64,
+    /// The F1 score of the answer given the compressed context.
+    pub compressed_quality: f64,
+}
+
+/// Tokens saved versus answer quality across a set of cases.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompressionEvalReport {
+    /// The outcome of each case.
+    pub cases: Vec<CompressionCaseResult>,
+}
+
+impl CompressionEvalReport {
+    /// Get the total tokens removed by compression.
+    pub fn tokens_saved(&self) -> usize {
+        self.cases.iter().map(|case| case.original_tokens.saturating_sub(case.compressed_tokens)).sum()
+    }
+
+    /// Get the share of compressible tokens kept, or 1 when nothing was compressible.
+    pub fn compression_ratio(&self) -> f64 {
+        let original: usize = self.cases.iter().map(|case| case.original_tokens).sum();
+        if original == 0 {
+            return 1.0;
+        }
+        self.cases.iter().map(|case| case.compressed_tokens).sum::<usize>() as f64 / original as f64
+    }
+
+    /// Get the mean quality of answers given the full context.
+    pub fn mean_full_quality(&self) -> f64 {
+        self.mean(|case| case.full_quality)
+    }
+
+    /// Get the mean quality of answers given the compressed context.
+    pub fn mean_compressed_quality(&self) -> f64 {
+        self.mean(|case| case.compressed_quality)
+    }
+
+    /// Get the mean change in quality from compressing, negative when answers got worse.
+    pub fn quality_delta(&self) -> f64 {
+        self.mean_compressed_quality() - self.mean_full_quality()
+    }
+
+    fn mean(&self, value: impl Fn(&CompressionCaseResult) -> f64) -> f64 {
+        if self.cases.is_empty() {
+            return 0.0;
+        }
+        self.cases.iter().map(value).sum::<f64>() / self.cases.len() as f64
+    }
+}
+
+/// Score an answer against the expected one by word-overlap F1.
+pub fn answer_f1(answer: &str, expected: &str) -> f64 {
+    let words = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let answer = words(answer);
+    let expected = words(expected);
+    if answer.is_empty() || expected.is_empty() {
+        return if answer.is_empty() && expected.is_empty() { 1.0 } else { 0.0 };
+    }
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for word in &expected {
+        *remaining.entry(word.as_str()).or_insert(0) += 1;
+    }
+    let mut overlap = 0;
+    for word in &answer {
+        if let Some(count) = remaining.get_mut(word.as_str()).filter(|count| **count > 0) {
+            *count -= 1;
+            overlap += 1;
+        }
+    }
+    if overlap == 0 {
+        return 0.0;
+    }
+    let precision = overlap as f64 / answer.len() as f64;
+    let recall = overlap as f64 / expected.len() as f64;
+    2.0 * precision * recall / (precision + recall)
+}
+
+/// Answer every case with and without compression and compare the answers.
+pub async fn evaluate_compression(
+    model: &dyn Model,
+    compressor: &ContextCompressor,
+    cases: &[CompressionCase],
+) -> IndubitablyResult<CompressionEvalReport> {
+    let mut report = CompressionEvalReport::default();
+    for case in cases {
+        let mut full = case.context.clone();
+        full.push(Message::user(&case.question));
+        let (compressed, stats) = compressor.compress(&full);
+
+        let full_answer = model.generate(&full, None, None).await?;
+        let compressed_answer = model.generate(&compressed, None, None).await?;
+        report.cases.push(CompressionCaseResult {
+            id: case.id.clone(),
+            original_tokens: stats.original_tokens,
+            compressed_tokens: stats.compressed_tokens,
+            full_quality: answer_f1(&full_answer.content, &case.expected),
+            compressed_quality: answer_f1(&compressed_answer.content, &case.expected),
+        });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::compression::CompressionConfig;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    #[tokio::test]
+    async fn test_evaluate_compression_reports_quality_delta() {
+        let document = "Filler sentence here. Filler sentence here. The vault code is 4812. Filler sentence here.";
+        let cases = vec![CompressionCase::new("vault", vec![Message::user(document)], "What is the vault code?", "4812")];
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("The code is 4812"),
+            ModelResponse::new("I do not know"),
+        ]);
+        let compressor = ContextCompressor::new(
+            CompressionConfig::new().with_ratio(0.4).with_keep_recent_messages(1).with_min_tokens(1),
+        );
+
+        let report = evaluate_compression(&model, &compressor, &cases).await.unwrap();
+
+        assert_eq!(report.cases.len(), 1);
+        assert!(report.tokens_saved() > 0);
+        assert!((report.mean_full_quality() - 0.4).abs() < 1e-9);
+        assert_eq!(report.mean_compressed_quality(), 0.0);
+        assert!(report.quality_delta() < 0.0);
+        assert_eq!(answer_f1("4812", "4812"), 1.0);
+    }
+}
@@ -0,0 +1,273 @@
+//! Fine-tuning dataset export for the SDK.
+//! 
+//! This module converts recorded sessions into fine-tuning datasets in the
+//! OpenAI chat JSONL or ShareGPT formats. A `DatasetFilter` selects which
+//! sessions are exported, by default only the ones rated thumbs-up, and
+//! strips tool chatter so the examples contain only the conversation
+//! itself. Corrections recorded as feedback replace the responses they
+//! correct.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::types::{FeedbackRating, IndubitablyResult, Session, SessionMessage};
+
+/// The format of an exported fine-tuning dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetFormat {
+    /// One `{"messages": [{"role", "content"}]}` object per line.
+    OpenAiJsonl,
+    /// One `{"id", "conversations": [{"from", "value"}]}` object per line.
+    ShareGpt,
+}
+
+impl DatasetFormat {
+    /// Get the format name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatasetFormat::OpenAiJsonl => "openai_jsonl",
+            DatasetFormat::ShareGpt => "sharegpt",
+        }
+    }
+}
+
+/// Which sessions and messages are exported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetFilter {
+    /// Export only sessions rated thumbs-up.
+    pub only_positive: bool,
+    /// Drop tool results and assistant turns that only call tools.
+    pub strip_tool_chatter: bool,
+    /// Replace responses with the corrections given in feedback.
+    pub apply_corrections: bool,
+    /// Prepend the session agent's system prompt, if recorded.
+    pub include_system_prompt: bool,
+    /// The minimum number of assistant turns a session needs to be exported.
+    pub min_assistant_turns: usize,
+}
+
+impl Default for DatasetFilter {
+    fn default() -> Self {
+        Self {
+            only_positive: true,
+            strip_tool_chatter: true,
+            apply_corrections: true,
+            include_system_prompt: true,
+            min_assistant_turns: 1,
+        }
+    }
+}
+
+impl DatasetFilter {
+    /// Create the default filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether only thumbs-up sessions are exported.
+    pub fn with_only_positive(mut self, only_positive: bool) -> Self {
+        self.only_positive = only_positive;
+        self
+    }
+
+    /// Set whether tool chatter is stripped.
+    pub fn with_strip_tool_chatter(mut self, strip_tool_chatter: bool) -> Self {
+        self.strip_tool_chatter = strip_tool_chatter;
+        self
+    }
+
+    /// Set whether corrections replace the responses they correct.
+    pub fn with_apply_corrections(mut self, apply_corrections: bool) -> Self {
+        self.apply_corrections = apply_corrections;
+        self
+    }
+
+    /// Set whether the system prompt is included.
+    pub fn with_include_system_prompt(mut self, include_system_prompt: bool) -> Self {
+        self.include_system_prompt = include_system_prompt;
+        self
+    }
+
+    /// Set the minimum number of assistant turns.
+    pub fn with_min_assistant_turns(mut self, min_assistant_turns: usize) -> Self {
+        self.min_assistant_turns = min_assistant_turns;
+        self
+    }
+
+    /// Get the messages of a session that belong in an example.
+    fn messages(&self, session: &Session) -> Vec<SessionMessage> {
+        let feedback = if self.apply_corrections { session.feedback() } else { Vec::new() };
+        session
+            .messages
+            .iter()
+            .filter(|message| !self.strip_tool_chatter || !is_tool_chatter(message))
+            .map(|message| {
+                let mut message = message.clone();
+                // The latest correction of a message wins.
+                if let Some(correction) = feedback
+                    .iter()
+                    .rev()
+                    .find(|record| record.message_id == message.id)
+                    .and_then(|record| record.correction.clone())
+                {
+                    message.content = correction;
+                }
+                message
+            })
+            .collect()
+    }
+}
+
+/// An exported fine-tuning dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinetuneDataset {
+    /// The dataset format.
+    pub format: DatasetFormat,
+    /// One record per exported session.
+    pub records: Vec<Value>,
+    /// The IDs of the sessions the filter left out.
+    pub skipped: Vec<String>,
+}
+
+impl FinetuneDataset {
+    /// Get the number of exported examples.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check whether no sessions were exported.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Serialize the dataset with one record per line.
+    pub fn to_jsonl(&self) -> IndubitablyResult<String> {
+        let mut output = String::new();
+        for record in &self.records {
+            output.push_str(&serde_json::to_string(record)?);
+            output.push('\n');
+        }
+        Ok(output)
+    }
+}
+
+/// Export sessions as a fine-tuning dataset using the default filter.
+pub fn export_finetune(sessions: &[Session], format: DatasetFormat) -> FinetuneDataset {
+    export_finetune_filtered(sessions, format, &DatasetFilter::default())
+}
+
+/// Export sessions as a fine-tuning dataset, keeping only those the filter selects.
+pub fn export_finetune_filtered(sessions: &[Session], format: DatasetFormat, filter: &DatasetFilter) -> FinetuneDataset {
+    let mut dataset = FinetuneDataset {
+        format,
+        records: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for session in sessions {
+        let messages = filter.messages(session);
+        let assistant_turns = messages.iter().filter(|message| message.role == "assistant").count();
+        if (filter.only_positive && session.rating() != Some(FeedbackRating::ThumbsUp)) || assistant_turns < filter.min_assistant_turns {
+            dataset.skipped.push(session.id.clone());
+            continue;
+        }
+
+        let system_prompt = session
+            .agent
+            .system_prompt
+            .as_deref()
+            .filter(|prompt| filter.include_system_prompt && !prompt.trim().is_empty());
+        dataset.records.push(match format {
+            DatasetFormat::OpenAiJsonl => openai_record(system_prompt, &messages),
+            DatasetFormat::ShareGpt => sharegpt_record(&session.id, system_prompt, &messages),
+        });
+    }
+    tracing::debug!(
+        "format=<{}>, exported=<{}>, skipped=<{}> | exported fine-tuning dataset",
+        format.as_str(),
+        dataset.records.len(),
+        dataset.skipped.len()
+    );
+    dataset
+}
+
+/// Check whether a message is a tool result or an assistant turn with no text.
+fn is_tool_chatter(message: &SessionMessage) -> bool {
+    message.role == "tool" || (message.role == "assistant" && message.content.trim().is_empty())
+}
+
+fn openai_record(system_prompt: Option<&str>, messages: &[SessionMessage]) -> Value {
+    let mut turns: Vec<Value> = system_prompt
+        .map(|prompt| vec![json!({"role": "system", "content": prompt})])
+        .unwrap_or_default();
+    for message in messages {
+        let mut turn = json!({"role": message.role, "content": message.content});
+        if let Some(tool_use_id) = message.metadata.as_ref().and_then(|metadata| metadata.get("tool_use_id")) {
+            turn["tool_call_id"] = tool_use_id.clone();
+        }
+        turns.push(turn);
+    }
+    json!({"messages": turns})
+}
+
+fn sharegpt_record(session_id: &str, system_prompt: Option<&str>, messages: &[SessionMessage]) -> Value {
+    let mut turns: Vec<Value> = system_prompt
+        .map(|prompt| vec![json!({"from": "system", "value": prompt})])
+        .unwrap_or_default();
+    for message in messages {
+        let from = match message.role.as_str() {
+            "user" => "human",
+            "assistant" => "gpt",
+            other => other,
+        };
+        turns.push(json!({"from": from, "value": message.content}));
+    }
+    json!({"id": session_id, "conversations": turns})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Feedback, SessionAgent, SessionType};
+
+    fn session(id: &str, rating: Option<FeedbackRating>) -> Session {
+        let agent = SessionAgent::new("a", "Agent").with_system_prompt("Be helpful.");
+        let mut session = Session::new(id, SessionType::Conversation, agent);
+        session.add_message(SessionMessage::new("m1", "user", "Weather in Paris?"));
+        session.add_message(SessionMessage::new("m2", "assistant", ""));
+        session.add_message(SessionMessage::new("m3", "tool", "{\"forecast\": \"sunny\"}"));
+        session.add_message(SessionMessage::new("m4", "assistant", "It is sunny."));
+        if let Some(rating) = rating {
+            session.add_feedback(Feedback::new(id, "m4", rating));
+        }
+        session
+    }
+
+    #[test]
+    fn test_export_finetune() {
+        let mut good = session("good", Some(FeedbackRating::ThumbsUp));
+        good.add_feedback(Feedback::new("good", "m4", FeedbackRating::ThumbsUp).with_correction("It is sunny, 24°C."));
+        let sessions = vec![good, session("bad", Some(FeedbackRating::ThumbsDown)), session("unrated", None)];
+
+        let openai = export_finetune(&sessions, DatasetFormat::OpenAiJsonl);
+        assert_eq!(openai.len(), 1);
+        assert_eq!(openai.skipped, vec!["bad", "unrated"]);
+        assert_eq!(
+            openai.records[0],
+            json!({"messages": [
+                {"role": "system", "content": "Be helpful."},
+                {"role": "user", "content": "Weather in Paris?"},
+                {"role": "assistant", "content": "It is sunny, 24°C."}
+            ]})
+        );
+        assert_eq!(openai.to_jsonl().unwrap().lines().count(), 1);
+
+        let filter = DatasetFilter::new().with_only_positive(false).with_strip_tool_chatter(false);
+        let sharegpt = export_finetune_filtered(&sessions, DatasetFormat::ShareGpt, &filter);
+        assert_eq!(sharegpt.len(), 3);
+        let conversations = sharegpt.records[0]["conversations"].as_array().unwrap();
+        assert_eq!(conversations.len(), 5);
+        assert_eq!(conversations[1], json!({"from": "human", "value": "Weather in Paris?"}));
+        assert_eq!(conversations[3]["from"], "tool");
+    }
+}
@@ -0,0 +1,132 @@
+//! Prompt experiment evaluation for the SDK.
+//! 
+//! This module runs a set of cases through every variant of an experiment
+//! before any of them reaches users. Each answer is checked by a task
+//! success assertion, that it contains the expected answer, and scored
+//! against it by word-overlap F1. Both are recorded under the variant in an
+//! `ExperimentOutcomes`, so `experiments::report` can tell a prompt change
+//! that helps from noise.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::compression::answer_f1;
+use crate::agent::experiments::{Experiment, ExperimentOutcomes, Variant};
+use crate::models::Model;
+use crate::types::{IndubitablyResult, Message};
+
+/// An input with the answer a good response contains.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptCase {
+    /// The case identifier.
+    pub id: String,
+    /// The user message sent to the model.
+    pub input: String,
+    /// The expected answer.
+    pub expected: String,
+}
+
+impl PromptCase {
+    /// Create a case.
+    pub fn new(id: &str, input: &str, expected: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            input: input.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    /// Check whether an answer contains the expected answer, ignoring case.
+    pub fn passes(&self, answer: &str) -> bool {
+        answer.to_lowercase().contains(&self.expected.to_lowercase())
+    }
+}
+
+/// Answer every case with every variant of an experiment and collect the outcomes.
+///
+/// Variants without a system prompt use the given one. The model's config is
+/// restored once the experiment is evaluated, including when a call fails.
+pub async fn evaluate_experiment(
+    model: &mut dyn Model,
+    system_prompt: &str,
+    experiment: &Experiment,
+    cases: &[PromptCase],
+) -> IndubitablyResult<ExperimentOutcomes> {
+    let baseline = model.config().clone();
+    let outcomes = ExperimentOutcomes::new();
+    let mut result = Ok(());
+    for variant in &experiment.variants {
+        *model.config_mut() = baseline.clone();
+        variant.apply_to_model_config(model.config_mut());
+        result = evaluate_variant(&*model, system_prompt, &experiment.name, variant, cases, &outcomes).await;
+        if result.is_err() {
+            break;
+        }
+    }
+    *model.config_mut() = baseline;
+    result.map(|_| outcomes)
+}
+
+async fn evaluate_variant(
+    model: &dyn Model,
+    system_prompt: &str,
+    experiment: &str,
+    variant: &Variant,
+    cases: &[PromptCase],
+    outcomes: &ExperimentOutcomes,
+) -> IndubitablyResult<()> {
+    let labels = BTreeMap::from([(experiment.to_string(), variant.name.clone())]);
+    let system_prompt = variant.system_prompt.as_deref().unwrap_or(system_prompt);
+    for case in cases {
+        let answer = model.generate(&vec![Message::user(&case.input)], None, Some(system_prompt)).await?;
+        tracing::debug!(
+            "experiment=<{}>, variant=<{}>, case=<{}> | evaluated prompt case",
+            experiment,
+            variant.name,
+            case.id
+        );
+        outcomes.record_assertion(&labels, case.passes(&answer.content));
+        outcomes.record_score(&labels, answer_f1(&answer.content, &case.expected));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::experiments::{report, Experiments, SignificanceTest, OUTCOME_SUCCESS_RATE};
+    use crate::models::model::{MockModel, ModelResponse};
+
+    #[tokio::test]
+    async fn test_evaluate_experiment_reports_significance() {
+        let experiment = Experiment::new("prompt_v2")
+            .with_variant(Variant::new("control"))
+            .with_variant(Variant::new("grounded").with_system_prompt("Answer from the facts.").with_temperature(0.0));
+        let cases: Vec<PromptCase> = (0..40).map(|i| PromptCase::new(&i.to_string(), "Capital?", "Paris")).collect();
+        // The control answers a quarter of the cases correctly, the variant all but two.
+        let responses = (0..40)
+            .map(|i| if i % 4 == 0 { "Paris" } else { "Lyon" })
+            .chain((0..40).map(|i| if i < 2 { "Lyon" } else { "It is Paris" }))
+            .map(ModelResponse::new)
+            .collect();
+        let mut model = MockModel::new().with_responses(responses);
+        let baseline = model.config().clone();
+
+        let outcomes = evaluate_experiment(&mut model, "Be helpful.", &experiment, &cases).await.unwrap();
+        assert_eq!(model.config(), &baseline);
+        let control = outcomes.variant("prompt_v2", "control").unwrap();
+        assert_eq!((control.assertions_passed, control.assertions_failed), (10, 30));
+
+        let reports = report(&Experiments::new().with_experiment(experiment), &outcomes);
+        assert_eq!(reports[0].control, "control");
+        let success = &reports[0].comparisons[0];
+        assert_eq!(success.metric, OUTCOME_SUCCESS_RATE);
+        assert_eq!((success.control_value, success.variant_value), (0.25, 0.95));
+        assert!(success.p_value < 0.001, "p-value was {}", success.p_value);
+        let score = &reports[0].comparisons[1];
+        assert_eq!(score.test, SignificanceTest::Bootstrap);
+        assert!(score.difference() > 0.0 && score.p_value < 0.05);
+        assert_eq!(reports[0].significant().len(), 2);
+    }
+}
@@ -0,0 +1,15 @@
+//! Evaluation and dataset tooling for the SDK.
+//! 
+//! This module provides helpers for turning recorded sessions into
+//! material for evaluating and improving agents, such as fine-tuning
+//! datasets built from production conversations, measurements of how
+//! context compression trades tokens for answer quality, and offline runs
+//! of prompt experiments.
+
+pub mod dataset;
+pub mod compression;
+pub mod experiments;
+
+pub use compression::{answer_f1, evaluate_compression, CompressionCase, CompressionEvalReport};
+pub use dataset::{export_finetune, export_finetune_filtered, DatasetFilter, DatasetFormat, FinetuneDataset};
+pub use experiments::{evaluate_experiment, PromptCase};
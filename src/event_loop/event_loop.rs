@@ -1,8 +1,15 @@
 //! Event loop implementation for the SDK.
 //! 
 //! This module provides the core event loop that manages
-//! agent execution cycles and tool interactions.
+//! agent execution cycles and tool interactions. Cycle durations, model
+//! and tool latencies and token usage are reported to an optional
+//! `MetricsSink` under the loop's labels.
 
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::telemetry::sink::{MetricEvent, MetricsSink};
 use crate::types::{Messages, IndubitablyResult};
 
 /// The main event loop for agent execution.
@@ -11,15 +18,18 @@ pub struct EventLoop {
     max_iterations: usize,
     /// The current iteration count.
     iteration_count: usize,
+    /// The sink receiving the loop's metrics.
+    metrics_sink: Option<Arc<dyn MetricsSink>>,
+    /// The labels attached to every metric.
+    labels: BTreeMap<String, String>,
+    /// When the current cycle started.
+    cycle_started: Option<Instant>,
 }
 
 impl EventLoop {
     /// Create a new event loop.
     pub fn new() -> Self {
-        Self {
-            max_iterations: 10,
-            iteration_count: 0,
-        }
+        Self::with_max_iterations(10)
     }
     
     /// Create a new event loop with the given configuration.
@@ -27,12 +37,36 @@ impl EventLoop {
         Self {
             max_iterations,
             iteration_count: 0,
+            metrics_sink: None,
+            labels: BTreeMap::new(),
+            cycle_started: None,
+        }
+    }
+    
+    /// Report the loop's metrics to the given sink.
+    pub fn with_metrics_sink(mut self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.metrics_sink = Some(sink);
+        self
+    }
+    
+    /// Attach a label to every metric the loop reports.
+    pub fn with_label(mut self, key: &str, value: &str) -> Self {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+    
+    /// Report a metric event to the sink, if one is set.
+    pub fn record(&self, event: MetricEvent) {
+        if let Some(ref sink) = self.metrics_sink {
+            sink.record(&event, &self.labels);
         }
     }
     
     /// Run a single event loop cycle.
     pub async fn cycle(&mut self, _messages: &Messages) -> IndubitablyResult<()> {
+        self.finish_cycle();
         self.iteration_count += 1;
+        self.cycle_started = Some(Instant::now());
         
         if self.iteration_count > self.max_iterations {
             return Err(crate::types::IndubitablyError::EventLoopError(
@@ -46,9 +80,20 @@ impl EventLoop {
         Ok(())
     }
     
+    /// Report the duration of the current cycle, if one is running.
+    pub fn finish_cycle(&mut self) {
+        if let Some(started) = self.cycle_started.take() {
+            self.record(MetricEvent::CycleDuration {
+                iteration: self.iteration_count,
+                duration: started.elapsed(),
+            });
+        }
+    }
+    
     /// Reset the iteration count.
     pub fn reset(&mut self) {
         self.iteration_count = 0;
+        self.cycle_started = None;
     }
     
     /// Get the current iteration count.
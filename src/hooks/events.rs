@@ -23,3 +23,33 @@ impl HookEvent {
         }
     }
 }
+
+/// The event emitted when a conversation approaches its budget.
+pub const BUDGET_WARNING_EVENT: &str = "budget_warning";
+
+/// The event emitted when a signed artifact fails verification.
+pub const SIGNATURE_VERIFICATION_FAILED_EVENT: &str = "signature_verification_failed";
+
+/// The event emitted when a secret is masked in model output or tool input.
+pub const SECRET_LEAK_BLOCKED_EVENT: &str = "secret_leak_blocked";
+
+/// The event emitted when a user rates an agent response.
+pub const FEEDBACK_RECEIVED_EVENT: &str = "feedback_received";
+
+/// The event emitted when a guardrail blocks user input or model output.
+pub const GUARDRAIL_BLOCKED_EVENT: &str = "guardrail_blocked";
+
+/// The event emitted when a guardrail decision is appealed or its review is decided.
+pub const GUARDRAIL_APPEALED_EVENT: &str = "guardrail_appealed";
+
+/// The event emitted when a fallback model fails over to its next model.
+pub const MODEL_FAILOVER_EVENT: &str = "model_failover";
+
+/// The event emitted periodically while a long run is still in progress.
+pub const RUN_HEARTBEAT_EVENT: &str = "run_heartbeat";
+
+/// The event emitted when the liveness watchdog aborts a run past its hard ceiling.
+pub const RUN_ABORTED_EVENT: &str = "run_aborted";
+
+/// The event emitted when a new query touches content that aged out of the conversation.
+pub const EXPIRED_CONTENT_RELEVANT_EVENT: &str = "expired_content_relevant";
@@ -1,10 +1,16 @@
 //! Hooks system for the SDK.
 //! 
 //! This module provides a hooks system for extending
-//! agent functionality with custom behaviors.
+//! agent functionality with custom behaviors, and typed subscriptions to
+//! agent events on top of it.
 
 pub mod events;
 pub mod registry;
+pub mod typed;
 
 pub use events::*;
-pub use registry::HookRegistry;
+pub use registry::{HookAbort, HookFailurePolicy, HookHealth, HookOptions, HookRegistry};
+pub use typed::{
+    BudgetWarning, ExpiredContentRelevant, ModelCallCompleted, ModelCallFailed, RunAborted, RunCompleted, RunHeartbeat,
+    RunStarted, Subscription, ToolExecuted, ToolStarted, TypedEvent,
+};
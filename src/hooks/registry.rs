@@ -1,10 +1,18 @@
 //! Hook registry for the SDK.
 //! 
 //! This module provides a registry for managing hooks
-//! and their event handlers.
+//! and their event handlers. Each hook runs isolated: a panic is caught,
+//! a hook running past its timeout is abandoned, and a hook failing
+//! repeatedly is disabled until it is reset. What a failure means for the
+//! run is set per hook with a `HookFailurePolicy`, and `health` reports
+//! each hook's record.
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
 use super::events::HookEvent;
@@ -12,10 +20,219 @@ use super::events::HookEvent;
 /// A hook function.
 pub type HookFunction = Box<dyn Fn(HookEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync>;
 
+/// A hook function shared with the blocking task running it.
+type SharedHookFunction = Arc<dyn Fn(HookEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync>;
+
+/// The default time a hook may run before it is abandoned.
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The default number of consecutive failures after which a hook is disabled.
+pub const DEFAULT_MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
+/// What a hook failure means for the event that triggered it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookFailurePolicy {
+    /// Record the failure and continue silently.
+    Ignore,
+    /// Log the failure as a warning and continue.
+    #[default]
+    Warn,
+    /// Stop running hooks for the event and abort the agent run.
+    Abort,
+}
+
+/// How a hook is run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookOptions {
+    /// The name used in logs and health reports; derived from the event type when unset.
+    pub name: Option<String>,
+    /// The time the hook may run before it is abandoned, if bounded.
+    pub timeout: Option<Duration>,
+    /// What a failure means for the event.
+    pub failure_policy: HookFailurePolicy,
+    /// The number of consecutive failures after which the hook is disabled, if any.
+    pub max_consecutive_failures: Option<u32>,
+}
+
+impl Default for HookOptions {
+    fn default() -> Self {
+        Self {
+            name: None,
+            timeout: Some(DEFAULT_HOOK_TIMEOUT),
+            failure_policy: HookFailurePolicy::default(),
+            max_consecutive_failures: Some(DEFAULT_MAX_CONSECUTIVE_FAILURES),
+        }
+    }
+}
+
+impl HookOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name used in logs and health reports.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Set the time the hook may run, or `None` to run it inline without a bound.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set what a failure means for the event.
+    pub fn with_failure_policy(mut self, policy: HookFailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Set the number of consecutive failures after which the hook is disabled, or `None` to never disable it.
+    pub fn with_max_consecutive_failures(mut self, failures: Option<u32>) -> Self {
+        self.max_consecutive_failures = failures;
+        self
+    }
+}
+
+/// The record of a hook's calls and failures.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookHealth {
+    /// The hook name.
+    pub name: String,
+    /// The event type the hook is registered for.
+    pub event_type: String,
+    /// The number of times the hook was run.
+    pub calls: u64,
+    /// The number of runs that failed, including panics and timeouts.
+    pub failures: u64,
+    /// The number of runs that panicked.
+    pub panics: u64,
+    /// The number of runs abandoned at the timeout.
+    pub timeouts: u64,
+    /// The number of failures since the last success.
+    pub consecutive_failures: u32,
+    /// Whether the hook was disabled for failing repeatedly.
+    pub disabled: bool,
+    /// The most recent failure, if any.
+    pub last_error: Option<String>,
+}
+
+/// A hook failure that aborted its event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookAbort {
+    /// The name of the failed hook.
+    pub hook: String,
+    /// The event type the hook ran for.
+    pub event_type: String,
+    /// The failure.
+    pub error: String,
+}
+
+impl std::fmt::Display for HookAbort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hook '{}' failed on '{}': {}", self.hook, self.event_type, self.error)
+    }
+}
+
+impl std::error::Error for HookAbort {}
+
+/// Why a hook run failed.
+enum HookFailure {
+    Error(String),
+    Panic(String),
+    Timeout(Duration),
+}
+
+impl std::fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error(error) => write!(f, "{}", error),
+            Self::Panic(message) => write!(f, "panicked: {}", message),
+            Self::Timeout(timeout) => write!(f, "timed out after {:?}", timeout),
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// A hook with its options and health record.
+struct RegisteredHook {
+    function: SharedHookFunction,
+    options: HookOptions,
+    health: Mutex<HookHealth>,
+}
+
+impl RegisteredHook {
+    fn is_disabled(&self) -> bool {
+        self.health.lock().unwrap_or_else(|e| e.into_inner()).disabled
+    }
+
+    /// Run the hook, catching panics and abandoning it at the timeout.
+    async fn run(&self, event: HookEvent) -> Result<(), HookFailure> {
+        let Some(timeout) = self.options.timeout else {
+            return match catch_unwind(AssertUnwindSafe(|| (self.function)(event))) {
+                Ok(result) => result.map_err(|e| HookFailure::Error(e.to_string())),
+                Err(payload) => Err(HookFailure::Panic(panic_message(payload))),
+            };
+        };
+        // A blocking task cannot be stopped, so a hook past its timeout finishes in the background
+        let function = Arc::clone(&self.function);
+        let task = tokio::task::spawn_blocking(move || function(event).map_err(|e| e.to_string()));
+        match tokio::time::timeout(timeout, task).await {
+            Ok(Ok(result)) => result.map_err(HookFailure::Error),
+            Ok(Err(join_error)) if join_error.is_panic() => Err(HookFailure::Panic(panic_message(join_error.into_panic()))),
+            Ok(Err(join_error)) => Err(HookFailure::Error(join_error.to_string())),
+            Err(_) => Err(HookFailure::Timeout(timeout)),
+        }
+    }
+
+    /// Record the outcome of a run and return the updated record.
+    fn record(&self, outcome: &Result<(), HookFailure>) -> HookHealth {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        health.calls += 1;
+        let Err(failure) = outcome else {
+            health.consecutive_failures = 0;
+            return health.clone();
+        };
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        match failure {
+            HookFailure::Panic(_) => health.panics += 1,
+            HookFailure::Timeout(_) => health.timeouts += 1,
+            HookFailure::Error(_) => {}
+        }
+        health.last_error = Some(failure.to_string());
+        if let Some(max) = self.options.max_consecutive_failures {
+            if health.consecutive_failures >= max && !health.disabled {
+                health.disabled = true;
+                tracing::warn!(
+                    "hook=<{}>, event_type=<{}>, consecutive_failures=<{}> | disabled hook after repeated failures",
+                    health.name,
+                    health.event_type,
+                    health.consecutive_failures
+                );
+            }
+        }
+        health.clone()
+    }
+}
+
 /// A registry for managing hooks.
+#[derive(Clone)]
 pub struct HookRegistry {
     /// The registered hooks.
-    hooks: Arc<RwLock<HashMap<String, Vec<HookFunction>>>>,
+    hooks: Arc<RwLock<HashMap<String, Vec<Arc<RegisteredHook>>>>>,
+    /// The most recent failure of a hook with the abort policy, until taken.
+    abort: Arc<Mutex<Option<HookAbort>>>,
 }
 
 impl HookRegistry {
@@ -23,25 +240,137 @@ impl HookRegistry {
     pub fn new() -> Self {
         Self {
             hooks: Arc::new(RwLock::new(HashMap::new())),
+            abort: Arc::new(Mutex::new(None)),
         }
     }
-    
+
     /// Register a hook for an event type.
     pub async fn register_hook(&self, event_type: &str, hook: HookFunction) {
+        self.register_hook_with_options(event_type, hook, HookOptions::default()).await;
+    }
+
+    /// Register a hook for an event type with a timeout, failure policy and circuit breaker.
+    pub async fn register_hook_with_options(&self, event_type: &str, hook: HookFunction, options: HookOptions) {
         let mut hooks = self.hooks.write().await;
-        hooks.entry(event_type.to_string()).or_insert_with(Vec::new).push(hook);
+        let event_hooks = hooks.entry(event_type.to_string()).or_insert_with(Vec::new);
+        let name = options
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{}#{}", event_type, event_hooks.len() + 1));
+        event_hooks.push(Arc::new(RegisteredHook {
+            function: Arc::from(hook),
+            options,
+            health: Mutex::new(HookHealth {
+                name,
+                event_type: event_type.to_string(),
+                ..Default::default()
+            }),
+        }));
+    }
+
+    /// Remove the hooks with the given name, returning whether any was registered.
+    pub async fn remove_hook(&self, name: &str) -> bool {
+        Self::remove_named(&mut *self.hooks.write().await, name)
+    }
+
+    /// Remove the hooks with the given name without waiting, unless the registry is busy.
+    pub(crate) fn try_remove_hook(&self, name: &str) -> bool {
+        match self.hooks.try_write() {
+            Ok(mut hooks) => Self::remove_named(&mut hooks, name),
+            Err(_) => false,
+        }
+    }
+
+    fn remove_named(hooks: &mut HashMap<String, Vec<Arc<RegisteredHook>>>, name: &str) -> bool {
+        let mut removed = false;
+        hooks.retain(|_, event_hooks| {
+            event_hooks.retain(|hook| {
+                let keep = hook.health.lock().unwrap_or_else(|e| e.into_inner()).name != name;
+                removed |= !keep;
+                keep
+            });
+            !event_hooks.is_empty()
+        });
+        removed
     }
-    
+
     /// Trigger hooks for an event type.
+    ///
+    /// Failures are handled by each hook's failure policy. A failure under
+    /// `HookFailurePolicy::Abort` stops the remaining hooks, is returned as a
+    /// `HookAbort` and is kept for `take_abort`.
     pub async fn trigger_hooks(&self, event: HookEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let hooks = self.hooks.read().await;
-        if let Some(event_hooks) = hooks.get(&event.event_type) {
-            for hook in event_hooks {
-                hook(event.clone())?;
+        let event_hooks = match self.hooks.read().await.get(&event.event_type) {
+            Some(event_hooks) => event_hooks.clone(),
+            None => return Ok(()),
+        };
+        for hook in event_hooks {
+            if hook.is_disabled() {
+                continue;
+            }
+            let outcome = hook.run(event.clone()).await;
+            let health = hook.record(&outcome);
+            let Err(failure) = outcome else {
+                continue;
+            };
+            match hook.options.failure_policy {
+                HookFailurePolicy::Ignore => tracing::debug!(
+                    "hook=<{}>, event_type=<{}>, error=<{}> | hook failed",
+                    health.name,
+                    event.event_type,
+                    failure
+                ),
+                HookFailurePolicy::Warn => tracing::warn!(
+                    "hook=<{}>, event_type=<{}>, error=<{}> | hook failed",
+                    health.name,
+                    event.event_type,
+                    failure
+                ),
+                HookFailurePolicy::Abort => {
+                    let abort = HookAbort {
+                        hook: health.name,
+                        event_type: event.event_type.clone(),
+                        error: failure.to_string(),
+                    };
+                    *self.abort.lock().unwrap_or_else(|e| e.into_inner()) = Some(abort.clone());
+                    return Err(Box::new(abort));
+                }
             }
         }
         Ok(())
     }
+
+    /// Take the most recent failure of a hook with the abort policy, if any.
+    pub fn take_abort(&self) -> Option<HookAbort> {
+        self.abort.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Get the health record of every hook, grouped by event type in registration order.
+    pub async fn health(&self) -> Vec<HookHealth> {
+        let hooks = self.hooks.read().await;
+        let mut event_types: Vec<&String> = hooks.keys().collect();
+        event_types.sort();
+        event_types
+            .into_iter()
+            .flat_map(|event_type| hooks[event_type].iter())
+            .map(|hook| hook.health.lock().unwrap_or_else(|e| e.into_inner()).clone())
+            .collect()
+    }
+
+    /// Re-enable a disabled hook and clear its consecutive failures, returning whether it was found.
+    pub async fn reset_hook(&self, name: &str) -> bool {
+        let hooks = self.hooks.read().await;
+        let mut found = false;
+        for hook in hooks.values().flatten() {
+            let mut health = hook.health.lock().unwrap_or_else(|e| e.into_inner());
+            if health.name == name {
+                health.disabled = false;
+                health.consecutive_failures = 0;
+                found = true;
+            }
+        }
+        found
+    }
 }
 
 impl Default for HookRegistry {
@@ -49,3 +378,59 @@ impl Default for HookRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_hooks_are_isolated_and_circuit_broken() {
+        let registry = HookRegistry::new();
+        registry
+            .register_hook_with_options(
+                "run_started",
+                Box::new(|_| panic!("hook bug")),
+                HookOptions::new().with_name("panicky").with_timeout(None).with_max_consecutive_failures(Some(2)),
+            )
+            .await;
+        registry
+            .register_hook_with_options(
+                "run_started",
+                Box::new(|_| {
+                    std::thread::sleep(Duration::from_millis(200));
+                    Ok(())
+                }),
+                HookOptions::new().with_name("slow").with_timeout(Some(Duration::from_millis(10))),
+            )
+            .await;
+        registry.register_hook("run_started", Box::new(|_| Ok(()))).await;
+
+        let event = HookEvent::new("run_started", json!({}));
+        assert!(registry.trigger_hooks(event.clone()).await.is_ok());
+        assert!(registry.trigger_hooks(event.clone()).await.is_ok());
+        assert!(registry.trigger_hooks(event.clone()).await.is_ok());
+
+        let health = registry.health().await;
+        let names: Vec<&str> = health.iter().map(|hook| hook.name.as_str()).collect();
+        assert_eq!(names, vec!["panicky", "slow", "run_started#3"]);
+        assert_eq!((health[0].calls, health[0].panics, health[0].disabled), (2, 2, true));
+        assert_eq!(health[0].last_error.as_deref(), Some("panicked: hook bug"));
+        assert_eq!((health[1].timeouts, health[1].disabled), (3, false));
+        assert_eq!((health[2].calls, health[2].failures), (3, 0));
+        assert!(registry.reset_hook("panicky").await);
+        assert!(!registry.health().await[0].disabled);
+
+        registry
+            .register_hook_with_options(
+                "tool_started",
+                Box::new(|_| Err("policy violation".into())),
+                HookOptions::new().with_name("gate").with_failure_policy(HookFailurePolicy::Abort),
+            )
+            .await;
+        let error = registry.trigger_hooks(HookEvent::new("tool_started", json!({}))).await.unwrap_err();
+        assert_eq!(error.to_string(), "Hook 'gate' failed on 'tool_started': policy violation");
+        assert_eq!(registry.take_abort().unwrap().hook, "gate");
+        assert!(registry.take_abort().is_none());
+    }
+}
@@ -0,0 +1,310 @@
+//! Typed event subscriptions for the SDK.
+//! 
+//! This module lets application code subscribe to agent events by type,
+//! as in `agent.on::<ToolExecuted>(|event| ...)`, instead of registering a
+//! hook for an event name and picking fields out of its JSON payload. Each
+//! event type names the hook event it is read from, and the returned
+//! `Subscription` removes the hook when it is dropped.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::events::{BUDGET_WARNING_EVENT, EXPIRED_CONTENT_RELEVANT_EVENT, RUN_ABORTED_EVENT, RUN_HEARTBEAT_EVENT};
+use super::registry::{HookOptions, HookRegistry};
+
+/// Numbers subscriptions so their hook names are unique.
+static NEXT_SUBSCRIPTION: AtomicU64 = AtomicU64::new(1);
+
+/// An event payload that can be subscribed to by type.
+///
+/// Implement it for your own payload type to subscribe to custom hook events.
+pub trait TypedEvent: DeserializeOwned + Send + 'static {
+    /// The hook event type the payload is read from.
+    const EVENT_TYPE: &'static str;
+}
+
+/// A run started for a user message.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunStarted {
+    /// The ID of the user message.
+    #[serde(default)]
+    pub message_id: Option<String>,
+}
+
+impl TypedEvent for RunStarted {
+    const EVENT_TYPE: &'static str = "run_started";
+}
+
+/// A run finished with an answer, a degraded response, an interrupt or a failure.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunCompleted {
+    /// How the run ended, such as `completed`, `degraded`, `failed` or `timed_out`.
+    pub outcome: String,
+    /// The ID of the response message, if the run produced one.
+    #[serde(default)]
+    pub message_id: Option<String>,
+    /// The kind of degraded response, if the model was unavailable.
+    #[serde(default)]
+    pub degraded_kind: Option<String>,
+    /// The name of the hook that aborted the run, if any.
+    #[serde(default)]
+    pub hook: Option<String>,
+}
+
+impl TypedEvent for RunCompleted {
+    const EVENT_TYPE: &'static str = "run_completed";
+}
+
+/// The model returned a response.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelCallCompleted {
+    /// The input tokens of the call.
+    #[serde(default)]
+    pub input_tokens: u64,
+    /// The output tokens of the call.
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// The output tokens spent on reasoning.
+    #[serde(default)]
+    pub reasoning_tokens: u64,
+    /// The number of tool calls the model asked for.
+    #[serde(default)]
+    pub tool_uses: usize,
+}
+
+impl TypedEvent for ModelCallCompleted {
+    const EVENT_TYPE: &'static str = "model_call_completed";
+}
+
+/// The model call failed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelCallFailed {
+    /// The error.
+    pub error: String,
+    /// Whether the agent answers with a degraded response instead of failing.
+    #[serde(default)]
+    pub degraded: bool,
+}
+
+impl TypedEvent for ModelCallFailed {
+    const EVENT_TYPE: &'static str = "model_call_failed";
+}
+
+/// A tool started executing.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ToolStarted {
+    /// The ID of the tool call.
+    pub tool_use_id: String,
+    /// The tool name.
+    pub name: String,
+}
+
+impl TypedEvent for ToolStarted {
+    const EVENT_TYPE: &'static str = "tool_started";
+}
+
+/// A tool finished executing.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ToolExecuted {
+    /// The ID of the tool call.
+    pub tool_use_id: String,
+    /// The tool name.
+    pub name: String,
+    /// Whether the tool returned an error.
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl TypedEvent for ToolExecuted {
+    const EVENT_TYPE: &'static str = "tool_completed";
+}
+
+/// The conversation is approaching its budget.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BudgetWarning {
+    /// The share of the budget used.
+    #[serde(default)]
+    pub fraction_used: f64,
+    /// Whether the budget is used up.
+    #[serde(default)]
+    pub exhausted: bool,
+}
+
+impl TypedEvent for BudgetWarning {
+    const EVENT_TYPE: &'static str = BUDGET_WARNING_EVENT;
+}
+
+/// A long run is still in progress.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunHeartbeat {
+    /// The time since the run started in milliseconds.
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    /// The step the run is in, such as `model_call`.
+    #[serde(default)]
+    pub phase: Option<String>,
+    /// The tools running right now.
+    #[serde(default)]
+    pub active_tools: Vec<String>,
+}
+
+impl TypedEvent for RunHeartbeat {
+    const EVENT_TYPE: &'static str = RUN_HEARTBEAT_EVENT;
+}
+
+/// The liveness watchdog aborted a run past its hard ceiling.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunAborted {
+    /// The time since the run started in milliseconds.
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    /// The hard ceiling in milliseconds.
+    #[serde(default)]
+    pub ceiling_ms: u64,
+    /// The step the run was in.
+    #[serde(default)]
+    pub phase: Option<String>,
+}
+
+impl TypedEvent for RunAborted {
+    const EVENT_TYPE: &'static str = RUN_ABORTED_EVENT;
+}
+
+/// A new query touches content that aged out of the conversation.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExpiredContentRelevant {
+    /// The ID of the expired message, if it had one.
+    #[serde(default)]
+    pub message_id: Option<String>,
+    /// When the message's content was removed.
+    #[serde(default)]
+    pub expired_at: Option<String>,
+    /// The number of query terms that also appeared in the expired content.
+    #[serde(default)]
+    pub matching_terms: usize,
+}
+
+impl TypedEvent for ExpiredContentRelevant {
+    const EVENT_TYPE: &'static str = EXPIRED_CONTENT_RELEVANT_EVENT;
+}
+
+/// A typed subscription, which removes its hook when dropped.
+#[must_use = "the subscription ends when it is dropped"]
+pub struct Subscription {
+    registry: HookRegistry,
+    name: String,
+    /// Cleared on drop so no event reaches the handler while the hook is being removed.
+    active: Arc<AtomicBool>,
+    detached: bool,
+}
+
+impl std::fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription").field("name", &self.name).field("detached", &self.detached).finish()
+    }
+}
+
+impl Subscription {
+    /// Get the hook name, as shown in health reports.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Keep the hook registered for the life of the registry.
+    pub fn detach(mut self) {
+        self.detached = true;
+    }
+
+    /// Remove the hook now rather than when the subscription is dropped.
+    pub async fn unsubscribe(mut self) {
+        self.detached = true;
+        self.active.store(false, Ordering::SeqCst);
+        self.registry.remove_hook(&self.name).await;
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if self.detached {
+            return;
+        }
+        self.active.store(false, Ordering::SeqCst);
+        let (registry, name) = (self.registry.clone(), std::mem::take(&mut self.name));
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    registry.remove_hook(&name).await;
+                });
+            }
+            Err(_) => {
+                registry.try_remove_hook(&name);
+            }
+        }
+    }
+}
+
+impl HookRegistry {
+    /// Call a handler with the typed payload of each event of its type.
+    ///
+    /// The handler runs like any hook, and a payload that does not match the
+    /// type counts as a hook failure.
+    pub async fn subscribe<E, F>(&self, handler: F) -> Subscription
+    where
+        E: TypedEvent,
+        F: Fn(E) + Send + Sync + 'static,
+    {
+        let id = NEXT_SUBSCRIPTION.fetch_add(1, Ordering::Relaxed);
+        let name = format!("{}:typed#{}", E::EVENT_TYPE, id);
+        let active = Arc::new(AtomicBool::new(true));
+        let running = Arc::clone(&active);
+        let hook = Box::new(move |event: super::events::HookEvent| {
+            if !running.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            let payload = serde_json::from_value::<E>(event.data)
+                .map_err(|e| format!("Invalid '{}' payload: {}", E::EVENT_TYPE, e))?;
+            handler(payload);
+            Ok(())
+        });
+        self.register_hook_with_options(E::EVENT_TYPE, hook, HookOptions::new().with_name(&name))
+            .await;
+        Subscription {
+            registry: self.clone(),
+            name,
+            active,
+            detached: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::HookEvent;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_typed_subscription_is_removed_on_drop() {
+        let registry = HookRegistry::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let subscription = registry
+            .subscribe(move |event: ToolExecuted| sink.lock().unwrap().push((event.name, event.is_error)))
+            .await;
+        assert!(subscription.name().starts_with("tool_completed:typed#"));
+
+        let event = json!({"tool_use_id": "t1", "name": "search", "is_error": false, "agent": "a"});
+        registry.trigger_hooks(HookEvent::new("tool_completed", event)).await.unwrap();
+        registry.trigger_hooks(HookEvent::new("tool_completed", json!({"name": 3}))).await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![("search".to_string(), false)]);
+        assert_eq!(registry.health().await[0].failures, 1);
+
+        drop(subscription);
+        tokio::task::yield_now().await;
+        assert!(registry.health().await.is_empty());
+    }
+}
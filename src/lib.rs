@@ -16,16 +16,29 @@
 //! }
 //! ```
 
+// Lets the paths `#[tool]` generates resolve inside the SDK itself.
+extern crate self as indubitably_rust_agent_sdk;
+
 pub mod agent;
+pub mod crypto;
+pub mod docs;
+pub mod media;
+pub mod eval;
 pub mod models;
 pub mod types;
 pub mod tools;
 pub mod session;
+pub mod privacy;
+pub mod skills;
 pub mod telemetry;
 pub mod hooks;
 pub mod handlers;
 pub mod event_loop;
 pub mod multiagent;
+pub mod patterns;
+pub mod providers;
+pub mod testing;
+pub mod transport;
 
 // Re-export main types for convenience
 pub use agent::Agent;
@@ -35,6 +48,12 @@ pub use types::*;
 // Re-export error types
 pub use types::exceptions::*;
 
+/// Dependencies of the code `#[tool]` generates; not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json;
+}
+
 /// Current version of the SDK
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -0,0 +1,454 @@
+//! Loading of media content from files, URLs and S3.
+//! 
+//! This module provides `MediaLoader`, which resolves `ImageContent`,
+//! `DocumentContent` and `VideoContent` whose source is a file path, an
+//! HTTP URL or an S3 object into base64 content that model providers can
+//! send inline. Media types are detected from the data's signature, then
+//! the file extension, then the `content-type` the server returned. URLs
+//! are fetched through the caller's `HttpClient`, and S3 objects are read
+//! with a SigV4-signed `GetObject` request. Loads larger than the
+//! configured limit are refused before they reach a request.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::crypto::{base64, hex, sha256};
+use crate::models::http::{HttpClient, HttpRequest};
+use crate::models::signing::{uri_encode, AwsCredentials, SigV4Signer};
+use crate::types::{
+    sniff_image_media_type, DocumentContent, DocumentError, DocumentSourceType, ImageContent, ImageSourceType,
+    IndubitablyError, IndubitablyResult, Message, Messages, VideoContent, VideoSourceType,
+};
+
+/// The default largest media file the loader reads, 20 MiB.
+pub const DEFAULT_MAX_MEDIA_BYTES: usize = 20 * 1024 * 1024;
+
+/// The signing service name of S3.
+const S3_SIGNING_SERVICE: &str = "s3";
+
+/// File extensions and the media types they stand for.
+const MEDIA_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("csv", "text/csv"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("doc", "application/msword"),
+    ("docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+    ("xls", "application/vnd.ms-excel"),
+    ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("mov", "video/quicktime"),
+];
+
+/// Detect the media type of data from its signature, then from the extension of its name.
+pub fn detect_media_type(bytes: &[u8], name: &str) -> Option<String> {
+    if let Some(media_type) = sniff_image_media_type(bytes) {
+        return Some(media_type.to_string());
+    }
+    if bytes.starts_with(b"%PDF-") {
+        return Some("application/pdf".to_string());
+    }
+    let extension = Path::new(name).extension()?.to_string_lossy().to_lowercase();
+    MEDIA_TYPES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, media_type)| media_type.to_string())
+}
+
+/// Get the usual file extension of a media type.
+fn extension_for(media_type: &str) -> Option<&'static str> {
+    MEDIA_TYPES.iter().find(|(_, known)| *known == media_type).map(|(extension, _)| *extension)
+}
+
+/// Get the location of a media source, treating bare S3 sources as `bucket/key`.
+fn source_location(file_path: Option<&str>, url: Option<&str>, s3: bool, kind: &str) -> IndubitablyResult<String> {
+    let location = file_path
+        .or(url)
+        .ok_or_else(|| IndubitablyError::ValidationError(format!("{} has no data, path or URL", kind)))?;
+    if s3 && !location.starts_with("s3://") {
+        return Ok(format!("s3://{}", location));
+    }
+    Ok(location.to_string())
+}
+
+/// Where a media file lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaLocation {
+    /// A local file.
+    File(PathBuf),
+    /// An HTTP or HTTPS URL.
+    Http(String),
+    /// An S3 object.
+    S3 {
+        /// The bucket name.
+        bucket: String,
+        /// The object key.
+        key: String,
+    },
+}
+
+impl MediaLocation {
+    /// Parse a file path, `file://` URL, HTTP(S) URL or `s3://bucket/key` URI.
+    pub fn parse(location: &str) -> IndubitablyResult<Self> {
+        if location.starts_with("http://") || location.starts_with("https://") {
+            return Ok(Self::Http(location.to_string()));
+        }
+        if let Some(object) = location.strip_prefix("s3://") {
+            return match object.split_once('/') {
+                Some((bucket, key)) if !bucket.is_empty() && !key.is_empty() => Ok(Self::S3 {
+                    bucket: bucket.to_string(),
+                    key: key.to_string(),
+                }),
+                _ => Err(IndubitablyError::ValidationError(format!("Invalid S3 location '{}'", location))),
+            };
+        }
+        let path = location.strip_prefix("file://").unwrap_or(location);
+        if path.is_empty() {
+            return Err(IndubitablyError::ValidationError("Empty media location".to_string()));
+        }
+        Ok(Self::File(PathBuf::from(path)))
+    }
+
+    /// Get the file name at the end of the location.
+    pub fn name(&self) -> String {
+        match self {
+            Self::File(path) => path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+            Self::Http(url) => {
+                let path = url.split(['?', '#']).next().unwrap_or_default();
+                path.rsplit('/').next().unwrap_or_default().to_string()
+            }
+            Self::S3 { key, .. } => key.rsplit('/').next().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Media data read by a `MediaLoader`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedMedia {
+    /// The data.
+    pub bytes: Vec<u8>,
+    /// The detected media type, such as `image/png`.
+    pub media_type: String,
+    /// The file name the data was read from.
+    pub name: String,
+}
+
+impl LoadedMedia {
+    /// Get the data base64 encoded.
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.bytes)
+    }
+
+    /// Convert the data to inline image content.
+    pub fn into_image(self) -> IndubitablyResult<ImageContent> {
+        if !self.media_type.starts_with("image/") {
+            return Err(IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(format!(
+                "{} is {}, not an image",
+                self.name, self.media_type
+            ))));
+        }
+        Ok(ImageContent::base64(&self.to_base64(), &self.media_type))
+    }
+
+    /// Convert the data to inline document content.
+    pub fn into_document(self) -> IndubitablyResult<DocumentContent> {
+        // Type the document by its detected media type when the name's extension says otherwise
+        let name = match extension_for(&self.media_type) {
+            Some(extension) if detect_media_type(&[], &self.name).as_deref() != Some(self.media_type.as_str()) => {
+                format!("{}.{}", self.name, extension)
+            }
+            _ => self.name,
+        };
+        DocumentContent::from_bytes(&self.bytes, &name)
+    }
+
+    /// Convert the data to inline video content.
+    pub fn into_video(self) -> IndubitablyResult<VideoContent> {
+        if !self.media_type.starts_with("video/") {
+            return Err(IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(format!(
+                "{} is {}, not a video",
+                self.name, self.media_type
+            ))));
+        }
+        Ok(VideoContent::base64(&self.to_base64(), &self.media_type))
+    }
+}
+
+/// Resolves media content from files, URLs and S3 into inline content.
+#[derive(Clone)]
+pub struct MediaLoader {
+    client: Option<Arc<dyn HttpClient>>,
+    s3: Option<(AwsCredentials, String)>,
+    max_bytes: usize,
+}
+
+impl std::fmt::Debug for MediaLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MediaLoader")
+            .field("http_client", &self.client.is_some())
+            .field("s3_region", &self.s3.as_ref().map(|(_, region)| region))
+            .field("max_bytes", &self.max_bytes)
+            .finish()
+    }
+}
+
+impl Default for MediaLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MediaLoader {
+    /// Create a loader reading local files only.
+    pub fn new() -> Self {
+        Self {
+            client: None,
+            s3: None,
+            max_bytes: DEFAULT_MAX_MEDIA_BYTES,
+        }
+    }
+
+    /// Set the HTTP client used to fetch URLs and S3 objects.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Read S3 objects in a region with the given credentials.
+    pub fn with_s3_credentials(mut self, credentials: AwsCredentials, region: &str) -> Self {
+        self.s3 = Some((credentials, region.to_string()));
+        self
+    }
+
+    /// Set the largest media file read, in bytes.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    fn check_size(&self, name: &str, size: usize) -> IndubitablyResult<()> {
+        if size > self.max_bytes {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Media '{}' is {} bytes, limit is {} bytes",
+                name, size, self.max_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    fn client(&self, location: &str) -> IndubitablyResult<&Arc<dyn HttpClient>> {
+        self.client.as_ref().ok_or_else(|| {
+            IndubitablyError::ConfigurationError(format!("Loading '{}' requires an HTTP client", location))
+        })
+    }
+
+    /// Read media from a file path, `file://` URL, HTTP(S) URL or `s3://bucket/key` URI.
+    pub async fn load(&self, location: &str) -> IndubitablyResult<LoadedMedia> {
+        let parsed = MediaLocation::parse(location)?;
+        let name = parsed.name();
+        let (bytes, served_type) = match parsed {
+            MediaLocation::File(ref path) => {
+                let size = std::fs::metadata(path).map(|metadata| metadata.len() as usize).unwrap_or(0);
+                self.check_size(&name, size)?;
+                let bytes = std::fs::read(path).map_err(|e| {
+                    let message = format!("Cannot read {}: {}", path.display(), e);
+                    IndubitablyError::DocumentError(DocumentError::Malformed(message))
+                })?;
+                (bytes, None)
+            }
+            MediaLocation::Http(ref url) => self.fetch(location, HttpRequest::get(url)).await?,
+            MediaLocation::S3 { ref bucket, ref key } => {
+                let (credentials, region) = self.s3.as_ref().ok_or_else(|| {
+                    IndubitablyError::ConfigurationError(format!("Loading '{}' requires S3 credentials", location))
+                })?;
+                let url = format!("https://{}.s3.{}.amazonaws.com/{}", bucket, region, uri_encode(key, false));
+                let mut request =
+                    HttpRequest::get(&url).with_header("x-amz-content-sha256", &hex::encode(&sha256(b"")));
+                SigV4Signer::new(credentials.clone(), region, S3_SIGNING_SERVICE)
+                    .sign_at(&mut request, chrono::Utc::now())?;
+                self.fetch(location, request).await?
+            }
+        };
+        self.check_size(&name, bytes.len())?;
+        let media_type = detect_media_type(&bytes, &name)
+            .or(served_type)
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        tracing::debug!(
+            "location=<{}>, media_type=<{}>, bytes=<{}> | loaded media",
+            location,
+            media_type,
+            bytes.len()
+        );
+        Ok(LoadedMedia { bytes, media_type, name })
+    }
+
+    /// Send a GET request, returning the body and the media type the server declared.
+    async fn fetch(&self, location: &str, request: HttpRequest) -> IndubitablyResult<(Vec<u8>, Option<String>)> {
+        let response = self.client(location)?.send(request).await?;
+        if !response.is_success() {
+            return Err(IndubitablyError::NetworkError(format!(
+                "Fetching '{}' returned status {}",
+                location, response.status
+            )));
+        }
+        let served_type = response
+            .header("content-type")
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty() && value != "application/octet-stream");
+        Ok((response.body, served_type))
+    }
+
+    /// Get an inline copy of an image, loading it if it is not already base64.
+    pub async fn resolve_image(&self, image: &ImageContent) -> IndubitablyResult<ImageContent> {
+        let data = &image.source.data;
+        if data.base64.is_some() {
+            return Ok(image.clone());
+        }
+        let s3 = image.source.source_type == ImageSourceType::S3;
+        let location = source_location(data.file_path.as_deref(), data.url.as_deref(), s3, "Image")?;
+        let mut resolved = self.load(&location).await?.into_image()?;
+        resolved.content_type = image.content_type.clone();
+        Ok(resolved)
+    }
+
+    /// Get an inline copy of a document, loading it if it holds neither text nor base64 data.
+    pub async fn resolve_document(&self, document: &DocumentContent) -> IndubitablyResult<DocumentContent> {
+        let data = &document.source.data;
+        if data.base64.is_some() || data.text.is_some() {
+            return Ok(document.clone());
+        }
+        let s3 = document.source.source_type == DocumentSourceType::S3;
+        let location = source_location(data.file_path.as_deref(), data.url.as_deref(), s3, "Document")?;
+        self.load(&location).await?.into_document()
+    }
+
+    /// Get an inline copy of a video, loading it if it is not already base64.
+    pub async fn resolve_video(&self, video: &VideoContent) -> IndubitablyResult<VideoContent> {
+        let data = &video.source.data;
+        if data.base64.is_some() {
+            return Ok(video.clone());
+        }
+        let s3 = video.source.source_type == VideoSourceType::S3;
+        let location = source_location(data.file_path.as_deref(), data.url.as_deref(), s3, "Video")?;
+        let mut resolved = self.load(&location).await?.into_video()?;
+        resolved.content_type = video.content_type.clone();
+        Ok(resolved)
+    }
+
+    /// Replace the media of a message with inline content.
+    pub async fn resolve_message(&self, message: &mut Message) -> IndubitablyResult<()> {
+        for block in &mut message.content {
+            if let Some(ref image) = block.image {
+                block.image = Some(self.resolve_image(image).await?);
+            }
+            if let Some(ref document) = block.document {
+                block.document = Some(self.resolve_document(document).await?);
+            }
+            if let Some(ref video) = block.video {
+                block.video = Some(self.resolve_video(video).await?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the media of every message with inline content.
+    pub async fn resolve_messages(&self, messages: &mut Messages) -> IndubitablyResult<()> {
+        for message in messages.iter_mut() {
+            self.resolve_message(message).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+    use crate::types::{ContentBlock, DocumentData, DocumentSource, DocumentType};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Serves a PDF at any URL and records the requests.
+    #[derive(Default)]
+    struct MediaServer {
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for MediaServer {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            if request.url.contains("missing") {
+                return Ok(HttpResponse::new(404, Vec::new()));
+            }
+            Ok(HttpResponse::new(200, b"%PDF-1.7 report".to_vec())
+                .with_header("content-type", "application/octet-stream"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_loader_resolves_files_urls_and_s3() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chart");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+
+        let server = Arc::new(MediaServer::default());
+        let loader = MediaLoader::new()
+            .with_client(server.clone())
+            .with_s3_credentials(AwsCredentials::new("AKID", "secret"), "eu-west-1");
+        let url_document = DocumentContent {
+            content_type: DocumentType::Pdf,
+            source: DocumentSource {
+                source_type: DocumentSourceType::Http,
+                media_type: String::new(),
+                data: DocumentData {
+                    text: None,
+                    base64: None,
+                    url: Some("https://example.com/q3".to_string()),
+                    file_path: None,
+                },
+            },
+        };
+        let mut s3_document = url_document.clone();
+        s3_document.source.source_type = DocumentSourceType::S3;
+        s3_document.source.data.url = None;
+        s3_document.source.data.file_path = Some("reports/2024/q3.pdf".to_string());
+        let mut messages = vec![Message::new(
+            crate::types::MessageRole::User,
+            vec![
+                ContentBlock::from_image(ImageContent::url(&format!("file://{}", path.display()), "")),
+                ContentBlock::from_document(url_document),
+                ContentBlock::from_document(s3_document),
+            ],
+        )];
+        loader.resolve_messages(&mut messages).await.unwrap();
+
+        let image = messages[0].content[0].image.as_ref().unwrap();
+        assert_eq!(image.source.media_type, "image/png");
+        assert!(image.source.data.base64.is_some());
+        for block in &messages[0].content[1..] {
+            let document = block.document.as_ref().unwrap();
+            assert_eq!(document.format(), "pdf");
+            assert_eq!(document.base64_data().unwrap(), base64::encode(b"%PDF-1.7 report"));
+        }
+        let requests = server.requests.lock().unwrap().clone();
+        assert_eq!(requests[1].url, "https://reports.s3.eu-west-1.amazonaws.com/2024/q3.pdf");
+        assert!(requests[1].header("authorization").unwrap().contains("/eu-west-1/s3/aws4_request"));
+
+        assert!(loader.load("https://example.com/missing.png").await.is_err());
+        assert!(loader.clone().with_max_bytes(4).load(path.to_str().unwrap()).await.is_err());
+        assert!(MediaLoader::new().load("s3://bucket/key.png").await.is_err());
+        assert!(MediaLocation::parse("s3://bucket").is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Media handling for the SDK.
+//! 
+//! This module resolves the images, documents and videos of messages from
+//! where they live, such as files, URLs and S3 objects, into inline content
+//! that model providers can send, and transcribes audio into text for
+//! voice input.
+
+pub mod loader;
+pub mod transcription;
+
+pub use loader::{detect_media_type, LoadedMedia, MediaLoader, MediaLocation};
+pub use transcription::{Transcriber, Transcription, WhisperCppTranscriber};
+#[cfg(feature = "openai")]
+pub use transcription::WhisperTranscriber;
@@ -0,0 +1,171 @@
+//! Speech transcription for voice input.
+//! 
+//! This module provides the `Transcriber` trait, which turns `AudioContent`
+//! into text that an agent can answer, and `WhisperCppTranscriber`, which
+//! runs a local whisper.cpp command-line build. `WhisperTranscriber`, which
+//! sends audio to OpenAI's transcription endpoint, lives in `crate::providers`. Audio with a URL source must be
+//! loaded with a `MediaLoader` before it is transcribed.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::base64;
+use crate::types::{AudioContent, DocumentError, IndubitablyError, IndubitablyResult};
+
+#[cfg(feature = "openai")]
+pub use crate::providers::openai_transcription::{WhisperTranscriber, DEFAULT_WHISPER_MODEL};
+
+/// The message metadata key holding the language and duration of transcribed voice input.
+pub const TRANSCRIPTION_METADATA_KEY: &str = "transcription";
+
+/// The default whisper.cpp command-line binary.
+pub const DEFAULT_WHISPER_CPP_BINARY: &str = "whisper-cli";
+
+pub(crate) fn transcription_error(message: impl std::fmt::Display) -> IndubitablyError {
+    IndubitablyError::DocumentError(DocumentError::TranscriptionFailed(message.to_string()))
+}
+
+/// The text of transcribed audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcription {
+    /// The transcribed text.
+    pub text: String,
+    /// The spoken language, if the transcriber detected it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// The length of the audio in seconds, if the transcriber reported it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+}
+
+impl Transcription {
+    /// Create a transcription of the given text.
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            language: None,
+            duration: None,
+        }
+    }
+}
+
+/// A service turning speech into text.
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    /// Transcribe the speech in the audio.
+    async fn transcribe(&self, audio: &AudioContent) -> IndubitablyResult<Transcription>;
+}
+
+/// Get the raw bytes of audio held inline or in a file.
+pub(crate) fn audio_bytes(audio: &AudioContent) -> IndubitablyResult<Vec<u8>> {
+    match audio.base64_data() {
+        Some(data) => base64::decode(&data),
+        None if audio.url_data().is_some() => {
+            Err(transcription_error("audio with a URL source must be loaded with a MediaLoader first"))
+        }
+        None => Err(transcription_error("audio has no data")),
+    }
+}
+
+/// Transcribes audio with a local whisper.cpp command-line build.
+///
+/// whisper.cpp reads 16 kHz WAV audio unless it was built with FFmpeg support.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhisperCppTranscriber {
+    binary: PathBuf,
+    model: PathBuf,
+    language: String,
+    threads: Option<usize>,
+}
+
+impl WhisperCppTranscriber {
+    /// Use `whisper-cli` from the `PATH` with the given ggml model file, detecting the language.
+    pub fn new(model: impl Into<PathBuf>) -> Self {
+        Self {
+            binary: PathBuf::from(DEFAULT_WHISPER_CPP_BINARY),
+            model: model.into(),
+            language: "auto".to_string(),
+            threads: None,
+        }
+    }
+
+    /// Set the path of the whisper.cpp binary.
+    pub fn with_binary(mut self, binary: impl Into<PathBuf>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+
+    /// Set the spoken language, such as `en`, instead of detecting it.
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+
+    /// Set the number of threads whisper.cpp uses.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+}
+
+#[async_trait]
+impl Transcriber for WhisperCppTranscriber {
+    async fn transcribe(&self, audio: &AudioContent) -> IndubitablyResult<Transcription> {
+        let path = std::env::temp_dir().join(format!(
+            "indubitably-audio-{}.{}",
+            uuid::Uuid::new_v4().simple(),
+            audio.format()
+        ));
+        std::fs::write(&path, audio_bytes(audio)?).map_err(transcription_error)?;
+        let mut command = tokio::process::Command::new(&self.binary);
+        command
+            .arg("-m")
+            .arg(&self.model)
+            .arg("-f")
+            .arg(&path)
+            .args(["-l", &self.language, "-nt", "-np"]);
+        if let Some(threads) = self.threads {
+            command.args(["-t", &threads.to_string()]);
+        }
+        let output = command.output().await;
+        let _ = std::fs::remove_file(&path);
+
+        let output =
+            output.map_err(|e| transcription_error(format!("could not run {}: {}", self.binary.display(), e)))?;
+        if !output.status.success() {
+            return Err(transcription_error(String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        let mut transcription = Transcription::new(&join_transcript_lines(&String::from_utf8_lossy(&output.stdout)));
+        if self.language != "auto" {
+            transcription.language = Some(self.language.clone());
+        }
+        tracing::debug!(
+            "language=<{}>, chars=<{}> | transcribed audio with whisper.cpp",
+            self.language,
+            transcription.text.len()
+        );
+        Ok(transcription)
+    }
+}
+
+/// Join the segment lines whisper.cpp prints into one transcript.
+fn join_transcript_lines(output: &str) -> String {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_transcript_lines() {
+        assert_eq!(join_transcript_lines("\n Book a table\n\n for two.\n"), "Book a table for two.");
+    }
+}
@@ -1,220 +0,0 @@
-//! Amazon Bedrock model implementation for the SDK.
-//! 
-//! This module provides integration with Amazon Bedrock for
-//! accessing various foundation models.
-
-use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-use super::model::{Model, ModelConfig, ModelResponse, ModelUsage, ModelStreamResponse};
-use crate::types::{Messages, ToolSpec, StreamEvent, IndubitablyResult};
-
-/// Default Bedrock model ID for Claude 3 Sonnet.
-pub const DEFAULT_BEDROCK_MODEL_ID: &str = "anthropic.claude-3-sonnet-20240229-v1:0";
-
-/// Configuration specific to Bedrock models.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BedrockConfig {
-    /// The AWS region to use.
-    pub region: String,
-    /// The model ID to use.
-    pub model_id: String,
-    /// The temperature for generation.
-    pub temperature: Option<f32>,
-    /// The maximum number of tokens to generate.
-    pub max_tokens: Option<u32>,
-    /// The top-p value for nucleus sampling.
-    pub top_p: Option<f32>,
-    /// The top-k value for top-k sampling.
-    pub top_k: Option<u32>,
-    /// Whether to enable streaming.
-    pub streaming: Option<bool>,
-    /// Additional Bedrock-specific configuration.
-    pub extra: HashMap<String, serde_json::Value>,
-}
-
-impl Default for BedrockConfig {
-    fn default() -> Self {
-        Self {
-            region: "us-west-2".to_string(),
-            model_id: DEFAULT_BEDROCK_MODEL_ID.to_string(),
-            temperature: Some(0.7),
-            max_tokens: Some(4096),
-            top_p: Some(1.0),
-            top_k: Some(250),
-            streaming: Some(false),
-            extra: HashMap::new(),
-        }
-    }
-}
-
-impl BedrockConfig {
-    /// Create a new Bedrock configuration.
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    /// Set the AWS region.
-    pub fn with_region(mut self, region: &str) -> Self {
-        self.region = region.to_string();
-        self
-    }
-
-    /// Set the model ID.
-    pub fn with_model_id(mut self, model_id: &str) -> Self {
-        self.model_id = model_id.to_string();
-        self
-    }
-
-    /// Set the temperature.
-    pub fn with_temperature(mut self, temperature: f32) -> Self {
-        self.temperature = Some(temperature);
-        self
-    }
-
-    /// Set the maximum tokens.
-    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
-        self.max_tokens = Some(max_tokens);
-        self
-    }
-
-    /// Set the top-p value.
-    pub fn with_top_p(mut self, top_p: f32) -> Self {
-        self.top_p = Some(top_p);
-        self
-    }
-
-    /// Set the top-k value.
-    pub fn with_top_k(mut self, top_k: u32) -> Self {
-        self.top_k = Some(top_k);
-        self
-    }
-
-    /// Enable or disable streaming.
-    pub fn with_streaming(mut self, streaming: bool) -> Self {
-        self.streaming = Some(streaming);
-        self
-    }
-
-    /// Add extra configuration.
-    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
-        self.extra.insert(key.to_string(), value);
-        self
-    }
-}
-
-/// The Bedrock model implementation.
-#[derive(Debug)]
-pub struct BedrockModel {
-    config: ModelConfig,
-    bedrock_config: BedrockConfig,
-}
-
-impl BedrockModel {
-    /// Create a new Bedrock model.
-    pub fn new() -> Self {
-        Self {
-            config: ModelConfig::default(),
-            bedrock_config: BedrockConfig::default(),
-        }
-    }
-
-    /// Create a new Bedrock model with the given configuration.
-    pub fn with_config(bedrock_config: BedrockConfig) -> Self {
-        Self {
-            config: ModelConfig::new(&bedrock_config.model_id)
-                .with_temperature(bedrock_config.temperature.unwrap_or(0.7))
-                .with_max_tokens(bedrock_config.max_tokens.unwrap_or(4096))
-                .with_top_p(bedrock_config.top_p.unwrap_or(1.0))
-                .with_top_k(bedrock_config.top_k.unwrap_or(250))
-                .with_streaming(bedrock_config.streaming.unwrap_or(false)),
-            bedrock_config,
-        }
-    }
-}
-
-#[async_trait]
-impl Model for BedrockModel {
-    fn config(&self) -> &ModelConfig {
-        &self.config
-    }
-
-    fn update_config(&mut self, config: ModelConfig) {
-        self.config = config;
-    }
-
-    fn config_mut(&mut self) -> &mut ModelConfig {
-        &mut self.config
-    }
-
-    async fn generate(
-        &self,
-        _messages: &Messages,
-        _tool_specs: Option<&[ToolSpec]>,
-        _system_prompt: Option<&str>,
-    ) -> IndubitablyResult<ModelResponse> {
-        // For now, we'll return a mock response since we need to implement the actual Bedrock API calls
-        // TODO: Implement actual Bedrock API integration
-        Ok(ModelResponse {
-            content: "This is a mock response from Bedrock. Actual integration coming soon.".to_string(),
-            usage: Some(ModelUsage {
-                input_tokens: 10,
-                output_tokens: 15,
-                total_tokens: 25,
-            }),
-            metadata: HashMap::new(),
-        })
-    }
-
-    async fn stream(
-        &self,
-        _messages: &Messages,
-        _tool_specs: Option<&[ToolSpec]>,
-        _system_prompt: Option<&str>,
-    ) -> IndubitablyResult<ModelStreamResponse> {
-        // TODO: Implement actual Bedrock streaming
-        use tokio_stream::wrappers::ReceiverStream;
-        use tokio::sync::mpsc;
-
-        let (tx, rx) = mpsc::channel(100);
-        
-        tokio::spawn(async move {
-            let events = vec![
-                StreamEvent::message_start(),
-                StreamEvent::content_block_start(vec![crate::types::streaming::StreamContent::text("Mock Bedrock")]),
-                StreamEvent::content_block_delta(vec![crate::types::streaming::StreamContent::text(" streaming")]),
-                StreamEvent::content_block_stop(),
-                StreamEvent::message_stop(),
-            ];
-
-            for event in events {
-                if tx.send(Ok(event)).await.is_err() {
-                    break;
-                }
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            }
-        });
-
-        Ok(Box::pin(ReceiverStream::new(rx)))
-    }
-
-    async fn structured_output(
-        &self,
-        _output_model: &str,
-        _messages: &Messages,
-        _system_prompt: Option<&str>,
-    ) -> IndubitablyResult<serde_json::Value> {
-        Err(crate::types::IndubitablyError::ModelError(
-            crate::types::ModelError::InvalidResponseFormat(
-                "Bedrock model does not support structured output yet".to_string(),
-            ),
-        ))
-    }
-}
-
-impl Default for BedrockModel {
-    fn default() -> Self {
-        Self::new()
-    }
-}
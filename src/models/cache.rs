@@ -0,0 +1,462 @@
+//! Response caching for model calls.
+//! 
+//! This module provides `ModelResponseCache` and `CachingModel`, which
+//! wraps any provider and answers `generate` and `stream` calls from the
+//! cache when an identical request was answered before. Requests are keyed
+//! by a SHA-256 fingerprint of the model settings, the message roles and
+//! content, the system prompt and the tool specs; message metadata such as
+//! IDs is ignored, so replayed conversations hit the cache. Entries live in
+//! an in-memory LRU bounded by `ResponseCacheConfig::max_entries`, or in any
+//! `ResponseCacheBackend`, and expire after the configured TTL. Caching
+//! suits deterministic workloads such as evaluation runs; errors are never
+//! cached.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::model::{response_stream, Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::crypto::{hex, sha256};
+use crate::types::{IndubitablyResult, Messages, ToolSpec};
+
+/// The default number of responses kept by the in-memory cache.
+pub const DEFAULT_CACHE_MAX_ENTRIES: usize = 1000;
+
+/// The response metadata key marking a response served from the cache.
+pub const CACHE_HIT_METADATA_KEY: &str = "cache_hit";
+
+/// How a model's responses are cached when it is wrapped in a `CachingModel`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    /// The most responses kept in memory; the least recently used are evicted.
+    pub max_entries: usize,
+    /// How long a response is served from the cache, or forever if unset.
+    pub ttl: Option<Duration>,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_CACHE_MAX_ENTRIES,
+            ttl: None,
+        }
+    }
+}
+
+impl ResponseCacheConfig {
+    /// Create the default cache configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the most responses kept in memory.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Set how long a response is served from the cache.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+/// A cached response and when it was stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// The cached response.
+    pub response: ModelResponse,
+    /// When the response was stored.
+    pub stored_at: DateTime<Utc>,
+}
+
+/// Storage for cached responses, keyed by request fingerprint.
+#[async_trait]
+pub trait ResponseCacheBackend: Send + Sync {
+    /// Get the entry for a fingerprint.
+    async fn get(&self, key: &str) -> IndubitablyResult<Option<CachedResponse>>;
+
+    /// Store the entry for a fingerprint, replacing any existing one.
+    async fn put(&self, key: &str, entry: CachedResponse) -> IndubitablyResult<()>;
+
+    /// Remove the entry for a fingerprint.
+    async fn remove(&self, key: &str) -> IndubitablyResult<()>;
+}
+
+/// An in-memory backend evicting the least recently used entries.
+#[derive(Debug)]
+pub struct LruResponseCache {
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (CachedResponse, u64)>>,
+    clock: AtomicU64,
+}
+
+impl LruResponseCache {
+    /// Create a backend holding at most the given number of entries.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries: max_entries.max(1),
+            entries: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the number of entries held.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check whether no entries are held.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl ResponseCacheBackend for LruResponseCache {
+    async fn get(&self, key: &str) -> IndubitablyResult<Option<CachedResponse>> {
+        let tick = self.tick();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.get_mut(key).map(|(entry, last_used)| {
+            *last_used = tick;
+            entry.clone()
+        }))
+    }
+
+    async fn put(&self, key: &str, entry: CachedResponse) -> IndubitablyResult<()> {
+        let tick = self.tick();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(key.to_string(), (entry, tick));
+        while entries.len() > self.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => entries.remove(&oldest),
+                None => break,
+            };
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> IndubitablyResult<()> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+        Ok(())
+    }
+}
+
+/// Compute the cache key of a request.
+pub fn fingerprint(
+    config: &ModelConfig,
+    messages: &Messages,
+    tool_specs: Option<&[ToolSpec]>,
+    system_prompt: Option<&str>,
+) -> String {
+    let messages: Vec<_> = messages
+        .iter()
+        .map(|message| json!({ "role": message.role, "content": message.content }))
+        .collect();
+    let request = json!({
+        "model_id": config.model_id,
+        "temperature": config.temperature,
+        "max_tokens": config.max_tokens,
+        "top_p": config.top_p,
+        "top_k": config.top_k,
+        "extra": config.extra,
+        "system_prompt": system_prompt,
+        "tool_specs": tool_specs,
+        "messages": messages,
+    });
+    hex::encode(&sha256(request.to_string().as_bytes()))
+}
+
+/// The hit and miss counts of a cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The requests answered from the cache.
+    pub hits: u64,
+    /// The requests passed on to the model.
+    pub misses: u64,
+}
+
+/// A response cache with a TTL over a pluggable backend, shared by cloning.
+#[derive(Clone)]
+pub struct ModelResponseCache {
+    backend: Arc<dyn ResponseCacheBackend>,
+    ttl: Option<Duration>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for ModelResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelResponseCache")
+            .field("ttl", &self.ttl)
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ModelResponseCache {
+    /// Create an in-memory LRU cache with the given configuration.
+    pub fn new(config: &ResponseCacheConfig) -> Self {
+        Self::with_backend(Arc::new(LruResponseCache::new(config.max_entries)), config.ttl)
+    }
+
+    /// Create a cache over the given backend.
+    pub fn with_backend(backend: Arc<dyn ResponseCacheBackend>, ttl: Option<Duration>) -> Self {
+        Self {
+            backend,
+            ttl,
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Get the hit and miss counts.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::SeqCst),
+            misses: self.misses.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Get the unexpired response for a fingerprint, counting the hit or miss.
+    ///
+    /// Backend errors are logged and count as misses.
+    pub async fn lookup(&self, key: &str) -> Option<ModelResponse> {
+        let entry = match self.backend.get(key).await {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("key=<{}>, error=<{}> | failed to read response cache", key, e);
+                None
+            }
+        };
+        let expired = |entry: &CachedResponse| {
+            self.ttl.is_some_and(|ttl| {
+                Utc::now().signed_duration_since(entry.stored_at).to_std().unwrap_or_default() >= ttl
+            })
+        };
+        match entry {
+            Some(entry) if !expired(&entry) => {
+                self.hits.fetch_add(1, Ordering::SeqCst);
+                Some(entry.response)
+            }
+            Some(_) => {
+                if let Err(e) = self.backend.remove(key).await {
+                    tracing::warn!("key=<{}>, error=<{}> | failed to remove expired cache entry", key, e);
+                }
+                self.misses.fetch_add(1, Ordering::SeqCst);
+                None
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::SeqCst);
+                None
+            }
+        }
+    }
+
+    /// Store the response for a fingerprint; backend errors are logged.
+    pub async fn store(&self, key: &str, response: &ModelResponse) {
+        let entry = CachedResponse {
+            response: response.clone(),
+            stored_at: Utc::now(),
+        };
+        if let Err(e) = self.backend.put(key, entry).await {
+            tracing::warn!("key=<{}>, error=<{}> | failed to write response cache", key, e);
+        }
+    }
+}
+
+/// A model wrapper answering repeated requests from a response cache.
+///
+/// Only `generate` results are stored; `stream` calls are answered from the
+/// cache on a hit and passed on otherwise, and `structured_output` is never cached.
+pub struct CachingModel {
+    inner: Box<dyn Model>,
+    cache: ModelResponseCache,
+}
+
+impl std::fmt::Debug for CachingModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachingModel")
+            .field("model_id", &self.inner.config().model_id)
+            .field("cache", &self.cache)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CachingModel {
+    /// Wrap a model with an in-memory cache configured by its `cache` config, or the default.
+    pub fn new(inner: Box<dyn Model>) -> Self {
+        let cache = ModelResponseCache::new(&inner.config().cache.clone().unwrap_or_default());
+        Self { inner, cache }
+    }
+
+    /// Use the given cache, such as one shared with other models.
+    pub fn with_cache(mut self, cache: ModelResponseCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Get the cache.
+    pub fn cache(&self) -> &ModelResponseCache {
+        &self.cache
+    }
+}
+
+#[async_trait]
+impl Model for CachingModel {
+    fn config(&self) -> &ModelConfig {
+        self.inner.config()
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.inner.update_config(config);
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        self.inner.config_mut()
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let key = fingerprint(self.inner.config(), messages, tool_specs, system_prompt);
+        if let Some(mut response) = self.cache.lookup(&key).await {
+            tracing::debug!("model_id=<{}>, key=<{}> | answered from response cache", self.inner.model_id(), key);
+            response.metadata.insert(CACHE_HIT_METADATA_KEY.to_string(), json!(true));
+            return Ok(response);
+        }
+        let response = self.inner.generate(messages, tool_specs, system_prompt).await?;
+        self.cache.store(&key, &response).await;
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let key = fingerprint(self.inner.config(), messages, tool_specs, system_prompt);
+        match self.cache.lookup(&key).await {
+            Some(response) => Ok(response_stream(response)),
+            None => self.inner.stream(messages, tool_specs, system_prompt).await,
+        }
+    }
+
+    async fn structured_output(
+        &self,
+        output_model: &str,
+        messages: &Messages,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        self.inner.structured_output(output_model, messages, system_prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Message;
+    use std::sync::atomic::AtomicU32;
+
+    /// Answers with the number of calls made so far.
+    struct CountingModel {
+        config: ModelConfig,
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl Model for CountingModel {
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn update_config(&mut self, config: ModelConfig) {
+            self.config = config;
+        }
+
+        fn config_mut(&mut self) -> &mut ModelConfig {
+            &mut self.config
+        }
+
+        async fn generate(
+            &self,
+            _messages: &Messages,
+            _tool_specs: Option<&[ToolSpec]>,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelResponse> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(ModelResponse::new(&format!("answer {}", call)))
+        }
+
+        async fn stream(
+            &self,
+            messages: &Messages,
+            tool_specs: Option<&[ToolSpec]>,
+            system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelStreamResponse> {
+            let response = self.generate(messages, tool_specs, system_prompt).await?;
+            Ok(response_stream(response))
+        }
+
+        async fn structured_output(
+            &self,
+            _output_model: &str,
+            _messages: &Messages,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<serde_json::Value> {
+            Ok(serde_json::Value::Null)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caching_model_answers_repeated_requests() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let inner = CountingModel {
+            config: ModelConfig::new("counting").with_cache(ResponseCacheConfig::new().with_max_entries(1)),
+            calls: calls.clone(),
+        };
+        let model = CachingModel::new(Box::new(inner));
+        let question = vec![Message::user("What is 2 + 2?")];
+
+        let first = model.generate(&question, None, Some("Be brief")).await.unwrap();
+        // Message IDs differ between runs but do not change the fingerprint
+        let replayed = vec![Message::user("What is 2 + 2?")];
+        let second = model.generate(&replayed, None, Some("Be brief")).await.unwrap();
+        assert_eq!(second.content, first.content);
+        assert_eq!(second.metadata.get(CACHE_HIT_METADATA_KEY), Some(&json!(true)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // A different system prompt misses, and evicts the only entry
+        model.generate(&question, None, Some("Be thorough")).await.unwrap();
+        let evicted = model.generate(&question, None, Some("Be brief")).await.unwrap();
+        assert_eq!(evicted.content, "answer 3");
+        assert_eq!(model.cache().stats(), CacheStats { hits: 1, misses: 3 });
+
+        // Expired entries are not served
+        let model = model.with_cache(ModelResponseCache::new(
+            &ResponseCacheConfig::new().with_ttl(Duration::ZERO),
+        ));
+        model.generate(&question, None, None).await.unwrap();
+        model.generate(&question, None, None).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+}
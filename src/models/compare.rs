@@ -0,0 +1,271 @@
+//! Multi-model comparison for the SDK.
+//! 
+//! This module provides `ModelComparison`, which sends the same prompt to
+//! several models at once and collects each response with its latency,
+//! token usage and, when a price is known, estimated cost. It is meant for
+//! evaluating providers against each other; `format_side_by_side` lays the
+//! results out as columns for a terminal.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::model::{Model, ModelUsage};
+use crate::types::{Message, Messages};
+
+/// The default width of a column in `format_side_by_side`.
+pub const DEFAULT_COLUMN_WIDTH: usize = 40;
+
+/// The price of a model's tokens in US dollars.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// The price per 1,000 input tokens.
+    pub input_cost_per_1k: f64,
+    /// The price per 1,000 output tokens.
+    pub output_cost_per_1k: f64,
+}
+
+impl ModelPrice {
+    /// Create a price from the costs per 1,000 input and output tokens.
+    pub fn new(input_cost_per_1k: f64, output_cost_per_1k: f64) -> Self {
+        Self { input_cost_per_1k, output_cost_per_1k }
+    }
+
+    /// Estimate the cost of a call.
+    pub fn cost(&self, usage: &ModelUsage) -> f64 {
+        f64::from(usage.input_tokens) / 1000.0 * self.input_cost_per_1k
+            + f64::from(usage.output_tokens) / 1000.0 * self.output_cost_per_1k
+    }
+}
+
+/// One model's answer in a comparison.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonResult {
+    /// The label the model was added under.
+    pub label: String,
+    /// The model ID.
+    pub model_id: String,
+    /// The response text, or the error the call failed with.
+    pub response: Result<String, String>,
+    /// How long the call took.
+    pub latency: Duration,
+    /// The tokens the call used, if the provider reported them.
+    pub usage: Option<ModelUsage>,
+    /// The estimated cost in US dollars, if the model has a price and reported usage.
+    pub cost: Option<f64>,
+}
+
+/// Runs a prompt through several models concurrently.
+#[derive(Clone, Default)]
+pub struct ModelComparison {
+    models: Vec<(String, Arc<dyn Model>)>,
+    prices: HashMap<String, ModelPrice>,
+    system_prompt: Option<String>,
+}
+
+impl std::fmt::Debug for ModelComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelComparison")
+            .field("models", &self.labels())
+            .field("prices", &self.prices)
+            .field("system_prompt", &self.system_prompt)
+            .finish()
+    }
+}
+
+impl ModelComparison {
+    /// Create a comparison without models.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a model under a label, such as its provider name.
+    pub fn with_model(mut self, label: &str, model: Arc<dyn Model>) -> Self {
+        self.models.push((label.to_string(), model));
+        self
+    }
+
+    /// Set the price of the model added under a label, so its cost is estimated.
+    pub fn with_price(mut self, label: &str, price: ModelPrice) -> Self {
+        self.prices.insert(label.to_string(), price);
+        self
+    }
+
+    /// Set the system prompt sent to every model.
+    pub fn with_system_prompt(mut self, system_prompt: &str) -> Self {
+        self.system_prompt = Some(system_prompt.to_string());
+        self
+    }
+
+    /// Get the labels of the models, in the order they were added.
+    pub fn labels(&self) -> Vec<&str> {
+        self.models.iter().map(|(label, _)| label.as_str()).collect()
+    }
+
+    /// Send a prompt to every model at once.
+    pub async fn run(&self, prompt: &str) -> Vec<ComparisonResult> {
+        self.run_messages(vec![Message::user(prompt)]).await
+    }
+
+    /// Send a conversation to every model at once, returning the results in the order the models were added.
+    pub async fn run_messages(&self, messages: Messages) -> Vec<ComparisonResult> {
+        let messages = Arc::new(messages);
+        let handles: Vec<_> = self
+            .models
+            .iter()
+            .map(|(label, model)| {
+                let model = Arc::clone(model);
+                let messages = Arc::clone(&messages);
+                let system_prompt = self.system_prompt.clone();
+                let price = self.prices.get(label).copied();
+                let label = label.clone();
+                tokio::spawn(async move {
+                    let started = Instant::now();
+                    let outcome = model.generate(&messages, None, system_prompt.as_deref()).await;
+                    let latency = started.elapsed();
+                    tracing::debug!(
+                        "label=<{}>, latency_ms=<{}> | compared model responded",
+                        label,
+                        latency.as_millis()
+                    );
+                    let (response, usage) = match outcome {
+                        Ok(response) => (Ok(response.content), response.usage),
+                        Err(e) => (Err(e.to_string()), None),
+                    };
+                    ComparisonResult {
+                        model_id: model.model_id().to_string(),
+                        cost: price.zip(usage.as_ref()).map(|(price, usage)| price.cost(usage)),
+                        label,
+                        response,
+                        latency,
+                        usage,
+                    }
+                })
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(handles.len());
+        for (handle, (label, model)) in handles.into_iter().zip(&self.models) {
+            results.push(handle.await.unwrap_or_else(|e| ComparisonResult {
+                label: label.clone(),
+                model_id: model.model_id().to_string(),
+                response: Err(format!("Comparison task failed: {}", e)),
+                latency: Duration::ZERO,
+                usage: None,
+                cost: None,
+            }));
+        }
+        results
+    }
+}
+
+/// Lay comparison results out side by side, one column per model, below rows of latency, tokens and cost.
+pub fn format_side_by_side(results: &[ComparisonResult], column_width: usize) -> String {
+    let width = column_width.max(8);
+    let mut rows: Vec<Vec<String>> = vec![
+        results.iter().map(|result| format!("{} ({})", result.label, result.model_id)).collect(),
+        results.iter().map(|result| format!("latency: {} ms", result.latency.as_millis())).collect(),
+        results
+            .iter()
+            .map(|result| match result.usage {
+                Some(ref usage) => format!("tokens: {} in / {} out", usage.input_tokens, usage.output_tokens),
+                None => "tokens: -".to_string(),
+            })
+            .collect(),
+        results
+            .iter()
+            .map(|result| match result.cost {
+                Some(cost) => format!("cost: ${:.4}", cost),
+                None => "cost: -".to_string(),
+            })
+            .collect(),
+        results.iter().map(|_| "-".repeat(width)).collect(),
+    ];
+
+    let columns: Vec<Vec<String>> = results
+        .iter()
+        .map(|result| match result.response {
+            Ok(ref text) => wrap(text, width),
+            Err(ref error) => wrap(&format!("error: {}", error), width),
+        })
+        .collect();
+    let height = columns.iter().map(Vec::len).max().unwrap_or(0);
+    for line in 0..height {
+        rows.push(columns.iter().map(|column| column.get(line).cloned().unwrap_or_default()).collect());
+    }
+
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row.iter().map(|cell| pad(cell, width)).collect();
+            cells.join(" | ").trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Pad or cut a cell to exactly `width` characters.
+fn pad(cell: &str, width: usize) -> String {
+    let mut cell: String = cell.chars().take(width).collect();
+    let length = cell.chars().count();
+    cell.extend(std::iter::repeat_n(' ', width - length));
+    cell
+}
+
+/// Wrap text to lines of at most `width` characters, breaking long words.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..width).collect());
+            }
+            let length = line.chars().count();
+            if length > 0 && length + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    #[tokio::test]
+    async fn test_comparison_runs_every_model_and_formats_columns() {
+        let usage = ModelUsage { input_tokens: 1000, output_tokens: 500, total_tokens: 1500, reasoning_tokens: 0 };
+        let mut priced = ModelResponse::new("Paris is the capital of France.");
+        priced.usage = Some(usage);
+        let comparison = ModelComparison::new()
+            .with_model("openai", Arc::new(MockModel::new().with_responses(vec![priced])))
+            .with_model("ollama", Arc::new(MockModel::new().with_responses(vec![ModelResponse::new("Paris.")])))
+            .with_price("openai", ModelPrice::new(0.01, 0.03));
+
+        let results = comparison.run("What is the capital of France?").await;
+        assert_eq!(results.iter().map(|result| result.label.as_str()).collect::<Vec<_>>(), ["openai", "ollama"]);
+        assert!((results[0].cost.unwrap() - 0.025).abs() < 1e-9);
+        assert_eq!(results[1].cost, None);
+
+        let table = format_side_by_side(&results, 26);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "openai (mock)              | ollama (mock)");
+        assert_eq!(lines[2], "tokens: 1000 in / 500 out  | tokens: -");
+        assert_eq!(lines[3], "cost: $0.0250              | cost: -");
+        assert_eq!(lines[5], "Paris is the capital of    | Paris.");
+        assert_eq!(lines[6], "France.                    |");
+    }
+}
@@ -0,0 +1,311 @@
+//! Provider failover for model calls.
+//! 
+//! This module provides `FallbackModel`, which wraps an ordered list of
+//! models, usually from different providers, and moves on to the next one
+//! when a call fails because the provider is throttling, returns a server
+//! error or times out. Errors caused by the request itself, such as an
+//! invalid configuration or an overflowing context window, are returned
+//! without failing over. Each failover is published to the model's event bus
+//! as a `model_failover` event carrying the failure reason, so hooks can
+//! alert on provider outages. A stream fails over only until it has been
+//! opened; errors within an open stream are passed on.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::telemetry::events::{EventBus, LifecycleEvent, LifecycleEventKind};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, ToolSpec};
+
+/// Why a fallback model moved on to the next model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverReason {
+    /// The provider throttled the request or the quota was exceeded.
+    Throttled,
+    /// The provider was unavailable or overloaded.
+    Unavailable,
+    /// The provider answered with a 5xx status.
+    ServerError,
+    /// The connection failed.
+    Network,
+    /// The request timed out.
+    Timeout,
+}
+
+impl FailoverReason {
+    /// Classify an error, or return `None` if it should not fail over.
+    pub fn classify(error: &IndubitablyError) -> Option<Self> {
+        match error {
+            IndubitablyError::ModelError(ModelError::ModelThrottled(_) | ModelError::QuotaExceeded(_)) => {
+                Some(Self::Throttled)
+            }
+            IndubitablyError::ModelError(ModelError::ModelNotAvailable(_)) => Some(Self::Unavailable),
+            IndubitablyError::ModelError(ModelError::RequestFailed(message)) if is_server_error(message) => {
+                Some(Self::ServerError)
+            }
+            IndubitablyError::NetworkError(_) => Some(Self::Network),
+            IndubitablyError::TimeoutError(_) => Some(Self::Timeout),
+            _ => None,
+        }
+    }
+
+    /// Get the reason name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Throttled => "throttled",
+            Self::Unavailable => "unavailable",
+            Self::ServerError => "server_error",
+            Self::Network => "network",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+/// Check whether a request failure message reports a 5xx status, as in "returned status 502".
+fn is_server_error(message: &str) -> bool {
+    let lowered = message.to_lowercase();
+    ["status ", "http "].iter().any(|marker| {
+        lowered.match_indices(marker).any(|(start, _)| {
+            let code = &lowered.as_bytes()[start + marker.len()..];
+            code.len() >= 3 && code[0] == b'5' && code[1..3].iter().all(u8::is_ascii_digit)
+        })
+    })
+}
+
+/// A model that fails over to the next of an ordered list of models.
+///
+/// The first model is the primary; its config is the one exposed and updated.
+pub struct FallbackModel {
+    models: Vec<Box<dyn Model>>,
+    events: Option<EventBus>,
+}
+
+impl std::fmt::Debug for FallbackModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FallbackModel")
+            .field("models", &self.model_ids())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FallbackModel {
+    /// Create a fallback chain with its primary model.
+    pub fn new(primary: Box<dyn Model>) -> Self {
+        Self {
+            models: vec![primary],
+            events: None,
+        }
+    }
+
+    /// Add a model to try after the ones already in the chain.
+    pub fn with_fallback(mut self, model: Box<dyn Model>) -> Self {
+        self.models.push(model);
+        self
+    }
+
+    /// Publish failovers to the given event bus.
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Get the IDs of the models, in the order they are tried.
+    pub fn model_ids(&self) -> Vec<&str> {
+        self.models.iter().map(|model| model.config().model_id.as_str()).collect()
+    }
+
+    /// Decide whether a failed call to the model at `index` moves on to the next model.
+    ///
+    /// Returns the error if it should not fail over or no model is left, and
+    /// publishes the failover otherwise.
+    async fn fail_over(&self, index: usize, error: IndubitablyError) -> IndubitablyResult<()> {
+        let Some(next) = self.models.get(index + 1) else {
+            return Err(error);
+        };
+        let Some(reason) = FailoverReason::classify(&error) else {
+            return Err(error);
+        };
+        let data = json!({
+            "from": self.models[index].config().model_id,
+            "to": next.config().model_id,
+            "reason": reason.as_str(),
+            "error": error.to_string(),
+        });
+        match &self.events {
+            Some(events) => {
+                events
+                    .publish(LifecycleEvent::new(LifecycleEventKind::ModelFailover, "fallback_model", data))
+                    .await
+            }
+            None => tracing::warn!("data=<{}> | model failed over", data),
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Model for FallbackModel {
+    fn config(&self) -> &ModelConfig {
+        self.models[0].config()
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.models[0].update_config(config);
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        self.models[0].config_mut()
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let mut index = 0;
+        loop {
+            match self.models[index].generate(messages, tool_specs, system_prompt).await {
+                Err(error) => self.fail_over(index, error).await?,
+                response => return response,
+            }
+            index += 1;
+        }
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let mut index = 0;
+        loop {
+            match self.models[index].stream(messages, tool_specs, system_prompt).await {
+                Err(error) => self.fail_over(index, error).await?,
+                stream => return stream,
+            }
+            index += 1;
+        }
+    }
+
+    async fn structured_output(
+        &self,
+        output_model: &str,
+        messages: &Messages,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        let mut index = 0;
+        loop {
+            match self.models[index].structured_output(output_model, messages, system_prompt).await {
+                Err(error) => self.fail_over(index, error).await?,
+                output => return output,
+            }
+            index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::MockModel;
+    use crate::telemetry::events::EventRecorder;
+    use crate::types::Message;
+    use std::sync::Arc;
+
+    /// Fails every call with the given error.
+    struct FailingModel {
+        config: ModelConfig,
+        error: fn() -> IndubitablyError,
+    }
+
+    #[async_trait]
+    impl Model for FailingModel {
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn update_config(&mut self, config: ModelConfig) {
+            self.config = config;
+        }
+
+        fn config_mut(&mut self) -> &mut ModelConfig {
+            &mut self.config
+        }
+
+        async fn generate(
+            &self,
+            _messages: &Messages,
+            _tool_specs: Option<&[ToolSpec]>,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelResponse> {
+            Err((self.error)())
+        }
+
+        async fn stream(
+            &self,
+            _messages: &Messages,
+            _tool_specs: Option<&[ToolSpec]>,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelStreamResponse> {
+            Err((self.error)())
+        }
+
+        async fn structured_output(
+            &self,
+            _output_model: &str,
+            _messages: &Messages,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<serde_json::Value> {
+            Err((self.error)())
+        }
+    }
+
+    fn failing(model_id: &str, error: fn() -> IndubitablyError) -> Box<dyn Model> {
+        Box::new(FailingModel { config: ModelConfig::new(model_id), error })
+    }
+
+    #[tokio::test]
+    async fn test_fallback_fails_over_on_outages_only() {
+        let throttled = || IndubitablyError::ModelError(ModelError::ModelThrottled("slow down".to_string()));
+        let bad_gateway =
+            || IndubitablyError::ModelError(ModelError::RequestFailed("OpenAI returned status 502: bad gateway".to_string()));
+        let bad_request =
+            || IndubitablyError::ModelError(ModelError::RequestFailed("OpenAI returned status 400: bad request".to_string()));
+        assert_eq!(FailoverReason::classify(&bad_gateway()), Some(FailoverReason::ServerError));
+        assert_eq!(FailoverReason::classify(&bad_request()), None);
+
+        let events = EventBus::new();
+        let recorder = EventRecorder::new();
+        events.subscribe(Arc::new(recorder.clone()));
+        let model = FallbackModel::new(failing("claude", throttled))
+            .with_fallback(failing("gpt-4o", bad_gateway))
+            .with_fallback(Box::new(MockModel::new().with_responses(vec![ModelResponse::new("Still up")])))
+            .with_event_bus(events);
+        assert_eq!(model.config().model_id, "claude");
+
+        let messages = vec![Message::user("Hi")];
+        let response = model.generate(&messages, None, None).await.unwrap();
+        assert_eq!(response.content, "Still up");
+        let failovers = recorder.events();
+        assert_eq!(failovers.len(), 2);
+        assert_eq!(failovers[0].kind, LifecycleEventKind::ModelFailover);
+        assert_eq!(failovers[0].data["reason"], "throttled");
+        assert_eq!(failovers[1].data["from"], "gpt-4o");
+        assert_eq!(failovers[1].data["reason"], "server_error");
+
+        // Request errors are returned without trying the next model
+        let model = FallbackModel::new(failing("claude", bad_request)).with_fallback(failing("gpt-4o", throttled));
+        assert!(matches!(
+            model.stream(&messages, None, None).await,
+            Err(IndubitablyError::ModelError(ModelError::RequestFailed(_)))
+        ));
+        // The last model's error is returned once the chain is exhausted
+        let model = FallbackModel::new(failing("claude", throttled)).with_fallback(failing("gpt-4o", bad_gateway));
+        let error = model.structured_output("Answer", &messages, None).await.unwrap_err();
+        assert!(error.to_string().contains("status 502"));
+    }
+}
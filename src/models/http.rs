@@ -0,0 +1,232 @@
+//! HTTP transport abstraction for model providers.
+//! 
+//! This module provides the `HttpClient` trait that providers use to talk
+//! to their APIs, along with the request and response types exchanged with
+//! it. Keeping the transport behind a trait lets applications supply their
+//! own client and lets the SDK layer logging and other concerns on top.
+
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio_stream::{Stream, StreamExt};
+
+use crate::types::{IndubitablyError, IndubitablyResult, ModelError};
+
+/// An HTTP request sent by a provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// The HTTP method.
+    pub method: String,
+    /// The full request URL.
+    pub url: String,
+    /// The request headers, in order.
+    pub headers: Vec<(String, String)>,
+    /// The request body.
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Create a new request with no headers and an empty body.
+    pub fn new(method: &str, url: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Create a new POST request.
+    pub fn post(url: &str) -> Self {
+        Self::new("POST", url)
+    }
+
+    /// Create a new GET request.
+    pub fn get(url: &str) -> Self {
+        Self::new("GET", url)
+    }
+
+    /// Add a header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the body.
+    pub fn with_body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Serialize a value as the JSON body and set the content type.
+    pub fn with_json_body<T: Serialize>(self, body: &T) -> IndubitablyResult<Self> {
+        let body = serde_json::to_vec(body).map_err(|e| {
+            IndubitablyError::ModelError(ModelError::RequestFailed(format!("Failed to serialize request body: {}", e)))
+        })?;
+        Ok(self.with_header("content-type", "application/json").with_body(body))
+    }
+
+    /// Get the first header with the given name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// An HTTP response received by a provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The response headers, in order.
+    pub headers: Vec<(String, String)>,
+    /// The response body.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Create a new response.
+    pub fn new(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// Add a header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Check if the status code indicates success.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Get the first header with the given name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Get the body as text, replacing invalid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Deserialize the body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> IndubitablyResult<T> {
+        serde_json::from_slice(&self.body).map_err(|e| {
+            IndubitablyError::ModelError(ModelError::InvalidResponseFormat(format!("Failed to parse response body: {}", e)))
+        })
+    }
+}
+
+/// The body of a streaming response, as chunks of bytes.
+pub type HttpBodyStream = Pin<Box<dyn Stream<Item = IndubitablyResult<Vec<u8>>> + Send>>;
+
+/// An HTTP response whose body is read as it arrives, such as a server-sent event stream.
+pub struct HttpStreamingResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The response headers, in order.
+    pub headers: Vec<(String, String)>,
+    /// The response body.
+    pub body: HttpBodyStream,
+}
+
+impl std::fmt::Debug for HttpStreamingResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpStreamingResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HttpStreamingResponse {
+    /// Check if the status code indicates success.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Get the first header with the given name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Read the rest of the body, such as the details of an error status.
+    pub async fn collect(mut self) -> IndubitablyResult<HttpResponse> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.body.next().await {
+            body.extend_from_slice(&chunk?);
+        }
+        Ok(HttpResponse {
+            status: self.status,
+            headers: self.headers,
+            body,
+        })
+    }
+}
+
+impl From<HttpResponse> for HttpStreamingResponse {
+    fn from(response: HttpResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers,
+            body: Box::pin(tokio_stream::once(Ok(response.body))),
+        }
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// A client that sends HTTP requests on behalf of a provider.
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    /// Send a request and return the response.
+    async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse>;
+
+    /// Send a request and return the response once its headers arrive, reading the body as it comes.
+    ///
+    /// The default waits for the whole response, so clients that cannot
+    /// stream should override this to support bodies that never end.
+    async fn send_streaming(&self, request: HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+        Ok(self.send(request).await?.into())
+    }
+}
+
+/// The client providers use until one is configured, failing every request.
+///
+/// The SDK does not bundle a TLS stack, so hosted providers need a client
+/// supplied through their `with_client` rather than silently answering.
+#[derive(Debug, Clone)]
+pub struct UnconfiguredHttpClient {
+    provider: String,
+}
+
+impl UnconfiguredHttpClient {
+    /// Create a client that reports the given provider as unconfigured.
+    pub fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl HttpClient for UnconfiguredHttpClient {
+    async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidConfiguration(format!(
+            "{} has no HTTP client to send {} {}; set one with `with_client`",
+            self.provider, request.method, request.url
+        ))))
+    }
+}
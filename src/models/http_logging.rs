@@ -0,0 +1,536 @@
+//! Provider HTTP logging for the SDK.
+//! 
+//! This module provides `LoggingHttpClient`, an opt-in `HttpClient` wrapper
+//! that logs raw provider requests and responses. Credentials are redacted
+//! before anything is written: sensitive headers, JSON fields, form fields
+//! and query parameters are masked by name, such as `access_token` or
+//! `client_secret`, and more can be added. Bodies in any other format are
+//! logged by size only. Logs go to `tracing` at debug level or to a
+//! size-rotated file.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+
+use crate::types::{IndubitablyError, IndubitablyResult};
+use super::http::{HttpClient, HttpRequest, HttpResponse, HttpStreamingResponse};
+
+/// The replacement written in place of redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers that are always redacted.
+pub const DEFAULT_REDACTED_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "api-key",
+    "x-amz-security-token",
+    "cookie",
+    "set-cookie",
+];
+
+/// Name endings that mark a header, field or parameter as sensitive, such as `refresh_token` or `private_key`.
+pub const DEFAULT_REDACTED_NAME_SUFFIXES: &[&str] = &["token", "key"];
+
+/// Name parts that mark a header, field or parameter as sensitive, such as `client_secret`.
+pub const DEFAULT_REDACTED_NAME_PARTS: &[&str] =
+    &["secret", "password", "passwd", "authorization", "assertion", "credential", "cookie"];
+
+/// Rules for masking sensitive data in logged traffic.
+///
+/// Names are compared ignoring case. A name is masked when it is listed,
+/// ends with one of the suffixes or contains one of the parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactionFilter {
+    headers: HashSet<String>,
+    fields: HashSet<String>,
+    name_suffixes: Vec<String>,
+    name_parts: Vec<String>,
+    max_body_bytes: usize,
+}
+
+impl Default for RedactionFilter {
+    fn default() -> Self {
+        Self {
+            headers: DEFAULT_REDACTED_HEADERS.iter().map(|h| h.to_string()).collect(),
+            fields: HashSet::new(),
+            name_suffixes: DEFAULT_REDACTED_NAME_SUFFIXES.iter().map(|s| s.to_string()).collect(),
+            name_parts: DEFAULT_REDACTED_NAME_PARTS.iter().map(|p| p.to_string()).collect(),
+            max_body_bytes: 64 * 1024,
+        }
+    }
+}
+
+impl RedactionFilter {
+    /// Create a new filter with the default rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redact an additional header.
+    pub fn with_header(mut self, name: &str) -> Self {
+        self.headers.insert(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Redact a JSON body field or query parameter wherever it appears.
+    pub fn with_field(mut self, name: &str) -> Self {
+        self.fields.insert(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Redact every header, field and parameter whose name ends with a suffix.
+    pub fn with_name_suffix(mut self, suffix: &str) -> Self {
+        self.name_suffixes.push(suffix.to_ascii_lowercase());
+        self
+    }
+
+    /// Check whether a field or query parameter is masked.
+    pub fn is_sensitive_field(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.fields.contains(&name) || self.matches_patterns(&name)
+    }
+
+    /// Check whether a header is masked.
+    pub fn is_sensitive_header(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.headers.contains(&name) || self.matches_patterns(&name)
+    }
+
+    fn matches_patterns(&self, name: &str) -> bool {
+        self.name_suffixes.iter().any(|suffix| name.ends_with(suffix.as_str()))
+            || self.name_parts.iter().any(|part| name.contains(part.as_str()))
+    }
+
+    /// Set the maximum number of body bytes written per entry.
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    /// Redact a header list into a JSON object.
+    pub fn redact_headers(&self, headers: &[(String, String)]) -> Value {
+        let mut redacted = Map::new();
+        for (name, value) in headers {
+            let value = if self.is_sensitive_header(name) {
+                REDACTED.to_string()
+            } else {
+                value.clone()
+            };
+            redacted.insert(name.clone(), Value::String(value));
+        }
+        Value::Object(redacted)
+    }
+
+    /// Redact masked query parameters in a URL.
+    pub fn redact_url(&self, url: &str) -> String {
+        match url.split_once('?') {
+            Some((base, query)) => format!("{}?{}", base, self.redact_form(query)),
+            None => url.to_string(),
+        }
+    }
+
+    /// Redact a body, truncating it if it is too large.
+    ///
+    /// JSON bodies have their sensitive fields masked and form-encoded bodies,
+    /// such as OAuth token requests, their sensitive parameters. Any other
+    /// body is replaced by its size, since its secrets cannot be found.
+    pub fn redact_body(&self, body: &[u8], content_type: Option<&str>) -> Value {
+        if body.is_empty() {
+            return Value::Null;
+        }
+        if let Ok(mut value) = serde_json::from_slice::<Value>(body) {
+            self.redact_value(&mut value);
+            let text = value.to_string();
+            if text.len() <= self.max_body_bytes {
+                return value;
+            }
+            return Value::String(truncate(&text, self.max_body_bytes));
+        }
+        let media_type = content_type.and_then(|value| value.split(';').next()).unwrap_or_default().trim();
+        if media_type.eq_ignore_ascii_case("application/x-www-form-urlencoded") {
+            let form = self.redact_form(&String::from_utf8_lossy(body));
+            return Value::String(truncate(&form, self.max_body_bytes));
+        }
+        Value::String(format!("<redacted {} bytes>", body.len()))
+    }
+
+    /// Redact the masked parameters of a form-encoded query or body.
+    fn redact_form(&self, form: &str) -> String {
+        form.split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((name, _)) if self.is_sensitive_field(name) => {
+                    format!("{}={}", name, REDACTED)
+                }
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.is_sensitive_field(key) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_value(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+}
+
+fn truncate(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut cut = max_bytes;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    format!("{}...[truncated {} bytes]", &text[..cut], text.len() - cut)
+}
+
+/// A log file that rotates once it reaches a size limit.
+///
+/// When the active file would exceed `max_bytes`, it is renamed to
+/// `<path>.1`, older files shift up by one and the oldest beyond
+/// `max_files` is removed.
+#[derive(Debug)]
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    state: Mutex<Option<(File, u64)>>,
+}
+
+impl RotatingFileWriter {
+    /// Create a new writer, keeping at most `max_files` rotated files.
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            max_files,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Get the path of the active log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a line to the log, rotating first if needed.
+    pub fn write_line(&self, line: &str) -> IndubitablyResult<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let line_len = line.len() as u64 + 1;
+
+        if let Some((_, size)) = state.as_ref() {
+            if *size > 0 && size + line_len > self.max_bytes {
+                *state = None;
+                self.rotate()?;
+            }
+        }
+
+        if state.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(log_error)?;
+            let size = file.metadata().map_err(log_error)?.len();
+            *state = Some((file, size));
+        }
+
+        if let Some((file, size)) = state.as_mut() {
+            writeln!(file, "{}", line).map_err(log_error)?;
+            *size += line_len;
+        }
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&self) -> IndubitablyResult<()> {
+        if self.max_files == 0 {
+            return fs::remove_file(&self.path).map_err(log_error);
+        }
+        let _ = fs::remove_file(self.rotated_path(self.max_files));
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1)).map_err(log_error)?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1)).map_err(log_error)
+    }
+}
+
+fn log_error(error: std::io::Error) -> IndubitablyError {
+    IndubitablyError::InternalError(format!("Failed to write HTTP log: {}", error))
+}
+
+/// An `HttpClient` wrapper that logs redacted requests and responses.
+pub struct LoggingHttpClient {
+    inner: Arc<dyn HttpClient>,
+    provider: String,
+    filter: RedactionFilter,
+    writer: Option<Arc<RotatingFileWriter>>,
+}
+
+impl LoggingHttpClient {
+    /// Wrap a client, logging traffic for the named provider via `tracing`.
+    pub fn new(inner: Arc<dyn HttpClient>, provider: &str) -> Self {
+        Self {
+            inner,
+            provider: provider.to_string(),
+            filter: RedactionFilter::default(),
+            writer: None,
+        }
+    }
+
+    /// Set the redaction filter.
+    pub fn with_filter(mut self, filter: RedactionFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Write log entries to a rotating file instead of `tracing`.
+    pub fn with_writer(mut self, writer: Arc<RotatingFileWriter>) -> Self {
+        self.writer = Some(writer);
+        self
+    }
+
+    fn emit(&self, entry: Value) {
+        match &self.writer {
+            Some(writer) => {
+                if let Err(e) = writer.write_line(&entry.to_string()) {
+                    tracing::warn!("provider=<{}>, error=<{}> | failed to write http log entry", self.provider, e);
+                }
+            }
+            None => tracing::debug!("provider=<{}>, entry=<{}> | http exchange", self.provider, entry),
+        }
+    }
+}
+
+#[async_trait]
+impl HttpClient for LoggingHttpClient {
+    async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        self.emit(json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "provider": self.provider,
+            "request_id": request_id,
+            "direction": "request",
+            "method": request.method,
+            "url": self.filter.redact_url(&request.url),
+            "headers": self.filter.redact_headers(&request.headers),
+            "body": self.filter.redact_body(&request.body, request.header("content-type")),
+        }));
+
+        let started = std::time::Instant::now();
+        let result = self.inner.send(request).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        match &result {
+            Ok(response) => self.emit(json!({
+                "timestamp": Utc::now().to_rfc3339(),
+                "provider": self.provider,
+                "request_id": request_id,
+                "direction": "response",
+                "status": response.status,
+                "elapsed_ms": elapsed_ms,
+                "headers": self.filter.redact_headers(&response.headers),
+                "body": self.filter.redact_body(&response.body, response.header("content-type")),
+            })),
+            Err(e) => self.emit(json!({
+                "timestamp": Utc::now().to_rfc3339(),
+                "provider": self.provider,
+                "request_id": request_id,
+                "direction": "error",
+                "elapsed_ms": elapsed_ms,
+                "error": e.to_string(),
+            })),
+        }
+        result
+    }
+
+    /// Forward a streaming request, logging the request and the response headers but not the streamed body.
+    async fn send_streaming(&self, request: HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        self.emit(json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "provider": self.provider,
+            "request_id": request_id,
+            "direction": "request",
+            "method": request.method,
+            "url": self.filter.redact_url(&request.url),
+            "headers": self.filter.redact_headers(&request.headers),
+            "body": self.filter.redact_body(&request.body, request.header("content-type")),
+        }));
+
+        let started = std::time::Instant::now();
+        let result = self.inner.send_streaming(request).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        let record = match &result {
+            Ok(response) => json!({
+                "status": response.status,
+                "headers": self.filter.redact_headers(&response.headers),
+                "streaming": true,
+            }),
+            Err(e) => json!({"direction": "error", "error": e.to_string()}),
+        };
+        let mut entry = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "provider": self.provider,
+            "request_id": request_id,
+            "direction": "response",
+            "elapsed_ms": elapsed_ms,
+        });
+        if let (Value::Object(entry), Value::Object(record)) = (&mut entry, record) {
+            entry.extend(record);
+        }
+        self.emit(entry);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoClient;
+
+    #[async_trait]
+    impl HttpClient for EchoClient {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            Ok(HttpResponse::new(200, request.body).with_header("set-cookie", "session=abc"))
+        }
+    }
+
+    fn temp_log_path() -> PathBuf {
+        std::env::temp_dir().join(format!("indubitably-http-{}.log", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_redaction_filter() {
+        let filter = RedactionFilter::new().with_field("ssn");
+
+        let headers = filter.redact_headers(&[
+            ("Authorization".to_string(), "Bearer sk-secret".to_string()),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ]);
+        assert_eq!(headers["Authorization"], json!(REDACTED));
+        assert_eq!(headers["Content-Type"], json!("application/json"));
+
+        let body = filter.redact_body(br#"{"user": {"name": "Ann", "ssn": "123"}, "items": [{"token": "t"}]}"#, None);
+        assert_eq!(body["user"]["ssn"], json!(REDACTED));
+        assert_eq!(body["user"]["name"], json!("Ann"));
+        assert_eq!(body["items"][0]["token"], json!(REDACTED));
+
+        assert_eq!(
+            filter.redact_url("https://api.example.com/v1?key=abc&alt=sse"),
+            "https://api.example.com/v1?key=[REDACTED]&alt=sse"
+        );
+
+        let sensitive = [
+            "access_token",
+            "refresh_token",
+            "id_token",
+            "client_secret",
+            "private_key",
+            "Authorization",
+            "assertion",
+            "clientSecret",
+        ];
+        for name in sensitive {
+            assert!(filter.is_sensitive_field(name), "{} was not redacted", name);
+        }
+        for name in ["max_tokens", "model", "keywords", "messages"] {
+            assert!(!filter.is_sensitive_field(name), "{} was redacted", name);
+        }
+        assert!(filter.is_sensitive_header("x-goog-api-key"));
+        let oauth =
+            filter.redact_body(br#"{"grant_type": "refresh_token", "refresh_token": "r", "expires_in": 60}"#, None);
+        assert_eq!(oauth, json!({"grant_type": "refresh_token", "refresh_token": REDACTED, "expires_in": 60}));
+    }
+
+    #[tokio::test]
+    async fn test_logging_client_writes_redacted_entries() {
+        let path = temp_log_path();
+        let writer = Arc::new(RotatingFileWriter::new(&path, 1024 * 1024, 2));
+        let client = LoggingHttpClient::new(Arc::new(EchoClient), "openai").with_writer(writer);
+
+        let request = HttpRequest::post("https://api.example.com/v1/chat")
+            .with_header("Authorization", "Bearer sk-secret")
+            .with_json_body(&json!({"prompt": "hi", "api_key": "sk-secret"}))
+            .unwrap();
+        let response = client.send(request).await.unwrap();
+        assert!(response.is_success());
+
+        let log = fs::read_to_string(&path).unwrap();
+        assert_eq!(log.lines().count(), 2);
+        assert!(!log.contains("sk-secret"));
+        assert!(!log.contains("session=abc"));
+        assert!(log.contains("\"prompt\":\"hi\""));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_logging_client_redacts_token_requests() {
+        let path = temp_log_path();
+        let writer = Arc::new(RotatingFileWriter::new(&path, 1024 * 1024, 2));
+        let client = LoggingHttpClient::new(Arc::new(EchoClient), "vertex").with_writer(writer);
+
+        let form = [
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", "eyJhbGciOiJSUzI1NiJ9.claims.signature"),
+            ("client_secret", "shh"),
+            ("refresh_token", "1//refresh"),
+        ];
+        // The echoed form is not a token response, so only the log matters here
+        let _ = super::super::signing::request_token(&client, "https://oauth2.example.com/token", &form).await;
+
+        let log = fs::read_to_string(&path).unwrap();
+        for secret in ["eyJhbGciOiJSUzI1NiJ9", "shh", "1%2F%2Frefresh"] {
+            assert!(!log.contains(secret), "{} was logged", secret);
+        }
+        assert!(log.contains("client_secret=[REDACTED]"));
+        assert!(log.contains("grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer"));
+        // The echoed response has no content type, so only its size is logged
+        assert!(log.contains("<redacted "));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rotating_file_writer() {
+        let path = temp_log_path();
+        let writer = RotatingFileWriter::new(&path, 20, 2);
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            writer.write_line(line).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth line\n");
+        assert_eq!(fs::read_to_string(writer.rotated_path(1)).unwrap(), "third line\n");
+        assert_eq!(fs::read_to_string(writer.rotated_path(2)).unwrap(), "second line\n");
+        assert!(!writer.rotated_path(3).exists());
+
+        for index in 0..=2 {
+            let _ = fs::remove_file(if index == 0 { path.clone() } else { writer.rotated_path(index) });
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Image generation models for the SDK.
+//! 
+//! This module provides the `ImageGenerationModel` trait, which turns a
+//! prompt into images. Its providers, `OpenAIImageModel` for the OpenAI
+//! Images API and `BedrockImageModel` for Amazon Titan Image Generator and
+//! Stability SDXL on Bedrock, live in `crate::providers`. Generated images are
+//! returned as base64 `ImageContent` so they can be attached to messages or results.
+
+use async_trait::async_trait;
+
+use crate::types::{ImageContent, IndubitablyResult};
+
+#[cfg(feature = "bedrock")]
+pub use crate::providers::bedrock_image::{BedrockImageModel, DEFAULT_BEDROCK_IMAGE_MODEL_ID};
+#[cfg(feature = "openai")]
+pub use crate::providers::openai_image::{OpenAIImageModel, DEFAULT_OPENAI_IMAGE_MODEL_ID};
+
+/// A request for generated images.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageRequest {
+    /// What to draw.
+    pub prompt: String,
+    /// What to keep out of the image, for models that accept it.
+    pub negative_prompt: Option<String>,
+    /// The number of images to generate.
+    pub count: u32,
+    /// The image width in pixels.
+    pub width: u32,
+    /// The image height in pixels.
+    pub height: u32,
+    /// The seed for reproducible output, for models that accept it.
+    pub seed: Option<u64>,
+}
+
+impl ImageRequest {
+    /// Create a request for one 1024x1024 image.
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            prompt: prompt.to_string(),
+            negative_prompt: None,
+            count: 1,
+            width: 1024,
+            height: 1024,
+            seed: None,
+        }
+    }
+
+    /// Set what to keep out of the image.
+    pub fn with_negative_prompt(mut self, negative_prompt: &str) -> Self {
+        self.negative_prompt = Some(negative_prompt.to_string());
+        self
+    }
+
+    /// Set the number of images to generate.
+    pub fn with_count(mut self, count: u32) -> Self {
+        self.count = count.max(1);
+        self
+    }
+
+    /// Set the image size in pixels.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Set the seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// A model that generates images from a prompt.
+#[async_trait]
+pub trait ImageGenerationModel: Send + Sync {
+    /// Get the model ID.
+    fn model_id(&self) -> &str;
+
+    /// Generate images for a request.
+    async fn generate_images(&self, request: &ImageRequest) -> IndubitablyResult<Vec<ImageContent>>;
+}
@@ -0,0 +1,252 @@
+//! Request and response middleware for model providers.
+//! 
+//! This module provides the `ModelMiddleware` trait and `MiddlewareChain`,
+//! which every HTTP provider runs around its calls. The chain is set per
+//! model with `ModelConfig::with_middleware`. Each middleware can rewrite the
+//! outbound provider request, such as its headers or JSON payload, and the
+//! inbound response before the provider parses it. Requests pass through the
+//! chain in order and responses in reverse order. Providers that sign their
+//! requests run the chain before signing, so rewritten payloads are signed.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::http::{HttpClient, HttpRequest, HttpResponse, HttpStreamingResponse};
+use super::signing::RequestSigner;
+use crate::types::IndubitablyResult;
+
+/// Rewrites provider requests and responses.
+#[async_trait]
+pub trait ModelMiddleware: Send + Sync {
+    /// Get the middleware's name, used in logs.
+    fn name(&self) -> &str;
+
+    /// Rewrite a request before it is sent.
+    async fn on_request(&self, _request: &mut HttpRequest) -> IndubitablyResult<()> {
+        Ok(())
+    }
+
+    /// Rewrite a response before the provider parses it.
+    async fn on_response(&self, _response: &mut HttpResponse) -> IndubitablyResult<()> {
+        Ok(())
+    }
+}
+
+/// The middleware a model runs around each provider call, in order.
+///
+/// Chains compare equal when they hold the same middleware instances.
+#[derive(Clone, Default)]
+pub struct MiddlewareChain {
+    middleware: Vec<Arc<dyn ModelMiddleware>>,
+}
+
+impl std::fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.middleware.iter().map(|middleware| middleware.name())).finish()
+    }
+}
+
+impl PartialEq for MiddlewareChain {
+    fn eq(&self, other: &Self) -> bool {
+        self.middleware.len() == other.middleware.len()
+            && self.middleware.iter().zip(&other.middleware).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl MiddlewareChain {
+    /// Create an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a middleware to the end of the chain.
+    pub fn with(mut self, middleware: Arc<dyn ModelMiddleware>) -> Self {
+        self.push(middleware);
+        self
+    }
+
+    /// Add a middleware to the end of the chain.
+    pub fn push(&mut self, middleware: Arc<dyn ModelMiddleware>) {
+        self.middleware.push(middleware);
+    }
+
+    /// Get the names of the middleware, in request order.
+    pub fn names(&self) -> Vec<&str> {
+        self.middleware.iter().map(|middleware| middleware.name()).collect()
+    }
+
+    /// Check whether the chain has no middleware.
+    pub fn is_empty(&self) -> bool {
+        self.middleware.is_empty()
+    }
+
+    /// Pass a request through each middleware in order.
+    pub async fn on_request(&self, request: &mut HttpRequest) -> IndubitablyResult<()> {
+        for middleware in &self.middleware {
+            middleware.on_request(request).await?;
+        }
+        Ok(())
+    }
+
+    /// Pass a response through each middleware in reverse order.
+    pub async fn on_response(&self, response: &mut HttpResponse) -> IndubitablyResult<()> {
+        for middleware in self.middleware.iter().rev() {
+            middleware.on_response(response).await?;
+        }
+        Ok(())
+    }
+
+    /// Send a request through the chain and a client.
+    pub async fn send(&self, client: &dyn HttpClient, mut request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+        self.on_request(&mut request).await?;
+        let mut response = client.send(request).await?;
+        self.on_response(&mut response).await?;
+        Ok(response)
+    }
+
+    /// Send a request through the chain and a client, reading the body as it arrives.
+    ///
+    /// A successful body is handed to the caller unread, so `on_response`
+    /// only sees unsuccessful responses, which are read in full first.
+    pub async fn send_streaming(
+        &self,
+        client: &dyn HttpClient,
+        mut request: HttpRequest,
+    ) -> IndubitablyResult<HttpStreamingResponse> {
+        self.on_request(&mut request).await?;
+        let response = client.send_streaming(request).await?;
+        if response.is_success() {
+            return Ok(response);
+        }
+        let mut response = response.collect().await?;
+        self.on_response(&mut response).await?;
+        Ok(response.into())
+    }
+}
+
+/// Adds fixed headers to every request, replacing headers of the same name.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderMiddleware {
+    headers: Vec<(String, String)>,
+}
+
+impl HeaderMiddleware {
+    /// Create a middleware adding no headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[async_trait]
+impl ModelMiddleware for HeaderMiddleware {
+    fn name(&self) -> &str {
+        "headers"
+    }
+
+    async fn on_request(&self, request: &mut HttpRequest) -> IndubitablyResult<()> {
+        for (name, value) in &self.headers {
+            request.headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+            request.headers.push((name.clone(), value.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Signs every request with a `RequestSigner`.
+pub struct SignerMiddleware {
+    signer: Arc<dyn RequestSigner>,
+}
+
+impl SignerMiddleware {
+    /// Create a middleware signing with the given signer.
+    pub fn new(signer: Arc<dyn RequestSigner>) -> Self {
+        Self { signer }
+    }
+}
+
+#[async_trait]
+impl ModelMiddleware for SignerMiddleware {
+    fn name(&self) -> &str {
+        "signer"
+    }
+
+    async fn on_request(&self, request: &mut HttpRequest) -> IndubitablyResult<()> {
+        self.signer.sign(request).await
+    }
+}
+
+#[cfg(all(test, feature = "openai"))]
+mod tests {
+    use super::*;
+    use crate::models::model::Model;
+    use crate::models::openai::{OpenAIConfig, OpenAIModel};
+    use crate::types::Message;
+    use serde_json::{json, Value};
+    use std::sync::Mutex;
+
+    /// Tags the payload and masks an account number in responses.
+    struct RedactingMiddleware;
+
+    #[async_trait]
+    impl ModelMiddleware for RedactingMiddleware {
+        fn name(&self) -> &str {
+            "redacting"
+        }
+
+        async fn on_request(&self, request: &mut HttpRequest) -> IndubitablyResult<()> {
+            let mut body: Value = serde_json::from_slice(&request.body)?;
+            body["user"] = json!("tenant-42");
+            request.body = serde_json::to_vec(&body)?;
+            Ok(())
+        }
+
+        async fn on_response(&self, response: &mut HttpResponse) -> IndubitablyResult<()> {
+            response.body = response.text().replace("4111-1111", "[ACCOUNT]").into_bytes();
+            Ok(())
+        }
+    }
+
+    struct CapturingClient {
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for CapturingClient {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.requests.lock().unwrap().push(request);
+            let body = json!({
+                "choices": [{ "message": { "role": "assistant", "content": "Card 4111-1111 is on file." }, "finish_reason": "stop" }],
+            });
+            Ok(HttpResponse::new(200, serde_json::to_vec(&body).unwrap()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_rewrites_requests_and_responses() {
+        let client = Arc::new(CapturingClient { requests: Mutex::new(Vec::new()) });
+        let mut model = OpenAIModel::with_config(OpenAIConfig::new().with_api_key("sk-test").with_model_id("gpt-4o"))
+            .with_client(client.clone());
+        let config = model
+            .config()
+            .clone()
+            .with_middleware(Arc::new(HeaderMiddleware::new().with_header("x-tenant", "42")))
+            .with_middleware(Arc::new(RedactingMiddleware));
+        assert_eq!(config.middleware.names(), vec!["headers", "redacting"]);
+        model.update_config(config);
+
+        let response = model.generate(&vec![Message::user("Which card?")], None, None).await.unwrap();
+        assert_eq!(response.content, "Card [ACCOUNT] is on file.");
+        let request = &client.requests.lock().unwrap()[0];
+        assert_eq!(request.header("x-tenant"), Some("42"));
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["user"], "tenant-42");
+        assert_eq!(body["model"], "gpt-4o");
+    }
+}
@@ -1,19 +1,89 @@
 //! Model implementations for the SDK.
 //! 
-//! This module contains the abstract Model trait and concrete
-//! implementations for various model providers.
+//! This module contains the abstract Model trait, the HTTP abstraction
+//! providers send requests through, and provider-independent wrappers such
+//! as retries, fallback, pooling and caching. Provider implementations live
+//! in `crate::providers` and are re-exported here under their old paths.
 
 pub mod model;
-pub mod bedrock;
-pub mod openai;
-pub mod anthropic;
-pub mod ollama;
+pub mod http;
+pub mod http_logging;
+pub mod plain_http;
+pub mod stream_decode;
+pub mod signing;
+pub mod middleware;
+pub mod roles;
+pub mod retry;
+pub mod fallback;
+pub mod rate_limit;
+pub mod pool;
+pub mod cache;
+pub mod tool_emulation;
+pub mod tokenizer;
+pub mod image;
+pub mod compare;
+
+// Providers used to live in this module; these shims keep their paths working.
+#[cfg(feature = "openai-compatible")]
+pub use crate::providers::openai_compat;
+#[cfg(feature = "openai")]
+pub use crate::providers::openai;
+#[cfg(feature = "azure-openai")]
+pub use crate::providers::azure_openai;
+#[cfg(feature = "anthropic")]
+pub use crate::providers::anthropic;
+#[cfg(feature = "bedrock")]
+pub use crate::providers::{bedrock, bedrock_converse, bedrock_failover};
+#[cfg(feature = "gemini")]
+pub use crate::providers::gemini;
+#[cfg(feature = "vertex")]
+pub use crate::providers::{google_auth, vertex};
+#[cfg(feature = "ollama")]
+pub use crate::providers::ollama;
+#[cfg(feature = "deepseek")]
+pub use crate::providers::deepseek;
+#[cfg(feature = "xai")]
+pub use crate::providers::xai;
 
 pub use model::Model;
-pub use bedrock::BedrockModel;
-pub use openai::OpenAIModel;
-pub use anthropic::AnthropicModel;
-pub use ollama::OllamaModel;
+pub use http::{HttpBodyStream, HttpClient, HttpRequest, HttpResponse, HttpStreamingResponse, UnconfiguredHttpClient};
+pub use plain_http::PlainHttpClient;
+pub use stream_decode::{LineSplitter, StreamDecoder};
+pub use http_logging::{LoggingHttpClient, RedactionFilter, RotatingFileWriter};
+pub use middleware::{HeaderMiddleware, MiddlewareChain, ModelMiddleware, SignerMiddleware};
+pub use signing::{BearerSigner, RequestSigner, SigV4Signer, SigningHttpClient, TokenProvider};
+pub use roles::{RoleMapping, SystemPromptStrategy};
+pub use retry::{RetryCondition, RetryPolicy, RetryingModel};
+pub use fallback::{FailoverReason, FallbackModel};
+pub use rate_limit::{FairShare, Priority, RateLimitPermit, RateLimiter, SessionWaitStats};
+pub use pool::{ModelPool, PoolMemberStats, PoolStrategy};
+pub use cache::{CachingModel, ModelResponseCache, ResponseCacheBackend, ResponseCacheConfig};
+pub use tool_emulation::ToolEmulatingModel;
+pub use image::{ImageGenerationModel, ImageRequest};
+#[cfg(feature = "openai-compatible")]
+pub use crate::providers::{OpenAICompatibleConfig, OpenAICompatibleModel};
+#[cfg(feature = "openai")]
+pub use crate::providers::{OpenAIImageModel, OpenAIModel};
+#[cfg(feature = "azure-openai")]
+pub use crate::providers::{AzureOpenAIConfig, AzureOpenAIModel};
+#[cfg(feature = "anthropic")]
+pub use crate::providers::AnthropicModel;
+#[cfg(feature = "bedrock")]
+pub use crate::providers::{BedrockImageModel, BedrockInvoker, BedrockModel, ConverseInvoker, RegionFailover};
+#[cfg(feature = "gemini")]
+pub use crate::providers::{GeminiConfig, GeminiModel, HarmBlockThreshold, HarmCategory, SafetySetting};
+#[cfg(feature = "vertex")]
+pub use crate::providers::{ApplicationDefaultCredentials, GoogleCredentials, VertexConfig, VertexModel};
+#[cfg(feature = "ollama")]
+pub use crate::providers::{OllamaModel, OllamaModelInfo};
+#[cfg(feature = "deepseek")]
+pub use crate::providers::DeepSeekModel;
+#[cfg(feature = "xai")]
+pub use crate::providers::GrokModel;
+pub use compare::{format_side_by_side, ComparisonResult, ModelComparison, ModelPrice, DEFAULT_COLUMN_WIDTH};
+pub use tokenizer::{HeuristicTokenizer, TiktokenEncoding, TiktokenTokenizer, Tokenizer, TokenizerRegistry};
+#[cfg(feature = "hf-tokenizers")]
+pub use tokenizer::HuggingFaceTokenizer;
 
 // Re-export commonly used types
 pub use model::{ModelConfig, ModelResponse, ModelStreamResponse};
@@ -5,14 +5,19 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use tokio_stream::Stream;
 
-use crate::types::{Messages, ToolSpec, IndubitablyResult, StreamEvent};
+use super::cache::ResponseCacheConfig;
+use super::middleware::{MiddlewareChain, ModelMiddleware};
+use super::retry::RetryPolicy;
+use super::roles::RoleMapping;
+use crate::types::{Citation, Messages, ReasoningContentBlock, ToolSpec, ToolUse, IndubitablyResult, StreamEvent};
 
 /// Configuration for a model.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelConfig {
     /// The model ID.
     pub model_id: String,
@@ -26,6 +31,21 @@ pub struct ModelConfig {
     pub top_k: Option<u32>,
     /// Whether to enable streaming.
     pub streaming: bool,
+    /// How system prompts map onto the roles the model supports.
+    #[serde(default)]
+    pub role_mapping: RoleMapping,
+    /// How failed calls are retried when the model is wrapped in a `RetryingModel`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// How responses are cached when the model is wrapped in a `CachingModel`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<ResponseCacheConfig>,
+    /// Whether tool schemas are rewritten for strict mode and sent as strict where the provider supports it.
+    #[serde(default)]
+    pub strict_tools: bool,
+    /// The middleware run around each provider request.
+    #[serde(skip)]
+    pub middleware: MiddlewareChain,
     /// Additional configuration options.
     pub extra: HashMap<String, serde_json::Value>,
 }
@@ -39,6 +59,11 @@ impl Default for ModelConfig {
             top_p: Some(1.0),
             top_k: Some(250),
             streaming: false,
+            role_mapping: RoleMapping::default(),
+            retry: None,
+            cache: None,
+            strict_tools: false,
+            middleware: MiddlewareChain::default(),
             extra: HashMap::new(),
         }
     }
@@ -83,6 +108,38 @@ impl ModelConfig {
         self
     }
 
+    /// Set how system prompts map onto the model's roles.
+    pub fn with_role_mapping(mut self, role_mapping: RoleMapping) -> Self {
+        self.role_mapping = role_mapping;
+        self
+    }
+
+    /// Set the retry policy for failed calls.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set how responses are cached.
+    pub fn with_cache(mut self, cache: ResponseCacheConfig) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Enable or disable strict tool schemas.
+    ///
+    /// Tools whose schemas cannot be made strict are sent as before.
+    pub fn with_strict_tools(mut self, strict_tools: bool) -> Self {
+        self.strict_tools = strict_tools;
+        self
+    }
+
+    /// Add a middleware to the end of the chain run around each provider request.
+    pub fn with_middleware(mut self, middleware: Arc<dyn ModelMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
     /// Add extra configuration.
     pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
         self.extra.insert(key.to_string(), value);
@@ -97,12 +154,77 @@ pub struct ModelResponse {
     pub content: String,
     /// Token usage information.
     pub usage: Option<ModelUsage>,
+    /// The tools the model asked to use.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_uses: Vec<ToolUse>,
+    /// The sources the provider cited for the content.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+    /// The reasoning the model produced before answering.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasoning: Vec<ReasoningContentBlock>,
     /// Additional metadata.
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl ModelResponse {
+    /// Create a new text response.
+    pub fn new(content: &str) -> Self {
+        Self {
+            content: content.to_string(),
+            usage: None,
+            tool_uses: Vec::new(),
+            citations: Vec::new(),
+            reasoning: Vec::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Set the token usage.
+    pub fn with_usage(mut self, input_tokens: u32, output_tokens: u32) -> Self {
+        self.usage = Some(ModelUsage {
+            input_tokens,
+            output_tokens,
+            total_tokens: input_tokens + output_tokens,
+            reasoning_tokens: 0,
+        });
+        self
+    }
+
+    /// Set how many of the output tokens were spent on reasoning.
+    pub fn with_reasoning_tokens(mut self, reasoning_tokens: u32) -> Self {
+        if let Some(ref mut usage) = self.usage {
+            usage.reasoning_tokens = reasoning_tokens;
+        }
+        self
+    }
+
+    /// Add reasoning text.
+    pub fn with_reasoning(mut self, text: &str) -> Self {
+        self.reasoning.push(ReasoningContentBlock::new(text));
+        self
+    }
+
+    /// Add a tool use request.
+    pub fn with_tool_use(mut self, tool_use: ToolUse) -> Self {
+        self.tool_uses.push(tool_use);
+        self
+    }
+
+    /// Add a citation.
+    pub fn with_citation(mut self, citation: Citation) -> Self {
+        self.citations.push(citation);
+        self
+    }
+
+    /// Check if the model asked to use any tools.
+    pub fn has_tool_uses(&self) -> bool {
+        !self.tool_uses.is_empty()
+    }
+}
+
 /// Token usage information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModelUsage {
     /// Number of input tokens.
     pub input_tokens: u32,
@@ -110,11 +232,41 @@ pub struct ModelUsage {
     pub output_tokens: u32,
     /// Total number of tokens.
     pub total_tokens: u32,
+    /// Number of output tokens spent on reasoning, included in `output_tokens`.
+    #[serde(default)]
+    pub reasoning_tokens: u32,
+}
+
+impl ModelUsage {
+    /// Add another call's usage to this one.
+    pub fn accumulate(&mut self, other: &ModelUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.total_tokens += other.total_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+    }
 }
 
 /// Stream response from a model.
 pub type ModelStreamResponse = Pin<Box<dyn Stream<Item = IndubitablyResult<StreamEvent>> + Send>>;
 
+/// Replay a complete response as a stream, for providers without incremental streaming.
+pub fn response_stream(response: ModelResponse) -> ModelStreamResponse {
+    let mut events = vec![StreamEvent::message_start()];
+    if !response.content.is_empty() {
+        events.push(StreamEvent::content_block_start(vec![crate::types::streaming::StreamContent::text(
+            response.content,
+        )]));
+        events.push(StreamEvent::content_block_stop());
+    }
+    for tool_use in response.tool_uses {
+        events.push(StreamEvent::tool_use_start(tool_use));
+        events.push(StreamEvent::tool_use_stop());
+    }
+    events.push(StreamEvent::message_stop());
+    Box::pin(tokio_stream::iter(events.into_iter().map(Ok)))
+}
+
 /// The core model trait that all model providers must implement.
 #[async_trait]
 pub trait Model: Send + Sync {
@@ -156,6 +308,11 @@ pub trait Model: Send + Sync {
         self.config().streaming
     }
 
+    /// Map messages and the system prompt onto the roles the model supports.
+    fn prepare_messages(&self, messages: &Messages, system_prompt: Option<&str>) -> (Messages, Option<String>) {
+        self.config().role_mapping.apply(messages, system_prompt)
+    }
+
     /// Get the model ID.
     fn model_id(&self) -> &str {
         &self.config().model_id
@@ -176,19 +333,27 @@ pub trait Model: Send + Sync {
 #[derive(Debug, Clone)]
 pub struct MockModel {
     config: ModelConfig,
+    responses: Arc<Mutex<VecDeque<ModelResponse>>>,
 }
 
 impl MockModel {
     /// Create a new mock model.
     pub fn new() -> Self {
-        Self {
-            config: ModelConfig::new("mock"),
-        }
+        Self::with_config(ModelConfig::new("mock"))
     }
 
     /// Create a new mock model with the given configuration.
     pub fn with_config(config: ModelConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            responses: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Queue responses returned in order before falling back to the default response.
+    pub fn with_responses(self, responses: Vec<ModelResponse>) -> Self {
+        self.responses.lock().unwrap_or_else(|e| e.into_inner()).extend(responses);
+        self
     }
 }
 
@@ -212,13 +377,21 @@ impl Model for MockModel {
         _tool_specs: Option<&[ToolSpec]>,
         _system_prompt: Option<&str>,
     ) -> IndubitablyResult<ModelResponse> {
+        if let Some(response) = self.responses.lock().unwrap_or_else(|e| e.into_inner()).pop_front() {
+            return Ok(response);
+        }
+
         Ok(ModelResponse {
             content: "This is a mock response from the mock model.".to_string(),
             usage: Some(ModelUsage {
                 input_tokens: 10,
                 output_tokens: 15,
                 total_tokens: 25,
+                reasoning_tokens: 0,
             }),
+            tool_uses: Vec::new(),
+            citations: Vec::new(),
+            reasoning: Vec::new(),
             metadata: HashMap::new(),
         })
     }
@@ -1,209 +0,0 @@
-//! OpenAI model implementation for the SDK.
-//! 
-//! This module provides integration with OpenAI's API for
-//! accessing various foundation models.
-
-use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-use super::model::{Model, ModelConfig, ModelResponse, ModelUsage, ModelStreamResponse};
-use crate::types::{Messages, ToolSpec, StreamEvent, IndubitablyResult};
-
-/// Default OpenAI model ID.
-pub const DEFAULT_OPENAI_MODEL_ID: &str = "gpt-4";
-
-/// Configuration specific to OpenAI models.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OpenAIConfig {
-    /// The OpenAI API key.
-    pub api_key: String,
-    /// The model ID to use.
-    pub model_id: String,
-    /// The temperature for generation.
-    pub temperature: Option<f32>,
-    /// The maximum number of tokens to generate.
-    pub max_tokens: Option<u32>,
-    /// The top-p value for nucleus sampling.
-    pub top_p: Option<f32>,
-    /// Whether to enable streaming.
-    pub streaming: Option<bool>,
-    /// Additional OpenAI-specific configuration.
-    pub extra: HashMap<String, serde_json::Value>,
-}
-
-impl Default for OpenAIConfig {
-    fn default() -> Self {
-        Self {
-            api_key: String::new(),
-            model_id: DEFAULT_OPENAI_MODEL_ID.to_string(),
-            temperature: Some(0.7),
-            max_tokens: Some(4096),
-            top_p: Some(1.0),
-            streaming: Some(false),
-            extra: HashMap::new(),
-        }
-    }
-}
-
-impl OpenAIConfig {
-    /// Create a new OpenAI configuration.
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    /// Set the API key.
-    pub fn with_api_key(mut self, api_key: &str) -> Self {
-        self.api_key = api_key.to_string();
-        self
-    }
-
-    /// Set the model ID.
-    pub fn with_model_id(mut self, model_id: &str) -> Self {
-        self.model_id = model_id.to_string();
-        self
-    }
-
-    /// Set the temperature.
-    pub fn with_temperature(mut self, temperature: f32) -> Self {
-        self.temperature = Some(temperature);
-        self
-    }
-
-    /// Set the maximum tokens.
-    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
-        self.max_tokens = Some(max_tokens);
-        self
-    }
-
-    /// Set the top-p value.
-    pub fn with_top_p(mut self, top_p: f32) -> Self {
-        self.top_p = Some(top_p);
-        self
-    }
-
-    /// Enable or disable streaming.
-    pub fn with_streaming(mut self, streaming: bool) -> Self {
-        self.streaming = Some(streaming);
-        self
-    }
-
-    /// Add extra configuration.
-    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
-        self.extra.insert(key.to_string(), value);
-        self
-    }
-}
-
-/// The OpenAI model implementation.
-#[derive(Debug)]
-pub struct OpenAIModel {
-    config: ModelConfig,
-    openai_config: OpenAIConfig,
-}
-
-impl OpenAIModel {
-    /// Create a new OpenAI model.
-    pub fn new() -> Self {
-        Self {
-            config: ModelConfig::default(),
-            openai_config: OpenAIConfig::default(),
-        }
-    }
-
-    /// Create a new OpenAI model with the given configuration.
-    pub fn with_config(openai_config: OpenAIConfig) -> Self {
-        Self {
-            config: ModelConfig::new(&openai_config.model_id)
-                .with_temperature(openai_config.temperature.unwrap_or(0.7))
-                .with_max_tokens(openai_config.max_tokens.unwrap_or(4096))
-                .with_top_p(openai_config.top_p.unwrap_or(1.0))
-                .with_streaming(openai_config.streaming.unwrap_or(false)),
-            openai_config,
-        }
-    }
-}
-
-#[async_trait]
-impl Model for OpenAIModel {
-    fn config(&self) -> &ModelConfig {
-        &self.config
-    }
-
-    fn update_config(&mut self, config: ModelConfig) {
-        self.config = config;
-    }
-
-    fn config_mut(&mut self) -> &mut ModelConfig {
-        &mut self.config
-    }
-
-    async fn generate(
-        &self,
-        _messages: &Messages,
-        _tool_specs: Option<&[ToolSpec]>,
-        _system_prompt: Option<&str>,
-    ) -> IndubitablyResult<ModelResponse> {
-        // TODO: Implement actual OpenAI API integration
-        Ok(ModelResponse {
-            content: "This is a mock response from OpenAI. Actual integration coming soon.".to_string(),
-            usage: Some(ModelUsage {
-                input_tokens: 10,
-                output_tokens: 15,
-                total_tokens: 25,
-            }),
-            metadata: HashMap::new(),
-        })
-    }
-
-    async fn stream(
-        &self,
-        _messages: &Messages,
-        _tool_specs: Option<&[ToolSpec]>,
-        _system_prompt: Option<&str>,
-    ) -> IndubitablyResult<ModelStreamResponse> {
-        // TODO: Implement actual OpenAI streaming
-        use tokio_stream::wrappers::ReceiverStream;
-        use tokio::sync::mpsc;
-
-        let (tx, rx) = mpsc::channel(100);
-        
-        tokio::spawn(async move {
-            let events = vec![
-                StreamEvent::message_start(),
-                StreamEvent::content_block_start(vec![crate::types::streaming::StreamContent::text("Mock OpenAI")]),
-                StreamEvent::content_block_delta(vec![crate::types::streaming::StreamContent::text(" streaming")]),
-                StreamEvent::content_block_stop(),
-                StreamEvent::message_stop(),
-            ];
-
-            for event in events {
-                if tx.send(Ok(event)).await.is_err() {
-                    break;
-                }
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            }
-        });
-
-        Ok(Box::pin(ReceiverStream::new(rx)))
-    }
-
-    async fn structured_output(
-        &self,
-        _output_model: &str,
-        _messages: &Messages,
-        _system_prompt: Option<&str>,
-    ) -> IndubitablyResult<serde_json::Value> {
-        Err(crate::types::IndubitablyError::ModelError(
-            crate::types::ModelError::InvalidResponseFormat(
-                "OpenAI model does not support structured output yet".to_string(),
-            ),
-        ))
-    }
-}
-
-impl Default for OpenAIModel {
-    fn default() -> Self {
-        Self::new()
-    }
-}
@@ -0,0 +1,342 @@
+//! Plain HTTP client for the SDK.
+//! 
+//! This module provides `PlainHttpClient`, an HTTP/1.1 client over a tokio
+//! TCP connection for servers reached without TLS, such as a local Ollama
+//! or vLLM server. Each request opens a connection that the server closes
+//! after responding; bodies framed by `Content-Length`, chunked encoding or
+//! the end of the connection are all read, and can be streamed as they
+//! arrive. `https://` URLs are refused, so
+//! hosted providers still need a TLS-capable client.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::http::{HttpClient, HttpRequest, HttpResponse, HttpStreamingResponse, UnconfiguredHttpClient};
+use crate::types::{IndubitablyError, IndubitablyResult, ModelError};
+
+/// The default time allowed for a whole request, generous enough for slow local generation.
+pub const DEFAULT_PLAIN_HTTP_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The largest response the client reads.
+pub const MAX_PLAIN_HTTP_RESPONSE_BYTES: usize = 64 * 1024 * 1024;
+
+/// The most bytes read from the connection at once.
+const READ_CHUNK_BYTES: usize = 8192;
+
+/// An HTTP/1.1 client for `http://` URLs.
+#[derive(Debug, Clone, Copy)]
+pub struct PlainHttpClient {
+    timeout: Duration,
+}
+
+impl Default for PlainHttpClient {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_PLAIN_HTTP_TIMEOUT,
+        }
+    }
+}
+
+impl PlainHttpClient {
+    /// Create a client with the default timeout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time allowed for a whole request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Connect and write the request.
+    async fn connect(&self, request: &HttpRequest) -> IndubitablyResult<TcpStream> {
+        let (authority, path) = split_url(&request.url)?;
+        let address = if authority.contains(':') { authority.to_string() } else { format!("{}:80", authority) };
+        let mut stream = TcpStream::connect(&address).await.map_err(|e| request_failed(&request.url, e))?;
+
+        let mut head = format!("{} {} HTTP/1.1\r\nhost: {}\r\nconnection: close\r\n", request.method, path, authority);
+        for (name, value) in &request.headers {
+            if !["host", "connection", "content-length"].contains(&name.to_ascii_lowercase().as_str()) {
+                head.push_str(&format!("{}: {}\r\n", name, value));
+            }
+        }
+        head.push_str(&format!("content-length: {}\r\n\r\n", request.body.len()));
+        stream.write_all(head.as_bytes()).await.map_err(|e| request_failed(&request.url, e))?;
+        stream.write_all(&request.body).await.map_err(|e| request_failed(&request.url, e))?;
+        Ok(stream)
+    }
+
+    /// Send the request and read the response head, leaving a task to read the body.
+    async fn open(&self, request: &HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+        let mut stream = self.connect(request).await?;
+        let mut raw = Vec::new();
+        let split = loop {
+            if let Some(split) = raw.windows(4).position(|window| window == b"\r\n\r\n") {
+                break split;
+            }
+            if raw.len() > MAX_PLAIN_HTTP_RESPONSE_BYTES {
+                return Err(invalid_response("missing end of headers"));
+            }
+            let mut chunk = [0; READ_CHUNK_BYTES];
+            let read = stream.read(&mut chunk).await.map_err(|e| request_failed(&request.url, e))?;
+            if read == 0 {
+                return Err(invalid_response("missing end of headers"));
+            }
+            raw.extend_from_slice(&chunk[..read]);
+        };
+        let (status, headers) = parse_head(&raw[..split])?;
+        let header = |name: &str| headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+        let framing = if header("transfer-encoding").is_some_and(|value| value.eq_ignore_ascii_case("chunked")) {
+            BodyFraming::Chunked
+        } else if let Some(length) = header("content-length").and_then(|value| value.parse::<usize>().ok()) {
+            BodyFraming::Length(length)
+        } else {
+            BodyFraming::UntilClose
+        };
+
+        let (sender, receiver) = mpsc::channel(16);
+        let body = raw.split_off(split + 4);
+        tokio::spawn(read_body(stream, body, framing, self.timeout, request.url.clone(), sender));
+        Ok(HttpStreamingResponse {
+            status,
+            headers,
+            body: Box::pin(ReceiverStream::new(receiver)),
+        })
+    }
+}
+
+#[async_trait]
+impl HttpClient for PlainHttpClient {
+    async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+        let exchange = async { self.open(&request).await?.collect().await };
+        tokio::time::timeout(self.timeout, exchange).await.map_err(|_| timed_out(&request, self.timeout))?
+    }
+
+    /// Send a request, returning once the response headers arrive.
+    ///
+    /// The timeout bounds reaching the headers and then each read of the
+    /// body, so a stream may run for as long as the server keeps sending.
+    async fn send_streaming(&self, request: HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+        tokio::time::timeout(self.timeout, self.open(&request)).await.map_err(|_| timed_out(&request, self.timeout))?
+    }
+}
+
+/// Get the client a provider uses until one is configured.
+///
+/// Servers reached over plain HTTP, such as local ones, get a `PlainHttpClient`;
+/// any other base URL gets an `UnconfiguredHttpClient` that fails every request.
+pub fn default_client(provider: &str, base_url: &str) -> Arc<dyn HttpClient> {
+    if base_url.starts_with("http://") {
+        Arc::new(PlainHttpClient::new())
+    } else {
+        Arc::new(UnconfiguredHttpClient::new(provider))
+    }
+}
+
+/// Split an `http://` URL into its authority and request target.
+fn split_url(url: &str) -> IndubitablyResult<(&str, &str)> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        IndubitablyError::ModelError(ModelError::InvalidConfiguration(format!(
+            "PlainHttpClient only sends http:// requests, not {}; configure a TLS-capable client",
+            url
+        )))
+    })?;
+    let end = rest.find(['/', '?', '#', '\\']).unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(end);
+    if authority.is_empty() || authority.contains('@') || path.starts_with('\\') {
+        return Err(IndubitablyError::ModelError(ModelError::InvalidConfiguration(format!("Invalid URL: {}", url))));
+    }
+    let path = path.split('#').next().unwrap_or_default();
+    Ok((authority, if path.is_empty() { "/" } else { path }))
+}
+
+/// Parse the status line and headers of a response.
+fn parse_head(raw: &[u8]) -> IndubitablyResult<(u16, Vec<(String, String)>)> {
+    let head = String::from_utf8_lossy(raw);
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| invalid_response("malformed status line"))?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    Ok((status, headers))
+}
+
+/// How the end of a response body is marked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyFraming {
+    /// Chunked transfer encoding, ending with an empty chunk.
+    Chunked,
+    /// A `Content-Length`, holding the bytes still to read.
+    Length(usize),
+    /// The server closing the connection.
+    UntilClose,
+}
+
+impl BodyFraming {
+    /// Take the decoded body bytes available in `raw`, and whether the body is complete.
+    fn take(&mut self, raw: &mut Vec<u8>) -> IndubitablyResult<(Vec<u8>, bool)> {
+        match self {
+            Self::Length(remaining) => {
+                let taken: Vec<u8> = raw.drain(..(*remaining).min(raw.len())).collect();
+                *remaining -= taken.len();
+                Ok((taken, *remaining == 0))
+            }
+            Self::UntilClose => Ok((std::mem::take(raw), false)),
+            Self::Chunked => {
+                let mut decoded = Vec::new();
+                loop {
+                    let Some(line_end) = raw.windows(2).position(|window| window == b"\r\n") else {
+                        return Ok((decoded, false));
+                    };
+                    let size_line = String::from_utf8_lossy(&raw[..line_end]);
+                    let size = usize::from_str_radix(size_line.split(';').next().unwrap_or_default().trim(), 16)
+                        .map_err(|_| invalid_response("malformed chunk size"))?;
+                    if size == 0 {
+                        raw.clear();
+                        return Ok((decoded, true));
+                    }
+                    if raw.len() < line_end + 2 + size + 2 {
+                        return Ok((decoded, false));
+                    }
+                    decoded.extend_from_slice(&raw[line_end + 2..line_end + 2 + size]);
+                    raw.drain(..line_end + 2 + size + 2);
+                }
+            }
+        }
+    }
+}
+
+/// Read a response body from the connection, sending its decoded bytes as they arrive.
+///
+/// Stops once the body is complete, on an error, or when the receiver is dropped.
+async fn read_body(
+    mut stream: TcpStream,
+    mut raw: Vec<u8>,
+    mut framing: BodyFraming,
+    timeout: Duration,
+    url: String,
+    sender: mpsc::Sender<IndubitablyResult<Vec<u8>>>,
+) {
+    let mut received = raw.len();
+    loop {
+        let (body, complete) = match framing.take(&mut raw) {
+            Ok(taken) => taken,
+            Err(e) => {
+                let _ = sender.send(Err(e)).await;
+                return;
+            }
+        };
+        if !body.is_empty() && sender.send(Ok(body)).await.is_err() || complete {
+            return;
+        }
+        let mut chunk = [0; READ_CHUNK_BYTES];
+        let read = match tokio::time::timeout(timeout, stream.read(&mut chunk)).await {
+            Ok(Ok(0)) if framing == BodyFraming::UntilClose => return,
+            Ok(Ok(0)) => Err(invalid_response("connection closed before the end of the body")),
+            Ok(Ok(read)) if received + read > MAX_PLAIN_HTTP_RESPONSE_BYTES => {
+                Err(invalid_response(&format!("response exceeds {} bytes", MAX_PLAIN_HTTP_RESPONSE_BYTES)))
+            }
+            Ok(Ok(read)) => Ok(read),
+            Ok(Err(e)) => Err(request_failed(&url, e)),
+            Err(_) => Err(invalid_response(&format!("no data for {:?}", timeout))),
+        };
+        match read {
+            Ok(read) => {
+                received += read;
+                raw.extend_from_slice(&chunk[..read]);
+            }
+            Err(e) => {
+                let _ = sender.send(Err(e)).await;
+                return;
+            }
+        }
+    }
+}
+
+fn timed_out(request: &HttpRequest, timeout: Duration) -> IndubitablyError {
+    IndubitablyError::ModelError(ModelError::RequestFailed(format!(
+        "{} {} timed out after {:?}",
+        request.method, request.url, timeout
+    )))
+}
+
+fn request_failed(url: &str, error: std::io::Error) -> IndubitablyError {
+    IndubitablyError::ModelError(ModelError::RequestFailed(format!("Request to {} failed: {}", url, error)))
+}
+
+fn invalid_response(reason: &str) -> IndubitablyError {
+    IndubitablyError::ModelError(ModelError::InvalidResponseFormat(format!("Invalid HTTP response: {}", reason)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_plain_http_client_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let read = socket.read(&mut request).await.unwrap();
+            let reply = b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\n\r\n\
+                5\r\n{\"ok\"\r\n6\r\n:true}\r\n0\r\n\r\n";
+            socket.write_all(reply).await.unwrap();
+            String::from_utf8_lossy(&request[..read]).to_string()
+        });
+
+        let client = PlainHttpClient::new().with_timeout(Duration::from_secs(5));
+        let url = format!("http://{}/api/chat?x=1", address);
+        let response = client.send(HttpRequest::post(&url).with_body(b"{}".to_vec())).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text(), "{\"ok\":true}");
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /api/chat?x=1 HTTP/1.1\r\n"));
+        assert!(request.contains("content-length: 2\r\n") && request.ends_with("\r\n\r\n{}"));
+
+        assert!(client.send(HttpRequest::get("https://api.example.com/")).await.is_err());
+        assert!(split_url("http://evil.com\\@localhost/").is_err());
+        assert!(split_url("http://user@localhost/").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_plain_http_client_streams_chunks_as_they_arrive() {
+        use tokio_stream::StreamExt;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            assert!(socket.read(&mut request).await.unwrap() > 0);
+            socket.write_all(b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n6\r\ndata: \r\n").await.unwrap();
+            released.await.unwrap();
+            socket.write_all(b"3\r\n{}\n\r\n0\r\n\r\n").await.unwrap();
+        });
+
+        let client = PlainHttpClient::new().with_timeout(Duration::from_secs(5));
+        let url = format!("http://{}/stream", address);
+        let mut response = client.send_streaming(HttpRequest::post(&url)).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.next().await.unwrap().unwrap(), b"data: ");
+        release.send(()).unwrap();
+        assert_eq!(response.body.next().await.unwrap().unwrap(), b"{}\n");
+        assert!(response.body.next().await.is_none());
+    }
+}
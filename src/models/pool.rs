@@ -0,0 +1,392 @@
+//! Load balancing across API keys and regions.
+//! 
+//! This module provides `ModelPool`, which spreads calls over several
+//! instances of the same provider and model, each usually configured with
+//! its own API key or region. Calls are routed round-robin or to the member
+//! with the fewest calls in flight. A member that is throttled or out of
+//! quota cools down and is used only after the healthy members, and the
+//! throttled call is retried on the next member. Requests, throttles and
+//! failures are tracked per member.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, ToolSpec};
+
+/// The default time a throttled member is used only after the healthy ones.
+pub const DEFAULT_MEMBER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// How a pool picks the member for each call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolStrategy {
+    /// Take the members in turn.
+    #[default]
+    RoundRobin,
+    /// Take the member with the fewest calls in flight, then the fewest calls made.
+    LeastLoaded,
+}
+
+/// The counters of one pool member.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolMemberStats {
+    /// The member name, such as the key or region it uses.
+    pub name: String,
+    /// The calls routed to the member.
+    pub requests: u64,
+    /// The calls the member's provider throttled or refused for quota.
+    pub throttles: u64,
+    /// The calls that failed for other reasons.
+    pub failures: u64,
+    /// The calls in flight.
+    pub in_flight: usize,
+    /// Whether the member is cooling down after a throttle.
+    pub cooling: bool,
+}
+
+/// Check whether an error means the member's rate limit or quota was hit.
+fn is_rate_limited(error: &IndubitablyError) -> bool {
+    matches!(
+        error,
+        IndubitablyError::ModelError(ModelError::ModelThrottled(_) | ModelError::QuotaExceeded(_))
+    )
+}
+
+/// A model in a pool and its load.
+struct PoolMember {
+    name: String,
+    model: Box<dyn Model>,
+    in_flight: AtomicUsize,
+    requests: AtomicU64,
+    throttles: AtomicU64,
+    failures: AtomicU64,
+    cooling_until: Mutex<Option<Instant>>,
+}
+
+/// Counts a call as in flight until dropped.
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl PoolMember {
+    fn cooling_until(&self) -> Option<Instant> {
+        self.cooling_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .filter(|until| *until > Instant::now())
+    }
+
+    /// Run a call on the member, tracking its load and outcome.
+    async fn call<T>(
+        &self,
+        cooldown: Duration,
+        call: impl std::future::Future<Output = IndubitablyResult<T>>,
+    ) -> IndubitablyResult<T> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let _in_flight = InFlight(&self.in_flight);
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let result = call.await;
+        match &result {
+            Ok(_) => *self.cooling_until.lock().unwrap_or_else(|e| e.into_inner()) = None,
+            Err(e) if is_rate_limited(e) => {
+                self.throttles.fetch_add(1, Ordering::SeqCst);
+                *self.cooling_until.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + cooldown);
+                tracing::warn!(
+                    "member=<{}>, cooldown_ms=<{}>, error=<{}> | pool member throttled",
+                    self.name,
+                    cooldown.as_millis(),
+                    e
+                );
+            }
+            Err(_) => {
+                self.failures.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        result
+    }
+}
+
+/// A model that balances calls across several instances of the same model.
+///
+/// The first member's config is the one exposed; `update_config` applies to
+/// every member, while `config_mut` changes the first member only.
+pub struct ModelPool {
+    members: Vec<PoolMember>,
+    strategy: PoolStrategy,
+    cooldown: Duration,
+    cursor: AtomicUsize,
+}
+
+impl std::fmt::Debug for ModelPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelPool")
+            .field("members", &self.members.iter().map(|member| &member.name).collect::<Vec<_>>())
+            .field("strategy", &self.strategy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ModelPool {
+    /// Create a pool with its first member.
+    pub fn new(name: &str, model: Box<dyn Model>) -> Self {
+        Self {
+            members: Vec::new(),
+            strategy: PoolStrategy::default(),
+            cooldown: DEFAULT_MEMBER_COOLDOWN,
+            cursor: AtomicUsize::new(0),
+        }
+        .with_member(name, model)
+    }
+
+    /// Add a member, named after the key or region it uses.
+    pub fn with_member(mut self, name: &str, model: Box<dyn Model>) -> Self {
+        self.members.push(PoolMember {
+            name: name.to_string(),
+            model,
+            in_flight: AtomicUsize::new(0),
+            requests: AtomicU64::new(0),
+            throttles: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            cooling_until: Mutex::new(None),
+        });
+        self
+    }
+
+    /// Set how members are picked.
+    pub fn with_strategy(mut self, strategy: PoolStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set how long a throttled member is used only after the healthy ones.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Get the number of members.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Check whether the pool has no members.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Get the counters of each member, in the order they were added.
+    pub fn stats(&self) -> Vec<PoolMemberStats> {
+        self.members
+            .iter()
+            .map(|member| PoolMemberStats {
+                name: member.name.clone(),
+                requests: member.requests.load(Ordering::SeqCst),
+                throttles: member.throttles.load(Ordering::SeqCst),
+                failures: member.failures.load(Ordering::SeqCst),
+                in_flight: member.in_flight.load(Ordering::SeqCst),
+                cooling: member.cooling_until().is_some(),
+            })
+            .collect()
+    }
+
+    /// Order the members for a call: available ones by strategy, then cooling ones by recovery time.
+    fn order(&self) -> Vec<usize> {
+        let start = self.cursor.fetch_add(1, Ordering::SeqCst);
+        let count = self.members.len();
+        let mut available: Vec<usize> = (0..count).map(|offset| (start + offset) % count.max(1)).collect();
+        let mut cooling: Vec<(Instant, usize)> = Vec::new();
+        available.retain(|index| match self.members[*index].cooling_until() {
+            Some(until) => {
+                cooling.push((until, *index));
+                false
+            }
+            None => true,
+        });
+        if self.strategy == PoolStrategy::LeastLoaded {
+            available.sort_by_key(|index| {
+                let member = &self.members[*index];
+                (member.in_flight.load(Ordering::SeqCst), member.requests.load(Ordering::SeqCst))
+            });
+        }
+        cooling.sort();
+        available.into_iter().chain(cooling.into_iter().map(|(_, index)| index)).collect()
+    }
+
+    fn no_members() -> IndubitablyError {
+        ModelError::InvalidConfiguration("Model pool has no members".to_string()).into()
+    }
+}
+
+#[async_trait]
+impl Model for ModelPool {
+    fn config(&self) -> &ModelConfig {
+        self.members[0].model.config()
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        for member in &mut self.members {
+            member.model.update_config(config.clone());
+        }
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        self.members[0].model.config_mut()
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let order = self.order();
+        for (position, index) in order.iter().enumerate() {
+            let member = &self.members[*index];
+            match member.call(self.cooldown, member.model.generate(messages, tool_specs, system_prompt)).await {
+                Err(e) if is_rate_limited(&e) && position + 1 < order.len() => continue,
+                result => return result,
+            }
+        }
+        Err(Self::no_members())
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let order = self.order();
+        for (position, index) in order.iter().enumerate() {
+            let member = &self.members[*index];
+            match member.call(self.cooldown, member.model.stream(messages, tool_specs, system_prompt)).await {
+                Err(e) if is_rate_limited(&e) && position + 1 < order.len() => continue,
+                result => return result,
+            }
+        }
+        Err(Self::no_members())
+    }
+
+    async fn structured_output(
+        &self,
+        output_model: &str,
+        messages: &Messages,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        let order = self.order();
+        for (position, index) in order.iter().enumerate() {
+            let member = &self.members[*index];
+            let call = member.model.structured_output(output_model, messages, system_prompt);
+            match member.call(self.cooldown, call).await {
+                Err(e) if is_rate_limited(&e) && position + 1 < order.len() => continue,
+                result => return result,
+            }
+        }
+        Err(Self::no_members())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::MockModel;
+    use crate::types::Message;
+
+    /// Answers with its key name, or throttles every call.
+    struct KeyedModel {
+        config: ModelConfig,
+        key: &'static str,
+        throttled: bool,
+    }
+
+    #[async_trait]
+    impl Model for KeyedModel {
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn update_config(&mut self, config: ModelConfig) {
+            self.config = config;
+        }
+
+        fn config_mut(&mut self) -> &mut ModelConfig {
+            &mut self.config
+        }
+
+        async fn generate(
+            &self,
+            _messages: &Messages,
+            _tool_specs: Option<&[ToolSpec]>,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelResponse> {
+            if self.throttled {
+                return Err(ModelError::ModelThrottled(format!("{} hit its rate limit", self.key)).into());
+            }
+            Ok(ModelResponse::new(self.key))
+        }
+
+        async fn stream(
+            &self,
+            messages: &Messages,
+            tool_specs: Option<&[ToolSpec]>,
+            system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelStreamResponse> {
+            let response = self.generate(messages, tool_specs, system_prompt).await?;
+            Ok(super::super::model::response_stream(response))
+        }
+
+        async fn structured_output(
+            &self,
+            _output_model: &str,
+            _messages: &Messages,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<serde_json::Value> {
+            Ok(serde_json::Value::Null)
+        }
+    }
+
+    fn keyed(key: &'static str, throttled: bool) -> Box<dyn Model> {
+        Box::new(KeyedModel { config: ModelConfig::new("gpt-4o"), key, throttled })
+    }
+
+    #[tokio::test]
+    async fn test_pool_balances_and_skips_throttled_keys() {
+        let messages = vec![Message::user("Hi")];
+        let pool = ModelPool::new("key-a", keyed("a", false)).with_member("key-b", keyed("b", false));
+        let mut answers = Vec::new();
+        for _ in 0..4 {
+            answers.push(pool.generate(&messages, None, None).await.unwrap().content);
+        }
+        assert_eq!(answers, vec!["a", "b", "a", "b"]);
+
+        // A throttled key cools down and its call is retried on the next key
+        let pool = ModelPool::new("key-a", keyed("a", true))
+            .with_member("key-b", keyed("b", false))
+            .with_strategy(PoolStrategy::LeastLoaded);
+        for _ in 0..3 {
+            assert_eq!(pool.generate(&messages, None, None).await.unwrap().content, "b");
+        }
+        let stats = pool.stats();
+        assert_eq!((stats[0].requests, stats[0].throttles, stats[0].cooling), (1, 1, true));
+        assert_eq!((stats[1].requests, stats[1].in_flight), (3, 0));
+
+        // Once every key is throttled the last error is returned
+        let pool = ModelPool::new("key-a", keyed("a", true)).with_member("key-b", keyed("b", true));
+        let error = pool.generate(&messages, None, None).await.unwrap_err();
+        assert!(is_rate_limited(&error));
+        assert!(pool.stats().iter().all(|member| member.cooling));
+
+        let mut pool = ModelPool::new("key-a", Box::new(MockModel::new())).with_member("key-b", Box::new(MockModel::new()));
+        pool.update_config(ModelConfig::new("gpt-4o-mini"));
+        assert!(pool.members.iter().all(|member| member.model.config().model_id == "gpt-4o-mini"));
+    }
+}
@@ -0,0 +1,516 @@
+//! Priority scheduling for shared model rate limits.
+//! 
+//! This module provides `RateLimiter`, which caps the model calls in flight
+//! across every agent sharing it. Calls that find no free slot wait in a
+//! priority queue, so interactive runs are served before background batch
+//! work. When the queue is full, a new call preempts the lowest-priority
+//! queued call, which fails as throttled; calls already in flight are never
+//! interrupted. A call queued for longer than the starvation timeout is
+//! served ahead of every priority, so background work always progresses.
+//! 
+//! With fair sharing enabled, each session calling through the limiter has a
+//! token bucket, and among queued calls of the same priority those from the
+//! sessions with the most tokens left are served first, so one busy session
+//! cannot starve the other sessions of its tenant.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::types::{IndubitablyError, IndubitablyResult, ModelError};
+
+/// The default time after which a queued call is served ahead of every priority.
+pub const DEFAULT_STARVATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default number of calls a session may make in a burst under fair sharing.
+pub const DEFAULT_FAIR_SHARE_BURST: f64 = 4.0;
+
+/// The bucket shared by calls made without a session.
+const UNNAMED_SESSION: &str = "";
+
+/// The scheduling priority of a run's model calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// Batch work that can wait, such as evaluations or backfills.
+    Background,
+    /// Work a user is waiting on.
+    #[default]
+    Interactive,
+}
+
+/// Token buckets giving each session of a tenant a comparable share of a limiter's slots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FairShare {
+    /// The tokens each session regains per second, one token being one call.
+    pub refill_per_second: f64,
+    /// The most tokens a session can save up, and the debt it can run into.
+    pub burst: f64,
+}
+
+impl FairShare {
+    /// Create fair sharing where each session regains the given calls per second.
+    pub fn new(refill_per_second: f64) -> Self {
+        Self {
+            refill_per_second: refill_per_second.max(0.0),
+            burst: DEFAULT_FAIR_SHARE_BURST,
+        }
+    }
+
+    /// Set the most tokens a session can save up.
+    pub fn with_burst(mut self, burst: f64) -> Self {
+        self.burst = burst.max(1.0);
+        self
+    }
+}
+
+/// The time a session's calls waited for a slot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionWaitStats {
+    /// The calls granted a slot.
+    pub calls: u64,
+    /// The total time the calls waited.
+    pub total_wait: Duration,
+    /// The longest time a call waited.
+    pub max_wait: Duration,
+}
+
+impl SessionWaitStats {
+    /// Get the mean time a call waited.
+    pub fn mean_wait(&self) -> Duration {
+        match self.calls {
+            0 => Duration::ZERO,
+            calls => self.total_wait / calls as u32,
+        }
+    }
+}
+
+/// A session's fair-share tokens as of the last update.
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A call waiting for a slot.
+struct Waiter {
+    priority: Priority,
+    session: String,
+    enqueued: Instant,
+    sequence: u64,
+    grant: oneshot::Sender<IndubitablyResult<Duration>>,
+}
+
+impl Waiter {
+    fn is_starved(&self, starvation_timeout: Duration) -> bool {
+        self.enqueued.elapsed() >= starvation_timeout
+    }
+}
+
+/// The scheduling rank: starved calls first, then by priority, then by session tokens, then oldest first.
+type Rank = (bool, Priority, i64, std::cmp::Reverse<u64>);
+
+/// The state shared by clones of a limiter and its permits.
+struct LimiterState {
+    max_in_flight: usize,
+    max_queued: Option<usize>,
+    starvation_timeout: Duration,
+    fair_share: Option<FairShare>,
+    in_flight: usize,
+    queue: Vec<Waiter>,
+    next_sequence: u64,
+    buckets: HashMap<String, Bucket>,
+    waits: BTreeMap<String, SessionWaitStats>,
+}
+
+impl LimiterState {
+    /// Get a session's tokens, refilled up to now; sessions without a bucket have a full one.
+    fn tokens(&self, session: &str) -> f64 {
+        let Some(fair_share) = self.fair_share else {
+            return 0.0;
+        };
+        match self.buckets.get(session) {
+            Some(bucket) => (bucket.tokens + bucket.updated.elapsed().as_secs_f64() * fair_share.refill_per_second)
+                .min(fair_share.burst),
+            None => fair_share.burst,
+        }
+    }
+
+    fn rank(&self, waiter: &Waiter) -> Rank {
+        (
+            waiter.is_starved(self.starvation_timeout),
+            waiter.priority,
+            // Whole tokens only, so sessions with similar usage are served in arrival order
+            self.tokens(&waiter.session).floor() as i64,
+            std::cmp::Reverse(waiter.sequence),
+        )
+    }
+
+    fn ranked(&self, pick: fn(Rank, Rank) -> bool) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (index, waiter) in self.queue.iter().enumerate() {
+            if best.is_none_or(|best| pick(self.rank(waiter), self.rank(&self.queue[best]))) {
+                best = Some(index);
+            }
+        }
+        best
+    }
+
+    /// Take a slot for a session, spending one of its tokens and recording its wait.
+    fn take_slot(&mut self, session: &str, waited: Duration) {
+        self.in_flight += 1;
+        if let Some(fair_share) = self.fair_share {
+            let tokens = self.tokens(session) - 1.0;
+            let now = Instant::now();
+            self.buckets.insert(session.to_string(), Bucket { tokens: tokens.max(-fair_share.burst), updated: now });
+            // Full buckets carry no information, so idle sessions do not accumulate
+            let refill = fair_share.refill_per_second;
+            self.buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * refill < fair_share.burst
+            });
+        }
+        let stats = self.waits.entry(session.to_string()).or_default();
+        stats.calls += 1;
+        stats.total_wait += waited;
+        stats.max_wait = stats.max_wait.max(waited);
+    }
+
+    /// Hand free slots to the highest-ranked waiters still listening.
+    fn grant_next(&mut self) {
+        while self.in_flight < self.max_in_flight {
+            let Some(index) = self.ranked(|a, b| a > b) else {
+                break;
+            };
+            let waiter = self.queue.remove(index);
+            let waited = waiter.enqueued.elapsed();
+            if waiter.grant.send(Ok(waited)).is_ok() {
+                self.take_slot(&waiter.session, waited);
+            }
+        }
+    }
+
+    fn release(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        self.grant_next();
+    }
+}
+
+fn lock(state: &Mutex<LimiterState>) -> std::sync::MutexGuard<'_, LimiterState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A slot for one model call, released when dropped.
+pub struct RateLimitPermit {
+    state: Arc<Mutex<LimiterState>>,
+    waited: Duration,
+}
+
+impl std::fmt::Debug for RateLimitPermit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimitPermit").field("waited", &self.waited).finish_non_exhaustive()
+    }
+}
+
+impl RateLimitPermit {
+    /// Get the time the call waited for its slot.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+impl Drop for RateLimitPermit {
+    fn drop(&mut self) {
+        lock(&self.state).release();
+    }
+}
+
+/// A queued call, which returns a slot granted after the caller stopped waiting.
+struct PendingGrant {
+    receiver: oneshot::Receiver<IndubitablyResult<Duration>>,
+    state: Arc<Mutex<LimiterState>>,
+    received: bool,
+}
+
+impl Drop for PendingGrant {
+    fn drop(&mut self) {
+        if !self.received {
+            self.receiver.close();
+            if let Ok(Ok(_)) = self.receiver.try_recv() {
+                lock(&self.state).release();
+            }
+        }
+    }
+}
+
+/// A limit on concurrent model calls, shared by cloning, with priority scheduling.
+#[derive(Clone)]
+pub struct RateLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = lock(&self.state);
+        f.debug_struct("RateLimiter")
+            .field("max_in_flight", &state.max_in_flight)
+            .field("max_queued", &state.max_queued)
+            .field("in_flight", &state.in_flight)
+            .field("queued", &state.queue.len())
+            .finish()
+    }
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing the given number of calls in flight, with an unbounded queue.
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LimiterState {
+                max_in_flight: max_in_flight.max(1),
+                max_queued: None,
+                starvation_timeout: DEFAULT_STARVATION_TIMEOUT,
+                fair_share: None,
+                in_flight: 0,
+                queue: Vec::new(),
+                next_sequence: 0,
+                buckets: HashMap::new(),
+                waits: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Bound the queue; a full queue preempts its lowest-priority call or refuses new ones.
+    pub fn with_max_queued(self, max_queued: usize) -> Self {
+        lock(&self.state).max_queued = Some(max_queued);
+        self
+    }
+
+    /// Set the time after which a queued call is served ahead of every priority.
+    pub fn with_starvation_timeout(self, timeout: Duration) -> Self {
+        lock(&self.state).starvation_timeout = timeout;
+        self
+    }
+
+    /// Share slots fairly between the sessions calling through the limiter.
+    pub fn with_fair_share(self, fair_share: FairShare) -> Self {
+        lock(&self.state).fair_share = Some(fair_share);
+        self
+    }
+
+    /// Get the time each session's calls waited for a slot, keyed by session ID.
+    ///
+    /// Calls made without a session are recorded under the empty string.
+    pub fn wait_stats(&self) -> BTreeMap<String, SessionWaitStats> {
+        lock(&self.state).waits.clone()
+    }
+
+    /// Clear the recorded wait times, such as after exporting them.
+    pub fn reset_wait_stats(&self) {
+        lock(&self.state).waits.clear();
+    }
+
+    /// Get the number of calls in flight.
+    pub fn in_flight(&self) -> usize {
+        lock(&self.state).in_flight
+    }
+
+    /// Get the number of calls waiting for a slot.
+    pub fn queued(&self) -> usize {
+        let mut state = lock(&self.state);
+        state.queue.retain(|waiter| !waiter.grant.is_closed());
+        state.queue.len()
+    }
+
+    /// Wait for a slot for a call of the given priority.
+    ///
+    /// Fails as throttled if the call is preempted while queued, or if the
+    /// queue is full of calls of the same or higher priority.
+    pub async fn acquire(&self, priority: Priority) -> IndubitablyResult<RateLimitPermit> {
+        self.acquire_for_session(priority, None).await
+    }
+
+    /// Wait for a slot for a call of the given priority made by a session.
+    ///
+    /// Under fair sharing, the session's tokens decide its place among queued
+    /// calls of the same priority. The wait is recorded in `wait_stats`.
+    pub async fn acquire_for_session(
+        &self,
+        priority: Priority,
+        session: Option<&str>,
+    ) -> IndubitablyResult<RateLimitPermit> {
+        let session = session.unwrap_or(UNNAMED_SESSION);
+        let receiver = {
+            let mut state = lock(&self.state);
+            state.queue.retain(|waiter| !waiter.grant.is_closed());
+            if state.in_flight < state.max_in_flight && state.queue.is_empty() {
+                state.take_slot(session, Duration::ZERO);
+                return Ok(self.permit(Duration::ZERO));
+            }
+            if state.max_queued.is_some_and(|max_queued| state.queue.len() >= max_queued) {
+                let timeout = state.starvation_timeout;
+                let lowest = state
+                    .ranked(|a, b| a < b)
+                    .filter(|index| {
+                        let waiter = &state.queue[*index];
+                        !waiter.is_starved(timeout) && waiter.priority < priority
+                    });
+                match lowest {
+                    Some(index) => {
+                        let preempted = state.queue.remove(index);
+                        tracing::debug!(
+                            "priority=<{:?}>, preempted_by=<{:?}> | preempted queued model call",
+                            preempted.priority,
+                            priority
+                        );
+                        let _ = preempted.grant.send(Err(ModelError::ModelThrottled(
+                            "Queued model call was preempted by higher-priority work".to_string(),
+                        )
+                        .into()));
+                    }
+                    None => {
+                        return Err(ModelError::ModelThrottled(format!(
+                            "Rate limiter queue is full with {} calls",
+                            state.queue.len()
+                        ))
+                        .into())
+                    }
+                }
+            }
+            let (grant, receiver) = oneshot::channel();
+            let sequence = state.next_sequence;
+            state.next_sequence += 1;
+            state.queue.push(Waiter {
+                priority,
+                session: session.to_string(),
+                enqueued: Instant::now(),
+                sequence,
+                grant,
+            });
+            receiver
+        };
+
+        // Returns the slot if the caller stops waiting after it was granted
+        let mut pending = PendingGrant {
+            receiver,
+            state: self.state.clone(),
+            received: false,
+        };
+        let granted = (&mut pending.receiver).await;
+        pending.received = true;
+        match granted {
+            Ok(Ok(waited)) => Ok(self.permit(waited)),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(IndubitablyError::InternalError("Rate limiter dropped a queued call".to_string())),
+        }
+    }
+
+    fn permit(&self, waited: Duration) -> RateLimitPermit {
+        RateLimitPermit {
+            state: self.state.clone(),
+            waited,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::task::JoinHandle;
+
+    /// Queue a call that reports its label once granted and holds its slot briefly.
+    fn queue(
+        limiter: &RateLimiter,
+        priority: Priority,
+        label: &'static str,
+        order: &Arc<Mutex<Vec<&'static str>>>,
+    ) -> JoinHandle<IndubitablyResult<()>> {
+        let limiter = limiter.clone();
+        let order = order.clone();
+        tokio::spawn(async move {
+            let _permit = limiter.acquire(priority).await?;
+            order.lock().unwrap().push(label);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok(())
+        })
+    }
+
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    #[tokio::test]
+    async fn test_priority_preemption_and_starvation() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let limiter = RateLimiter::new(1).with_max_queued(2);
+        let held = limiter.acquire(Priority::Interactive).await.unwrap();
+        let batch = queue(&limiter, Priority::Background, "batch", &order);
+        settle().await;
+        let first = queue(&limiter, Priority::Interactive, "first", &order);
+        settle().await;
+        let second = queue(&limiter, Priority::Interactive, "second", &order);
+        settle().await;
+        assert_eq!(limiter.in_flight(), 1);
+        assert_eq!(limiter.queued(), 2);
+
+        // The queued background call was preempted, and a full queue of interactive calls refuses more
+        assert!(matches!(
+            batch.await.unwrap(),
+            Err(IndubitablyError::ModelError(ModelError::ModelThrottled(_)))
+        ));
+        assert!(limiter.acquire(Priority::Interactive).await.is_err());
+        drop(held);
+        first.await.unwrap().unwrap();
+        second.await.unwrap().unwrap();
+        assert_eq!(*order.lock().unwrap(), vec!["first", "second"]);
+        assert_eq!(limiter.in_flight(), 0);
+
+        // Background calls queued past the starvation timeout are served before interactive ones
+        order.lock().unwrap().clear();
+        let limiter = RateLimiter::new(1).with_starvation_timeout(Duration::from_millis(30));
+        let held = limiter.acquire(Priority::Background).await.unwrap();
+        let starved = queue(&limiter, Priority::Background, "starved", &order);
+        let background = queue(&limiter, Priority::Background, "background", &order);
+        settle().await;
+        settle().await;
+        let interactive = queue(&limiter, Priority::Interactive, "interactive", &order);
+        settle().await;
+        drop(held);
+        for call in [starved, background, interactive] {
+            call.await.unwrap().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec!["starved", "background", "interactive"]);
+    }
+
+    #[tokio::test]
+    async fn test_fair_share_serves_quiet_sessions_first() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let limiter = RateLimiter::new(1).with_fair_share(FairShare::new(0.0).with_burst(2.0));
+        let held = limiter.acquire_for_session(Priority::Interactive, Some("busy")).await.unwrap();
+        let mut calls = Vec::new();
+        for label in ["busy-1", "busy-2", "busy-3", "quiet"] {
+            let limiter = limiter.clone();
+            let order = order.clone();
+            let session = label.split('-').next().unwrap();
+            calls.push(tokio::spawn(async move {
+                let _permit = limiter.acquire_for_session(Priority::Interactive, Some(session)).await?;
+                order.lock().unwrap().push(label);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                IndubitablyResult::Ok(())
+            }));
+            settle().await;
+        }
+        drop(held);
+        for call in calls {
+            call.await.unwrap().unwrap();
+        }
+
+        // The quiet session overtakes the busy one, which keeps its own arrival order
+        assert_eq!(*order.lock().unwrap(), vec!["quiet", "busy-1", "busy-2", "busy-3"]);
+        let stats = limiter.wait_stats();
+        assert_eq!(stats["busy"].calls, 4);
+        assert_eq!(stats["quiet"].calls, 1);
+        assert!(stats["busy"].max_wait > stats["quiet"].max_wait);
+        assert!(stats["quiet"].mean_wait() > Duration::ZERO);
+    }
+}
@@ -0,0 +1,363 @@
+//! Retries with exponential backoff for model calls.
+//! 
+//! This module provides `RetryPolicy`, set per model with
+//! `ModelConfig::with_retry`, and `RetryingModel`, which wraps any provider
+//! and retries failed `generate` and `stream` calls under the wrapped
+//! model's policy. Only errors the policy classifies as transient, by
+//! default throttling, unavailability, network errors and timeouts, are
+//! retried. A stream is retried only until it has been opened; errors
+//! within an open stream are passed on.
+
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, ToolSpec};
+
+/// A class of error a retry policy can retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryCondition {
+    /// The provider throttled the request.
+    Throttled,
+    /// The model or service was unavailable.
+    Unavailable,
+    /// The quota was exceeded.
+    QuotaExceeded,
+    /// The request failed for another reason, such as a server error.
+    RequestFailed,
+    /// The connection failed.
+    Network,
+    /// The request timed out.
+    Timeout,
+}
+
+impl RetryCondition {
+    /// Check whether an error falls in this class.
+    pub fn matches(&self, error: &IndubitablyError) -> bool {
+        matches!(
+            (self, error),
+            (Self::Throttled, IndubitablyError::ModelError(ModelError::ModelThrottled(_)))
+                | (Self::Unavailable, IndubitablyError::ModelError(ModelError::ModelNotAvailable(_)))
+                | (Self::QuotaExceeded, IndubitablyError::ModelError(ModelError::QuotaExceeded(_)))
+                | (Self::RequestFailed, IndubitablyError::ModelError(ModelError::RequestFailed(_)))
+                | (Self::Network, IndubitablyError::NetworkError(_))
+                | (Self::Timeout, IndubitablyError::TimeoutError(_))
+        )
+    }
+}
+
+/// How failed model calls are retried.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// The most attempts per call, including the first.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The longest delay between attempts.
+    pub max_backoff: Duration,
+    /// The factor the delay grows by after each retry.
+    pub multiplier: f64,
+    /// The fraction of each delay randomized, from 0 for none to 1 for full jitter.
+    pub jitter: f64,
+    /// The errors that are retried.
+    pub retry_on: Vec<RetryCondition>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(20),
+            multiplier: 2.0,
+            jitter: 0.2,
+            retry_on: vec![
+                RetryCondition::Throttled,
+                RetryCondition::Unavailable,
+                RetryCondition::Network,
+                RetryCondition::Timeout,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create the default policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a policy that makes a single attempt.
+    pub fn none() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    /// Set the most attempts per call, including the first.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry and the longest delay.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Set the factor the delay grows by after each retry.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Set the fraction of each delay that is randomized.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the errors that are retried.
+    pub fn with_retry_on(mut self, conditions: Vec<RetryCondition>) -> Self {
+        self.retry_on = conditions;
+        self
+    }
+
+    /// Check whether an error is retried.
+    pub fn is_retryable(&self, error: &IndubitablyError) -> bool {
+        self.retry_on.iter().any(|condition| condition.matches(error))
+    }
+
+    /// Get the delay after a failed attempt, counting from 1, before jitter.
+    pub fn base_backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.saturating_sub(1).min(64) as i32);
+        self.initial_backoff.mul_f64(factor).min(self.max_backoff)
+    }
+
+    /// Get the delay after a failed attempt, with jitter applied.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.base_backoff(attempt);
+        if self.jitter == 0.0 {
+            return base;
+        }
+        let mut bytes = [0u8; 4];
+        let random = match getrandom::fill(&mut bytes) {
+            Ok(()) => u32::from_le_bytes(bytes) as f64 / u32::MAX as f64,
+            Err(_) => 0.5,
+        };
+        base.mul_f64(1.0 - self.jitter * random)
+    }
+
+    /// Run an operation, retrying retryable errors with backoff.
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> IndubitablyResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = IndubitablyResult<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(error) if attempt < self.max_attempts && self.is_retryable(&error) => {
+                    let delay = self.backoff(attempt);
+                    tracing::warn!(
+                        "attempt=<{}>, max_attempts=<{}>, delay_ms=<{}>, error=<{}> | retrying model call",
+                        attempt,
+                        self.max_attempts,
+                        delay.as_millis(),
+                        error
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A model wrapper retrying failed calls under the wrapped model's retry policy.
+///
+/// Calls to a model without a policy in its config use `RetryPolicy::default`.
+pub struct RetryingModel {
+    inner: Box<dyn Model>,
+}
+
+impl std::fmt::Debug for RetryingModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryingModel")
+            .field("model_id", &self.inner.config().model_id)
+            .field("retry", &self.inner.config().retry)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RetryingModel {
+    /// Wrap a model.
+    pub fn new(inner: Box<dyn Model>) -> Self {
+        Self { inner }
+    }
+
+    /// Set the retry policy in the wrapped model's config.
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.inner.config_mut().retry = Some(policy);
+        self
+    }
+
+    fn policy(&self) -> RetryPolicy {
+        self.inner.config().retry.clone().unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl Model for RetryingModel {
+    fn config(&self) -> &ModelConfig {
+        self.inner.config()
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.inner.update_config(config);
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        self.inner.config_mut()
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        self.policy()
+            .run(|| self.inner.generate(messages, tool_specs, system_prompt))
+            .await
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        self.policy()
+            .run(|| self.inner.stream(messages, tool_specs, system_prompt))
+            .await
+    }
+
+    async fn structured_output(
+        &self,
+        output_model: &str,
+        messages: &Messages,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        self.policy()
+            .run(|| self.inner.structured_output(output_model, messages, system_prompt))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Message;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Fails with the given errors, then answers.
+    struct FlakyModel {
+        config: ModelConfig,
+        failures: Vec<fn() -> IndubitablyError>,
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl Model for FlakyModel {
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn update_config(&mut self, config: ModelConfig) {
+            self.config = config;
+        }
+
+        fn config_mut(&mut self) -> &mut ModelConfig {
+            &mut self.config
+        }
+
+        async fn generate(
+            &self,
+            _messages: &Messages,
+            _tool_specs: Option<&[ToolSpec]>,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelResponse> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) as usize;
+            match self.failures.get(call) {
+                Some(failure) => Err(failure()),
+                None => Ok(ModelResponse::new("ok")),
+            }
+        }
+
+        async fn stream(
+            &self,
+            messages: &Messages,
+            tool_specs: Option<&[ToolSpec]>,
+            system_prompt: Option<&str>,
+        ) -> IndubitablyResult<ModelStreamResponse> {
+            let response = self.generate(messages, tool_specs, system_prompt).await?;
+            Ok(super::super::model::response_stream(response))
+        }
+
+        async fn structured_output(
+            &self,
+            _output_model: &str,
+            _messages: &Messages,
+            _system_prompt: Option<&str>,
+        ) -> IndubitablyResult<serde_json::Value> {
+            Ok(serde_json::Value::Null)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_backoff_and_classification() {
+        let policy = RetryPolicy::new()
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(300))
+            .with_jitter(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(5), Duration::from_millis(300));
+        let jittered = policy.clone().with_jitter(0.5).backoff(2);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+
+        let throttled = || IndubitablyError::ModelError(ModelError::ModelThrottled("slow down".to_string()));
+        let network = || IndubitablyError::NetworkError("connection reset".to_string());
+        let invalid = || IndubitablyError::ModelError(ModelError::InvalidConfiguration("bad key".to_string()));
+        let calls = Arc::new(AtomicU32::new(0));
+        let flaky = FlakyModel {
+            config: ModelConfig::new("flaky").with_retry(
+                RetryPolicy::new().with_backoff(Duration::from_millis(1), Duration::from_millis(2)),
+            ),
+            failures: vec![throttled, network, throttled, invalid],
+            calls: calls.clone(),
+        };
+        let model = RetryingModel::new(Box::new(flaky));
+        let messages = vec![Message::user("Hi")];
+
+        // All three attempts fail with transient errors
+        assert!(matches!(
+            model.generate(&messages, None, None).await,
+            Err(IndubitablyError::ModelError(ModelError::ModelThrottled(_)))
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        // Configuration errors are not retried
+        assert!(model.stream(&messages, None, None).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert!(model.stream(&messages, None, None).await.is_ok());
+
+        let model = model.with_policy(RetryPolicy::none());
+        assert_eq!(model.config().retry.as_ref().unwrap().max_attempts, 1);
+    }
+}
@@ -0,0 +1,182 @@
+//! Message role mapping for the SDK.
+//! 
+//! This module provides `RoleMapping`, which adapts the system prompt and
+//! system messages for models without a system role. Many local models
+//! served through Ollama or llama.cpp only understand user and assistant
+//! turns; the mapping folds system text into the first user message,
+//! optionally wrapped in the model's special tokens, so the same agent
+//! configuration behaves consistently across providers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::content::MessageRole;
+use crate::types::{ContentBlock, Message, Messages};
+
+/// How a model receives the system prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SystemPromptStrategy {
+    /// The model has a system role; messages are sent unchanged.
+    Native,
+    /// System text is prepended to the first user message.
+    FoldIntoFirstUser,
+    /// System text is wrapped in special tokens and prepended to the first user message.
+    SpecialTokens {
+        /// The tokens before the system text.
+        prefix: String,
+        /// The tokens after the system text.
+        suffix: String,
+    },
+}
+
+/// A mapping from the SDK's message roles to the roles a model supports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleMapping {
+    /// How the system prompt is delivered.
+    pub strategy: SystemPromptStrategy,
+    /// The text between folded system text and the user message.
+    pub separator: String,
+}
+
+impl Default for RoleMapping {
+    fn default() -> Self {
+        Self {
+            strategy: SystemPromptStrategy::Native,
+            separator: "\n\n".to_string(),
+        }
+    }
+}
+
+impl RoleMapping {
+    /// Create a mapping for models with a system role.
+    pub fn native() -> Self {
+        Self::default()
+    }
+
+    /// Create a mapping that folds system text into the first user message.
+    pub fn fold_into_first_user() -> Self {
+        Self {
+            strategy: SystemPromptStrategy::FoldIntoFirstUser,
+            ..Self::default()
+        }
+    }
+
+    /// Create a mapping that wraps system text in special tokens.
+    pub fn special_tokens(prefix: &str, suffix: &str) -> Self {
+        Self {
+            strategy: SystemPromptStrategy::SpecialTokens {
+                prefix: prefix.to_string(),
+                suffix: suffix.to_string(),
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Create a mapping using the Llama 2 chat `<<SYS>>` markers.
+    pub fn llama2() -> Self {
+        Self::special_tokens("<<SYS>>\n", "\n<</SYS>>")
+    }
+
+    /// Set the separator between system text and the user message.
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Check whether the mapping changes messages.
+    pub fn is_native(&self) -> bool {
+        self.strategy == SystemPromptStrategy::Native
+    }
+
+    /// Map messages and a system prompt to what the model accepts.
+    ///
+    /// Returns the messages to send and the system prompt to pass natively,
+    /// which is `None` once it has been folded into the messages. System
+    /// messages before the first user message are folded with the prompt;
+    /// later ones, such as budget notes, are sent as user messages.
+    pub fn apply(&self, messages: &[Message], system_prompt: Option<&str>) -> (Messages, Option<String>) {
+        if self.is_native() {
+            return (messages.to_vec(), system_prompt.map(str::to_string));
+        }
+
+        let mut system_parts: Vec<String> = system_prompt
+            .filter(|prompt| !prompt.trim().is_empty())
+            .map(|prompt| vec![prompt.to_string()])
+            .unwrap_or_default();
+        let mut mapped = Messages::with_capacity(messages.len());
+        let mut seen_user = false;
+        for message in messages {
+            match message.role {
+                MessageRole::System if !seen_user => system_parts.push(message.all_text()),
+                MessageRole::System => {
+                    let mut message = message.clone();
+                    message.role = MessageRole::User;
+                    mapped.push(message);
+                }
+                MessageRole::User => {
+                    seen_user = true;
+                    mapped.push(message.clone());
+                }
+                _ => mapped.push(message.clone()),
+            }
+        }
+
+        if system_parts.is_empty() {
+            return (mapped, None);
+        }
+        let system_text = match self.strategy {
+            SystemPromptStrategy::SpecialTokens { ref prefix, ref suffix } => {
+                format!("{}{}{}", prefix, system_parts.join(&self.separator), suffix)
+            }
+            _ => system_parts.join(&self.separator),
+        };
+
+        match mapped.iter_mut().find(|message| message.role == MessageRole::User) {
+            Some(first_user) => prepend_text(first_user, &system_text, &self.separator),
+            None => mapped.insert(0, Message::user(&system_text)),
+        }
+        (mapped, None)
+    }
+}
+
+/// Prepend text to the first text block of a message, adding a block if it has none.
+fn prepend_text(message: &mut Message, text: &str, separator: &str) {
+    match message.content.iter_mut().find_map(|block| block.text.as_mut()) {
+        Some(existing) => *existing = format!("{}{}{}", text, separator, existing),
+        None => message.content.insert(0, ContentBlock {
+            text: Some(text.to_string()),
+            ..Default::default()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_mapping() {
+        let messages = vec![
+            Message::system("Answer in French."),
+            Message::user("Hello"),
+            Message::assistant("Bonjour"),
+            Message::system("Wrap up now."),
+        ];
+
+        let (native, prompt) = RoleMapping::native().apply(&messages, Some("Be brief."));
+        assert_eq!(native, messages);
+        assert_eq!(prompt.as_deref(), Some("Be brief."));
+
+        let (folded, prompt) = RoleMapping::fold_into_first_user().apply(&messages, Some("Be brief."));
+        assert!(prompt.is_none());
+        assert_eq!(folded.len(), 3);
+        assert_eq!(folded[0].text(), Some("Be brief.\n\nAnswer in French.\n\nHello"));
+        assert_eq!(folded[2].role, MessageRole::User);
+
+        let (wrapped, _) = RoleMapping::llama2().apply(&[Message::user("Hi")], Some("Be brief."));
+        assert_eq!(wrapped[0].text(), Some("<<SYS>>\nBe brief.\n<</SYS>>\n\nHi"));
+
+        let (inserted, _) = RoleMapping::fold_into_first_user().apply(&[], Some("Be brief."));
+        assert_eq!(inserted, vec![Message::user("Be brief.")]);
+    }
+}
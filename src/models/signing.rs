@@ -0,0 +1,513 @@
+//! Request signing for model gateways.
+//! 
+//! This module provides the `RequestSigner` trait and `SigningHttpClient`,
+//! an `HttpClient` wrapper that signs every request before sending it, so
+//! any HTTP provider can talk to a gateway protected by IAM or OIDC.
+//! `SigV4Signer` implements AWS Signature Version 4 and `BearerSigner`
+//! attaches bearer tokens from a `TokenProvider`, such as the OAuth 2.0
+//! client-credentials flow of an OIDC identity provider.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use super::http::{HttpClient, HttpRequest, HttpResponse, HttpStreamingResponse};
+use crate::crypto::{hex, hmac_sha256, sha256};
+use crate::types::{IndubitablyError, IndubitablyResult, ModelError};
+
+/// The algorithm name of AWS Signature Version 4.
+pub const SIGV4_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// How long before expiry a cached token is refreshed.
+pub const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Signs outgoing requests.
+#[async_trait]
+pub trait RequestSigner: Send + Sync {
+    /// Add authentication to a request.
+    async fn sign(&self, request: &mut HttpRequest) -> IndubitablyResult<()>;
+}
+
+/// An `HttpClient` that signs each request before passing it on.
+pub struct SigningHttpClient {
+    inner: Arc<dyn HttpClient>,
+    signer: Arc<dyn RequestSigner>,
+}
+
+impl SigningHttpClient {
+    /// Wrap a client so its requests are signed by the given signer.
+    pub fn new(inner: Arc<dyn HttpClient>, signer: Arc<dyn RequestSigner>) -> Self {
+        Self { inner, signer }
+    }
+}
+
+#[async_trait]
+impl HttpClient for SigningHttpClient {
+    async fn send(&self, mut request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+        self.signer.sign(&mut request).await?;
+        self.inner.send(request).await
+    }
+
+    async fn send_streaming(&self, mut request: HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+        self.signer.sign(&mut request).await?;
+        self.inner.send_streaming(request).await
+    }
+}
+
+/// AWS credentials used for SigV4 signing.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    /// The access key ID.
+    pub access_key_id: String,
+    /// The secret access key.
+    pub secret_access_key: String,
+    /// The session token of temporary credentials.
+    pub session_token: Option<String>,
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"[REDACTED]")
+            .field("session_token", &self.session_token.as_ref().map(|_| "[REDACTED]"))
+            .finish()
+    }
+}
+
+impl AwsCredentials {
+    /// Create long-term credentials.
+    pub fn new(access_key_id: &str, secret_access_key: &str) -> Self {
+        Self {
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: None,
+        }
+    }
+
+    /// Set the session token of temporary credentials.
+    pub fn with_session_token(mut self, session_token: &str) -> Self {
+        self.session_token = Some(session_token.to_string());
+        self
+    }
+
+    /// Read credentials from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> IndubitablyResult<Self> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| {
+                IndubitablyError::ModelError(ModelError::InvalidConfiguration(format!("{} is not set", name)))
+            })
+        };
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+/// Signs requests with AWS Signature Version 4.
+#[derive(Debug, Clone)]
+pub struct SigV4Signer {
+    credentials: AwsCredentials,
+    region: String,
+    service: String,
+}
+
+impl SigV4Signer {
+    /// Create a signer for a service in a region, such as `bedrock` in `us-east-1`.
+    pub fn new(credentials: AwsCredentials, region: &str, service: &str) -> Self {
+        Self {
+            credentials,
+            region: region.to_string(),
+            service: service.to_string(),
+        }
+    }
+
+    /// Sign a request as of the given time.
+    pub fn sign_at(&self, request: &mut HttpRequest, time: DateTime<Utc>) -> IndubitablyResult<()> {
+        let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = &amz_date[..8];
+        let (host, path, query) = split_url(&request.url).ok_or_else(|| {
+            IndubitablyError::ModelError(ModelError::InvalidConfiguration(format!("Cannot sign URL '{}'", request.url)))
+        })?;
+        let host = host.to_string();
+        let canonical_uri = if path.is_empty() { "/".to_string() } else { uri_encode(path, false) };
+        let canonical_query = canonical_query(query);
+
+        if request.header("host").is_none() {
+            request.headers.push(("host".to_string(), host));
+        }
+        set_header(request, "x-amz-date", &amz_date);
+        if let Some(ref token) = self.credentials.session_token {
+            set_header(request, "x-amz-security-token", token);
+        }
+
+        // Headers repeated under any case are signed once, their values joined in order
+        let mut headers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, value) in request.headers.iter().filter(|(name, _)| !name.eq_ignore_ascii_case("authorization")) {
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            headers.entry(name.to_ascii_lowercase()).or_default().push(value);
+        }
+        let canonical_headers: String =
+            headers.iter().map(|(name, values)| format!("{}:{}\n", name, values.join(","))).collect();
+        let signed_headers = headers.keys().map(String::as_str).collect::<Vec<_>>().join(";");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            request.method.to_ascii_uppercase(),
+            canonical_uri,
+            canonical_query,
+            canonical_headers,
+            signed_headers,
+            hex::encode(&sha256(&request.body))
+        );
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            SIGV4_ALGORITHM,
+            amz_date,
+            scope,
+            hex::encode(&sha256(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac_sha256(format!("AWS4{}", self.credentials.secret_access_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), self.service.as_str(), "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex::encode(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        set_header(
+            request,
+            "authorization",
+            &format!(
+                "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+                SIGV4_ALGORITHM, self.credentials.access_key_id, scope, signed_headers, signature
+            ),
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RequestSigner for SigV4Signer {
+    async fn sign(&self, request: &mut HttpRequest) -> IndubitablyResult<()> {
+        self.sign_at(request, Utc::now())
+    }
+}
+
+/// Supplies bearer tokens.
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    /// Get a valid access token.
+    async fn token(&self) -> IndubitablyResult<String>;
+}
+
+/// A provider returning a fixed token.
+#[derive(Clone)]
+pub struct StaticTokenProvider {
+    token: String,
+}
+
+impl StaticTokenProvider {
+    /// Create a provider for the given token.
+    pub fn new(token: &str) -> Self {
+        Self { token: token.to_string() }
+    }
+}
+
+#[async_trait]
+impl TokenProvider for StaticTokenProvider {
+    async fn token(&self) -> IndubitablyResult<String> {
+        Ok(self.token.clone())
+    }
+}
+
+/// An OAuth 2.0 token endpoint response.
+#[derive(Deserialize)]
+pub(crate) struct TokenResponse {
+    pub(crate) access_token: String,
+    #[serde(default)]
+    pub(crate) expires_in: Option<u64>,
+}
+
+/// Post a form to an OAuth 2.0 token endpoint.
+pub(crate) async fn request_token(
+    client: &dyn HttpClient,
+    token_url: &str,
+    form: &[(&str, &str)],
+) -> IndubitablyResult<TokenResponse> {
+    let body: Vec<String> = form
+        .iter()
+        .map(|(name, value)| format!("{}={}", uri_encode(name, true), uri_encode(value, true)))
+        .collect();
+    let request = HttpRequest::post(token_url)
+        .with_header("content-type", "application/x-www-form-urlencoded")
+        .with_body(body.join("&").into_bytes());
+
+    let response = client.send(request).await?;
+    if !response.is_success() {
+        return Err(IndubitablyError::ModelError(ModelError::RequestFailed(format!(
+            "Token endpoint returned status {}",
+            response.status
+        ))));
+    }
+    response.json()
+}
+
+/// Fetches tokens from an OIDC provider with the OAuth 2.0 client-credentials grant.
+///
+/// Tokens are cached and refreshed `TOKEN_REFRESH_MARGIN` before they expire.
+pub struct OidcClientCredentials {
+    client: Arc<dyn HttpClient>,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+    audience: Option<String>,
+    cached: Mutex<Option<(String, Option<Instant>)>>,
+}
+
+impl OidcClientCredentials {
+    /// Create a provider for the given token endpoint and client.
+    pub fn new(client: Arc<dyn HttpClient>, token_url: &str, client_id: &str, client_secret: &str) -> Self {
+        Self {
+            client,
+            token_url: token_url.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            scope: None,
+            audience: None,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Set the requested scope.
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        self.scope = Some(scope.to_string());
+        self
+    }
+
+    /// Set the requested audience.
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.audience = Some(audience.to_string());
+        self
+    }
+
+    async fn fetch(&self) -> IndubitablyResult<TokenResponse> {
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        if let Some(ref scope) = self.scope {
+            form.push(("scope", scope));
+        }
+        if let Some(ref audience) = self.audience {
+            form.push(("audience", audience));
+        }
+        request_token(self.client.as_ref(), &self.token_url, &form).await
+    }
+}
+
+#[async_trait]
+impl TokenProvider for OidcClientCredentials {
+    async fn token(&self) -> IndubitablyResult<String> {
+        let mut cached = self.cached.lock().await;
+        if let Some((ref token, expires_at)) = *cached {
+            if expires_at.is_none_or(|expires_at| Instant::now() + TOKEN_REFRESH_MARGIN < expires_at) {
+                return Ok(token.clone());
+            }
+        }
+        let response = self.fetch().await?;
+        tracing::debug!("token_url=<{}>, expires_in=<{:?}> | fetched access token", self.token_url, response.expires_in);
+        let expires_at = response.expires_in.map(|seconds| Instant::now() + Duration::from_secs(seconds));
+        *cached = Some((response.access_token.clone(), expires_at));
+        Ok(response.access_token)
+    }
+}
+
+/// Signs requests with an `Authorization: Bearer` header.
+pub struct BearerSigner {
+    provider: Arc<dyn TokenProvider>,
+}
+
+impl BearerSigner {
+    /// Create a signer using tokens from the given provider.
+    pub fn new(provider: Arc<dyn TokenProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl RequestSigner for BearerSigner {
+    async fn sign(&self, request: &mut HttpRequest) -> IndubitablyResult<()> {
+        let token = self.provider.token().await?;
+        set_header(request, "authorization", &format!("Bearer {}", token));
+        Ok(())
+    }
+}
+
+/// Replace every header with the given name.
+fn set_header(request: &mut HttpRequest, name: &str, value: &str) {
+    request.headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+    request.headers.push((name.to_string(), value.to_string()));
+}
+
+/// Split a URL into host, path and query.
+fn split_url(url: &str) -> Option<(&str, &str, &str)> {
+    let (_, rest) = url.split_once("://")?;
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let path_start = rest.find('/').unwrap_or(rest.len());
+    let (host, path) = rest.split_at(path_start);
+    (!host.is_empty()).then_some((host, path, query))
+}
+
+/// Percent-encode everything but unreserved characters, and `/` unless `encode_slash` is set.
+///
+/// Paths are encoded as sent, so escapes already in them are encoded again,
+/// as SigV4 requires for every service but S3.
+pub(crate) fn uri_encode(text: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escape = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Build the SigV4 canonical query string: parameters encoded strictly and sorted.
+fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (uri_encode(&percent_decode(name), true), uri_encode(&percent_decode(value), true))
+        })
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_sigv4_signer() {
+        // The example request from the AWS Signature Version 4 documentation.
+        let signer = SigV4Signer::new(
+            AwsCredentials::new("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+            "us-east-1",
+            "iam",
+        );
+        let mut request = HttpRequest::get("https://iam.amazonaws.com/?Version=2010-05-08&Action=ListUsers")
+            .with_header("Content-Type", "application/x-www-form-urlencoded; charset=utf-8");
+        signer
+            .sign_at(&mut request, Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap())
+            .unwrap();
+
+        assert_eq!(request.header("x-amz-date"), Some("20150830T123600Z"));
+        assert_eq!(
+            request.header("authorization"),
+            Some(
+                "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+                 SignedHeaders=content-type;host;x-amz-date, \
+                 Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+            )
+        );
+        assert_eq!(uri_encode("/model/anthropic.claude-v2%3A1/invoke", false), "/model/anthropic.claude-v2%253A1/invoke");
+    }
+
+    #[test]
+    fn test_sigv4_signer_merges_repeated_headers() {
+        let signer = SigV4Signer::new(AwsCredentials::new("AKIDEXAMPLE", "secret"), "us-east-1", "bedrock");
+        let time = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let mut repeated = HttpRequest::post("https://bedrock-runtime.us-east-1.amazonaws.com/model/m/converse")
+            .with_header("My-Header", "value2")
+            .with_header("my-header", "value1")
+            .with_json_body(&serde_json::json!({}))
+            .unwrap()
+            .with_header("Content-Type", "application/json");
+        let mut joined = HttpRequest::post("https://bedrock-runtime.us-east-1.amazonaws.com/model/m/converse")
+            .with_header("my-header", "value2,value1")
+            .with_header("content-type", "application/json,application/json")
+            .with_body(b"{}".to_vec());
+        signer.sign_at(&mut repeated, time).unwrap();
+        signer.sign_at(&mut joined, time).unwrap();
+
+        let authorization = repeated.header("authorization").unwrap();
+        assert!(authorization.contains("SignedHeaders=content-type;host;my-header;x-amz-date,"));
+        assert_eq!(Some(authorization), joined.header("authorization"));
+    }
+
+    struct TokenEndpoint {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpClient for TokenEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            if request.url.ends_with("/token") {
+                self.requests.fetch_add(1, Ordering::SeqCst);
+                assert!(String::from_utf8_lossy(&request.body).contains("grant_type=client_credentials"));
+                return Ok(HttpResponse::new(200, br#"{"access_token": "abc", "expires_in": 3600}"#.to_vec()));
+            }
+            let authorization = request.header("authorization").unwrap_or_default().to_string();
+            Ok(HttpResponse::new(200, authorization.into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bearer_signer_with_oidc_tokens() {
+        let endpoint = Arc::new(TokenEndpoint { requests: AtomicUsize::new(0) });
+        let tokens = OidcClientCredentials::new(endpoint.clone(), "https://idp.example.com/token", "agent", "s3cret")
+            .with_scope("models.invoke");
+        let client = SigningHttpClient::new(endpoint.clone(), Arc::new(BearerSigner::new(Arc::new(tokens))));
+
+        for _ in 0..2 {
+            let response = client.send(HttpRequest::post("https://gateway.example.com/v1/chat")).await.unwrap();
+            assert_eq!(response.text(), "Bearer abc");
+        }
+        assert_eq!(endpoint.requests.load(Ordering::SeqCst), 1);
+    }
+}
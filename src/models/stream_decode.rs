@@ -0,0 +1,193 @@
+//! Incremental decoding of streamed response bodies.
+//! 
+//! Providers stream responses as server-sent events, newline-delimited JSON
+//! or AWS event stream messages. A provider's `StreamDecoder` is fed the
+//! body chunk by chunk as it arrives and returns the stream events each
+//! chunk completes, so callers see text before the response has finished.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio_stream::Stream;
+
+use super::http::HttpBodyStream;
+use super::model::ModelStreamResponse;
+use crate::types::{IndubitablyResult, StreamEvent};
+
+/// Maps the chunks of a streamed body to stream events as they arrive.
+pub trait StreamDecoder: Send + Unpin + 'static {
+    /// Decode the next chunk of the body, returning the events it completes.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<IndubitablyResult<StreamEvent>>;
+
+    /// Decode whatever remains once the body ends, returning the final events.
+    fn finish(&mut self) -> Vec<IndubitablyResult<StreamEvent>>;
+
+    /// Whether the stream has ended, after which the rest of the body is ignored.
+    fn is_done(&self) -> bool;
+}
+
+/// Decode a complete body at once, as if it had arrived in one chunk.
+pub fn decode_all<D: StreamDecoder>(mut decoder: D, body: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+    let mut events = decoder.feed(body);
+    events.extend(decoder.finish());
+    events
+}
+
+/// Decode a body as it arrives, yielding each event once its chunk is read.
+pub fn decode_body<D: StreamDecoder>(body: HttpBodyStream, decoder: D) -> ModelStreamResponse {
+    Box::pin(DecodedStream {
+        body,
+        decoder,
+        pending: VecDeque::new(),
+        ended: false,
+    })
+}
+
+struct DecodedStream<D> {
+    body: HttpBodyStream,
+    decoder: D,
+    pending: VecDeque<IndubitablyResult<StreamEvent>>,
+    ended: bool,
+}
+
+impl<D: StreamDecoder> Stream for DecodedStream<D> {
+    type Item = IndubitablyResult<StreamEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(event) = this.pending.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+            if this.ended {
+                return Poll::Ready(None);
+            }
+            if this.decoder.is_done() {
+                this.ended = true;
+                this.pending.extend(this.decoder.finish());
+                continue;
+            }
+            match this.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.pending.extend(this.decoder.feed(&chunk)),
+                Poll::Ready(Some(Err(e))) => {
+                    this.ended = true;
+                    this.pending.push_back(Err(e));
+                }
+                Poll::Ready(None) => {
+                    this.ended = true;
+                    this.pending.extend(this.decoder.finish());
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Splits a body into lines as its chunks arrive, for server-sent events and newline-delimited JSON.
+///
+/// Lines are only decoded once complete, so a multi-byte character split
+/// across chunks is never mangled.
+#[derive(Debug, Default)]
+pub struct LineSplitter {
+    buffer: Vec<u8>,
+}
+
+impl LineSplitter {
+    /// Add a chunk, returning the lines it completes without their line endings.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let Some(last) = self.buffer.iter().rposition(|&byte| byte == b'\n') else {
+            return Vec::new();
+        };
+        let rest = self.buffer.split_off(last + 1);
+        let complete = std::mem::replace(&mut self.buffer, rest);
+        complete[..last]
+            .split(|&byte| byte == b'\n')
+            .map(|line| String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned())
+            .collect()
+    }
+
+    /// Take the unterminated last line, if any.
+    pub fn finish(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.buffer);
+        (!line.is_empty()).then(|| String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(&line)).into_owned())
+    }
+}
+
+/// Get the payload of a server-sent event `data:` line.
+pub fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{StreamContent, StreamEventType};
+    use tokio_stream::StreamExt;
+
+    fn text_event(text: &str) -> IndubitablyResult<StreamEvent> {
+        Ok(StreamEvent::content_block_delta(vec![StreamContent::text(text)]))
+    }
+
+    fn event_text(event: IndubitablyResult<StreamEvent>) -> Option<String> {
+        event.unwrap().content.and_then(|content| content.first().and_then(|content| content.text.clone()))
+    }
+
+    /// Emits one text delta per line and a stop once the body ends.
+    struct Lines {
+        lines: LineSplitter,
+        done: bool,
+    }
+
+    impl StreamDecoder for Lines {
+        fn feed(&mut self, chunk: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+            let mut events = Vec::new();
+            for line in self.lines.push(chunk) {
+                if line == "end" {
+                    self.done = true;
+                    break;
+                }
+                events.push(text_event(&line));
+            }
+            events
+        }
+
+        fn finish(&mut self) -> Vec<IndubitablyResult<StreamEvent>> {
+            let mut events: Vec<_> = self.lines.finish().map(|line| text_event(&line)).into_iter().collect();
+            events.push(Ok(StreamEvent::message_stop()));
+            events
+        }
+
+        fn is_done(&self) -> bool {
+            self.done
+        }
+    }
+
+    #[test]
+    fn test_line_splitter_joins_split_characters() {
+        let mut lines = LineSplitter::default();
+        let text = "caf\u{e9}\r\nna\u{ef}ve\n";
+        let (first, second) = text.as_bytes().split_at(4);
+        assert!(lines.push(first).is_empty());
+        assert_eq!(lines.push(second), vec!["caf\u{e9}", "na\u{ef}ve"]);
+        assert!(lines.push(b"tail").is_empty());
+        assert_eq!(lines.finish().as_deref(), Some("tail"));
+        assert_eq!(sse_data("data: {}"), Some("{}"));
+    }
+
+    #[tokio::test]
+    async fn test_decode_body_yields_events_before_the_body_ends() {
+        let (sender, receiver) = tokio::sync::mpsc::channel(4);
+        let body: HttpBodyStream = Box::pin(tokio_stream::wrappers::ReceiverStream::new(receiver));
+        let mut stream = decode_body(body, Lines { lines: LineSplitter::default(), done: false });
+
+        sender.send(Ok(b"one\ntw".to_vec())).await.unwrap();
+        assert_eq!(event_text(stream.next().await.unwrap()).as_deref(), Some("one"));
+        sender.send(Ok(b"o\nend\nignored\n".to_vec())).await.unwrap();
+        assert_eq!(event_text(stream.next().await.unwrap()).as_deref(), Some("two"));
+        let stop = stream.next().await.unwrap().unwrap();
+        assert!(matches!(stop.event_type, StreamEventType::MessageStop));
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -0,0 +1,476 @@
+//! Token counting for the SDK.
+//! 
+//! This module provides the `Tokenizer` trait and a `TokenizerRegistry`
+//! mapping model id patterns to tokenizers. Byte-pair encoders load the
+//! rank files published for OpenAI's tiktoken encodings and, with the
+//! `hf-tokenizers` feature, Hugging Face `tokenizer.json` files. Models
+//! without a registered tokenizer fall back to a characters-per-token
+//! heuristic.
+//! 
+//! Conversation managers use a tokenizer to trim history to a token limit,
+//! and the agent uses the registry to reject requests that would overflow
+//! the context window and to estimate usage providers do not report.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use regex::Regex;
+
+use crate::crypto::base64;
+use crate::types::{ContentBlock, IndubitablyError, IndubitablyResult, Message};
+
+/// The tokens counted for each message's role and framing.
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// The pre-tokenizer pattern of the `cl100k_base` encoding.
+const CL100K_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+";
+
+/// The pre-tokenizer pattern of the `o200k_base` encoding.
+const O200K_PATTERN: &str = r"[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]*[\p{Ll}\p{Lm}\p{Lo}\p{M}]+(?i:'s|'t|'re|'ve|'m|'ll|'d)?|[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]+[\p{Ll}\p{Lm}\p{Lo}\p{M}]*(?i:'s|'t|'re|'ve|'m|'ll|'d)?|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+";
+
+/// A counter of the tokens a model sees for some text.
+pub trait Tokenizer: Send + Sync {
+    /// Get the tokenizer's name.
+    fn name(&self) -> &str;
+
+    /// Count the tokens in the given text.
+    fn count_tokens(&self, text: &str) -> usize;
+
+    /// Count the tokens in the given messages, including per-message overhead.
+    fn count_message_tokens(&self, messages: &[Message]) -> usize {
+        messages
+            .iter()
+            .map(|message| {
+                let content: usize = message.content.iter().map(|block| self.count_tokens(&block_text(block))).sum();
+                MESSAGE_OVERHEAD_TOKENS + content
+            })
+            .sum()
+    }
+}
+
+/// Get the text of a content block as the model sees it.
+fn block_text(block: &ContentBlock) -> String {
+    let mut parts = Vec::new();
+    if let Some(ref text) = block.text {
+        parts.push(text.clone());
+    }
+    if let Some(ref reasoning) = block.reasoning_content {
+        parts.push(reasoning.reasoning_text.text.clone());
+    }
+    if let Some(ref tool_use) = block.tool_use {
+        parts.push(tool_use.name.clone());
+        if let Some(ref input) = tool_use.input {
+            parts.push(input.to_string());
+        }
+    }
+    if let Some(ref result) = block.tool_result {
+        parts.extend(result.content.iter().filter_map(|content| content.text.clone()));
+    }
+    parts.join("\n")
+}
+
+/// A tokenizer estimating tokens from the number of characters.
+#[derive(Debug, Clone)]
+pub struct HeuristicTokenizer {
+    chars_per_token: f64,
+}
+
+impl HeuristicTokenizer {
+    /// Create a heuristic tokenizer assuming the given characters per token.
+    pub fn new(chars_per_token: f64) -> Self {
+        Self { chars_per_token: chars_per_token.max(1.0) }
+    }
+}
+
+impl Default for HeuristicTokenizer {
+    fn default() -> Self {
+        Self::new(4.0)
+    }
+}
+
+impl Tokenizer for HeuristicTokenizer {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        (text.chars().count() as f64 / self.chars_per_token).ceil() as usize
+    }
+}
+
+/// A byte-pair encoder over a rank table and a pre-tokenizer pattern.
+struct BytePairEncoder {
+    ranks: HashMap<Vec<u8>, u32>,
+    pattern: Regex,
+}
+
+impl BytePairEncoder {
+    fn new(ranks: HashMap<Vec<u8>, u32>, pattern: &str) -> IndubitablyResult<Self> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| IndubitablyError::ValidationError(format!("Invalid pre-tokenizer pattern: {}", e)))?;
+        Ok(Self { ranks, pattern })
+    }
+
+    /// Split text into pieces, giving back the last whitespace before a word as `\s+(?!\S)` would.
+    fn pieces<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut pieces = Vec::new();
+        let mut position = 0;
+        while let Some(found) = self.pattern.find_at(text, position) {
+            let piece = found.as_str();
+            let mut end = found.end();
+            let before_word = text[end..].chars().next().is_some_and(|c| !c.is_whitespace());
+            if before_word && piece.chars().all(char::is_whitespace) && !piece.ends_with(['\r', '\n']) {
+                if let Some((last, _)) = piece.char_indices().last().filter(|(last, _)| *last > 0) {
+                    end = found.start() + last;
+                }
+            }
+            pieces.push(&text[found.start()..end]);
+            position = end;
+        }
+        pieces
+    }
+
+    /// Count the tokens a piece merges into.
+    fn count_piece(&self, piece: &[u8]) -> usize {
+        if piece.len() < 2 || self.ranks.contains_key(piece) {
+            return 1;
+        }
+        let mut bounds: Vec<usize> = (0..=piece.len()).collect();
+        loop {
+            let best = (0..bounds.len() - 2)
+                .filter_map(|i| self.ranks.get(&piece[bounds[i]..bounds[i + 2]]).map(|rank| (*rank, i)))
+                .min();
+            let Some((_, i)) = best else { break };
+            bounds.remove(i + 1);
+        }
+        bounds.len() - 1
+    }
+
+    fn count(&self, text: &str) -> usize {
+        self.pieces(text).into_iter().map(|piece| self.count_piece(piece.as_bytes())).sum()
+    }
+}
+
+/// The tiktoken encodings used by OpenAI models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiktokenEncoding {
+    /// The encoding of GPT-4, GPT-3.5 and the third-generation embeddings.
+    Cl100kBase,
+    /// The encoding of GPT-4o, GPT-4.1 and the o-series.
+    O200kBase,
+}
+
+impl TiktokenEncoding {
+    /// Get the encoding's name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cl100kBase => "cl100k_base",
+            Self::O200kBase => "o200k_base",
+        }
+    }
+
+    /// Get the name of the encoding's rank file.
+    pub fn file_name(&self) -> String {
+        format!("{}.tiktoken", self.name())
+    }
+
+    /// Get the model id patterns using the encoding.
+    pub fn model_patterns(&self) -> &'static [&'static str] {
+        match self {
+            Self::Cl100kBase => &["gpt-4*", "gpt-3.5*", "text-embedding-3*", "text-embedding-ada-002"],
+            Self::O200kBase => &["gpt-4o*", "gpt-4.1*", "gpt-5*", "o1*", "o3*", "o4*"],
+        }
+    }
+
+    /// Get the encoding a model uses, if it is an OpenAI model.
+    pub fn for_model(model_id: &str) -> Option<Self> {
+        [Self::Cl100kBase, Self::O200kBase]
+            .into_iter()
+            .flat_map(|encoding| encoding.model_patterns().iter().map(move |pattern| (encoding, *pattern)))
+            .filter(|(_, pattern)| glob_match(pattern, model_id))
+            .max_by_key(|(_, pattern)| specificity(pattern))
+            .map(|(encoding, _)| encoding)
+    }
+
+    fn pattern(&self) -> &'static str {
+        match self {
+            Self::Cl100kBase => CL100K_PATTERN,
+            Self::O200kBase => O200K_PATTERN,
+        }
+    }
+}
+
+/// A tokenizer for a tiktoken encoding.
+pub struct TiktokenTokenizer {
+    encoding: TiktokenEncoding,
+    encoder: BytePairEncoder,
+}
+
+impl TiktokenTokenizer {
+    /// Load an encoding from a rank file of `base64-token rank` lines.
+    pub fn from_file(encoding: TiktokenEncoding, path: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        let text = std::fs::read_to_string(path.as_ref())?;
+        let mut ranks = HashMap::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (token, rank) = line.split_once(' ').ok_or_else(|| {
+                IndubitablyError::ValidationError(format!("Invalid tiktoken rank line: {}", line))
+            })?;
+            let rank = rank
+                .trim()
+                .parse()
+                .map_err(|_| IndubitablyError::ValidationError(format!("Invalid tiktoken rank: {}", rank)))?;
+            ranks.insert(base64::decode(token)?, rank);
+        }
+        Self::from_ranks(encoding, ranks)
+    }
+
+    /// Create a tokenizer from an encoding's rank table.
+    pub fn from_ranks(encoding: TiktokenEncoding, ranks: HashMap<Vec<u8>, u32>) -> IndubitablyResult<Self> {
+        Ok(Self {
+            encoding,
+            encoder: BytePairEncoder::new(ranks, encoding.pattern())?,
+        })
+    }
+
+    /// Get the tokenizer's encoding.
+    pub fn encoding(&self) -> TiktokenEncoding {
+        self.encoding
+    }
+}
+
+impl Tokenizer for TiktokenTokenizer {
+    fn name(&self) -> &str {
+        self.encoding.name()
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        self.encoder.count(text)
+    }
+}
+
+/// The pre-tokenizer pattern of byte-level BPE tokenizers.
+#[cfg(feature = "hf-tokenizers")]
+const BYTE_LEVEL_PATTERN: &str = r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+";
+
+/// A tokenizer for a byte-level BPE model in Hugging Face's `tokenizer.json` format.
+#[cfg(feature = "hf-tokenizers")]
+pub struct HuggingFaceTokenizer {
+    name: String,
+    encoder: BytePairEncoder,
+}
+
+#[cfg(feature = "hf-tokenizers")]
+impl HuggingFaceTokenizer {
+    /// Load a tokenizer from a `tokenizer.json` file.
+    pub fn from_file(name: &str, path: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        Self::from_json(name, &std::fs::read_to_string(path.as_ref())?)
+    }
+
+    /// Load a tokenizer from the contents of a `tokenizer.json` file.
+    pub fn from_json(name: &str, json: &str) -> IndubitablyResult<Self> {
+        let invalid = |message: &str| IndubitablyError::ValidationError(format!("Invalid tokenizer.json: {}", message));
+        let document: serde_json::Value = serde_json::from_str(json)?;
+        let model = &document["model"];
+        if model["type"].as_str().is_some_and(|kind| kind != "BPE") {
+            return Err(invalid("only BPE models are supported"));
+        }
+        let merges = model["merges"].as_array().ok_or_else(|| invalid("missing model.merges"))?;
+
+        let byte_of: HashMap<char, u8> = byte_level_alphabet().into_iter().map(|(byte, c)| (c, byte)).collect();
+        let decode = |token: &str| -> Vec<u8> {
+            let mut bytes = Vec::new();
+            for c in token.chars() {
+                match byte_of.get(&c) {
+                    Some(byte) => bytes.push(*byte),
+                    None => bytes.extend(c.to_string().as_bytes()),
+                }
+            }
+            bytes
+        };
+
+        let mut ranks = HashMap::new();
+        for (rank, merge) in merges.iter().enumerate() {
+            let (left, right) = match merge {
+                serde_json::Value::String(pair) => pair.split_once(' ').ok_or_else(|| invalid(pair))?,
+                serde_json::Value::Array(pair) => match (pair.first().and_then(|v| v.as_str()), pair.get(1).and_then(|v| v.as_str())) {
+                    (Some(left), Some(right)) => (left, right),
+                    _ => return Err(invalid("malformed merge pair")),
+                },
+                _ => return Err(invalid("malformed merge")),
+            };
+            let mut merged = decode(left);
+            merged.extend(decode(right));
+            ranks.entry(merged).or_insert(rank as u32);
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            encoder: BytePairEncoder::new(ranks, BYTE_LEVEL_PATTERN)?,
+        })
+    }
+}
+
+#[cfg(feature = "hf-tokenizers")]
+impl Tokenizer for HuggingFaceTokenizer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        self.encoder.count(text)
+    }
+}
+
+/// Map every byte to the printable character byte-level BPE vocabularies use for it.
+#[cfg(feature = "hf-tokenizers")]
+fn byte_level_alphabet() -> Vec<(u8, char)> {
+    let mut shifted = 0;
+    (0..=255u8)
+        .map(|byte| {
+            let printable = matches!(byte, b'!'..=b'~' | 0xa1..=0xac | 0xae..=0xff);
+            if printable {
+                (byte, byte as char)
+            } else {
+                shifted += 1;
+                (byte, char::from_u32(255 + shifted).unwrap_or('?'))
+            }
+        })
+        .collect()
+}
+
+/// Check whether text matches a pattern where `*` matches any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Rank a pattern by how many characters it fixes.
+fn specificity(pattern: &str) -> usize {
+    pattern.chars().filter(|c| *c != '*').count()
+}
+
+/// A registry mapping model id patterns to tokenizers.
+#[derive(Clone)]
+pub struct TokenizerRegistry {
+    entries: Vec<(String, Arc<dyn Tokenizer>)>,
+    fallback: Arc<dyn Tokenizer>,
+}
+
+impl TokenizerRegistry {
+    /// Create a registry counting every model with the heuristic tokenizer.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            fallback: Arc::new(HeuristicTokenizer::default()),
+        }
+    }
+
+    /// Register a tokenizer for model ids matching a pattern, where `*` matches anything.
+    pub fn register(&mut self, pattern: &str, tokenizer: Arc<dyn Tokenizer>) {
+        self.entries.push((pattern.to_string(), tokenizer));
+    }
+
+    /// Register a tokenizer for model ids matching a pattern.
+    pub fn with_tokenizer(mut self, pattern: &str, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.register(pattern, tokenizer);
+        self
+    }
+
+    /// Set the tokenizer used for models matching no pattern.
+    pub fn with_fallback(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.fallback = tokenizer;
+        self
+    }
+
+    /// Register the tiktoken encodings whose rank files are in the given directory.
+    pub fn with_tiktoken_dir(mut self, dir: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        for encoding in [TiktokenEncoding::Cl100kBase, TiktokenEncoding::O200kBase] {
+            let path = dir.as_ref().join(encoding.file_name());
+            if !path.exists() {
+                continue;
+            }
+            let tokenizer: Arc<dyn Tokenizer> = Arc::new(TiktokenTokenizer::from_file(encoding, &path)?);
+            for pattern in encoding.model_patterns() {
+                self.register(pattern, tokenizer.clone());
+            }
+        }
+        Ok(self)
+    }
+
+    /// Get the tokenizer for a model, preferring the most specific matching pattern.
+    pub fn resolve(&self, model_id: &str) -> Arc<dyn Tokenizer> {
+        self.entries
+            .iter()
+            .filter(|(pattern, _)| glob_match(pattern, model_id))
+            .max_by_key(|(pattern, _)| specificity(pattern))
+            .map(|(_, tokenizer)| tokenizer.clone())
+            .unwrap_or_else(|| self.fallback.clone())
+    }
+
+    /// Count the tokens a model sees for some text.
+    pub fn count_tokens(&self, model_id: &str, text: &str) -> usize {
+        self.resolve(model_id).count_tokens(text)
+    }
+
+    /// Count the tokens a model sees for some messages.
+    pub fn count_messages(&self, model_id: &str, messages: &[Message]) -> usize {
+        self.resolve(model_id).count_message_tokens(messages)
+    }
+}
+
+impl Default for TokenizerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for TokenizerRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenizerRegistry")
+            .field("entries", &self.entries.iter().map(|(pattern, tokenizer)| (pattern, tokenizer.name())).collect::<Vec<_>>())
+            .field("fallback", &self.fallback.name())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_resolves_tiktoken_ranks() {
+        let mut ranks = HashMap::new();
+        for (rank, token) in ["he", "ll", "hell", "hello", " w", "or", " wor", " world"].iter().enumerate() {
+            ranks.insert(token.as_bytes().to_vec(), rank as u32);
+        }
+        let tiktoken = Arc::new(TiktokenTokenizer::from_ranks(TiktokenEncoding::O200kBase, ranks).unwrap());
+        let registry = TokenizerRegistry::new()
+            .with_tokenizer("gpt-4*", Arc::new(HeuristicTokenizer::new(2.0)))
+            .with_tokenizer("gpt-4o*", tiktoken);
+
+        assert_eq!(registry.resolve("gpt-4o-mini").name(), "o200k_base");
+        assert_eq!(registry.resolve("gpt-4-turbo").name(), "heuristic");
+        assert_eq!(registry.count_tokens("gpt-4o", "hello world"), 2);
+        assert_eq!(registry.count_tokens("gpt-4o", "hello  world!"), 4);
+        assert_eq!(registry.count_tokens("claude-3", "12345678"), 2);
+        assert_eq!(registry.count_messages("claude-3", &[Message::user("1234")]), MESSAGE_OVERHEAD_TOKENS + 1);
+        assert_eq!(TiktokenEncoding::for_model("gpt-4o-2024-08-06"), Some(TiktokenEncoding::O200kBase));
+        assert_eq!(TiktokenEncoding::for_model("gpt-4-0613"), Some(TiktokenEncoding::Cl100kBase));
+        assert_eq!(TiktokenEncoding::for_model("llama3"), None);
+    }
+}
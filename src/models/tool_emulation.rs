@@ -0,0 +1,269 @@
+//! Tool calling emulation for the SDK.
+//! 
+//! This module provides `ToolEmulatingModel`, which gives models without
+//! native tool calling the same tool API as every other provider. Tool specs
+//! are described in the system prompt along with a strict call format:
+//! 
+//! ```text
+//! <tool_call>
+//! {"name": "get_weather", "arguments": {"city": "Paris"}}
+//! </tool_call>
+//! ```
+//! 
+//! Calls in the model's answer are parsed into `ToolUse`s and removed from
+//! its text, so the agent executes them as usual. Earlier tool calls and
+//! results in the conversation are rendered back into this format, since the
+//! wrapped model cannot read them natively. Arguments that cannot be parsed
+//! are reported under `MALFORMED_TOOL_CALLS_METADATA_KEY` like a native call.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::json;
+
+use super::model::{response_stream, Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::tools::repair::{parse_arguments, MALFORMED_TOOL_CALLS_METADATA_KEY};
+use crate::types::{ContentBlock, IndubitablyResult, Message, Messages, ToolSpec, ToolUse};
+
+/// The tag opening an emulated tool call.
+pub const TOOL_CALL_OPEN: &str = "<tool_call>";
+
+/// The tag closing an emulated tool call.
+pub const TOOL_CALL_CLOSE: &str = "</tool_call>";
+
+/// Describe tools and the call format for the system prompt.
+pub fn tool_instructions(tool_specs: &[ToolSpec]) -> String {
+    let mut instructions = String::from(
+        "You can call the tools below. To call a tool, reply with one block per call in exactly this format, \
+         with a JSON object naming the tool and giving its arguments:\n\
+         <tool_call>\n{\"name\": \"tool_name\", \"arguments\": {\"argument\": \"value\"}}\n</tool_call>\n\
+         Write nothing after your tool calls. Each result is returned to you in a <tool_result> block. \
+         When you need no tool, answer normally without any <tool_call> block.\n\nTools:",
+    );
+    for spec in tool_specs {
+        let schema = spec.input_schema.clone().unwrap_or_else(|| json!({ "type": "object" }));
+        instructions.push_str(&format!("\n- {}: {}\n  Arguments schema: {}", spec.name, spec.description, schema));
+    }
+    instructions
+}
+
+/// Render tool calls and results in a conversation as text in the emulated format.
+pub fn render_tool_messages(messages: &[Message]) -> Messages {
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    messages
+        .iter()
+        .map(|message| {
+            let tool_uses = message.tool_uses();
+            let results = message.tool_result_blocks();
+            if tool_uses.is_empty() && results.is_empty() {
+                return message.clone();
+            }
+            let mut blocks: Vec<String> = Vec::new();
+            for tool_use in tool_uses {
+                tool_names.insert(&tool_use.tool_use_id, &tool_use.name);
+                let call = json!({ "name": tool_use.name, "arguments": tool_use.input.clone().unwrap_or_else(|| json!({})) });
+                blocks.push(format!("{}\n{}\n{}", TOOL_CALL_OPEN, call, TOOL_CALL_CLOSE));
+            }
+            for result in results {
+                let name = tool_names.get(result.tool_use_id.as_str()).copied().unwrap_or_default();
+                let output: Vec<&str> = result.content.iter().filter_map(|content| content.text.as_deref()).collect();
+                let status = if result.is_error == Some(true) { " error=\"true\"" } else { "" };
+                blocks.push(format!(
+                    "<tool_result name=\"{}\"{}>\n{}\n</tool_result>",
+                    name,
+                    status,
+                    output.join("\n")
+                ));
+            }
+
+            let mut rendered = message.clone();
+            rendered.content.retain(|block| block.tool_use.is_none() && block.tool_result.is_none());
+            rendered.content.push(ContentBlock {
+                text: Some(blocks.join("\n")),
+                ..Default::default()
+            });
+            rendered
+        })
+        .collect()
+}
+
+/// Tool calls parsed from a model's answer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmulatedToolCalls {
+    /// The answer with the call blocks removed.
+    pub content: String,
+    /// The parsed calls, in order.
+    pub tool_uses: Vec<ToolUse>,
+    /// The parse error of each call whose arguments could not be read, keyed by tool use ID.
+    pub malformed: HashMap<String, String>,
+}
+
+/// Parse the emulated tool calls out of a model's answer.
+///
+/// A call left unclosed, as when generation stops at the output limit, runs
+/// to the end of the answer. Blocks without a readable tool name stay in the text.
+pub fn parse_tool_calls(text: &str) -> EmulatedToolCalls {
+    let name_pattern = Regex::new(r#""name"\s*:\s*"([^"]+)""#).expect("tool name pattern is valid");
+    let mut parsed = EmulatedToolCalls::default();
+    let mut rest = text;
+    while let Some(start) = rest.find(TOOL_CALL_OPEN) {
+        let body_start = start + TOOL_CALL_OPEN.len();
+        let (body, next) = match rest[body_start..].find(TOOL_CALL_CLOSE) {
+            Some(end) => (&rest[body_start..body_start + end], body_start + end + TOOL_CALL_CLOSE.len()),
+            None => (&rest[body_start..], rest.len()),
+        };
+        let tool_use_id = format!("call_{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+        let call = parse_arguments(body);
+        let name = match call {
+            Ok(ref call) => call["name"].as_str().map(str::to_string),
+            Err(_) => name_pattern.captures(body).map(|captures| captures[1].to_string()),
+        };
+        match (name, call) {
+            (Some(name), Ok(call)) => {
+                parsed.content.push_str(&rest[..start]);
+                let arguments = call.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                parsed.tool_uses.push(ToolUse::new(&name, &tool_use_id).with_input(arguments));
+            }
+            (Some(name), Err(error)) => {
+                parsed.content.push_str(&rest[..start]);
+                parsed.malformed.insert(tool_use_id.clone(), error);
+                parsed.tool_uses.push(ToolUse::new(&name, &tool_use_id));
+            }
+            (None, _) => parsed.content.push_str(&rest[..next]),
+        }
+        rest = &rest[next..];
+    }
+    parsed.content.push_str(rest);
+    parsed.content = parsed.content.trim().to_string();
+    parsed
+}
+
+/// A model wrapper emulating tool calling through the prompt.
+pub struct ToolEmulatingModel {
+    inner: Box<dyn Model>,
+}
+
+impl std::fmt::Debug for ToolEmulatingModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolEmulatingModel")
+            .field("model_id", &self.inner.config().model_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ToolEmulatingModel {
+    /// Wrap a model that has no native tool calling.
+    pub fn new(inner: Box<dyn Model>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl Model for ToolEmulatingModel {
+    fn config(&self) -> &ModelConfig {
+        self.inner.config()
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.inner.update_config(config);
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        self.inner.config_mut()
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let messages = render_tool_messages(messages);
+        let Some(specs) = tool_specs.filter(|specs| !specs.is_empty()) else {
+            return self.inner.generate(&messages, None, system_prompt).await;
+        };
+        let instructions = tool_instructions(specs);
+        let system_prompt = match system_prompt {
+            Some(prompt) if !prompt.is_empty() => format!("{}\n\n{}", prompt, instructions),
+            _ => instructions,
+        };
+
+        let mut response = self.inner.generate(&messages, None, Some(&system_prompt)).await?;
+        let calls = parse_tool_calls(&response.content);
+        tracing::debug!(
+            "model_id=<{}>, tool_uses=<{}>, malformed=<{}> | parsed emulated tool calls",
+            self.inner.config().model_id,
+            calls.tool_uses.len(),
+            calls.malformed.len()
+        );
+        response.content = calls.content;
+        response.tool_uses.extend(calls.tool_uses);
+        if !calls.malformed.is_empty() {
+            response.metadata.insert(MALFORMED_TOOL_CALLS_METADATA_KEY.to_string(), json!(calls.malformed));
+        }
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        // Calls can only be separated from the text once the whole answer is in
+        let response = self.generate(messages, tool_specs, system_prompt).await?;
+        Ok(response_stream(response))
+    }
+
+    async fn structured_output(
+        &self,
+        output_model: &str,
+        messages: &Messages,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        self.inner
+            .structured_output(output_model, &render_tool_messages(messages), system_prompt)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::MockModel;
+    use crate::types::ToolResult;
+
+    #[tokio::test]
+    async fn test_emulated_tool_calls() {
+        let answer = "Let me check.\n<tool_call>\n{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Paris\"}}\n</tool_call>\n\
+                      <tool_call>{\"name\": \"get_time\", \"arguments\": {\"zone\": \"CET\"</tool_call>\n\
+                      <tool_call>{\"name\": \"lookup\", \"arguments\": {\"q\": ???}}";
+        let model = ToolEmulatingModel::new(Box::new(MockModel::new().with_responses(vec![ModelResponse::new(answer)])));
+        let tools = vec![ToolSpec::new("get_weather", "Get the weather for a city")];
+        let messages = vec![
+            Message::user("Weather?"),
+            Message::assistant_with_tool_uses("", vec![ToolUse::new("get_weather", "call_0").with_input(json!({}))]),
+            Message::tool_results(vec![ToolResult::error("call_0", "city is required")]),
+        ];
+
+        let response = model.generate(&messages, Some(&tools), Some("Be brief.")).await.unwrap();
+        assert_eq!(response.content, "Let me check.");
+        let names: Vec<&str> = response.tool_uses.iter().map(|tool_use| tool_use.name.as_str()).collect();
+        assert_eq!(names, vec!["get_weather", "get_time", "lookup"]);
+        assert_eq!(response.tool_uses[0].input, Some(json!({ "city": "Paris" })));
+        let malformed = &response.metadata[MALFORMED_TOOL_CALLS_METADATA_KEY];
+        assert!(malformed[&response.tool_uses[1].tool_use_id].as_str().unwrap().contains("cut off"));
+        assert!(malformed.get(&response.tool_uses[2].tool_use_id).is_some());
+
+        let rendered = render_tool_messages(&messages);
+        assert!(rendered[1].tool_uses().is_empty());
+        assert_eq!(rendered[1].all_text(), "<tool_call>\n{\"arguments\":{},\"name\":\"get_weather\"}\n</tool_call>");
+        assert_eq!(
+            rendered[2].all_text(),
+            "<tool_result name=\"get_weather\" error=\"true\">\ncity is required\n</tool_result>"
+        );
+        assert!(tool_instructions(&tools).contains("- get_weather: Get the weather for a city"));
+        assert_eq!(parse_tool_calls("Use <tool_call> tags to call tools.").content, "Use <tool_call> tags to call tools.");
+    }
+}
@@ -0,0 +1,278 @@
+//! Multi-agent debate for the SDK.
+//! 
+//! This module provides `Debate`, which has several agents argue
+//! alternative answers to a question over a number of rounds. After each
+//! round a judge agent scores the arguments, and once the rounds are over
+//! the judge selects or synthesizes the final answer.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::Agent;
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// The default number of debate rounds.
+pub const DEFAULT_DEBATE_ROUNDS: usize = 2;
+
+/// One debater's argument in a round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebateTurn {
+    /// The index of the debater.
+    pub debater: usize,
+    /// The name of the debater agent.
+    pub debater_name: String,
+    /// The argument made by the debater.
+    pub argument: String,
+}
+
+/// A completed debate round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebateRound {
+    /// The round number, starting at 1.
+    pub round: usize,
+    /// The arguments made in the round, in debater order.
+    pub turns: Vec<DebateTurn>,
+    /// The judge's score for each debater, or empty if the judge's reply could not be parsed.
+    pub scores: Vec<f64>,
+}
+
+/// The outcome of a debate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebateResult {
+    /// The question that was debated.
+    pub question: String,
+    /// The debate rounds, in order.
+    pub rounds: Vec<DebateRound>,
+    /// The final answer selected or synthesized by the judge.
+    pub final_answer: String,
+}
+
+impl DebateResult {
+    /// Get the total score of each debater across all rounds.
+    pub fn total_scores(&self) -> Vec<f64> {
+        let mut totals = Vec::new();
+        for round in &self.rounds {
+            if totals.len() < round.scores.len() {
+                totals.resize(round.scores.len(), 0.0);
+            }
+            for (total, score) in totals.iter_mut().zip(&round.scores) {
+                *total += score;
+            }
+        }
+        totals
+    }
+
+    /// Render the full debate as readable text.
+    pub fn transcript(&self) -> String {
+        let mut transcript = format!("Question: {}\n", self.question);
+        for round in &self.rounds {
+            transcript.push_str(&format!("\nRound {}\n", round.round));
+            for turn in &round.turns {
+                transcript.push_str(&format!("[{}] {}\n", turn.debater_name, turn.argument));
+            }
+            if !round.scores.is_empty() {
+                transcript.push_str(&format!("Scores: {:?}\n", round.scores));
+            }
+        }
+        transcript.push_str(&format!("\nFinal answer: {}\n", self.final_answer));
+        transcript
+    }
+}
+
+/// A debate between several agents, refereed by a judge agent.
+pub struct Debate {
+    debaters: Vec<Agent>,
+    judge: Agent,
+    rounds: usize,
+}
+
+impl Debate {
+    /// Create a new debate refereed by the given judge.
+    pub fn new(judge: Agent) -> Self {
+        Self {
+            debaters: Vec::new(),
+            judge,
+            rounds: DEFAULT_DEBATE_ROUNDS,
+        }
+    }
+
+    /// Add a debater.
+    pub fn with_debater(mut self, debater: Agent) -> Self {
+        self.debaters.push(debater);
+        self
+    }
+
+    /// Set the number of rounds.
+    pub fn with_rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Get the number of debaters.
+    pub fn debater_count(&self) -> usize {
+        self.debaters.len()
+    }
+
+    /// Run the debate on a question.
+    pub async fn run(&mut self, question: &str) -> IndubitablyResult<DebateResult> {
+        if self.debaters.len() < 2 {
+            return Err(IndubitablyError::ValidationError(
+                "A debate needs at least two debaters".to_string(),
+            ));
+        }
+        if self.rounds == 0 {
+            return Err(IndubitablyError::ValidationError(
+                "A debate needs at least one round".to_string(),
+            ));
+        }
+
+        let mut rounds: Vec<DebateRound> = Vec::with_capacity(self.rounds);
+        for round in 1..=self.rounds {
+            let mut turns = Vec::with_capacity(self.debaters.len());
+            for (index, debater) in self.debaters.iter_mut().enumerate() {
+                let prompt = match rounds.last() {
+                    None => format!(
+                        "Question: {}\n\nGive your best answer and argue for it concisely.",
+                        question
+                    ),
+                    Some(previous) => format!(
+                        "Question: {}\n\nThese were the answers in the previous round:\n{}\n\
+                         Critique the other answers, then give your revised answer and argue for it concisely.",
+                        question,
+                        format_turns(&previous.turns, Some(index))
+                    ),
+                };
+                let argument = debater.run(&prompt).await?.response;
+                turns.push(DebateTurn {
+                    debater: index,
+                    debater_name: debater.config().name.clone(),
+                    argument,
+                });
+            }
+
+            let scores = self.score(question, &turns).await?;
+            tracing::debug!("round=<{}>, scores=<{:?}> | debate round scored", round, scores);
+            rounds.push(DebateRound { round, turns, scores });
+        }
+
+        let mut transcript = String::new();
+        for round in &rounds {
+            transcript.push_str(&format!("Round {}:\n{}\n", round.round, format_turns(&round.turns, None)));
+        }
+        let final_answer = self
+            .judge
+            .run(&format!(
+                "Question: {}\n\nDebate transcript:\n{}\
+                 Select the best answer or synthesize a better one from the arguments. Reply with the final answer only.",
+                question, transcript
+            ))
+            .await?
+            .response;
+
+        Ok(DebateResult {
+            question: question.to_string(),
+            rounds,
+            final_answer,
+        })
+    }
+
+    /// Ask the judge to score the arguments of a round.
+    async fn score(&mut self, question: &str, turns: &[DebateTurn]) -> IndubitablyResult<Vec<f64>> {
+        let reply = self
+            .judge
+            .run(&format!(
+                "Question: {}\n\nAnswers:\n{}\n\
+                 Score each answer from 0 to 10 for correctness and quality. \
+                 Reply with JSON only, in the form {{\"scores\": [<score for answer 1>, ...]}}.",
+                question,
+                format_turns(turns, None)
+            ))
+            .await?
+            .response;
+
+        let scores = parse_scores(&reply, turns.len());
+        if scores.is_empty() {
+            tracing::warn!("reply=<{}> | could not parse debate scores from judge", reply);
+        }
+        Ok(scores)
+    }
+}
+
+/// Format the turns of a round as a numbered list, optionally marking one as the reader's own.
+fn format_turns(turns: &[DebateTurn], own: Option<usize>) -> String {
+    turns
+        .iter()
+        .map(|turn| {
+            let marker = if Some(turn.debater) == own { " (yours)" } else { "" };
+            format!("{}. [{}{}] {}\n", turn.debater + 1, turn.debater_name, marker, turn.argument)
+        })
+        .collect()
+}
+
+/// Parse a `{"scores": [...]}` object out of a judge reply.
+fn parse_scores(reply: &str, expected: usize) -> Vec<f64> {
+    let (Some(start), Some(end)) = (reply.find('{'), reply.rfind('}')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+
+    #[derive(Deserialize)]
+    struct Scores {
+        scores: Vec<f64>,
+    }
+
+    match serde_json::from_str::<Scores>(&reply[start..=end]) {
+        Ok(parsed) if parsed.scores.len() == expected => parsed.scores,
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    fn scripted_agent(name: &str, replies: &[&str]) -> Agent {
+        let model = MockModel::new()
+            .with_responses(replies.iter().map(|reply| ModelResponse::new(reply)).collect());
+        AgentBuilder::new().name(name).model(Box::new(model)).build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_debate_runs_rounds_and_judges() {
+        let judge = scripted_agent("judge", &[
+            "{\"scores\": [6, 4]}",
+            "Scores: {\"scores\": [8, 5]}",
+            "4",
+        ]);
+        let mut debate = Debate::new(judge)
+            .with_debater(scripted_agent("alice", &["4", "Still 4"]))
+            .with_debater(scripted_agent("bob", &["5", "Maybe 4"]))
+            .with_rounds(2);
+
+        let result = debate.run("What is 2 + 2?").await.unwrap();
+
+        assert_eq!(result.rounds.len(), 2);
+        assert_eq!(result.rounds[1].turns[1].argument, "Maybe 4");
+        assert_eq!(result.rounds[1].scores, vec![8.0, 5.0]);
+        assert_eq!(result.total_scores(), vec![14.0, 9.0]);
+        assert_eq!(result.final_answer, "4");
+        assert!(result.transcript().contains("[bob] Maybe 4"));
+    }
+
+    #[tokio::test]
+    async fn test_debate_requires_two_debaters() {
+        let mut debate = Debate::new(scripted_agent("judge", &[]))
+            .with_debater(scripted_agent("alice", &[]));
+        assert!(debate.run("Question?").await.is_err());
+    }
+
+    #[test]
+    fn test_parse_scores() {
+        assert_eq!(parse_scores("{\"scores\": [1, 2.5]}", 2), vec![1.0, 2.5]);
+        assert!(parse_scores("{\"scores\": [1]}", 2).is_empty());
+        assert!(parse_scores("no json here", 2).is_empty());
+    }
+}
@@ -1,9 +1,38 @@
 //! Agent graph for the SDK.
 //! 
 //! This module provides functionality for building and managing
-//! agent graphs and workflows.
+//! agent graphs and workflows. `AgentGraph::execute` runs a workflow from an
+//! entry node, handing each node's response to the next node along the first
+//! matching edge. Execution is observable like a single-agent run: every node
+//! and handoff is a span in the `GraphResult` timeline, alongside the model
+//! and tool spans of the node agents, and node durations, handoffs and the
+//! number of active workflows are reported to the graph's metrics sink.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::agent::Agent;
+use crate::telemetry::sink::{MetricEvent, MetricsSink};
+use crate::telemetry::timeline::{SpanCategory, Timeline};
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// The default name of a graph, used in metrics and timelines.
+pub const DEFAULT_GRAPH_NAME: &str = "graph";
+
+/// The default maximum number of node executions in one workflow.
+pub const DEFAULT_MAX_GRAPH_STEPS: usize = 32;
+
+/// The number of graph workflows executing in the process.
+static ACTIVE_WORKFLOWS: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the number of graph workflows executing in the process.
+pub fn active_workflows() -> usize {
+    ACTIVE_WORKFLOWS.load(Ordering::SeqCst)
+}
 
 /// A node in an agent graph.
 pub struct AgentNode {
@@ -25,22 +54,132 @@ pub struct AgentEdge {
     pub condition: Option<String>,
 }
 
+impl AgentEdge {
+    /// Check whether the edge is taken after its source node responded.
+    ///
+    /// Edges without a condition are always taken; otherwise the response must
+    /// contain the condition, ignoring case.
+    pub fn matches(&self, response: &str) -> bool {
+        match &self.condition {
+            None => true,
+            Some(condition) => response.to_lowercase().contains(&condition.to_lowercase()),
+        }
+    }
+}
+
+/// The outcome of one node in a graph workflow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeResult {
+    /// The node that ran.
+    pub node_id: String,
+    /// The node agent's response.
+    pub response: String,
+    /// The wall time of the node.
+    pub duration: Duration,
+}
+
+/// The outcome of a graph workflow.
+#[derive(Debug, Clone)]
+pub struct GraphResult {
+    /// The response of the last node.
+    pub response: String,
+    /// The node outcomes in execution order.
+    pub nodes: Vec<NodeResult>,
+    /// The handoffs taken, as source and target node IDs, in order.
+    pub handoffs: Vec<(String, String)>,
+    /// The wall time of the workflow.
+    pub duration: Duration,
+    timeline: Timeline,
+}
+
+impl GraphResult {
+    /// Get the timed spans of the workflow: the workflow itself, each node and
+    /// handoff, and the model calls and tool executions of the node agents.
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Get the IDs of the nodes that ran, in order.
+    pub fn path(&self) -> Vec<&str> {
+        self.nodes.iter().map(|node| node.node_id.as_str()).collect()
+    }
+}
+
+/// Counts a workflow as active until dropped, reporting the gauge on each change.
+struct ActiveWorkflow<'a> {
+    sink: Option<&'a Arc<dyn MetricsSink>>,
+}
+
+impl<'a> ActiveWorkflow<'a> {
+    fn start(sink: Option<&'a Arc<dyn MetricsSink>>) -> Self {
+        let active = ACTIVE_WORKFLOWS.fetch_add(1, Ordering::SeqCst) + 1;
+        let workflow = Self { sink };
+        workflow.report(active);
+        workflow
+    }
+
+    fn report(&self, active: usize) {
+        if let Some(sink) = self.sink {
+            sink.record(&MetricEvent::ActiveWorkflows { active }, &BTreeMap::new());
+        }
+    }
+}
+
+impl Drop for ActiveWorkflow<'_> {
+    fn drop(&mut self) {
+        let active = ACTIVE_WORKFLOWS.fetch_sub(1, Ordering::SeqCst) - 1;
+        self.report(active);
+    }
+}
+
 /// An agent graph for managing multi-agent workflows.
 pub struct AgentGraph {
+    /// The graph name, used in metrics and timelines.
+    name: String,
     /// The nodes in the graph.
     nodes: HashMap<String, AgentNode>,
     /// The edges in the graph.
     edges: Vec<AgentEdge>,
+    /// The maximum number of node executions in one workflow.
+    max_steps: usize,
+    /// The sink receiving the graph's metrics.
+    metrics_sink: Option<Arc<dyn MetricsSink>>,
 }
 
 impl AgentGraph {
     /// Create a new agent graph.
     pub fn new() -> Self {
         Self {
+            name: DEFAULT_GRAPH_NAME.to_string(),
             nodes: HashMap::new(),
             edges: Vec::new(),
+            max_steps: DEFAULT_MAX_GRAPH_STEPS,
+            metrics_sink: None,
         }
     }
+
+    /// Set the graph name.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Set the maximum number of node executions in one workflow, which bounds cycles.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Report the graph's metrics to the given sink.
+    pub fn with_metrics_sink(mut self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.metrics_sink = Some(sink);
+        self
+    }
+
+    /// Get the graph name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
     
     /// Add a node to the graph.
     pub fn add_node(&mut self, node: AgentNode) {
@@ -66,6 +205,124 @@ impl AgentGraph {
     pub fn edges(&self) -> &[AgentEdge] {
         &self.edges
     }
+
+    /// Run a workflow from the entry node with the agents bound to the node IDs.
+    ///
+    /// Each node's agent is run with the previous node's response, starting
+    /// with `input`, and the workflow follows the first edge out of the node
+    /// whose condition matches the response. It ends at a node with no
+    /// matching edge.
+    pub async fn execute(
+        &self,
+        agents: &mut HashMap<String, Agent>,
+        entry: &str,
+        input: &str,
+    ) -> IndubitablyResult<GraphResult> {
+        let started = Instant::now();
+        let _active = ActiveWorkflow::start(self.metrics_sink.as_ref());
+        let mut timeline = Timeline::new(&self.name);
+        let mut nodes: Vec<NodeResult> = Vec::new();
+        let mut handoffs = Vec::new();
+        let mut node_id = entry.to_string();
+        let mut response = input.to_string();
+
+        loop {
+            if nodes.len() >= self.max_steps {
+                return Err(IndubitablyError::ValidationError(format!(
+                    "Graph '{}' exceeded {} steps",
+                    self.name, self.max_steps
+                )));
+            }
+            if !self.nodes.contains_key(&node_id) {
+                return Err(IndubitablyError::ValidationError(format!(
+                    "Graph '{}' has no node '{}'",
+                    self.name, node_id
+                )));
+            }
+            let agent = agents.get_mut(&node_id).ok_or_else(|| {
+                IndubitablyError::ValidationError(format!("No agent is bound to graph node '{}'", node_id))
+            })?;
+
+            let node_started = Instant::now();
+            let result = agent.run(&response).await;
+            let duration = node_started.elapsed();
+            self.record(MetricEvent::NodeDuration {
+                workflow: self.name.clone(),
+                node_id: node_id.clone(),
+                duration,
+                is_error: result.is_err(),
+            });
+            let mut args = json!({"node": node_id, "step": nodes.len() + 1, "is_error": result.is_err()});
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    tracing::warn!("graph=<{}>, node=<{}>, error=<{}> | graph node failed", self.name, node_id, e);
+                    args["error"] = json!(e.to_string());
+                    timeline.record(SpanCategory::GraphNode, &node_id, node_started, args);
+                    return Err(e);
+                }
+            };
+            let mut node_timeline = result.timeline;
+            node_timeline.spans.retain(|span| span.category != SpanCategory::Run);
+            timeline.merge(&node_timeline);
+            args["stop_reason"] = json!(result.stop_reason);
+            timeline.record(SpanCategory::GraphNode, &node_id, node_started, args);
+            tracing::debug!(
+                "graph=<{}>, node=<{}>, duration_ms=<{}> | graph node completed",
+                self.name,
+                node_id,
+                duration.as_millis()
+            );
+            response = result.response;
+            nodes.push(NodeResult {
+                node_id: node_id.clone(),
+                response: response.clone(),
+                duration,
+            });
+
+            let handoff_started = Instant::now();
+            let Some(edge) = self
+                .edges
+                .iter()
+                .find(|edge| edge.source == node_id && edge.matches(&response))
+            else {
+                break;
+            };
+            self.record(MetricEvent::Handoff {
+                workflow: self.name.clone(),
+                from: node_id.clone(),
+                to: edge.target.clone(),
+            });
+            timeline.record(
+                SpanCategory::Handoff,
+                &format!("{} -> {}", node_id, edge.target),
+                handoff_started,
+                json!({"from": node_id, "to": edge.target, "condition": edge.condition}),
+            );
+            handoffs.push((node_id, edge.target.clone()));
+            node_id = edge.target.clone();
+        }
+
+        timeline.record_from_origin(
+            SpanCategory::Run,
+            "graph.execute",
+            json!({"graph": self.name, "steps": nodes.len()}),
+        );
+        Ok(GraphResult {
+            response,
+            nodes,
+            handoffs,
+            duration: started.elapsed(),
+            timeline,
+        })
+    }
+
+    /// Report a metric event to the sink, if one is set.
+    fn record(&self, event: MetricEvent) {
+        if let Some(ref sink) = self.metrics_sink {
+            sink.record(&event, &BTreeMap::new());
+        }
+    }
 }
 
 impl Default for AgentGraph {
@@ -73,3 +330,80 @@ impl Default for AgentGraph {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::models::model::{MockModel, ModelResponse};
+    use crate::telemetry::metrics::labeled_name;
+    use crate::telemetry::sink::{
+        RegistryMetricsSink, METRIC_ACTIVE_WORKFLOWS, METRIC_GRAPH_HANDOFFS, METRIC_GRAPH_NODE_DURATION,
+    };
+
+    fn scripted_agent(name: &str, reply: &str) -> Agent {
+        let model = MockModel::new().with_responses(vec![ModelResponse::new(reply)]);
+        AgentBuilder::new().name(name).model(Box::new(model)).build().unwrap()
+    }
+
+    fn node(agent_id: &str) -> AgentNode {
+        AgentNode {
+            agent_id: agent_id.to_string(),
+            node_type: "agent".to_string(),
+            config: HashMap::new(),
+        }
+    }
+
+    fn edge(source: &str, target: &str, condition: Option<&str>) -> AgentEdge {
+        AgentEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            condition: condition.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_graph_execution_is_traced_and_measured() {
+        let sink = Arc::new(RegistryMetricsSink::new());
+        let mut graph = AgentGraph::new().with_name("triage").with_metrics_sink(sink.clone());
+        for id in ["router", "billing", "support"] {
+            graph.add_node(node(id));
+        }
+        graph.add_edge(edge("router", "support", Some("technical")));
+        graph.add_edge(edge("router", "billing", Some("BILLING")));
+        graph.add_edge(edge("billing", "support", Some("escalate")));
+        let mut agents = HashMap::from([
+            ("router".to_string(), scripted_agent("router", "This is a billing question")),
+            ("billing".to_string(), scripted_agent("billing", "Refund issued")),
+            ("support".to_string(), scripted_agent("support", "unused")),
+        ]);
+
+        let result = graph.execute(&mut agents, "router", "I was charged twice").await.unwrap();
+        assert_eq!(result.path(), vec!["router", "billing"]);
+        assert_eq!(result.handoffs, vec![("router".to_string(), "billing".to_string())]);
+        assert_eq!(result.response, "Refund issued");
+
+        let timeline = result.timeline();
+        let nodes: Vec<&str> = timeline.spans_of(SpanCategory::GraphNode).map(|span| span.name.as_str()).collect();
+        assert_eq!(nodes, vec!["router", "billing"]);
+        assert_eq!(timeline.spans_of(SpanCategory::Handoff).next().unwrap().name, "router -> billing");
+        assert_eq!(timeline.spans_of(SpanCategory::Model).count(), 2);
+        let run: Vec<_> = timeline.spans_of(SpanCategory::Run).collect();
+        assert_eq!(run.len(), 1);
+        assert_eq!(run[0].args["steps"], 2);
+
+        let metrics = sink.snapshot();
+        assert_eq!(metrics.get(&format!("{}.count", METRIC_GRAPH_NODE_DURATION)), Some(2.0));
+        let inf = BTreeMap::from([("le".to_string(), "+Inf".to_string())]);
+        assert_eq!(metrics.get(&labeled_name(&format!("{}.bucket", METRIC_GRAPH_NODE_DURATION), &inf)), Some(2.0));
+        assert_eq!(metrics.get(METRIC_GRAPH_HANDOFFS), Some(1.0));
+        assert_eq!(metrics.get(&format!("{}.count", METRIC_GRAPH_HANDOFFS)), None);
+        assert_eq!(metrics.get(METRIC_ACTIVE_WORKFLOWS), Some(active_workflows() as f64));
+
+        // Unbound nodes fail the workflow
+        agents.remove("support");
+        graph.add_edge(edge("billing", "support", None));
+        let error = graph.execute(&mut agents, "billing", "Anything else?").await.unwrap_err();
+        assert!(error.to_string().contains("graph node 'support'"));
+    }
+}
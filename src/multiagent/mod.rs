@@ -6,7 +6,9 @@
 pub mod base;
 pub mod graph;
 pub mod swarm;
+pub mod debate;
 
 pub use base::MultiAgent;
-pub use graph::AgentGraph;
+pub use graph::{AgentGraph, GraphResult, NodeResult};
 pub use swarm::AgentSwarm;
+pub use debate::{Debate, DebateResult, DebateRound};
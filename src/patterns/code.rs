@@ -0,0 +1,222 @@
+//! An edit-and-test coding loop.
+//! 
+//! This module provides `CodeTask` and the `code_task` shortcut, a coding
+//! agent skeleton for a repository checkout. The agent gets file tools, a
+//! patch tool that only applies unified diffs that match the files, and a
+//! test runner tool. Each attempt asks the agent to make the change, then
+//! runs the test command itself; while the tests fail, the agent is shown
+//! the failure and tries again, up to the attempt limit. Attempts run as
+//! cycles of an `EventLoop`. If the tests still fail at the end, or the
+//! agent fails, every file the tools changed is restored.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::Agent;
+use crate::event_loop::EventLoop;
+use crate::tools::fs::{create_fs_tools, FileJournal};
+use crate::tools::patch::create_apply_patch_tool;
+use crate::tools::test_runner::{create_run_tests_tool, TestCommand, TestOutcome};
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// The default number of attempts at a coding task.
+pub const DEFAULT_CODE_TASK_ATTEMPTS: usize = 3;
+
+/// The outcome of a coding task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeTaskReport {
+    /// The instruction given.
+    pub instruction: String,
+    /// Whether the tests passed after the change.
+    pub succeeded: bool,
+    /// The number of attempts made.
+    pub attempts: usize,
+    /// The files the agent changed, relative to the repository.
+    pub changed_files: Vec<PathBuf>,
+    /// Whether the changes were rolled back because the tests kept failing.
+    pub rolled_back: bool,
+    /// The last test run.
+    pub tests: TestOutcome,
+    /// The agent's last reply, describing its change.
+    pub summary: String,
+}
+
+/// A bounded edit-and-test loop around an agent working in a repository.
+pub struct CodeTask {
+    agent: Agent,
+    repo: PathBuf,
+    test_command: TestCommand,
+    max_attempts: usize,
+    journal: Arc<FileJournal>,
+}
+
+impl CodeTask {
+    /// Create a task for the agent in the given repository, tested with `cargo test`.
+    pub fn new(agent: Agent, repo_path: impl Into<PathBuf>) -> Self {
+        Self {
+            agent,
+            repo: repo_path.into(),
+            test_command: TestCommand::cargo(),
+            max_attempts: DEFAULT_CODE_TASK_ATTEMPTS,
+            journal: Arc::new(FileJournal::new()),
+        }
+    }
+
+    /// Set the command that runs the repository's tests.
+    pub fn with_test_command(mut self, command: TestCommand) -> Self {
+        self.test_command = command;
+        self
+    }
+
+    /// Set the most attempts before the change is rolled back.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Get the agent.
+    pub fn agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    /// Make a change to the repository and keep it only if the tests pass.
+    pub async fn run(&mut self, instruction: &str) -> IndubitablyResult<CodeTaskReport> {
+        if !self.repo.is_dir() {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Repository {} is not a directory",
+                self.repo.display()
+            )));
+        }
+        for tool in create_fs_tools(&self.repo, Arc::clone(&self.journal)) {
+            self.agent.add_tool(tool).await?;
+        }
+        self.agent.add_tool(create_apply_patch_tool(&self.repo, Arc::clone(&self.journal))).await?;
+        self.agent.add_tool(create_run_tests_tool(&self.repo, self.test_command.clone())).await?;
+        self.journal.clear();
+
+        let attempted = self.attempt(instruction).await;
+        let changed_files: Vec<PathBuf> = self
+            .journal
+            .changed_files()
+            .iter()
+            .filter_map(|path| path.strip_prefix(&self.repo).ok().map(Path::to_path_buf))
+            .collect();
+        let (attempts, tests, summary) = match attempted {
+            Ok(attempted) => attempted,
+            Err(e) => {
+                self.journal.rollback()?;
+                return Err(e);
+            }
+        };
+        let rolled_back = !tests.passed && self.journal.rollback()? > 0;
+        tracing::debug!(
+            "repo=<{}>, attempts=<{}>, passed=<{}>, changed=<{}>, rolled_back=<{}> | code task finished",
+            self.repo.display(),
+            attempts,
+            tests.passed,
+            changed_files.len(),
+            rolled_back
+        );
+        Ok(CodeTaskReport {
+            instruction: instruction.to_string(),
+            succeeded: tests.passed,
+            attempts,
+            changed_files,
+            rolled_back,
+            tests,
+            summary,
+        })
+    }
+
+    /// Prompt and test until the tests pass or the attempts run out.
+    async fn attempt(&mut self, instruction: &str) -> IndubitablyResult<(usize, TestOutcome, String)> {
+        let mut event_loop = EventLoop::with_max_iterations(self.max_attempts).with_label("pattern", "code_task");
+        let mut prompt = format!(
+            "You are working in a repository. Task:\n{}\n\n\
+             Explore it with `list_files` and `read_file`, then make the change with `apply_patch`, using a unified \
+             diff, or `write_file`. Check your work with `run_tests` (`{}`). \
+             When you are done, reply with a short summary of the change.",
+            instruction,
+            self.test_command.command_line()
+        );
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            event_loop.cycle(&Vec::new()).await?;
+            let summary = self.agent.run(&prompt).await?.response;
+            let tests = self.run_tests().await?;
+            if tests.passed || attempt >= self.max_attempts {
+                event_loop.finish_cycle();
+                return Ok((attempt, tests, summary));
+            }
+            tracing::debug!("attempt=<{}>, exit_code=<{:?}> | tests failed, retrying", attempt, tests.exit_code);
+            prompt = format!(
+                "The tests still fail after your change (attempt {} of {}). Task:\n{}\n\n\
+                 Test output:\n{}\n\nFix the cause, then reply with a short summary of the change.",
+                attempt, self.max_attempts, instruction, tests.output
+            );
+        }
+    }
+
+    /// Run the test command.
+    async fn run_tests(&self) -> IndubitablyResult<TestOutcome> {
+        self.test_command.run(&self.repo).await
+    }
+}
+
+/// Make a change to a repository with an agent, tested with `cargo test`, keeping it only if the tests pass.
+pub async fn code_task(
+    agent: Agent,
+    repo_path: impl Into<PathBuf>,
+    instruction: &str,
+) -> IndubitablyResult<CodeTaskReport> {
+    CodeTask::new(agent, repo_path).run(instruction).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::agent::AgentBuilder;
+    use crate::models::model::{MockModel, ModelResponse};
+    use crate::types::ToolUse;
+
+    fn write_call(id: &str, content: &str) -> ModelResponse {
+        ModelResponse::new("").with_tool_use(
+            ToolUse::new("write_file", id).with_input(json!({ "path": "answer.txt", "content": content })),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_code_task_retries_and_rolls_back_failing_changes() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("answer.txt"), "41").unwrap();
+        let tests = TestCommand::new("grep").with_args(&["-qx", "42", "answer.txt"]);
+
+        let model = MockModel::new().with_responses(vec![
+            write_call("call-1", "40"),
+            ModelResponse::new("Changed the answer."),
+            write_call("call-2", "42"),
+            ModelResponse::new("Fixed the answer."),
+        ]);
+        let agent = AgentBuilder::new().model(Box::new(model)).build().unwrap();
+        let mut task = CodeTask::new(agent, repo.path()).with_test_command(tests.clone());
+        let report = task.run("Make the answer 42").await.unwrap();
+        assert!(report.succeeded);
+        assert_eq!(report.attempts, 2);
+        assert_eq!(report.changed_files, vec![PathBuf::from("answer.txt")]);
+        assert_eq!(report.summary, "Fixed the answer.");
+        assert_eq!(std::fs::read_to_string(repo.path().join("answer.txt")).unwrap(), "42");
+
+        std::fs::write(repo.path().join("answer.txt"), "41").unwrap();
+        let model = MockModel::new().with_responses(vec![write_call("call-3", "43"), ModelResponse::new("Done.")]);
+        let agent = AgentBuilder::new().model(Box::new(model)).build().unwrap();
+        let mut task = CodeTask::new(agent, repo.path()).with_test_command(tests).with_max_attempts(1);
+        let report = task.run("Make the answer 42").await.unwrap();
+        assert!(!report.succeeded);
+        assert!(report.rolled_back);
+        assert_eq!(std::fs::read_to_string(repo.path().join("answer.txt")).unwrap(), "41");
+    }
+}
@@ -0,0 +1,470 @@
+//! Form filling by conversation.
+//! 
+//! This module provides `FormAgent`, which collects a record of typed slots,
+//! such as a name, a date or a choice from a list, by talking with a user.
+//! Each user message goes to the agent to extract slot values, including
+//! corrections to values already given. Every value is checked against its
+//! slot's type, pattern and constraints before it is kept, and problems are
+//! raised with the user in the agent's next reply, which asks for what is
+//! still missing. Once every required slot holds a valid value, the record
+//! is complete and can be taken as JSON or deserialized into a struct.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::agent::Agent;
+use crate::tools::constraints::ArgumentConstraint;
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// The date format of date slots.
+pub const FORM_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The type of a slot's value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SlotType {
+    /// Free text.
+    Text,
+    /// A whole number.
+    Integer,
+    /// A number.
+    Number,
+    /// Yes or no.
+    Boolean,
+    /// A calendar date, kept as `YYYY-MM-DD`.
+    Date,
+    /// An email address.
+    Email,
+    /// One of a fixed set of options.
+    Choice {
+        /// The allowed options.
+        options: Vec<String>,
+    },
+}
+
+impl SlotType {
+    /// Create a choice among the given options.
+    pub fn choice(options: &[&str]) -> Self {
+        Self::Choice { options: options.iter().map(|option| option.to_string()).collect() }
+    }
+
+    /// Get the JSON schema of values of this type.
+    pub fn json_schema(&self) -> Value {
+        match self {
+            Self::Text => json!({ "type": "string" }),
+            Self::Integer => json!({ "type": "integer" }),
+            Self::Number => json!({ "type": "number" }),
+            Self::Boolean => json!({ "type": "boolean" }),
+            Self::Date => json!({ "type": "string", "format": "date" }),
+            Self::Email => json!({ "type": "string", "format": "email" }),
+            Self::Choice { options } => json!({ "type": "string", "enum": options }),
+        }
+    }
+
+    /// Convert a value to this type, accepting common spellings such as `"42"` or `"yes"`.
+    pub fn coerce(&self, value: &Value) -> Result<Value, String> {
+        let text = match value {
+            Value::String(text) => Some(text.trim()),
+            _ => None,
+        };
+        match self {
+            Self::Text => match value {
+                Value::String(text) if !text.trim().is_empty() => Ok(json!(text.trim())),
+                Value::Number(number) => Ok(json!(number.to_string())),
+                _ => Err("must be text".to_string()),
+            },
+            Self::Integer => {
+                let integer = match (value.as_i64(), value.as_f64(), text) {
+                    (Some(integer), _, _) => Some(integer),
+                    (None, Some(number), _) if number.fract() == 0.0 => Some(number as i64),
+                    (_, _, Some(text)) => text.parse::<i64>().ok(),
+                    _ => None,
+                };
+                integer.map(|integer| json!(integer)).ok_or_else(|| "must be a whole number".to_string())
+            }
+            Self::Number => value
+                .as_f64()
+                .or_else(|| text.and_then(|text| text.parse::<f64>().ok()))
+                .map(|number| json!(number))
+                .ok_or_else(|| "must be a number".to_string()),
+            Self::Boolean => match (value.as_bool(), text.map(str::to_lowercase).as_deref()) {
+                (Some(flag), _) => Ok(json!(flag)),
+                (None, Some("yes" | "y" | "true")) => Ok(json!(true)),
+                (None, Some("no" | "n" | "false")) => Ok(json!(false)),
+                _ => Err("must be yes or no".to_string()),
+            },
+            Self::Date => text
+                .and_then(|text| NaiveDate::parse_from_str(text, FORM_DATE_FORMAT).ok())
+                .map(|date| json!(date.format(FORM_DATE_FORMAT).to_string()))
+                .ok_or_else(|| "must be a date in YYYY-MM-DD format".to_string()),
+            Self::Email => match text.and_then(|text| text.split_once('@').map(|parts| (text, parts))) {
+                Some((text, (local, domain)))
+                    if !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !text.contains(' ') =>
+                {
+                    Ok(json!(text))
+                }
+                _ => Err("must be an email address".to_string()),
+            },
+            Self::Choice { options } => text
+                .and_then(|text| options.iter().find(|option| option.eq_ignore_ascii_case(text)))
+                .map(|option| json!(option))
+                .ok_or_else(|| format!("must be one of: {}", options.join(", "))),
+        }
+    }
+}
+
+/// A field of a form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Slot {
+    /// The slot name, used as the record key.
+    pub name: String,
+    /// What the slot holds, as shown to the agent.
+    pub description: String,
+    /// The type of the slot's value.
+    pub slot_type: SlotType,
+    /// Whether the form is complete without the slot.
+    pub required: bool,
+    /// A regular expression text values must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Constraints the value must satisfy, such as a numeric range.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<ArgumentConstraint>,
+}
+
+impl Slot {
+    /// Create a required slot.
+    pub fn new(name: &str, description: &str, slot_type: SlotType) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            slot_type,
+            required: true,
+            pattern: None,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Make the slot optional.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Require text values to match a regular expression.
+    pub fn with_pattern(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Add a constraint on the value.
+    pub fn with_constraint(mut self, constraint: ArgumentConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Get the JSON schema of the slot's value.
+    pub fn json_schema(&self) -> Value {
+        let mut schema = self.slot_type.json_schema();
+        schema["description"] = json!(self.description);
+        if let Some(ref pattern) = self.pattern {
+            schema["pattern"] = json!(pattern);
+        }
+        schema
+    }
+
+    /// Check a value, returning it in its normal form or a description of the problem.
+    pub fn validate(&self, value: &Value) -> Result<Value, String> {
+        let value = self.slot_type.coerce(value)?;
+        if let (Some(pattern), Some(text)) = (&self.pattern, value.as_str()) {
+            let regex = Regex::new(pattern).map_err(|e| format!("has an invalid pattern: {}", e))?;
+            if !regex.is_match(text) {
+                return Err(format!("does not match the expected format {}", pattern));
+            }
+        }
+        match self.constraints.iter().find_map(|constraint| constraint.check(&value)) {
+            Some(problem) => Err(problem),
+            None => Ok(value),
+        }
+    }
+}
+
+/// A value the user gave that was not kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotError {
+    /// The slot name.
+    pub slot: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+/// The outcome of one user message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormReply {
+    /// The agent's reply to the user.
+    pub reply: String,
+    /// The slots filled for the first time.
+    pub filled: Vec<String>,
+    /// The slots whose earlier value was changed or cleared.
+    pub corrected: Vec<String>,
+    /// The values that were not kept.
+    pub errors: Vec<SlotError>,
+    /// Whether every required slot now holds a value.
+    pub complete: bool,
+}
+
+/// Values the agent extracted from a user message.
+#[derive(Debug, Default, Deserialize)]
+struct Extraction {
+    #[serde(default)]
+    slots: Map<String, Value>,
+    #[serde(default)]
+    clear: Vec<String>,
+}
+
+/// A dialog manager filling a form of typed slots by conversation.
+pub struct FormAgent {
+    agent: Agent,
+    slots: Vec<Slot>,
+    values: BTreeMap<String, Value>,
+}
+
+impl FormAgent {
+    /// Create a form with the given slots, filled by conversation through the agent.
+    pub fn new(agent: Agent, slots: Vec<Slot>) -> Self {
+        Self {
+            agent,
+            slots,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Get the slots.
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    /// Get the values collected so far.
+    pub fn values(&self) -> &BTreeMap<String, Value> {
+        &self.values
+    }
+
+    /// Get the required slots without a value.
+    pub fn missing(&self) -> Vec<&Slot> {
+        self.slots
+            .iter()
+            .filter(|slot| slot.required && !self.values.contains_key(&slot.name))
+            .collect()
+    }
+
+    /// Check whether every required slot holds a value.
+    pub fn is_complete(&self) -> bool {
+        self.missing().is_empty()
+    }
+
+    /// Get the JSON schema of the record.
+    pub fn json_schema(&self) -> Value {
+        let properties: Map<String, Value> =
+            self.slots.iter().map(|slot| (slot.name.clone(), slot.json_schema())).collect();
+        let required: Vec<&str> =
+            self.slots.iter().filter(|slot| slot.required).map(|slot| slot.name.as_str()).collect();
+        json!({ "type": "object", "properties": properties, "required": required, "additionalProperties": false })
+    }
+
+    /// Get the completed record, checking every value against its slot again.
+    pub fn record(&self) -> IndubitablyResult<Value> {
+        let mut problems: Vec<String> = self.missing().iter().map(|slot| format!("{} is missing", slot.name)).collect();
+        let mut record = Map::new();
+        for slot in &self.slots {
+            if let Some(value) = self.values.get(&slot.name) {
+                match slot.validate(value) {
+                    Ok(value) => {
+                        record.insert(slot.name.clone(), value);
+                    }
+                    Err(problem) => problems.push(format!("{} {}", slot.name, problem)),
+                }
+            }
+        }
+        if !problems.is_empty() {
+            return Err(IndubitablyError::ValidationError(format!("Form is not complete: {}", problems.join("; "))));
+        }
+        Ok(Value::Object(record))
+    }
+
+    /// Get the completed record deserialized into a type.
+    pub fn record_as<T: DeserializeOwned>(&self) -> IndubitablyResult<T> {
+        Ok(serde_json::from_value(self.record()?)?)
+    }
+
+    /// Forget the values collected so far.
+    pub fn reset(&mut self) {
+        self.values.clear();
+    }
+
+    /// Take a user message: keep the slot values it gives or corrects, then reply asking for what is missing.
+    pub async fn reply(&mut self, message: &str) -> IndubitablyResult<FormReply> {
+        let extraction = self.extract(message).await?;
+        let mut filled = Vec::new();
+        let mut corrected = Vec::new();
+        let mut errors = Vec::new();
+        for name in extraction.clear {
+            if self.values.remove(&name).is_some() {
+                corrected.push(name);
+            }
+        }
+        for (name, value) in extraction.slots {
+            let Some(slot) = self.slots.iter().find(|slot| slot.name == name) else {
+                continue;
+            };
+            if value.is_null() {
+                continue;
+            }
+            match slot.validate(&value) {
+                Ok(value) => match self.values.insert(name.clone(), value.clone()) {
+                    None => filled.push(name),
+                    Some(previous) if previous != value => corrected.push(name),
+                    Some(_) => {}
+                },
+                Err(message) => errors.push(SlotError { slot: name, message }),
+            }
+        }
+        let complete = self.is_complete();
+        tracing::debug!(
+            "filled=<{:?}>, corrected=<{:?}>, errors=<{}>, complete=<{}> | form updated",
+            filled,
+            corrected,
+            errors.len(),
+            complete
+        );
+        let reply = self.respond(&errors).await?;
+        Ok(FormReply { reply, filled, corrected, errors, complete })
+    }
+
+    /// Describe the slots and their current values for a prompt.
+    fn describe_slots(&self) -> String {
+        self.slots
+            .iter()
+            .map(|slot| {
+                let current = self.values.get(&slot.name).map_or("(empty)".to_string(), Value::to_string);
+                format!(
+                    "- {} ({}{}): {} Current value: {}\n",
+                    slot.name,
+                    slot.slot_type.json_schema(),
+                    if slot.required { ", required" } else { ", optional" },
+                    slot.description,
+                    current
+                )
+            })
+            .collect()
+    }
+
+    /// Ask the agent for the slot values in a user message.
+    async fn extract(&mut self, message: &str) -> IndubitablyResult<Extraction> {
+        let prompt = format!(
+            "You are filling in a form from a conversation. The fields:\n{}\nThe user said:\n{}\n\n\
+             Reply with JSON only, in the form {{\"slots\": {{\"field\": value}}, \"clear\": [\"field\"]}}. \
+             Include only fields the user gave or corrected in this message, and list under \"clear\" fields \
+             the user asked to remove. Dates are YYYY-MM-DD.",
+            self.describe_slots(),
+            message
+        );
+        let reply = self.agent.run(&prompt).await?.response;
+        Ok(parse_extraction(&reply))
+    }
+
+    /// Ask the agent for the reply to the user.
+    async fn respond(&mut self, errors: &[SlotError]) -> IndubitablyResult<String> {
+        let problems: String = errors.iter().map(|error| format!("- {} {}\n", error.slot, error.message)).collect();
+        let task = if self.is_complete() {
+            "Every required field is filled. Summarize the values and ask the user to confirm or correct them."
+                .to_string()
+        } else {
+            let missing: Vec<&str> = self.missing().iter().map(|slot| slot.name.as_str()).collect();
+            format!("Ask the user for the missing fields, one or two at a time: {}.", missing.join(", "))
+        };
+        let prompt = format!(
+            "You are helping a user fill in a form. The fields:\n{}\n{}{}\n\
+             Reply to the user directly, in one short message.",
+            self.describe_slots(),
+            if problems.is_empty() {
+                String::new()
+            } else {
+                format!("These answers were not accepted:\n{}", problems)
+            },
+            task
+        );
+        Ok(self.agent.run(&prompt).await?.response)
+    }
+}
+
+/// Parse the extracted values out of an agent reply.
+fn parse_extraction(reply: &str) -> Extraction {
+    let (Some(start), Some(end)) = (reply.find('{'), reply.rfind('}')) else {
+        return Extraction::default();
+    };
+    if end < start {
+        return Extraction::default();
+    }
+    serde_json::from_str(&reply[start..=end]).unwrap_or_else(|_| {
+        tracing::warn!("reply=<{}> | could not parse form values from agent", reply);
+        Extraction::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Signup {
+        name: String,
+        age: i64,
+        plan: String,
+        newsletter: Option<bool>,
+    }
+
+    #[tokio::test]
+    async fn test_form_agent_validates_and_applies_corrections() {
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("{\"slots\": {\"name\": \"Ada\", \"age\": \"about forty\"}}"),
+            ModelResponse::new("How old are you?"),
+            ModelResponse::new("{\"slots\": {\"age\": \"36\", \"plan\": \"PRO\"}}"),
+            ModelResponse::new("Ada, 36, pro. Correct?"),
+            ModelResponse::new("Sure: {\"slots\": {\"name\": \"Ada Lovelace\", \"plan\": \"gold\"}}"),
+            ModelResponse::new("Gold is not a plan we offer."),
+        ]);
+        let agent = AgentBuilder::new().model(Box::new(model)).build().unwrap();
+        let mut form = FormAgent::new(agent, vec![
+            Slot::new("name", "Full name", SlotType::Text),
+            Slot::new("age", "Age in years", SlotType::Integer)
+                .with_constraint(ArgumentConstraint::range(Some(18.0), None)),
+            Slot::new("plan", "Subscription plan", SlotType::choice(&["free", "pro"])),
+            Slot::new("newsletter", "Wants the newsletter", SlotType::Boolean).optional(),
+        ]);
+
+        let first = form.reply("I'm Ada, about forty").await.unwrap();
+        assert_eq!(first.filled, vec!["name"]);
+        assert_eq!(first.errors[0].slot, "age");
+        assert!(!first.complete);
+        assert!(form.record().is_err());
+
+        let second = form.reply("36, and the pro plan").await.unwrap();
+        assert!(second.complete);
+        assert_eq!(second.reply, "Ada, 36, pro. Correct?");
+
+        let third = form.reply("Actually Ada Lovelace, and gold").await.unwrap();
+        assert_eq!(third.corrected, vec!["name"]);
+        assert_eq!(third.errors[0].message, "must be one of: free, pro");
+        let signup: Signup = form.record_as().unwrap();
+        let expected = Signup { name: "Ada Lovelace".to_string(), age: 36, plan: "pro".to_string(), newsletter: None };
+        assert_eq!(signup, expected);
+        assert_eq!(form.json_schema()["required"], json!(["name", "age", "plan"]));
+    }
+}
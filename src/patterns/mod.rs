@@ -0,0 +1,16 @@
+//! Prebuilt agent patterns for the SDK.
+//! 
+//! This module provides routines that orchestrate agents and tools for
+//! common multi-step tasks, such as time-boxed deep research, an
+//! edit-and-test coding loop, form filling by conversation and routing
+//! between knowledge-base and tool answers.
+
+pub mod research;
+pub mod code;
+pub mod form;
+pub mod router;
+
+pub use research::{deep_research, DeepResearch, ResearchBudget, ResearchNote, ResearchReport};
+pub use code::{code_task, CodeTask, CodeTaskReport};
+pub use form::{FormAgent, FormReply, Slot, SlotError, SlotType};
+pub use router::{AnswerPath, AnswerRouter, FallbackReason, RoutedAnswer};
@@ -0,0 +1,479 @@
+//! Time-boxed deep research.
+//! 
+//! This module provides `DeepResearch` and the `deep_research` shortcut,
+//! which research a topic in rounds of search, read and take notes, then
+//! write a report citing the sources it read. Each round, a lead agent plans
+//! search queries from the notes so far; the search tool finds sources, the
+//! fetch tool reads them, and a reader agent, the lead unless another is
+//! set, keeps what is relevant as a numbered note. Rounds run as cycles of
+//! an `EventLoop` and stop at the budget's depth, its source limit, its time
+//! limit, or when the lead has no more queries. The notes are kept in the
+//! lead agent's state as working memory, and the final synthesis always
+//! runs, even after the time limit, so a report is returned.
+//! 
+//! The search tool takes `{"query": ..., "max_results": ...}` and returns
+//! results with a `url` and optionally a `title`, either as an array or
+//! under `results`. The fetch tool takes `{"url": ...}` and returns the page
+//! text, either as a string or under `content` or `text`. Tool failures are
+//! logged and the query or source is skipped.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::agent::Agent;
+use crate::event_loop::EventLoop;
+use crate::tools::registry::Tool;
+use crate::types::{Citation, IndubitablyError, IndubitablyResult};
+
+/// The default number of search rounds.
+pub const DEFAULT_RESEARCH_DEPTH: usize = 3;
+
+/// The default number of queries per round.
+pub const DEFAULT_RESEARCH_BREADTH: usize = 3;
+
+/// The default number of sources read.
+pub const DEFAULT_RESEARCH_MAX_SOURCES: usize = 12;
+
+/// The agent state key holding the research notes.
+pub const RESEARCH_NOTES_KEY: &str = "research_notes";
+
+/// The most characters of a page given to the reader.
+const MAX_PAGE_CHARS: usize = 12_000;
+
+/// The reader's reply for a source with nothing relevant.
+const IRRELEVANT_SOURCE: &str = "NONE";
+
+/// The limits of a research run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResearchBudget {
+    /// The time allowed for searching and reading, before the synthesis.
+    pub time_limit: Duration,
+    /// The most search rounds.
+    pub depth: usize,
+    /// The most queries per round and results read per query.
+    pub breadth: usize,
+    /// The most sources read.
+    pub max_sources: usize,
+}
+
+impl ResearchBudget {
+    /// Create a budget with the given time limit and the default depth, breadth and source limit.
+    pub fn new(time_limit: Duration) -> Self {
+        Self {
+            time_limit,
+            depth: DEFAULT_RESEARCH_DEPTH,
+            breadth: DEFAULT_RESEARCH_BREADTH,
+            max_sources: DEFAULT_RESEARCH_MAX_SOURCES,
+        }
+    }
+
+    /// Set the most search rounds.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Set the most queries per round and results read per query.
+    pub fn with_breadth(mut self, breadth: usize) -> Self {
+        self.breadth = breadth.max(1);
+        self
+    }
+
+    /// Set the most sources read.
+    pub fn with_max_sources(mut self, max_sources: usize) -> Self {
+        self.max_sources = max_sources.max(1);
+        self
+    }
+}
+
+/// A note taken from one source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResearchNote {
+    /// The source's citation marker, such as `S1`.
+    pub source_id: String,
+    /// The source URL.
+    pub url: String,
+    /// The source title, if the search returned one.
+    pub title: Option<String>,
+    /// The query that found the source.
+    pub query: String,
+    /// The round the source was read in, starting at 1.
+    pub round: usize,
+    /// What the reader kept from the source.
+    pub note: String,
+}
+
+/// The outcome of a research run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResearchReport {
+    /// The researched topic.
+    pub topic: String,
+    /// The final report, citing sources as `[S1]`.
+    pub report: String,
+    /// The notes taken, in the order the sources were read.
+    pub notes: Vec<ResearchNote>,
+    /// The sources the report cites.
+    pub citations: Vec<Citation>,
+    /// The queries run, in order.
+    pub queries: Vec<String>,
+    /// The number of rounds run.
+    pub rounds: usize,
+    /// Whether the time limit cut the research short.
+    pub timed_out: bool,
+    /// How long the run took, including the synthesis.
+    pub duration: Duration,
+}
+
+/// An iterative search, read and synthesize routine around a lead agent.
+pub struct DeepResearch {
+    lead: Agent,
+    reader: Option<Agent>,
+    search: Tool,
+    fetch: Tool,
+}
+
+impl DeepResearch {
+    /// Create a routine planning and writing with the lead agent, using the given search and fetch tools.
+    pub fn new(lead: Agent, search: Tool, fetch: Tool) -> Self {
+        Self {
+            lead,
+            reader: None,
+            search,
+            fetch,
+        }
+    }
+
+    /// Take notes with a separate reader agent, such as one on a cheaper model.
+    pub fn with_reader(mut self, reader: Agent) -> Self {
+        self.reader = Some(reader);
+        self
+    }
+
+    /// Get the lead agent, whose state holds the notes of the last run.
+    pub fn lead(&self) -> &Agent {
+        &self.lead
+    }
+
+    /// Research a topic within a budget.
+    pub async fn run(&mut self, topic: &str, budget: ResearchBudget) -> IndubitablyResult<ResearchReport> {
+        if topic.trim().is_empty() {
+            return Err(IndubitablyError::ValidationError("A research topic is required".to_string()));
+        }
+        let started = Instant::now();
+        let mut event_loop = EventLoop::with_max_iterations(budget.depth).with_label("pattern", "deep_research");
+        let mut notes: Vec<ResearchNote> = Vec::new();
+        let mut queries: Vec<String> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut timed_out = false;
+        let mut rounds = 0;
+
+        'rounds: for round in 1..=budget.depth {
+            if notes.len() >= budget.max_sources {
+                break;
+            }
+            let plan = self
+                .within(started, budget, |this| this.plan(topic, round, budget.breadth, &notes, &queries))
+                .await?;
+            let Some(planned) = plan else {
+                timed_out = true;
+                break;
+            };
+            let planned: Vec<String> = planned.into_iter().filter(|query| !queries.contains(query)).collect();
+            if planned.is_empty() {
+                break;
+            }
+            event_loop.cycle(&Vec::new()).await?;
+            rounds = round;
+
+            for query in planned {
+                queries.push(query.clone());
+                let search = self.search.execute_async(json!({ "query": query, "max_results": budget.breadth }));
+                let hits = match search.await {
+                    Ok(output) => search_hits(&output),
+                    Err(e) => {
+                        tracing::warn!("query=<{}>, error=<{}> | research search failed", query, e);
+                        continue;
+                    }
+                };
+                for (url, title) in hits.into_iter().take(budget.breadth) {
+                    if notes.len() >= budget.max_sources {
+                        break 'rounds;
+                    }
+                    if !seen.insert(url.clone()) {
+                        continue;
+                    }
+                    let page = match self.fetch.execute_async(json!({ "url": url })).await {
+                        Ok(output) => page_text(&output),
+                        Err(e) => {
+                            tracing::warn!("url=<{}>, error=<{}> | research fetch failed", url, e);
+                            continue;
+                        }
+                    };
+                    let read = self.within(started, budget, |this| this.read(topic, &url, &page)).await?;
+                    let Some(note) = read else {
+                        timed_out = true;
+                        break 'rounds;
+                    };
+                    if let Some(note) = note {
+                        notes.push(ResearchNote {
+                            source_id: format!("S{}", notes.len() + 1),
+                            url,
+                            title,
+                            query: query.clone(),
+                            round,
+                            note,
+                        });
+                        self.lead.state_mut().set_metadata(RESEARCH_NOTES_KEY, json!(notes));
+                    }
+                }
+            }
+        }
+        event_loop.finish_cycle();
+
+        let report = self.synthesize(topic, &notes).await?;
+        let citations = cited_sources(&report, &notes);
+        tracing::debug!(
+            "topic=<{}>, rounds=<{}>, sources=<{}>, cited=<{}>, timed_out=<{}> | research finished",
+            topic,
+            rounds,
+            notes.len(),
+            citations.len(),
+            timed_out
+        );
+        Ok(ResearchReport {
+            topic: topic.to_string(),
+            report,
+            notes,
+            citations,
+            queries,
+            rounds,
+            timed_out,
+            duration: started.elapsed(),
+        })
+    }
+
+    /// Run a step within the time left, returning `None` if the time runs out.
+    async fn within<'a, T, F, Fut>(
+        &'a mut self,
+        started: Instant,
+        budget: ResearchBudget,
+        step: F,
+    ) -> IndubitablyResult<Option<T>>
+    where
+        F: FnOnce(&'a mut Self) -> Fut,
+        Fut: std::future::Future<Output = IndubitablyResult<T>> + 'a,
+    {
+        let remaining = budget.time_limit.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+        match tokio::time::timeout(remaining, step(self)).await {
+            Ok(result) => result.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Ask the lead for the next queries, given the notes so far.
+    async fn plan(
+        &mut self,
+        topic: &str,
+        round: usize,
+        breadth: usize,
+        notes: &[ResearchNote],
+        queries: &[String],
+    ) -> IndubitablyResult<Vec<String>> {
+        let prompt = format!(
+            "You are researching: {}\n\nRound {}. Queries already run:\n{}\nNotes so far:\n{}\n\
+             Propose up to {} new web search queries that fill the gaps in the notes. \
+             Reply with JSON only, in the form {{\"queries\": [\"...\"]}}, \
+             or {{\"queries\": []}} if the notes suffice.",
+            topic,
+            round,
+            bullet_list(queries),
+            format_notes(notes),
+            breadth
+        );
+        let reply = self.lead.run(&prompt).await?.response;
+        Ok(parse_queries(&reply).into_iter().take(breadth).collect())
+    }
+
+    /// Ask the reader for a note on a source, or `None` if nothing in it is relevant.
+    async fn read(&mut self, topic: &str, url: &str, page: &str) -> IndubitablyResult<Option<String>> {
+        let page: String = page.chars().take(MAX_PAGE_CHARS).collect();
+        let prompt = format!(
+            "You are taking notes for research on: {}\n\nSource: {}\n\n{}\n\n\
+             Write concise notes of the facts in this source relevant to the topic. \
+             Reply with {} only if nothing is relevant.",
+            topic, url, page, IRRELEVANT_SOURCE
+        );
+        let reader = self.reader.as_mut().unwrap_or(&mut self.lead);
+        let note = reader.run(&prompt).await?.response;
+        let note = note.trim();
+        Ok((!note.is_empty() && note != IRRELEVANT_SOURCE).then(|| note.to_string()))
+    }
+
+    /// Ask the lead for the report from the notes.
+    async fn synthesize(&mut self, topic: &str, notes: &[ResearchNote]) -> IndubitablyResult<String> {
+        let prompt = format!(
+            "Write a research report on: {}\n\nNotes, each marked with its source:\n{}\n\
+             Use only these notes. Cite the sources supporting each claim with their markers, such as [S1]. \
+             Say where the notes leave the topic unresolved.",
+            topic,
+            format_notes(notes)
+        );
+        Ok(self.lead.run(&prompt).await?.response)
+    }
+}
+
+/// Research a topic with a lead agent and search and fetch tools within a budget.
+pub async fn deep_research(
+    lead: Agent,
+    search: Tool,
+    fetch: Tool,
+    topic: &str,
+    budget: ResearchBudget,
+) -> IndubitablyResult<ResearchReport> {
+    DeepResearch::new(lead, search, fetch).run(topic, budget).await
+}
+
+/// Format items as a bulleted list, or `(none)`.
+fn bullet_list(items: &[String]) -> String {
+    if items.is_empty() {
+        return "(none)\n".to_string();
+    }
+    items.iter().map(|item| format!("- {}\n", item)).collect()
+}
+
+/// Format notes with their source markers, or `(none)`.
+fn format_notes(notes: &[ResearchNote]) -> String {
+    if notes.is_empty() {
+        return "(none)\n".to_string();
+    }
+    notes
+        .iter()
+        .map(|note| {
+            let title = note.title.as_deref().unwrap_or(&note.url);
+            format!("[{}] {} ({})\n{}\n", note.source_id, title, note.url, note.note)
+        })
+        .collect()
+}
+
+/// Parse a `{"queries": [...]}` object out of a lead reply.
+fn parse_queries(reply: &str) -> Vec<String> {
+    let (Some(start), Some(end)) = (reply.find('{'), reply.rfind('}')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+
+    #[derive(Deserialize)]
+    struct Queries {
+        queries: Vec<String>,
+    }
+
+    match serde_json::from_str::<Queries>(&reply[start..=end]) {
+        Ok(parsed) => parsed
+            .queries
+            .into_iter()
+            .map(|query| query.trim().to_string())
+            .filter(|query| !query.is_empty())
+            .collect(),
+        Err(_) => {
+            tracing::warn!("reply=<{}> | could not parse research queries from lead", reply);
+            Vec::new()
+        }
+    }
+}
+
+/// Get the URL and title of each search result.
+fn search_hits(output: &Value) -> Vec<(String, Option<String>)> {
+    output
+        .as_array()
+        .or_else(|| output.get("results").and_then(Value::as_array))
+        .into_iter()
+        .flatten()
+        .filter_map(|hit| {
+            let url = hit.get("url").and_then(Value::as_str)?;
+            let title = hit.get("title").and_then(Value::as_str).map(str::to_string);
+            Some((url.to_string(), title))
+        })
+        .collect()
+}
+
+/// Get the text of a fetched page.
+fn page_text(output: &Value) -> String {
+    match output {
+        Value::String(text) => text.clone(),
+        _ => ["content", "text"]
+            .iter()
+            .find_map(|key| output.get(*key).and_then(Value::as_str))
+            .map(str::to_string)
+            .unwrap_or_else(|| output.to_string()),
+    }
+}
+
+/// Get citations for the sources the report cites, in note order.
+fn cited_sources(report: &str, notes: &[ResearchNote]) -> Vec<Citation> {
+    notes
+        .iter()
+        .filter(|note| report.contains(&format!("[{}]", note.source_id)))
+        .map(|note| {
+            let citation = Citation::new(&note.source_id).with_uri(&note.url);
+            match note.title {
+                Some(ref title) => citation.with_title(title),
+                None => citation,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::agent::AgentBuilder;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    fn scripted_agent(replies: &[&str]) -> Agent {
+        let model = MockModel::new()
+            .with_responses(replies.iter().map(|reply| ModelResponse::new(reply)).collect());
+        AgentBuilder::new().model(Box::new(model)).build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_deep_research_reads_sources_and_cites_them() {
+        let lead = scripted_agent(&[
+            "{\"queries\": [\"rust async runtimes\"]}",
+            "Plan: {\"queries\": []}",
+            "Tokio is the most used runtime [S1].",
+        ]);
+        let reader = scripted_agent(&["Tokio is widely used.", "NONE"]);
+        let search = Tool::new("search", "Search the web", Arc::new(|_| {
+            Ok(json!({ "results": [
+                { "url": "https://tokio.rs", "title": "Tokio" },
+                { "url": "https://example.com/ads" },
+                { "url": "https://tokio.rs" },
+            ]}))
+        }));
+        let fetch = Tool::new("fetch", "Fetch a page", Arc::new(|input: Value| {
+            Ok(json!({ "content": format!("Page at {}", input["url"]) }))
+        }));
+        let mut research = DeepResearch::new(lead, search, fetch).with_reader(reader);
+
+        let report = research.run("Rust async", ResearchBudget::new(Duration::from_secs(30))).await.unwrap();
+
+        assert_eq!(report.queries, vec!["rust async runtimes"]);
+        assert_eq!(report.rounds, 1);
+        assert!(!report.timed_out);
+        assert_eq!(report.notes.len(), 1);
+        assert_eq!(report.notes[0].note, "Tokio is widely used.");
+        assert_eq!(report.citations.len(), 1);
+        assert_eq!(report.citations[0].uri.as_deref(), Some("https://tokio.rs"));
+        assert_eq!(report.report, "Tokio is the most used runtime [S1].");
+        assert!(research.lead().state().get_metadata(RESEARCH_NOTES_KEY).is_some());
+    }
+}
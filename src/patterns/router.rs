@@ -0,0 +1,265 @@
+//! Routing between retrieval-grounded answers and tool answers.
+//! 
+//! This module provides `AnswerRouter`, which answers a question from a
+//! knowledge base when retrieval is confident and falls back to tool use,
+//! such as web search, when it is not. The question is first given to a
+//! `Retriever`; if the best chunk's similarity reaches the threshold, the
+//! agent answers from the retrieved chunks alone, citing them. If no chunk
+//! scores high enough, or the agent finds that the chunks do not hold the
+//! answer, the question goes to the fallback agent with its tools. Every
+//! answer records the path taken, why, and the retrieval scores.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::Agent;
+use crate::docs::retrieval::{Retriever, ScoredChunk};
+use crate::types::{Citation, IndubitablyResult};
+
+/// The default lowest similarity for answering from retrieved chunks.
+pub const DEFAULT_RETRIEVAL_THRESHOLD: f32 = 0.75;
+
+/// The default number of chunks retrieved per question.
+pub const DEFAULT_RETRIEVAL_TOP_K: usize = 4;
+
+/// The reply of an agent whose retrieved chunks do not hold the answer.
+const NOT_IN_CONTEXT: &str = "NOT_IN_CONTEXT";
+
+/// How a question was answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerPath {
+    /// From retrieved chunks.
+    Retrieval,
+    /// By the fallback agent with its tools.
+    Fallback,
+}
+
+/// Why a question was not answered from retrieved chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackReason {
+    /// Nothing was retrieved.
+    NoChunks,
+    /// The best chunk scored below the threshold.
+    LowConfidence,
+    /// The agent found that the retrieved chunks do not hold the answer.
+    NotInContext,
+}
+
+/// An answer with the path that produced it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutedAnswer {
+    /// The question.
+    pub question: String,
+    /// The answer.
+    pub answer: String,
+    /// The path that produced the answer.
+    pub path: AnswerPath,
+    /// Why retrieval was not used, if it was not.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_reason: Option<FallbackReason>,
+    /// The threshold the best score was compared with.
+    pub threshold: f32,
+    /// The scores of the retrieved chunks, highest first.
+    pub scores: Vec<f32>,
+    /// The chunks the answer cites, for retrieval answers.
+    pub citations: Vec<Citation>,
+    /// The tools the fallback agent called, in order.
+    pub tools_used: Vec<String>,
+}
+
+impl RoutedAnswer {
+    /// Get the best retrieval score, if anything was retrieved.
+    pub fn top_score(&self) -> Option<f32> {
+        self.scores.first().copied()
+    }
+}
+
+/// Answers from a knowledge base when retrieval is confident, and with tools otherwise.
+pub struct AnswerRouter {
+    agent: Agent,
+    fallback: Option<Agent>,
+    retriever: Arc<dyn Retriever>,
+    threshold: f32,
+    top_k: usize,
+}
+
+impl AnswerRouter {
+    /// Create a router answering with the agent, from the retriever's chunks or else with the agent's tools.
+    pub fn new(agent: Agent, retriever: Arc<dyn Retriever>) -> Self {
+        Self {
+            agent,
+            fallback: None,
+            retriever,
+            threshold: DEFAULT_RETRIEVAL_THRESHOLD,
+            top_k: DEFAULT_RETRIEVAL_TOP_K,
+        }
+    }
+
+    /// Answer fallback questions with a separate agent, such as one with web search tools.
+    pub fn with_fallback(mut self, agent: Agent) -> Self {
+        self.fallback = Some(agent);
+        self
+    }
+
+    /// Set the lowest similarity of the best chunk for answering from retrieval.
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the number of chunks retrieved per question.
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k.max(1);
+        self
+    }
+
+    /// Answer a question, from retrieved chunks if they are relevant enough and with tools otherwise.
+    pub async fn answer(&mut self, question: &str) -> IndubitablyResult<RoutedAnswer> {
+        let chunks = self.retriever.retrieve(question, self.top_k).await?;
+        let scores: Vec<f32> = chunks.iter().map(|chunk| chunk.score).collect();
+        let reason = match scores.first() {
+            None => Some(FallbackReason::NoChunks),
+            Some(&top) if top < self.threshold => Some(FallbackReason::LowConfidence),
+            Some(_) => None,
+        };
+
+        let mut routed = RoutedAnswer {
+            question: question.to_string(),
+            answer: String::new(),
+            path: AnswerPath::Retrieval,
+            fallback_reason: reason,
+            threshold: self.threshold,
+            scores,
+            citations: Vec::new(),
+            tools_used: Vec::new(),
+        };
+        if reason.is_none() {
+            let relevant: Vec<&ScoredChunk> = chunks.iter().filter(|chunk| chunk.score >= self.threshold).collect();
+            let answer = self.agent.run(&grounded_prompt(question, &relevant)).await?.response;
+            if !answer.contains(NOT_IN_CONTEXT) {
+                routed.citations = cited_chunks(&answer, &relevant);
+                routed.answer = answer;
+                self.log(&routed);
+                return Ok(routed);
+            }
+            routed.fallback_reason = Some(FallbackReason::NotInContext);
+        }
+
+        let prompt = format!(
+            "Answer the question below. The knowledge base had no reliable answer, so use your tools, \
+             such as search, to find one, and say where it comes from.\n\nQuestion: {}",
+            question
+        );
+        let agent = self.fallback.as_mut().unwrap_or(&mut self.agent);
+        let result = agent.run(&prompt).await?;
+        routed.path = AnswerPath::Fallback;
+        routed.tools_used = result.tool_calls.iter().map(|call| call.name.clone()).collect();
+        routed.citations = result.citations;
+        routed.answer = result.response;
+        self.log(&routed);
+        Ok(routed)
+    }
+
+    fn log(&self, routed: &RoutedAnswer) {
+        tracing::debug!(
+            "path=<{:?}>, reason=<{:?}>, top_score=<{:?}>, threshold=<{}> | routed question",
+            routed.path,
+            routed.fallback_reason,
+            routed.top_score(),
+            routed.threshold
+        );
+    }
+}
+
+/// Build the prompt asking for an answer from the retrieved chunks alone.
+fn grounded_prompt(question: &str, chunks: &[&ScoredChunk]) -> String {
+    let context: String = chunks
+        .iter()
+        .enumerate()
+        .map(|(index, scored)| format!("[C{}] {}\n{}\n\n", index + 1, scored.chunk.document, scored.chunk.text))
+        .collect();
+    format!(
+        "Answer the question using only the context below, citing the passages you use as [C1], [C2] and so on. \
+         If the context does not contain the answer, reply with {} only.\n\nContext:\n{}Question: {}",
+        NOT_IN_CONTEXT, context, question
+    )
+}
+
+/// Get citations for the chunks an answer cites, in retrieval order.
+fn cited_chunks(answer: &str, chunks: &[&ScoredChunk]) -> Vec<Citation> {
+    chunks
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| answer.contains(&format!("[C{}]", index + 1)))
+        .map(|(_, scored)| scored.chunk.citation())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::agent::AgentBuilder;
+    use crate::docs::document::DocumentChunk;
+    use crate::docs::retrieval::EmbeddingRetriever;
+    use crate::models::model::{MockModel, ModelResponse};
+    use crate::tools::selector::Embedder;
+
+    struct TopicEmbedder;
+
+    #[async_trait]
+    impl Embedder for TopicEmbedder {
+        async fn embed(&self, texts: &[String]) -> IndubitablyResult<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    ["refund", "shipping", "weather"].iter().map(|w| text.matches(w).count() as f32).collect()
+                })
+                .collect())
+        }
+    }
+
+    fn chunk(id: &str, text: &str) -> DocumentChunk {
+        DocumentChunk {
+            id: id.to_string(),
+            document: "policy.pdf".to_string(),
+            index: 0,
+            text: text.to_string(),
+            page: 1,
+            bbox: None,
+            location: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_router_falls_back_below_threshold() {
+        let retriever = Arc::new(EmbeddingRetriever::new(Arc::new(TopicEmbedder)));
+        retriever
+            .add_chunks(vec![
+                chunk("refunds", "Refund requests are accepted within 30 days."),
+                chunk("shipping", "Shipping takes 3 to 5 days."),
+            ])
+            .await
+            .unwrap();
+        let model = MockModel::new().with_responses(vec![
+            ModelResponse::new("Refunds are accepted within 30 days [C1]."),
+            ModelResponse::new("It is sunny in Oslo."),
+        ]);
+        let agent = AgentBuilder::new().model(Box::new(model)).build().unwrap();
+        let mut router = AnswerRouter::new(agent, retriever);
+
+        let grounded = router.answer("How long do I have for a refund?").await.unwrap();
+        assert_eq!(grounded.path, AnswerPath::Retrieval);
+        assert_eq!(grounded.top_score(), Some(1.0));
+        assert_eq!(grounded.citations[0].source_id, "refunds");
+
+        let fallback = router.answer("What is the weather in Oslo?").await.unwrap();
+        assert_eq!(fallback.path, AnswerPath::Fallback);
+        assert_eq!(fallback.fallback_reason, Some(FallbackReason::LowConfidence));
+        assert_eq!(fallback.answer, "It is sunny in Oslo.");
+    }
+}
@@ -0,0 +1,8 @@
+//! Privacy compliance for the SDK.
+//! 
+//! This module provides the tools needed to honour users' data rights,
+//! such as purging everything stored about a user on request.
+
+pub mod purge;
+
+pub use purge::{purge_user, DataKind, DeletionReport, PrivacyBackends, PurgeItem, PurgeMode};
@@ -0,0 +1,388 @@
+//! User data purge for the SDK.
+//! 
+//! This module provides `purge_user`, which removes everything tied to a
+//! user from the configured backends: sessions recorded with the user's id,
+//! the feedback stored on them, artifacts those sessions reference, user
+//! memory entries and audit events carrying the user's id. Sessions and
+//! audit events can be deleted or anonymized; memories and artifacts are
+//! always deleted. A backend failing does not stop the others, so the
+//! returned `DeletionReport` lists what was removed and what still needs
+//! attention.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::session::user_memory::UserMemory;
+use crate::session::SessionManager;
+use crate::telemetry::events::EventRecorder;
+use crate::tools::artifacts::ArtifactStore;
+use crate::types::{IndubitablyError, IndubitablyResult, Session, FEEDBACK_METADATA_KEY, USER_ID_METADATA_KEY};
+
+/// The text replacing anonymized message content and user ids.
+pub const ANONYMIZED: &str = "[anonymized]";
+
+/// Whether user data is deleted or anonymized where both are possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeMode {
+    /// Delete sessions and audit events.
+    Delete,
+    /// Keep sessions and audit events for aggregate statistics, stripped of personal data.
+    Anonymize,
+}
+
+/// The kinds of data a purge covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataKind {
+    /// Recorded sessions.
+    Sessions,
+    /// Feedback stored on sessions.
+    Feedback,
+    /// Artifacts referenced by sessions.
+    Artifacts,
+    /// User memory entries.
+    Memories,
+    /// Audit events.
+    AuditEntries,
+}
+
+/// What one backend removed for one kind of data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PurgeItem {
+    /// The kind of data.
+    pub kind: DataKind,
+    /// The backend the data was removed from.
+    pub backend: String,
+    /// The number of records deleted.
+    pub deleted: usize,
+    /// The number of records anonymized.
+    pub anonymized: usize,
+    /// The error that stopped the backend, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PurgeItem {
+    fn new(kind: DataKind, backend: &str) -> Self {
+        Self {
+            kind,
+            backend: backend.to_string(),
+            deleted: 0,
+            anonymized: 0,
+            error: None,
+        }
+    }
+
+    fn failed(mut self, error: &IndubitablyError) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+/// The outcome of purging a user's data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeletionReport {
+    /// The user whose data was purged.
+    pub user_id: String,
+    /// Whether data was deleted or anonymized.
+    pub mode: PurgeMode,
+    /// When the purge started.
+    pub started_at: DateTime<Utc>,
+    /// When the purge finished.
+    pub completed_at: DateTime<Utc>,
+    /// What each backend removed.
+    pub items: Vec<PurgeItem>,
+}
+
+impl DeletionReport {
+    /// Get the number of records of a kind deleted across backends.
+    pub fn deleted(&self, kind: DataKind) -> usize {
+        self.items.iter().filter(|item| item.kind == kind).map(|item| item.deleted).sum()
+    }
+
+    /// Get the number of records of a kind anonymized across backends.
+    pub fn anonymized(&self, kind: DataKind) -> usize {
+        self.items.iter().filter(|item| item.kind == kind).map(|item| item.anonymized).sum()
+    }
+
+    /// Check whether every backend was purged without error.
+    pub fn is_complete(&self) -> bool {
+        self.items.iter().all(|item| item.error.is_none())
+    }
+
+    /// Get the items whose backend failed.
+    pub fn failures(&self) -> Vec<&PurgeItem> {
+        self.items.iter().filter(|item| item.error.is_some()).collect()
+    }
+}
+
+/// A session manager shared between purges.
+type SharedSessionManager = Arc<Mutex<Box<dyn SessionManager>>>;
+
+/// The backends holding user data.
+#[derive(Clone)]
+pub struct PrivacyBackends {
+    mode: PurgeMode,
+    session_managers: Vec<(String, SharedSessionManager)>,
+    memories: Vec<UserMemory>,
+    artifact_stores: Vec<Arc<dyn ArtifactStore>>,
+    audit_logs: Vec<EventRecorder>,
+}
+
+impl std::fmt::Debug for PrivacyBackends {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivacyBackends")
+            .field("mode", &self.mode)
+            .field("session_managers", &self.session_managers.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .field("memories", &self.memories.len())
+            .field("artifact_stores", &self.artifact_stores)
+            .field("audit_logs", &self.audit_logs.len())
+            .finish()
+    }
+}
+
+impl Default for PrivacyBackends {
+    fn default() -> Self {
+        Self {
+            mode: PurgeMode::Delete,
+            session_managers: Vec::new(),
+            memories: Vec::new(),
+            artifact_stores: Vec::new(),
+            audit_logs: Vec::new(),
+        }
+    }
+}
+
+impl PrivacyBackends {
+    /// Create an empty set of backends that deletes data.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether sessions and audit events are deleted or anonymized.
+    pub fn with_mode(mut self, mode: PurgeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Add a session manager, named in the report.
+    pub fn with_session_manager(mut self, name: &str, manager: Box<dyn SessionManager>) -> Self {
+        self.session_managers.push((name.to_string(), Arc::new(Mutex::new(manager))));
+        self
+    }
+
+    /// Add a user memory.
+    pub fn with_user_memory(mut self, memory: UserMemory) -> Self {
+        self.memories.push(memory);
+        self
+    }
+
+    /// Add an artifact store holding artifacts referenced by sessions.
+    pub fn with_artifact_store(mut self, store: Arc<dyn ArtifactStore>) -> Self {
+        self.artifact_stores.push(store);
+        self
+    }
+
+    /// Add an audit log of lifecycle events.
+    pub fn with_audit_log(mut self, recorder: EventRecorder) -> Self {
+        self.audit_logs.push(recorder);
+        self
+    }
+}
+
+/// Delete or anonymize all data tied to a user across the configured backends.
+pub async fn purge_user(user_id: &str, backends: &PrivacyBackends) -> IndubitablyResult<DeletionReport> {
+    if user_id.is_empty() {
+        return Err(IndubitablyError::ValidationError("Cannot purge an empty user id".to_string()));
+    }
+    tracing::info!("user_id=<{}>, mode=<{:?}> | purging user data", user_id, backends.mode);
+    let started_at = Utc::now();
+    let mut items = Vec::new();
+
+    let mut artifact_handles = BTreeSet::new();
+    for (name, manager) in &backends.session_managers {
+        let mut manager = manager.lock().await;
+        let (sessions, feedback) = purge_sessions(manager.as_mut(), name, user_id, backends.mode, &mut artifact_handles).await;
+        items.push(sessions);
+        items.push(feedback);
+    }
+
+    for (index, store) in backends.artifact_stores.iter().enumerate() {
+        let mut item = PurgeItem::new(DataKind::Artifacts, &format!("artifacts-{}", index));
+        for handle in &artifact_handles {
+            if store.size(handle).is_err() {
+                continue;
+            }
+            match store.delete(handle) {
+                Ok(()) => item.deleted += 1,
+                Err(e) => item = item.failed(&e),
+            }
+        }
+        items.push(item);
+    }
+
+    for (index, memory) in backends.memories.iter().enumerate() {
+        let item = PurgeItem::new(DataKind::Memories, &format!("memory-{}", index));
+        items.push(match memory.delete_user(user_id).await {
+            Ok(deleted) => PurgeItem { deleted, ..item },
+            Err(e) => item.failed(&e),
+        });
+    }
+
+    for (index, recorder) in backends.audit_logs.iter().enumerate() {
+        let mut item = PurgeItem::new(DataKind::AuditEntries, &format!("audit-{}", index));
+        let mut anonymized = 0;
+        item.deleted = recorder.retain(|event| {
+            if event.get(USER_ID_METADATA_KEY).and_then(Value::as_str) != Some(user_id) {
+                return true;
+            }
+            if backends.mode == PurgeMode::Delete {
+                return false;
+            }
+            event.data[USER_ID_METADATA_KEY] = Value::String(ANONYMIZED.to_string());
+            anonymized += 1;
+            true
+        });
+        item.anonymized = anonymized;
+        items.push(item);
+    }
+
+    let report = DeletionReport {
+        user_id: user_id.to_string(),
+        mode: backends.mode,
+        started_at,
+        completed_at: Utc::now(),
+        items,
+    };
+    if !report.is_complete() {
+        tracing::warn!("user_id=<{}>, failures=<{}> | user data purge incomplete", user_id, report.failures().len());
+    }
+    Ok(report)
+}
+
+/// Purge a user's sessions from one manager, collecting the artifacts they reference.
+async fn purge_sessions(
+    manager: &mut dyn SessionManager,
+    name: &str,
+    user_id: &str,
+    mode: PurgeMode,
+    artifact_handles: &mut BTreeSet<String>,
+) -> (PurgeItem, PurgeItem) {
+    let mut sessions_item = PurgeItem::new(DataKind::Sessions, name);
+    let mut feedback_item = PurgeItem::new(DataKind::Feedback, name);
+    let sessions = match manager.list_sessions().await {
+        Ok(sessions) => sessions,
+        Err(e) => return (sessions_item.failed(&e), feedback_item),
+    };
+    let handle_pattern = Regex::new(r"artifact-[0-9a-f]{32}").expect("artifact handle pattern is valid");
+
+    for session in sessions.into_iter().filter(|session| session.user_id() == Some(user_id)) {
+        for message in &session.messages {
+            artifact_handles.extend(handle_pattern.find_iter(&message.content).map(|found| found.as_str().to_string()));
+        }
+        let feedback = session.feedback().len();
+        let result = match mode {
+            PurgeMode::Delete => manager.delete_session(&session.id).await,
+            PurgeMode::Anonymize => manager.update_session(anonymize_session(session.clone())).await,
+        };
+        match (result, mode) {
+            (Ok(()), PurgeMode::Delete) => {
+                sessions_item.deleted += 1;
+                feedback_item.deleted += feedback;
+            }
+            (Ok(()), PurgeMode::Anonymize) => {
+                sessions_item.anonymized += 1;
+                feedback_item.anonymized += feedback;
+            }
+            (Err(e), _) => {
+                tracing::warn!("session_id=<{}>, error=<{}> | failed to purge session", session.id, e);
+                sessions_item = sessions_item.failed(&e);
+            }
+        }
+    }
+    (sessions_item, feedback_item)
+}
+
+/// Strip a session of message content, metadata and feedback text, keeping its shape and ratings.
+fn anonymize_session(mut session: Session) -> Session {
+    for message in &mut session.messages {
+        message.content = ANONYMIZED.to_string();
+        message.metadata = None;
+    }
+    let mut feedback = session.feedback();
+    for record in &mut feedback {
+        record.comment = None;
+        record.correction = None;
+    }
+    if let Some(ref mut metadata) = session.metadata {
+        metadata.remove(USER_ID_METADATA_KEY);
+    }
+    if !feedback.is_empty() {
+        session.add_metadata(FEEDBACK_METADATA_KEY, serde_json::json!(feedback));
+    }
+    session.updated_at = Utc::now();
+    session
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::FileSessionManager;
+    use crate::telemetry::events::{EventSubscriber, LifecycleEvent, LifecycleEventKind};
+    use crate::tools::artifacts::InMemoryArtifactStore;
+    use crate::types::{Feedback, FeedbackRating, SessionAgent, SessionMessage, SessionType};
+
+    #[tokio::test]
+    async fn test_purge_user_across_backends() {
+        let directory = tempfile::tempdir().unwrap();
+        let store: Arc<dyn ArtifactStore> = Arc::new(InMemoryArtifactStore::new());
+        let handle = store.put(b"payroll.csv", "text/csv").unwrap();
+        let mut sessions = FileSessionManager::new(directory.path().to_str().unwrap());
+        for (id, user) in [("s1", "alice"), ("s2", "bob")] {
+            let mut session = Session::new(id, SessionType::Conversation, SessionAgent::new("a", "Agent")).with_user_id(user);
+            session.add_message(SessionMessage::new("m1", "tool", &format!("stored as artifact '{}'", handle)));
+            session.add_feedback(Feedback::new(id, "m1", FeedbackRating::ThumbsUp));
+            sessions.create_session(session).await.unwrap();
+        }
+        let memory = UserMemory::in_memory();
+        memory.add("alice", "Lives in Oslo").await.unwrap();
+        let audit = EventRecorder::new();
+        for user in ["alice", "bob"] {
+            let event = LifecycleEvent::new(LifecycleEventKind::RunStarted, "agent", serde_json::json!({ "user_id": user }));
+            audit.on_event(&event).await.unwrap();
+        }
+        let backends = PrivacyBackends::new()
+            .with_session_manager("files", Box::new(FileSessionManager::new(directory.path().to_str().unwrap())))
+            .with_artifact_store(store.clone())
+            .with_user_memory(memory.clone())
+            .with_audit_log(audit.clone());
+
+        let report = purge_user("alice", &backends).await.unwrap();
+
+        assert!(report.is_complete());
+        assert_eq!(report.deleted(DataKind::Sessions), 1);
+        assert_eq!(report.deleted(DataKind::Feedback), 1);
+        assert_eq!(report.deleted(DataKind::Artifacts), 1);
+        assert_eq!(report.deleted(DataKind::Memories), 1);
+        assert_eq!(report.deleted(DataKind::AuditEntries), 1);
+        assert!(sessions.get_session("s1").await.unwrap().is_none());
+        assert!(sessions.get_session("s2").await.unwrap().is_some());
+        assert!(store.size(&handle).is_err());
+        assert_eq!(audit.events()[0].data["user_id"], "bob");
+
+        let report = purge_user("bob", &backends.with_mode(PurgeMode::Anonymize)).await.unwrap();
+        assert_eq!(report.anonymized(DataKind::Sessions), 1);
+        let anonymized = sessions.get_session("s2").await.unwrap().unwrap();
+        assert_eq!(anonymized.user_id(), None);
+        assert_eq!(anonymized.messages[0].content, ANONYMIZED);
+        assert_eq!(anonymized.rating(), Some(FeedbackRating::ThumbsUp));
+        assert_eq!(audit.events()[0].data["user_id"], ANONYMIZED);
+    }
+}
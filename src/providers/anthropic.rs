@@ -0,0 +1,885 @@
+//! Anthropic model implementation for the SDK.
+//! 
+//! This module provides integration with Anthropic's Messages API for
+//! accessing Claude models. System prompts, tool use and tool result blocks
+//! and extended thinking are mapped to and from the SDK's types; thinking
+//! blocks become `ReasoningContentBlock`s and keep their signatures so they
+//! can be replayed on the next turn. Streamed responses arrive as
+//! server-sent events and are translated into `StreamEvent`s.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::models::http::{HttpClient, HttpRequest, HttpResponse, UnconfiguredHttpClient};
+use crate::models::stream_decode::{decode_all, decode_body, sse_data, LineSplitter, StreamDecoder};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::tools::repair::{parse_arguments, MALFORMED_TOOL_CALLS_METADATA_KEY};
+use crate::types::streaming::{MessageDelta, StreamContent, StreamEventPool};
+use crate::types::{
+    Citation, IndubitablyError, IndubitablyResult, MessageRole, Messages, ModelError, ReasoningContentBlock,
+    StreamEvent, ToolSpec, ToolUse,
+};
+
+/// Default Anthropic model ID.
+pub const DEFAULT_ANTHROPIC_MODEL_ID: &str = "claude-3-sonnet-20240229";
+
+/// The base URL of the Anthropic API.
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+
+/// The environment variable holding the Anthropic API key.
+pub const ANTHROPIC_API_KEY_ENV: &str = "ANTHROPIC_API_KEY";
+
+/// The API version sent in the `anthropic-version` header.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Configuration specific to Anthropic models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicConfig {
+    /// The Anthropic API key.
+    pub api_key: String,
+    /// The base URL of the API.
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// Whether to enable streaming.
+    pub streaming: Option<bool>,
+    /// The token budget for extended thinking, which is off when unset.
+    #[serde(default)]
+    pub thinking_budget: Option<u32>,
+    /// Additional Anthropic-specific configuration.
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: default_base_url(),
+            model_id: DEFAULT_ANTHROPIC_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: Some(1.0),
+            streaming: Some(false),
+            thinking_budget: None,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl AnthropicConfig {
+    /// Create a new Anthropic configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a configuration with the API key from `ANTHROPIC_API_KEY`.
+    pub fn from_env() -> Self {
+        Self::default().with_api_key(&std::env::var(ANTHROPIC_API_KEY_ENV).unwrap_or_default())
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Set the base URL, such as a proxy endpoint.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Enable or disable streaming.
+    pub fn with_streaming(mut self, streaming: bool) -> Self {
+        self.streaming = Some(streaming);
+        self
+    }
+
+    /// Enable extended thinking with the given token budget.
+    pub fn with_thinking_budget(mut self, budget_tokens: u32) -> Self {
+        self.thinking_budget = Some(budget_tokens);
+        self
+    }
+
+    /// Add extra configuration.
+    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+}
+
+
+fn default_base_url() -> String {
+    ANTHROPIC_BASE_URL.to_string()
+}
+
+/// Build a Messages API request body.
+///
+/// System messages join the system prompt, consecutive messages with the same
+/// role are merged since roles must alternate, and tool results are sent in
+/// user turns. Thinking is replayed only with the signature it came with.
+pub fn messages_request_body(
+    config: &ModelConfig,
+    messages: &Messages,
+    tool_specs: Option<&[ToolSpec]>,
+    system_prompt: Option<&str>,
+) -> Value {
+    let mut system: Vec<&str> = system_prompt.filter(|prompt| !prompt.is_empty()).into_iter().collect();
+    let mut wire_messages: Vec<Value> = Vec::new();
+    for message in messages {
+        let role = match message.role {
+            MessageRole::System => {
+                system.extend(message.content.iter().filter_map(|block| block.text.as_deref()));
+                continue;
+            }
+            MessageRole::Assistant => "assistant",
+            MessageRole::User | MessageRole::Tool => "user",
+        };
+        let mut content = Vec::new();
+        for block in &message.content {
+            if let Some(ref reasoning) = block.reasoning_content {
+                if !reasoning.redacted_content.is_empty() {
+                    content.push(json!({
+                        "type": "redacted_thinking",
+                        "data": String::from_utf8_lossy(&reasoning.redacted_content),
+                    }));
+                } else if let Some(ref signature) = reasoning.reasoning_text.signature {
+                    content.push(json!({
+                        "type": "thinking",
+                        "thinking": reasoning.reasoning_text.text,
+                        "signature": signature,
+                    }));
+                }
+            }
+            if let Some(text) = block.text.as_deref().filter(|text| !text.is_empty()) {
+                content.push(json!({ "type": "text", "text": text }));
+            }
+            if let Some(ref image) = block.image {
+                let source = match image.url_data() {
+                    Some(url) => Some(json!({ "type": "url", "url": url })),
+                    None => image.base64_data().map(|data| {
+                        json!({ "type": "base64", "media_type": image.source.media_type, "data": data })
+                    }),
+                };
+                if let Some(source) = source {
+                    content.push(json!({ "type": "image", "source": source }));
+                }
+            }
+            if let Some(ref document) = block.document {
+                let source = match document.text_data() {
+                    Some(text) => Some(json!({ "type": "text", "media_type": "text/plain", "data": text })),
+                    None => document.base64_data().map(|data| {
+                        json!({ "type": "base64", "media_type": document.source.media_type, "data": data })
+                    }),
+                };
+                if let Some(source) = source {
+                    content.push(json!({ "type": "document", "source": source }));
+                }
+            }
+            if let Some(ref tool_use) = block.tool_use {
+                content.push(json!({
+                    "type": "tool_use",
+                    "id": tool_use.tool_use_id,
+                    "name": tool_use.name,
+                    "input": tool_use.input.clone().unwrap_or_else(|| json!({})),
+                }));
+            }
+            if let Some(ref result) = block.tool_result {
+                let result_content: Vec<Value> = result
+                    .content
+                    .iter()
+                    .filter_map(|content| content.text.as_deref())
+                    .map(|text| json!({ "type": "text", "text": text }))
+                    .collect();
+                content.push(json!({
+                    "type": "tool_result",
+                    "tool_use_id": result.tool_use_id,
+                    "content": result_content,
+                    "is_error": result.is_error == Some(true),
+                }));
+            }
+        }
+        if content.is_empty() {
+            continue;
+        }
+        match wire_messages.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(blocks) = last["content"].as_array_mut() {
+                    blocks.extend(content);
+                }
+            }
+            _ => wire_messages.push(json!({ "role": role, "content": content })),
+        }
+    }
+
+    let mut body = json!({
+        "model": config.model_id,
+        "max_tokens": config.max_tokens.unwrap_or(4096),
+        "messages": wire_messages,
+    });
+    if !system.is_empty() {
+        body["system"] = json!(system.join("\n\n"));
+    }
+    if let Some(temperature) = config.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = config.top_p {
+        body["top_p"] = json!(top_p);
+    }
+    if let Some(top_k) = config.top_k {
+        body["top_k"] = json!(top_k);
+    }
+    if let Some(specs) = tool_specs.filter(|specs| !specs.is_empty()) {
+        let tools: Vec<Value> = specs
+            .iter()
+            .map(|spec| {
+                json!({
+                    "name": spec.name,
+                    "description": spec.description,
+                    "input_schema": spec.input_schema.clone().unwrap_or_else(|| json!({ "type": "object" })),
+                })
+            })
+            .collect();
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<ResponseBlock>,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<MessagesUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ResponseBlock {
+    Text {
+        text: String,
+        #[serde(default)]
+        citations: Vec<Value>,
+    },
+    Thinking {
+        thinking: String,
+        #[serde(default)]
+        signature: Option<String>,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct MessagesUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+/// Map a Messages API response body to a model response.
+pub fn parse_messages_response(response: &HttpResponse) -> IndubitablyResult<ModelResponse> {
+    let message: MessagesResponse = response.json()?;
+    let mut model_response = ModelResponse::new("");
+    for block in message.content {
+        match block {
+            ResponseBlock::Text { text, citations } => {
+                let start = model_response.content.len();
+                model_response.content.push_str(&text);
+                let span = (start, model_response.content.len());
+                model_response
+                    .citations
+                    .extend(citations.iter().filter_map(|citation| anthropic_citation(citation, span)));
+            }
+            ResponseBlock::Thinking { thinking, signature } => {
+                let mut block = ReasoningContentBlock::new(&thinking);
+                block.reasoning_text.signature = signature;
+                model_response.reasoning.push(block);
+            }
+            ResponseBlock::RedactedThinking { data } => {
+                let mut block = ReasoningContentBlock::new("");
+                block.redacted_content = data.into_bytes();
+                model_response.reasoning.push(block);
+            }
+            ResponseBlock::ToolUse { id, name, input } => {
+                model_response = model_response.with_tool_use(ToolUse::new(&name, &id).with_input(input));
+            }
+            ResponseBlock::Other => {}
+        }
+    }
+    if let Some(usage) = message.usage {
+        model_response = model_response.with_usage(usage.input_tokens, usage.output_tokens);
+    }
+    if let Some(reason) = message.stop_reason {
+        model_response.metadata.insert("stop_reason".to_string(), json!(reason));
+    }
+    Ok(model_response)
+}
+
+/// Map a citation of a text block to a citation of the response text the block spans.
+///
+/// Document locations are identified by document index, and web and search
+/// results by their URL or source.
+fn anthropic_citation(citation: &Value, (start, end): (usize, usize)) -> Option<Citation> {
+    let source_id = match citation["type"].as_str()? {
+        "char_location" | "page_location" | "content_block_location" => {
+            format!("document-{}", citation["document_index"].as_u64()?)
+        }
+        "web_search_result_location" => citation["url"].as_str()?.to_string(),
+        "search_result_location" => citation["source"].as_str()?.to_string(),
+        _ => return None,
+    };
+    let mut mapped = Citation::new(&source_id).with_span(start, end);
+    if let Some(uri) = citation["url"].as_str().or(citation["source"].as_str()) {
+        mapped = mapped.with_uri(uri);
+    }
+    if let Some(title) = citation["document_title"].as_str().or(citation["title"].as_str()) {
+        mapped = mapped.with_title(title);
+    }
+    if let Some(snippet) = citation["cited_text"].as_str() {
+        mapped = mapped.with_snippet(snippet);
+    }
+    Some(mapped)
+}
+
+/// Map an Anthropic error, by its type and message, to an error.
+pub fn anthropic_error(error_type: &str, message: &str) -> IndubitablyError {
+    let message = format!("Anthropic {}: {}", error_type, message);
+    IndubitablyError::ModelError(match error_type {
+        "rate_limit_error" => ModelError::ModelThrottled(message),
+        "overloaded_error" | "api_error" => ModelError::ModelNotAvailable(message),
+        "authentication_error" | "permission_error" | "not_found_error" => ModelError::InvalidConfiguration(message),
+        "invalid_request_error" if message.contains("prompt is too long") => ModelError::ContextWindowOverflow(message),
+        _ => ModelError::RequestFailed(message),
+    })
+}
+
+/// Map an unsuccessful response to an error.
+fn status_error(response: &HttpResponse) -> IndubitablyError {
+    let body: Value = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+    let error_type = body["error"]["type"].as_str().map(str::to_string).unwrap_or_else(|| match response.status {
+        429 => "rate_limit_error".to_string(),
+        529 | 503 => "overloaded_error".to_string(),
+        401 => "authentication_error".to_string(),
+        403 => "permission_error".to_string(),
+        status => format!("HTTP {}", status),
+    });
+    let message = body["error"]["message"].as_str().map(str::to_string).unwrap_or_else(|| response.text());
+    anthropic_error(&error_type, &message)
+}
+
+/// A content block being assembled from stream deltas.
+enum StreamBlock {
+    Text { started: bool },
+    Thinking,
+    Tool(ToolUse, String),
+}
+
+/// Map a streamed Messages API body of server-sent events to stream events.
+///
+/// Text and thinking are forwarded as they arrive, thinking as deltas marked
+/// with `reasoning` metadata and its signature as a final delta carrying
+/// `signature` metadata. Tool inputs arrive as JSON fragments, so each tool
+/// use is emitted once its block stops. Usage is attached to the final
+/// `message_stop` event.
+pub fn parse_messages_stream(body: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+    decode_all(MessagesStreamDecoder::default(), body)
+}
+
+/// Decodes a streamed Messages API body as it arrives; see `parse_messages_stream`.
+#[derive(Default)]
+pub struct MessagesStreamDecoder {
+    lines: LineSplitter,
+    blocks: BTreeMap<u64, StreamBlock>,
+    malformed: serde_json::Map<String, Value>,
+    input_tokens: u64,
+    output_tokens: u64,
+    stop_reason: Option<String>,
+    done: bool,
+    failed: bool,
+}
+
+impl MessagesStreamDecoder {
+    fn fail(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>, error: IndubitablyError) {
+        events.push(Err(error));
+        self.done = true;
+        self.failed = true;
+    }
+
+    fn on_data(&mut self, data: &str, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        let payload: Value = match serde_json::from_str(data) {
+            Ok(payload) => payload,
+            Err(e) => {
+                let error = ModelError::InvalidResponseFormat(format!("Invalid stream event: {}", e));
+                return self.fail(events, IndubitablyError::ModelError(error));
+            }
+        };
+        let index = payload["index"].as_u64().unwrap_or_default();
+        match payload["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                self.input_tokens = payload["message"]["usage"]["input_tokens"].as_u64().unwrap_or_default();
+                events.push(Ok(StreamEvent::message_start()));
+            }
+            "content_block_start" => {
+                let block = &payload["content_block"];
+                let stream_block = match block["type"].as_str().unwrap_or_default() {
+                    "thinking" | "redacted_thinking" => StreamBlock::Thinking,
+                    "tool_use" => {
+                        let name = block["name"].as_str().unwrap_or_default();
+                        let id = block["id"].as_str().unwrap_or_default();
+                        StreamBlock::Tool(ToolUse::new(name, id), String::new())
+                    }
+                    _ => StreamBlock::Text { started: false },
+                };
+                self.blocks.insert(index, stream_block);
+            }
+            "content_block_delta" => {
+                let delta = &payload["delta"];
+                match (self.blocks.get_mut(&index), delta["type"].as_str().unwrap_or_default()) {
+                    (Some(StreamBlock::Text { started }), "text_delta") => {
+                        let text = delta["text"].as_str().unwrap_or_default();
+                        if std::mem::replace(started, true) {
+                            events.push(Ok(StreamEventPool::shared().text_delta(text)));
+                        } else {
+                            events.push(Ok(StreamEvent::content_block_start(vec![StreamContent::text(text)])));
+                        }
+                    }
+                    (Some(StreamBlock::Thinking), "thinking_delta") => {
+                        let thinking = delta["thinking"].as_str().unwrap_or_default();
+                        events.push(Ok(StreamEventPool::shared().text_delta(thinking)
+                            .with_metadata("reasoning", json!(true))));
+                    }
+                    (Some(StreamBlock::Thinking), "signature_delta") => {
+                        events.push(Ok(StreamEvent::content_block_delta(Vec::new())
+                            .with_metadata("reasoning", json!(true))
+                            .with_metadata("signature", delta["signature"].clone())));
+                    }
+                    (Some(StreamBlock::Tool(_, input)), "input_json_delta") => {
+                        input.push_str(delta["partial_json"].as_str().unwrap_or_default());
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => match self.blocks.remove(&index) {
+                Some(StreamBlock::Text { started: true }) => events.push(Ok(StreamEvent::content_block_stop())),
+                Some(StreamBlock::Tool(tool_use, input)) => {
+                    let tool_use = match parse_arguments(&input) {
+                        Ok(input) => tool_use.with_input(input),
+                        Err(error) => {
+                            self.malformed.insert(tool_use.tool_use_id.clone(), json!(error));
+                            tool_use
+                        }
+                    };
+                    events.push(Ok(StreamEvent::tool_use_start(tool_use)));
+                    events.push(Ok(StreamEvent::tool_use_stop()));
+                }
+                _ => {}
+            },
+            "message_delta" => {
+                self.output_tokens = payload["usage"]["output_tokens"].as_u64().unwrap_or(self.output_tokens);
+                if let Some(reason) = payload["delta"]["stop_reason"].as_str() {
+                    self.stop_reason = Some(reason.to_string());
+                    events.push(Ok(StreamEvent::message_delta(MessageDelta {
+                        role: None,
+                        content: None,
+                        stop_reason: Some(reason.to_string()),
+                        stop_sequence: payload["delta"]["stop_sequence"].as_str().map(str::to_string),
+                    })));
+                }
+            }
+            "message_stop" => self.done = true,
+            "error" => {
+                let error = &payload["error"];
+                let error = anthropic_error(
+                    error["type"].as_str().unwrap_or_default(),
+                    error["message"].as_str().unwrap_or_default(),
+                );
+                self.fail(events, error);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl StreamDecoder for MessagesStreamDecoder {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        for line in self.lines.push(chunk) {
+            if self.done {
+                break;
+            }
+            if let Some(data) = sse_data(&line) {
+                self.on_data(data, &mut events);
+            }
+        }
+        events
+    }
+
+    fn finish(&mut self) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        if let Some(line) = self.lines.finish().filter(|_| !self.done) {
+            if let Some(data) = sse_data(&line) {
+                self.on_data(data, &mut events);
+            }
+        }
+        if self.failed {
+            return events;
+        }
+        self.done = true;
+        let mut stop = StreamEvent::message_stop();
+        if let Some(reason) = self.stop_reason.take() {
+            stop = stop.with_metadata("stop_reason", json!(reason));
+        }
+        if !self.malformed.is_empty() {
+            stop = stop.with_metadata(MALFORMED_TOOL_CALLS_METADATA_KEY, json!(std::mem::take(&mut self.malformed)));
+        }
+        let usage = json!({
+            "input_tokens": self.input_tokens,
+            "output_tokens": self.output_tokens,
+            "total_tokens": self.input_tokens + self.output_tokens,
+        });
+        events.push(Ok(stop.with_metadata("usage", usage)));
+        events
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// The Anthropic model implementation.
+pub struct AnthropicModel {
+    config: ModelConfig,
+    anthropic_config: AnthropicConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl std::fmt::Debug for AnthropicModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnthropicModel")
+            .field("config", &self.config)
+            .field("base_url", &self.anthropic_config.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AnthropicModel {
+    /// Create a new Anthropic model.
+    pub fn new() -> Self {
+        Self::with_config(AnthropicConfig::default())
+    }
+
+    /// Create a new Anthropic model with the given configuration.
+    pub fn with_config(anthropic_config: AnthropicConfig) -> Self {
+        Self {
+            config: ModelConfig::new(&anthropic_config.model_id)
+                .with_temperature(anthropic_config.temperature.unwrap_or(0.7))
+                .with_max_tokens(anthropic_config.max_tokens.unwrap_or(4096))
+                .with_top_p(anthropic_config.top_p.unwrap_or(1.0))
+                .with_streaming(anthropic_config.streaming.unwrap_or(false)),
+            anthropic_config,
+            client: Arc::new(UnconfiguredHttpClient::new("Anthropic")),
+        }
+    }
+
+    /// Set the client that sends requests to the Anthropic API.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Build a Messages API request.
+    fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let mut body = messages_request_body(&self.config, messages, tool_specs, system_prompt);
+        if let Some(budget_tokens) = self.anthropic_config.thinking_budget {
+            // Thinking rejects sampling changes
+            body["thinking"] = json!({ "type": "enabled", "budget_tokens": budget_tokens });
+            if let Some(fields) = body.as_object_mut() {
+                fields.remove("temperature");
+                fields.remove("top_p");
+                fields.remove("top_k");
+            }
+        }
+        for (key, value) in self.anthropic_config.extra.iter().chain(&self.config.extra) {
+            body[key] = value.clone();
+        }
+        if stream {
+            body["stream"] = json!(true);
+        }
+        HttpRequest::post(&format!("{}/messages", self.anthropic_config.base_url))
+            .with_header("x-api-key", &self.anthropic_config.api_key)
+            .with_header("anthropic-version", ANTHROPIC_VERSION)
+            .with_json_body(&body)
+    }
+}
+
+#[async_trait]
+impl Model for AnthropicModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let response = self.config.middleware.send(self.client.as_ref(), self.request(messages, tool_specs, system_prompt, false)?).await?;
+        if !response.is_success() {
+            return Err(status_error(&response));
+        }
+        let model_response = parse_messages_response(&response)?;
+        tracing::debug!(
+            "model_id=<{}>, tool_uses=<{}> | anthropic response received",
+            self.config.model_id,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, true)?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response.collect().await?));
+        }
+        Ok(decode_body(response.body, MessagesStreamDecoder::default()))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(
+            "Anthropic model does not support structured output yet".to_string(),
+        )))
+    }
+}
+
+impl Default for AnthropicModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpStreamingResponse;
+    use crate::types::{Message, ToolResult, ToolResultContent};
+    use tokio_stream::StreamExt;
+
+    /// Records request bodies and answers with thinking and a tool use, streamed when asked.
+    #[derive(Default)]
+    struct MessagesEndpoint {
+        bodies: std::sync::Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for MessagesEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert_eq!(request.url, "https://api.anthropic.com/v1/messages");
+            assert_eq!(request.header("x-api-key"), Some("sk-ant"));
+            assert_eq!(request.header("anthropic-version"), Some(ANTHROPIC_VERSION));
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let streaming = body["stream"] == json!(true);
+            self.bodies.lock().unwrap().push(body);
+            if streaming {
+                let events = [
+                    json!({ "type": "message_start", "message": { "usage": { "input_tokens": 12, "output_tokens": 1 } } }),
+                    json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "thinking", "thinking": "" } }),
+                    json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "thinking_delta", "thinking": "Need weather." } }),
+                    json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "signature_delta", "signature": "sig-2" } }),
+                    json!({ "type": "content_block_stop", "index": 0 }),
+                    json!({ "type": "content_block_start", "index": 1, "content_block": { "type": "text", "text": "" } }),
+                    json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "text_delta", "text": "Checking " } }),
+                    json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "text_delta", "text": "Oslo." } }),
+                    json!({ "type": "content_block_stop", "index": 1 }),
+                    json!({ "type": "content_block_start", "index": 2, "content_block": { "type": "tool_use", "id": "toolu_2", "name": "lookup", "input": {} } }),
+                    json!({ "type": "content_block_delta", "index": 2, "delta": { "type": "input_json_delta", "partial_json": "{\"city\": \"Os" } }),
+                    json!({ "type": "content_block_delta", "index": 2, "delta": { "type": "input_json_delta", "partial_json": "lo\"}" } }),
+                    json!({ "type": "content_block_stop", "index": 2 }),
+                    json!({ "type": "message_delta", "delta": { "stop_reason": "tool_use" }, "usage": { "output_tokens": 30 } }),
+                    json!({ "type": "message_stop" }),
+                ];
+                let sse: String = events
+                    .iter()
+                    .map(|event| format!("event: {}\ndata: {}\n\n", event["type"].as_str().unwrap(), event))
+                    .collect();
+                return Ok(HttpResponse::new(200, sse.into_bytes()));
+            }
+            let body = json!({
+                "content": [
+                    { "type": "thinking", "thinking": "Need weather.", "signature": "sig-1" },
+                    { "type": "tool_use", "id": "toolu_1", "name": "lookup", "input": { "city": "Paris" } }
+                ],
+                "stop_reason": "tool_use",
+                "usage": { "input_tokens": 20, "output_tokens": 8 }
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+
+        async fn send_streaming(&self, request: HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+            // Deliver the body in small pieces so events span chunk boundaries
+            let response = self.send(request).await?;
+            let chunks: Vec<_> = response.body.chunks(7).map(|chunk| Ok(chunk.to_vec())).collect();
+            Ok(HttpStreamingResponse {
+                status: response.status,
+                headers: response.headers,
+                body: Box::pin(tokio_stream::iter(chunks)),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_messages_and_streaming() {
+        let endpoint = Arc::new(MessagesEndpoint::default());
+        let tools = vec![ToolSpec::new("lookup", "Look up the weather")];
+        let model = AnthropicModel::with_config(
+            AnthropicConfig::new().with_api_key("sk-ant").with_thinking_budget(1024),
+        )
+        .with_client(endpoint.clone());
+
+        let response = model.generate(&vec![Message::user("Weather in Paris?")], Some(&tools), Some("Be brief.")).await.unwrap();
+        assert_eq!(response.reasoning[0].reasoning_text.signature.as_deref(), Some("sig-1"));
+        assert_eq!(response.tool_uses[0].input, Some(json!({ "city": "Paris" })));
+        assert_eq!(response.usage.as_ref().unwrap().total_tokens, 28);
+        // Without a client the model fails instead of answering with made-up text
+        let unconfigured = AnthropicModel::new().generate(&vec![Message::user("Hi")], None, None).await;
+        assert!(matches!(unconfigured, Err(IndubitablyError::ModelError(ModelError::InvalidConfiguration(_)))));
+
+        let messages = vec![
+            Message::system("Use metric units."),
+            Message::user("Weather in Paris?"),
+            Message::assistant_with_tool_uses("", response.tool_uses.clone()).with_reasoning(response.reasoning.clone()),
+            Message::tool_results(vec![ToolResult::new("toolu_1", vec![ToolResultContent::text("18C")])]),
+            Message::user("And Oslo?"),
+        ];
+        let events: Vec<StreamEvent> = model.stream(&messages, Some(&tools), None).await.unwrap().map(Result::unwrap).collect().await;
+        let texts: Vec<&str> = events
+            .iter()
+            .filter(|event| event.metadata.as_ref().is_none_or(|metadata| !metadata.contains_key("reasoning")))
+            .filter_map(|event| event.content.as_ref()?.first()?.text.as_deref())
+            .collect();
+        assert_eq!(texts, vec!["Checking ", "Oslo."]);
+        let tool_use = events.iter().find_map(|event| event.tool_use.as_ref()).unwrap();
+        assert_eq!(tool_use.input, Some(json!({ "city": "Oslo" })));
+        assert_eq!(events.last().unwrap().metadata.as_ref().unwrap()["usage"]["total_tokens"], 42);
+
+        let bodies = endpoint.bodies.lock().unwrap().clone();
+        assert_eq!(bodies[0]["system"], "Be brief.");
+        assert_eq!(bodies[0]["tools"][0]["input_schema"], json!({ "type": "object" }));
+        assert_eq!(bodies[0]["thinking"]["budget_tokens"], 1024);
+        assert!(bodies[0].get("temperature").is_none());
+        assert_eq!(bodies[1]["system"], "Use metric units.");
+        let replayed = &bodies[1]["messages"];
+        assert_eq!(replayed[1]["content"][0], json!({ "type": "thinking", "thinking": "Need weather.", "signature": "sig-1" }));
+        assert_eq!(replayed[2]["content"][0]["type"], "tool_result");
+        assert_eq!(replayed[2]["content"][1], json!({ "type": "text", "text": "And Oslo?" }));
+
+        let error = anthropic_error("overloaded_error", "Overloaded");
+        assert!(matches!(error, IndubitablyError::ModelError(ModelError::ModelNotAvailable(_))));
+    }
+
+    #[test]
+    fn test_anthropic_citations() {
+        let body = json!({
+            "content": [
+                { "type": "text", "text": "Per the policy, " },
+                { "type": "text", "text": "refunds take 30 days.", "citations": [{
+                    "type": "char_location", "cited_text": "Refunds within 30 days.", "document_index": 0,
+                    "document_title": "Policy", "start_char_index": 0, "end_char_index": 23
+                }, {
+                    "type": "web_search_result_location", "url": "https://example.com/refunds", "title": "Refunds",
+                    "cited_text": "30 days", "encrypted_index": "abc"
+                }] }
+            ],
+            "stop_reason": "end_turn"
+        });
+        let response = parse_messages_response(&HttpResponse::new(200, body.to_string().into_bytes())).unwrap();
+
+        assert_eq!(response.content, "Per the policy, refunds take 30 days.");
+        assert_eq!(
+            response.citations,
+            vec![
+                Citation::new("document-0")
+                    .with_span(16, 37)
+                    .with_title("Policy")
+                    .with_snippet("Refunds within 30 days."),
+                Citation::new("https://example.com/refunds")
+                    .with_span(16, 37)
+                    .with_uri("https://example.com/refunds")
+                    .with_title("Refunds")
+                    .with_snippet("30 days"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Azure OpenAI model implementation for the SDK.
+//! 
+//! Azure OpenAI serves OpenAI's chat completions wire format from a
+//! resource endpoint, addressing a deployment instead of a model and taking
+//! the API version as a query parameter. Requests authenticate with the
+//! resource's API key or with Microsoft Entra ID (Azure AD) bearer tokens
+//! from a `TokenProvider`.
+//! 
+//! OpenAI's parameter rules apply to the deployment name, so deployments
+//! of reasoning models should be named after the model, such as `o3-mini`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::models::http::{HttpClient, HttpRequest, UnconfiguredHttpClient};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::decode_body;
+use super::openai::openai_request_body;
+use super::openai_compat::{parse_chat_response, status_error, ChatStreamDecoder};
+use crate::models::signing::{OidcClientCredentials, TokenProvider};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, ToolSpec};
+
+/// The default Azure OpenAI API version.
+pub const DEFAULT_AZURE_OPENAI_API_VERSION: &str = "2024-10-21";
+
+/// The environment variable holding the resource endpoint.
+pub const AZURE_OPENAI_ENDPOINT_ENV: &str = "AZURE_OPENAI_ENDPOINT";
+
+/// The environment variable holding the resource API key.
+pub const AZURE_OPENAI_API_KEY_ENV: &str = "AZURE_OPENAI_API_KEY";
+
+/// The environment variable holding the API version.
+pub const AZURE_OPENAI_API_VERSION_ENV: &str = "OPENAI_API_VERSION";
+
+/// The Entra ID scope of Azure OpenAI tokens.
+pub const AZURE_COGNITIVE_SERVICES_SCOPE: &str = "https://cognitiveservices.azure.com/.default";
+
+/// Configuration specific to Azure OpenAI models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureOpenAIConfig {
+    /// The resource endpoint, such as `https://my-resource.openai.azure.com`.
+    pub endpoint: String,
+    /// The deployment to send requests to.
+    pub deployment: String,
+    /// The API version sent as the `api-version` query parameter.
+    pub api_version: String,
+    /// The resource API key, unused when a token provider is set.
+    pub api_key: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// Additional request body fields.
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Default for AzureOpenAIConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            deployment: String::new(),
+            api_version: DEFAULT_AZURE_OPENAI_API_VERSION.to_string(),
+            api_key: String::new(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: Some(1.0),
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl AzureOpenAIConfig {
+    /// Create a configuration for a deployment on a resource endpoint.
+    pub fn new(endpoint: &str, deployment: &str) -> Self {
+        Self::default().with_endpoint(endpoint).with_deployment(deployment)
+    }
+
+    /// Create a configuration from `AZURE_OPENAI_ENDPOINT`, `AZURE_OPENAI_API_KEY` and `OPENAI_API_VERSION`.
+    pub fn from_env(deployment: &str) -> Self {
+        let mut config = Self::new(&std::env::var(AZURE_OPENAI_ENDPOINT_ENV).unwrap_or_default(), deployment)
+            .with_api_key(&std::env::var(AZURE_OPENAI_API_KEY_ENV).unwrap_or_default());
+        if let Ok(api_version) = std::env::var(AZURE_OPENAI_API_VERSION_ENV) {
+            config = config.with_api_version(&api_version);
+        }
+        config
+    }
+
+    /// Set the resource endpoint.
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the deployment.
+    pub fn with_deployment(mut self, deployment: &str) -> Self {
+        self.deployment = deployment.to_string();
+        self
+    }
+
+    /// Set the API version.
+    pub fn with_api_version(mut self, api_version: &str) -> Self {
+        self.api_version = api_version.to_string();
+        self
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Add extra configuration.
+    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+
+    /// Get the chat completions URL of the deployment.
+    pub fn chat_completions_url(&self) -> String {
+        format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.endpoint, self.deployment, self.api_version
+        )
+    }
+}
+
+/// The Azure OpenAI model implementation.
+pub struct AzureOpenAIModel {
+    config: ModelConfig,
+    azure_config: AzureOpenAIConfig,
+    client: Arc<dyn HttpClient>,
+    tokens: Option<Arc<dyn TokenProvider>>,
+}
+
+impl std::fmt::Debug for AzureOpenAIModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzureOpenAIModel")
+            .field("config", &self.config)
+            .field("endpoint", &self.azure_config.endpoint)
+            .field("api_version", &self.azure_config.api_version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AzureOpenAIModel {
+    /// Create a new Azure OpenAI model with the given configuration.
+    pub fn with_config(azure_config: AzureOpenAIConfig) -> Self {
+        let mut config = ModelConfig::new(&azure_config.deployment);
+        config.temperature = azure_config.temperature;
+        config.max_tokens = azure_config.max_tokens;
+        config.top_p = azure_config.top_p;
+        Self {
+            config,
+            azure_config,
+            client: Arc::new(UnconfiguredHttpClient::new("Azure OpenAI")),
+            tokens: None,
+        }
+    }
+
+    /// Set the client that sends requests to Azure OpenAI.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Authenticate with Entra ID bearer tokens instead of the API key.
+    pub fn with_token_provider(mut self, tokens: Arc<dyn TokenProvider>) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    /// Authenticate as an Entra ID app registration with the client-credentials grant.
+    ///
+    /// Tokens are fetched through `client` and cached until shortly before they expire.
+    pub fn with_client_credentials(
+        self,
+        client: Arc<dyn HttpClient>,
+        tenant_id: &str,
+        client_id: &str,
+        client_secret: &str,
+    ) -> Self {
+        let token_url = format!("https://login.microsoftonline.com/{}/oauth2/v2.0/token", tenant_id);
+        let credentials = OidcClientCredentials::new(client, &token_url, client_id, client_secret)
+            .with_scope(AZURE_COGNITIVE_SERVICES_SCOPE);
+        self.with_token_provider(Arc::new(credentials))
+    }
+
+    /// Build a chat completions request for the deployment.
+    async fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        if self.azure_config.endpoint.is_empty() || self.azure_config.deployment.is_empty() {
+            return Err(IndubitablyError::ModelError(ModelError::InvalidConfiguration(
+                "Azure OpenAI needs an endpoint and a deployment".to_string(),
+            )));
+        }
+        let mut body = openai_request_body(
+            &self.config,
+            &self.azure_config.extra,
+            messages,
+            tool_specs,
+            system_prompt,
+            stream,
+        );
+        // The deployment selects the model
+        if let Some(fields) = body.as_object_mut() {
+            fields.remove("model");
+        }
+
+        let request = HttpRequest::post(&self.azure_config.chat_completions_url());
+        let request = match self.tokens {
+            Some(ref tokens) => request.with_header("authorization", &format!("Bearer {}", tokens.token().await?)),
+            None => request.with_header("api-key", &self.azure_config.api_key),
+        };
+        request.with_json_body(&body)
+    }
+}
+
+#[async_trait]
+impl Model for AzureOpenAIModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, false).await?;
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error("Azure OpenAI", &response));
+        }
+        let model_response = parse_chat_response(&response)?;
+        tracing::debug!(
+            "deployment=<{}>, tool_uses=<{}> | azure openai response received",
+            self.azure_config.deployment,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, true).await?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error("Azure OpenAI", &response.collect().await?));
+        }
+        Ok(decode_body(response.body, ChatStreamDecoder::default()))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(
+            "Azure OpenAI model does not support structured output yet".to_string(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+    use crate::models::signing::StaticTokenProvider;
+    use crate::types::{Message, StreamEvent};
+    use serde_json::json;
+    use tokio_stream::StreamExt;
+
+    /// Records requests and answers with a completion, streamed when asked.
+    #[derive(Default)]
+    struct AzureEndpoint {
+        requests: std::sync::Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for AzureEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            let streaming = serde_json::from_slice::<serde_json::Value>(&request.body).unwrap()["stream"] == json!(true);
+            self.requests.lock().unwrap().push(request);
+            if streaming {
+                let chunks = [
+                    json!({ "choices": [], "prompt_filter_results": [{ "prompt_index": 0 }] }),
+                    json!({ "choices": [{ "index": 0, "delta": { "role": "assistant", "content": "Hello" } }] }),
+                    json!({ "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }] }),
+                    json!({ "choices": [], "usage": { "prompt_tokens": 4, "completion_tokens": 1, "total_tokens": 5 } }),
+                ];
+                let mut sse: String = chunks.iter().map(|chunk| format!("data: {}\n\n", chunk)).collect();
+                sse.push_str("data: [DONE]\n\n");
+                return Ok(HttpResponse::new(200, sse.into_bytes()));
+            }
+            let body = json!({
+                "choices": [{ "message": { "role": "assistant", "content": "Hello" }, "finish_reason": "stop" }],
+                "usage": { "prompt_tokens": 4, "completion_tokens": 1, "total_tokens": 5 }
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_azure_openai_deployment_and_auth() {
+        let endpoint = Arc::new(AzureEndpoint::default());
+        let config = AzureOpenAIConfig::new("https://contoso.openai.azure.com/", "gpt-4o-prod").with_api_key("azure-key");
+        let model = AzureOpenAIModel::with_config(config.clone()).with_client(endpoint.clone());
+        let messages = vec![Message::user("Hi")];
+
+        let response = model.generate(&messages, None, Some("Be brief.")).await.unwrap();
+        assert_eq!(response.usage.unwrap().total_tokens, 5);
+        let events: Vec<StreamEvent> = model.stream(&messages, None, None).await.unwrap().map(Result::unwrap).collect().await;
+        assert_eq!(events.last().unwrap().metadata.as_ref().unwrap()["usage"]["total_tokens"], 5);
+
+        let model = AzureOpenAIModel::with_config(config.with_api_version("2025-01-01-preview"))
+            .with_client(endpoint.clone())
+            .with_token_provider(Arc::new(StaticTokenProvider::new("entra-token")));
+        model.generate(&messages, None, None).await.unwrap();
+        let unconfigured = AzureOpenAIModel::with_config(AzureOpenAIConfig::default());
+        assert!(unconfigured.generate(&messages, None, None).await.is_err());
+
+        let requests = endpoint.requests.lock().unwrap();
+        assert_eq!(
+            requests[0].url,
+            "https://contoso.openai.azure.com/openai/deployments/gpt-4o-prod/chat/completions?api-version=2024-10-21"
+        );
+        assert_eq!(requests[0].header("api-key"), Some("azure-key"));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert!(body.get("model").is_none());
+        assert_eq!(body["max_completion_tokens"], 4096);
+        assert!(requests[2].url.ends_with("api-version=2025-01-01-preview"));
+        assert_eq!(requests[2].header("authorization"), Some("Bearer entra-token"));
+        assert_eq!(requests[2].header("api-key"), None);
+    }
+}
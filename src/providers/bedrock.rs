@@ -0,0 +1,374 @@
+//! Amazon Bedrock model implementation for the SDK.
+//! 
+//! This module provides integration with Amazon Bedrock for
+//! accessing various foundation models. Calls go to a prioritized list of
+//! regions and fail over to the next one when a region throttles or is
+//! unavailable; model IDs can be routed through cross-region inference
+//! profiles. Requests are sent by a `BedrockInvoker`; `ConverseInvoker`
+//! calls the Converse and ConverseStream APIs.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::bedrock_converse::ConverseInvoker;
+use super::bedrock_failover::{is_region_failure, RegionFailover, DEFAULT_REGION_COOLDOWN};
+use crate::models::http::HttpClient;
+use crate::models::model::{response_stream, Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::signing::AwsCredentials;
+use crate::telemetry::Metrics;
+use crate::types::{Messages, ToolSpec, IndubitablyError, IndubitablyResult, ModelError};
+
+/// Default Bedrock model ID for Claude 3 Sonnet.
+pub const DEFAULT_BEDROCK_MODEL_ID: &str = "anthropic.claude-3-sonnet-20240229-v1:0";
+
+/// Configuration specific to Bedrock models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BedrockConfig {
+    /// The AWS region to use.
+    pub region: String,
+    /// The regions to fail over to, in priority order.
+    #[serde(default)]
+    pub fallback_regions: Vec<String>,
+    /// The cross-region inference profile prefix, such as `us` or `eu`.
+    #[serde(default)]
+    pub inference_profile: Option<String>,
+    /// How long a failed region is skipped for, in seconds.
+    #[serde(default = "default_region_cooldown_secs")]
+    pub region_cooldown_secs: u64,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// The top-k value for top-k sampling.
+    pub top_k: Option<u32>,
+    /// Whether to enable streaming.
+    pub streaming: Option<bool>,
+    /// Additional Bedrock-specific configuration.
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Default for BedrockConfig {
+    fn default() -> Self {
+        Self {
+            region: "us-west-2".to_string(),
+            fallback_regions: Vec::new(),
+            inference_profile: None,
+            region_cooldown_secs: default_region_cooldown_secs(),
+            model_id: DEFAULT_BEDROCK_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: Some(1.0),
+            top_k: Some(250),
+            streaming: Some(false),
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl BedrockConfig {
+    /// Create a new Bedrock configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the AWS region.
+    pub fn with_region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+        self
+    }
+
+    /// Add a region to fail over to.
+    pub fn with_fallback_region(mut self, region: &str) -> Self {
+        self.fallback_regions.push(region.to_string());
+        self
+    }
+
+    /// Route calls through a cross-region inference profile, such as `us` or `eu`.
+    pub fn with_inference_profile(mut self, profile: &str) -> Self {
+        self.inference_profile = Some(profile.to_string());
+        self
+    }
+
+    /// Set how long a failed region is skipped for.
+    pub fn with_region_cooldown(mut self, cooldown: Duration) -> Self {
+        self.region_cooldown_secs = cooldown.as_secs();
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Get the primary region followed by the fallback regions, without duplicates.
+    pub fn regions(&self) -> Vec<String> {
+        let mut regions: Vec<String> = Vec::with_capacity(1 + self.fallback_regions.len());
+        for region in std::iter::once(&self.region).chain(&self.fallback_regions) {
+            if !regions.contains(region) {
+                regions.push(region.clone());
+            }
+        }
+        regions
+    }
+
+    /// Get the ID to invoke a model with, prefixed with the inference profile if one is set.
+    pub fn inference_model_id(&self, model_id: &str) -> String {
+        match self.inference_profile {
+            Some(ref profile) if !model_id.starts_with(&format!("{}.", profile)) => format!("{}.{}", profile, model_id),
+            _ => model_id.to_string(),
+        }
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set the top-k value.
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Enable or disable streaming.
+    pub fn with_streaming(mut self, streaming: bool) -> Self {
+        self.streaming = Some(streaming);
+        self
+    }
+
+    /// Add extra configuration.
+    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+}
+
+fn default_region_cooldown_secs() -> u64 {
+    DEFAULT_REGION_COOLDOWN.as_secs()
+}
+
+/// Sends a request to the Bedrock endpoint of one region.
+#[async_trait]
+pub trait BedrockInvoker: Send + Sync {
+    /// Invoke a model in the given region with the model's current configuration.
+    async fn invoke(
+        &self,
+        config: &ModelConfig,
+        region: &str,
+        model_id: &str,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse>;
+
+    /// Invoke a model in the given region and stream its response.
+    ///
+    /// The default implementation replays the complete response from `invoke`.
+    async fn invoke_stream(
+        &self,
+        config: &ModelConfig,
+        region: &str,
+        model_id: &str,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let response = self.invoke(config, region, model_id, messages, tool_specs, system_prompt).await?;
+        Ok(response_stream(response))
+    }
+}
+
+/// The invoker used until a client is configured, failing every call.
+#[derive(Debug, Clone, Copy, Default)]
+struct UnconfiguredInvoker;
+
+#[async_trait]
+impl BedrockInvoker for UnconfiguredInvoker {
+    async fn invoke(
+        &self,
+        _config: &ModelConfig,
+        _region: &str,
+        model_id: &str,
+        _messages: &Messages,
+        _tool_specs: Option<&[ToolSpec]>,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidConfiguration(format!(
+            "Bedrock has no client to invoke {}; create the model with `with_client` or set an invoker",
+            model_id
+        ))))
+    }
+}
+
+/// The Bedrock model implementation.
+pub struct BedrockModel {
+    config: ModelConfig,
+    bedrock_config: BedrockConfig,
+    invoker: Arc<dyn BedrockInvoker>,
+    failover: RegionFailover,
+}
+
+impl std::fmt::Debug for BedrockModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BedrockModel")
+            .field("config", &self.config)
+            .field("bedrock_config", &self.bedrock_config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BedrockModel {
+    /// Create a new Bedrock model.
+    pub fn new() -> Self {
+        Self::with_config(BedrockConfig::default())
+    }
+
+    /// Create a new Bedrock model with the given configuration.
+    pub fn with_config(bedrock_config: BedrockConfig) -> Self {
+        Self {
+            config: ModelConfig::new(&bedrock_config.model_id)
+                .with_temperature(bedrock_config.temperature.unwrap_or(0.7))
+                .with_max_tokens(bedrock_config.max_tokens.unwrap_or(4096))
+                .with_top_p(bedrock_config.top_p.unwrap_or(1.0))
+                .with_top_k(bedrock_config.top_k.unwrap_or(250))
+                .with_streaming(bedrock_config.streaming.unwrap_or(false)),
+            invoker: Arc::new(UnconfiguredInvoker),
+            failover: RegionFailover::new(Duration::from_secs(bedrock_config.region_cooldown_secs)),
+            bedrock_config,
+        }
+    }
+
+    /// Create a Bedrock model calling the Converse API through `client`, signed with `credentials`.
+    pub fn with_client(bedrock_config: BedrockConfig, client: Arc<dyn HttpClient>, credentials: AwsCredentials) -> Self {
+        let model = Self::with_config(bedrock_config);
+        let invoker = ConverseInvoker::new(client, credentials);
+        model.with_invoker(Arc::new(invoker))
+    }
+
+    /// Set the invoker that sends requests to each region.
+    pub fn with_invoker(mut self, invoker: Arc<dyn BedrockInvoker>) -> Self {
+        self.invoker = invoker;
+        self
+    }
+
+    /// Get the per-region latency, request and failure metrics.
+    pub fn region_metrics(&self) -> Metrics {
+        self.failover.metrics()
+    }
+
+    /// Call each region in failover order until one succeeds or fails for a reason other than the region.
+    async fn with_failover<T, F, Fut>(&self, call: F) -> IndubitablyResult<(T, String)>
+    where
+        F: Fn(String, String) -> Fut,
+        Fut: Future<Output = IndubitablyResult<T>>,
+    {
+        let model_id = self.bedrock_config.inference_model_id(&self.config.model_id);
+        let mut last_error = None;
+        for (attempt, region) in self.failover.order(&self.bedrock_config.regions()).iter().enumerate() {
+            let started = Instant::now();
+            match call(region.clone(), model_id.clone()).await {
+                Ok(value) => {
+                    self.failover.record_success(region, started.elapsed());
+                    if attempt > 0 {
+                        self.failover.record_failover();
+                    }
+                    return Ok((value, region.clone()));
+                }
+                Err(e) if is_region_failure(&e) => {
+                    tracing::warn!("region=<{}>, model_id=<{}>, error=<{}> | bedrock region failed, failing over", region, model_id, e);
+                    self.failover.record_failure(region);
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            IndubitablyError::ModelError(ModelError::InvalidConfiguration("No Bedrock regions configured".to_string()))
+        }))
+    }
+}
+
+#[async_trait]
+impl Model for BedrockModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let (mut response, region) = self
+            .with_failover(|region, model_id| async move {
+                self.invoker.invoke(&self.config, &region, &model_id, messages, tool_specs, system_prompt).await
+            })
+            .await?;
+        response.metadata.insert("region".to_string(), serde_json::json!(region));
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let (stream, _) = self
+            .with_failover(|region, model_id| async move {
+                self.invoker.invoke_stream(&self.config, &region, &model_id, messages, tool_specs, system_prompt).await
+            })
+            .await?;
+        Ok(stream)
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(crate::types::IndubitablyError::ModelError(
+            crate::types::ModelError::InvalidResponseFormat(
+                "Bedrock model does not support structured output yet".to_string(),
+            ),
+        ))
+    }
+}
+
+impl Default for BedrockModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,795 @@
+//! Bedrock Converse API for the SDK.
+//! 
+//! This module provides `ConverseInvoker`, the `BedrockInvoker` that calls
+//! the Bedrock runtime's `Converse` and `ConverseStream` operations over
+//! SigV4-signed HTTP. SDK messages, system prompts and tool specs are
+//! mapped to Converse request bodies, and responses back to a
+//! `ModelResponse` with its usage. `ConverseStream` responses use the AWS
+//! event stream encoding, which is decoded here frame by frame.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::bedrock::BedrockInvoker;
+use crate::crypto::crc32::crc32;
+use crate::models::http::{HttpClient, HttpRequest, HttpResponse};
+use crate::models::model::{ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::{decode_body, StreamDecoder};
+use crate::models::signing::{uri_encode, AwsCredentials, SigV4Signer};
+use crate::tools::strict::strict_tool_schema;
+use crate::types::streaming::{MessageDelta, StreamContent, StreamEventPool};
+use crate::types::{
+    IndubitablyError, IndubitablyResult, MessageRole, Messages, ModelError, ReasoningContentBlock, StreamEvent,
+    ToolSpec, ToolUse,
+};
+
+/// The service name Bedrock runtime requests are signed for.
+pub const BEDROCK_SIGNING_SERVICE: &str = "bedrock";
+
+/// Get the runtime endpoint of a Converse operation, such as `converse` or `converse-stream`.
+pub fn converse_endpoint(region: &str, model_id: &str, operation: &str) -> String {
+    format!(
+        "https://bedrock-runtime.{}.amazonaws.com/model/{}/{}",
+        region,
+        uri_encode(model_id, true),
+        operation
+    )
+}
+
+/// Build a Converse request body.
+///
+/// Consecutive messages with the same role are merged, since Converse requires
+/// roles to alternate, and tool results are sent in user turns.
+pub fn converse_request_body(
+    config: &ModelConfig,
+    messages: &Messages,
+    tool_specs: Option<&[ToolSpec]>,
+    system_prompt: Option<&str>,
+) -> Value {
+    let mut system: Vec<Value> = system_prompt
+        .filter(|prompt| !prompt.is_empty())
+        .map(|prompt| json!({ "text": prompt }))
+        .into_iter()
+        .collect();
+    let mut wire_messages: Vec<Value> = Vec::new();
+    // Converse requires every document in a request to have a distinct name
+    let mut documents = 0;
+    for message in messages {
+        let role = match message.role {
+            MessageRole::System => {
+                system.extend(message.content.iter().filter_map(|block| block.text.as_deref()).map(|text| json!({ "text": text })));
+                continue;
+            }
+            MessageRole::Assistant => "assistant",
+            MessageRole::User | MessageRole::Tool => "user",
+        };
+        let mut content = Vec::new();
+        for block in &message.content {
+            if let Some(ref reasoning) = block.reasoning_content {
+                // Reasoning can only be replayed with the signature the model returned with it
+                if let Some(ref signature) = reasoning.reasoning_text.signature {
+                    content.push(json!({
+                        "reasoningContent": {
+                            "reasoningText": { "text": reasoning.reasoning_text.text, "signature": signature },
+                        }
+                    }));
+                }
+            }
+            if let Some(text) = block.text.as_deref().filter(|text| !text.is_empty()) {
+                content.push(json!({ "text": text }));
+            }
+            if let Some(ref image) = block.image {
+                if let Some(data) = image.base64_data() {
+                    content.push(json!({ "image": { "format": image.format(), "source": { "bytes": data } } }));
+                }
+            }
+            if let Some(ref document) = block.document {
+                if let Some(data) = document.base64_data() {
+                    documents += 1;
+                    content.push(json!({
+                        "document": {
+                            "format": document.format(),
+                            "name": format!("document-{}", documents),
+                            "source": { "bytes": data },
+                        }
+                    }));
+                }
+            }
+            if let Some(ref tool_use) = block.tool_use {
+                content.push(json!({
+                    "toolUse": {
+                        "toolUseId": tool_use.tool_use_id,
+                        "name": tool_use.name,
+                        "input": tool_use.input.clone().unwrap_or_else(|| json!({})),
+                    }
+                }));
+            }
+            if let Some(ref result) = block.tool_result {
+                let result_content: Vec<Value> = result
+                    .content
+                    .iter()
+                    .filter_map(|content| content.text.as_deref())
+                    .map(|text| json!({ "text": text }))
+                    .collect();
+                content.push(json!({
+                    "toolResult": {
+                        "toolUseId": result.tool_use_id,
+                        "content": result_content,
+                        "status": if result.is_error == Some(true) { "error" } else { "success" },
+                    }
+                }));
+            }
+        }
+        if content.is_empty() {
+            continue;
+        }
+        match wire_messages.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(blocks) = last["content"].as_array_mut() {
+                    blocks.extend(content);
+                }
+            }
+            _ => wire_messages.push(json!({ "role": role, "content": content })),
+        }
+    }
+
+    let mut inference_config = serde_json::Map::new();
+    if let Some(max_tokens) = config.max_tokens {
+        inference_config.insert("maxTokens".to_string(), json!(max_tokens));
+    }
+    if let Some(temperature) = config.temperature {
+        inference_config.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = config.top_p {
+        inference_config.insert("topP".to_string(), json!(top_p));
+    }
+
+    let mut body = json!({ "messages": wire_messages, "inferenceConfig": inference_config });
+    if !system.is_empty() {
+        body["system"] = json!(system);
+    }
+    if let Some(specs) = tool_specs.filter(|specs| !specs.is_empty()) {
+        let tools: Vec<Value> = specs
+            .iter()
+            .map(|spec| {
+                let mut tool_spec = json!({
+                    "name": spec.name,
+                    "description": spec.description,
+                    "inputSchema": { "json": spec.input_schema.clone().unwrap_or_else(|| json!({ "type": "object" })) },
+                });
+                if let Some(strict) = config.strict_tools.then(|| strict_tool_schema(spec)).flatten() {
+                    tool_spec["inputSchema"] = json!({ "json": strict });
+                    tool_spec["strict"] = json!(true);
+                }
+                json!({ "toolSpec": tool_spec })
+            })
+            .collect();
+        body["toolConfig"] = json!({ "tools": tools });
+    }
+    // Top-k is not part of the common inference config; only Anthropic models take it directly
+    if let Some(top_k) = config.top_k.filter(|_| config.model_id.contains("anthropic.")) {
+        body["additionalModelRequestFields"] = json!({ "top_k": top_k });
+    }
+    body
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseResponse {
+    output: ConverseOutput,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+#[derive(Deserialize)]
+struct ConverseOutput {
+    #[serde(default)]
+    message: Option<ConverseMessage>,
+}
+
+#[derive(Deserialize)]
+struct ConverseMessage {
+    #[serde(default)]
+    content: Vec<ConverseBlock>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseBlock {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    tool_use: Option<ConverseToolUse>,
+    #[serde(default)]
+    reasoning_content: Option<ConverseReasoning>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseToolUse {
+    tool_use_id: String,
+    name: String,
+    #[serde(default)]
+    input: Option<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseReasoning {
+    #[serde(default)]
+    reasoning_text: Option<ReasoningText>,
+}
+
+#[derive(Deserialize)]
+struct ReasoningText {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+/// Map a Converse response body to a model response.
+pub fn parse_converse_response(response: &HttpResponse) -> IndubitablyResult<ModelResponse> {
+    let converse: ConverseResponse = response.json()?;
+    let mut model_response = ModelResponse::new("");
+    for block in converse.output.message.map(|message| message.content).unwrap_or_default() {
+        if let Some(text) = block.text {
+            model_response.content.push_str(&text);
+        }
+        if let Some(tool_use) = block.tool_use {
+            let mut sdk_tool_use = ToolUse::new(&tool_use.name, &tool_use.tool_use_id);
+            sdk_tool_use.input = tool_use.input;
+            model_response = model_response.with_tool_use(sdk_tool_use);
+        }
+        if let Some(reasoning) = block.reasoning_content.and_then(|reasoning| reasoning.reasoning_text) {
+            let mut block = ReasoningContentBlock::new(&reasoning.text);
+            block.reasoning_text.signature = reasoning.signature;
+            model_response.reasoning.push(block);
+        }
+    }
+    if let Some(usage) = converse.usage {
+        model_response = model_response.with_usage(usage.input_tokens, usage.output_tokens);
+    }
+    if let Some(reason) = converse.stop_reason {
+        model_response.metadata.insert("stop_reason".to_string(), json!(reason));
+    }
+    Ok(model_response)
+}
+
+/// Map a Bedrock exception, by its type and message, to an error.
+pub fn converse_error(error_type: &str, message: &str) -> IndubitablyError {
+    let message = format!("Bedrock {}: {}", error_type, message);
+    let lowered = message.to_lowercase();
+    IndubitablyError::ModelError(match error_type.trim_end_matches("Exception").to_lowercase().as_str() {
+        "throttling" => ModelError::ModelThrottled(message),
+        "serviceunavailable" | "modelnotready" | "internalserver" => ModelError::ModelNotAvailable(message),
+        "servicequotaexceeded" => ModelError::QuotaExceeded(message),
+        "accessdenied" | "unrecognizedclient" | "resourcenotfound" => ModelError::InvalidConfiguration(message),
+        "validation" if lowered.contains("too long") || lowered.contains("too many input tokens") => {
+            ModelError::ContextWindowOverflow(message)
+        }
+        _ => ModelError::RequestFailed(message),
+    })
+}
+
+/// Map an unsuccessful Converse response to an error.
+pub(crate) fn status_error(response: &HttpResponse) -> IndubitablyError {
+    let body: Value = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+    let message = body["message"].as_str().or(body["Message"].as_str()).map(str::to_string).unwrap_or_else(|| response.text());
+    let error_type = response
+        .header("x-amzn-errortype")
+        .map(|error_type| error_type.split(':').next().unwrap_or_default().to_string())
+        .unwrap_or_else(|| match response.status {
+            429 => "ThrottlingException".to_string(),
+            503 => "ServiceUnavailableException".to_string(),
+            401 | 403 => "AccessDeniedException".to_string(),
+            status => format!("HTTP {}", status),
+        });
+    converse_error(&error_type, &message)
+}
+
+/// A decoded AWS event stream message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventStreamFrame {
+    /// The string headers, such as `:event-type`.
+    pub headers: HashMap<String, String>,
+    /// The payload, JSON for Bedrock.
+    pub payload: Vec<u8>,
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes.get(at..at + 4).map(|word| u32::from_be_bytes([word[0], word[1], word[2], word[3]]))
+}
+
+/// Decode a body of AWS event stream messages, checking their checksums.
+pub fn decode_event_stream(body: &[u8]) -> IndubitablyResult<Vec<EventStreamFrame>> {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while offset < body.len() {
+        let (frame, len) = decode_event_stream_frame(&body[offset..])?
+            .ok_or_else(|| invalid_event_stream("truncated message"))?;
+        frames.push(frame);
+        offset += len;
+    }
+    Ok(frames)
+}
+
+/// Decode the AWS event stream message at the start of `bytes`, with its length.
+///
+/// Returns `None` while the message is incomplete, so a body can be decoded
+/// as it arrives.
+pub fn decode_event_stream_frame(bytes: &[u8]) -> IndubitablyResult<Option<(EventStreamFrame, usize)>> {
+    let (Some(total), Some(headers_len)) = (read_u32(bytes, 0), read_u32(bytes, 4)) else {
+        return Ok(None);
+    };
+    let (total, headers_len) = (total as usize, headers_len as usize);
+    if bytes.len() < 12 {
+        return Ok(None);
+    }
+    if total < 16 + headers_len {
+        return Err(invalid_event_stream("truncated message"));
+    }
+    if read_u32(bytes, 8) != Some(crc32(&bytes[..8])) {
+        return Err(invalid_event_stream("prelude checksum mismatch"));
+    }
+    if bytes.len() < total {
+        return Ok(None);
+    }
+    let message = &bytes[..total];
+    if read_u32(message, total - 4) != Some(crc32(&message[..total - 4])) {
+        return Err(invalid_event_stream("message checksum mismatch"));
+    }
+
+    let mut headers = HashMap::new();
+    let header_bytes = &message[12..12 + headers_len];
+    let mut at = 0;
+    while at < header_bytes.len() {
+        let name_len = usize::from(header_bytes[at]);
+        let name = header_bytes.get(at + 1..at + 1 + name_len).ok_or_else(|| invalid_event_stream("truncated header"))?;
+        let name = String::from_utf8_lossy(name).to_string();
+        at += 1 + name_len;
+        let value_type = *header_bytes.get(at).ok_or_else(|| invalid_event_stream("truncated header"))?;
+        at += 1;
+        let fixed_len = match value_type {
+            0 | 1 => Some(0),
+            2 => Some(1),
+            3 => Some(2),
+            4 => Some(4),
+            5 | 8 => Some(8),
+            9 => Some(16),
+            6 | 7 => None,
+            _ => return Err(invalid_event_stream("unknown header type")),
+        };
+        match fixed_len {
+            Some(len) => at += len,
+            None => {
+                let len_bytes = header_bytes.get(at..at + 2).ok_or_else(|| invalid_event_stream("truncated header"))?;
+                let len = usize::from(u16::from_be_bytes([len_bytes[0], len_bytes[1]]));
+                let value =
+                    header_bytes.get(at + 2..at + 2 + len).ok_or_else(|| invalid_event_stream("truncated header"))?;
+                if value_type == 7 {
+                    headers.insert(name, String::from_utf8_lossy(value).to_string());
+                }
+                at += 2 + len;
+            }
+        }
+    }
+
+    let frame = EventStreamFrame {
+        headers,
+        payload: message[12 + headers_len..total - 4].to_vec(),
+    };
+    Ok(Some((frame, total)))
+}
+
+fn invalid_event_stream(reason: &str) -> IndubitablyError {
+    IndubitablyError::ModelError(ModelError::InvalidResponseFormat(format!("Invalid event stream: {}", reason)))
+}
+
+/// The state of one content block while a stream is decoded.
+enum StreamBlock {
+    Text,
+    Tool(ToolUse, String),
+}
+
+/// Map `ConverseStream` event stream messages to stream events.
+///
+/// Text deltas are forwarded as they arrive. Tool input arrives as JSON
+/// fragments, so each tool use is emitted once its block stops and the input
+/// is complete. Usage from the trailing metadata event is attached to the
+/// final `message_stop` event.
+pub fn converse_stream_events(frames: Vec<EventStreamFrame>) -> Vec<IndubitablyResult<StreamEvent>> {
+    let mut decoder = ConverseStreamDecoder::default();
+    let mut events = Vec::new();
+    for frame in frames {
+        if decoder.done {
+            break;
+        }
+        decoder.on_frame(frame, &mut events);
+    }
+    events.extend(decoder.finish());
+    events
+}
+
+/// Decodes a `ConverseStream` body of event stream messages as it arrives; see `converse_stream_events`.
+#[derive(Default)]
+pub struct ConverseStreamDecoder {
+    buffer: Vec<u8>,
+    blocks: HashMap<u64, StreamBlock>,
+    stop_reason: Option<Option<String>>,
+    usage: Option<Value>,
+    done: bool,
+}
+
+impl ConverseStreamDecoder {
+    fn fail(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>, error: IndubitablyError) {
+        events.push(Err(error));
+        self.done = true;
+    }
+
+    fn on_frame(&mut self, frame: EventStreamFrame, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        let payload: Value = serde_json::from_slice(&frame.payload).unwrap_or(Value::Null);
+        let header = |name: &str| frame.headers.get(name).map(String::as_str).unwrap_or_default();
+        if header(":message-type") == "exception" {
+            let message = payload["message"].as_str().unwrap_or_default();
+            return self.fail(events, converse_error(header(":exception-type"), message));
+        }
+        let index = payload["contentBlockIndex"].as_u64().unwrap_or_default();
+        match header(":event-type") {
+            "messageStart" => events.push(Ok(StreamEvent::message_start())),
+            "contentBlockStart" => {
+                if let Some(start) = payload["start"]["toolUse"].as_object() {
+                    let name = start.get("name").and_then(Value::as_str).unwrap_or_default();
+                    let id = start.get("toolUseId").and_then(Value::as_str).unwrap_or_default();
+                    self.blocks.insert(index, StreamBlock::Tool(ToolUse::new(name, id), String::new()));
+                }
+            }
+            "contentBlockDelta" => {
+                let delta = &payload["delta"];
+                if let Some(text) = delta["text"].as_str() {
+                    match self.blocks.entry(index) {
+                        Entry::Occupied(_) => events.push(Ok(StreamEventPool::shared().text_delta(text))),
+                        Entry::Vacant(entry) => {
+                            entry.insert(StreamBlock::Text);
+                            events.push(Ok(StreamEvent::content_block_start(vec![StreamContent::text(text)])));
+                        }
+                    }
+                } else if let Some(fragment) = delta["toolUse"]["input"].as_str() {
+                    if let Some(StreamBlock::Tool(_, input)) = self.blocks.get_mut(&index) {
+                        input.push_str(fragment);
+                    }
+                } else if let Some(text) = delta["reasoningContent"]["text"].as_str() {
+                    events.push(Ok(StreamEventPool::shared().text_delta(text)
+                        .with_metadata("reasoning", json!(true))));
+                }
+            }
+            "contentBlockStop" => match self.blocks.remove(&index) {
+                Some(StreamBlock::Text) => events.push(Ok(StreamEvent::content_block_stop())),
+                Some(StreamBlock::Tool(mut tool_use, input)) => {
+                    tool_use.input = Some(if input.trim().is_empty() {
+                        json!({})
+                    } else {
+                        match serde_json::from_str(&input) {
+                            Ok(input) => input,
+                            Err(e) => {
+                                let error = ModelError::InvalidResponseFormat(format!(
+                                    "Invalid input for tool use {}: {}",
+                                    tool_use.tool_use_id, e
+                                ));
+                                return self.fail(events, IndubitablyError::ModelError(error));
+                            }
+                        }
+                    });
+                    events.push(Ok(StreamEvent::tool_use_start(tool_use)));
+                    events.push(Ok(StreamEvent::tool_use_stop()));
+                }
+                None => {}
+            },
+            "messageStop" => {
+                let stop_reason = payload["stopReason"].as_str().map(str::to_string);
+                self.stop_reason = Some(stop_reason.clone());
+                events.push(Ok(StreamEvent::message_delta(MessageDelta {
+                    role: None,
+                    content: None,
+                    stop_reason,
+                    stop_sequence: None,
+                })));
+            }
+            "metadata" => {
+                if let Some(usage) = payload.get("usage") {
+                    self.usage = Some(usage.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl StreamDecoder for ConverseStreamDecoder {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        self.buffer.extend_from_slice(chunk);
+        while !self.done {
+            match decode_event_stream_frame(&self.buffer) {
+                Ok(Some((frame, len))) => {
+                    self.buffer.drain(..len);
+                    self.on_frame(frame, &mut events);
+                }
+                Ok(None) => break,
+                Err(e) => self.fail(&mut events, e),
+            }
+        }
+        events
+    }
+
+    fn finish(&mut self) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        if std::mem::replace(&mut self.done, true) {
+            return events;
+        }
+        if !self.buffer.is_empty() {
+            events.push(Err(invalid_event_stream("truncated message")));
+            return events;
+        }
+        let mut stop = StreamEvent::message_stop();
+        if let Some(stop_reason) = self.stop_reason.take() {
+            stop = stop.with_metadata("stop_reason", json!(stop_reason));
+        }
+        if let Some(usage) = self.usage.take() {
+            stop = stop.with_metadata("usage", usage);
+        }
+        events.push(Ok(stop));
+        events
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Calls the Bedrock runtime `Converse` and `ConverseStream` operations.
+///
+/// The model's configuration, including its middleware, is passed with each
+/// call, so changes made after the invoker is created take effect.
+pub struct ConverseInvoker {
+    client: Arc<dyn HttpClient>,
+    credentials: AwsCredentials,
+}
+
+impl std::fmt::Debug for ConverseInvoker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConverseInvoker").finish_non_exhaustive()
+    }
+}
+
+impl ConverseInvoker {
+    /// Create an invoker sending requests through `client`, signed with `credentials`.
+    pub fn new(client: Arc<dyn HttpClient>, credentials: AwsCredentials) -> Self {
+        Self { client, credentials }
+    }
+
+    /// Build a Converse operation for a region, passed through the middleware and signed.
+    ///
+    /// `config` names the model to call, which may be an inference profile for the region.
+    async fn request(
+        &self,
+        operation: &str,
+        region: &str,
+        config: &ModelConfig,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<HttpRequest> {
+        let body = converse_request_body(config, messages, tool_specs, system_prompt);
+        let url = converse_endpoint(region, &config.model_id, operation);
+        let mut request = HttpRequest::post(&url).with_json_body(&body)?;
+        config.middleware.on_request(&mut request).await?;
+        SigV4Signer::new(self.credentials.clone(), region, BEDROCK_SIGNING_SERVICE).sign_at(&mut request, chrono::Utc::now())?;
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl BedrockInvoker for ConverseInvoker {
+    async fn invoke(
+        &self,
+        config: &ModelConfig,
+        region: &str,
+        model_id: &str,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let config = ModelConfig { model_id: model_id.to_string(), ..config.clone() };
+        let request = self.request("converse", region, &config, messages, tool_specs, system_prompt).await?;
+        let mut response = self.client.send(request).await?;
+        config.middleware.on_response(&mut response).await?;
+        if !response.is_success() {
+            return Err(status_error(&response));
+        }
+        let model_response = parse_converse_response(&response)?;
+        tracing::debug!(
+            "region=<{}>, model_id=<{}>, tool_uses=<{}> | bedrock converse response received",
+            region,
+            model_id,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn invoke_stream(
+        &self,
+        config: &ModelConfig,
+        region: &str,
+        model_id: &str,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let config = ModelConfig { model_id: model_id.to_string(), ..config.clone() };
+        let request = self.request("converse-stream", region, &config, messages, tool_specs, system_prompt).await?;
+        let response = self.client.send_streaming(request).await?;
+        if !response.is_success() {
+            let mut response = response.collect().await?;
+            config.middleware.on_response(&mut response).await?;
+            return Err(status_error(&response));
+        }
+        Ok(decode_body(response.body, ConverseStreamDecoder::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::bedrock::{BedrockConfig, BedrockModel};
+    use crate::models::http::HttpStreamingResponse;
+    use crate::models::model::Model;
+    use crate::types::{Message, ToolResult};
+    use tokio_stream::StreamExt;
+
+    fn encode_frame(headers: &[(&str, &str)], payload: &Value) -> Vec<u8> {
+        let mut header_bytes = Vec::new();
+        for (name, value) in headers {
+            header_bytes.push(name.len() as u8);
+            header_bytes.extend_from_slice(name.as_bytes());
+            header_bytes.push(7);
+            header_bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            header_bytes.extend_from_slice(value.as_bytes());
+        }
+        let payload = payload.to_string().into_bytes();
+        let total = (16 + header_bytes.len() + payload.len()) as u32;
+        let mut frame = total.to_be_bytes().to_vec();
+        frame.extend_from_slice(&(header_bytes.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&crc32(&frame).to_be_bytes());
+        frame.extend(header_bytes);
+        frame.extend(payload);
+        frame.extend_from_slice(&crc32(&frame).to_be_bytes());
+        frame
+    }
+
+    fn event(event_type: &str, payload: Value) -> Vec<u8> {
+        encode_frame(&[(":message-type", "event"), (":event-type", event_type)], &payload)
+    }
+
+    /// Records requests and answers like the Bedrock runtime.
+    #[derive(Default)]
+    struct RuntimeEndpoint {
+        requests: std::sync::Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for RuntimeEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            let streaming = request.url.ends_with("/converse-stream");
+            self.requests.lock().unwrap().push(request);
+            if !streaming {
+                let body = json!({
+                    "output": { "message": { "role": "assistant", "content": [
+                        { "reasoningContent": { "reasoningText": { "text": "Need the weather.", "signature": "sig" } } },
+                        { "text": "Checking." },
+                        { "toolUse": { "toolUseId": "tooluse_1", "name": "lookup", "input": { "city": "Paris" } } },
+                    ] } },
+                    "stopReason": "tool_use",
+                    "usage": { "inputTokens": 42, "outputTokens": 7, "totalTokens": 49 },
+                });
+                return Ok(HttpResponse::new(200, body.to_string().into_bytes()));
+            }
+            let mut body = event("messageStart", json!({ "role": "assistant" }));
+            body.extend(event("contentBlockDelta", json!({ "contentBlockIndex": 0, "delta": { "text": "Sunny" } })));
+            body.extend(event("contentBlockDelta", json!({ "contentBlockIndex": 0, "delta": { "text": " today." } })));
+            body.extend(event("contentBlockStop", json!({ "contentBlockIndex": 0 })));
+            body.extend(event(
+                "contentBlockStart",
+                json!({ "contentBlockIndex": 1, "start": { "toolUse": { "toolUseId": "tooluse_2", "name": "lookup" } } }),
+            ));
+            body.extend(event("contentBlockDelta", json!({ "contentBlockIndex": 1, "delta": { "toolUse": { "input": "{\"city\":" } } })));
+            body.extend(event("contentBlockDelta", json!({ "contentBlockIndex": 1, "delta": { "toolUse": { "input": "\"Oslo\"}" } } })));
+            body.extend(event("contentBlockStop", json!({ "contentBlockIndex": 1 })));
+            body.extend(event("messageStop", json!({ "stopReason": "tool_use" })));
+            body.extend(event("metadata", json!({ "usage": { "inputTokens": 5, "outputTokens": 3 } })));
+            Ok(HttpResponse::new(200, body))
+        }
+
+        async fn send_streaming(&self, request: HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+            // Deliver the body in small pieces so messages span chunk boundaries
+            let response = self.send(request).await?;
+            let chunks: Vec<_> = response.body.chunks(11).map(|chunk| Ok(chunk.to_vec())).collect();
+            Ok(HttpStreamingResponse {
+                status: response.status,
+                headers: response.headers,
+                body: Box::pin(tokio_stream::iter(chunks)),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_converse_generate_and_stream() {
+        let endpoint = Arc::new(RuntimeEndpoint::default());
+        let model = BedrockModel::with_client(
+            BedrockConfig::new().with_region("us-east-1").with_model_id("anthropic.claude-3-5-sonnet-20240620-v1:0"),
+            endpoint.clone(),
+            AwsCredentials::new("AKIDEXAMPLE", "secret"),
+        );
+        let messages = vec![
+            Message::user("Weather in Paris?"),
+            Message::assistant_with_tool_uses("", vec![ToolUse::new("lookup", "tooluse_0").with_input(json!({}))]),
+            Message::tool_results(vec![ToolResult::error("tooluse_0", "missing city")]),
+        ];
+        let specs = vec![ToolSpec::new("lookup", "Look up the weather")];
+
+        let response = model.generate(&messages, Some(&specs), Some("Be brief.")).await.unwrap();
+        assert_eq!(response.content, "Checking.");
+        assert_eq!(response.tool_uses[0].input, Some(json!({ "city": "Paris" })));
+        assert_eq!(response.reasoning[0].reasoning_text.signature.as_deref(), Some("sig"));
+        assert_eq!(response.usage.as_ref().map(|usage| usage.total_tokens), Some(49));
+        assert_eq!(response.metadata["stop_reason"], json!("tool_use"));
+
+        let request = endpoint.requests.lock().unwrap()[0].clone();
+        assert_eq!(
+            request.url,
+            "https://bedrock-runtime.us-east-1.amazonaws.com/model/anthropic.claude-3-5-sonnet-20240620-v1%3A0/converse"
+        );
+        assert!(request.header("authorization").unwrap().contains("/us-east-1/bedrock/aws4_request"));
+        assert_eq!(request.headers.iter().filter(|(name, _)| name == "content-type").count(), 1);
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["system"], json!([{ "text": "Be brief." }]));
+        assert_eq!(body["messages"][2]["content"][0]["toolResult"]["status"], "error");
+        assert_eq!(body["toolConfig"]["tools"][0]["toolSpec"]["name"], "lookup");
+        assert_eq!(body["additionalModelRequestFields"]["top_k"], 250);
+
+        let events: Vec<StreamEvent> = model.stream(&messages, Some(&specs), None).await.unwrap().map(Result::unwrap).collect().await;
+        let texts: Vec<&str> = events
+            .iter()
+            .filter_map(|event| event.content.as_ref()?.first()?.text.as_deref())
+            .collect();
+        assert_eq!(texts, vec!["Sunny", " today."]);
+        let tool_use = events.iter().find_map(|event| event.tool_use.as_ref()).unwrap();
+        assert_eq!(tool_use.input, Some(json!({ "city": "Oslo" })));
+        let stop = events.last().unwrap();
+        assert_eq!(stop.metadata.as_ref().unwrap()["usage"]["outputTokens"], 3);
+
+        // Configuration changes after the model is built reach the next request
+        let mut model = model;
+        model.config_mut().temperature = Some(0.25);
+        model.generate(&messages, None, None).await.unwrap();
+        let request = endpoint.requests.lock().unwrap().last().unwrap().clone();
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["inferenceConfig"]["temperature"], 0.25);
+    }
+}
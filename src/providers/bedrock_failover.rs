@@ -0,0 +1,207 @@
+//! Region failover for the Bedrock provider.
+//! 
+//! This module provides `RegionFailover`, which orders a prioritized list of
+//! AWS regions for each Bedrock call. A region that throttles or is
+//! unavailable cools down for a while and is tried only after the healthy
+//! ones; each call's latency is recorded as a per-region metric.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::telemetry::metrics::{labeled_name, Metrics};
+use crate::types::{IndubitablyError, ModelError};
+
+/// The default time a failed region is skipped for.
+pub const DEFAULT_REGION_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// The gauge holding the latency of the last call to a region, in milliseconds.
+pub const METRIC_BEDROCK_REGION_LATENCY_MS: &str = "bedrock.region.latency_ms";
+
+/// The metric counting successful calls to a region.
+pub const METRIC_BEDROCK_REGION_REQUESTS: &str = "bedrock.region.requests";
+
+/// The metric counting region-level failures.
+pub const METRIC_BEDROCK_REGION_FAILURES: &str = "bedrock.region.failures";
+
+/// The metric counting calls answered by a region other than the first one tried.
+pub const METRIC_BEDROCK_FAILOVERS: &str = "bedrock.failovers";
+
+/// Check whether an error means the region, rather than the request, is the problem.
+pub fn is_region_failure(error: &IndubitablyError) -> bool {
+    matches!(
+        error,
+        IndubitablyError::ModelError(
+            ModelError::ModelThrottled(_)
+                | ModelError::ModelNotAvailable(_)
+                | ModelError::QuotaExceeded(_)
+                | ModelError::RequestFailed(_)
+        )
+    )
+}
+
+#[derive(Debug, Default)]
+struct RegionState {
+    cooling_until: Option<Instant>,
+}
+
+/// Tracks region health and orders regions for failover.
+#[derive(Debug)]
+pub struct RegionFailover {
+    cooldown: Duration,
+    states: Mutex<HashMap<String, RegionState>>,
+    metrics: Mutex<Metrics>,
+}
+
+impl Default for RegionFailover {
+    fn default() -> Self {
+        Self::new(DEFAULT_REGION_COOLDOWN)
+    }
+}
+
+impl RegionFailover {
+    /// Create a tracker that skips failed regions for the given cooldown.
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            states: Mutex::new(HashMap::new()),
+            metrics: Mutex::new(Metrics::new()),
+        }
+    }
+
+    /// Check whether a region is not cooling down after a failure.
+    pub fn is_available(&self, region: &str) -> bool {
+        let states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        cooling_until(&states, region).is_none()
+    }
+
+    /// Order regions for a call: available ones by priority, then cooling ones by recovery time.
+    pub fn order(&self, regions: &[String]) -> Vec<String> {
+        let states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut cooling, available): (Vec<&String>, Vec<&String>) =
+            regions.iter().partition(|region| cooling_until(&states, region).is_some());
+        cooling.sort_by_key(|region| cooling_until(&states, region));
+        available.into_iter().chain(cooling).cloned().collect()
+    }
+
+    /// Record a successful call and its latency.
+    pub fn record_success(&self, region: &str, latency: Duration) {
+        self.states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(region.to_string())
+            .or_default()
+            .cooling_until = None;
+
+        let labels = region_labels(region);
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        metrics.increment_labeled(METRIC_BEDROCK_REGION_REQUESTS, &labels, 1.0);
+        metrics.set(
+            &labeled_name(METRIC_BEDROCK_REGION_LATENCY_MS, &labels),
+            latency.as_secs_f64() * 1000.0,
+        );
+    }
+
+    /// Record a region-level failure, putting the region into cooldown.
+    pub fn record_failure(&self, region: &str) {
+        self.states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(region.to_string())
+            .or_default()
+            .cooling_until = Some(Instant::now() + self.cooldown);
+        self.metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .increment_labeled(METRIC_BEDROCK_REGION_FAILURES, &region_labels(region), 1.0);
+    }
+
+    /// Record that a call was answered after failing over to another region.
+    pub fn record_failover(&self) {
+        self.metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .increment(METRIC_BEDROCK_FAILOVERS, 1.0);
+    }
+
+    /// Get a copy of the per-region metrics.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+fn cooling_until(states: &HashMap<String, RegionState>, region: &str) -> Option<Instant> {
+    states
+        .get(region)
+        .and_then(|state| state.cooling_until)
+        .filter(|until| *until > Instant::now())
+}
+
+fn region_labels(region: &str) -> BTreeMap<String, String> {
+    BTreeMap::from([("region".to_string(), region.to_string())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::bedrock::{BedrockConfig, BedrockInvoker, BedrockModel};
+    use crate::models::model::{Model, ModelConfig, ModelResponse};
+    use crate::types::{Message, Messages, ToolSpec};
+    use async_trait::async_trait;
+    use std::sync::Arc;
+
+    /// Throttles in us-east-1 and records the regions and model IDs it was called with.
+    #[derive(Default)]
+    struct ThrottledEastInvoker {
+        calls: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl BedrockInvoker for ThrottledEastInvoker {
+        async fn invoke(
+            &self,
+            _config: &ModelConfig,
+            region: &str,
+            model_id: &str,
+            _messages: &Messages,
+            _tool_specs: Option<&[ToolSpec]>,
+            _system_prompt: Option<&str>,
+        ) -> crate::types::IndubitablyResult<ModelResponse> {
+            self.calls.lock().unwrap().push((region.to_string(), model_id.to_string()));
+            if region == "us-east-1" {
+                return Err(IndubitablyError::ModelError(ModelError::ModelThrottled("slow down".to_string())));
+            }
+            Ok(ModelResponse::new("ok"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bedrock_region_failover() {
+        let invoker = Arc::new(ThrottledEastInvoker::default());
+        let config = BedrockConfig::new()
+            .with_region("us-east-1")
+            .with_fallback_region("us-west-2")
+            .with_fallback_region("us-east-1")
+            .with_inference_profile("us");
+        let model = BedrockModel::with_config(config).with_invoker(invoker.clone());
+        let messages = vec![Message::user("Hi")];
+
+        let first = model.generate(&messages, None, None).await.unwrap();
+        assert_eq!(first.metadata["region"], "us-west-2");
+        // The throttled region cools down, so the next call goes straight to the fallback.
+        model.generate(&messages, None, None).await.unwrap();
+
+        let calls = invoker.calls.lock().unwrap().clone();
+        let regions: Vec<&str> = calls.iter().map(|(region, _)| region.as_str()).collect();
+        assert_eq!(regions, vec!["us-east-1", "us-west-2", "us-west-2"]);
+        assert!(calls[0].1.starts_with("us.anthropic."));
+
+        let metrics = model.region_metrics();
+        assert_eq!(metrics.get(METRIC_BEDROCK_FAILOVERS), Some(1.0));
+        assert_eq!(metrics.get("bedrock.region.requests{region=\"us-west-2\"}"), Some(2.0));
+        assert_eq!(metrics.get("bedrock.region.failures{region=\"us-east-1\"}"), Some(1.0));
+        assert!(metrics.get("bedrock.region.latency_ms{region=\"us-west-2\"}").is_some());
+        assert!(is_region_failure(&IndubitablyError::ModelError(ModelError::ModelNotAvailable("down".to_string()))));
+        assert!(!is_region_failure(&IndubitablyError::ModelError(ModelError::ContextWindowOverflow("big".to_string()))));
+    }
+}
@@ -0,0 +1,197 @@
+//! Bedrock image generation for the SDK.
+//! 
+//! This module provides `BedrockImageModel`, an `ImageGenerationModel` for
+//! Amazon Titan Image Generator and Stability SDXL on Bedrock.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::bedrock_converse::{status_error as bedrock_status_error, BEDROCK_SIGNING_SERVICE};
+use crate::models::http::{HttpClient, HttpRequest};
+use crate::models::image::{ImageGenerationModel, ImageRequest};
+use crate::models::signing::{uri_encode, AwsCredentials, SigV4Signer};
+use crate::types::{ImageContent, IndubitablyError, IndubitablyResult, ModelError};
+
+/// Default Bedrock image model ID.
+pub const DEFAULT_BEDROCK_IMAGE_MODEL_ID: &str = "amazon.titan-image-generator-v2:0";
+
+/// Generates images with Amazon Titan Image Generator or Stability SDXL on Bedrock.
+pub struct BedrockImageModel {
+    client: Arc<dyn HttpClient>,
+    credentials: AwsCredentials,
+    region: String,
+    model_id: String,
+}
+
+impl std::fmt::Debug for BedrockImageModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BedrockImageModel")
+            .field("region", &self.region)
+            .field("model_id", &self.model_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BedrockImageModel {
+    /// Create a model sending requests to a region through `client`, signed with `credentials`.
+    pub fn new(client: Arc<dyn HttpClient>, credentials: AwsCredentials, region: &str) -> Self {
+        Self {
+            client,
+            credentials,
+            region: region.to_string(),
+            model_id: DEFAULT_BEDROCK_IMAGE_MODEL_ID.to_string(),
+        }
+    }
+
+    /// Set the model ID, a Titan Image Generator or `stability.stable-diffusion-xl` model.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    fn is_stability(&self) -> bool {
+        self.model_id.starts_with("stability.")
+    }
+
+    /// Build the InvokeModel body for the model family.
+    fn request_body(&self, request: &ImageRequest) -> Value {
+        if self.is_stability() {
+            let mut prompts = vec![json!({ "text": request.prompt, "weight": 1.0 })];
+            if let Some(ref negative) = request.negative_prompt {
+                prompts.push(json!({ "text": negative, "weight": -1.0 }));
+            }
+            let mut body = json!({
+                "text_prompts": prompts,
+                "width": request.width,
+                "height": request.height,
+                "samples": request.count,
+            });
+            if let Some(seed) = request.seed {
+                body["seed"] = json!(seed);
+            }
+            return body;
+        }
+
+        let mut params = json!({ "text": request.prompt });
+        if let Some(ref negative) = request.negative_prompt {
+            params["negativeText"] = json!(negative);
+        }
+        let mut config = json!({
+            "numberOfImages": request.count,
+            "width": request.width,
+            "height": request.height,
+        });
+        if let Some(seed) = request.seed {
+            config["seed"] = json!(seed);
+        }
+        json!({ "taskType": "TEXT_IMAGE", "textToImageParams": params, "imageGenerationConfig": config })
+    }
+}
+
+#[async_trait]
+impl ImageGenerationModel for BedrockImageModel {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    async fn generate_images(&self, request: &ImageRequest) -> IndubitablyResult<Vec<ImageContent>> {
+        let url = format!(
+            "https://bedrock-runtime.{}.amazonaws.com/model/{}/invoke",
+            self.region,
+            uri_encode(&self.model_id, true)
+        );
+        let mut http_request = HttpRequest::post(&url)
+            .with_header("accept", "application/json")
+            .with_json_body(&self.request_body(request))?;
+        SigV4Signer::new(self.credentials.clone(), &self.region, BEDROCK_SIGNING_SERVICE)
+            .sign_at(&mut http_request, chrono::Utc::now())?;
+        let response = self.client.send(http_request).await?;
+        if !response.is_success() {
+            return Err(bedrock_status_error(&response));
+        }
+
+        let body: Value = response.json()?;
+        let images: Vec<ImageContent> = if self.is_stability() {
+            body["artifacts"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|artifact| artifact["finishReason"].as_str().is_none_or(|reason| reason == "SUCCESS"))
+                .filter_map(|artifact| artifact["base64"].as_str())
+                .map(|base64| ImageContent::base64(base64, "image/png"))
+                .collect()
+        } else {
+            if let Some(error) = body["error"].as_str() {
+                return Err(IndubitablyError::ModelError(ModelError::RequestFailed(format!("Bedrock {}", error))));
+            }
+            body["images"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(|base64| ImageContent::base64(base64, "image/png"))
+                .collect()
+        };
+        if images.is_empty() {
+            return Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat("Bedrock returned no images".to_string())));
+        }
+        tracing::debug!(
+            "region=<{}>, model_id=<{}>, images=<{}> | bedrock images generated",
+            self.region,
+            self.model_id,
+            images.len()
+        );
+        Ok(images)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+
+    /// Records request bodies and answers in the format of the requested model family.
+    #[derive(Default)]
+    struct ImagesEndpoint {
+        bodies: std::sync::Mutex<Vec<(String, Value)>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for ImagesEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert!(request.header("authorization").unwrap().starts_with("AWS4-HMAC-SHA256"));
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            self.bodies.lock().unwrap().push((request.url.clone(), body));
+            let reply = if request.url.contains("stability") {
+                json!({ "artifacts": [{ "base64": "c2R4bA==", "finishReason": "SUCCESS" }, { "base64": "", "finishReason": "CONTENT_FILTERED" }] })
+            } else {
+                json!({ "images": ["dGl0YW4=", "dGl0YW4y"] })
+            };
+            Ok(HttpResponse::new(200, reply.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bedrock_image_models() {
+        let endpoint = Arc::new(ImagesEndpoint::default());
+        let request = ImageRequest::new("a lighthouse at dusk").with_negative_prompt("people").with_count(2).with_seed(7);
+
+        let credentials = AwsCredentials::new("AKID", "secret");
+        let titan = BedrockImageModel::new(endpoint.clone(), credentials.clone(), "us-east-1");
+        assert_eq!(titan.generate_images(&request).await.unwrap().len(), 2);
+
+        let sdxl = BedrockImageModel::new(endpoint.clone(), credentials, "us-west-2").with_model_id("stability.stable-diffusion-xl-v1");
+        let images = sdxl.generate_images(&request).await.unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].source.media_type, "image/png");
+
+        let bodies = endpoint.bodies.lock().unwrap().clone();
+        assert_eq!(bodies[0].0, "https://bedrock-runtime.us-east-1.amazonaws.com/model/amazon.titan-image-generator-v2%3A0/invoke");
+        assert_eq!(bodies[0].1["textToImageParams"]["negativeText"], "people");
+        assert_eq!(bodies[0].1["imageGenerationConfig"]["numberOfImages"], 2);
+        assert_eq!(bodies[1].1["text_prompts"][1]["weight"], -1.0);
+        assert_eq!(bodies[1].1["seed"], 7);
+    }
+}
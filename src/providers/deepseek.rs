@@ -0,0 +1,341 @@
+//! DeepSeek model implementation for the SDK.
+//! 
+//! This module provides `DeepSeekModel`, which talks to DeepSeek's
+//! OpenAI-compatible chat completions endpoint. It serves both the chat
+//! model and the reasoner model; the reasoner's chain of thought comes back
+//! as reasoning content blocks and its reasoning tokens are reported
+//! separately in usage, so budgets and metrics can account for them.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::models::http::{HttpClient, HttpRequest, UnconfiguredHttpClient};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::decode_body;
+use super::openai_compat::{chat_request_body, parse_chat_response, status_error, ChatStreamDecoder};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, ToolSpec};
+
+/// The base URL of the DeepSeek API.
+pub const DEEPSEEK_BASE_URL: &str = "https://api.deepseek.com";
+
+/// The environment variable holding the DeepSeek API key.
+pub const DEEPSEEK_API_KEY_ENV: &str = "DEEPSEEK_API_KEY";
+
+/// The DeepSeek chat model ID.
+pub const DEEPSEEK_CHAT_MODEL_ID: &str = "deepseek-chat";
+
+/// The DeepSeek reasoner model ID.
+pub const DEEPSEEK_REASONER_MODEL_ID: &str = "deepseek-reasoner";
+
+/// Request parameters the reasoner rejects or ignores.
+const REASONER_UNSUPPORTED_PARAMS: &[&str] = &[
+    "temperature",
+    "top_p",
+    "presence_penalty",
+    "frequency_penalty",
+    "logprobs",
+    "top_logprobs",
+];
+
+/// Check whether a DeepSeek model ID names a reasoning model.
+pub fn is_reasoner(model_id: &str) -> bool {
+    model_id.contains("reasoner") || model_id.contains("-r1")
+}
+
+/// Configuration specific to DeepSeek models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepSeekConfig {
+    /// The DeepSeek API key.
+    pub api_key: String,
+    /// The base URL of the API.
+    pub base_url: String,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation, ignored by the reasoner.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate, including reasoning.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling, ignored by the reasoner.
+    pub top_p: Option<f32>,
+}
+
+impl Default for DeepSeekConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: DEEPSEEK_BASE_URL.to_string(),
+            model_id: DEEPSEEK_CHAT_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: None,
+        }
+    }
+}
+
+impl DeepSeekConfig {
+    /// Create a new DeepSeek configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a configuration with the API key from `DEEPSEEK_API_KEY`.
+    pub fn from_env() -> Self {
+        Self::default().with_api_key(&std::env::var(DEEPSEEK_API_KEY_ENV).unwrap_or_default())
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Set the base URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+}
+
+/// The DeepSeek model implementation.
+pub struct DeepSeekModel {
+    config: ModelConfig,
+    deepseek_config: DeepSeekConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl std::fmt::Debug for DeepSeekModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeepSeekModel")
+            .field("config", &self.config)
+            .field("base_url", &self.deepseek_config.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeepSeekModel {
+    /// Create a new DeepSeek model.
+    pub fn new() -> Self {
+        Self::with_config(DeepSeekConfig::default())
+    }
+
+    /// Create a new DeepSeek model with the given configuration.
+    pub fn with_config(deepseek_config: DeepSeekConfig) -> Self {
+        let mut config = ModelConfig::new(&deepseek_config.model_id);
+        config.temperature = deepseek_config.temperature;
+        config.max_tokens = deepseek_config.max_tokens;
+        config.top_p = deepseek_config.top_p;
+        Self {
+            config,
+            deepseek_config,
+            client: Arc::new(UnconfiguredHttpClient::new("DeepSeek")),
+        }
+    }
+
+    /// Set the client that sends requests to the DeepSeek API.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Check whether the configured model is a reasoning model.
+    pub fn is_reasoner(&self) -> bool {
+        is_reasoner(&self.config.model_id)
+    }
+
+    /// Build a request body, dropping sampling parameters the reasoner does not accept.
+    fn request_body(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> serde_json::Value {
+        let mut body = chat_request_body(&self.config, messages, tool_specs, system_prompt);
+        for (key, value) in &self.config.extra {
+            body[key] = value.clone();
+        }
+        if self.is_reasoner() {
+            if let Some(fields) = body.as_object_mut() {
+                fields.retain(|key, _| !REASONER_UNSUPPORTED_PARAMS.contains(&key.as_str()));
+            }
+        }
+        body
+    }
+
+    /// Build a chat completions request, streamed with usage when asked.
+    fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let mut body = self.request_body(messages, tool_specs, system_prompt);
+        if stream {
+            body["stream"] = json!(true);
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        HttpRequest::post(&format!("{}/chat/completions", self.deepseek_config.base_url))
+            .with_header("authorization", &format!("Bearer {}", self.deepseek_config.api_key))
+            .with_json_body(&body)
+    }
+}
+
+#[async_trait]
+impl Model for DeepSeekModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, false)?;
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error("DeepSeek", &response));
+        }
+        let model_response = parse_chat_response(&response)?;
+        tracing::debug!(
+            "model_id=<{}>, reasoning_tokens=<{}> | deepseek response received",
+            self.config.model_id,
+            model_response.usage.as_ref().map_or(0, |usage| usage.reasoning_tokens)
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, true)?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error("DeepSeek", &response.collect().await?));
+        }
+        Ok(decode_body(response.body, ChatStreamDecoder::default()))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(
+            "DeepSeek model does not support structured output yet".to_string(),
+        )))
+    }
+}
+
+impl Default for DeepSeekModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{AgentBuilder, ConversationBudget};
+    use crate::models::http::HttpResponse;
+    use serde_json::json;
+
+    /// Records request bodies and answers like the reasoner.
+    #[derive(Default)]
+    struct ReasonerEndpoint {
+        bodies: std::sync::Mutex<Vec<serde_json::Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for ReasonerEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.bodies.lock().unwrap().push(serde_json::from_slice(&request.body).unwrap());
+            let body = json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "reasoning_content": "9.11 has fewer tenths than 9.8.",
+                        "content": "9.8 is larger."
+                    },
+                    "finish_reason": "stop"
+                }],
+                "usage": {
+                    "prompt_tokens": 14,
+                    "completion_tokens": 120,
+                    "total_tokens": 134,
+                    "completion_tokens_details": { "reasoning_tokens": 100 }
+                }
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deepseek_reasoner_accounting() {
+        let endpoint = Arc::new(ReasonerEndpoint::default());
+        let model = DeepSeekModel::with_config(
+            DeepSeekConfig::new().with_api_key("sk-test").with_model_id(DEEPSEEK_REASONER_MODEL_ID),
+        )
+        .with_client(endpoint.clone());
+        assert!(model.is_reasoner());
+
+        let mut agent = AgentBuilder::new()
+            .model(Box::new(model))
+            .budget(ConversationBudget::new().with_max_cost(10.0, 0.5, 2.0))
+            .build()
+            .unwrap();
+        let result = agent.run("Which is larger, 9.11 or 9.8?").await.unwrap();
+
+        assert_eq!(result.response, "9.8 is larger.");
+        let reasoning = result.response_message.reasoning();
+        assert_eq!(reasoning[0].reasoning_text.text, "9.11 has fewer tenths than 9.8.");
+        let usage = agent.budget_usage();
+        assert_eq!((usage.output_tokens, usage.reasoning_tokens), (120, 100));
+        assert!((usage.reasoning_cost - 0.2).abs() < 1e-9);
+
+        let body = endpoint.bodies.lock().unwrap()[0].clone();
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["model"], DEEPSEEK_REASONER_MODEL_ID);
+        assert!(!is_reasoner(DEEPSEEK_CHAT_MODEL_ID));
+    }
+}
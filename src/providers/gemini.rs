@@ -0,0 +1,952 @@
+//! Gemini model implementation for the SDK.
+//! 
+//! This module provides access to Gemini models through the Gemini API's
+//! `generateContent` and `streamGenerateContent` endpoints, keyed by an API
+//! key. It also holds the Gemini wire format shared with the Vertex AI
+//! provider: function declarations and calls, safety settings, and system
+//! content blocks sent as the `systemInstruction`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::models::http::{HttpClient, HttpRequest, HttpResponse, UnconfiguredHttpClient};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::{decode_all, decode_body, sse_data, LineSplitter, StreamDecoder};
+use crate::types::streaming::{MessageDelta, StreamContent, StreamEventPool};
+use crate::types::{
+    Citation, IndubitablyError, IndubitablyResult, MessageRole, Messages, ModelError, StreamEvent, SystemContentBlock,
+    ToolSpec, ToolUse,
+};
+
+/// Default Gemini model ID.
+pub const DEFAULT_GEMINI_MODEL_ID: &str = "gemini-2.0-flash";
+
+/// The base URL of the Gemini API.
+pub const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// The environment variable holding the Gemini API key.
+pub const GEMINI_API_KEY_ENV: &str = "GEMINI_API_KEY";
+
+/// A category of harmful content Gemini can filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HarmCategory {
+    /// Harassment.
+    #[serde(rename = "HARM_CATEGORY_HARASSMENT")]
+    Harassment,
+    /// Hate speech.
+    #[serde(rename = "HARM_CATEGORY_HATE_SPEECH")]
+    HateSpeech,
+    /// Sexually explicit content.
+    #[serde(rename = "HARM_CATEGORY_SEXUALLY_EXPLICIT")]
+    SexuallyExplicit,
+    /// Dangerous content.
+    #[serde(rename = "HARM_CATEGORY_DANGEROUS_CONTENT")]
+    DangerousContent,
+    /// Civic integrity.
+    #[serde(rename = "HARM_CATEGORY_CIVIC_INTEGRITY")]
+    CivicIntegrity,
+}
+
+/// The probability of harm at which Gemini blocks content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HarmBlockThreshold {
+    /// Block low, medium and high probability content.
+    BlockLowAndAbove,
+    /// Block medium and high probability content.
+    BlockMediumAndAbove,
+    /// Block only high probability content.
+    BlockOnlyHigh,
+    /// Block nothing, but still report safety ratings.
+    BlockNone,
+    /// Turn the safety filter off.
+    Off,
+}
+
+/// A safety filter setting for one harm category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetySetting {
+    /// The harm category.
+    pub category: HarmCategory,
+    /// The blocking threshold.
+    pub threshold: HarmBlockThreshold,
+}
+
+/// Configuration specific to Gemini models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiConfig {
+    /// The Gemini API key.
+    pub api_key: String,
+    /// The base URL of the API.
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// The top-k value for sampling.
+    pub top_k: Option<u32>,
+    /// The safety filter settings.
+    #[serde(default)]
+    pub safety_settings: Vec<SafetySetting>,
+    /// System content sent ahead of the system prompt in every request.
+    #[serde(default)]
+    pub system_instruction: Vec<SystemContentBlock>,
+    /// Additional `generationConfig` fields.
+    #[serde(default)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl Default for GeminiConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: default_base_url(),
+            model_id: DEFAULT_GEMINI_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: None,
+            top_k: None,
+            safety_settings: Vec::new(),
+            system_instruction: Vec::new(),
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl GeminiConfig {
+    /// Create a new Gemini configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a configuration with the API key from `GEMINI_API_KEY`.
+    pub fn from_env() -> Self {
+        Self::new().with_api_key(&std::env::var(GEMINI_API_KEY_ENV).unwrap_or_default())
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Set the base URL of the API.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set the top-k value.
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Set the blocking threshold for a harm category.
+    pub fn with_safety_setting(mut self, category: HarmCategory, threshold: HarmBlockThreshold) -> Self {
+        self.safety_settings.retain(|setting| setting.category != category);
+        self.safety_settings.push(SafetySetting { category, threshold });
+        self
+    }
+
+    /// Add a system content block sent with every request.
+    pub fn with_system_content(mut self, block: SystemContentBlock) -> Self {
+        self.system_instruction.push(block);
+        self
+    }
+
+    /// Add an additional `generationConfig` field.
+    pub fn with_extra(mut self, key: &str, value: Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+}
+
+fn default_base_url() -> String {
+    GEMINI_BASE_URL.to_string()
+}
+
+/// Build a `generateContent` request body.
+///
+/// System messages join the given system content in the `systemInstruction`,
+/// one part per block. Gemini identifies function responses by name, so the
+/// name of each tool result's call is looked up by its ID.
+pub fn generate_content_body(
+    config: &ModelConfig,
+    messages: &Messages,
+    tool_specs: Option<&[ToolSpec]>,
+    system: &[SystemContentBlock],
+    safety_settings: &[SafetySetting],
+) -> Value {
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    let mut system_texts: Vec<&str> = system.iter().map(|block| block.text.as_str()).collect();
+    let mut contents = Vec::new();
+    for message in messages {
+        let role = match message.role {
+            MessageRole::System => {
+                system_texts.extend(message.content.iter().filter_map(|block| block.text.as_deref()));
+                continue;
+            }
+            MessageRole::Assistant => "model",
+            MessageRole::User | MessageRole::Tool => "user",
+        };
+        let mut parts = Vec::new();
+        for block in &message.content {
+            if let Some(ref text) = block.text {
+                parts.push(json!({ "text": text }));
+            }
+            if let Some(ref image) = block.image {
+                match image.url_data() {
+                    Some(url) => parts.push(json!({
+                        "fileData": { "mimeType": image.source.media_type, "fileUri": url }
+                    })),
+                    None => parts.extend(image.base64_data().map(|data| {
+                        json!({ "inlineData": { "mimeType": image.source.media_type, "data": data } })
+                    })),
+                }
+            }
+            if let Some(ref document) = block.document {
+                parts.extend(document.base64_data().map(|data| {
+                    json!({ "inlineData": { "mimeType": document.source.media_type, "data": data } })
+                }));
+            }
+            if let Some(ref tool_use) = block.tool_use {
+                tool_names.insert(&tool_use.tool_use_id, &tool_use.name);
+                parts.push(json!({
+                    "functionCall": {
+                        "name": tool_use.name,
+                        "args": tool_use.input.clone().unwrap_or_else(|| json!({})),
+                    }
+                }));
+            }
+            if let Some(ref result) = block.tool_result {
+                let text: Vec<&str> = result.content.iter().filter_map(|content| content.text.as_deref()).collect();
+                let key = if result.is_error == Some(true) { "error" } else { "content" };
+                parts.push(json!({
+                    "functionResponse": {
+                        "name": tool_names.get(result.tool_use_id.as_str()).copied().unwrap_or_default(),
+                        "response": { key: text.join("\n") },
+                    }
+                }));
+            }
+        }
+        if !parts.is_empty() {
+            contents.push(json!({ "role": role, "parts": parts }));
+        }
+    }
+
+    let mut generation_config = serde_json::Map::new();
+    if let Some(temperature) = config.temperature {
+        generation_config.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(max_tokens) = config.max_tokens {
+        generation_config.insert("maxOutputTokens".to_string(), json!(max_tokens));
+    }
+    if let Some(top_p) = config.top_p {
+        generation_config.insert("topP".to_string(), json!(top_p));
+    }
+    if let Some(top_k) = config.top_k {
+        generation_config.insert("topK".to_string(), json!(top_k));
+    }
+
+    let mut body = json!({ "contents": contents, "generationConfig": generation_config });
+    let system_texts: Vec<&str> = system_texts.into_iter().filter(|text| !text.is_empty()).collect();
+    if !system_texts.is_empty() {
+        let parts: Vec<Value> = system_texts.iter().map(|text| json!({ "text": text })).collect();
+        body["systemInstruction"] = json!({ "parts": parts });
+    }
+    if let Some(specs) = tool_specs.filter(|specs| !specs.is_empty()) {
+        let declarations: Vec<Value> = specs
+            .iter()
+            .map(|spec| {
+                let mut declaration = json!({ "name": spec.name, "description": spec.description });
+                if let Some(ref schema) = spec.input_schema {
+                    declaration["parameters"] = schema.clone();
+                }
+                declaration
+            })
+            .collect();
+        body["tools"] = json!([{ "functionDeclarations": declarations }]);
+    }
+    if !safety_settings.is_empty() {
+        body["safetySettings"] = json!(safety_settings);
+    }
+    body
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    #[serde(default)]
+    usage_metadata: Option<UsageMetadata>,
+    #[serde(default)]
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    #[serde(default)]
+    content: Option<CandidateContent>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+    #[serde(default)]
+    grounding_metadata: Option<GroundingMetadata>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroundingMetadata {
+    #[serde(default)]
+    grounding_chunks: Vec<GroundingChunk>,
+    #[serde(default)]
+    grounding_supports: Vec<GroundingSupport>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroundingChunk {
+    #[serde(default)]
+    web: Option<GroundingSource>,
+    #[serde(default)]
+    retrieved_context: Option<GroundingSource>,
+}
+
+#[derive(Deserialize)]
+struct GroundingSource {
+    #[serde(default)]
+    uri: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GroundingSupport {
+    #[serde(default)]
+    segment: Option<Segment>,
+    #[serde(default)]
+    grounding_chunk_indices: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Segment {
+    #[serde(default)]
+    start_index: usize,
+    #[serde(default)]
+    end_index: usize,
+}
+
+#[derive(Deserialize)]
+struct CandidateContent {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    thought: bool,
+    #[serde(default)]
+    function_call: Option<FunctionCall>,
+}
+
+#[derive(Deserialize)]
+struct FunctionCall {
+    #[serde(default)]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Option<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    thoughts_token_count: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    #[serde(default)]
+    block_reason: Option<String>,
+}
+
+impl FunctionCall {
+    /// Convert the call, generating the ID Gemini usually leaves out.
+    fn into_tool_use(self) -> ToolUse {
+        let id = self.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let mut tool_use = ToolUse::new(&self.name, &id);
+        tool_use.input = self.args;
+        tool_use
+    }
+}
+
+impl GroundingMetadata {
+    /// Convert to citations, one per source of each supported segment.
+    ///
+    /// Without supports, every source is cited for the whole response.
+    fn citations(&self) -> Vec<Citation> {
+        let source = |index: usize| {
+            let chunk = self.grounding_chunks.get(index)?;
+            let source = chunk.web.as_ref().or(chunk.retrieved_context.as_ref())?;
+            let mut citation = Citation::new(&source.uri.clone().unwrap_or_else(|| format!("chunk-{}", index)));
+            if let Some(ref uri) = source.uri {
+                citation = citation.with_uri(uri);
+            }
+            if let Some(ref title) = source.title {
+                citation = citation.with_title(title);
+            }
+            if let Some(ref text) = source.text {
+                citation = citation.with_snippet(text);
+            }
+            Some(citation)
+        };
+        if self.grounding_supports.is_empty() {
+            return (0..self.grounding_chunks.len()).filter_map(source).collect();
+        }
+        self.grounding_supports
+            .iter()
+            .flat_map(|support| {
+                support.grounding_chunk_indices.iter().filter_map(move |&index| {
+                    let citation = source(index)?;
+                    Some(match support.segment {
+                        Some(ref segment) => citation.with_span(segment.start_index, segment.end_index),
+                        None => citation,
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+/// Fail on a response whose prompt was blocked by the safety filters.
+fn check_prompt_feedback(response: &GenerateContentResponse, provider: &str) -> IndubitablyResult<()> {
+    match response.prompt_feedback.as_ref().and_then(|feedback| feedback.block_reason.as_ref()) {
+        Some(reason) => Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(format!(
+            "{} blocked the prompt: {}",
+            provider, reason
+        )))),
+        None => Ok(()),
+    }
+}
+
+/// Map a `generateContent` response body to a model response.
+pub(crate) fn parse_generate_content(response: &HttpResponse, provider: &str) -> IndubitablyResult<ModelResponse> {
+    let response: GenerateContentResponse = response.json()?;
+    check_prompt_feedback(&response, provider)?;
+    let candidate = response.candidates.into_iter().next().ok_or_else(|| {
+        IndubitablyError::ModelError(ModelError::InvalidResponseFormat(format!("{} returned no candidates", provider)))
+    })?;
+
+    let mut model_response = ModelResponse::new("");
+    for part in candidate.content.map(|content| content.parts).unwrap_or_default() {
+        match part.text {
+            Some(text) if part.thought => model_response = model_response.with_reasoning(&text),
+            Some(text) => model_response.content.push_str(&text),
+            None => {}
+        }
+        if let Some(call) = part.function_call {
+            model_response = model_response.with_tool_use(call.into_tool_use());
+        }
+    }
+    if let Some(usage) = response.usage_metadata {
+        model_response = model_response.with_usage(usage.prompt_token_count, usage.candidates_token_count);
+        if usage.thoughts_token_count > 0 {
+            model_response = model_response.with_reasoning_tokens(usage.thoughts_token_count);
+        }
+    }
+    if let Some(ref grounding) = candidate.grounding_metadata {
+        model_response.citations = grounding.citations();
+    }
+    if let Some(reason) = candidate.finish_reason {
+        model_response.metadata.insert("finish_reason".to_string(), json!(reason));
+    }
+    Ok(model_response)
+}
+
+/// Map a `streamGenerateContent?alt=sse` body to stream events.
+///
+/// Each chunk is a partial `generateContent` response. Text and thoughts are
+/// forwarded as they arrive; Gemini sends each function call complete in one
+/// chunk. Usage from the last chunk is attached to the `message_stop` event.
+pub fn parse_stream_generate_content(body: &[u8], provider: &str) -> Vec<IndubitablyResult<StreamEvent>> {
+    decode_all(GenerateContentStreamDecoder::new(provider), body)
+}
+
+/// Decodes a `streamGenerateContent?alt=sse` body as it arrives; see `parse_stream_generate_content`.
+pub struct GenerateContentStreamDecoder {
+    provider: String,
+    lines: LineSplitter,
+    started: bool,
+    text_open: bool,
+    usage: Option<Value>,
+    finish_reason: Option<String>,
+    done: bool,
+}
+
+impl GenerateContentStreamDecoder {
+    /// Create a decoder naming `provider` in its errors.
+    pub fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            lines: LineSplitter::default(),
+            started: false,
+            text_open: false,
+            usage: None,
+            finish_reason: None,
+            done: false,
+        }
+    }
+
+    fn fail(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>, error: IndubitablyError) {
+        events.push(Err(error));
+        self.done = true;
+    }
+
+    fn start(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        if !std::mem::replace(&mut self.started, true) {
+            events.push(Ok(StreamEvent::message_start()));
+        }
+    }
+
+    fn on_data(&mut self, data: &str, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        let invalid = |e: serde_json::Error| {
+            IndubitablyError::ModelError(ModelError::InvalidResponseFormat(format!("Invalid stream chunk: {}", e)))
+        };
+        let raw: Value = match serde_json::from_str(data) {
+            Ok(raw) => raw,
+            Err(e) => return self.fail(events, invalid(e)),
+        };
+        if let Some(message) = raw["error"]["message"].as_str() {
+            let error = ModelError::RequestFailed(format!("{}: {}", self.provider, message));
+            return self.fail(events, IndubitablyError::ModelError(error));
+        }
+        let chunk: GenerateContentResponse = match serde_json::from_value(raw) {
+            Ok(chunk) => chunk,
+            Err(e) => return self.fail(events, invalid(e)),
+        };
+        if let Err(e) = check_prompt_feedback(&chunk, &self.provider) {
+            return self.fail(events, e);
+        }
+        if let Some(usage) = chunk.usage_metadata {
+            let input_tokens = usage.prompt_token_count;
+            let output_tokens = usage.candidates_token_count;
+            self.usage = Some(json!({
+                "input_tokens": input_tokens,
+                "output_tokens": output_tokens,
+                "total_tokens": input_tokens + output_tokens,
+                "reasoning_tokens": usage.thoughts_token_count,
+            }));
+        }
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            return;
+        };
+        for part in candidate.content.map(|content| content.parts).unwrap_or_default() {
+            match part.text.filter(|text| !text.is_empty()) {
+                Some(text) if part.thought => {
+                    events.push(Ok(StreamEventPool::shared().text_delta(&text)
+                        .with_metadata("reasoning", json!(true))));
+                }
+                Some(text) => {
+                    if self.text_open {
+                        events.push(Ok(StreamEventPool::shared().text_delta(&text)));
+                    } else {
+                        self.text_open = true;
+                        events.push(Ok(StreamEvent::content_block_start(vec![StreamContent::text(text)])));
+                    }
+                }
+                None => {}
+            }
+            if let Some(call) = part.function_call {
+                if std::mem::take(&mut self.text_open) {
+                    events.push(Ok(StreamEvent::content_block_stop()));
+                }
+                events.push(Ok(StreamEvent::tool_use_start(call.into_tool_use())));
+                events.push(Ok(StreamEvent::tool_use_stop()));
+            }
+        }
+        if candidate.finish_reason.is_some() {
+            self.finish_reason = candidate.finish_reason;
+        }
+    }
+}
+
+impl StreamDecoder for GenerateContentStreamDecoder {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        self.start(&mut events);
+        for line in self.lines.push(chunk) {
+            if self.done {
+                break;
+            }
+            if let Some(data) = sse_data(&line) {
+                self.on_data(data, &mut events);
+            }
+        }
+        events
+    }
+
+    fn finish(&mut self) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        self.start(&mut events);
+        if let Some(line) = self.lines.finish().filter(|_| !self.done) {
+            if let Some(data) = sse_data(&line) {
+                self.on_data(data, &mut events);
+            }
+        }
+        if std::mem::replace(&mut self.done, true) {
+            return events;
+        }
+        if std::mem::take(&mut self.text_open) {
+            events.push(Ok(StreamEvent::content_block_stop()));
+        }
+        let mut stop = StreamEvent::message_stop();
+        if let Some(usage) = self.usage.take() {
+            stop = stop.with_metadata("usage", usage);
+        }
+        if let Some(reason) = self.finish_reason.take() {
+            stop = stop.with_metadata("finish_reason", json!(reason));
+            events.push(Ok(StreamEvent::message_delta(MessageDelta {
+                role: None,
+                content: None,
+                stop_reason: Some(reason),
+                stop_sequence: None,
+            })));
+        }
+        events.push(Ok(stop));
+        events
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Map an unsuccessful response to an error.
+pub(crate) fn status_error(response: &HttpResponse, provider: &str) -> IndubitablyError {
+    let body: Value = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+    let message = format!(
+        "{} returned status {}: {}",
+        provider,
+        response.status,
+        body["error"]["message"].as_str().map(str::to_string).unwrap_or_else(|| response.text())
+    );
+    IndubitablyError::ModelError(match response.status {
+        429 => ModelError::ModelThrottled(message),
+        404 | 503 => ModelError::ModelNotAvailable(message),
+        _ => ModelError::RequestFailed(message),
+    })
+}
+
+/// The Gemini model implementation.
+pub struct GeminiModel {
+    config: ModelConfig,
+    gemini_config: GeminiConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl std::fmt::Debug for GeminiModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeminiModel")
+            .field("config", &self.config)
+            .field("base_url", &self.gemini_config.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GeminiModel {
+    /// Create a new Gemini model.
+    pub fn new() -> Self {
+        Self::with_config(GeminiConfig::default())
+    }
+
+    /// Create a new Gemini model with the given configuration.
+    pub fn with_config(gemini_config: GeminiConfig) -> Self {
+        let mut config = ModelConfig::new(&gemini_config.model_id);
+        config.temperature = gemini_config.temperature;
+        config.max_tokens = gemini_config.max_tokens;
+        config.top_p = gemini_config.top_p;
+        config.top_k = gemini_config.top_k;
+        Self {
+            config,
+            gemini_config,
+            client: Arc::new(UnconfiguredHttpClient::new("Gemini")),
+        }
+    }
+
+    /// Set the client that sends requests to the Gemini API.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Build a `generateContent` or `streamGenerateContent` request.
+    fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let (messages, system_prompt) = self.prepare_messages(messages, system_prompt);
+        let mut system = self.gemini_config.system_instruction.clone();
+        system.extend(system_prompt.map(|text| SystemContentBlock { text }));
+        let mut body = generate_content_body(
+            &self.config,
+            &messages,
+            tool_specs,
+            &system,
+            &self.gemini_config.safety_settings,
+        );
+        for (key, value) in self.gemini_config.extra.iter().chain(&self.config.extra) {
+            body["generationConfig"][key] = value.clone();
+        }
+
+        let method = if stream { "streamGenerateContent?alt=sse" } else { "generateContent" };
+        let url = format!("{}/models/{}:{}", self.gemini_config.base_url, self.config.model_id, method);
+        HttpRequest::post(&url)
+            .with_header("x-goog-api-key", &self.gemini_config.api_key)
+            .with_json_body(&body)
+    }
+}
+
+#[async_trait]
+impl Model for GeminiModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, false)?;
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response, "Gemini"));
+        }
+        let model_response = parse_generate_content(&response, "Gemini")?;
+        tracing::debug!(
+            "model_id=<{}>, tool_uses=<{}> | gemini response received",
+            self.config.model_id,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, true)?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response.collect().await?, "Gemini"));
+        }
+        Ok(decode_body(response.body, GenerateContentStreamDecoder::new("Gemini")))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<Value> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(
+            "Gemini model does not support structured output yet".to_string(),
+        )))
+    }
+}
+
+impl Default for GeminiModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Message, ToolResult, ToolResultContent};
+    use tokio_stream::StreamExt;
+
+    /// Records requests and answers with a function call, streamed when asked.
+    #[derive(Default)]
+    struct GeminiApi {
+        requests: std::sync::Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for GeminiApi {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            let streaming = request.url.contains(":streamGenerateContent");
+            self.requests.lock().unwrap().push(request);
+            let call = json!({ "functionCall": { "name": "weather", "args": { "city": "Paris" } } });
+            if streaming {
+                let chunks = [
+                    json!({ "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Plan the call.", "thought": true }] } }] }),
+                    json!({ "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Checking " }] } }] }),
+                    json!({ "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Paris." }, call] }, "finishReason": "STOP" }],
+                            "usageMetadata": { "promptTokenCount": 30, "candidatesTokenCount": 9, "thoughtsTokenCount": 4 } }),
+                ];
+                let sse: String = chunks.iter().map(|chunk| format!("data: {}\n\n", chunk)).collect();
+                return Ok(HttpResponse::new(200, sse.into_bytes()));
+            }
+            let body = json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [{ "text": "Checking the weather." }, call] },
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": { "promptTokenCount": 12, "candidatesTokenCount": 5 }
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gemini_generate_and_stream_content() {
+        let api = Arc::new(GeminiApi::default());
+        let config = GeminiConfig::new()
+            .with_api_key("gm-key")
+            .with_system_content(SystemContentBlock { text: "You are a forecaster.".to_string() })
+            .with_safety_setting(HarmCategory::Harassment, HarmBlockThreshold::BlockNone);
+        let model = GeminiModel::with_config(config).with_client(api.clone());
+        let tools = vec![ToolSpec::new("weather", "Get the weather")];
+
+        let response = model.generate(&vec![Message::user("Weather in Paris?")], Some(&tools), Some("Be brief.")).await.unwrap();
+        assert_eq!(response.content, "Checking the weather.");
+        let call = response.tool_uses[0].clone();
+        assert_eq!(call.input, Some(json!({ "city": "Paris" })));
+        assert_eq!(response.usage.unwrap().total_tokens, 17);
+
+        let messages = vec![
+            Message::user("Weather in Paris?"),
+            Message::assistant_with_tool_uses("", vec![call.clone()]),
+            Message::tool_results(vec![ToolResult::new(&call.tool_use_id, vec![ToolResultContent::text("Sun")])]),
+        ];
+        let events: Vec<StreamEvent> = model.stream(&messages, Some(&tools), None).await.unwrap().map(Result::unwrap).collect().await;
+        let texts: Vec<&str> = events
+            .iter()
+            .filter(|event| event.metadata.as_ref().is_none_or(|metadata| !metadata.contains_key("reasoning")))
+            .filter_map(|event| event.content.as_ref()?.first()?.text.as_deref())
+            .collect();
+        assert_eq!(texts, vec!["Checking ", "Paris."]);
+        assert!(events.iter().any(|event| event.tool_use.as_ref().is_some_and(|tool_use| tool_use.name == "weather")));
+        let stop = events.last().unwrap().metadata.as_ref().unwrap();
+        assert_eq!(stop["usage"]["total_tokens"], 39);
+        assert_eq!(stop["finish_reason"], "STOP");
+
+        let requests = api.requests.lock().unwrap();
+        assert_eq!(requests[0].url, format!("{}/models/{}:generateContent", GEMINI_BASE_URL, DEFAULT_GEMINI_MODEL_ID));
+        assert!(requests[1].url.ends_with(":streamGenerateContent?alt=sse"));
+        assert_eq!(requests[0].header("x-goog-api-key"), Some("gm-key"));
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            body["systemInstruction"]["parts"],
+            json!([{ "text": "You are a forecaster." }, { "text": "Be brief." }])
+        );
+        assert_eq!(body["safetySettings"][0], json!({ "category": "HARM_CATEGORY_HARASSMENT", "threshold": "BLOCK_NONE" }));
+        assert_eq!(body["tools"][0]["functionDeclarations"][0]["name"], "weather");
+        let body: Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(body["contents"][2]["parts"][0]["functionResponse"]["name"], "weather");
+    }
+
+    #[test]
+    fn test_gemini_grounding_citations() {
+        let body = json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "Oslo is sunny. Bergen is rainy." }] },
+                "groundingMetadata": {
+                    "groundingChunks": [
+                        { "web": { "uri": "https://example.com/oslo", "title": "Oslo forecast" } },
+                        { "retrievedContext": { "uri": "gs://weather/bergen.txt", "text": "Rain in Bergen" } }
+                    ],
+                    "groundingSupports": [
+                        {
+                            "segment": { "startIndex": 0, "endIndex": 14, "text": "Oslo is sunny." },
+                            "groundingChunkIndices": [0]
+                        },
+                        { "segment": { "startIndex": 15, "endIndex": 31 }, "groundingChunkIndices": [1, 7] }
+                    ]
+                },
+                "finishReason": "STOP"
+            }]
+        });
+        let response = HttpResponse::new(200, body.to_string().into_bytes());
+        let response = parse_generate_content(&response, "Gemini").unwrap();
+
+        assert_eq!(
+            response.citations,
+            vec![
+                Citation::new("https://example.com/oslo")
+                    .with_uri("https://example.com/oslo")
+                    .with_title("Oslo forecast")
+                    .with_span(0, 14),
+                Citation::new("gs://weather/bergen.txt")
+                    .with_uri("gs://weather/bergen.txt")
+                    .with_snippet("Rain in Bergen")
+                    .with_span(15, 31),
+            ]
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Model providers for the SDK.
+//! 
+//! This module holds the integrations with model vendors, each behind its
+//! own feature flag so that slim builds compile only the providers they use;
+//! the `providers` feature, on by default, enables them all. Providers depend
+//! on the core only through the `Model` and `ImageGenerationModel` traits and
+//! the `HttpClient` they send requests with, and the core does not depend on
+//! any provider. Providers are also re-exported from `crate::models`, where
+//! they used to live, so existing paths keep working.
+
+#[cfg(feature = "openai-compatible")]
+pub mod openai_compat;
+#[cfg(feature = "openai")]
+pub mod openai;
+#[cfg(feature = "openai")]
+pub mod openai_image;
+#[cfg(feature = "openai")]
+pub mod openai_transcription;
+#[cfg(feature = "azure-openai")]
+pub mod azure_openai;
+#[cfg(feature = "anthropic")]
+pub mod anthropic;
+#[cfg(feature = "bedrock")]
+pub mod bedrock;
+#[cfg(feature = "bedrock")]
+pub mod bedrock_converse;
+#[cfg(feature = "bedrock")]
+pub mod bedrock_failover;
+#[cfg(feature = "bedrock")]
+pub mod bedrock_image;
+#[cfg(feature = "gemini")]
+pub mod gemini;
+#[cfg(feature = "vertex")]
+pub mod vertex;
+#[cfg(feature = "vertex")]
+pub mod google_auth;
+#[cfg(feature = "ollama")]
+pub mod ollama;
+#[cfg(feature = "deepseek")]
+pub mod deepseek;
+#[cfg(feature = "xai")]
+pub mod xai;
+
+#[cfg(feature = "openai-compatible")]
+pub use openai_compat::{OpenAICompatibleConfig, OpenAICompatibleModel};
+#[cfg(feature = "openai")]
+pub use openai::OpenAIModel;
+#[cfg(feature = "openai")]
+pub use openai_image::OpenAIImageModel;
+#[cfg(feature = "openai")]
+pub use openai_transcription::WhisperTranscriber;
+#[cfg(feature = "azure-openai")]
+pub use azure_openai::{AzureOpenAIConfig, AzureOpenAIModel};
+#[cfg(feature = "anthropic")]
+pub use anthropic::AnthropicModel;
+#[cfg(feature = "bedrock")]
+pub use bedrock::{BedrockInvoker, BedrockModel};
+#[cfg(feature = "bedrock")]
+pub use bedrock_converse::ConverseInvoker;
+#[cfg(feature = "bedrock")]
+pub use bedrock_failover::RegionFailover;
+#[cfg(feature = "bedrock")]
+pub use bedrock_image::BedrockImageModel;
+#[cfg(feature = "gemini")]
+pub use gemini::{GeminiConfig, GeminiModel, HarmBlockThreshold, HarmCategory, SafetySetting};
+#[cfg(feature = "vertex")]
+pub use vertex::{VertexConfig, VertexModel};
+#[cfg(feature = "vertex")]
+pub use google_auth::{ApplicationDefaultCredentials, GoogleCredentials};
+#[cfg(feature = "ollama")]
+pub use ollama::{OllamaModel, OllamaModelInfo};
+#[cfg(feature = "deepseek")]
+pub use deepseek::DeepSeekModel;
+#[cfg(feature = "xai")]
+pub use xai::GrokModel;
@@ -0,0 +1,699 @@
+//! Ollama model implementation for the SDK.
+//! 
+//! This module provides integration with Ollama for
+//! accessing local models through a local server's `/api/chat` endpoint.
+//! Hosts reached over plain HTTP are called with `PlainHttpClient` by
+//! default, so agents can run against a local server without a network. Streamed responses arrive as newline-delimited
+//! JSON and are decoded into stream events. Ollama does not assign tool call
+//! IDs, so they are generated here and tool results are matched back to
+//! their calls by tool name.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::http::{HttpClient, HttpRequest, HttpResponse};
+use crate::models::plain_http::default_client;
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::{decode_all, decode_body, LineSplitter, StreamDecoder};
+use crate::models::roles::RoleMapping;
+use crate::types::streaming::{MessageDelta, StreamContent, StreamEventPool};
+use crate::types::{
+    IndubitablyError, IndubitablyResult, MessageRole, Messages, ModelError, StreamEvent, ToolSpec, ToolUse,
+};
+
+/// Default Ollama host.
+pub const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+
+/// Default Ollama model ID.
+pub const DEFAULT_OLLAMA_MODEL_ID: &str = "llama3";
+
+/// Configuration specific to Ollama models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    /// The Ollama host URL.
+    pub host: String,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// Whether to enable streaming.
+    pub streaming: Option<bool>,
+    /// How system prompts map onto the model's roles; some local models lack a system role.
+    #[serde(default)]
+    pub role_mapping: RoleMapping,
+    /// How long the server keeps the model loaded after a request, such as `10m` or `-1` for indefinitely.
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// Additional Ollama-specific configuration.
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_OLLAMA_HOST.to_string(),
+            model_id: DEFAULT_OLLAMA_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: Some(1.0),
+            streaming: Some(false),
+            role_mapping: RoleMapping::default(),
+            keep_alive: None,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl OllamaConfig {
+    /// Create a new Ollama configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the host URL.
+    pub fn with_host(mut self, host: &str) -> Self {
+        self.host = host.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Enable or disable streaming.
+    pub fn with_streaming(mut self, streaming: bool) -> Self {
+        self.streaming = Some(streaming);
+        self
+    }
+
+    /// Set how system prompts map onto the model's roles.
+    pub fn with_role_mapping(mut self, role_mapping: RoleMapping) -> Self {
+        self.role_mapping = role_mapping;
+        self
+    }
+
+    /// Set how long the server keeps the model loaded after a request.
+    pub fn with_keep_alive(mut self, keep_alive: &str) -> Self {
+        self.keep_alive = Some(keep_alive.to_string());
+        self
+    }
+
+    /// Add extra configuration.
+    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+}
+
+
+/// A model available on an Ollama server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OllamaModelInfo {
+    /// The model name, such as `llama3:latest`.
+    pub name: String,
+    /// The size of the model on disk in bytes.
+    #[serde(default)]
+    pub size: u64,
+    /// When the model was last modified.
+    #[serde(default)]
+    pub modified_at: Option<String>,
+    /// The model family, such as `llama`.
+    #[serde(default)]
+    pub family: Option<String>,
+    /// The parameter count, such as `8.0B`.
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+}
+
+/// Build an `/api/chat` request body.
+///
+/// Ollama takes tool call arguments as objects and identifies tool results by
+/// tool name, so the name of each result's call is looked up by its ID.
+pub fn chat_request_body(
+    config: &ModelConfig,
+    messages: &Messages,
+    tool_specs: Option<&[ToolSpec]>,
+    system_prompt: Option<&str>,
+) -> Value {
+    let tool_names: HashMap<&str, &str> = messages
+        .iter()
+        .flat_map(|message| message.tool_uses())
+        .map(|tool_use| (tool_use.tool_use_id.as_str(), tool_use.name.as_str()))
+        .collect();
+    let mut wire_messages = Vec::new();
+    if let Some(prompt) = system_prompt.filter(|prompt| !prompt.is_empty()) {
+        wire_messages.push(json!({ "role": "system", "content": prompt }));
+    }
+    for message in messages {
+        let text: Vec<&str> = message.content.iter().filter_map(|block| block.text.as_deref()).collect();
+        match message.role {
+            MessageRole::System => wire_messages.push(json!({ "role": "system", "content": text.join("\n") })),
+            MessageRole::Assistant => {
+                let mut wire = json!({ "role": "assistant", "content": text.join("") });
+                let tool_calls: Vec<Value> = message
+                    .tool_uses()
+                    .into_iter()
+                    .map(|tool_use| {
+                        json!({
+                            "function": {
+                                "name": tool_use.name,
+                                "arguments": tool_use.input.clone().unwrap_or_else(|| json!({})),
+                            }
+                        })
+                    })
+                    .collect();
+                if !tool_calls.is_empty() {
+                    wire["tool_calls"] = json!(tool_calls);
+                }
+                wire_messages.push(wire);
+            }
+            MessageRole::User | MessageRole::Tool => {
+                for result in message.tool_result_blocks() {
+                    let content: Vec<&str> = result.content.iter().filter_map(|content| content.text.as_deref()).collect();
+                    let mut wire = json!({ "role": "tool", "content": content.join("\n") });
+                    if let Some(name) = tool_names.get(result.tool_use_id.as_str()) {
+                        wire["tool_name"] = json!(name);
+                    }
+                    wire_messages.push(wire);
+                }
+                // Ollama takes images as base64 beside the text and has no document input
+                let mut text = text;
+                text.extend(message.content.iter().filter_map(|block| block.document.as_ref()?.text_data()));
+                let images: Vec<String> = message
+                    .content
+                    .iter()
+                    .filter_map(|block| block.image.as_ref()?.base64_data())
+                    .collect();
+                if !text.is_empty() || !images.is_empty() {
+                    let mut wire = json!({ "role": "user", "content": text.join("\n") });
+                    if !images.is_empty() {
+                        wire["images"] = json!(images);
+                    }
+                    wire_messages.push(wire);
+                }
+            }
+        }
+    }
+
+    let mut options = serde_json::Map::new();
+    if let Some(temperature) = config.temperature {
+        options.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = config.top_p {
+        options.insert("top_p".to_string(), json!(top_p));
+    }
+    if let Some(top_k) = config.top_k {
+        options.insert("top_k".to_string(), json!(top_k));
+    }
+    if let Some(max_tokens) = config.max_tokens {
+        options.insert("num_predict".to_string(), json!(max_tokens));
+    }
+    let mut body = json!({ "model": config.model_id, "messages": wire_messages, "options": options });
+    if let Some(specs) = tool_specs.filter(|specs| !specs.is_empty()) {
+        let tools: Vec<Value> = specs
+            .iter()
+            .map(|spec| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": spec.name,
+                        "description": spec.description,
+                        "parameters": spec.input_schema.clone().unwrap_or_else(|| json!({ "type": "object" })),
+                    },
+                })
+            })
+            .collect();
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+/// Convert the tool calls of an Ollama message, generating the IDs Ollama leaves out.
+fn tool_uses(message: &Value) -> Vec<ToolUse> {
+    message["tool_calls"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|call| {
+            let id = call["id"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+            let arguments = match &call["function"]["arguments"] {
+                // Some models send arguments as a JSON string
+                Value::String(raw) => serde_json::from_str(raw).unwrap_or_else(|_| json!({})),
+                Value::Null => json!({}),
+                arguments => arguments.clone(),
+            };
+            ToolUse::new(call["function"]["name"].as_str().unwrap_or_default(), &id).with_input(arguments)
+        })
+        .collect()
+}
+
+/// Map an `/api/chat` response body to a model response.
+pub fn parse_chat_response(response: &HttpResponse) -> IndubitablyResult<ModelResponse> {
+    let body: Value = response.json()?;
+    let message = body.get("message").ok_or_else(|| {
+        IndubitablyError::ModelError(ModelError::InvalidResponseFormat("Response has no message".to_string()))
+    })?;
+
+    let mut model_response = ModelResponse::new(message["content"].as_str().unwrap_or_default());
+    if let Some(thinking) = message["thinking"].as_str().filter(|thinking| !thinking.is_empty()) {
+        model_response = model_response.with_reasoning(thinking);
+    }
+    for tool_use in tool_uses(message) {
+        model_response = model_response.with_tool_use(tool_use);
+    }
+    if body.get("prompt_eval_count").is_some() || body.get("eval_count").is_some() {
+        model_response = model_response.with_usage(
+            body["prompt_eval_count"].as_u64().unwrap_or_default() as u32,
+            body["eval_count"].as_u64().unwrap_or_default() as u32,
+        );
+    }
+    if let Some(reason) = body["done_reason"].as_str() {
+        model_response.metadata.insert("finish_reason".to_string(), json!(reason));
+    }
+    Ok(model_response)
+}
+
+/// Map a streamed `/api/chat` body of newline-delimited JSON to stream events.
+///
+/// Text and thinking are forwarded as they arrive; Ollama sends each tool call
+/// complete in one chunk. Usage from the final `done` chunk is attached to
+/// the `message_stop` event.
+pub fn parse_chat_stream(body: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+    decode_all(ChatStreamDecoder::default(), body)
+}
+
+/// Decodes a streamed `/api/chat` body as it arrives; see `parse_chat_stream`.
+#[derive(Default)]
+pub struct ChatStreamDecoder {
+    lines: LineSplitter,
+    started: bool,
+    text_open: bool,
+    usage: Option<Value>,
+    finish_reason: Option<String>,
+    done: bool,
+    failed: bool,
+}
+
+impl ChatStreamDecoder {
+    fn fail(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>, error: ModelError) {
+        events.push(Err(IndubitablyError::ModelError(error)));
+        self.done = true;
+        self.failed = true;
+    }
+
+    fn start(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        if !std::mem::replace(&mut self.started, true) {
+            events.push(Ok(StreamEvent::message_start()));
+        }
+    }
+
+    fn on_line(&mut self, line: &str, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        let chunk: Value = match serde_json::from_str(line) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                return self.fail(events, ModelError::InvalidResponseFormat(format!("Invalid stream chunk: {}", e)));
+            }
+        };
+        if let Some(error) = chunk["error"].as_str() {
+            return self.fail(events, ModelError::RequestFailed(format!("Ollama: {}", error)));
+        }
+        let message = &chunk["message"];
+        if let Some(thinking) = message["thinking"].as_str().filter(|thinking| !thinking.is_empty()) {
+            events.push(Ok(StreamEventPool::shared().text_delta(thinking)
+                .with_metadata("reasoning", json!(true))));
+        }
+        if let Some(content) = message["content"].as_str().filter(|content| !content.is_empty()) {
+            if self.text_open {
+                events.push(Ok(StreamEventPool::shared().text_delta(content)));
+            } else {
+                self.text_open = true;
+                events.push(Ok(StreamEvent::content_block_start(vec![StreamContent::text(content)])));
+            }
+        }
+        for tool_use in tool_uses(message) {
+            if std::mem::take(&mut self.text_open) {
+                events.push(Ok(StreamEvent::content_block_stop()));
+            }
+            events.push(Ok(StreamEvent::tool_use_start(tool_use)));
+            events.push(Ok(StreamEvent::tool_use_stop()));
+        }
+        if chunk["done"] == json!(true) {
+            if std::mem::take(&mut self.text_open) {
+                events.push(Ok(StreamEvent::content_block_stop()));
+            }
+            let input_tokens = chunk["prompt_eval_count"].as_u64().unwrap_or_default();
+            let output_tokens = chunk["eval_count"].as_u64().unwrap_or_default();
+            self.usage = Some(json!({
+                "input_tokens": input_tokens,
+                "output_tokens": output_tokens,
+                "total_tokens": input_tokens + output_tokens,
+            }));
+            let reason = chunk["done_reason"].as_str().unwrap_or("stop");
+            self.finish_reason = Some(reason.to_string());
+            events.push(Ok(StreamEvent::message_delta(MessageDelta {
+                role: None,
+                content: None,
+                stop_reason: Some(reason.to_string()),
+                stop_sequence: None,
+            })));
+            self.done = true;
+        }
+    }
+}
+
+impl StreamDecoder for ChatStreamDecoder {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        self.start(&mut events);
+        for line in self.lines.push(chunk) {
+            if self.done {
+                break;
+            }
+            self.on_line(&line, &mut events);
+        }
+        events
+    }
+
+    fn finish(&mut self) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        self.start(&mut events);
+        if let Some(line) = self.lines.finish().filter(|_| !self.done) {
+            self.on_line(&line, &mut events);
+        }
+        if self.failed {
+            return events;
+        }
+        self.done = true;
+        let mut stop = StreamEvent::message_stop();
+        if let Some(usage) = self.usage.take() {
+            stop = stop.with_metadata("usage", usage);
+        }
+        if let Some(reason) = self.finish_reason.take() {
+            stop = stop.with_metadata("finish_reason", json!(reason));
+        }
+        events.push(Ok(stop));
+        events
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Map an unsuccessful response to an error.
+fn status_error(response: &HttpResponse) -> IndubitablyError {
+    let body: Value = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+    let message = format!(
+        "Ollama returned status {}: {}",
+        response.status,
+        body["error"].as_str().map(str::to_string).unwrap_or_else(|| response.text())
+    );
+    IndubitablyError::ModelError(match response.status {
+        // Ollama answers 404 for models that have not been pulled
+        404 => ModelError::ModelNotAvailable(format!("{}; pull the model with `ollama pull`", message)),
+        429 => ModelError::ModelThrottled(message),
+        503 => ModelError::ModelNotAvailable(message),
+        _ => ModelError::RequestFailed(message),
+    })
+}
+
+/// The Ollama model implementation.
+pub struct OllamaModel {
+    config: ModelConfig,
+    ollama_config: OllamaConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl std::fmt::Debug for OllamaModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OllamaModel")
+            .field("config", &self.config)
+            .field("host", &self.ollama_config.host)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OllamaModel {
+    /// Create a new Ollama model.
+    pub fn new() -> Self {
+        Self::with_config(OllamaConfig::default())
+    }
+
+    /// Create a new Ollama model with the given configuration.
+    pub fn with_config(ollama_config: OllamaConfig) -> Self {
+        Self {
+            config: ModelConfig::new(&ollama_config.model_id)
+                .with_temperature(ollama_config.temperature.unwrap_or(0.7))
+                .with_max_tokens(ollama_config.max_tokens.unwrap_or(4096))
+                .with_top_p(ollama_config.top_p.unwrap_or(1.0))
+                .with_streaming(ollama_config.streaming.unwrap_or(false))
+                .with_role_mapping(ollama_config.role_mapping.clone()),
+            client: default_client("Ollama", &ollama_config.host),
+            ollama_config,
+        }
+    }
+
+    /// Set the client that sends requests to the Ollama server.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// List the models pulled on the server.
+    pub async fn list_models(&self) -> IndubitablyResult<Vec<OllamaModelInfo>> {
+        let request = HttpRequest::get(&format!("{}/api/tags", self.ollama_config.host));
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response));
+        }
+        let body: Value = response.json()?;
+        Ok(body["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| {
+                let mut info: OllamaModelInfo = serde_json::from_value(model.clone()).ok()?;
+                info.family = info.family.or_else(|| model["details"]["family"].as_str().map(str::to_string));
+                info.parameter_size = info
+                    .parameter_size
+                    .or_else(|| model["details"]["parameter_size"].as_str().map(str::to_string));
+                Some(info)
+            })
+            .collect())
+    }
+
+    /// Build an `/api/chat` request.
+    fn chat_request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let (messages, system_prompt) = self.prepare_messages(messages, system_prompt);
+        let mut body = chat_request_body(&self.config, &messages, tool_specs, system_prompt.as_deref());
+        body["stream"] = json!(stream);
+        if let Some(ref keep_alive) = self.ollama_config.keep_alive {
+            body["keep_alive"] = json!(keep_alive);
+        }
+        for (key, value) in self.ollama_config.extra.iter().chain(&self.config.extra) {
+            body["options"][key] = value.clone();
+        }
+
+        HttpRequest::post(&format!("{}/api/chat", self.ollama_config.host)).with_json_body(&body)
+    }
+}
+
+#[async_trait]
+impl Model for OllamaModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.chat_request(messages, tool_specs, system_prompt, false)?;
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response));
+        }
+        let model_response = parse_chat_response(&response)?;
+        tracing::debug!(
+            "model_id=<{}>, tool_uses=<{}> | ollama response received",
+            self.config.model_id,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.chat_request(messages, tool_specs, system_prompt, true)?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response.collect().await?));
+        }
+        Ok(decode_body(response.body, ChatStreamDecoder::default()))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(crate::types::IndubitablyError::ModelError(
+            crate::types::ModelError::InvalidResponseFormat(
+                "Ollama model does not support structured output yet".to_string(),
+            ),
+        ))
+    }
+}
+
+impl Default for OllamaModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Message, ToolResult, ToolResultContent};
+    use tokio_stream::StreamExt;
+
+    /// Records chat request bodies and answers with a tool call, streamed when asked.
+    #[derive(Default)]
+    struct OllamaServer {
+        bodies: std::sync::Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for OllamaServer {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            if request.url == "http://gpu-box:11434/api/tags" {
+                let body = json!({ "models": [
+                    { "name": "qwen3:8b", "size": 5_200_000_000u64, "details": { "family": "qwen3", "parameter_size": "8.2B" } }
+                ] });
+                return Ok(HttpResponse::new(200, body.to_string().into_bytes()));
+            }
+            assert_eq!(request.url, "http://gpu-box:11434/api/chat");
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let streaming = body["stream"] == json!(true);
+            self.bodies.lock().unwrap().push(body);
+            let call = json!({ "function": { "name": "lookup", "arguments": { "city": "Oslo" } } });
+            if streaming {
+                let chunks = [
+                    json!({ "message": { "role": "assistant", "content": "", "thinking": "Need weather." }, "done": false }),
+                    json!({ "message": { "role": "assistant", "content": "Checking " }, "done": false }),
+                    json!({ "message": { "role": "assistant", "content": "Oslo." }, "done": false }),
+                    json!({ "message": { "role": "assistant", "content": "", "tool_calls": [call] }, "done": false }),
+                    json!({ "message": { "role": "assistant", "content": "" }, "done": true, "done_reason": "stop", "prompt_eval_count": 30, "eval_count": 12 }),
+                ];
+                let ndjson: String = chunks.iter().map(|chunk| format!("{}\n", chunk)).collect();
+                return Ok(HttpResponse::new(200, ndjson.into_bytes()));
+            }
+            let body = json!({
+                "message": { "role": "assistant", "content": "", "tool_calls": [call] },
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 20,
+                "eval_count": 8
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ollama_chat_streaming_and_models() {
+        let server = Arc::new(OllamaServer::default());
+        let model = OllamaModel::with_config(
+            OllamaConfig::new().with_host("http://gpu-box:11434/").with_model_id("qwen3:8b").with_keep_alive("30m"),
+        )
+        .with_client(server.clone());
+        let tools = vec![ToolSpec::new("lookup", "Look up the weather")];
+
+        let response = model.generate(&vec![Message::user("Weather in Oslo?")], Some(&tools), Some("Be brief.")).await.unwrap();
+        let call = &response.tool_uses[0];
+        assert!(call.tool_use_id.starts_with("call_"));
+        assert_eq!(call.input, Some(json!({ "city": "Oslo" })));
+        assert_eq!(response.usage.as_ref().unwrap().total_tokens, 28);
+
+        let messages = vec![
+            Message::user("Weather in Oslo?"),
+            Message::assistant_with_tool_uses("", response.tool_uses.clone()),
+            Message::tool_results(vec![ToolResult::new(&call.tool_use_id, vec![ToolResultContent::text("4C")])]),
+        ];
+        let events: Vec<StreamEvent> = model.stream(&messages, Some(&tools), None).await.unwrap().map(Result::unwrap).collect().await;
+        let texts: Vec<&str> = events
+            .iter()
+            .filter(|event| event.metadata.as_ref().is_none_or(|metadata| !metadata.contains_key("reasoning")))
+            .filter_map(|event| event.content.as_ref()?.first()?.text.as_deref())
+            .collect();
+        assert_eq!(texts, vec!["Checking ", "Oslo."]);
+        assert!(events.iter().any(|event| event.tool_use.as_ref().is_some_and(|tool_use| tool_use.name == "lookup")));
+        assert_eq!(events.last().unwrap().metadata.as_ref().unwrap()["usage"]["total_tokens"], 42);
+
+        let bodies = server.bodies.lock().unwrap().clone();
+        assert_eq!(bodies[0]["messages"][0], json!({ "role": "system", "content": "Be brief." }));
+        assert_eq!(bodies[0]["options"]["num_predict"], 4096);
+        assert_eq!(bodies[0]["keep_alive"], "30m");
+        assert_eq!(bodies[0]["stream"], false);
+        assert_eq!(bodies[1]["messages"][1]["tool_calls"][0]["function"]["arguments"], json!({ "city": "Oslo" }));
+        assert_eq!(bodies[1]["messages"][2], json!({ "role": "tool", "content": "4C", "tool_name": "lookup" }));
+
+        let models = model.list_models().await.unwrap();
+        assert_eq!(models[0].name, "qwen3:8b");
+        assert_eq!(models[0].parameter_size.as_deref(), Some("8.2B"));
+    }
+}
@@ -0,0 +1,408 @@
+//! OpenAI model implementation for the SDK.
+//! 
+//! This module provides integration with OpenAI's API for
+//! accessing various foundation models through the `/chat/completions`
+//! endpoint, including tool calling and streamed responses. Reasoning models
+//! take `max_completion_tokens` and reject sampling parameters, so those are
+//! adjusted before the request is sent.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::http::{HttpClient, HttpRequest, UnconfiguredHttpClient};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::decode_body;
+use super::openai_compat::{chat_request_body, parse_chat_response, status_error, ChatStreamDecoder};
+use crate::types::{Messages, ToolSpec, IndubitablyResult};
+
+/// Default OpenAI model ID.
+pub const DEFAULT_OPENAI_MODEL_ID: &str = "gpt-4";
+
+/// The base URL of the OpenAI API.
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// The environment variable holding the OpenAI API key.
+pub const OPENAI_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Prefixes of reasoning models, which reject sampling parameters.
+const REASONING_MODEL_PREFIXES: &[&str] = &["o1", "o3", "o4", "gpt-5"];
+
+/// Check whether a model is a reasoning model.
+fn is_reasoning_model(model_id: &str) -> bool {
+    REASONING_MODEL_PREFIXES.iter().any(|prefix| model_id.starts_with(prefix))
+}
+
+/// Configuration specific to OpenAI models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIConfig {
+    /// The OpenAI API key.
+    pub api_key: String,
+    /// The base URL of the API.
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    /// The organization requests are billed to.
+    #[serde(default)]
+    pub organization: Option<String>,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// Whether to enable streaming.
+    pub streaming: Option<bool>,
+    /// Additional OpenAI-specific configuration.
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Default for OpenAIConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: default_base_url(),
+            organization: None,
+            model_id: DEFAULT_OPENAI_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: Some(1.0),
+            streaming: Some(false),
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl OpenAIConfig {
+    /// Create a new OpenAI configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a configuration with the API key from `OPENAI_API_KEY`.
+    pub fn from_env() -> Self {
+        Self::default().with_api_key(&std::env::var(OPENAI_API_KEY_ENV).unwrap_or_default())
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Set the base URL, such as a proxy endpoint.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the organization requests are billed to.
+    pub fn with_organization(mut self, organization: &str) -> Self {
+        self.organization = Some(organization.to_string());
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Enable or disable streaming.
+    pub fn with_streaming(mut self, streaming: bool) -> Self {
+        self.streaming = Some(streaming);
+        self
+    }
+
+    /// Add extra configuration.
+    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+}
+
+fn default_base_url() -> String {
+    OPENAI_BASE_URL.to_string()
+}
+
+/// The OpenAI model implementation.
+pub struct OpenAIModel {
+    config: ModelConfig,
+    openai_config: OpenAIConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl std::fmt::Debug for OpenAIModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenAIModel")
+            .field("config", &self.config)
+            .field("base_url", &self.openai_config.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OpenAIModel {
+    /// Create a new OpenAI model.
+    pub fn new() -> Self {
+        Self::with_config(OpenAIConfig::default())
+    }
+
+    /// Create a new OpenAI model with the given configuration.
+    pub fn with_config(openai_config: OpenAIConfig) -> Self {
+        Self {
+            config: ModelConfig::new(&openai_config.model_id)
+                .with_temperature(openai_config.temperature.unwrap_or(0.7))
+                .with_max_tokens(openai_config.max_tokens.unwrap_or(4096))
+                .with_top_p(openai_config.top_p.unwrap_or(1.0))
+                .with_streaming(openai_config.streaming.unwrap_or(false)),
+            openai_config,
+            client: Arc::new(UnconfiguredHttpClient::new("OpenAI")),
+        }
+    }
+
+    /// Set the client that sends requests to the OpenAI API.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Build a chat completions request.
+    fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let body = openai_request_body(
+            &self.config,
+            &self.openai_config.extra,
+            messages,
+            tool_specs,
+            system_prompt,
+            stream,
+        );
+        let mut request = HttpRequest::post(&format!("{}/chat/completions", self.openai_config.base_url))
+            .with_header("authorization", &format!("Bearer {}", self.openai_config.api_key));
+        if let Some(ref organization) = self.openai_config.organization {
+            request = request.with_header("openai-organization", organization);
+        }
+        request.with_json_body(&body)
+    }
+}
+
+/// Build a chat completions body with OpenAI's parameter rules applied.
+///
+/// Provider extras are merged first, then the model config's extras.
+pub(crate) fn openai_request_body(
+    config: &ModelConfig,
+    extra: &HashMap<String, serde_json::Value>,
+    messages: &Messages,
+    tool_specs: Option<&[ToolSpec]>,
+    system_prompt: Option<&str>,
+    stream: bool,
+) -> serde_json::Value {
+    let mut body = chat_request_body(config, messages, tool_specs, system_prompt);
+    for (key, value) in extra.iter().chain(&config.extra) {
+        body[key] = value.clone();
+    }
+    if let Some(fields) = body.as_object_mut() {
+        if let Some(max_tokens) = fields.remove("max_tokens") {
+            fields.insert("max_completion_tokens".to_string(), max_tokens);
+        }
+        if is_reasoning_model(&config.model_id) {
+            fields.remove("temperature");
+            fields.remove("top_p");
+        }
+    }
+    if stream {
+        body["stream"] = json!(true);
+        body["stream_options"] = json!({ "include_usage": true });
+    }
+    body
+}
+
+#[async_trait]
+impl Model for OpenAIModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let response = self.config.middleware.send(self.client.as_ref(), self.request(messages, tool_specs, system_prompt, false)?).await?;
+        if !response.is_success() {
+            return Err(status_error("OpenAI", &response));
+        }
+        let model_response = parse_chat_response(&response)?;
+        tracing::debug!(
+            "model_id=<{}>, tool_uses=<{}> | openai response received",
+            self.config.model_id,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, true)?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error("OpenAI", &response.collect().await?));
+        }
+        Ok(decode_body(response.body, ChatStreamDecoder::default()))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(crate::types::IndubitablyError::ModelError(
+            crate::types::ModelError::InvalidResponseFormat(
+                "OpenAI model does not support structured output yet".to_string(),
+            ),
+        ))
+    }
+}
+
+impl Default for OpenAIModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+    use crate::types::{Message, StreamEvent, ToolResult, ToolResultContent, ToolUse};
+    use tokio_stream::StreamExt;
+
+    /// Records request bodies and answers with a tool call, streamed when asked.
+    #[derive(Default)]
+    struct OpenAIEndpoint {
+        bodies: std::sync::Mutex<Vec<serde_json::Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for OpenAIEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert_eq!(request.url, "https://api.openai.com/v1/chat/completions");
+            assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let streaming = body["stream"] == json!(true);
+            self.bodies.lock().unwrap().push(body);
+            if streaming {
+                let chunks = [
+                    json!({ "choices": [{ "index": 0, "delta": { "role": "assistant", "content": "Let me " } }] }),
+                    json!({ "choices": [{ "index": 0, "delta": { "content": "check." } }] }),
+                    json!({ "choices": [{ "index": 0, "delta": { "tool_calls": [
+                        { "index": 0, "id": "call_2", "type": "function", "function": { "name": "lookup", "arguments": "{\"ci" } }
+                    ] } }] }),
+                    json!({ "choices": [{ "index": 0, "delta": { "tool_calls": [
+                        { "index": 0, "function": { "arguments": "ty\":\"Oslo\"}" } }
+                    ] } }] }),
+                    json!({ "choices": [{ "index": 0, "delta": {}, "finish_reason": "tool_calls" }] }),
+                    json!({ "choices": [], "usage": { "prompt_tokens": 9, "completion_tokens": 6, "total_tokens": 15 } }),
+                ];
+                let mut sse: String = chunks.iter().map(|chunk| format!("data: {}\n\n", chunk)).collect();
+                sse.push_str("data: [DONE]\n\n");
+                return Ok(HttpResponse::new(200, sse.into_bytes()));
+            }
+            let body = json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": { "name": "lookup", "arguments": "{\"city\":\"Paris\"}" }
+                        }]
+                    },
+                    "finish_reason": "tool_calls"
+                }],
+                "usage": { "prompt_tokens": 20, "completion_tokens": 8, "total_tokens": 28 }
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_openai_tool_calls_and_streaming() {
+        let endpoint = Arc::new(OpenAIEndpoint::default());
+        let tools = vec![ToolSpec::new("lookup", "Look up the weather")];
+        let messages = vec![
+            Message::user("Weather in Paris?"),
+            Message::assistant_with_tool_uses("", vec![ToolUse::new("lookup", "call_0").with_input(json!({}))]),
+            Message::tool_results(vec![ToolResult::new("call_0", vec![ToolResultContent::text("missing city")])]),
+        ];
+        let model = OpenAIModel::with_config(OpenAIConfig::new().with_api_key("sk-test").with_model_id("gpt-4o"))
+            .with_client(endpoint.clone());
+
+        let response = model.generate(&messages, Some(&tools), Some("Be brief.")).await.unwrap();
+        assert_eq!(response.tool_uses[0].tool_use_id, "call_1");
+        assert_eq!(response.tool_uses[0].input, Some(json!({ "city": "Paris" })));
+        assert_eq!(response.usage.unwrap().total_tokens, 28);
+
+        let events: Vec<StreamEvent> = model.stream(&messages, Some(&tools), None).await.unwrap().map(Result::unwrap).collect().await;
+        let texts: Vec<&str> = events
+            .iter()
+            .filter_map(|event| event.content.as_ref()?.first()?.text.as_deref())
+            .collect();
+        assert_eq!(texts, vec!["Let me ", "check."]);
+        let tool_use = events.iter().find_map(|event| event.tool_use.as_ref()).unwrap();
+        assert_eq!(tool_use.input, Some(json!({ "city": "Oslo" })));
+        assert_eq!(events.last().unwrap().metadata.as_ref().unwrap()["usage"]["total_tokens"], 15);
+
+        let o3 = OpenAIModel::with_config(OpenAIConfig::new().with_api_key("sk-test").with_model_id("o3-mini"))
+            .with_client(endpoint.clone());
+        o3.generate(&messages, None, None).await.unwrap();
+
+        let bodies = endpoint.bodies.lock().unwrap().clone();
+        assert_eq!(bodies[0]["tools"][0]["function"]["name"], "lookup");
+        assert_eq!(bodies[0]["messages"][0], json!({ "role": "system", "content": "Be brief." }));
+        assert_eq!(bodies[0]["max_completion_tokens"], 4096);
+        assert_eq!(bodies[1]["stream_options"]["include_usage"], true);
+        assert!(bodies[2].get("temperature").is_none());
+    }
+}
@@ -0,0 +1,836 @@
+//! OpenAI-compatible chat completions for the SDK.
+//! 
+//! Several providers serve OpenAI's `/chat/completions` wire format with
+//! their own base URL, model list and quirks. This module holds the shared
+//! pieces: mapping SDK messages and tool specs to a request body, mapping
+//! the response back to a `ModelResponse`, and classifying error statuses.
+//! Provider modules adjust the body for their quirks before sending it.
+//! 
+//! Reasoning returned in `reasoning_content` becomes the response's
+//! reasoning blocks. It is never sent back: providers that return it reject
+//! requests that echo it in the conversation.
+//! 
+//! Streamed completions arrive as server-sent events; `parse_chat_stream`
+//! turns them into stream events, assembling tool call arguments from their
+//! fragments.
+//! 
+//! `OpenAICompatibleModel` sends these requests to any compatible endpoint,
+//! such as Mistral, Together, Groq or a self-hosted vLLM or LM Studio
+//! server, without OpenAI's own parameter rules.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::models::http::{HttpClient, HttpRequest, HttpResponse};
+use crate::models::plain_http::default_client;
+use crate::models::stream_decode::{decode_all, decode_body, sse_data, LineSplitter, StreamDecoder};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::tools::repair::{parse_arguments, MALFORMED_TOOL_CALLS_METADATA_KEY};
+use crate::tools::strict::strict_tool_schema;
+use crate::types::streaming::{MessageDelta, StreamContent, StreamEventPool};
+use crate::types::{
+    ContentBlock, IndubitablyError, IndubitablyResult, MessageRole, Messages, ModelError, StreamEvent, ToolSpec, ToolUse,
+};
+
+/// Build a chat completions request body.
+pub fn chat_request_body(
+    config: &ModelConfig,
+    messages: &Messages,
+    tool_specs: Option<&[ToolSpec]>,
+    system_prompt: Option<&str>,
+) -> serde_json::Value {
+    let mut wire_messages = Vec::new();
+    if let Some(prompt) = system_prompt {
+        wire_messages.push(json!({ "role": "system", "content": prompt }));
+    }
+    for message in messages {
+        let text: Vec<&str> = message.content.iter().filter_map(|block| block.text.as_deref()).collect();
+        match message.role {
+            MessageRole::System => wire_messages.push(json!({ "role": "system", "content": text.join("\n") })),
+            MessageRole::Assistant => {
+                let tool_calls: Vec<serde_json::Value> = message
+                    .tool_uses()
+                    .into_iter()
+                    .map(|tool_use| {
+                        json!({
+                            "id": tool_use.tool_use_id,
+                            "type": "function",
+                            "function": {
+                                "name": tool_use.name,
+                                "arguments": tool_use.input.clone().unwrap_or_else(|| json!({})).to_string(),
+                            },
+                        })
+                    })
+                    .collect();
+                let mut wire = json!({
+                    "role": "assistant",
+                    "content": if text.is_empty() { serde_json::Value::Null } else { json!(text.join("")) },
+                });
+                if !tool_calls.is_empty() {
+                    wire["tool_calls"] = json!(tool_calls);
+                }
+                wire_messages.push(wire);
+            }
+            MessageRole::User | MessageRole::Tool => {
+                for result in message.tool_result_blocks() {
+                    let content: Vec<&str> = result.content.iter().filter_map(|content| content.text.as_deref()).collect();
+                    wire_messages.push(json!({
+                        "role": "tool",
+                        "tool_call_id": result.tool_use_id,
+                        "content": content.join("\n"),
+                    }));
+                }
+                let has_media = message.content.iter().any(|block| block.image.is_some() || block.document.is_some());
+                if has_media {
+                    let parts: Vec<serde_json::Value> = message.content.iter().flat_map(user_content_parts).collect();
+                    wire_messages.push(json!({ "role": "user", "content": parts }));
+                } else if !text.is_empty() {
+                    wire_messages.push(json!({ "role": "user", "content": text.join("\n") }));
+                }
+            }
+        }
+    }
+
+    let mut body = json!({ "model": config.model_id, "messages": wire_messages });
+    if let Some(temperature) = config.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(max_tokens) = config.max_tokens {
+        body["max_tokens"] = json!(max_tokens);
+    }
+    if let Some(top_p) = config.top_p {
+        body["top_p"] = json!(top_p);
+    }
+    if let Some(specs) = tool_specs.filter(|specs| !specs.is_empty()) {
+        let tools: Vec<serde_json::Value> = specs
+            .iter()
+            .map(|spec| {
+                let mut function = json!({
+                    "name": spec.name,
+                    "description": spec.description,
+                    "parameters": spec.input_schema.clone().unwrap_or_else(|| json!({ "type": "object" })),
+                });
+                if let Some(strict) = config.strict_tools.then(|| strict_tool_schema(spec)).flatten() {
+                    function["parameters"] = strict;
+                    function["strict"] = json!(true);
+                }
+                json!({ "type": "function", "function": function })
+            })
+            .collect();
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+/// Map a user content block to chat content parts.
+///
+/// Images are sent as URLs or base64 data URLs, PDFs as inline files, and
+/// text documents as text.
+fn user_content_parts(block: &ContentBlock) -> Vec<serde_json::Value> {
+    let mut parts = Vec::new();
+    if let Some(text) = block.text.as_deref().filter(|text| !text.is_empty()) {
+        parts.push(json!({ "type": "text", "text": text }));
+    }
+    if let Some(ref image) = block.image {
+        let url = match image.url_data() {
+            Some(url) => Some(url.to_string()),
+            None => image
+                .base64_data()
+                .map(|data| format!("data:{};base64,{}", image.source.media_type, data)),
+        };
+        if let Some(url) = url {
+            parts.push(json!({ "type": "image_url", "image_url": { "url": url } }));
+        }
+    }
+    if let Some(ref document) = block.document {
+        match document.text_data() {
+            Some(text) => parts.push(json!({ "type": "text", "text": text })),
+            None => {
+                if let Some(data) = document.base64_data() {
+                    parts.push(json!({
+                        "type": "file",
+                        "file": {
+                            "filename": format!("document.{}", document.format()),
+                            "file_data": format!("data:{};base64,{}", document.source.media_type, data),
+                        },
+                    }));
+                }
+            }
+        }
+    }
+    parts
+}
+
+#[derive(Deserialize)]
+struct ChatCompletion {
+    #[serde(default)]
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ChatMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    id: String,
+    function: FunctionCall,
+}
+
+#[derive(Deserialize)]
+struct FunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+    #[serde(default)]
+    completion_tokens_details: Option<CompletionTokensDetails>,
+}
+
+#[derive(Deserialize)]
+struct CompletionTokensDetails {
+    #[serde(default)]
+    reasoning_tokens: u32,
+}
+
+/// Map a chat completions response body to a model response.
+pub fn parse_chat_response(response: &HttpResponse) -> IndubitablyResult<ModelResponse> {
+    let completion: ChatCompletion = response.json()?;
+    let choice = completion.choices.into_iter().next().ok_or_else(|| {
+        IndubitablyError::ModelError(ModelError::InvalidResponseFormat("Response has no choices".to_string()))
+    })?;
+
+    let mut model_response = ModelResponse::new(choice.message.content.as_deref().unwrap_or_default());
+    if let Some(reasoning) = choice.message.reasoning_content.filter(|reasoning| !reasoning.is_empty()) {
+        model_response = model_response.with_reasoning(&reasoning);
+    }
+    let mut malformed = serde_json::Map::new();
+    for call in choice.message.tool_calls {
+        let tool_use = ToolUse::new(&call.function.name, &call.id);
+        let tool_use = match parse_arguments(&call.function.arguments) {
+            Ok(input) => tool_use.with_input(input),
+            Err(error) => {
+                malformed.insert(call.id.clone(), json!(error));
+                tool_use
+            }
+        };
+        model_response = model_response.with_tool_use(tool_use);
+    }
+    if !malformed.is_empty() {
+        model_response.metadata.insert(MALFORMED_TOOL_CALLS_METADATA_KEY.to_string(), json!(malformed));
+    }
+    if let Some(usage) = completion.usage {
+        let reasoning_tokens = usage.completion_tokens_details.map_or(0, |details| details.reasoning_tokens);
+        model_response = model_response
+            .with_usage(usage.prompt_tokens, usage.completion_tokens)
+            .with_reasoning_tokens(reasoning_tokens);
+    }
+    if let Some(reason) = choice.finish_reason {
+        model_response.metadata.insert("finish_reason".to_string(), json!(reason));
+    }
+    Ok(model_response)
+}
+
+/// Map a streamed chat completions body of server-sent events to stream events.
+///
+/// Text is forwarded as it arrives. Tool calls arrive as argument fragments
+/// keyed by index, so each is emitted once the choice finishes. Usage from
+/// the final chunk, sent when `stream_options.include_usage` is set, is
+/// attached to the `message_stop` event.
+pub fn parse_chat_stream(body: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+    decode_all(ChatStreamDecoder::default(), body)
+}
+
+/// Decodes a streamed chat completions body as it arrives; see `parse_chat_stream`.
+#[derive(Default)]
+pub struct ChatStreamDecoder {
+    lines: LineSplitter,
+    started: bool,
+    text_open: bool,
+    tool_calls: BTreeMap<u64, (String, String, String)>,
+    malformed: serde_json::Map<String, Value>,
+    usage: Option<Value>,
+    finish_reason: Option<String>,
+    done: bool,
+    failed: bool,
+}
+
+impl ChatStreamDecoder {
+    fn fail(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>, error: ModelError) {
+        events.push(Err(IndubitablyError::ModelError(error)));
+        self.done = true;
+        self.failed = true;
+    }
+
+    fn on_data(&mut self, data: &str, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        if data == "[DONE]" {
+            self.done = true;
+            return;
+        }
+        let chunk: Value = match serde_json::from_str(data) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                return self.fail(events, ModelError::InvalidResponseFormat(format!("Invalid stream chunk: {}", e)));
+            }
+        };
+        if let Some(error) = chunk.get("error") {
+            let message = error["message"].as_str().unwrap_or_default();
+            return self.fail(events, ModelError::RequestFailed(message.to_string()));
+        }
+        if let Some(usage) = chunk.get("usage").filter(|usage| !usage.is_null()) {
+            self.usage = Some(usage.clone());
+        }
+        let Some(choice) = chunk["choices"].get(0) else {
+            return;
+        };
+        let delta = &choice["delta"];
+        if let Some(content) = delta["content"].as_str().filter(|content| !content.is_empty()) {
+            if self.text_open {
+                events.push(Ok(StreamEventPool::shared().text_delta(content)));
+            } else {
+                self.text_open = true;
+                events.push(Ok(StreamEvent::content_block_start(vec![StreamContent::text(content)])));
+            }
+        }
+        if let Some(reasoning) = delta["reasoning_content"].as_str().filter(|reasoning| !reasoning.is_empty()) {
+            events.push(Ok(StreamEventPool::shared().text_delta(reasoning)
+                .with_metadata("reasoning", json!(true))));
+        }
+        for call in delta["tool_calls"].as_array().into_iter().flatten() {
+            let (id, name, arguments) = self.tool_calls.entry(call["index"].as_u64().unwrap_or_default()).or_default();
+            if let Some(call_id) = call["id"].as_str() {
+                id.push_str(call_id);
+            }
+            if let Some(function_name) = call["function"]["name"].as_str() {
+                name.push_str(function_name);
+            }
+            if let Some(fragment) = call["function"]["arguments"].as_str() {
+                arguments.push_str(fragment);
+            }
+        }
+        if let Some(reason) = choice["finish_reason"].as_str() {
+            if std::mem::take(&mut self.text_open) {
+                events.push(Ok(StreamEvent::content_block_stop()));
+            }
+            for (id, name, arguments) in std::mem::take(&mut self.tool_calls).into_values() {
+                let tool_use = ToolUse::new(&name, &id);
+                let tool_use = match parse_arguments(&arguments) {
+                    Ok(input) => tool_use.with_input(input),
+                    Err(error) => {
+                        self.malformed.insert(id.clone(), json!(error));
+                        tool_use
+                    }
+                };
+                events.push(Ok(StreamEvent::tool_use_start(tool_use)));
+                events.push(Ok(StreamEvent::tool_use_stop()));
+            }
+            self.finish_reason = Some(reason.to_string());
+            events.push(Ok(StreamEvent::message_delta(MessageDelta {
+                role: None,
+                content: None,
+                stop_reason: Some(reason.to_string()),
+                stop_sequence: None,
+            })));
+        }
+    }
+
+    fn start(&mut self, events: &mut Vec<IndubitablyResult<StreamEvent>>) {
+        if !std::mem::replace(&mut self.started, true) {
+            events.push(Ok(StreamEvent::message_start()));
+        }
+    }
+}
+
+impl StreamDecoder for ChatStreamDecoder {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        self.start(&mut events);
+        for line in self.lines.push(chunk) {
+            if self.done {
+                break;
+            }
+            if let Some(data) = sse_data(&line) {
+                self.on_data(data, &mut events);
+            }
+        }
+        events
+    }
+
+    fn finish(&mut self) -> Vec<IndubitablyResult<StreamEvent>> {
+        let mut events = Vec::new();
+        self.start(&mut events);
+        if let Some(line) = self.lines.finish().filter(|_| !self.done) {
+            if let Some(data) = sse_data(&line) {
+                self.on_data(data, &mut events);
+            }
+        }
+        if self.failed {
+            return events;
+        }
+        self.done = true;
+        let mut stop = StreamEvent::message_stop();
+        if let Some(usage) = self.usage.take() {
+            stop = stop.with_metadata("usage", usage);
+        }
+        if let Some(reason) = self.finish_reason.take() {
+            stop = stop.with_metadata("finish_reason", json!(reason));
+        }
+        if !self.malformed.is_empty() {
+            stop = stop.with_metadata(MALFORMED_TOOL_CALLS_METADATA_KEY, json!(std::mem::take(&mut self.malformed)));
+        }
+        events.push(Ok(stop));
+        events
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Map an unsuccessful response to an error.
+pub fn status_error(provider: &str, response: &HttpResponse) -> IndubitablyError {
+    let message = format!("{} returned status {}: {}", provider, response.status, response.text());
+    IndubitablyError::ModelError(match response.status {
+        401 | 403 => ModelError::InvalidConfiguration(message),
+        429 => ModelError::ModelThrottled(message),
+        503 => ModelError::ModelNotAvailable(message),
+        _ => ModelError::RequestFailed(message),
+    })
+}
+
+/// The base URL of the Mistral API.
+pub const MISTRAL_BASE_URL: &str = "https://api.mistral.ai/v1";
+
+/// The environment variable holding the Mistral API key.
+pub const MISTRAL_API_KEY_ENV: &str = "MISTRAL_API_KEY";
+
+/// The base URL of the Together API.
+pub const TOGETHER_BASE_URL: &str = "https://api.together.xyz/v1";
+
+/// The environment variable holding the Together API key.
+pub const TOGETHER_API_KEY_ENV: &str = "TOGETHER_API_KEY";
+
+/// The base URL of Groq's OpenAI-compatible API.
+pub const GROQ_BASE_URL: &str = "https://api.groq.com/openai/v1";
+
+/// The environment variable holding the Groq API key.
+pub const GROQ_API_KEY_ENV: &str = "GROQ_API_KEY";
+
+/// The default base URL of a local vLLM server.
+pub const VLLM_BASE_URL: &str = "http://localhost:8000/v1";
+
+/// The default base URL of a local LM Studio server.
+pub const LM_STUDIO_BASE_URL: &str = "http://localhost:1234/v1";
+
+/// Configuration for an OpenAI-compatible endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAICompatibleConfig {
+    /// The provider name used in errors and logs.
+    pub provider: String,
+    /// The base URL the `/chat/completions` path is appended to.
+    pub base_url: String,
+    /// The API key sent as a bearer token; local servers usually need none.
+    pub api_key: Option<String>,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// Whether streamed requests ask for usage with `stream_options`, which some servers reject.
+    pub stream_usage: bool,
+    /// Additional request headers.
+    pub headers: HashMap<String, String>,
+    /// Additional request body fields.
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl OpenAICompatibleConfig {
+    /// Create a configuration for a model served at a base URL.
+    pub fn new(base_url: &str, model_id: &str) -> Self {
+        Self {
+            provider: "OpenAI-compatible endpoint".to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+            model_id: model_id.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: None,
+            stream_usage: true,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Create a configuration for Mistral with the API key from `MISTRAL_API_KEY`.
+    pub fn mistral(model_id: &str) -> Self {
+        Self::new(MISTRAL_BASE_URL, model_id)
+            .with_provider("Mistral")
+            .with_api_key_from_env(MISTRAL_API_KEY_ENV)
+            .with_stream_usage(false)
+    }
+
+    /// Create a configuration for Together with the API key from `TOGETHER_API_KEY`.
+    pub fn together(model_id: &str) -> Self {
+        Self::new(TOGETHER_BASE_URL, model_id)
+            .with_provider("Together")
+            .with_api_key_from_env(TOGETHER_API_KEY_ENV)
+    }
+
+    /// Create a configuration for Groq with the API key from `GROQ_API_KEY`.
+    pub fn groq(model_id: &str) -> Self {
+        Self::new(GROQ_BASE_URL, model_id)
+            .with_provider("Groq")
+            .with_api_key_from_env(GROQ_API_KEY_ENV)
+            .with_stream_usage(false)
+    }
+
+    /// Create a configuration for a local vLLM server.
+    pub fn vllm(model_id: &str) -> Self {
+        Self::new(VLLM_BASE_URL, model_id).with_provider("vLLM")
+    }
+
+    /// Create a configuration for a local LM Studio server.
+    pub fn lm_studio(model_id: &str) -> Self {
+        Self::new(LM_STUDIO_BASE_URL, model_id)
+            .with_provider("LM Studio")
+            .with_stream_usage(false)
+    }
+
+    /// Set the provider name used in errors and logs.
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = provider.to_string();
+        self
+    }
+
+    /// Set the base URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Set the API key from an environment variable, leaving it unset when the variable is not.
+    pub fn with_api_key_from_env(mut self, variable: &str) -> Self {
+        self.api_key = std::env::var(variable).ok().filter(|key| !key.is_empty());
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set whether streamed requests ask for usage with `stream_options`.
+    pub fn with_stream_usage(mut self, stream_usage: bool) -> Self {
+        self.stream_usage = stream_usage;
+        self
+    }
+
+    /// Add a request header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Add extra configuration.
+    pub fn with_extra(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+}
+
+/// A model served by any OpenAI-compatible chat completions endpoint.
+pub struct OpenAICompatibleModel {
+    config: ModelConfig,
+    compatible_config: OpenAICompatibleConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl std::fmt::Debug for OpenAICompatibleModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenAICompatibleModel")
+            .field("config", &self.config)
+            .field("provider", &self.compatible_config.provider)
+            .field("base_url", &self.compatible_config.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OpenAICompatibleModel {
+    /// Create a new model with the given configuration.
+    pub fn with_config(compatible_config: OpenAICompatibleConfig) -> Self {
+        let mut config = ModelConfig::new(&compatible_config.model_id);
+        config.temperature = compatible_config.temperature;
+        config.max_tokens = compatible_config.max_tokens;
+        config.top_p = compatible_config.top_p;
+        Self {
+            client: default_client(&compatible_config.provider, &compatible_config.base_url),
+            config,
+            compatible_config,
+        }
+    }
+
+    /// Set the client that sends requests to the endpoint.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// List the IDs of the models the endpoint serves.
+    pub async fn list_models(&self) -> IndubitablyResult<Vec<String>> {
+        let request = self.authorize(HttpRequest::get(&format!("{}/models", self.compatible_config.base_url)));
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&self.compatible_config.provider, &response));
+        }
+        let body: Value = response.json()?;
+        Ok(body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model["id"].as_str().map(str::to_string))
+            .collect())
+    }
+
+    /// Add the API key and configured headers to a request.
+    fn authorize(&self, mut request: HttpRequest) -> HttpRequest {
+        if let Some(ref api_key) = self.compatible_config.api_key {
+            request = request.with_header("authorization", &format!("Bearer {}", api_key));
+        }
+        for (name, value) in &self.compatible_config.headers {
+            request = request.with_header(name, value);
+        }
+        request
+    }
+
+    /// Build a chat completions request.
+    fn chat_request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let mut body = chat_request_body(&self.config, messages, tool_specs, system_prompt);
+        for (key, value) in self.compatible_config.extra.iter().chain(&self.config.extra) {
+            body[key] = value.clone();
+        }
+        if stream {
+            body["stream"] = json!(true);
+            if self.compatible_config.stream_usage {
+                body["stream_options"] = json!({ "include_usage": true });
+            }
+        }
+        self.authorize(HttpRequest::post(&format!("{}/chat/completions", self.compatible_config.base_url)))
+            .with_json_body(&body)
+    }
+}
+
+#[async_trait]
+impl Model for OpenAICompatibleModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.chat_request(messages, tool_specs, system_prompt, false)?;
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&self.compatible_config.provider, &response));
+        }
+        let model_response = parse_chat_response(&response)?;
+        tracing::debug!(
+            "provider=<{}>, model_id=<{}>, tool_uses=<{}> | openai-compatible response received",
+            self.compatible_config.provider,
+            self.config.model_id,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.chat_request(messages, tool_specs, system_prompt, true)?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&self.compatible_config.provider, &response.collect().await?));
+        }
+        Ok(decode_body(response.body, ChatStreamDecoder::default()))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(format!(
+            "{} model does not support structured output yet",
+            self.compatible_config.provider
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Message;
+    use tokio_stream::StreamExt;
+
+    /// A client answering every chat completions request with a canned completion.
+    #[derive(Debug, Clone)]
+    struct MockChatCompletions {
+        reply: String,
+    }
+
+    impl MockChatCompletions {
+        /// Create a client that always replies with the given text.
+        fn new(reply: &str) -> Self {
+            Self { reply: reply.to_string() }
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for MockChatCompletions {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            let streaming = serde_json::from_slice::<Value>(&request.body).is_ok_and(|body| body["stream"] == json!(true));
+            if streaming {
+                let chunks = [
+                    json!({ "choices": [{ "index": 0, "delta": { "role": "assistant", "content": self.reply } }] }),
+                    json!({ "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }] }),
+                    json!({ "choices": [], "usage": { "prompt_tokens": 10, "completion_tokens": 15, "total_tokens": 25 } }),
+                ];
+                let mut body: String = chunks.iter().map(|chunk| format!("data: {}\n\n", chunk)).collect();
+                body.push_str("data: [DONE]\n\n");
+                return Ok(HttpResponse::new(200, body.into_bytes()).with_header("content-type", "text/event-stream"));
+            }
+            let body = json!({
+                "choices": [{ "message": { "role": "assistant", "content": self.reply }, "finish_reason": "stop" }],
+                "usage": { "prompt_tokens": 10, "completion_tokens": 15, "total_tokens": 25 },
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    /// Records requests and answers like a local server that rejects `stream_options`.
+    #[derive(Default)]
+    struct LocalServer {
+        requests: std::sync::Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for LocalServer {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            if request.url.ends_with("/models") {
+                let body = json!({ "object": "list", "data": [{ "id": "qwen2.5-7b-instruct" }, { "id": "llama-3.1-8b" }] });
+                return Ok(HttpResponse::new(200, body.to_string().into_bytes()));
+            }
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            if body.get("stream_options").is_some() {
+                return Ok(HttpResponse::new(400, b"Unrecognized request argument: stream_options".to_vec()));
+            }
+            MockChatCompletions::new("Hello from the local model.").send(request).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_endpoints() {
+        let server = Arc::new(LocalServer::default());
+        let model = OpenAICompatibleModel::with_config(
+            OpenAICompatibleConfig::lm_studio("qwen2.5-7b-instruct").with_header("x-request-source", "tests"),
+        )
+        .with_client(server.clone());
+        let messages = vec![Message::user("Hi")];
+
+        let response = model.generate(&messages, None, None).await.unwrap();
+        assert_eq!(response.content, "Hello from the local model.");
+        let events: Vec<StreamEvent> = model.stream(&messages, None, None).await.unwrap().map(Result::unwrap).collect().await;
+        assert_eq!(events.last().unwrap().metadata.as_ref().unwrap()["usage"]["total_tokens"], 25);
+        assert_eq!(model.list_models().await.unwrap(), vec!["qwen2.5-7b-instruct", "llama-3.1-8b"]);
+
+        let requests = server.requests.lock().unwrap().clone();
+        assert_eq!(requests[0].url, "http://localhost:1234/v1/chat/completions");
+        assert_eq!(requests[0].header("authorization"), None);
+        assert_eq!(requests[0].header("x-request-source"), Some("tests"));
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["max_tokens"], 4096);
+        assert_eq!(requests[2].url, "http://localhost:1234/v1/models");
+
+        let vllm = OpenAICompatibleModel::with_config(OpenAICompatibleConfig::vllm("llama-3.1-8b").with_api_key("token"))
+            .with_client(server.clone());
+        let error = vllm.stream(&messages, None, None).await.err().unwrap();
+        assert!(error.to_string().contains("vLLM returned status 400"));
+        assert_eq!(server.requests.lock().unwrap()[3].header("authorization"), Some("Bearer token"));
+    }
+}
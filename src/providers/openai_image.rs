@@ -0,0 +1,135 @@
+//! OpenAI image generation for the SDK.
+//! 
+//! This module provides `OpenAIImageModel`, an `ImageGenerationModel` for the
+//! OpenAI Images API, covering both GPT image and DALL-E models.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::openai::OPENAI_BASE_URL;
+use super::openai_compat::status_error;
+use crate::models::http::{HttpClient, HttpRequest};
+use crate::models::image::{ImageGenerationModel, ImageRequest};
+use crate::types::{ImageContent, IndubitablyError, IndubitablyResult, ModelError};
+
+/// Default OpenAI image model ID.
+pub const DEFAULT_OPENAI_IMAGE_MODEL_ID: &str = "gpt-image-1";
+
+/// Generates images with the OpenAI Images API.
+pub struct OpenAIImageModel {
+    client: Arc<dyn HttpClient>,
+    api_key: String,
+    base_url: String,
+    model_id: String,
+}
+
+impl std::fmt::Debug for OpenAIImageModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenAIImageModel")
+            .field("base_url", &self.base_url)
+            .field("model_id", &self.model_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OpenAIImageModel {
+    /// Create a model sending requests through `client` with an API key.
+    pub fn new(client: Arc<dyn HttpClient>, api_key: &str) -> Self {
+        Self {
+            client,
+            api_key: api_key.to_string(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            model_id: DEFAULT_OPENAI_IMAGE_MODEL_ID.to_string(),
+        }
+    }
+
+    /// Set the model ID, such as `dall-e-3`.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the base URL, such as a proxy endpoint.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl ImageGenerationModel for OpenAIImageModel {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    async fn generate_images(&self, request: &ImageRequest) -> IndubitablyResult<Vec<ImageContent>> {
+        let mut body = json!({
+            "model": self.model_id,
+            "prompt": request.prompt,
+            "n": request.count,
+            "size": format!("{}x{}", request.width, request.height),
+        });
+        // DALL-E models return URLs unless asked for base64; GPT image models only return base64
+        if self.model_id.starts_with("dall-e") {
+            body["response_format"] = json!("b64_json");
+        }
+        let http_request = HttpRequest::post(&format!("{}/images/generations", self.base_url))
+            .with_header("authorization", &format!("Bearer {}", self.api_key))
+            .with_json_body(&body)?;
+        let response = self.client.send(http_request).await?;
+        if !response.is_success() {
+            return Err(status_error("OpenAI", &response));
+        }
+
+        let body: Value = response.json()?;
+        let images: Vec<ImageContent> = body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|image| match (image["b64_json"].as_str(), image["url"].as_str()) {
+                (Some(base64), _) => Some(ImageContent::base64(base64, "image/png")),
+                (None, Some(url)) => Some(ImageContent::url(url, "image/png")),
+                (None, None) => None,
+            })
+            .collect();
+        if images.is_empty() {
+            return Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat("OpenAI returned no images".to_string())));
+        }
+        tracing::debug!("model_id=<{}>, images=<{}> | openai images generated", self.model_id, images.len());
+        Ok(images)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+
+    struct ImagesEndpoint {
+        bodies: std::sync::Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for ImagesEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert_eq!(request.url, "https://api.openai.com/v1/images/generations");
+            self.bodies.lock().unwrap().push(serde_json::from_slice(&request.body).unwrap());
+            let reply = json!({ "data": [{ "b64_json": "aW1hZ2U=" }] });
+            Ok(HttpResponse::new(200, reply.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_openai_image_model() {
+        let endpoint = Arc::new(ImagesEndpoint { bodies: Default::default() });
+        let model = OpenAIImageModel::new(endpoint.clone(), "sk-test").with_model_id("dall-e-3");
+        let images = model.generate_images(&ImageRequest::new("a lighthouse at dusk")).await.unwrap();
+        assert_eq!(images[0].source.data.base64.as_deref(), Some("aW1hZ2U="));
+
+        let bodies = endpoint.bodies.lock().unwrap();
+        assert_eq!(bodies[0]["response_format"], "b64_json");
+        assert_eq!(bodies[0]["size"], "1024x1024");
+    }
+}
@@ -0,0 +1,185 @@
+//! OpenAI speech transcription for the SDK.
+//! 
+//! This module provides `WhisperTranscriber`, a `Transcriber` sending audio
+//! to OpenAI's transcription endpoint through the caller's `HttpClient`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::openai::{OPENAI_API_KEY_ENV, OPENAI_BASE_URL};
+use crate::media::transcription::{audio_bytes, transcription_error, Transcriber, Transcription};
+use crate::models::http::{HttpClient, HttpRequest};
+use crate::types::{AudioContent, IndubitablyError, IndubitablyResult};
+
+/// The default OpenAI transcription model.
+pub const DEFAULT_WHISPER_MODEL: &str = "whisper-1";
+
+/// Transcribes audio with OpenAI's Whisper transcription endpoint.
+#[derive(Clone)]
+pub struct WhisperTranscriber {
+    client: Option<Arc<dyn HttpClient>>,
+    api_key: String,
+    base_url: String,
+    model: String,
+    language: Option<String>,
+    prompt: Option<String>,
+}
+
+impl std::fmt::Debug for WhisperTranscriber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WhisperTranscriber")
+            .field("base_url", &self.base_url)
+            .field("model", &self.model)
+            .field("language", &self.language)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WhisperTranscriber {
+    /// Create a transcriber with the given API key.
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: None,
+            api_key: api_key.to_string(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            model: DEFAULT_WHISPER_MODEL.to_string(),
+            language: None,
+            prompt: None,
+        }
+    }
+
+    /// Create a transcriber with the API key from `OPENAI_API_KEY`.
+    pub fn from_env() -> Self {
+        Self::new(&std::env::var(OPENAI_API_KEY_ENV).unwrap_or_default())
+    }
+
+    /// Set the client that sends requests to the OpenAI API.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Set the base URL, such as that of an OpenAI-compatible server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the transcription model, such as `whisper-1` or `gpt-4o-transcribe`.
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    /// Set the spoken language as an ISO-639-1 code, instead of detecting it.
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// Set text guiding the transcription's spelling and style.
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Build the multipart transcription request.
+    fn request(&self, audio: &AudioContent) -> IndubitablyResult<HttpRequest> {
+        let boundary = format!("indubitably-{}", uuid::Uuid::new_v4().simple());
+        let mut body = Vec::new();
+        let mut field = |name: &str, value: &str| {
+            body.extend_from_slice(
+                format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value)
+                    .as_bytes(),
+            );
+        };
+        field("model", &self.model);
+        field("response_format", if self.model.starts_with("whisper") { "verbose_json" } else { "json" });
+        if let Some(ref language) = self.language {
+            field("language", language);
+        }
+        if let Some(ref prompt) = self.prompt {
+            field("prompt", prompt);
+        }
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.{}\"\r\n\
+                 Content-Type: {}\r\n\r\n",
+                boundary,
+                audio.format(),
+                audio.source.media_type
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&audio_bytes(audio)?);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+        Ok(HttpRequest::post(&format!("{}/audio/transcriptions", self.base_url))
+            .with_header("authorization", &format!("Bearer {}", self.api_key))
+            .with_header("content-type", &format!("multipart/form-data; boundary={}", boundary))
+            .with_body(body))
+    }
+}
+
+#[async_trait]
+impl Transcriber for WhisperTranscriber {
+    async fn transcribe(&self, audio: &AudioContent) -> IndubitablyResult<Transcription> {
+        let client = self.client.as_ref().ok_or_else(|| {
+            IndubitablyError::ConfigurationError("Whisper transcription needs an HTTP client".to_string())
+        })?;
+        let response = client.send(self.request(audio)?).await?;
+        if !response.is_success() {
+            return Err(transcription_error(format!(
+                "OpenAI returned status {}: {}",
+                response.status,
+                response.text()
+            )));
+        }
+        let transcription: Transcription = response.json()?;
+        tracing::debug!(
+            "model=<{}>, language=<{:?}>, chars=<{}> | transcribed audio",
+            self.model,
+            transcription.language,
+            transcription.text.len()
+        );
+        Ok(transcription)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+
+    struct TranscriptionEndpoint;
+
+    #[async_trait]
+    impl HttpClient for TranscriptionEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert_eq!(request.url, "https://api.openai.com/v1/audio/transcriptions");
+            assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+            assert!(request.header("content-type").unwrap().starts_with("multipart/form-data; boundary="));
+            let body = String::from_utf8_lossy(&request.body);
+            assert!(body.contains("name=\"model\"\r\n\r\nwhisper-1\r\n"));
+            assert!(body.contains("name=\"language\"\r\n\r\nen\r\n"));
+            assert!(body.contains("filename=\"audio.wav\"\r\nContent-Type: audio/wav\r\n\r\nRIFF"));
+            let body = br#"{"text": "Book a table for two.", "language": "english", "duration": 1.5}"#;
+            Ok(HttpResponse::new(200, body.to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_whisper_sends_multipart_audio() {
+        let audio = AudioContent::from_bytes(b"RIFF\x24\x00\x00\x00WAVEfmt ", "note.bin").unwrap();
+        let transcriber = WhisperTranscriber::new("sk-test")
+            .with_client(Arc::new(TranscriptionEndpoint))
+            .with_language("en");
+
+        let transcription = transcriber.transcribe(&audio).await.unwrap();
+
+        assert_eq!(transcription.text, "Book a table for two.");
+        assert_eq!(transcription.language.as_deref(), Some("english"));
+        assert_eq!(transcription.duration, Some(1.5));
+    }
+}
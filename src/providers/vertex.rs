@@ -0,0 +1,369 @@
+//! Vertex AI model implementation for the SDK.
+//! 
+//! This module provides access to Gemini models through Google Cloud's
+//! Vertex AI endpoints. Unlike the consumer Gemini API, which is keyed by an
+//! API key, Vertex AI is addressed by project and location and authorized
+//! with Google Cloud credentials, usually Application Default Credentials.
+//! The request and response format is shared with `GeminiModel`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::gemini::{generate_content_body, parse_generate_content, status_error, GenerateContentStreamDecoder};
+use super::google_auth::ApplicationDefaultCredentials;
+use crate::models::http::{HttpClient, HttpRequest};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::decode_body;
+use crate::models::signing::TokenProvider;
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, SystemContentBlock, ToolSpec};
+
+pub use super::gemini::{HarmBlockThreshold, HarmCategory, SafetySetting};
+
+/// Default Vertex AI model ID.
+pub const DEFAULT_VERTEX_MODEL_ID: &str = "gemini-1.5-pro";
+
+/// Default Vertex AI location.
+pub const DEFAULT_VERTEX_LOCATION: &str = "us-central1";
+
+/// Configuration specific to Vertex AI models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VertexConfig {
+    /// The Google Cloud project ID.
+    pub project_id: String,
+    /// The Vertex AI location, such as `us-central1` or `global`.
+    pub location: String,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// The top-k value for sampling.
+    pub top_k: Option<u32>,
+    /// The safety filter settings.
+    #[serde(default)]
+    pub safety_settings: Vec<SafetySetting>,
+}
+
+impl Default for VertexConfig {
+    fn default() -> Self {
+        Self {
+            project_id: String::new(),
+            location: DEFAULT_VERTEX_LOCATION.to_string(),
+            model_id: DEFAULT_VERTEX_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: None,
+            top_k: None,
+            safety_settings: Vec::new(),
+        }
+    }
+}
+
+impl VertexConfig {
+    /// Create a new Vertex AI configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the Google Cloud project ID.
+    pub fn with_project_id(mut self, project_id: &str) -> Self {
+        self.project_id = project_id.to_string();
+        self
+    }
+
+    /// Set the Vertex AI location.
+    pub fn with_location(mut self, location: &str) -> Self {
+        self.location = location.to_string();
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set the top-k value.
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Set the blocking threshold for a harm category.
+    pub fn with_safety_setting(mut self, category: HarmCategory, threshold: HarmBlockThreshold) -> Self {
+        self.safety_settings.retain(|setting| setting.category != category);
+        self.safety_settings.push(SafetySetting { category, threshold });
+        self
+    }
+
+    /// Get the `generateContent` endpoint for a model.
+    pub fn endpoint(&self, model_id: &str) -> String {
+        let host = if self.location == "global" {
+            "aiplatform.googleapis.com".to_string()
+        } else {
+            format!("{}-aiplatform.googleapis.com", self.location)
+        };
+        format!(
+            "https://{}/v1/projects/{}/locations/{}/publishers/google/models/{}:generateContent",
+            host, self.project_id, self.location, model_id
+        )
+    }
+
+    /// Get the `streamGenerateContent` endpoint for a model, answering with server-sent events.
+    pub fn stream_endpoint(&self, model_id: &str) -> String {
+        let endpoint = self.endpoint(model_id);
+        format!("{}:streamGenerateContent?alt=sse", endpoint.trim_end_matches(":generateContent"))
+    }
+}
+
+/// The Vertex AI model implementation.
+pub struct VertexModel {
+    config: ModelConfig,
+    vertex_config: VertexConfig,
+    client: Arc<dyn HttpClient>,
+    tokens: Arc<dyn TokenProvider>,
+}
+
+impl std::fmt::Debug for VertexModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VertexModel")
+            .field("config", &self.config)
+            .field("vertex_config", &self.vertex_config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VertexModel {
+    /// Create a Vertex AI model sending requests through `client` with tokens from `tokens`.
+    pub fn new(vertex_config: VertexConfig, client: Arc<dyn HttpClient>, tokens: Arc<dyn TokenProvider>) -> Self {
+        let mut config = ModelConfig::new(&vertex_config.model_id);
+        config.temperature = vertex_config.temperature;
+        config.max_tokens = vertex_config.max_tokens;
+        config.top_p = vertex_config.top_p;
+        config.top_k = vertex_config.top_k;
+        Self {
+            config,
+            vertex_config,
+            client,
+            tokens,
+        }
+    }
+
+    /// Create a Vertex AI model authorized with Application Default Credentials.
+    ///
+    /// An empty project ID is filled in from the credentials file when it names one.
+    /// Service-account key files need an RSA signer, so build their
+    /// `ApplicationDefaultCredentials` with `with_signer` and pass them to `new`.
+    pub fn with_application_default_credentials(
+        mut vertex_config: VertexConfig,
+        client: Arc<dyn HttpClient>,
+    ) -> IndubitablyResult<Self> {
+        let credentials = ApplicationDefaultCredentials::new(client.clone())?;
+        if vertex_config.project_id.is_empty() {
+            vertex_config.project_id = credentials.project_id().unwrap_or_default().to_string();
+        }
+        if vertex_config.project_id.is_empty() {
+            return Err(IndubitablyError::ModelError(ModelError::InvalidConfiguration(
+                "Vertex AI requires a project ID".to_string(),
+            )));
+        }
+        Ok(Self::new(vertex_config, client, Arc::new(credentials)))
+    }
+
+    /// Get the Vertex AI configuration.
+    pub fn vertex_config(&self) -> &VertexConfig {
+        &self.vertex_config
+    }
+
+    /// Build an authorized `generateContent` or `streamGenerateContent` request.
+    async fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let system: Vec<SystemContentBlock> = system_prompt
+            .map(|text| SystemContentBlock { text: text.to_string() })
+            .into_iter()
+            .collect();
+        let token = self.tokens.token().await?;
+        let url = if stream {
+            self.vertex_config.stream_endpoint(&self.config.model_id)
+        } else {
+            self.vertex_config.endpoint(&self.config.model_id)
+        };
+        HttpRequest::post(&url)
+            .with_header("authorization", &format!("Bearer {}", token))
+            .with_header("x-goog-user-project", &self.vertex_config.project_id)
+            .with_json_body(&generate_content_body(
+                &self.config,
+                messages,
+                tool_specs,
+                &system,
+                &self.vertex_config.safety_settings,
+            ))
+    }
+}
+
+#[async_trait]
+impl Model for VertexModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, false).await?;
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response, "Vertex AI"));
+        }
+        let model_response = parse_generate_content(&response, "Vertex AI")?;
+        tracing::debug!(
+            "model_id=<{}>, location=<{}>, tool_uses=<{}> | vertex ai response received",
+            self.config.model_id,
+            self.vertex_config.location,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, true).await?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error(&response.collect().await?, "Vertex AI"));
+        }
+        Ok(decode_body(response.body, GenerateContentStreamDecoder::new("Vertex AI")))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(
+            "Vertex AI model does not support structured output yet".to_string(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+    use crate::models::signing::StaticTokenProvider;
+    use crate::types::{Message, ToolResult, ToolUse};
+    use serde_json::json;
+
+    /// Records requests and answers with a canned Gemini response.
+    #[derive(Default)]
+    struct GeminiEndpoint {
+        requests: std::sync::Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for GeminiEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.requests.lock().unwrap().push(request);
+            let body = json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [
+                        { "text": "Checking the weather." },
+                        { "functionCall": { "name": "weather", "args": { "city": "Paris" } } }
+                    ]},
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": { "promptTokenCount": 12, "candidatesTokenCount": 5, "totalTokenCount": 17 }
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vertex_generate_content() {
+        let endpoint = Arc::new(GeminiEndpoint::default());
+        let config = VertexConfig::new()
+            .with_project_id("demo-project")
+            .with_location("europe-west4")
+            .with_safety_setting(HarmCategory::DangerousContent, HarmBlockThreshold::BlockOnlyHigh);
+        let model = VertexModel::new(config, endpoint.clone(), Arc::new(StaticTokenProvider::new("ya29.token")));
+
+        let messages = vec![
+            Message::user("Weather in Lyon?"),
+            Message::assistant_with_tool_uses("", vec![ToolUse::new("weather", "t1").with_input(json!({ "city": "Lyon" }))]),
+            Message::tool_results(vec![ToolResult::new("t1", vec![crate::types::ToolResultContent::text("Rain")])]),
+        ];
+        let tools = vec![ToolSpec::new("weather", "Get the weather").with_input_schema(json!({ "type": "object" }))];
+        let response = model.generate(&messages, Some(&tools), Some("Be brief.")).await.unwrap();
+
+        assert_eq!(response.content, "Checking the weather.");
+        assert_eq!(response.tool_uses[0].name, "weather");
+        assert_eq!(response.tool_uses[0].input, Some(json!({ "city": "Paris" })));
+        assert_eq!(response.usage.unwrap().total_tokens, 17);
+
+        let request = endpoint.requests.lock().unwrap().remove(0);
+        assert_eq!(
+            request.url,
+            "https://europe-west4-aiplatform.googleapis.com/v1/projects/demo-project/locations/europe-west4/publishers/google/models/gemini-1.5-pro:generateContent"
+        );
+        assert_eq!(request.header("authorization"), Some("Bearer ya29.token"));
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief.");
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(body["contents"][2]["parts"][0]["functionResponse"]["name"], "weather");
+        assert_eq!(body["contents"][2]["parts"][0]["functionResponse"]["response"]["content"], "Rain");
+        assert_eq!(body["tools"][0]["functionDeclarations"][0]["parameters"]["type"], "object");
+        assert_eq!(body["safetySettings"][0]["category"], "HARM_CATEGORY_DANGEROUS_CONTENT");
+        assert_eq!(body["safetySettings"][0]["threshold"], "BLOCK_ONLY_HIGH");
+        assert!(VertexConfig::new()
+            .with_location("global")
+            .endpoint("gemini-1.5-flash")
+            .starts_with("https://aiplatform.googleapis.com/v1/projects//locations/global/"));
+        assert!(VertexConfig::new()
+            .stream_endpoint("gemini-1.5-flash")
+            .ends_with("/models/gemini-1.5-flash:streamGenerateContent?alt=sse"));
+    }
+}
@@ -0,0 +1,449 @@
+//! xAI Grok model implementation for the SDK.
+//! 
+//! This module provides `GrokModel`, which talks to xAI's OpenAI-compatible
+//! chat completions endpoint. It handles the places Grok differs from
+//! OpenAI: reasoning models reject sampling penalties and stop sequences,
+//! only some of them accept a reasoning effort, and not every model in the
+//! catalog can call tools.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::models::http::{HttpClient, HttpRequest, UnconfiguredHttpClient};
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::models::stream_decode::decode_body;
+use super::openai_compat::{chat_request_body, parse_chat_response, status_error, ChatStreamDecoder};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, ToolSpec};
+
+/// The base URL of the xAI API.
+pub const XAI_BASE_URL: &str = "https://api.x.ai/v1";
+
+/// The environment variable holding the xAI API key.
+pub const XAI_API_KEY_ENV: &str = "XAI_API_KEY";
+
+/// Default Grok model ID.
+pub const DEFAULT_GROK_MODEL_ID: &str = "grok-3";
+
+/// Request parameters Grok reasoning models reject.
+const REASONING_UNSUPPORTED_PARAMS: &[&str] = &["presence_penalty", "frequency_penalty", "stop", "reasoning_effort"];
+
+/// What a Grok model can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrokModelInfo {
+    /// The model ID, which also matches dated variants such as `grok-4-0709`.
+    pub id: &'static str,
+    /// The context window in tokens.
+    pub context_window: u32,
+    /// Whether the model can call tools.
+    pub supports_tools: bool,
+    /// Whether the model accepts images.
+    pub supports_vision: bool,
+    /// Whether the model always reasons before answering.
+    pub reasoning: bool,
+    /// Whether the model accepts a `reasoning_effort`.
+    pub supports_reasoning_effort: bool,
+}
+
+/// The Grok models the SDK knows about.
+pub const GROK_MODELS: &[GrokModelInfo] = &[
+    GrokModelInfo {
+        id: "grok-4",
+        context_window: 256_000,
+        supports_tools: true,
+        supports_vision: true,
+        reasoning: true,
+        supports_reasoning_effort: false,
+    },
+    GrokModelInfo {
+        id: "grok-3",
+        context_window: 131_072,
+        supports_tools: true,
+        supports_vision: false,
+        reasoning: false,
+        supports_reasoning_effort: false,
+    },
+    GrokModelInfo {
+        id: "grok-3-mini",
+        context_window: 131_072,
+        supports_tools: true,
+        supports_vision: false,
+        reasoning: true,
+        supports_reasoning_effort: true,
+    },
+    GrokModelInfo {
+        id: "grok-2-vision",
+        context_window: 32_768,
+        supports_tools: true,
+        supports_vision: true,
+        reasoning: false,
+        supports_reasoning_effort: false,
+    },
+    GrokModelInfo {
+        id: "grok-2-image",
+        context_window: 8_192,
+        supports_tools: false,
+        supports_vision: false,
+        reasoning: false,
+        supports_reasoning_effort: false,
+    },
+];
+
+/// Look up a Grok model by ID, matching the longest catalog ID it starts with.
+pub fn grok_model_info(model_id: &str) -> Option<&'static GrokModelInfo> {
+    GROK_MODELS
+        .iter()
+        .filter(|info| model_id.starts_with(info.id))
+        .max_by_key(|info| info.id.len())
+}
+
+/// How hard a Grok reasoning model thinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    /// Answer quickly with little reasoning.
+    Low,
+    /// Reason at length before answering.
+    High,
+}
+
+/// Configuration specific to Grok models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrokConfig {
+    /// The xAI API key.
+    pub api_key: String,
+    /// The base URL of the API.
+    pub base_url: String,
+    /// The model ID to use.
+    pub model_id: String,
+    /// The temperature for generation.
+    pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate.
+    pub max_tokens: Option<u32>,
+    /// The top-p value for nucleus sampling.
+    pub top_p: Option<f32>,
+    /// The reasoning effort, sent only to models that accept it.
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
+impl Default for GrokConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: XAI_BASE_URL.to_string(),
+            model_id: DEFAULT_GROK_MODEL_ID.to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            top_p: None,
+            reasoning_effort: None,
+        }
+    }
+}
+
+impl GrokConfig {
+    /// Create a new Grok configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a configuration with the API key from `XAI_API_KEY`.
+    pub fn from_env() -> Self {
+        Self::default().with_api_key(&std::env::var(XAI_API_KEY_ENV).unwrap_or_default())
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Set the base URL.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the model ID.
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Set the temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the maximum tokens.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the top-p value.
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set the reasoning effort.
+    pub fn with_reasoning_effort(mut self, reasoning_effort: ReasoningEffort) -> Self {
+        self.reasoning_effort = Some(reasoning_effort);
+        self
+    }
+}
+
+/// The xAI Grok model implementation.
+pub struct GrokModel {
+    config: ModelConfig,
+    grok_config: GrokConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl std::fmt::Debug for GrokModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrokModel")
+            .field("config", &self.config)
+            .field("model_id", &self.grok_config.model_id)
+            .field("base_url", &self.grok_config.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GrokModel {
+    /// Create a new Grok model.
+    pub fn new() -> Self {
+        Self::with_config(GrokConfig::default())
+    }
+
+    /// Create a new Grok model with the given configuration.
+    pub fn with_config(grok_config: GrokConfig) -> Self {
+        let mut config = ModelConfig::new(&grok_config.model_id);
+        config.temperature = grok_config.temperature;
+        config.max_tokens = grok_config.max_tokens;
+        config.top_p = grok_config.top_p;
+        Self {
+            config,
+            grok_config,
+            client: Arc::new(UnconfiguredHttpClient::new("xAI Grok")),
+        }
+    }
+
+    /// Set the client that sends requests to the xAI API.
+    pub fn with_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Get the capabilities of the configured model, if it is in the catalog.
+    pub fn info(&self) -> Option<&'static GrokModelInfo> {
+        grok_model_info(&self.config.model_id)
+    }
+
+    /// Build a request body with Grok's quirks applied.
+    fn request_body(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        let info = self.info();
+        if tool_specs.is_some_and(|specs| !specs.is_empty()) && info.is_some_and(|info| !info.supports_tools) {
+            return Err(IndubitablyError::ModelError(ModelError::InvalidConfiguration(format!(
+                "Grok model {} does not support tool calling",
+                self.config.model_id
+            ))));
+        }
+
+        let mut body = chat_request_body(&self.config, messages, tool_specs, system_prompt);
+        for (key, value) in &self.config.extra {
+            body[key] = value.clone();
+        }
+        if let Some(effort) = self.grok_config.reasoning_effort {
+            body["reasoning_effort"] = json!(effort);
+        }
+        if let Some(fields) = body.as_object_mut() {
+            match info {
+                Some(info) if info.reasoning && !info.supports_reasoning_effort => {
+                    fields.retain(|key, _| !REASONING_UNSUPPORTED_PARAMS.contains(&key.as_str()));
+                }
+                Some(info) if !info.supports_reasoning_effort => {
+                    fields.remove("reasoning_effort");
+                }
+                _ => {}
+            }
+        }
+        Ok(body)
+    }
+
+    /// Build a chat completions request, streamed with usage when asked.
+    fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> IndubitablyResult<HttpRequest> {
+        let mut body = self.request_body(messages, tool_specs, system_prompt)?;
+        if stream {
+            body["stream"] = json!(true);
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        HttpRequest::post(&format!("{}/chat/completions", self.grok_config.base_url))
+            .with_header("authorization", &format!("Bearer {}", self.grok_config.api_key))
+            .with_json_body(&body)
+    }
+}
+
+#[async_trait]
+impl Model for GrokModel {
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.config = config;
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        &mut self.config
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, false)?;
+        let response = self.config.middleware.send(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error("xAI", &response));
+        }
+        let model_response = parse_chat_response(&response)?;
+        tracing::debug!(
+            "model_id=<{}>, tool_uses=<{}> | grok response received",
+            self.config.model_id,
+            model_response.tool_uses.len()
+        );
+        Ok(model_response)
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt, true)?;
+        let response = self.config.middleware.send_streaming(self.client.as_ref(), request).await?;
+        if !response.is_success() {
+            return Err(status_error("xAI", &response.collect().await?));
+        }
+        Ok(decode_body(response.body, ChatStreamDecoder::default()))
+    }
+
+    async fn structured_output(
+        &self,
+        _output_model: &str,
+        _messages: &Messages,
+        _system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        Err(IndubitablyError::ModelError(ModelError::InvalidResponseFormat(
+            "Grok model does not support structured output yet".to_string(),
+        )))
+    }
+}
+
+impl Default for GrokModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+    use crate::types::{Message, ToolResult, ToolResultContent, ToolUse};
+
+    /// Records request bodies and answers with a tool call.
+    #[derive(Default)]
+    struct XaiEndpoint {
+        bodies: std::sync::Mutex<Vec<serde_json::Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for XaiEndpoint {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert_eq!(request.url, "https://api.x.ai/v1/chat/completions");
+            assert_eq!(request.header("authorization"), Some("Bearer xai-key"));
+            self.bodies.lock().unwrap().push(serde_json::from_slice(&request.body).unwrap());
+            let body = json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": { "name": "search", "arguments": "{\"query\":\"rust\"}" }
+                        }]
+                    },
+                    "finish_reason": "tool_calls"
+                }],
+                "usage": { "prompt_tokens": 20, "completion_tokens": 8, "total_tokens": 28 }
+            });
+            Ok(HttpResponse::new(200, body.to_string().into_bytes()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grok_quirks_and_tool_calls() {
+        let endpoint = Arc::new(XaiEndpoint::default());
+        let tools = vec![ToolSpec::new("search", "Search the web")];
+        let messages = vec![
+            Message::user("Find Rust news"),
+            Message::assistant_with_tool_uses("", vec![ToolUse::new("search", "call_0").with_input(json!({}))]),
+            Message::tool_results(vec![ToolResult::new("call_0", vec![ToolResultContent::text("none")])]),
+        ];
+
+        let mut grok4 = GrokModel::with_config(
+            GrokConfig::new()
+                .with_api_key("xai-key")
+                .with_model_id("grok-4-0709")
+                .with_reasoning_effort(ReasoningEffort::High),
+        )
+        .with_client(endpoint.clone());
+        grok4.config_mut().extra.insert("presence_penalty".to_string(), json!(0.5));
+        let response = grok4.generate(&messages, Some(&tools), None).await.unwrap();
+        assert_eq!(response.tool_uses[0].tool_use_id, "call_1");
+        assert_eq!(response.tool_uses[0].input, Some(json!({ "query": "rust" })));
+        assert_eq!(response.usage.unwrap().total_tokens, 28);
+
+        let mini = GrokModel::with_config(
+            GrokConfig::new()
+                .with_api_key("xai-key")
+                .with_model_id("grok-3-mini")
+                .with_reasoning_effort(ReasoningEffort::Low),
+        )
+        .with_client(endpoint.clone());
+        mini.generate(&messages, None, Some("Be brief.")).await.unwrap();
+
+        let bodies = endpoint.bodies.lock().unwrap().clone();
+        assert!(bodies[0].get("presence_penalty").is_none());
+        assert!(bodies[0].get("reasoning_effort").is_none());
+        assert_eq!(bodies[0]["tools"][0]["function"]["name"], "search");
+        assert_eq!(bodies[0]["messages"][1]["tool_calls"][0]["id"], "call_0");
+        assert_eq!(bodies[0]["messages"][2]["role"], "tool");
+        assert_eq!(bodies[1]["reasoning_effort"], "low");
+        assert_eq!(bodies[1]["messages"][0]["content"], "Be brief.");
+        assert_eq!(grok_model_info("grok-3-mini-fast").unwrap().id, "grok-3-mini");
+
+        let image = GrokModel::with_config(GrokConfig::new().with_model_id("grok-2-image-1212"));
+        assert!(image.generate(&messages, Some(&tools), None).await.is_err());
+    }
+}
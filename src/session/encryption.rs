@@ -0,0 +1,361 @@
+//! Client-side encryption of session content.
+//! 
+//! This module provides `EncryptedSessionManager`, which wraps any session
+//! backend so message content is encrypted before it is stored and
+//! decrypted transparently on load. Each session gets its own random data
+//! key, wrapped by a master key fetched from a `SecretProvider` and stored
+//! in the session metadata; message content is replaced by an envelope
+//! string, so backends store it like any other text. Message metadata and
+//! session structure stay in the clear for indexing and usage reporting.
+//! 
+//! The SDK does not implement ChaCha20-Poly1305 itself; supply an
+//! implementation with `with_cipher`. Without one, sessions can be neither
+//! saved nor loaded through the manager.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::SessionManager;
+use crate::crypto::backend::{KEY_LEN, NONCE_LEN};
+use crate::crypto::{hex, Aead};
+use crate::types::{IndubitablyResult, Session, SessionError};
+
+/// The session metadata key holding the encryption envelope.
+pub const ENCRYPTION_METADATA_KEY: &str = "encryption";
+
+/// The prefix marking encrypted message content.
+pub const ENVELOPE_PREFIX: &str = "enc:v1:";
+
+/// The envelope format version.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// The algorithm used for data keys and content.
+pub const ENVELOPE_ALGORITHM: &str = "chacha20-poly1305";
+
+/// Provides secrets such as encryption keys by name.
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Get a secret by name, or `None` if it is not defined.
+    async fn get_secret(&self, name: &str) -> IndubitablyResult<Option<String>>;
+}
+
+/// Reads secrets from environment variables.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvSecretProvider;
+
+#[async_trait]
+impl SecretProvider for EnvSecretProvider {
+    async fn get_secret(&self, name: &str) -> IndubitablyResult<Option<String>> {
+        Ok(std::env::var(name).ok())
+    }
+}
+
+/// Serves secrets from an in-memory map.
+#[derive(Debug, Clone, Default)]
+pub struct StaticSecretProvider {
+    secrets: HashMap<String, String>,
+}
+
+impl StaticSecretProvider {
+    /// Create an empty provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a secret.
+    pub fn with_secret(mut self, name: &str, value: &str) -> Self {
+        self.secrets.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl SecretProvider for StaticSecretProvider {
+    async fn get_secret(&self, name: &str) -> IndubitablyResult<Option<String>> {
+        Ok(self.secrets.get(name).cloned())
+    }
+}
+
+/// The per-session key material stored in session metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionEnvelope {
+    /// The envelope format version.
+    pub version: u32,
+    /// The encryption algorithm.
+    pub algorithm: String,
+    /// The name of the master key that wraps the data key.
+    pub key_name: String,
+    /// The hex-encoded nonce and sealed data key.
+    pub wrapped_key: String,
+}
+
+/// A session manager that encrypts message content before it reaches the wrapped backend.
+pub struct EncryptedSessionManager<M: SessionManager> {
+    inner: M,
+    secrets: Arc<dyn SecretProvider>,
+    master_key_name: String,
+    cipher: Option<Arc<dyn Aead>>,
+}
+
+impl<M: SessionManager> EncryptedSessionManager<M> {
+    /// Wrap a backend, using the named secret as the hex-encoded 32-byte master key.
+    pub fn new(inner: M, secrets: Arc<dyn SecretProvider>, master_key_name: &str) -> Self {
+        Self {
+            inner,
+            secrets,
+            master_key_name: master_key_name.to_string(),
+            cipher: None,
+        }
+    }
+
+    /// Set the ChaCha20-Poly1305 implementation that seals data keys and content.
+    pub fn with_cipher(mut self, cipher: Arc<dyn Aead>) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Get the wrapped backend.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Unwrap the backend.
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+
+    fn cipher(&self) -> IndubitablyResult<&dyn Aead> {
+        self.cipher.as_deref().ok_or_else(|| {
+            let message = "No ChaCha20-Poly1305 implementation is configured; set one with `with_cipher`";
+            encryption_error(message.to_string())
+        })
+    }
+
+    async fn master_key(&self, name: &str) -> IndubitablyResult<[u8; KEY_LEN]> {
+        let secret = self
+            .secrets
+            .get_secret(name)
+            .await?
+            .ok_or_else(|| encryption_error(format!("Master key '{}' is not defined", name)))?;
+        hex::decode(secret.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| encryption_error(format!("Master key '{}' must be {} hex-encoded bytes", name, KEY_LEN)))
+    }
+
+    /// Create a data key for a session, or unwrap the one it already carries.
+    async fn data_key(&self, session: &Session) -> IndubitablyResult<([u8; KEY_LEN], SessionEnvelope)> {
+        if let Some(envelope) = envelope_of(session)? {
+            let key = self.unwrap_key(session, &envelope).await?;
+            return Ok((key, envelope));
+        }
+
+        let cipher = self.cipher()?;
+        let master = self.master_key(&self.master_key_name).await?;
+        let key: [u8; KEY_LEN] = random_bytes()?;
+        let nonce: [u8; NONCE_LEN] = random_bytes()?;
+        let mut wrapped = nonce.to_vec();
+        wrapped.extend(cipher.seal(&master, &nonce, key_aad(&session.id).as_bytes(), &key));
+        let envelope = SessionEnvelope {
+            version: ENVELOPE_VERSION,
+            algorithm: ENVELOPE_ALGORITHM.to_string(),
+            key_name: self.master_key_name.clone(),
+            wrapped_key: hex::encode(&wrapped),
+        };
+        Ok((key, envelope))
+    }
+
+    async fn unwrap_key(&self, session: &Session, envelope: &SessionEnvelope) -> IndubitablyResult<[u8; KEY_LEN]> {
+        if envelope.version != ENVELOPE_VERSION || envelope.algorithm != ENVELOPE_ALGORITHM {
+            return Err(encryption_error(format!(
+                "Unsupported envelope version {} ({})",
+                envelope.version, envelope.algorithm
+            )));
+        }
+        let cipher = self.cipher()?;
+        let master = self.master_key(&envelope.key_name).await?;
+        let wrapped = hex::decode(&envelope.wrapped_key)?;
+        let (nonce, sealed) = split_nonce(&wrapped)?;
+        cipher
+            .open(&master, &nonce, key_aad(&session.id).as_bytes(), sealed)
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| encryption_error(format!("Cannot unwrap the data key of session '{}'", session.id)))
+    }
+
+    async fn encrypt(&self, mut session: Session) -> IndubitablyResult<Session> {
+        let cipher = self.cipher()?;
+        let (key, envelope) = self.data_key(&session).await?;
+        for message in &mut session.messages {
+            let nonce: [u8; NONCE_LEN] = random_bytes()?;
+            let aad = content_aad(&session.id, &message.id);
+            let mut sealed = nonce.to_vec();
+            sealed.extend(cipher.seal(&key, &nonce, aad.as_bytes(), message.content.as_bytes()));
+            message.content = format!("{}{}", ENVELOPE_PREFIX, hex::encode(&sealed));
+        }
+        let envelope = serde_json::to_value(&envelope).map_err(|e| encryption_error(e.to_string()))?;
+        session.add_metadata(ENCRYPTION_METADATA_KEY, envelope);
+        Ok(session)
+    }
+
+    async fn decrypt(&self, mut session: Session) -> IndubitablyResult<Session> {
+        let Some(envelope) = envelope_of(&session)? else {
+            return Ok(session);
+        };
+        let cipher = self.cipher()?;
+        let key = self.unwrap_key(&session, &envelope).await?;
+        for message in &mut session.messages {
+            // Every message of an encrypted session is sealed, so plaintext was written around the encryption
+            let Some(encoded) = message.content.strip_prefix(ENVELOPE_PREFIX) else {
+                return Err(encryption_error(format!(
+                    "Message '{}' of encrypted session '{}' is not encrypted",
+                    message.id, session.id
+                )));
+            };
+            let sealed = hex::decode(encoded)?;
+            let (nonce, sealed) = split_nonce(&sealed)?;
+            let aad = content_aad(&session.id, &message.id);
+            let plaintext = cipher
+                .open(&key, &nonce, aad.as_bytes(), sealed)
+                .ok_or_else(|| encryption_error(format!("Message '{}' failed authentication", message.id)))?;
+            message.content = String::from_utf8(plaintext).map_err(|e| encryption_error(e.to_string()))?;
+        }
+        Ok(session)
+    }
+}
+
+#[async_trait]
+impl<M: SessionManager> SessionManager for EncryptedSessionManager<M> {
+    async fn create_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        let session = self.encrypt(session).await?;
+        self.inner.create_session(session).await
+    }
+
+    async fn get_session(&self, session_id: &str) -> IndubitablyResult<Option<Session>> {
+        match self.inner.get_session(session_id).await? {
+            Some(session) => Ok(Some(self.decrypt(session).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        let session = self.encrypt(session).await?;
+        self.inner.update_session(session).await
+    }
+
+    async fn delete_session(&mut self, session_id: &str) -> IndubitablyResult<()> {
+        self.inner.delete_session(session_id).await
+    }
+
+    async fn list_sessions(&self) -> IndubitablyResult<Vec<Session>> {
+        let mut sessions = Vec::new();
+        for session in self.inner.list_sessions().await? {
+            sessions.push(self.decrypt(session).await?);
+        }
+        Ok(sessions)
+    }
+
+    async fn session_exists(&self, session_id: &str) -> IndubitablyResult<bool> {
+        self.inner.session_exists(session_id).await
+    }
+}
+
+fn envelope_of(session: &Session) -> IndubitablyResult<Option<SessionEnvelope>> {
+    let Some(value) = session.metadata.as_ref().and_then(|metadata| metadata.get(ENCRYPTION_METADATA_KEY)) else {
+        return Ok(None);
+    };
+    serde_json::from_value(value.clone())
+        .map(Some)
+        .map_err(|e| encryption_error(format!("Invalid envelope on session '{}': {}", session.id, e)))
+}
+
+fn key_aad(session_id: &str) -> String {
+    format!("session-key:{}", session_id)
+}
+
+/// Bind content to its session and message so ciphertexts cannot be swapped between them.
+fn content_aad(session_id: &str, message_id: &str) -> String {
+    format!("session-message:{}:{}", session_id, message_id)
+}
+
+fn split_nonce(bytes: &[u8]) -> IndubitablyResult<([u8; NONCE_LEN], &[u8])> {
+    if bytes.len() < NONCE_LEN {
+        return Err(encryption_error("Envelope is truncated".to_string()));
+    }
+    let (nonce, rest) = bytes.split_at(NONCE_LEN);
+    let nonce = nonce.try_into().map_err(|_| encryption_error("Envelope is truncated".to_string()))?;
+    Ok((nonce, rest))
+}
+
+fn random_bytes<const N: usize>() -> IndubitablyResult<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| encryption_error(format!("Failed to generate random bytes: {}", e)))?;
+    Ok(bytes)
+}
+
+fn encryption_error(message: String) -> crate::types::IndubitablyError {
+    SessionError::EncryptionFailed(message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::backend::testing::InsecureAead;
+    use crate::session::FileSessionManager;
+    use crate::types::{SessionAgent, SessionMessage, SessionType};
+
+    const MASTER_KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[tokio::test]
+    async fn test_encrypted_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = Arc::new(StaticSecretProvider::new().with_secret("SESSION_KEY", MASTER_KEY));
+        let mut unconfigured = EncryptedSessionManager::new(
+            FileSessionManager::new(dir.path().to_str().unwrap()),
+            secrets.clone(),
+            "SESSION_KEY",
+        );
+        let mut manager = EncryptedSessionManager::new(
+            FileSessionManager::new(dir.path().to_str().unwrap()),
+            secrets,
+            "SESSION_KEY",
+        )
+        .with_cipher(Arc::new(InsecureAead));
+
+        let mut session = Session::new("s1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        session.add_message(SessionMessage::new("m1", "user", "my account number is 1234"));
+        assert!(unconfigured.create_session(session.clone()).await.is_err());
+        manager.create_session(session).await.unwrap();
+        assert!(unconfigured.get_session("s1").await.is_err());
+
+        let raw = std::fs::read_to_string(dir.path().join("s1.json")).unwrap();
+        assert!(!raw.contains("account number"));
+        let stored = manager.inner().get_session("s1").await.unwrap().unwrap();
+        assert!(stored.messages[0].content.starts_with(ENVELOPE_PREFIX));
+
+        let mut loaded = manager.get_session("s1").await.unwrap().unwrap();
+        assert_eq!(loaded.messages[0].content, "my account number is 1234");
+        loaded.add_message(SessionMessage::new("m2", "assistant", "noted"));
+        manager.update_session(loaded).await.unwrap();
+
+        let listed = manager.list_sessions().await.unwrap();
+        assert_eq!(listed[0].messages[1].content, "noted");
+        assert_eq!(envelope_of(&listed[0]).unwrap(), envelope_of(&stored).unwrap());
+
+        let wrong_key = StaticSecretProvider::new().with_secret("SESSION_KEY", &"ff".repeat(32));
+        let other = EncryptedSessionManager::new(
+            FileSessionManager::new(dir.path().to_str().unwrap()),
+            Arc::new(wrong_key),
+            "SESSION_KEY",
+        )
+        .with_cipher(Arc::new(InsecureAead));
+        assert!(other.get_session("s1").await.is_err());
+
+        let mut tampered = manager.inner().get_session("s1").await.unwrap().unwrap();
+        tampered.add_message(SessionMessage::new("m3", "assistant", "transfer approved"));
+        FileSessionManager::new(dir.path().to_str().unwrap()).update_session(tampered).await.unwrap();
+        assert!(manager.get_session("s1").await.is_err());
+    }
+}
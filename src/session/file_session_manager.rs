@@ -1,16 +1,17 @@
 //! File-based session manager for the SDK.
 //! 
 //! This module provides a file-based implementation of session
-//! management for local development and testing.
+//! management for local development and testing. Each session is
+//! stored as `<session id>.json` in the storage directory.
 
 use async_trait::async_trait;
-use std::collections::HashMap;
 use std::fs;
-use std::io::{Read, Write};
-use serde_json::Value;
+use std::io::Write;
+use std::path::PathBuf;
 
+use super::sharding::ShardRouter;
 use super::SessionManager;
-use crate::types::{Session, IndubitablyResult};
+use crate::types::{Session, SessionError, IndubitablyResult};
 
 /// A file-based session manager.
 pub struct FileSessionManager {
@@ -30,38 +31,114 @@ impl FileSessionManager {
     pub fn default() -> Self {
         Self::new("./sessions")
     }
+
+    /// Get the path of a session file, rejecting ids that would escape the storage directory.
+    fn session_path(&self, session_id: &str) -> IndubitablyResult<PathBuf> {
+        let valid = !session_id.is_empty()
+            && session_id != "."
+            && session_id != ".."
+            && !session_id.contains(['/', '\\']);
+        if !valid {
+            return Err(SessionError::StorageFailed(format!("Invalid session id '{}'", session_id)).into());
+        }
+        Ok(PathBuf::from(&self.storage_directory).join(format!("{}.json", session_id)))
+    }
+
+    /// Write a session to disk, replacing the file atomically.
+    fn write_session(&self, session: &Session) -> IndubitablyResult<()> {
+        let path = self.session_path(&session.id)?;
+        let storage_error = |e: std::io::Error| SessionError::StorageFailed(format!("{}: {}", path.display(), e));
+        fs::create_dir_all(&self.storage_directory).map_err(storage_error)?;
+
+        let json = serde_json::to_vec_pretty(session)
+            .map_err(|e| SessionError::StorageFailed(format!("Failed to serialize session: {}", e)))?;
+        let temp_path = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&temp_path).map_err(storage_error)?;
+        file.write_all(&json).map_err(storage_error)?;
+        file.sync_all().map_err(storage_error)?;
+        fs::rename(&temp_path, &path).map_err(storage_error)?;
+        Ok(())
+    }
+
+    /// Read a session from disk, if it exists.
+    fn read_session(&self, path: &PathBuf) -> IndubitablyResult<Option<Session>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(SessionError::StorageFailed(format!("{}: {}", path.display(), e)).into()),
+        };
+        let session = serde_json::from_slice(&bytes)
+            .map_err(|e| SessionError::StorageFailed(format!("Invalid session file {}: {}", path.display(), e)))?;
+        Ok(Some(session))
+    }
+
+    /// Read the sessions whose IDs match a filter, oldest first, skipping the others unread.
+    fn list_matching(&self, filter: impl Fn(&str) -> bool) -> IndubitablyResult<Vec<Session>> {
+        let entries = match fs::read_dir(&self.storage_directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(SessionError::StorageFailed(format!("{}: {}", self.storage_directory, e)).into()),
+        };
+
+        let mut sessions = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| SessionError::StorageFailed(e.to_string()))?
+                .path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+            if !path.file_stem().and_then(|stem| stem.to_str()).is_some_and(&filter) {
+                continue;
+            }
+            if let Some(session) = self.read_session(&path)? {
+                sessions.push(session);
+            }
+        }
+        sessions.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(sessions)
+    }
 }
 
 #[async_trait]
 impl SessionManager for FileSessionManager {
-    async fn create_session(&mut self, _session: Session) -> IndubitablyResult<()> {
-        // TODO: Implement file-based session creation
-        Ok(())
+    async fn create_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        if self.session_path(&session.id)?.exists() {
+            return Err(SessionError::CreationFailed(format!("Session '{}' already exists", session.id)).into());
+        }
+        self.write_session(&session)
     }
     
-    async fn get_session(&self, _session_id: &str) -> IndubitablyResult<Option<Session>> {
-        // TODO: Implement file-based session retrieval
-        Ok(None)
+    async fn get_session(&self, session_id: &str) -> IndubitablyResult<Option<Session>> {
+        let path = self.session_path(session_id)?;
+        self.read_session(&path)
     }
     
-    async fn update_session(&mut self, _session: Session) -> IndubitablyResult<()> {
-        // TODO: Implement file-based session update
-        Ok(())
+    async fn update_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        if !self.session_path(&session.id)?.exists() {
+            return Err(SessionError::SessionNotFound(session.id).into());
+        }
+        self.write_session(&session)
     }
     
-    async fn delete_session(&mut self, _session_id: &str) -> IndubitablyResult<()> {
-        // TODO: Implement file-based session deletion
-        Ok(())
+    async fn delete_session(&mut self, session_id: &str) -> IndubitablyResult<()> {
+        match fs::remove_file(self.session_path(session_id)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(SessionError::DeletionFailed(format!("{}: {}", session_id, e)).into()),
+        }
     }
     
     async fn list_sessions(&self) -> IndubitablyResult<Vec<Session>> {
-        // TODO: Implement file-based session listing
-        Ok(Vec::new())
+        self.list_matching(|_| true)
     }
     
-    async fn session_exists(&self, _session_id: &str) -> IndubitablyResult<bool> {
-        // TODO: Implement file-based session existence check
-        Ok(false)
+    async fn session_exists(&self, session_id: &str) -> IndubitablyResult<bool> {
+        Ok(self.session_path(session_id)?.exists())
+    }
+
+    async fn list_shard_sessions(&self, router: &ShardRouter, shard: &str) -> IndubitablyResult<Vec<Session>> {
+        self.list_matching(|session_id| router.shard_for(session_id) == shard)
     }
 }
 
@@ -70,3 +147,30 @@ impl Default for FileSessionManager {
         Self::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SessionAgent, SessionMessage, SessionType};
+
+    #[tokio::test]
+    async fn test_file_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = FileSessionManager::new(dir.path().to_str().unwrap());
+
+        let mut session = Session::new("session-1", SessionType::Conversation, SessionAgent::new("agent-1", "Agent"));
+        manager.create_session(session.clone()).await.unwrap();
+        assert!(manager.create_session(session.clone()).await.is_err());
+        assert!(manager.session_exists("session-1").await.unwrap());
+
+        session.add_message(SessionMessage::new("m1", "user", "Hello"));
+        manager.update_session(session).await.unwrap();
+        let stored = manager.get_session("session-1").await.unwrap().unwrap();
+        assert_eq!(stored.message_count(), 1);
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 1);
+
+        assert!(manager.get_session("../escape").await.is_err());
+        manager.delete_session("session-1").await.unwrap();
+        assert!(manager.get_session("session-1").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! Retention janitor for session backends.
+//! 
+//! This module provides `RetentionJanitor`, which removes messages whose
+//! TTL has passed from persisted sessions. Messages get a TTL with
+//! `Message::with_ttl`, and `SessionMessage::from_message` carries it into
+//! the session. The janitor either deletes expired messages or replaces
+//! their content with a placeholder, keeping the message ids that feedback
+//! and sync cursors refer to. It can sweep on demand or run as a background
+//! task on an interval.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use super::SessionManager;
+use crate::types::{IndubitablyResult, EXPIRED_CONTENT_PLACEHOLDER, MESSAGE_EXPIRED_AT_KEY, MESSAGE_EXPIRES_AT_KEY};
+
+/// The default time between background sweeps.
+pub const DEFAULT_SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+/// What the janitor does with an expired message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryAction {
+    /// Remove the message from its session.
+    Delete,
+    /// Replace the message's content with a placeholder, keeping its id and role.
+    Redact,
+}
+
+/// The outcome of one sweep.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SweepReport {
+    /// The number of sessions checked.
+    pub sessions_scanned: usize,
+    /// The number of sessions written back.
+    pub sessions_updated: usize,
+    /// The number of messages deleted.
+    pub messages_deleted: usize,
+    /// The number of messages redacted.
+    pub messages_redacted: usize,
+}
+
+/// Removes expired messages from a session backend.
+pub struct RetentionJanitor {
+    session_manager: Arc<Mutex<Box<dyn SessionManager>>>,
+    action: ExpiryAction,
+    interval: Duration,
+}
+
+impl std::fmt::Debug for RetentionJanitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetentionJanitor")
+            .field("action", &self.action)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl RetentionJanitor {
+    /// Create a janitor that redacts expired messages every `DEFAULT_SWEEP_INTERVAL`.
+    pub fn new(session_manager: Arc<Mutex<Box<dyn SessionManager>>>) -> Self {
+        Self {
+            session_manager,
+            action: ExpiryAction::Redact,
+            interval: DEFAULT_SWEEP_INTERVAL,
+        }
+    }
+
+    /// Set what is done with expired messages.
+    pub fn with_action(mut self, action: ExpiryAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Set the time between background sweeps.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Remove the messages expired by now from every session.
+    pub async fn sweep(&self) -> IndubitablyResult<SweepReport> {
+        self.sweep_at(Utc::now()).await
+    }
+
+    /// Remove the messages expired by the given time from every session.
+    pub async fn sweep_at(&self, now: DateTime<Utc>) -> IndubitablyResult<SweepReport> {
+        let mut manager = self.session_manager.lock().await;
+        let mut report = SweepReport::default();
+        for mut session in manager.list_sessions().await? {
+            report.sessions_scanned += 1;
+            let expired = |message: &crate::types::SessionMessage| message.expires_at().is_some_and(|at| at <= now);
+            let count = session.messages.iter().filter(|message| expired(message)).count();
+            if count == 0 {
+                continue;
+            }
+            match self.action {
+                ExpiryAction::Delete => {
+                    session.messages.retain(|message| !expired(message));
+                    report.messages_deleted += count;
+                }
+                ExpiryAction::Redact => {
+                    for message in session.messages.iter_mut().filter(|message| expired(message)) {
+                        message.content = EXPIRED_CONTENT_PLACEHOLDER.to_string();
+                        if let Some(ref mut metadata) = message.metadata {
+                            metadata.remove(MESSAGE_EXPIRES_AT_KEY);
+                        }
+                        message.add_metadata(MESSAGE_EXPIRED_AT_KEY, Value::String(now.to_rfc3339()));
+                    }
+                    report.messages_redacted += count;
+                }
+            }
+            session.updated_at = Utc::now();
+            tracing::debug!("session_id=<{}>, count=<{}> | removed expired messages", session.id, count);
+            manager.update_session(session).await?;
+            report.sessions_updated += 1;
+        }
+        Ok(report)
+    }
+
+    /// Sweep in the background on the configured interval until the handle is aborted.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            loop {
+                ticker.tick().await;
+                match self.sweep().await {
+                    Ok(report) if report.sessions_updated > 0 => tracing::info!(
+                        "deleted=<{}>, redacted=<{}> | retention janitor removed expired messages",
+                        report.messages_deleted,
+                        report.messages_redacted
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("error=<{}> | retention janitor sweep failed", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::FileSessionManager;
+    use crate::types::{Message, Session, SessionAgent, SessionMessage, SessionType};
+
+    #[tokio::test]
+    async fn test_janitor_redacts_or_deletes_expired_messages() {
+        let directory = tempfile::tempdir().unwrap();
+        let manager: Box<dyn SessionManager> = Box::new(FileSessionManager::new(directory.path().to_str().unwrap()));
+        let manager = Arc::new(Mutex::new(manager));
+        let now = Utc::now();
+        let mut session = Session::new("s1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        let upload = Message::user("Contract text").with_expires_at(now - chrono::Duration::minutes(5));
+        session.add_message(SessionMessage::from_message("m1", &upload));
+        session.add_message(SessionMessage::new("m2", "assistant", "Summary"));
+        manager.lock().await.create_session(session).await.unwrap();
+
+        let janitor = RetentionJanitor::new(manager.clone());
+        let report = janitor.sweep_at(now).await.unwrap();
+        assert_eq!(report.messages_redacted, 1);
+        let stored = manager.lock().await.get_session("s1").await.unwrap().unwrap();
+        assert_eq!(stored.messages[0].content, EXPIRED_CONTENT_PLACEHOLDER);
+        assert!(stored.messages[0].expires_at().is_none());
+        assert_eq!(janitor.sweep_at(now).await.unwrap().sessions_updated, 0);
+
+        let mut session = stored;
+        session.add_message(SessionMessage::from_message("m3", &Message::user("PIN 1234").with_expires_at(now)));
+        manager.lock().await.update_session(session).await.unwrap();
+        let report = janitor.with_action(ExpiryAction::Delete).sweep_at(now).await.unwrap();
+        assert_eq!(report.messages_deleted, 1);
+        let stored = manager.lock().await.get_session("s1").await.unwrap().unwrap();
+        assert_eq!(stored.messages.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), vec!["m1", "m2"]);
+    }
+}
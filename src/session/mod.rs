@@ -6,7 +6,19 @@
 pub mod session_manager;
 pub mod file_session_manager;
 pub mod repository_session_manager;
+pub mod encryption;
+pub mod write_buffer;
+pub mod user_memory;
+pub mod sharding;
+pub mod sync;
+pub mod janitor;
 
 pub use session_manager::SessionManager;
 pub use file_session_manager::FileSessionManager;
 pub use repository_session_manager::RepositorySessionManager;
+pub use encryption::{EncryptedSessionManager, EnvSecretProvider, SecretProvider, StaticSecretProvider};
+pub use write_buffer::{BufferedSessionManager, WriteStats};
+pub use user_memory::{FileUserMemoryStore, InMemoryUserMemoryStore, MemoryEntry, UserMemory, UserMemoryStore};
+pub use sharding::{ShardMove, ShardRouter, ShardedSessionManager};
+pub use sync::{apply_changes, SessionChanges, SessionCursor};
+pub use janitor::{ExpiryAction, RetentionJanitor, SweepReport, DEFAULT_SWEEP_INTERVAL};
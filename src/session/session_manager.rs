@@ -5,7 +5,9 @@
 
 use async_trait::async_trait;
 
-use crate::types::{Session, IndubitablyResult};
+use super::sharding::ShardRouter;
+use super::sync::{SessionChanges, SessionCursor};
+use crate::types::{Feedback, FeedbackRating, IndubitablyError, IndubitablyResult, Session, SessionError};
 
 /// A trait for managing sessions.
 #[async_trait]
@@ -27,4 +29,65 @@ pub trait SessionManager: Send + Sync {
     
     /// Check if a session exists.
     async fn session_exists(&self, session_id: &str) -> IndubitablyResult<bool>;
+
+    /// List the sessions the router assigns to a shard.
+    ///
+    /// Backends that can select a shard without loading every session should override this.
+    async fn list_shard_sessions(&self, router: &ShardRouter, shard: &str) -> IndubitablyResult<Vec<Session>> {
+        let sessions = self.list_sessions().await?;
+        Ok(sessions.into_iter().filter(|session| router.shard_for(&session.id) == shard).collect())
+    }
+
+    /// Rate a message of a session, with an optional comment.
+    async fn add_feedback(
+        &mut self,
+        session_id: &str,
+        message_id: &str,
+        rating: FeedbackRating,
+        comment: Option<&str>,
+    ) -> IndubitablyResult<Feedback> {
+        let mut feedback = Feedback::new(session_id, message_id, rating);
+        feedback.comment = comment.map(str::to_string);
+        self.record_feedback(feedback).await
+    }
+
+    /// Record a feedback record, such as one carrying a correction, on its session.
+    async fn record_feedback(&mut self, feedback: Feedback) -> IndubitablyResult<Feedback> {
+        let mut session = self
+            .get_session(&feedback.session_id)
+            .await?
+            .ok_or_else(|| IndubitablyError::SessionError(SessionError::SessionNotFound(feedback.session_id.clone())))?;
+        if !session.messages.iter().any(|message| message.id == feedback.message_id) {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Message '{}' not found in session '{}'",
+                feedback.message_id, feedback.session_id
+            )));
+        }
+        session.add_feedback(feedback.clone());
+        self.update_session(session).await?;
+        Ok(feedback)
+    }
+
+    /// List the feedback recorded on a session, oldest first.
+    async fn list_feedback(&self, session_id: &str) -> IndubitablyResult<Vec<Feedback>> {
+        self.get_session(session_id)
+            .await?
+            .map(|session| session.feedback())
+            .ok_or_else(|| IndubitablyError::SessionError(SessionError::SessionNotFound(session_id.to_string())))
+    }
+
+    /// Get the messages and metadata changes of a session since a cursor from an earlier sync.
+    ///
+    /// Without a cursor, or when the history before the cursor was rewritten,
+    /// the changes are a full snapshot with `reset` set.
+    async fn changes_since(
+        &self,
+        session_id: &str,
+        cursor: Option<&SessionCursor>,
+    ) -> IndubitablyResult<SessionChanges> {
+        self.get_session(session_id)
+            .await?
+            .map(|session| SessionChanges::since(&session, cursor))
+            .ok_or_else(|| IndubitablyError::SessionError(SessionError::SessionNotFound(session_id.to_string())))
+    }
 }
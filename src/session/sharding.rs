@@ -0,0 +1,273 @@
+//! Session sharding across storage backends.
+//! 
+//! This module provides `ShardRouter`, which assigns each session ID to one
+//! of a set of named shards by consistent hashing, and
+//! `ShardedSessionManager`, which routes every `SessionManager` call to the
+//! backend of the session's shard so large deployments can split session
+//! storage across databases without changing how agents use sessions. Each
+//! shard is placed on a hash ring many times, so adding or removing a shard
+//! moves only about its share of sessions; `ShardRouter::relocations` lists
+//! which sessions must be copied before a new layout goes live.
+
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+
+use super::SessionManager;
+use crate::crypto::sha256;
+use crate::types::{IndubitablyError, IndubitablyResult, Session, SessionError};
+
+/// The default number of points each shard has on the hash ring.
+pub const DEFAULT_VIRTUAL_NODES: usize = 128;
+
+/// Hash a key to a point on the ring.
+fn ring_point(key: &str) -> u64 {
+    let digest = sha256(key.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// A session that moves to another shard when the shard layout changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardMove {
+    /// The session ID.
+    pub session_id: String,
+    /// The shard holding the session under the current layout.
+    pub from: String,
+    /// The shard holding the session under the new layout.
+    pub to: String,
+}
+
+/// Assigns session IDs to named shards by consistent hashing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardRouter {
+    shards: Vec<String>,
+    virtual_nodes: usize,
+    ring: BTreeMap<u64, usize>,
+}
+
+impl ShardRouter {
+    /// Create a router over the given shards, rejecting an empty or duplicated list.
+    pub fn new<I, S>(shards: I) -> IndubitablyResult<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let shards: Vec<String> = shards.into_iter().map(Into::into).collect();
+        if shards.is_empty() {
+            return Err(IndubitablyError::ValidationError("A shard router needs at least one shard".to_string()));
+        }
+        for (index, shard) in shards.iter().enumerate() {
+            if shards[..index].contains(shard) {
+                return Err(IndubitablyError::ValidationError(format!("Duplicate shard '{}'", shard)));
+            }
+        }
+        let mut router = Self {
+            shards,
+            virtual_nodes: DEFAULT_VIRTUAL_NODES,
+            ring: BTreeMap::new(),
+        };
+        router.build_ring();
+        Ok(router)
+    }
+
+    /// Set the number of points each shard has on the ring; more points spread sessions more evenly.
+    pub fn with_virtual_nodes(mut self, virtual_nodes: usize) -> Self {
+        self.virtual_nodes = virtual_nodes.max(1);
+        self.build_ring();
+        self
+    }
+
+    /// Add a shard, such as when scaling out; existing shards are kept.
+    pub fn with_shard(mut self, shard: &str) -> Self {
+        if !self.shards.iter().any(|existing| existing == shard) {
+            self.shards.push(shard.to_string());
+            self.build_ring();
+        }
+        self
+    }
+
+    fn build_ring(&mut self) {
+        self.ring.clear();
+        for (index, shard) in self.shards.iter().enumerate() {
+            for replica in 0..self.virtual_nodes {
+                self.ring.entry(ring_point(&format!("{}#{}", shard, replica))).or_insert(index);
+            }
+        }
+    }
+
+    /// Get the shard names, in the order they were added.
+    pub fn shards(&self) -> &[String] {
+        &self.shards
+    }
+
+    /// Get the shard holding a session.
+    pub fn shard_for(&self, session_id: &str) -> &str {
+        let point = ring_point(session_id);
+        let index = self
+            .ring
+            .range(point..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, index)| *index)
+            .unwrap_or_default();
+        &self.shards[index]
+    }
+
+    /// List the sessions that change shard when moving to a new layout.
+    pub fn relocations<'a, I>(&self, next: &ShardRouter, session_ids: I) -> Vec<ShardMove>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        session_ids
+            .into_iter()
+            .filter_map(|session_id| {
+                let (from, to) = (self.shard_for(session_id), next.shard_for(session_id));
+                (from != to).then(|| ShardMove {
+                    session_id: session_id.to_string(),
+                    from: from.to_string(),
+                    to: to.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A session manager routing each session to the backend of its shard.
+pub struct ShardedSessionManager<M: SessionManager> {
+    router: ShardRouter,
+    backends: HashMap<String, M>,
+}
+
+impl<M: SessionManager> ShardedSessionManager<M> {
+    /// Create a manager from a router and a backend for each of its shards.
+    pub fn new(router: ShardRouter, backends: HashMap<String, M>) -> IndubitablyResult<Self> {
+        if let Some(missing) = router.shards().iter().find(|shard| !backends.contains_key(*shard)) {
+            return Err(IndubitablyError::ValidationError(format!("No backend for shard '{}'", missing)));
+        }
+        if let Some(unknown) = backends.keys().find(|shard| !router.shards().contains(shard)) {
+            return Err(IndubitablyError::ValidationError(format!("Backend for unknown shard '{}'", unknown)));
+        }
+        Ok(Self { router, backends })
+    }
+
+    /// Get the router.
+    pub fn router(&self) -> &ShardRouter {
+        &self.router
+    }
+
+    /// Get the backend of a shard.
+    pub fn backend(&self, shard: &str) -> Option<&M> {
+        self.backends.get(shard)
+    }
+
+    fn backend_for(&self, session_id: &str) -> IndubitablyResult<&M> {
+        let shard = self.router.shard_for(session_id);
+        self.backends
+            .get(shard)
+            .ok_or_else(|| SessionError::StorageFailed(format!("No backend for shard '{}'", shard)).into())
+    }
+
+    fn backend_for_mut(&mut self, session_id: &str) -> IndubitablyResult<&mut M> {
+        let shard = self.router.shard_for(session_id);
+        self.backends
+            .get_mut(shard)
+            .ok_or_else(|| SessionError::StorageFailed(format!("No backend for shard '{}'", shard)).into())
+    }
+}
+
+#[async_trait]
+impl<M: SessionManager> SessionManager for ShardedSessionManager<M> {
+    async fn create_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        self.backend_for_mut(&session.id)?.create_session(session).await
+    }
+
+    async fn get_session(&self, session_id: &str) -> IndubitablyResult<Option<Session>> {
+        self.backend_for(session_id)?.get_session(session_id).await
+    }
+
+    async fn update_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        self.backend_for_mut(&session.id)?.update_session(session).await
+    }
+
+    async fn delete_session(&mut self, session_id: &str) -> IndubitablyResult<()> {
+        self.backend_for_mut(session_id)?.delete_session(session_id).await
+    }
+
+    async fn list_sessions(&self) -> IndubitablyResult<Vec<Session>> {
+        let mut sessions = Vec::new();
+        for shard in self.router.shards() {
+            if let Some(backend) = self.backends.get(shard) {
+                sessions.extend(backend.list_sessions().await?);
+            }
+        }
+        sessions.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(sessions)
+    }
+
+    async fn session_exists(&self, session_id: &str) -> IndubitablyResult<bool> {
+        self.backend_for(session_id)?.session_exists(session_id).await
+    }
+
+    async fn list_shard_sessions(&self, router: &ShardRouter, shard: &str) -> IndubitablyResult<Vec<Session>> {
+        match self.backends.get(shard) {
+            Some(backend) if *router == self.router => backend.list_sessions().await,
+            _ => {
+                let sessions = self.list_sessions().await?;
+                Ok(sessions.into_iter().filter(|session| router.shard_for(&session.id) == shard).collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::FileSessionManager;
+    use crate::types::{SessionAgent, SessionType};
+
+    #[tokio::test]
+    async fn test_sessions_are_routed_to_their_shard() {
+        let router = ShardRouter::new(["east", "west"]).unwrap();
+        assert!(ShardRouter::new(Vec::<String>::new()).is_err());
+        assert!(ShardRouter::new(["east", "east"]).is_err());
+
+        let ids: Vec<String> = (0..200).map(|i| format!("session-{}", i)).collect();
+        let east = ids.iter().filter(|id| router.shard_for(id) == "east").count();
+        assert!(east > 50 && east < 150, "uneven split: {}", east);
+
+        // Adding a shard only moves sessions onto it
+        let scaled = router.clone().with_shard("central");
+        let moves = router.relocations(&scaled, ids.iter().map(String::as_str));
+        assert!(!moves.is_empty() && moves.len() < 120);
+        assert!(moves.iter().all(|moved| moved.to == "central"));
+
+        let dirs: Vec<_> = (0..2).map(|_| tempfile::tempdir().unwrap()).collect();
+        let backends = HashMap::from([
+            ("east".to_string(), FileSessionManager::new(dirs[0].path().to_str().unwrap())),
+            ("west".to_string(), FileSessionManager::new(dirs[1].path().to_str().unwrap())),
+        ]);
+        let mut manager = ShardedSessionManager::new(router.clone(), backends).unwrap();
+        for id in &ids[..10] {
+            let session = Session::new(id, SessionType::Conversation, SessionAgent::new("a", "Agent"));
+            manager.create_session(session).await.unwrap();
+        }
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 10);
+        for id in &ids[..10] {
+            let shard = router.shard_for(id);
+            assert!(manager.backend(shard).unwrap().session_exists(id).await.unwrap());
+            assert!(manager.get_session(id).await.unwrap().is_some());
+        }
+
+        let east_sessions = manager.list_shard_sessions(&router, "east").await.unwrap();
+        let file_east = manager.backend("east").unwrap().list_shard_sessions(&router, "east").await.unwrap();
+        let ids_of = |sessions: &[Session]| sessions.iter().map(|session| session.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids_of(&east_sessions), ids_of(&file_east));
+        assert!(east_sessions.iter().all(|session| router.shard_for(&session.id) == "east"));
+        assert_eq!(
+            manager.list_shard_sessions(&router, "west").await.unwrap().len(),
+            10 - east_sessions.len()
+        );
+    }
+}
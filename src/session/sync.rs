@@ -0,0 +1,223 @@
+//! Incremental session sync for the SDK.
+//! 
+//! This module lets chat UIs and mobile clients keep a local copy of a
+//! session up to date without fetching the full transcript each time. A
+//! client passes the `SessionCursor` from its last sync to
+//! `SessionManager::changes_since` and receives only the messages added since
+//! then, the session metadata if it changed, and a new cursor. The cursor
+//! fingerprints the history the client already has, so if that history was
+//! rewritten, for example by trimming or redaction, the client gets a full
+//! snapshot flagged with `reset` instead of a delta that would not apply.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::crypto::{hex, Sha256};
+use crate::types::{IndubitablyError, IndubitablyResult, Session, SessionMessage};
+
+/// The number of hex digits kept from each fingerprint in a cursor.
+const FINGERPRINT_LEN: usize = 16;
+
+/// An opaque position in a session's history, returned by each sync.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SessionCursor {
+    message_count: usize,
+    messages_fingerprint: String,
+    state_fingerprint: String,
+}
+
+impl SessionCursor {
+    /// Get the cursor at the current end of a session.
+    pub fn of(session: &Session) -> Self {
+        Self {
+            message_count: session.messages.len(),
+            messages_fingerprint: messages_fingerprint(&session.messages),
+            state_fingerprint: state_fingerprint(session),
+        }
+    }
+
+    /// Get the number of messages the client has.
+    pub fn message_count(&self) -> usize {
+        self.message_count
+    }
+}
+
+impl fmt::Display for SessionCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.message_count, self.messages_fingerprint, self.state_fingerprint)
+    }
+}
+
+impl FromStr for SessionCursor {
+    type Err = IndubitablyError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || IndubitablyError::ValidationError(format!("Invalid session cursor '{}'", text));
+        let mut parts = text.split('.');
+        let (Some(count), Some(messages), Some(state), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let is_fingerprint = |part: &str| part.len() == FINGERPRINT_LEN && part.chars().all(|c| c.is_ascii_hexdigit());
+        if !is_fingerprint(messages) || !is_fingerprint(state) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            message_count: count.parse().map_err(|_| invalid())?,
+            messages_fingerprint: messages.to_string(),
+            state_fingerprint: state.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for SessionCursor {
+    type Error = IndubitablyError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<SessionCursor> for String {
+    fn from(cursor: SessionCursor) -> Self {
+        cursor.to_string()
+    }
+}
+
+/// The changes to a session since a cursor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionChanges {
+    /// The session ID.
+    pub session_id: String,
+    /// The cursor to pass to the next sync.
+    pub cursor: SessionCursor,
+    /// Whether the client must replace its copy instead of applying the changes.
+    pub reset: bool,
+    /// The messages added since the cursor, or every message on a reset.
+    pub messages: Vec<SessionMessage>,
+    /// The session metadata, if it changed since the cursor or on a reset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, Value>>,
+    /// When the session was last updated.
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SessionChanges {
+    /// Compute the changes to a session since a cursor, or a full snapshot without one.
+    pub fn since(session: &Session, cursor: Option<&SessionCursor>) -> Self {
+        let current = SessionCursor::of(session);
+        let in_history = cursor.filter(|cursor| {
+            cursor.message_count <= session.messages.len()
+                && messages_fingerprint(&session.messages[..cursor.message_count]) == cursor.messages_fingerprint
+        });
+        let (reset, first_new, state_changed) = match in_history {
+            Some(cursor) => (false, cursor.message_count, cursor.state_fingerprint != current.state_fingerprint),
+            None => (true, 0, true),
+        };
+        if reset && cursor.is_some() {
+            tracing::debug!("session_id=<{}> | session history changed since cursor, sending snapshot", session.id);
+        }
+        Self {
+            session_id: session.id.clone(),
+            cursor: current,
+            reset,
+            messages: session.messages[first_new..].to_vec(),
+            metadata: if state_changed { Some(session.metadata.clone().unwrap_or_default()) } else { None },
+            updated_at: session.updated_at,
+        }
+    }
+
+    /// Check whether nothing changed since the cursor.
+    pub fn is_empty(&self) -> bool {
+        !self.reset && self.messages.is_empty() && self.metadata.is_none()
+    }
+}
+
+/// Apply changes to a client's copy of a session.
+pub fn apply_changes(session: &mut Session, changes: SessionChanges) -> IndubitablyResult<()> {
+    if session.id != changes.session_id {
+        return Err(IndubitablyError::ValidationError(format!(
+            "Changes for session '{}' cannot be applied to session '{}'",
+            changes.session_id, session.id
+        )));
+    }
+    if changes.reset {
+        session.messages.clear();
+    }
+    session.messages.extend(changes.messages);
+    if let Some(metadata) = changes.metadata {
+        session.metadata = (!metadata.is_empty()).then_some(metadata);
+    }
+    session.updated_at = changes.updated_at;
+    Ok(())
+}
+
+fn fingerprint(hasher: Sha256) -> String {
+    hex::encode(&hasher.finalize())[..FINGERPRINT_LEN].to_string()
+}
+
+fn messages_fingerprint(messages: &[SessionMessage]) -> String {
+    let mut hasher = Sha256::new();
+    for message in messages {
+        hasher.update(&serde_json::to_vec(message).unwrap_or_default());
+        hasher.update(b"\n");
+    }
+    fingerprint(hasher)
+}
+
+fn state_fingerprint(session: &Session) -> String {
+    // Sort the keys so the fingerprint does not depend on map iteration order
+    let state: std::collections::BTreeMap<_, _> = session.metadata.iter().flatten().collect();
+    let mut hasher = Sha256::new();
+    hasher.update(&serde_json::to_vec(&state).unwrap_or_default());
+    fingerprint(hasher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SessionAgent, SessionType};
+
+    #[test]
+    fn test_changes_since_returns_deltas_and_resets_on_rewrite() {
+        let mut server = Session::new("s1", SessionType::Conversation, SessionAgent::new("a1", "Agent"));
+        server.add_message(SessionMessage::new("m1", "user", "Hello"));
+        let mut client = server.clone();
+        client.messages.clear();
+
+        let first = SessionChanges::since(&server, None);
+        assert!(first.reset);
+        let cursor: SessionCursor = first.cursor.to_string().parse().unwrap();
+        apply_changes(&mut client, first).unwrap();
+
+        server.add_message(SessionMessage::new("m2", "assistant", "Hi"));
+        let delta = SessionChanges::since(&server, Some(&cursor));
+        assert!(!delta.reset);
+        assert!(delta.metadata.is_none());
+        assert_eq!(delta.messages.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), vec!["m2"]);
+        let cursor = delta.cursor.clone();
+        apply_changes(&mut client, delta).unwrap();
+        assert_eq!(client.messages.len(), 2);
+        assert!(SessionChanges::since(&server, Some(&cursor)).is_empty());
+
+        server.add_metadata("title", serde_json::json!("Greetings"));
+        let delta = SessionChanges::since(&server, Some(&cursor));
+        assert!(delta.messages.is_empty());
+        assert_eq!(delta.metadata.unwrap()["title"], "Greetings");
+
+        server.messages.remove(0);
+        let delta = SessionChanges::since(&server, Some(&cursor));
+        assert!(delta.reset);
+        apply_changes(&mut client, delta).unwrap();
+        assert_eq!(client.messages.len(), 1);
+
+        assert!("3.nothex.0000000000000000".parse::<SessionCursor>().is_err());
+    }
+}
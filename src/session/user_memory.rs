@@ -0,0 +1,407 @@
+//! Cross-session user memory for the SDK.
+//! 
+//! This module provides `UserMemory`, a store of durable facts and
+//! preferences keyed by user id. Unlike session history, entries outlive
+//! the conversation they were learned in: after each turn an extraction
+//! prompt asks the model for new facts worth remembering, and later
+//! sessions for the same user start with a compact profile of them in the
+//! system prompt. Entries can be listed, edited and deleted, including all
+//! of a user's entries at once, so users can see and erase what is kept
+//! about them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Model;
+use crate::types::{IndubitablyResult, Message, MessageRole, SessionError};
+
+/// The default prompt asking the model for durable facts from a turn.
+pub const DEFAULT_EXTRACTION_PROMPT: &str = "You maintain long-term memory about a user. From the conversation \
+below, list new durable facts about the user or their preferences that would help in future conversations, \
+one per line starting with \"- \". Skip anything already known, temporary or about the assistant. If there is \
+nothing new, reply with NONE.";
+
+/// The default maximum number of entries included in a profile.
+pub const DEFAULT_MAX_PROFILE_ENTRIES: usize = 20;
+
+/// A durable fact or preference remembered about a user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    /// The entry identifier.
+    pub id: String,
+    /// The user the entry is about.
+    pub user_id: String,
+    /// The fact or preference.
+    pub content: String,
+    /// The session the entry was learned in, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_session_id: Option<String>,
+    /// When the entry was created.
+    pub created_at: DateTime<Utc>,
+    /// When the entry was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MemoryEntry {
+    /// Create an entry with a fresh id.
+    pub fn new(user_id: &str, content: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            content: content.to_string(),
+            source_session_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Record the session the entry was learned in.
+    pub fn with_source_session_id(mut self, session_id: &str) -> Self {
+        self.source_session_id = Some(session_id.to_string());
+        self
+    }
+}
+
+/// A backend persisting user memory entries.
+#[async_trait]
+pub trait UserMemoryStore: Send + Sync {
+    /// List a user's entries, oldest first.
+    async fn list(&self, user_id: &str) -> IndubitablyResult<Vec<MemoryEntry>>;
+
+    /// Insert an entry or replace the one with the same id.
+    async fn upsert(&self, entry: MemoryEntry) -> IndubitablyResult<()>;
+
+    /// Delete an entry, returning whether it existed.
+    async fn delete(&self, user_id: &str, entry_id: &str) -> IndubitablyResult<bool>;
+
+    /// Delete all of a user's entries, returning how many there were.
+    async fn delete_user(&self, user_id: &str) -> IndubitablyResult<usize>;
+}
+
+/// A user memory store kept in process memory.
+#[derive(Debug, Default)]
+pub struct InMemoryUserMemoryStore {
+    entries: Mutex<HashMap<String, Vec<MemoryEntry>>>,
+}
+
+impl InMemoryUserMemoryStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UserMemoryStore for InMemoryUserMemoryStore {
+    async fn list(&self, user_id: &str) -> IndubitablyResult<Vec<MemoryEntry>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.get(user_id).cloned().unwrap_or_default())
+    }
+
+    async fn upsert(&self, entry: MemoryEntry) -> IndubitablyResult<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        upsert_entry(entries.entry(entry.user_id.clone()).or_default(), entry);
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: &str, entry_id: &str) -> IndubitablyResult<bool> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(user_entries) = entries.get_mut(user_id) else {
+            return Ok(false);
+        };
+        let before = user_entries.len();
+        user_entries.retain(|entry| entry.id != entry_id);
+        Ok(user_entries.len() < before)
+    }
+
+    async fn delete_user(&self, user_id: &str) -> IndubitablyResult<usize> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.remove(user_id).map_or(0, |user_entries| user_entries.len()))
+    }
+}
+
+/// Replace the entry with the same id, or append it.
+fn upsert_entry(entries: &mut Vec<MemoryEntry>, entry: MemoryEntry) {
+    match entries.iter_mut().find(|existing| existing.id == entry.id) {
+        Some(existing) => *existing = entry,
+        None => entries.push(entry),
+    }
+}
+
+/// A user memory store keeping each user's entries in `<user id>.json`.
+#[derive(Debug)]
+pub struct FileUserMemoryStore {
+    storage_directory: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileUserMemoryStore {
+    /// Create a store in the given directory.
+    pub fn new(storage_directory: impl Into<PathBuf>) -> Self {
+        Self {
+            storage_directory: storage_directory.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Get the path of a user's file, rejecting ids that would escape the storage directory.
+    fn user_path(&self, user_id: &str) -> IndubitablyResult<PathBuf> {
+        let valid = !user_id.is_empty() && user_id != "." && user_id != ".." && !user_id.contains(['/', '\\']);
+        if !valid {
+            return Err(SessionError::StorageFailed(format!("Invalid user id '{}'", user_id)).into());
+        }
+        Ok(self.storage_directory.join(format!("{}.json", user_id)))
+    }
+
+    fn read(&self, user_id: &str) -> IndubitablyResult<Vec<MemoryEntry>> {
+        let path = self.user_path(user_id)?;
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(SessionError::StorageFailed(format!("{}: {}", path.display(), e)).into()),
+        };
+        serde_json::from_slice(&bytes)
+            .map_err(|e| SessionError::StorageFailed(format!("Invalid memory file {}: {}", path.display(), e)).into())
+    }
+
+    /// Write a user's entries, replacing the file atomically, or remove it when there are none.
+    fn write(&self, user_id: &str, entries: &[MemoryEntry]) -> IndubitablyResult<()> {
+        let path = self.user_path(user_id)?;
+        let storage_error = |e: std::io::Error| SessionError::StorageFailed(format!("{}: {}", path.display(), e));
+        if entries.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(storage_error(e).into()),
+                _ => Ok(()),
+            };
+        }
+        fs::create_dir_all(&self.storage_directory).map_err(storage_error)?;
+        let json = serde_json::to_vec_pretty(entries)
+            .map_err(|e| SessionError::StorageFailed(format!("Failed to serialize memory: {}", e)))?;
+        let temp_path = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&temp_path).map_err(storage_error)?;
+        file.write_all(&json).map_err(storage_error)?;
+        file.sync_all().map_err(storage_error)?;
+        fs::rename(&temp_path, &path).map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserMemoryStore for FileUserMemoryStore {
+    async fn list(&self, user_id: &str) -> IndubitablyResult<Vec<MemoryEntry>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.read(user_id)
+    }
+
+    async fn upsert(&self, entry: MemoryEntry) -> IndubitablyResult<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.read(&entry.user_id)?;
+        let user_id = entry.user_id.clone();
+        upsert_entry(&mut entries, entry);
+        self.write(&user_id, &entries)
+    }
+
+    async fn delete(&self, user_id: &str, entry_id: &str) -> IndubitablyResult<bool> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.read(user_id)?;
+        let before = entries.len();
+        entries.retain(|entry| entry.id != entry_id);
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.write(user_id, &entries)?;
+        Ok(true)
+    }
+
+    async fn delete_user(&self, user_id: &str) -> IndubitablyResult<usize> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let count = self.read(user_id)?.len();
+        self.write(user_id, &[])?;
+        Ok(count)
+    }
+}
+
+/// Durable facts and preferences about users, shared across their sessions.
+#[derive(Clone)]
+pub struct UserMemory {
+    store: Arc<dyn UserMemoryStore>,
+    extraction_prompt: String,
+    max_profile_entries: usize,
+}
+
+impl std::fmt::Debug for UserMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserMemory")
+            .field("max_profile_entries", &self.max_profile_entries)
+            .finish()
+    }
+}
+
+impl UserMemory {
+    /// Create a user memory over the given store.
+    pub fn new(store: Arc<dyn UserMemoryStore>) -> Self {
+        Self {
+            store,
+            extraction_prompt: DEFAULT_EXTRACTION_PROMPT.to_string(),
+            max_profile_entries: DEFAULT_MAX_PROFILE_ENTRIES,
+        }
+    }
+
+    /// Create a user memory kept in process memory.
+    pub fn in_memory() -> Self {
+        Self::new(Arc::new(InMemoryUserMemoryStore::new()))
+    }
+
+    /// Set the prompt asking the model for durable facts from a turn.
+    pub fn with_extraction_prompt(mut self, prompt: &str) -> Self {
+        self.extraction_prompt = prompt.to_string();
+        self
+    }
+
+    /// Set the maximum number of entries included in a profile, newest first.
+    pub fn with_max_profile_entries(mut self, max_profile_entries: usize) -> Self {
+        self.max_profile_entries = max_profile_entries;
+        self
+    }
+
+    /// List everything remembered about a user, oldest first.
+    pub async fn entries(&self, user_id: &str) -> IndubitablyResult<Vec<MemoryEntry>> {
+        self.store.list(user_id).await
+    }
+
+    /// Remember a fact about a user.
+    pub async fn add(&self, user_id: &str, content: &str) -> IndubitablyResult<MemoryEntry> {
+        let entry = MemoryEntry::new(user_id, content);
+        self.store.upsert(entry.clone()).await?;
+        Ok(entry)
+    }
+
+    /// Change the content of an entry.
+    pub async fn edit(&self, user_id: &str, entry_id: &str, content: &str) -> IndubitablyResult<MemoryEntry> {
+        let mut entry = self
+            .entries(user_id)
+            .await?
+            .into_iter()
+            .find(|entry| entry.id == entry_id)
+            .ok_or_else(|| {
+                SessionError::UpdateFailed(format!("Memory entry '{}' not found for user '{}'", entry_id, user_id))
+            })?;
+        entry.content = content.to_string();
+        entry.updated_at = Utc::now();
+        self.store.upsert(entry.clone()).await?;
+        Ok(entry)
+    }
+
+    /// Forget an entry, returning whether it existed.
+    pub async fn delete(&self, user_id: &str, entry_id: &str) -> IndubitablyResult<bool> {
+        self.store.delete(user_id, entry_id).await
+    }
+
+    /// Forget everything about a user, returning how many entries were deleted.
+    pub async fn delete_user(&self, user_id: &str) -> IndubitablyResult<usize> {
+        tracing::debug!("user_id=<{}> | deleting user memory", user_id);
+        self.store.delete_user(user_id).await
+    }
+
+    /// Render a compact profile of a user for the system prompt, if anything is known.
+    pub async fn profile(&self, user_id: &str) -> IndubitablyResult<Option<String>> {
+        let entries = self.entries(user_id).await?;
+        if entries.is_empty() {
+            return Ok(None);
+        }
+        let facts: Vec<String> = entries
+            .iter()
+            .rev()
+            .take(self.max_profile_entries)
+            .rev()
+            .map(|entry| format!("- {}", entry.content))
+            .collect();
+        Ok(Some(format!("What you know about the user:\n{}", facts.join("\n"))))
+    }
+
+    /// Ask the model for durable facts in a turn and remember the new ones.
+    pub async fn extract(
+        &self,
+        model: &dyn Model,
+        user_id: &str,
+        session_id: Option<&str>,
+        turn: &[Message],
+    ) -> IndubitablyResult<Vec<MemoryEntry>> {
+        let existing = self.entries(user_id).await?;
+        let transcript: Vec<String> = turn
+            .iter()
+            .filter(|message| matches!(message.role, MessageRole::User | MessageRole::Assistant))
+            .filter_map(|message| message.text().map(|text| format!("{:?}: {}", message.role, text)))
+            .collect();
+        if transcript.is_empty() {
+            return Ok(Vec::new());
+        }
+        let known: Vec<String> = existing.iter().map(|entry| format!("- {}", entry.content)).collect();
+        let request = format!(
+            "Already known:\n{}\n\nConversation:\n{}",
+            if known.is_empty() { "(nothing)".to_string() } else { known.join("\n") },
+            transcript.join("\n")
+        );
+        let response = model
+            .generate(&vec![Message::user(&request)], None, Some(&self.extraction_prompt))
+            .await?;
+
+        let mut seen: Vec<String> = existing.iter().map(|entry| entry.content.to_lowercase()).collect();
+        let mut added = Vec::new();
+        for line in response.content.lines() {
+            let Some(fact) = line.trim().strip_prefix("- ").map(str::trim).filter(|fact| !fact.is_empty()) else {
+                continue;
+            };
+            if seen.contains(&fact.to_lowercase()) {
+                continue;
+            }
+            seen.push(fact.to_lowercase());
+            let mut entry = MemoryEntry::new(user_id, fact);
+            if let Some(session_id) = session_id {
+                entry = entry.with_source_session_id(session_id);
+            }
+            self.store.upsert(entry.clone()).await?;
+            added.push(entry);
+        }
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    #[tokio::test]
+    async fn test_user_memory_extracts_and_profiles() {
+        let directory = tempfile::tempdir().unwrap();
+        let memory = UserMemory::new(Arc::new(FileUserMemoryStore::new(directory.path())));
+        memory.add("alice", "Prefers metric units").await.unwrap();
+        let model = MockModel::new().with_responses(vec![ModelResponse::new(
+            "- Lives in Oslo\n- prefers metric units\nnot a fact",
+        )]);
+        let turn = [Message::user("I'm in Oslo, what's the temperature?"), Message::assistant("It is 4 °C.")];
+
+        let added = memory.extract(&model, "alice", Some("s1"), &turn).await.unwrap();
+
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].source_session_id.as_deref(), Some("s1"));
+        let profile = memory.profile("alice").await.unwrap().unwrap();
+        assert!(profile.contains("- Prefers metric units\n- Lives in Oslo"));
+        assert!(memory.profile("bob").await.unwrap().is_none());
+
+        let edited = memory.edit("alice", &added[0].id, "Lives in Bergen").await.unwrap();
+        assert_eq!(edited.content, "Lives in Bergen");
+        assert!(memory.delete("alice", &added[0].id).await.unwrap());
+        assert_eq!(memory.delete_user("alice").await.unwrap(), 1);
+        assert!(memory.entries("alice").await.unwrap().is_empty());
+    }
+}
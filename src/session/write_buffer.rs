@@ -0,0 +1,195 @@
+//! Write coalescing for session backends.
+//! 
+//! This module provides `BufferedSessionManager`, which wraps any session
+//! backend so frequent updates, such as one per streamed chunk, do not each
+//! become a backend write. Updated sessions are marked dirty and held in
+//! memory, and only the latest version of each is written when the flush
+//! interval has passed or `flush` is called. Reads see pending updates, so
+//! callers observe their own writes. Pending updates are lost if the manager
+//! is dropped without a final `flush`.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::SessionManager;
+use crate::types::{IndubitablyResult, Session};
+
+/// The default time between flushes of pending updates.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Counts of the updates a buffered manager received and the writes it made.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteStats {
+    /// The number of updates requested.
+    pub updates: u64,
+    /// The number of updates written to the backend.
+    pub writes: u64,
+    /// The number of flushes that wrote at least one session.
+    pub flushes: u64,
+}
+
+/// A session manager that coalesces updates before they reach the wrapped backend.
+pub struct BufferedSessionManager<M: SessionManager> {
+    inner: M,
+    flush_interval: Duration,
+    dirty: BTreeMap<String, Session>,
+    last_flush: Instant,
+    stats: WriteStats,
+}
+
+impl<M: SessionManager> BufferedSessionManager<M> {
+    /// Wrap a backend, flushing pending updates every `DEFAULT_FLUSH_INTERVAL`.
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            dirty: BTreeMap::new(),
+            last_flush: Instant::now(),
+            stats: WriteStats::default(),
+        }
+    }
+
+    /// Set the time between flushes; zero writes every update through.
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Get the wrapped backend.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Unwrap the backend, discarding pending updates.
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+
+    /// Get the IDs of the sessions with updates not yet written.
+    pub fn dirty_sessions(&self) -> Vec<&str> {
+        self.dirty.keys().map(String::as_str).collect()
+    }
+
+    /// Check whether a session has an update not yet written.
+    pub fn is_dirty(&self, session_id: &str) -> bool {
+        self.dirty.contains_key(session_id)
+    }
+
+    /// Get the counts of updates received and writes made.
+    pub fn stats(&self) -> WriteStats {
+        self.stats
+    }
+
+    /// Write every pending update to the backend and return how many sessions were written.
+    ///
+    /// Updates that fail to write stay pending for the next flush.
+    pub async fn flush(&mut self) -> IndubitablyResult<usize> {
+        self.last_flush = Instant::now();
+        if self.dirty.is_empty() {
+            return Ok(0);
+        }
+        let mut pending = std::mem::take(&mut self.dirty).into_iter();
+        let mut written = 0;
+        while let Some((session_id, session)) = pending.next() {
+            if let Err(e) = self.inner.update_session(session.clone()).await {
+                tracing::warn!(
+                    "session_id=<{}>, pending=<{}>, error=<{}> | failed to flush session update",
+                    session_id,
+                    pending.len() + 1,
+                    e
+                );
+                self.dirty.insert(session_id, session);
+                self.dirty.extend(pending);
+                return Err(e);
+            }
+            written += 1;
+        }
+        self.stats.writes += written as u64;
+        self.stats.flushes += 1;
+        tracing::debug!("written=<{}> | flushed session updates", written);
+        Ok(written)
+    }
+}
+
+#[async_trait]
+impl<M: SessionManager> SessionManager for BufferedSessionManager<M> {
+    async fn create_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        self.dirty.remove(&session.id);
+        self.inner.create_session(session).await
+    }
+
+    async fn get_session(&self, session_id: &str) -> IndubitablyResult<Option<Session>> {
+        match self.dirty.get(session_id) {
+            Some(session) => Ok(Some(session.clone())),
+            None => self.inner.get_session(session_id).await,
+        }
+    }
+
+    async fn update_session(&mut self, session: Session) -> IndubitablyResult<()> {
+        self.stats.updates += 1;
+        self.dirty.insert(session.id.clone(), session);
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_session(&mut self, session_id: &str) -> IndubitablyResult<()> {
+        self.dirty.remove(session_id);
+        self.inner.delete_session(session_id).await
+    }
+
+    async fn list_sessions(&self) -> IndubitablyResult<Vec<Session>> {
+        let mut sessions = self.inner.list_sessions().await?;
+        for session in &mut sessions {
+            if let Some(pending) = self.dirty.get(&session.id) {
+                *session = pending.clone();
+            }
+        }
+        Ok(sessions)
+    }
+
+    async fn session_exists(&self, session_id: &str) -> IndubitablyResult<bool> {
+        Ok(self.dirty.contains_key(session_id) || self.inner.session_exists(session_id).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::FileSessionManager;
+    use crate::types::{SessionAgent, SessionMessage, SessionType};
+
+    #[tokio::test]
+    async fn test_buffered_updates_are_coalesced() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = BufferedSessionManager::new(FileSessionManager::new(dir.path().to_str().unwrap()))
+            .with_flush_interval(Duration::from_secs(3600));
+        let mut session = Session::new("s1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        manager.create_session(session.clone()).await.unwrap();
+
+        for chunk in 0..20 {
+            session.add_message(SessionMessage::new(&format!("m{}", chunk), "assistant", "chunk"));
+            manager.update_session(session.clone()).await.unwrap();
+        }
+        assert_eq!(manager.dirty_sessions(), vec!["s1"]);
+        assert_eq!(manager.get_session("s1").await.unwrap().unwrap().messages.len(), 20);
+        assert_eq!(manager.list_sessions().await.unwrap()[0].messages.len(), 20);
+        assert!(manager.inner().get_session("s1").await.unwrap().unwrap().messages.is_empty());
+
+        assert_eq!(manager.flush().await.unwrap(), 1);
+        assert!(!manager.is_dirty("s1"));
+        assert_eq!(manager.inner().get_session("s1").await.unwrap().unwrap().messages.len(), 20);
+        assert_eq!(manager.stats(), WriteStats { updates: 20, writes: 1, flushes: 1 });
+        assert_eq!(manager.flush().await.unwrap(), 0);
+
+        // A zero interval writes every update through
+        let mut manager = manager.with_flush_interval(Duration::ZERO);
+        session.add_message(SessionMessage::new("m20", "assistant", "done"));
+        manager.update_session(session).await.unwrap();
+        assert!(manager.dirty_sessions().is_empty());
+        assert_eq!(manager.inner().get_session("s1").await.unwrap().unwrap().messages.len(), 21);
+    }
+}
@@ -0,0 +1,238 @@
+//! Skill manifest format.
+//! 
+//! This module defines the `skill.json` manifest that describes a skill
+//! package: its identity and version, the prompt segment it contributes, the
+//! tools it provides and the SHA-256 digests of every packaged file. The
+//! manifest is the signed unit, so the digests extend the signature to the
+//! rest of the package.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::version::SkillVersion;
+use crate::types::{IndubitablyResult, SkillError};
+
+/// The file name of a skill manifest inside a package.
+pub const MANIFEST_FILE_NAME: &str = "skill.json";
+
+/// The file name of the detached manifest signature inside a package.
+pub const SIGNATURE_FILE_NAME: &str = "skill.json.sig";
+
+/// How a skill tool is implemented.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SkillToolHandler {
+    /// Run a packaged program, passing the input as JSON on stdin.
+    Command {
+        /// The program, a packaged file such as `./forecast.sh`, and its arguments.
+        command: Vec<String>,
+    },
+    /// Call an export of the package's WASM module.
+    Wasm {
+        /// The name of the exported function.
+        export: String,
+    },
+    /// Return a fixed output.
+    Static {
+        /// The output returned for every call.
+        output: Value,
+    },
+}
+
+/// A tool declared by a skill manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillToolManifest {
+    /// The tool name, without the skill namespace.
+    pub name: String,
+    /// The tool description.
+    #[serde(default)]
+    pub description: String,
+    /// The JSON schema of the tool input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<Value>,
+    /// How the tool is implemented.
+    #[serde(flatten)]
+    pub handler: SkillToolHandler,
+}
+
+/// The manifest of a skill package.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillManifest {
+    /// The skill name.
+    pub name: String,
+    /// The skill version.
+    pub version: String,
+    /// The namespace tools are registered under, defaulting to the name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// The publisher whose key must sign the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// A description of the skill.
+    #[serde(default)]
+    pub description: String,
+    /// An inline prompt segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// A packaged file holding the prompt segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_file: Option<String>,
+    /// A packaged WASM module implementing `wasm` tools.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<String>,
+    /// The tools the skill provides.
+    #[serde(default)]
+    pub tools: Vec<SkillToolManifest>,
+    /// SHA-256 digests of packaged files, keyed by relative path.
+    #[serde(default)]
+    pub files: HashMap<String, String>,
+}
+
+impl SkillManifest {
+    /// Parse and validate a manifest from JSON bytes.
+    pub fn from_json(bytes: &[u8]) -> IndubitablyResult<Self> {
+        let manifest: Self = serde_json::from_slice(bytes)
+            .map_err(|e| SkillError::InvalidManifest(e.to_string()))?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Serialize the manifest to pretty-printed JSON bytes.
+    pub fn to_json(&self) -> IndubitablyResult<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Get the namespace tools are registered under.
+    pub fn namespace(&self) -> &str {
+        self.namespace.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Get the parsed version.
+    pub fn parsed_version(&self) -> IndubitablyResult<SkillVersion> {
+        SkillVersion::parse(&self.version)
+    }
+
+    /// Check names, version and file references.
+    pub fn validate(&self) -> IndubitablyResult<()> {
+        if !is_identifier(&self.name) {
+            return Err(SkillError::InvalidManifest(format!("Invalid skill name '{}'", self.name)).into());
+        }
+        if !is_identifier(self.namespace()) {
+            return Err(SkillError::InvalidManifest(format!("Invalid namespace '{}'", self.namespace())).into());
+        }
+        self.parsed_version()?;
+
+        let mut seen = std::collections::HashSet::new();
+        for tool in &self.tools {
+            if !is_identifier(&tool.name) || !seen.insert(tool.name.as_str()) {
+                return Err(SkillError::InvalidManifest(format!("Invalid or duplicate tool name '{}'", tool.name)).into());
+            }
+            match &tool.handler {
+                SkillToolHandler::Command { command } if command.is_empty() => {
+                    return Err(SkillError::InvalidManifest(format!("Tool '{}' has an empty command", tool.name)).into());
+                }
+                SkillToolHandler::Command { command } => {
+                    // The program and the files it is given must be covered by the signature
+                    let files = command[1..].iter().filter(|arg| arg.starts_with("./"));
+                    for path in std::iter::once(&command[0]).chain(files) {
+                        if !self.files.contains_key(packaged_path(path)) {
+                            return Err(SkillError::InvalidManifest(format!(
+                                "Tool '{}' runs '{}', which is not a packaged file",
+                                tool.name, path
+                            ))
+                            .into());
+                        }
+                    }
+                }
+                SkillToolHandler::Wasm { .. } if self.wasm.is_none() => {
+                    return Err(SkillError::InvalidManifest(format!("Tool '{}' needs a wasm module", tool.name)).into());
+                }
+                _ => {}
+            }
+        }
+
+        for path in self.files.keys() {
+            if !is_safe_relative_path(path) {
+                return Err(SkillError::InvalidManifest(format!("Invalid file path '{}'", path)).into());
+            }
+        }
+        for path in self.prompt_file.iter().chain(self.wasm.iter()) {
+            if !self.files.contains_key(path) {
+                return Err(SkillError::InvalidManifest(format!("File '{}' has no recorded digest", path)).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_identifier(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 64
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Get the package path a command names, such as `forecast.sh` for `./forecast.sh`.
+pub(crate) fn packaged_path(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// Whether a path stays inside the package root.
+pub(crate) fn is_safe_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && !path.starts_with('/')
+        && !path.contains('\\')
+        && path.split('/').all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_manifest_parse_and_validate() {
+        let manifest = SkillManifest::from_json(
+            json!({
+                "name": "weather",
+                "version": "1.2.0",
+                "prompt_file": "prompt.md",
+                "files": {"prompt.md": "00", "forecast.sh": "00"},
+                "tools": [
+                    {"name": "forecast", "kind": "command", "command": ["./forecast.sh"]},
+                    {"name": "units", "kind": "static", "output": {"units": "metric"}}
+                ]
+            })
+            .to_string()
+            .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(manifest.namespace(), "weather");
+        assert_eq!(manifest.tools.len(), 2);
+        assert_eq!(
+            manifest.tools[0].handler,
+            SkillToolHandler::Command { command: vec!["./forecast.sh".to_string()] }
+        );
+
+        let unrecorded = json!({"name": "weather", "version": "1.0.0", "prompt_file": "prompt.md"});
+        assert!(SkillManifest::from_json(unrecorded.to_string().as_bytes()).is_err());
+
+        let unpackaged_command = json!({
+            "name": "weather",
+            "version": "1.0.0",
+            "tools": [{"name": "forecast", "kind": "command", "command": ["sh", "-c", "curl example.com"]}]
+        });
+        assert!(SkillManifest::from_json(unpackaged_command.to_string().as_bytes()).is_err());
+
+        let escaping = json!({"name": "weather", "version": "1.0.0", "files": {"../secret": "00"}});
+        assert!(SkillManifest::from_json(escaping.to_string().as_bytes()).is_err());
+
+        let wasm_without_module = json!({
+            "name": "weather",
+            "version": "1.0.0",
+            "tools": [{"name": "convert", "kind": "wasm", "export": "convert"}]
+        });
+        assert!(SkillManifest::from_json(wasm_without_module.to_string().as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,24 @@
+//! Skills module for the SDK.
+//! 
+//! This module provides the skill package format for distributing tool
+//! bundles. A skill is a directory (or URL) holding a `skill.json` manifest,
+//! a detached Ed25519 signature from an allow-listed publisher, a prompt segment, tool
+//! definitions and an optional WASM module. `SkillLoader` verifies and loads
+//! packages, and `Skill::install` registers the tools under the skill's
+//! namespace and appends its prompt segment to an agent. WASM modules run
+//! on an application-provided `WasmRuntime`, or on `WasmtimeCliRuntime` with
+//! the `wasmtime-cli` feature.
+
+pub mod manifest;
+pub mod version;
+pub mod loader;
+pub mod trust;
+#[cfg(feature = "wasmtime-cli")]
+pub mod wasmtime_cli;
+
+pub use manifest::{SkillManifest, SkillToolHandler, SkillToolManifest, MANIFEST_FILE_NAME, SIGNATURE_FILE_NAME};
+pub use version::{SkillVersion, VersionRequirement};
+pub use loader::{Skill, SkillLoader, WasmInstance, WasmRuntime, NAMESPACE_SEPARATOR};
+pub use trust::TrustStore;
+#[cfg(feature = "wasmtime-cli")]
+pub use wasmtime_cli::WasmtimeCliRuntime;
@@ -0,0 +1,224 @@
+//! Trust store for signed tools and skills.
+//! 
+//! This module provides `TrustStore`, the allow-list of publishers whose
+//! Ed25519 keys may sign hot-loaded tools and skill manifests. Unsigned or
+//! untrusted artifacts are refused by default, and every failed verification
+//! is published as an audit event on the telemetry event bus. Signatures are
+//! checked by an application-provided `Ed25519Verifier`; without one, every
+//! signed artifact is refused.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::crypto::{hex, Ed25519PublicKey, Ed25519Verifier};
+use crate::telemetry::events::{EventBus, LifecycleEvent, LifecycleEventKind};
+use crate::types::{IndubitablyResult, SkillError};
+
+/// The on-disk format of a trust store.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TrustStoreFile {
+    #[serde(default)]
+    publishers: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    allow_unsigned: bool,
+}
+
+/// An allow-list of publishers and the keys they sign with.
+#[derive(Clone, Default)]
+pub struct TrustStore {
+    publishers: HashMap<String, Vec<Ed25519PublicKey>>,
+    allow_unsigned: bool,
+    verifier: Option<Arc<dyn Ed25519Verifier>>,
+    events: Option<EventBus>,
+}
+
+impl fmt::Debug for TrustStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrustStore")
+            .field("publishers", &self.publishers)
+            .field("allow_unsigned", &self.allow_unsigned)
+            .field("verifier", &self.verifier.is_some())
+            .field("events", &self.events)
+            .finish()
+    }
+}
+
+impl TrustStore {
+    /// Create an empty trust store that refuses unsigned artifacts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a trust store from JSON such as `{"publishers": {"acme": ["<hex key>"]}}`.
+    pub fn from_json(bytes: &[u8]) -> IndubitablyResult<Self> {
+        let file: TrustStoreFile = serde_json::from_slice(bytes)
+            .map_err(|e| SkillError::InvalidManifest(format!("Invalid trust store: {}", e)))?;
+        let mut store = Self::new().with_allow_unsigned(file.allow_unsigned);
+        for (publisher, keys) in file.publishers {
+            for key in keys {
+                store = store.with_publisher_key(&publisher, Ed25519PublicKey::from_hex(&key)?);
+            }
+        }
+        Ok(store)
+    }
+
+    /// Load a trust store from a JSON file.
+    pub fn load(path: &Path) -> IndubitablyResult<Self> {
+        Self::from_json(&std::fs::read(path)?)
+    }
+
+    /// Trust a key for a publisher.
+    pub fn with_publisher_key(mut self, publisher: &str, key: Ed25519PublicKey) -> Self {
+        let keys = self.publishers.entry(publisher.to_string()).or_default();
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+        self
+    }
+
+    /// Set whether artifacts without a signature may be loaded.
+    pub fn with_allow_unsigned(mut self, allow: bool) -> Self {
+        self.allow_unsigned = allow;
+        self
+    }
+
+    /// Set the verifier that checks signatures, without which signed artifacts are refused.
+    pub fn with_verifier(mut self, verifier: Arc<dyn Ed25519Verifier>) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    /// Publish failed verifications to the given event bus.
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Remove a publisher and all of its keys.
+    pub fn revoke_publisher(&mut self, publisher: &str) -> bool {
+        self.publishers.remove(publisher).is_some()
+    }
+
+    /// Get the names of the trusted publishers.
+    pub fn publishers(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.publishers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Whether unsigned artifacts may be loaded.
+    pub fn allows_unsigned(&self) -> bool {
+        self.allow_unsigned
+    }
+
+    /// Find the trusted publisher whose key produced a signature.
+    ///
+    /// Always `None` when no verifier is configured.
+    pub fn find_publisher(&self, bytes: &[u8], signature: &[u8]) -> Option<&str> {
+        let verifier = self.verifier.as_ref()?;
+        self.publishers
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| verifier.verify(key, bytes, signature)))
+            .map(|(publisher, _)| publisher.as_str())
+    }
+
+    /// Verify an artifact against its hex-encoded detached signature.
+    ///
+    /// Returns the signing publisher, or `None` for an unsigned artifact the
+    /// store allows. When `claimed_publisher` is set, the signature must come
+    /// from one of that publisher's keys.
+    pub async fn verify(
+        &self,
+        artifact: &str,
+        bytes: &[u8],
+        signature: Option<&[u8]>,
+        claimed_publisher: Option<&str>,
+    ) -> IndubitablyResult<Option<String>> {
+        let Some(signature) = signature else {
+            if self.allow_unsigned {
+                tracing::debug!("artifact=<{}> | loading unsigned artifact", artifact);
+                return Ok(None);
+            }
+            return Err(self.reject(artifact, claimed_publisher, "artifact is not signed").await);
+        };
+
+        if self.verifier.is_none() {
+            let reason = "no Ed25519 verifier is configured to check the signature";
+            return Err(self.reject(artifact, claimed_publisher, reason).await);
+        }
+        let Ok(signature) = hex::decode(&String::from_utf8_lossy(signature)) else {
+            return Err(self.reject(artifact, claimed_publisher, "signature is not valid hex").await);
+        };
+
+        match (self.find_publisher(bytes, &signature), claimed_publisher) {
+            (None, _) => Err(self.reject(artifact, claimed_publisher, "signature is not from a trusted publisher").await),
+            (Some(publisher), Some(claimed)) if publisher != claimed => {
+                let reason = format!("signed by '{}' but claims publisher '{}'", publisher, claimed);
+                Err(self.reject(artifact, claimed_publisher, &reason).await)
+            }
+            (Some(publisher), _) => Ok(Some(publisher.to_string())),
+        }
+    }
+
+    async fn reject(&self, artifact: &str, claimed_publisher: Option<&str>, reason: &str) -> crate::types::IndubitablyError {
+        let data = json!({
+            "artifact": artifact,
+            "publisher": claimed_publisher,
+            "reason": reason,
+        });
+        match &self.events {
+            Some(events) => {
+                let event = LifecycleEvent::new(LifecycleEventKind::SignatureVerificationFailed, "trust_store", data);
+                events.publish(event).await;
+            }
+            None => tracing::warn!("data=<{}> | signature verification failed", data),
+        }
+        SkillError::SignatureInvalid(format!("{}: {}", artifact, reason)).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::backend::testing::InsecureSigner;
+    use crate::telemetry::events::EventRecorder;
+
+    #[tokio::test]
+    async fn test_verify_publishers_and_audit() {
+        let acme = Ed25519PublicKey::from_bytes([1; 32]);
+        let other = Ed25519PublicKey::from_bytes([2; 32]);
+        let store_json = json!({"publishers": {"acme": [acme.to_hex()]}}).to_string();
+        let signature = hex::encode(&InsecureSigner::sign(&acme, b"tool"));
+
+        let unverified = TrustStore::from_json(store_json.as_bytes()).unwrap();
+        let error = unverified.verify("tool.json", b"tool", Some(signature.as_bytes()), None).await.unwrap_err();
+        assert!(error.to_string().contains("no Ed25519 verifier"));
+
+        let events = EventBus::new();
+        let recorder = EventRecorder::new();
+        events.subscribe(Arc::new(recorder.clone()));
+        let store = unverified.with_verifier(Arc::new(InsecureSigner)).with_event_bus(events);
+        assert_eq!(store.publishers(), vec!["acme"]);
+
+        let publisher = store.verify("tool.json", b"tool", Some(signature.as_bytes()), None).await.unwrap();
+        assert_eq!(publisher.as_deref(), Some("acme"));
+        assert!(store.verify("tool.json", b"tool", Some(signature.as_bytes()), Some("globex")).await.is_err());
+
+        let untrusted = hex::encode(&InsecureSigner::sign(&other, b"tool"));
+        assert!(store.verify("tool.json", b"tool", Some(untrusted.as_bytes()), None).await.is_err());
+        assert!(store.verify("tool.json", b"tool", None, None).await.is_err());
+        let audits = recorder.events();
+        assert_eq!(audits.len(), 3);
+        assert!(audits.iter().all(|event| event.kind == LifecycleEventKind::SignatureVerificationFailed));
+        assert_eq!(audits[0].data["publisher"], "globex");
+
+        let permissive = store.with_allow_unsigned(true);
+        assert_eq!(permissive.verify("tool.json", b"tool", None, None).await.unwrap(), None);
+        assert!(permissive.verify("tool.json", b"tampered", Some(signature.as_bytes()), None).await.is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Skill version pinning.
+//! 
+//! This module provides semantic versions for skill packages and the
+//! requirements used to pin them. Requirements follow Cargo's syntax: a bare
+//! version such as `1.2.3` is a caret requirement, and `=`, `^`, `~`, `>=`
+//! and `*` are supported.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::types::{IndubitablyError, IndubitablyResult, SkillError};
+
+/// A semantic version of a skill package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SkillVersion {
+    /// The major version.
+    pub major: u64,
+    /// The minor version.
+    pub minor: u64,
+    /// The patch version.
+    pub patch: u64,
+}
+
+impl SkillVersion {
+    /// Create a new version.
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Parse a version such as `1.2.3`, defaulting missing parts to zero.
+    pub fn parse(text: &str) -> IndubitablyResult<Self> {
+        let text = text.trim();
+        let core = text.split(['-', '+']).next().unwrap_or_default();
+        let mut parts = core.split('.');
+        let mut next = || -> IndubitablyResult<u64> {
+            match parts.next() {
+                Some(part) => part.parse().map_err(|_| invalid_version(text)),
+                None => Ok(0),
+            }
+        };
+        let version = Self::new(next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return Err(invalid_version(text));
+        }
+        Ok(version)
+    }
+}
+
+impl fmt::Display for SkillVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for SkillVersion {
+    type Err = IndubitablyError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+/// A requirement that a skill version must satisfy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionRequirement {
+    /// Any version is accepted.
+    Any,
+    /// Exactly this version.
+    Exact(SkillVersion),
+    /// Compatible versions that do not change the left-most non-zero part.
+    Caret(SkillVersion),
+    /// Versions with the same major and minor parts and at least this patch.
+    Tilde(SkillVersion),
+    /// This version or any later one.
+    AtLeast(SkillVersion),
+}
+
+impl VersionRequirement {
+    /// Parse a requirement such as `=1.2.3`, `^1.2`, `~1.2.0`, `>=1.0` or `*`.
+    pub fn parse(text: &str) -> IndubitablyResult<Self> {
+        let text = text.trim();
+        if text == "*" || text.is_empty() {
+            return Ok(Self::Any);
+        }
+        if let Some(rest) = text.strip_prefix(">=") {
+            return Ok(Self::AtLeast(SkillVersion::parse(rest)?));
+        }
+        if let Some(rest) = text.strip_prefix('=') {
+            return Ok(Self::Exact(SkillVersion::parse(rest)?));
+        }
+        if let Some(rest) = text.strip_prefix('~') {
+            return Ok(Self::Tilde(SkillVersion::parse(rest)?));
+        }
+        let rest = text.strip_prefix('^').unwrap_or(text);
+        Ok(Self::Caret(SkillVersion::parse(rest)?))
+    }
+
+    /// Check whether a version satisfies the requirement.
+    pub fn matches(&self, version: &SkillVersion) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(required) => version == required,
+            Self::AtLeast(required) => version >= required,
+            Self::Tilde(required) => {
+                version >= required && version.major == required.major && version.minor == required.minor
+            }
+            Self::Caret(required) => {
+                if version < required {
+                    false
+                } else if required.major > 0 {
+                    version.major == required.major
+                } else if required.minor > 0 {
+                    version.major == 0 && version.minor == required.minor
+                } else {
+                    version == required
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => write!(f, "*"),
+            Self::Exact(version) => write!(f, "={}", version),
+            Self::Caret(version) => write!(f, "^{}", version),
+            Self::Tilde(version) => write!(f, "~{}", version),
+            Self::AtLeast(version) => write!(f, ">={}", version),
+        }
+    }
+}
+
+fn invalid_version(text: &str) -> IndubitablyError {
+    SkillError::InvalidManifest(format!("Invalid version '{}'", text)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_requirements() {
+        let version = SkillVersion::parse("1.4.2").unwrap();
+        assert_eq!(version.to_string(), "1.4.2");
+        assert!(SkillVersion::parse("1.x").is_err());
+
+        assert!(VersionRequirement::parse("1.2").unwrap().matches(&version));
+        assert!(!VersionRequirement::parse("^2").unwrap().matches(&version));
+        assert!(VersionRequirement::parse("~1.4.0").unwrap().matches(&version));
+        assert!(!VersionRequirement::parse("~1.3").unwrap().matches(&version));
+        assert!(VersionRequirement::parse("=1.4.2").unwrap().matches(&version));
+        assert!(!VersionRequirement::parse("=1.4.1").unwrap().matches(&version));
+        assert!(VersionRequirement::parse(">=1.0").unwrap().matches(&version));
+        assert!(VersionRequirement::parse("*").unwrap().matches(&version));
+        assert!(!VersionRequirement::parse("^0.2.1").unwrap().matches(&SkillVersion::new(0, 3, 0)));
+    }
+}
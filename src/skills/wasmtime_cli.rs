@@ -0,0 +1,157 @@
+//! A WASM runtime backed by the Wasmtime command-line tool.
+//! 
+//! This module provides `WasmtimeCliRuntime`, a `WasmRuntime` that runs skill
+//! and plugin modules with the `wasmtime` binary. Modules are WASI commands:
+//! each call runs the module with the export name in the
+//! `INDUBITABLY_WASM_EXPORT` environment variable and the JSON input on
+//! stdin, and reads the JSON output from stdout. Every call runs in a fresh
+//! instance, so modules keep no state between calls. It is compiled with the
+//! `wasmtime-cli` feature and needs Wasmtime installed.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::loader::{WasmInstance, WasmRuntime, DEFAULT_COMMAND_TIMEOUT};
+use crate::types::{IndubitablyError, IndubitablyResult, SkillError, ToolError};
+
+/// The environment variable naming the export a call runs.
+pub const EXPORT_ENV_VAR: &str = "INDUBITABLY_WASM_EXPORT";
+
+/// Runs WASM modules with the Wasmtime command-line tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmtimeCliRuntime {
+    binary: PathBuf,
+    timeout: Duration,
+}
+
+impl WasmtimeCliRuntime {
+    /// Use `wasmtime` from the `PATH` with the default command timeout.
+    pub fn new() -> Self {
+        Self {
+            binary: PathBuf::from("wasmtime"),
+            timeout: DEFAULT_COMMAND_TIMEOUT,
+        }
+    }
+
+    /// Set the path of the Wasmtime binary.
+    pub fn with_binary(mut self, binary: impl Into<PathBuf>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+
+    /// Set the time limit of a call, after which Wasmtime stops the module.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Default for WasmtimeCliRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WasmRuntime for WasmtimeCliRuntime {
+    fn instantiate(&self, module: &[u8]) -> IndubitablyResult<Arc<dyn WasmInstance>> {
+        if !module.starts_with(b"\0asm") {
+            return Err(SkillError::Unsupported("WASM module is missing the \\0asm header".to_string()).into());
+        }
+        let path = std::env::temp_dir().join(format!("indubitably-wasm-{}.wasm", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&path, module)?;
+        tracing::debug!("path=<{}>, bytes=<{}> | instantiated wasm module", path.display(), module.len());
+        Ok(Arc::new(WasmtimeCliInstance {
+            runtime: self.clone(),
+            path,
+        }))
+    }
+}
+
+/// A module written to a temporary file, removed when the instance is dropped.
+struct WasmtimeCliInstance {
+    runtime: WasmtimeCliRuntime,
+    path: PathBuf,
+}
+
+impl WasmtimeCliInstance {
+    fn run(&self, export: &str, input: &[u8]) -> IndubitablyResult<Vec<u8>> {
+        let failed = |message: String| IndubitablyError::from(ToolError::ExecutionFailed(message));
+        let mut child = Command::new(&self.runtime.binary)
+            .arg("run")
+            .args(["-W", &format!("timeout={}ms", self.runtime.timeout.as_millis())])
+            .args(["--env", &format!("{}={}", EXPORT_ENV_VAR, export)])
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| failed(format!("could not run {}: {}", self.runtime.binary.display(), e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(input) {
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+                _ => {}
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(failed(format!(
+                "WASM export '{}' failed: {}",
+                export,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl WasmInstance for WasmtimeCliInstance {
+    fn call(&self, export: &str, input: Value) -> IndubitablyResult<Value> {
+        let output = self.run(export, &serde_json::to_vec(&input)?)?;
+        serde_json::from_slice(&output).map_err(|e| {
+            ToolError::ExecutionFailed(format!("WASM export '{}' returned invalid JSON: {}", export, e)).into()
+        })
+    }
+}
+
+impl Drop for WasmtimeCliInstance {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wasmtime_cli_runtime_passes_export_and_input() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stands in for `wasmtime run`: describes itself, echoes `call` input and fails anything else.
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("wasmtime");
+        std::fs::write(
+            &binary,
+            "#!/bin/sh\ncase \"$*\" in\n\
+             *EXPORT=describe*) echo '{\"name\": \"echo\"}' ;;\n\
+             *EXPORT=call*) cat ;;\n\
+             *) echo 'no such export' >&2; exit 1 ;;\nesac\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let runtime = WasmtimeCliRuntime::new().with_binary(&binary);
+        assert!(runtime.instantiate(b"not wasm").is_err());
+        let instance = runtime.instantiate(b"\0asm\x01\0\0\0").unwrap();
+        assert_eq!(instance.call("describe", Value::Null).unwrap()["name"], "echo");
+        assert_eq!(instance.call("call", serde_json::json!({"text": "hi"})).unwrap()["text"], "hi");
+        let error = instance.call("missing", Value::Null).unwrap_err().to_string();
+        assert!(error.contains("no such export"), "{}", error);
+    }
+}
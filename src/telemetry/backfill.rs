@@ -0,0 +1,316 @@
+//! Metrics backfill from persisted sessions.
+//! 
+//! This module replays stored sessions to compute historical usage, cost
+//! and latency aggregates, so teams that adopt telemetry late can still
+//! analyze past traffic. Values are read from message metadata where they
+//! were recorded, either as top-level keys or inside a `usage` object;
+//! messages without them are counted but contribute nothing else.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::events::{METRIC_INPUT_TOKENS, METRIC_OUTPUT_TOKENS};
+use super::metrics::Metrics;
+use crate::session::SessionManager;
+use crate::types::{IndubitablyError, IndubitablyResult, Session, SessionMessage};
+
+/// The message metadata key holding input tokens.
+pub const INPUT_TOKENS_KEY: &str = "input_tokens";
+
+/// The message metadata key holding output tokens.
+pub const OUTPUT_TOKENS_KEY: &str = "output_tokens";
+
+/// The message metadata key holding the cost of the model call.
+pub const COST_KEY: &str = "cost";
+
+/// The message metadata key holding the model call latency in milliseconds.
+pub const LATENCY_MS_KEY: &str = "latency_ms";
+
+/// The message metadata key holding a nested usage object.
+pub const USAGE_KEY: &str = "usage";
+
+/// The message metadata key holding the model that produced the message.
+pub const MODEL_KEY: &str = "model";
+
+/// The metric counting the cost of model calls.
+pub const METRIC_COST: &str = "agent.cost";
+
+/// The metric counting sessions.
+pub const METRIC_SESSIONS: &str = "agent.sessions";
+
+/// The metric counting stored messages.
+pub const METRIC_MESSAGES: &str = "agent.messages";
+
+/// The gauge holding the mean model latency in milliseconds.
+pub const METRIC_LATENCY_MEAN_MS: &str = "agent.model.latency_ms.mean";
+
+/// The gauge holding the maximum model latency in milliseconds.
+pub const METRIC_LATENCY_MAX_MS: &str = "agent.model.latency_ms.max";
+
+/// The model name used when a message's model is unknown.
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Usage, cost and latency aggregated over a set of messages.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageAggregate {
+    /// The number of sessions contributing messages.
+    pub sessions: usize,
+    /// The number of messages.
+    pub messages: usize,
+    /// The number of assistant messages without recorded usage.
+    pub messages_without_usage: usize,
+    /// The total input tokens.
+    pub input_tokens: u64,
+    /// The total output tokens.
+    pub output_tokens: u64,
+    /// The total cost.
+    pub cost: f64,
+    /// The number of recorded latencies.
+    pub latency_samples: usize,
+    /// The sum of recorded latencies in milliseconds.
+    pub latency_total_ms: f64,
+    /// The largest recorded latency in milliseconds.
+    pub latency_max_ms: f64,
+}
+
+impl UsageAggregate {
+    /// Get the total tokens.
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Get the mean recorded latency in milliseconds.
+    pub fn mean_latency_ms(&self) -> Option<f64> {
+        (self.latency_samples > 0).then(|| self.latency_total_ms / self.latency_samples as f64)
+    }
+
+    fn add_message(&mut self, message: &SessionMessage) {
+        self.messages += 1;
+        let usage = MessageUsage::from_message(message);
+        if !usage.recorded && message.role == "assistant" {
+            self.messages_without_usage += 1;
+        }
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cost += usage.cost;
+        if let Some(latency) = usage.latency_ms {
+            self.latency_samples += 1;
+            self.latency_total_ms += latency;
+            self.latency_max_ms = self.latency_max_ms.max(latency);
+        }
+    }
+}
+
+/// Usage read from one message's metadata.
+#[derive(Debug, Default)]
+struct MessageUsage {
+    recorded: bool,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost: f64,
+    latency_ms: Option<f64>,
+}
+
+impl MessageUsage {
+    fn from_message(message: &SessionMessage) -> Self {
+        let Some(ref metadata) = message.metadata else {
+            return Self::default();
+        };
+        let nested = metadata.get(USAGE_KEY).and_then(Value::as_object);
+        let lookup = |key: &str| {
+            metadata
+                .get(key)
+                .or_else(|| nested.and_then(|usage| usage.get(key)))
+                .and_then(Value::as_f64)
+        };
+
+        let input_tokens = lookup(INPUT_TOKENS_KEY);
+        let output_tokens = lookup(OUTPUT_TOKENS_KEY);
+        let cost = lookup(COST_KEY);
+        let latency_ms = lookup(LATENCY_MS_KEY);
+        Self {
+            recorded: input_tokens.is_some() || output_tokens.is_some() || cost.is_some() || latency_ms.is_some(),
+            input_tokens: input_tokens.unwrap_or(0.0) as u64,
+            output_tokens: output_tokens.unwrap_or(0.0) as u64,
+            cost: cost.unwrap_or(0.0),
+            latency_ms,
+        }
+    }
+}
+
+/// Historical aggregates computed from stored sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackfillReport {
+    /// When the report was generated.
+    pub generated_at: DateTime<Utc>,
+    /// The timestamp of the earliest replayed message.
+    pub first_activity: Option<DateTime<Utc>>,
+    /// The timestamp of the latest replayed message.
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Aggregates over all sessions.
+    pub totals: UsageAggregate,
+    /// Aggregates per UTC day, keyed by `YYYY-MM-DD`.
+    pub by_day: BTreeMap<String, UsageAggregate>,
+    /// Aggregates per model.
+    pub by_model: BTreeMap<String, UsageAggregate>,
+}
+
+impl BackfillReport {
+    /// Compute a report from already loaded sessions.
+    pub fn from_session_list(sessions: &[Session]) -> Self {
+        let mut report = Self {
+            generated_at: Utc::now(),
+            first_activity: None,
+            last_activity: None,
+            totals: UsageAggregate::default(),
+            by_day: BTreeMap::new(),
+            by_model: BTreeMap::new(),
+        };
+
+        for session in sessions {
+            let session_model = session.agent.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+            let mut days = Vec::new();
+            let mut models = Vec::new();
+
+            for message in &session.messages {
+                let day = message.created_at.format("%Y-%m-%d").to_string();
+                let model = message
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.get(MODEL_KEY))
+                    .and_then(Value::as_str)
+                    .unwrap_or(session_model)
+                    .to_string();
+
+                report.totals.add_message(message);
+                report.by_day.entry(day.clone()).or_default().add_message(message);
+                report.by_model.entry(model.clone()).or_default().add_message(message);
+                if !days.contains(&day) {
+                    days.push(day);
+                }
+                if !models.contains(&model) {
+                    models.push(model);
+                }
+
+                report.first_activity = Some(report.first_activity.map_or(message.created_at, |t| t.min(message.created_at)));
+                report.last_activity = Some(report.last_activity.map_or(message.created_at, |t| t.max(message.created_at)));
+            }
+
+            report.totals.sessions += 1;
+            for day in days {
+                report.by_day.entry(day).or_default().sessions += 1;
+            }
+            for model in models {
+                report.by_model.entry(model).or_default().sessions += 1;
+            }
+        }
+
+        report
+    }
+
+    /// Add the report totals to a metrics registry.
+    pub fn apply_to(&self, metrics: &mut Metrics) {
+        metrics.increment(METRIC_SESSIONS, self.totals.sessions as f64);
+        metrics.increment(METRIC_MESSAGES, self.totals.messages as f64);
+        metrics.increment(METRIC_INPUT_TOKENS, self.totals.input_tokens as f64);
+        metrics.increment(METRIC_OUTPUT_TOKENS, self.totals.output_tokens as f64);
+        metrics.increment(METRIC_COST, self.totals.cost);
+        if let Some(mean) = self.totals.mean_latency_ms() {
+            metrics.set(METRIC_LATENCY_MEAN_MS, mean);
+            metrics.set(METRIC_LATENCY_MAX_MS, self.totals.latency_max_ms);
+        }
+    }
+
+    /// Serialize the report to a JSON string.
+    pub fn to_json(&self) -> IndubitablyResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| IndubitablyError::InternalError(format!("Failed to serialize backfill report: {}", e)))
+    }
+
+    /// Save the report to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> IndubitablyResult<()> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+/// Replay every stored session and compute historical aggregates.
+pub async fn from_sessions(manager: &dyn SessionManager) -> IndubitablyResult<BackfillReport> {
+    let sessions = manager.list_sessions().await?;
+    let report = BackfillReport::from_session_list(&sessions);
+    tracing::debug!(
+        "sessions=<{}>, messages=<{}>, without_usage=<{}> | backfilled metrics from sessions",
+        report.totals.sessions,
+        report.totals.messages,
+        report.totals.messages_without_usage
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::FileSessionManager;
+    use crate::types::{SessionAgent, SessionType};
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn message(id: &str, role: &str, day: u32, metadata: Option<Value>) -> SessionMessage {
+        let mut message = SessionMessage::new(id, role, "text");
+        message.created_at = Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap();
+        if let Some(Value::Object(fields)) = metadata {
+            for (key, value) in fields {
+                message.add_metadata(&key, value);
+            }
+        }
+        message
+    }
+
+    #[tokio::test]
+    async fn test_backfill_from_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = FileSessionManager::new(dir.path().to_str().unwrap());
+
+        let mut first = Session::new("s1", SessionType::Conversation, SessionAgent::new("a", "Agent").with_model("claude"));
+        first.add_message(message("m1", "user", 1, None));
+        first.add_message(message("m2", "assistant", 1, Some(json!({
+            "input_tokens": 100, "output_tokens": 20, "cost": 0.5, "latency_ms": 300.0
+        }))));
+        first.add_message(message("m3", "assistant", 2, Some(json!({
+            "usage": {"input_tokens": 50, "output_tokens": 10, "latency_ms": 100.0}
+        }))));
+        manager.create_session(first).await.unwrap();
+
+        let mut second = Session::new("s2", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        second.add_message(message("m4", "assistant", 2, Some(json!({"model": "gpt", "output_tokens": 5}))));
+        second.add_message(message("m5", "assistant", 2, None));
+        manager.create_session(second).await.unwrap();
+
+        let report = from_sessions(&manager).await.unwrap();
+        assert_eq!(report.totals.sessions, 2);
+        assert_eq!(report.totals.messages, 5);
+        assert_eq!(report.totals.messages_without_usage, 1);
+        assert_eq!(report.totals.input_tokens, 150);
+        assert_eq!(report.totals.output_tokens, 35);
+        assert_eq!(report.totals.mean_latency_ms(), Some(200.0));
+        assert_eq!(report.by_day["2026-03-02"].sessions, 2);
+        assert_eq!(report.by_model["claude"].total_tokens(), 180);
+        assert_eq!(report.by_model["gpt"].output_tokens, 5);
+        assert_eq!(report.by_model[UNKNOWN_MODEL].messages, 1);
+
+        let mut metrics = Metrics::new();
+        report.apply_to(&mut metrics);
+        assert_eq!(metrics.get(METRIC_INPUT_TOKENS), Some(150.0));
+        assert_eq!(metrics.get(METRIC_COST), Some(0.5));
+        assert_eq!(metrics.get(METRIC_LATENCY_MAX_MS), Some(300.0));
+
+        let path = dir.path().join("report.out");
+        report.save(&path).unwrap();
+        let saved: BackfillReport = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved, report);
+    }
+}
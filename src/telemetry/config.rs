@@ -2,9 +2,59 @@
 //! 
 //! This module provides configuration options for
 //! metrics, tracing, and other observability features.
+//! 
+//! `TelemetryConfig::from_env` and `with_env_overrides` read the standard
+//! OpenTelemetry variables and the SDK's own `INDUBITABLY_*` variables, so
+//! deployments can configure observability without code changes. Settings
+//! resolve in this order, first match wins:
+//! 
+//! 1. `INDUBITABLY_*` variables;
+//! 2. signal-specific OpenTelemetry variables, such as `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`;
+//! 3. general OpenTelemetry variables, such as `OTEL_EXPORTER_OTLP_ENDPOINT`;
+//! 4. the programmatic configuration;
+//! 5. the defaults.
+//! 
+//! A signal with an endpoint from the environment is enabled unless its
+//! `INDUBITABLY_*_ENABLED` variable says otherwise, and `OTEL_SDK_DISABLED=true`
+//! disables both signals. Empty variables count as unset.
+
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
+use super::sink::OtlpMetricsSink;
+use crate::models::HttpClient;
+
+/// The environment variable holding the base OTLP endpoint for all signals.
+pub const OTEL_EXPORTER_OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// The environment variable holding the OTLP metrics endpoint.
+pub const OTEL_EXPORTER_OTLP_METRICS_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT";
+
+/// The environment variable holding the OTLP traces endpoint.
+pub const OTEL_EXPORTER_OTLP_TRACES_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT";
+
+/// The environment variable holding the service name.
+pub const OTEL_SERVICE_NAME_ENV: &str = "OTEL_SERVICE_NAME";
+
+/// The environment variable disabling telemetry when `true`.
+pub const OTEL_SDK_DISABLED_ENV: &str = "OTEL_SDK_DISABLED";
+
+/// The environment variable enabling or disabling metrics.
+pub const INDUBITABLY_METRICS_ENABLED_ENV: &str = "INDUBITABLY_METRICS_ENABLED";
+
+/// The environment variable enabling or disabling tracing.
+pub const INDUBITABLY_TRACING_ENABLED_ENV: &str = "INDUBITABLY_TRACING_ENABLED";
+
+/// The environment variable holding the metrics endpoint.
+pub const INDUBITABLY_METRICS_ENDPOINT_ENV: &str = "INDUBITABLY_METRICS_ENDPOINT";
+
+/// The environment variable holding the tracing endpoint.
+pub const INDUBITABLY_TRACING_ENDPOINT_ENV: &str = "INDUBITABLY_TRACING_ENDPOINT";
+
+/// The environment variable holding the service name.
+pub const INDUBITABLY_SERVICE_NAME_ENV: &str = "INDUBITABLY_SERVICE_NAME";
+
 /// Configuration for telemetry features.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TelemetryConfig {
@@ -16,6 +66,9 @@ pub struct TelemetryConfig {
     pub metrics_endpoint: Option<String>,
     /// The tracing endpoint.
     pub tracing_endpoint: Option<String>,
+    /// The service name reported with telemetry.
+    #[serde(default)]
+    pub service_name: Option<String>,
 }
 
 impl Default for TelemetryConfig {
@@ -25,6 +78,7 @@ impl Default for TelemetryConfig {
             tracing_enabled: false,
             metrics_endpoint: None,
             tracing_endpoint: None,
+            service_name: None,
         }
     }
 }
@@ -34,6 +88,67 @@ impl TelemetryConfig {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Create a configuration from the environment alone.
+    pub fn from_env() -> Self {
+        Self::default().with_env_overrides()
+    }
+
+    /// Override this configuration with the environment.
+    pub fn with_env_overrides(self) -> Self {
+        self.with_overrides_from(|name| std::env::var(name).ok())
+    }
+
+    /// Override this configuration with variables read through `lookup`, with the same precedence as the environment.
+    pub fn with_overrides_from<F>(mut self, lookup: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name: &str| lookup(name).filter(|value| !value.trim().is_empty());
+        let flag = |name: &str| {
+            let value = var(name)?;
+            match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Some(true),
+                "false" | "0" | "no" | "off" => Some(false),
+                _ => {
+                    tracing::warn!("variable=<{}>, value=<{}> | ignoring invalid boolean", name, value);
+                    None
+                }
+            }
+        };
+        let base = var(OTEL_EXPORTER_OTLP_ENDPOINT_ENV).map(|base| base.trim_end_matches('/').to_string());
+
+        let metrics_endpoint = var(INDUBITABLY_METRICS_ENDPOINT_ENV)
+            .or_else(|| var(OTEL_EXPORTER_OTLP_METRICS_ENDPOINT_ENV))
+            .or_else(|| base.as_ref().map(|base| format!("{}/v1/metrics", base)));
+        let tracing_endpoint = var(INDUBITABLY_TRACING_ENDPOINT_ENV)
+            .or_else(|| var(OTEL_EXPORTER_OTLP_TRACES_ENDPOINT_ENV))
+            .or_else(|| base.as_ref().map(|base| format!("{}/v1/traces", base)));
+        let disabled = flag(OTEL_SDK_DISABLED_ENV) == Some(true);
+
+        if let Some(endpoint) = metrics_endpoint {
+            self.metrics_endpoint = Some(endpoint);
+            self.metrics_enabled = true;
+        }
+        if let Some(endpoint) = tracing_endpoint {
+            self.tracing_endpoint = Some(endpoint);
+            self.tracing_enabled = true;
+        }
+        if disabled {
+            self.metrics_enabled = false;
+            self.tracing_enabled = false;
+        }
+        if let Some(enabled) = flag(INDUBITABLY_METRICS_ENABLED_ENV) {
+            self.metrics_enabled = enabled;
+        }
+        if let Some(enabled) = flag(INDUBITABLY_TRACING_ENABLED_ENV) {
+            self.tracing_enabled = enabled;
+        }
+        if let Some(service_name) = var(INDUBITABLY_SERVICE_NAME_ENV).or_else(|| var(OTEL_SERVICE_NAME_ENV)) {
+            self.service_name = Some(service_name);
+        }
+        self
+    }
     
     /// Enable metrics.
     pub fn with_metrics(mut self, enabled: bool) -> Self {
@@ -58,4 +173,57 @@ impl TelemetryConfig {
         self.tracing_endpoint = Some(endpoint.to_string());
         self
     }
+
+    /// Set the service name reported with telemetry.
+    pub fn with_service_name(mut self, service_name: &str) -> Self {
+        self.service_name = Some(service_name.to_string());
+        self
+    }
+
+    /// Create an OTLP metrics sink for this configuration, if metrics are enabled and have an endpoint.
+    pub fn otlp_metrics_sink(&self, client: Arc<dyn HttpClient>) -> Option<OtlpMetricsSink> {
+        let endpoint = self.metrics_endpoint.as_deref().filter(|_| self.metrics_enabled)?;
+        let sink = OtlpMetricsSink::new(client, endpoint);
+        Some(match self.service_name {
+            Some(ref service_name) => sink.with_service_name(service_name),
+            None => sink,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_env_overrides_follow_precedence() {
+        let env = HashMap::from([
+            (OTEL_EXPORTER_OTLP_ENDPOINT_ENV, "http://collector:4318/"),
+            (OTEL_EXPORTER_OTLP_TRACES_ENDPOINT_ENV, "http://traces:4318/v1/traces"),
+            (OTEL_SERVICE_NAME_ENV, "checkout"),
+            (INDUBITABLY_SERVICE_NAME_ENV, "checkout-agent"),
+            (INDUBITABLY_TRACING_ENABLED_ENV, "false"),
+            (INDUBITABLY_METRICS_ENDPOINT_ENV, ""),
+        ]);
+        let lookup = |name: &str| env.get(name).map(|value| value.to_string());
+
+        let config = TelemetryConfig::new()
+            .with_tracing(true)
+            .with_metrics_endpoint("http://localhost:4318/v1/metrics")
+            .with_service_name("programmatic")
+            .with_overrides_from(lookup);
+        assert!(config.metrics_enabled);
+        assert_eq!(config.metrics_endpoint.as_deref(), Some("http://collector:4318/v1/metrics"));
+        assert!(!config.tracing_enabled);
+        assert_eq!(config.tracing_endpoint.as_deref(), Some("http://traces:4318/v1/traces"));
+        assert_eq!(config.service_name.as_deref(), Some("checkout-agent"));
+
+        let untouched = TelemetryConfig::new().with_metrics(true).with_overrides_from(|_| None);
+        assert!(untouched.metrics_enabled && untouched.metrics_endpoint.is_none());
+
+        let disabled = TelemetryConfig::new()
+            .with_overrides_from(|name| (name == OTEL_SDK_DISABLED_ENV).then(|| "true".to_string()));
+        assert!(!disabled.metrics_enabled && !disabled.tracing_enabled);
+    }
 }
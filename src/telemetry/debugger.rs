@@ -0,0 +1,222 @@
+//! Time-travel debugging for recorded runs.
+//! 
+//! This module provides `RunDebugger`, which loads a run captured by the
+//! recorder and steps through it cycle by cycle. The request of any cycle
+//! can be inspected, edited and re-executed against a live model.
+
+use std::path::Path;
+use chrono::Utc;
+
+use crate::models::model::{Model, ModelResponse};
+use crate::types::{IndubitablyError, IndubitablyResult};
+use super::recorder::{RecordedCycle, RecordedRequest, RecordedRun};
+
+/// A debugger that steps through a recorded run.
+#[derive(Debug, Clone)]
+pub struct RunDebugger {
+    /// The run being debugged, including any re-executed cycles.
+    run: RecordedRun,
+    /// The index of the current cycle.
+    position: usize,
+}
+
+impl RunDebugger {
+    /// Create a debugger positioned at the first cycle of a run.
+    pub fn new(run: RecordedRun) -> IndubitablyResult<Self> {
+        if run.cycles.is_empty() {
+            return Err(IndubitablyError::ValidationError(
+                "Recorded run contains no cycles".to_string(),
+            ));
+        }
+        Ok(Self { run, position: 0 })
+    }
+
+    /// Load a recorded run from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        Self::new(RecordedRun::load(path)?)
+    }
+
+    /// Get the run being debugged.
+    pub fn run(&self) -> &RecordedRun {
+        &self.run
+    }
+
+    /// Get the index of the current cycle.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the number of cycles in the run.
+    pub fn len(&self) -> usize {
+        self.run.cycles.len()
+    }
+
+    /// Check if the run has no cycles.
+    pub fn is_empty(&self) -> bool {
+        self.run.cycles.is_empty()
+    }
+
+    /// Get the current cycle.
+    pub fn current(&self) -> &RecordedCycle {
+        &self.run.cycles[self.position]
+    }
+
+    /// Get the exact request sent to the model in the current cycle.
+    pub fn request(&self) -> &RecordedRequest {
+        &self.current().request
+    }
+
+    /// Get the current request for modification before re-executing it.
+    pub fn request_mut(&mut self) -> &mut RecordedRequest {
+        &mut self.run.cycles[self.position].request
+    }
+
+    /// Get the recorded response of the current cycle.
+    pub fn response(&self) -> Option<&ModelResponse> {
+        self.current().response.as_ref()
+    }
+
+    /// Step forward one cycle, returning the new current cycle.
+    pub fn step(&mut self) -> Option<&RecordedCycle> {
+        if self.position + 1 >= self.run.cycles.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(self.current())
+    }
+
+    /// Step back one cycle, returning the new current cycle.
+    pub fn step_back(&mut self) -> Option<&RecordedCycle> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        Some(self.current())
+    }
+
+    /// Jump to the cycle at the given index.
+    pub fn seek(&mut self, index: usize) -> IndubitablyResult<&RecordedCycle> {
+        if index >= self.run.cycles.len() {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Cycle index {} out of range for run with {} cycles",
+                index,
+                self.run.cycles.len()
+            )));
+        }
+        self.position = index;
+        Ok(self.current())
+    }
+
+    /// Re-execute the current request against a live model.
+    ///
+    /// The request is sent with its recorded model configuration, such as the
+    /// model ID, sampling parameters and strict tool mode, and its recorded
+    /// tool specs. The model keeps its own middleware, which is not recorded,
+    /// and gets its configuration back afterwards.
+    ///
+    /// The recorded cycles after the current one no longer follow from the
+    /// new response, so they are discarded and the run continues from here.
+    pub async fn replay(&mut self, model: &mut dyn Model) -> IndubitablyResult<&ModelResponse> {
+        let request = self.request().clone();
+        let live_config = model.config().clone();
+        let mut config = request.config.clone();
+        config.middleware = live_config.middleware.clone();
+        model.update_config(config);
+        let result = model
+            .generate(
+                &request.messages,
+                request.tool_specs.as_deref(),
+                request.system_prompt.as_deref(),
+            )
+            .await;
+        model.update_config(live_config);
+
+        self.run.cycles.truncate(self.position + 1);
+        let cycle = &mut self.run.cycles[self.position];
+        cycle.timestamp = Utc::now();
+        cycle.streamed = false;
+
+        match result {
+            Ok(response) => {
+                cycle.error = None;
+                Ok(cycle.response.insert(response))
+            }
+            Err(e) => {
+                cycle.response = None;
+                cycle.error = Some(e.to_string());
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::MockModel;
+    use crate::telemetry::recorder::{RecordingModel, RunRecorder};
+    use crate::types::Message;
+
+    async fn recorded_run() -> RecordedRun {
+        let recorder = RunRecorder::new();
+        let mut model = RecordingModel::new(Box::new(MockModel::new()), recorder.clone());
+        model.config_mut().model_id = "recorded-model".to_string();
+        model.config_mut().temperature = Some(0.2);
+        model.config_mut().strict_tools = true;
+        for text in ["first", "second", "third"] {
+            model.generate(&vec![Message::user(text)], None, None).await.unwrap();
+        }
+        recorder.snapshot()
+    }
+
+    #[tokio::test]
+    async fn test_step_through_run() {
+        let mut debugger = RunDebugger::new(recorded_run().await).unwrap();
+        assert_eq!(debugger.len(), 3);
+        assert_eq!(debugger.request().messages[0].all_text(), "first");
+
+        assert!(debugger.step().is_some());
+        assert!(debugger.step().is_some());
+        assert!(debugger.step().is_none());
+        assert_eq!(debugger.position(), 2);
+
+        assert!(debugger.step_back().is_some());
+        assert_eq!(debugger.request().messages[0].all_text(), "second");
+        assert!(debugger.seek(5).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_modify_and_replay() {
+        let mut debugger = RunDebugger::new(recorded_run().await).unwrap();
+        debugger.seek(1).unwrap();
+        debugger.request_mut().system_prompt = Some("Answer in French.".to_string());
+
+        let response = debugger.replay(&mut MockModel::new()).await.unwrap();
+        assert!(!response.content.is_empty());
+        assert_eq!(debugger.len(), 2);
+        assert_eq!(debugger.request().system_prompt.as_deref(), Some("Answer in French."));
+    }
+
+    #[tokio::test]
+    async fn test_replay_applies_recorded_config() {
+        let mut debugger = RunDebugger::new(recorded_run().await).unwrap();
+        let recorder = RunRecorder::new();
+        let mut live = RecordingModel::new(Box::new(MockModel::new()), recorder.clone());
+        live.config_mut().model_id = "live-model".to_string();
+
+        debugger.replay(&mut live).await.unwrap();
+
+        let replayed = &recorder.snapshot().cycles[0].request.config;
+        assert_eq!(replayed.model_id, "recorded-model");
+        assert_eq!(replayed.temperature, Some(0.2));
+        assert!(replayed.strict_tools);
+        assert_eq!(live.config().model_id, "live-model");
+        assert_eq!(live.config().temperature, Some(0.7));
+        assert!(!live.config().strict_tools);
+    }
+
+    #[test]
+    fn test_empty_run_rejected() {
+        assert!(RunDebugger::new(RecordedRun::new()).is_err());
+    }
+}
@@ -0,0 +1,595 @@
+//! Lifecycle event bus for the SDK.
+//! 
+//! This module provides `EventBus`, the single place subsystems publish
+//! structured lifecycle events to. Hooks, metrics, tracing, webhooks and
+//! recorders are subscribers, so each event is produced once and every
+//! consumer sees the same payload.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::metrics::Metrics;
+use crate::hooks::{
+    HookEvent, HookRegistry, BUDGET_WARNING_EVENT, EXPIRED_CONTENT_RELEVANT_EVENT, FEEDBACK_RECEIVED_EVENT,
+    GUARDRAIL_APPEALED_EVENT, GUARDRAIL_BLOCKED_EVENT, MODEL_FAILOVER_EVENT, RUN_ABORTED_EVENT, RUN_HEARTBEAT_EVENT,
+    SECRET_LEAK_BLOCKED_EVENT, SIGNATURE_VERIFICATION_FAILED_EVENT,
+};
+use crate::models::{HttpClient, HttpRequest};
+use crate::types::{HookError, IndubitablyError, IndubitablyResult, TelemetryError};
+
+/// The metric counting responses generated by the model.
+pub const METRIC_RESPONSES_NORMAL: &str = "agent.responses.normal";
+
+/// The metric counting responses produced in degraded mode.
+pub const METRIC_RESPONSES_DEGRADED: &str = "agent.responses.degraded";
+
+/// The metric counting runs interrupted to ask for clarification.
+pub const METRIC_RUNS_INTERRUPTED: &str = "agent.runs.interrupted";
+
+/// The metric counting runs aborted by the liveness watchdog.
+pub const METRIC_RUNS_ABORTED: &str = "agent.runs.aborted";
+
+/// The metric counting runs whose input or output a guardrail blocked.
+pub const METRIC_GUARDRAIL_BLOCKS: &str = "agent.guardrail.blocks";
+
+/// The metric counting failed model calls.
+pub const METRIC_MODEL_ERRORS: &str = "agent.model.errors";
+
+/// The metric counting model calls failed over to a fallback model.
+pub const METRIC_MODEL_FAILOVERS: &str = "agent.model.failovers";
+
+/// The metric counting input tokens sent to the model.
+pub const METRIC_INPUT_TOKENS: &str = "agent.tokens.input";
+
+/// The metric counting output tokens generated by the model.
+pub const METRIC_OUTPUT_TOKENS: &str = "agent.tokens.output";
+
+/// The metric counting output tokens spent on reasoning.
+pub const METRIC_REASONING_TOKENS: &str = "agent.tokens.reasoning";
+
+/// The metric counting tokens removed from requests by context compression.
+pub const METRIC_COMPRESSION_TOKENS_SAVED: &str = "agent.compression.tokens_saved";
+
+/// The metric counting tool calls.
+pub const METRIC_TOOL_CALLS: &str = "agent.tools.calls";
+
+/// The metric counting tool calls that returned an error.
+pub const METRIC_TOOL_ERRORS: &str = "agent.tools.errors";
+
+/// The metric counting tool calls whose spec was in the selected set sent to the model.
+pub const METRIC_TOOL_SELECTION_HITS: &str = "agent.tools.selection.hits";
+
+/// The metric counting tool calls whose spec was left out by tool selection.
+pub const METRIC_TOOL_SELECTION_MISSES: &str = "agent.tools.selection.misses";
+
+/// The metric counting thumbs-up ratings.
+pub const METRIC_FEEDBACK_THUMBS_UP: &str = "agent.feedback.thumbs_up";
+
+/// The metric counting thumbs-down ratings.
+pub const METRIC_FEEDBACK_THUMBS_DOWN: &str = "agent.feedback.thumbs_down";
+
+/// The gauge holding the share of ratings that are thumbs-up.
+pub const METRIC_FEEDBACK_THUMBS_UP_RATE: &str = "agent.feedback.thumbs_up_rate";
+
+/// The kind of a lifecycle event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleEventKind {
+    /// A run started for a user message.
+    RunStarted,
+    /// A run finished with an answer, a degraded response or an interrupt.
+    RunCompleted,
+    /// The model returned a response.
+    ModelCallCompleted,
+    /// The model call failed.
+    ModelCallFailed,
+    /// A fallback model failed over to its next model.
+    ModelFailover,
+    /// A tool started executing.
+    ToolStarted,
+    /// A tool finished executing.
+    ToolCompleted,
+    /// The conversation is approaching its budget.
+    BudgetWarning,
+    /// A signed artifact failed verification.
+    SignatureVerificationFailed,
+    /// A secret was masked in model output or tool input.
+    SecretLeakBlocked,
+    /// A user rated an agent response.
+    FeedbackReceived,
+    /// A guardrail blocked user input or model output.
+    GuardrailBlocked,
+    /// A guardrail decision was appealed or its review was decided.
+    GuardrailAppealed,
+    /// A long run is still in progress.
+    RunHeartbeat,
+    /// The liveness watchdog aborted a run past its hard ceiling.
+    RunAborted,
+    /// A new query touches content that aged out of the conversation.
+    ExpiredContentRelevant,
+}
+
+impl LifecycleEventKind {
+    /// Get the event name, which is also the hook event type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RunStarted => "run_started",
+            Self::RunCompleted => "run_completed",
+            Self::ModelCallCompleted => "model_call_completed",
+            Self::ModelCallFailed => "model_call_failed",
+            Self::ModelFailover => MODEL_FAILOVER_EVENT,
+            Self::ToolStarted => "tool_started",
+            Self::ToolCompleted => "tool_completed",
+            Self::BudgetWarning => BUDGET_WARNING_EVENT,
+            Self::SignatureVerificationFailed => SIGNATURE_VERIFICATION_FAILED_EVENT,
+            Self::SecretLeakBlocked => SECRET_LEAK_BLOCKED_EVENT,
+            Self::FeedbackReceived => FEEDBACK_RECEIVED_EVENT,
+            Self::GuardrailBlocked => GUARDRAIL_BLOCKED_EVENT,
+            Self::GuardrailAppealed => GUARDRAIL_APPEALED_EVENT,
+            Self::RunHeartbeat => RUN_HEARTBEAT_EVENT,
+            Self::RunAborted => RUN_ABORTED_EVENT,
+            Self::ExpiredContentRelevant => EXPIRED_CONTENT_RELEVANT_EVENT,
+        }
+    }
+
+    /// Get a short human-readable description used in logs.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::RunStarted => "run started",
+            Self::RunCompleted => "run completed",
+            Self::ModelCallCompleted => "model call completed",
+            Self::ModelCallFailed => "model call failed",
+            Self::ModelFailover => "model failed over",
+            Self::ToolStarted => "tool started",
+            Self::ToolCompleted => "tool completed",
+            Self::BudgetWarning => "conversation is approaching its budget",
+            Self::SignatureVerificationFailed => "signature verification failed",
+            Self::SecretLeakBlocked => "secret leak blocked",
+            Self::FeedbackReceived => "feedback received",
+            Self::GuardrailBlocked => "guardrail blocked content",
+            Self::GuardrailAppealed => "guardrail decision appealed",
+            Self::RunHeartbeat => "run still in progress",
+            Self::RunAborted => "run aborted by the liveness watchdog",
+            Self::ExpiredContentRelevant => "query touches expired content",
+        }
+    }
+}
+
+/// A structured lifecycle event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    /// The kind of event.
+    pub kind: LifecycleEventKind,
+    /// The publishing subsystem, such as the agent name.
+    pub source: String,
+    /// When the event occurred.
+    pub timestamp: DateTime<Utc>,
+    /// The event payload.
+    pub data: Value,
+}
+
+impl LifecycleEvent {
+    /// Create a new event stamped with the current time.
+    pub fn new(kind: LifecycleEventKind, source: &str, data: Value) -> Self {
+        Self {
+            kind,
+            source: source.to_string(),
+            timestamp: Utc::now(),
+            data,
+        }
+    }
+
+    /// Get a field of the payload.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.data.get(key)
+    }
+}
+
+/// A consumer of lifecycle events.
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    /// Get the subscriber's name, used in logs.
+    fn name(&self) -> &str;
+
+    /// Handle an event.
+    async fn on_event(&self, event: &LifecycleEvent) -> IndubitablyResult<()>;
+}
+
+/// A bus that delivers lifecycle events to its subscribers.
+///
+/// Clones share the same subscriber list.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<RwLock<Vec<Arc<dyn EventSubscriber>>>>,
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscriber_names())
+            .finish()
+    }
+}
+
+impl EventBus {
+    /// Create a new bus with no subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a subscriber.
+    pub fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) {
+        self.subscribers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(subscriber);
+    }
+
+    /// Get the names of the subscribers, in delivery order.
+    pub fn subscriber_names(&self) -> Vec<String> {
+        self.subscribers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|subscriber| subscriber.name().to_string())
+            .collect()
+    }
+
+    /// Deliver an event to every subscriber.
+    ///
+    /// Subscriber failures are logged and do not stop delivery to the others.
+    pub async fn publish(&self, event: LifecycleEvent) {
+        let subscribers = self.subscribers.read().unwrap_or_else(|e| e.into_inner()).clone();
+        for subscriber in subscribers {
+            if let Err(e) = subscriber.on_event(&event).await {
+                tracing::warn!(
+                    "subscriber=<{}>, event=<{}>, error=<{}> | event subscriber failed",
+                    subscriber.name(),
+                    event.kind.as_str(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Forwards events to hooks registered for their event type.
+pub struct HookSubscriber {
+    hooks: HookRegistry,
+}
+
+impl HookSubscriber {
+    /// Create a subscriber that triggers the given hooks.
+    pub fn new(hooks: HookRegistry) -> Self {
+        Self { hooks }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for HookSubscriber {
+    fn name(&self) -> &str {
+        "hooks"
+    }
+
+    async fn on_event(&self, event: &LifecycleEvent) -> IndubitablyResult<()> {
+        self.hooks
+            .trigger_hooks(HookEvent::new(event.kind.as_str(), event.data.clone()))
+            .await
+            .map_err(|e| IndubitablyError::HookError(HookError::ExecutionFailed(e.to_string())))
+    }
+}
+
+/// Aggregates events into counters.
+#[derive(Clone, Default)]
+pub struct MetricsSubscriber {
+    metrics: Arc<Mutex<Metrics>>,
+}
+
+impl MetricsSubscriber {
+    /// Create a subscriber with empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a copy of the current metrics.
+    pub fn snapshot(&self) -> Metrics {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for MetricsSubscriber {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    async fn on_event(&self, event: &LifecycleEvent) -> IndubitablyResult<()> {
+        // Events from a session in an experiment are also counted under its variant labels.
+        let labels: BTreeMap<String, String> = event
+            .get("experiments")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default();
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        let mut increment = |name: &str, value: f64| {
+            metrics.increment(name, value);
+            if !labels.is_empty() {
+                metrics.increment_labeled(name, &labels, value);
+            }
+        };
+        match event.kind {
+            LifecycleEventKind::RunCompleted => match event.get("outcome").and_then(Value::as_str) {
+                Some("answered") => increment(METRIC_RESPONSES_NORMAL, 1.0),
+                Some("degraded") => {
+                    increment(METRIC_RESPONSES_DEGRADED, 1.0);
+                    if let Some(kind) = event.get("degraded_kind").and_then(Value::as_str) {
+                        increment(&format!("{}.{}", METRIC_RESPONSES_DEGRADED, kind), 1.0);
+                    }
+                }
+                Some("interrupted") => increment(METRIC_RUNS_INTERRUPTED, 1.0),
+                _ => {}
+            },
+            LifecycleEventKind::ModelCallCompleted => {
+                let tokens = |key: &str| event.get(key).and_then(Value::as_f64).unwrap_or(0.0);
+                increment(METRIC_INPUT_TOKENS, tokens("input_tokens"));
+                increment(METRIC_OUTPUT_TOKENS, tokens("output_tokens"));
+                increment(METRIC_REASONING_TOKENS, tokens("reasoning_tokens"));
+                increment(METRIC_COMPRESSION_TOKENS_SAVED, tokens("compression_tokens_saved"));
+            }
+            LifecycleEventKind::ModelCallFailed => increment(METRIC_MODEL_ERRORS, 1.0),
+            LifecycleEventKind::ModelFailover => increment(METRIC_MODEL_FAILOVERS, 1.0),
+            LifecycleEventKind::RunAborted => increment(METRIC_RUNS_ABORTED, 1.0),
+            LifecycleEventKind::GuardrailBlocked => increment(METRIC_GUARDRAIL_BLOCKS, 1.0),
+            LifecycleEventKind::ToolStarted => match event.get("selected").and_then(Value::as_bool) {
+                Some(true) => increment(METRIC_TOOL_SELECTION_HITS, 1.0),
+                Some(false) => increment(METRIC_TOOL_SELECTION_MISSES, 1.0),
+                None => {}
+            },
+            LifecycleEventKind::ToolCompleted => {
+                increment(METRIC_TOOL_CALLS, 1.0);
+                if event.get("is_error").and_then(Value::as_bool) == Some(true) {
+                    increment(METRIC_TOOL_ERRORS, 1.0);
+                }
+            }
+            LifecycleEventKind::FeedbackReceived => {
+                match event.get("rating").and_then(Value::as_str) {
+                    Some("thumbs_up") => increment(METRIC_FEEDBACK_THUMBS_UP, 1.0),
+                    Some("thumbs_down") => increment(METRIC_FEEDBACK_THUMBS_DOWN, 1.0),
+                    _ => return Ok(()),
+                }
+                let up = metrics.get(METRIC_FEEDBACK_THUMBS_UP).unwrap_or(0.0);
+                let down = metrics.get(METRIC_FEEDBACK_THUMBS_DOWN).unwrap_or(0.0);
+                metrics.set(METRIC_FEEDBACK_THUMBS_UP_RATE, up / (up + down));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Logs events through `tracing` at a level matching their severity.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingSubscriber;
+
+#[async_trait]
+impl EventSubscriber for TracingSubscriber {
+    fn name(&self) -> &str {
+        "tracing"
+    }
+
+    async fn on_event(&self, event: &LifecycleEvent) -> IndubitablyResult<()> {
+        let description = event.kind.description();
+        match event.kind {
+            LifecycleEventKind::ModelCallFailed
+            | LifecycleEventKind::ModelFailover
+            | LifecycleEventKind::BudgetWarning
+            | LifecycleEventKind::SignatureVerificationFailed
+            | LifecycleEventKind::SecretLeakBlocked
+            | LifecycleEventKind::GuardrailBlocked
+            | LifecycleEventKind::RunAborted => {
+                tracing::warn!("source=<{}>, data=<{}> | {}", event.source, event.data, description)
+            }
+            LifecycleEventKind::RunCompleted if event.get("outcome").and_then(Value::as_str) != Some("answered") => {
+                tracing::info!("source=<{}>, data=<{}> | {}", event.source, event.data, description)
+            }
+            _ => tracing::debug!("source=<{}>, data=<{}> | {}", event.source, event.data, description),
+        }
+        Ok(())
+    }
+}
+
+/// Posts events as JSON to a webhook URL.
+pub struct WebhookSubscriber {
+    client: Arc<dyn HttpClient>,
+    url: String,
+    kinds: Option<Vec<LifecycleEventKind>>,
+}
+
+impl WebhookSubscriber {
+    /// Create a subscriber that posts every event to the given URL.
+    pub fn new(client: Arc<dyn HttpClient>, url: &str) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            kinds: None,
+        }
+    }
+
+    /// Only post events of the given kinds.
+    pub fn with_kinds(mut self, kinds: Vec<LifecycleEventKind>) -> Self {
+        self.kinds = Some(kinds);
+        self
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for WebhookSubscriber {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn on_event(&self, event: &LifecycleEvent) -> IndubitablyResult<()> {
+        if let Some(ref kinds) = self.kinds {
+            if !kinds.contains(&event.kind) {
+                return Ok(());
+            }
+        }
+
+        let response = self.client.send(HttpRequest::post(&self.url).with_json_body(event)?).await?;
+        if !response.is_success() {
+            return Err(TelemetryError::ExportFailed(format!(
+                "Webhook returned status {}",
+                response.status
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Keeps published events in memory, optionally only the most recent ones.
+#[derive(Debug, Clone, Default)]
+pub struct EventRecorder {
+    events: Arc<Mutex<VecDeque<LifecycleEvent>>>,
+    capacity: Option<usize>,
+}
+
+impl EventRecorder {
+    /// Create a new empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only the given number of most recent events.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity.max(1));
+        self
+    }
+
+    /// Get a copy of the recorded events.
+    pub fn events(&self) -> Vec<LifecycleEvent> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    /// Get the kinds of the recorded events, in order.
+    pub fn kinds(&self) -> Vec<LifecycleEventKind> {
+        self.events().iter().map(|event| event.kind).collect()
+    }
+
+    /// Keep only the events the predicate accepts, which may also edit them, and return how many were removed.
+    pub fn retain(&self, mut keep: impl FnMut(&mut LifecycleEvent) -> bool) -> usize {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        let before = events.len();
+        events.retain_mut(|event| keep(event));
+        before - events.len()
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for EventRecorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+
+    async fn on_event(&self, event: &LifecycleEvent) -> IndubitablyResult<()> {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(capacity) = self.capacity {
+            while events.len() >= capacity {
+                events.pop_front();
+            }
+        }
+        events.push_back(event.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HttpResponse;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct FailingSubscriber;
+
+    #[async_trait]
+    impl EventSubscriber for FailingSubscriber {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn on_event(&self, _event: &LifecycleEvent) -> IndubitablyResult<()> {
+            Err(IndubitablyError::InternalError("boom".to_string()))
+        }
+    }
+
+    struct CapturingClient {
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for CapturingClient {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.requests.lock().unwrap().push(request);
+            Ok(HttpResponse::new(204, Vec::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bus_delivers_to_all_subscribers() {
+        let bus = EventBus::new();
+        let hooks = HookRegistry::new();
+        let budget_hooks = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&budget_hooks);
+        hooks
+            .register_hook(BUDGET_WARNING_EVENT, Box::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }))
+            .await;
+        let metrics = MetricsSubscriber::new();
+        let recorder = EventRecorder::new();
+        let client = Arc::new(CapturingClient { requests: Mutex::new(Vec::new()) });
+
+        bus.subscribe(Arc::new(FailingSubscriber));
+        bus.subscribe(Arc::new(HookSubscriber::new(hooks)));
+        bus.subscribe(Arc::new(metrics.clone()));
+        bus.subscribe(Arc::new(recorder.clone()));
+        bus.subscribe(Arc::new(
+            WebhookSubscriber::new(client.clone(), "https://hooks.example.com/agent")
+                .with_kinds(vec![LifecycleEventKind::BudgetWarning]),
+        ));
+
+        bus.publish(LifecycleEvent::new(LifecycleEventKind::BudgetWarning, "agent", json!({"fraction_used": 0.9}))).await;
+        bus.publish(LifecycleEvent::new(
+            LifecycleEventKind::RunCompleted,
+            "agent",
+            json!({"outcome": "degraded", "degraded_kind": "canned"}),
+        ))
+        .await;
+        bus.publish(LifecycleEvent::new(LifecycleEventKind::ToolCompleted, "agent", json!({"is_error": true}))).await;
+
+        assert_eq!(budget_hooks.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            recorder.kinds(),
+            vec![
+                LifecycleEventKind::BudgetWarning,
+                LifecycleEventKind::RunCompleted,
+                LifecycleEventKind::ToolCompleted
+            ]
+        );
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.get(METRIC_RESPONSES_DEGRADED), Some(1.0));
+        assert_eq!(snapshot.get("agent.responses.degraded.canned"), Some(1.0));
+        assert_eq!(snapshot.get(METRIC_TOOL_ERRORS), Some(1.0));
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["kind"], "budget_warning");
+    }
+}
@@ -3,9 +3,10 @@
 //! This module provides functionality for collecting and
 //! reporting metrics about agent performance and usage.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// A metrics collector for the SDK.
+#[derive(Debug, Clone)]
 pub struct Metrics {
     /// The metrics data.
     data: HashMap<String, f64>,
@@ -24,11 +25,33 @@ impl Metrics {
         *self.data.entry(name.to_string()).or_insert(0.0) += value;
     }
     
+    /// Increment a counter metric under a set of labels.
+    pub fn increment_labeled(&mut self, name: &str, labels: &BTreeMap<String, String>, value: f64) {
+        self.increment(&labeled_name(name, labels), value);
+    }
+    
     /// Set a gauge metric.
     pub fn set(&mut self, name: &str, value: f64) {
         self.data.insert(name.to_string(), value);
     }
     
+    /// Record an observation in a histogram under a set of labels.
+    ///
+    /// The histogram is kept as `.sum` and `.count` metrics plus a cumulative
+    /// `.bucket` metric per upper bound, labeled `le`, and one for `+Inf`.
+    pub fn observe(&mut self, name: &str, labels: &BTreeMap<String, String>, value: f64, bounds: &[f64]) {
+        self.increment_labeled(&format!("{}.sum", name), labels, value);
+        self.increment_labeled(&format!("{}.count", name), labels, 1.0);
+        let bucket = format!("{}.bucket", name);
+        let mut bucket_labels = labels.clone();
+        for bound in bounds.iter().filter(|bound| value <= **bound) {
+            bucket_labels.insert("le".to_string(), bound.to_string());
+            self.increment_labeled(&bucket, &bucket_labels, 1.0);
+        }
+        bucket_labels.insert("le".to_string(), "+Inf".to_string());
+        self.increment_labeled(&bucket, &bucket_labels, 1.0);
+    }
+    
     /// Get a metric value.
     pub fn get(&self, name: &str) -> Option<f64> {
         self.data.get(name).copied()
@@ -40,6 +63,15 @@ impl Metrics {
     }
 }
 
+/// Build the key of a labeled metric, such as `agent.tools.calls{prompt_v2="concise"}`.
+pub fn labeled_name(name: &str, labels: &BTreeMap<String, String>) -> String {
+    if labels.is_empty() {
+        return name.to_string();
+    }
+    let labels: Vec<String> = labels.iter().map(|(key, value)| format!("{}=\"{}\"", key, value)).collect();
+    format!("{}{{{}}}", name, labels.join(","))
+}
+
 impl Default for Metrics {
     fn default() -> Self {
         Self::new()
@@ -1,12 +1,28 @@
 //! Telemetry and observability for the SDK.
 //! 
 //! This module provides functionality for metrics, tracing,
-//! and other observability features.
+//! and other observability features. Subsystems publish lifecycle events
+//! to an `EventBus`, and metrics, hooks, tracing and exporters subscribe.
 
 pub mod metrics;
 pub mod tracer;
 pub mod config;
+pub mod recorder;
+pub mod debugger;
+pub mod events;
+pub mod backfill;
+pub mod timeline;
+pub mod sink;
 
 pub use metrics::Metrics;
 pub use tracer::Tracer;
 pub use config::TelemetryConfig;
+pub use recorder::{RecordedRun, RecordingModel, RunRecorder};
+pub use debugger::RunDebugger;
+pub use backfill::{from_sessions, BackfillReport, UsageAggregate};
+pub use timeline::{SpanCategory, Timeline, TimelineSpan};
+pub use sink::{MetricEvent, MetricsSink, OtlpMetricsSink, RegistryMetricsSink};
+pub use events::{
+    EventBus, EventRecorder, EventSubscriber, HookSubscriber, LifecycleEvent, LifecycleEventKind,
+    MetricsSubscriber, TracingSubscriber, WebhookSubscriber,
+};
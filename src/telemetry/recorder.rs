@@ -0,0 +1,245 @@
+//! Run recording for the SDK.
+//! 
+//! This module provides `RecordingModel`, a model wrapper that captures every
+//! request sent to the model together with its response, and `RecordedRun`,
+//! the serializable record used for replay and debugging.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ToolSpec};
+
+/// The exact request sent to the model in one cycle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// The model configuration at the time of the request.
+    pub config: ModelConfig,
+    /// The messages sent to the model.
+    pub messages: Messages,
+    /// The tool specifications sent to the model.
+    pub tool_specs: Option<Vec<ToolSpec>>,
+    /// The system prompt sent to the model.
+    pub system_prompt: Option<String>,
+}
+
+/// A single recorded model cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCycle {
+    /// The position of the cycle within the run.
+    pub index: usize,
+    /// When the request was sent.
+    pub timestamp: DateTime<Utc>,
+    /// The request sent to the model.
+    pub request: RecordedRequest,
+    /// The response returned by the model, if the call succeeded and was not streamed.
+    pub response: Option<ModelResponse>,
+    /// The error returned by the model, if the call failed.
+    pub error: Option<String>,
+    /// Whether the cycle used the streaming API.
+    pub streamed: bool,
+}
+
+/// A recorded agent run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRun {
+    /// The unique identifier of the run.
+    pub run_id: String,
+    /// When the recording started.
+    pub started_at: DateTime<Utc>,
+    /// The recorded model cycles, in order.
+    pub cycles: Vec<RecordedCycle>,
+}
+
+impl RecordedRun {
+    /// Create a new empty recorded run.
+    pub fn new() -> Self {
+        Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            started_at: Utc::now(),
+            cycles: Vec::new(),
+        }
+    }
+
+    /// Serialize the run to a JSON string.
+    pub fn to_json(&self) -> IndubitablyResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| IndubitablyError::InternalError(format!("Failed to serialize recorded run: {}", e)))
+    }
+
+    /// Deserialize a run from a JSON string.
+    pub fn from_json(json: &str) -> IndubitablyResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| IndubitablyError::ValidationError(format!("Invalid recorded run: {}", e)))
+    }
+
+    /// Save the run to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> IndubitablyResult<()> {
+        std::fs::write(path, self.to_json()?)
+            .map_err(|e| IndubitablyError::InternalError(format!("Failed to write recorded run: {}", e)))
+    }
+
+    /// Load a run from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| IndubitablyError::InternalError(format!("Failed to read recorded run: {}", e)))?;
+        Self::from_json(&json)
+    }
+}
+
+impl Default for RecordedRun {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A shared handle to a run being recorded.
+#[derive(Debug, Clone, Default)]
+pub struct RunRecorder {
+    run: Arc<Mutex<RecordedRun>>,
+}
+
+impl RunRecorder {
+    /// Create a new recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a cycle.
+    pub fn record(
+        &self,
+        request: RecordedRequest,
+        response: Option<ModelResponse>,
+        error: Option<String>,
+        streamed: bool,
+    ) {
+        let mut run = self.run.lock().unwrap_or_else(|e| e.into_inner());
+        let index = run.cycles.len();
+        run.cycles.push(RecordedCycle {
+            index,
+            timestamp: Utc::now(),
+            request,
+            response,
+            error,
+            streamed,
+        });
+    }
+
+    /// Get a copy of the run recorded so far.
+    pub fn snapshot(&self) -> RecordedRun {
+        self.run.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// A model wrapper that records every request and response.
+pub struct RecordingModel {
+    inner: Box<dyn Model>,
+    recorder: RunRecorder,
+}
+
+impl RecordingModel {
+    /// Wrap a model, recording into the given recorder.
+    pub fn new(inner: Box<dyn Model>, recorder: RunRecorder) -> Self {
+        Self { inner, recorder }
+    }
+
+    /// Get the recorder.
+    pub fn recorder(&self) -> &RunRecorder {
+        &self.recorder
+    }
+
+    fn request(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> RecordedRequest {
+        RecordedRequest {
+            config: self.inner.config().clone(),
+            messages: messages.clone(),
+            tool_specs: tool_specs.map(|specs| specs.to_vec()),
+            system_prompt: system_prompt.map(str::to_string),
+        }
+    }
+}
+
+#[async_trait]
+impl Model for RecordingModel {
+    fn config(&self) -> &ModelConfig {
+        self.inner.config()
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.inner.update_config(config);
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        self.inner.config_mut()
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        let request = self.request(messages, tool_specs, system_prompt);
+        let result = self.inner.generate(messages, tool_specs, system_prompt).await;
+        match &result {
+            Ok(response) => self.recorder.record(request, Some(response.clone()), None, false),
+            Err(e) => self.recorder.record(request, None, Some(e.to_string()), false),
+        }
+        result
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        let request = self.request(messages, tool_specs, system_prompt);
+        let result = self.inner.stream(messages, tool_specs, system_prompt).await;
+        let error = result.as_ref().err().map(|e| e.to_string());
+        self.recorder.record(request, None, error, true);
+        result
+    }
+
+    async fn structured_output(
+        &self,
+        output_model: &str,
+        messages: &Messages,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        self.inner.structured_output(output_model, messages, system_prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::MockModel;
+    use crate::types::Message;
+
+    #[tokio::test]
+    async fn test_recording_model_captures_cycles() {
+        let recorder = RunRecorder::new();
+        let model = RecordingModel::new(Box::new(MockModel::new()), recorder.clone());
+
+        let messages = vec![Message::user("Hello")];
+        model.generate(&messages, None, Some("Be brief.")).await.unwrap();
+
+        let run = recorder.snapshot();
+        assert_eq!(run.cycles.len(), 1);
+        assert_eq!(run.cycles[0].request.messages, messages);
+        assert_eq!(run.cycles[0].request.system_prompt.as_deref(), Some("Be brief."));
+        assert!(run.cycles[0].response.is_some());
+
+        let restored = RecordedRun::from_json(&run.to_json().unwrap()).unwrap();
+        assert_eq!(restored.run_id, run.run_id);
+        assert_eq!(restored.cycles[0].request, run.cycles[0].request);
+    }
+}
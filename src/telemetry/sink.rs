@@ -0,0 +1,478 @@
+//! Typed metrics sinks for the SDK.
+//! 
+//! The event loop reports cycle durations, model and tool latencies and
+//! token usage as `MetricEvent`s to a `MetricsSink`, together with labels
+//! such as the agent name. Agent graphs report node durations, handoffs and
+//! the number of active workflows the same way. `RegistryMetricsSink` folds them into a
+//! `Metrics` registry, and `OtlpMetricsSink` buffers them and exports them
+//! to an OpenTelemetry collector over OTLP/HTTP with JSON encoding.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use super::events::{METRIC_INPUT_TOKENS, METRIC_OUTPUT_TOKENS, METRIC_REASONING_TOKENS};
+use super::metrics::Metrics;
+use crate::models::http::{HttpClient, HttpRequest};
+use crate::types::{IndubitablyResult, TelemetryError};
+
+/// The metric recording the wall time of event loop cycles, in seconds.
+pub const METRIC_CYCLE_DURATION: &str = "agent.cycle.duration";
+
+/// The metric recording model call latency, in seconds.
+pub const METRIC_MODEL_LATENCY: &str = "agent.model.latency";
+
+/// The metric recording tool call latency, in seconds.
+pub const METRIC_TOOL_LATENCY: &str = "agent.tools.latency";
+
+/// The metric recording the wall time of agent graph nodes, in seconds, as a histogram.
+pub const METRIC_GRAPH_NODE_DURATION: &str = "multiagent.node.duration";
+
+/// The metric counting handoffs between agent graph nodes.
+pub const METRIC_GRAPH_HANDOFFS: &str = "multiagent.handoffs";
+
+/// The metric gauging the agent graph workflows currently executing.
+pub const METRIC_ACTIVE_WORKFLOWS: &str = "multiagent.workflows.active";
+
+/// The metric recording the time model calls waited for a rate limiter slot, in seconds.
+pub const METRIC_RATE_LIMIT_WAIT: &str = "agent.rate_limit.wait";
+
+/// The histogram bucket upper bounds for durations, in seconds.
+pub const DURATION_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// A measurement reported by the event loop or an agent graph.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricEvent {
+    /// One event loop cycle finished.
+    CycleDuration {
+        /// The 1-based cycle number within the run.
+        iteration: usize,
+        /// The wall time of the cycle.
+        duration: Duration,
+    },
+    /// A model call returned.
+    ModelLatency {
+        /// The model called.
+        model_id: String,
+        /// The time until the response or error.
+        duration: Duration,
+    },
+    /// A tool call returned.
+    ToolLatency {
+        /// The tool called.
+        tool_name: String,
+        /// The time until the result.
+        duration: Duration,
+        /// Whether the tool returned an error.
+        is_error: bool,
+    },
+    /// A model call reported or was estimated to use tokens.
+    TokensUsed {
+        /// The model called.
+        model_id: String,
+        /// The input tokens.
+        input: u64,
+        /// The output tokens, including reasoning.
+        output: u64,
+        /// The output tokens spent on reasoning.
+        reasoning: u64,
+    },
+    /// An agent graph node finished.
+    NodeDuration {
+        /// The graph the node belongs to.
+        workflow: String,
+        /// The node that ran.
+        node_id: String,
+        /// The wall time of the node.
+        duration: Duration,
+        /// Whether the node failed.
+        is_error: bool,
+    },
+    /// An agent graph handed off from one node to the next.
+    Handoff {
+        /// The graph the nodes belong to.
+        workflow: String,
+        /// The node handing off.
+        from: String,
+        /// The node taking over.
+        to: String,
+    },
+    /// A model call was granted a rate limiter slot.
+    RateLimitWait {
+        /// The session the call was made for, if any.
+        session: Option<String>,
+        /// The time the call waited for the slot.
+        duration: Duration,
+    },
+    /// The number of executing agent graph workflows changed.
+    ActiveWorkflows {
+        /// The workflows now executing in the process.
+        active: usize,
+    },
+}
+
+impl MetricEvent {
+    /// Get the labels identifying what the event measured, such as the model or tool.
+    pub fn labels(&self) -> BTreeMap<String, String> {
+        let mut labels = BTreeMap::new();
+        match self {
+            Self::CycleDuration { .. } | Self::ActiveWorkflows { .. } => {}
+            Self::ModelLatency { model_id, .. } | Self::TokensUsed { model_id, .. } => {
+                labels.insert("model".to_string(), model_id.clone());
+            }
+            Self::ToolLatency { tool_name, is_error, .. } => {
+                labels.insert("tool".to_string(), tool_name.clone());
+                labels.insert("is_error".to_string(), is_error.to_string());
+            }
+            Self::NodeDuration { workflow, node_id, is_error, .. } => {
+                labels.insert("workflow".to_string(), workflow.clone());
+                labels.insert("node".to_string(), node_id.clone());
+                labels.insert("is_error".to_string(), is_error.to_string());
+            }
+            Self::Handoff { workflow, from, to } => {
+                labels.insert("workflow".to_string(), workflow.clone());
+                labels.insert("from".to_string(), from.clone());
+                labels.insert("to".to_string(), to.clone());
+            }
+            Self::RateLimitWait { session, .. } => {
+                if let Some(session) = session {
+                    labels.insert("session".to_string(), session.clone());
+                }
+            }
+        }
+        labels
+    }
+
+    /// Get the metric names and values the event contributes.
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        match self {
+            Self::CycleDuration { duration, .. } => vec![(METRIC_CYCLE_DURATION, duration.as_secs_f64())],
+            Self::ModelLatency { duration, .. } => vec![(METRIC_MODEL_LATENCY, duration.as_secs_f64())],
+            Self::ToolLatency { duration, .. } => vec![(METRIC_TOOL_LATENCY, duration.as_secs_f64())],
+            Self::TokensUsed { input, output, reasoning, .. } => vec![
+                (METRIC_INPUT_TOKENS, *input as f64),
+                (METRIC_OUTPUT_TOKENS, *output as f64),
+                (METRIC_REASONING_TOKENS, *reasoning as f64),
+            ],
+            Self::NodeDuration { duration, .. } => vec![(METRIC_GRAPH_NODE_DURATION, duration.as_secs_f64())],
+            Self::Handoff { .. } => vec![(METRIC_GRAPH_HANDOFFS, 1.0)],
+            Self::RateLimitWait { duration, .. } => vec![(METRIC_RATE_LIMIT_WAIT, duration.as_secs_f64())],
+            Self::ActiveWorkflows { active } => vec![(METRIC_ACTIVE_WORKFLOWS, *active as f64)],
+        }
+    }
+}
+
+/// A destination for the typed metrics reported by the event loop.
+pub trait MetricsSink: Send + Sync {
+    /// Record an event under the given labels, in addition to the event's own.
+    fn record(&self, event: &MetricEvent, labels: &BTreeMap<String, String>);
+}
+
+/// Merge the caller's labels with the event's own, the event's taking precedence.
+fn merged_labels(event: &MetricEvent, labels: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    let mut merged = labels.clone();
+    merged.extend(event.labels());
+    merged
+}
+
+/// A sink folding events into a `Metrics` registry.
+///
+/// Each value is added to its metric both unlabeled and labeled; latencies
+/// also increment a `.count` companion so means can be derived. Node
+/// durations are recorded as histograms over `DURATION_BUCKETS`, and the
+/// active workflow gauge is set rather than added to.
+#[derive(Debug, Clone, Default)]
+pub struct RegistryMetricsSink {
+    metrics: Arc<Mutex<Metrics>>,
+}
+
+impl RegistryMetricsSink {
+    /// Create a sink with an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a sink writing into a shared registry.
+    pub fn with_registry(metrics: Arc<Mutex<Metrics>>) -> Self {
+        Self { metrics }
+    }
+
+    /// Get a copy of the current metrics.
+    pub fn snapshot(&self) -> Metrics {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl MetricsSink for RegistryMetricsSink {
+    fn record(&self, event: &MetricEvent, labels: &BTreeMap<String, String>) {
+        let labels = merged_labels(event, labels);
+        let counted = !matches!(event, MetricEvent::TokensUsed { .. } | MetricEvent::Handoff { .. });
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        for (name, value) in event.values() {
+            match event {
+                MetricEvent::NodeDuration { .. } => {
+                    metrics.observe(name, &BTreeMap::new(), value, DURATION_BUCKETS);
+                    metrics.observe(name, &labels, value, DURATION_BUCKETS);
+                    continue;
+                }
+                MetricEvent::ActiveWorkflows { .. } => {
+                    metrics.set(name, value);
+                    continue;
+                }
+                _ => {}
+            }
+            metrics.increment(name, value);
+            metrics.increment_labeled(name, &labels, value);
+            if counted {
+                let count = format!("{}.count", name);
+                metrics.increment(&count, 1.0);
+                metrics.increment_labeled(&count, &labels, 1.0);
+            }
+        }
+    }
+}
+
+/// One buffered measurement awaiting export.
+#[derive(Debug, Clone)]
+struct DataPoint {
+    name: &'static str,
+    value: f64,
+    labels: BTreeMap<String, String>,
+    time_unix_nano: u128,
+}
+
+/// A sink buffering events and exporting them to an OTLP/HTTP collector.
+///
+/// Recording never blocks on the network; call `flush` periodically or at
+/// the end of a run to post the buffered points.
+#[derive(Clone)]
+pub struct OtlpMetricsSink {
+    client: Arc<dyn HttpClient>,
+    endpoint: String,
+    service_name: String,
+    buffer: Arc<Mutex<Vec<DataPoint>>>,
+}
+
+impl std::fmt::Debug for OtlpMetricsSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtlpMetricsSink")
+            .field("endpoint", &self.endpoint)
+            .field("service_name", &self.service_name)
+            .finish()
+    }
+}
+
+impl OtlpMetricsSink {
+    /// Create a sink posting to the given metrics endpoint, such as `http://localhost:4318/v1/metrics`.
+    pub fn new(client: Arc<dyn HttpClient>, endpoint: &str) -> Self {
+        Self {
+            client,
+            endpoint: endpoint.to_string(),
+            service_name: "indubitably-agent".to_string(),
+            buffer: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Set the `service.name` resource attribute.
+    pub fn with_service_name(mut self, service_name: &str) -> Self {
+        self.service_name = service_name.to_string();
+        self
+    }
+
+    /// Get the number of points awaiting export.
+    pub fn pending(&self) -> usize {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Post the buffered points, returning how many were exported.
+    ///
+    /// Points are kept for the next flush if the collector rejects them.
+    pub async fn flush(&self) -> IndubitablyResult<usize> {
+        let points = std::mem::take(&mut *self.buffer.lock().unwrap_or_else(|e| e.into_inner()));
+        if points.is_empty() {
+            return Ok(0);
+        }
+
+        let request = HttpRequest::post(&self.endpoint).with_json_body(&self.export_request(&points))?;
+        let exported = match self.client.send(request).await {
+            Ok(response) if response.is_success() => Ok(points.len()),
+            Ok(response) => Err(TelemetryError::ExportFailed(format!(
+                "OTLP collector returned status {}",
+                response.status
+            ))
+            .into()),
+            Err(e) => Err(e),
+        };
+        if exported.is_err() {
+            let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+            let newer = std::mem::replace(&mut *buffer, points);
+            buffer.extend(newer);
+        }
+        exported
+    }
+
+    /// Build an OTLP `ExportMetricsServiceRequest` in its JSON encoding.
+    fn export_request(&self, points: &[DataPoint]) -> Value {
+        let mut by_name: BTreeMap<&str, Vec<&DataPoint>> = BTreeMap::new();
+        for point in points {
+            by_name.entry(point.name).or_default().push(point);
+        }
+        let metrics: Vec<Value> = by_name
+            .into_iter()
+            .map(|(name, points)| {
+                let data_points: Vec<Value> = points
+                    .iter()
+                    .map(|point| {
+                        json!({
+                            "attributes": attributes(&point.labels),
+                            "timeUnixNano": point.time_unix_nano.to_string(),
+                            "asDouble": point.value,
+                        })
+                    })
+                    .collect();
+                if name.starts_with("agent.tokens.") || name == METRIC_GRAPH_HANDOFFS {
+                    let unit = if name == METRIC_GRAPH_HANDOFFS { "{handoff}" } else { "{token}" };
+                    json!({
+                        "name": name,
+                        "unit": unit,
+                        "sum": {
+                            "dataPoints": data_points,
+                            "aggregationTemporality": 1,
+                            "isMonotonic": true,
+                        },
+                    })
+                } else if name == METRIC_GRAPH_NODE_DURATION {
+                    json!({
+                        "name": name,
+                        "unit": "s",
+                        "histogram": {
+                            "dataPoints": points.iter().map(|point| histogram_point(point)).collect::<Vec<Value>>(),
+                            "aggregationTemporality": 1,
+                        },
+                    })
+                } else if name == METRIC_ACTIVE_WORKFLOWS {
+                    json!({ "name": name, "unit": "{workflow}", "gauge": { "dataPoints": data_points } })
+                } else {
+                    json!({ "name": name, "unit": "s", "gauge": { "dataPoints": data_points } })
+                }
+            })
+            .collect();
+
+        let resource = BTreeMap::from([("service.name".to_string(), self.service_name.clone())]);
+        json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": attributes(&resource) },
+                "scopeMetrics": [{
+                    "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                    "metrics": metrics,
+                }],
+            }],
+        })
+    }
+}
+
+/// Convert a duration point to an OTLP histogram data point holding one observation.
+fn histogram_point(point: &DataPoint) -> Value {
+    let mut bucket_counts = vec![0u64; DURATION_BUCKETS.len() + 1];
+    let bucket = DURATION_BUCKETS
+        .iter()
+        .position(|bound| point.value <= *bound)
+        .unwrap_or(DURATION_BUCKETS.len());
+    bucket_counts[bucket] = 1;
+    json!({
+        "attributes": attributes(&point.labels),
+        "timeUnixNano": point.time_unix_nano.to_string(),
+        "count": "1",
+        "sum": point.value,
+        "bucketCounts": bucket_counts.iter().map(u64::to_string).collect::<Vec<String>>(),
+        "explicitBounds": DURATION_BUCKETS,
+    })
+}
+
+/// Convert labels to OTLP key-value attributes.
+fn attributes(labels: &BTreeMap<String, String>) -> Vec<Value> {
+    labels
+        .iter()
+        .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+        .collect()
+}
+
+impl MetricsSink for OtlpMetricsSink {
+    fn record(&self, event: &MetricEvent, labels: &BTreeMap<String, String>) {
+        let labels = merged_labels(event, labels);
+        let time_unix_nano = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos());
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        for (name, value) in event.values() {
+            buffer.push(DataPoint {
+                name,
+                value,
+                labels: labels.clone(),
+                time_unix_nano,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::http::HttpResponse;
+    use crate::telemetry::metrics::labeled_name;
+    use async_trait::async_trait;
+
+    struct CapturingClient {
+        bodies: Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for CapturingClient {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.bodies.lock().unwrap().push(serde_json::from_slice(&request.body).unwrap());
+            Ok(HttpResponse::new(200, Vec::new()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_and_otlp_sinks() {
+        let labels = BTreeMap::from([("agent".to_string(), "support".to_string())]);
+        let events = [
+            MetricEvent::ToolLatency {
+                tool_name: "lookup".to_string(),
+                duration: Duration::from_millis(250),
+                is_error: false,
+            },
+            MetricEvent::TokensUsed {
+                model_id: "mock".to_string(),
+                input: 12,
+                output: 5,
+                reasoning: 0,
+            },
+        ];
+
+        let registry = RegistryMetricsSink::new();
+        let client = Arc::new(CapturingClient { bodies: Mutex::new(Vec::new()) });
+        let otlp = OtlpMetricsSink::new(client.clone(), "http://localhost:4318/v1/metrics");
+        for event in &events {
+            registry.record(event, &labels);
+            otlp.record(event, &labels);
+        }
+
+        let snapshot = registry.snapshot();
+        let tool_labels = merged_labels(&events[0], &labels);
+        assert_eq!(snapshot.get(METRIC_TOOL_LATENCY), Some(0.25));
+        assert_eq!(snapshot.get(&labeled_name("agent.tools.latency.count", &tool_labels)), Some(1.0));
+        assert_eq!(snapshot.get(METRIC_INPUT_TOKENS), Some(12.0));
+
+        assert_eq!(otlp.pending(), 4);
+        assert_eq!(otlp.flush().await.unwrap(), 4);
+        assert_eq!(otlp.pending(), 0);
+        let bodies = client.bodies.lock().unwrap();
+        let metrics = &bodies[0]["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics.as_array().unwrap().len(), 4);
+        assert_eq!(metrics[0]["name"], "agent.tokens.input");
+        assert_eq!(metrics[0]["sum"]["dataPoints"][0]["asDouble"], 12.0);
+        assert_eq!(metrics[3]["gauge"]["dataPoints"][0]["attributes"][1]["key"], "is_error");
+    }
+}
@@ -0,0 +1,214 @@
+//! Run timelines in Chrome trace-event format.
+//! 
+//! This module provides `Timeline`, which records timed spans for model
+//! calls, tool executions, graph nodes and handoffs during a run, and exports them
+//! as Chrome trace-event JSON. The output opens in Perfetto or
+//! `chrome://tracing` to show where time went in multi-tool runs.
+
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+/// The trace process identifier used for every span.
+const TRACE_PID: u32 = 1;
+
+/// The kind of work a span measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanCategory {
+    /// A whole agent run.
+    Run,
+    /// A model call.
+    Model,
+    /// A tool execution.
+    Tool,
+    /// A node in a multi-agent graph.
+    GraphNode,
+    /// A handoff between multi-agent graph nodes.
+    Handoff,
+}
+
+impl SpanCategory {
+    /// Get the category name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Run => "run",
+            Self::Model => "model",
+            Self::Tool => "tool",
+            Self::GraphNode => "graph_node",
+            Self::Handoff => "handoff",
+        }
+    }
+
+    /// Get the trace thread the category is drawn on, so each kind has its own row.
+    fn lane(&self) -> u32 {
+        match self {
+            Self::Run => 1,
+            Self::Model => 2,
+            Self::Tool => 3,
+            Self::GraphNode => 4,
+            Self::Handoff => 5,
+        }
+    }
+}
+
+/// A timed span within a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineSpan {
+    /// The span name, such as the tool name.
+    pub name: String,
+    /// The kind of work measured.
+    pub category: SpanCategory,
+    /// Microseconds from the start of the timeline to the start of the span.
+    pub start_us: u64,
+    /// The span duration in microseconds.
+    pub duration_us: u64,
+    /// Details shown when the span is selected.
+    pub args: Value,
+}
+
+/// The timed spans of a run.
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    /// The name of the traced process, usually the agent name.
+    pub process_name: String,
+    /// When the timeline started.
+    pub started_at: DateTime<Utc>,
+    /// The recorded spans in completion order.
+    pub spans: Vec<TimelineSpan>,
+    origin: Instant,
+}
+
+impl Timeline {
+    /// Create a timeline starting now.
+    pub fn new(process_name: &str) -> Self {
+        Self {
+            process_name: process_name.to_string(),
+            started_at: Utc::now(),
+            spans: Vec::new(),
+            origin: Instant::now(),
+        }
+    }
+
+    /// Record a span that started at `started` and ends now.
+    pub fn record(&mut self, category: SpanCategory, name: &str, started: Instant, args: Value) {
+        let end = Instant::now();
+        self.spans.push(TimelineSpan {
+            name: name.to_string(),
+            category,
+            start_us: started.saturating_duration_since(self.origin).as_micros() as u64,
+            duration_us: end.saturating_duration_since(started).as_micros() as u64,
+            args,
+        });
+    }
+
+    /// Record a span covering the whole timeline so far.
+    pub fn record_from_origin(&mut self, category: SpanCategory, name: &str, args: Value) {
+        let origin = self.origin;
+        self.record(category, name, origin, args);
+    }
+
+    /// Add the spans of another timeline, shifted onto this timeline's clock.
+    pub fn merge(&mut self, other: &Timeline) {
+        let offset = other.origin.saturating_duration_since(self.origin).as_micros() as u64;
+        self.spans.extend(other.spans.iter().cloned().map(|mut span| {
+            span.start_us += offset;
+            span
+        }));
+    }
+
+    /// Get the spans of one category.
+    pub fn spans_of(&self, category: SpanCategory) -> impl Iterator<Item = &TimelineSpan> {
+        self.spans.iter().filter(move |span| span.category == category)
+    }
+
+    /// Build the Chrome trace-event document for the timeline.
+    pub fn to_trace_value(&self) -> Value {
+        let mut events = vec![json!({
+            "name": "process_name",
+            "ph": "M",
+            "pid": TRACE_PID,
+            "args": {"name": self.process_name},
+        })];
+        for category in [
+            SpanCategory::Run,
+            SpanCategory::Model,
+            SpanCategory::Tool,
+            SpanCategory::GraphNode,
+            SpanCategory::Handoff,
+        ] {
+            if self.spans_of(category).next().is_some() {
+                events.push(json!({
+                    "name": "thread_name",
+                    "ph": "M",
+                    "pid": TRACE_PID,
+                    "tid": category.lane(),
+                    "args": {"name": category.as_str()},
+                }));
+            }
+        }
+        // Complete events ("X") carry their own duration, so no begin/end pairing is needed.
+        events.extend(self.spans.iter().map(|span| {
+            json!({
+                "name": span.name,
+                "cat": span.category.as_str(),
+                "ph": "X",
+                "ts": span.start_us,
+                "dur": span.duration_us,
+                "pid": TRACE_PID,
+                "tid": span.category.lane(),
+                "args": span.args,
+            })
+        }));
+
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+            "otherData": {"started_at": self.started_at.to_rfc3339()},
+        })
+    }
+
+    /// Serialize the timeline as Chrome trace-event JSON.
+    pub fn to_trace_json(&self) -> IndubitablyResult<String> {
+        serde_json::to_string(&self.to_trace_value())
+            .map_err(|e| IndubitablyError::InternalError(format!("Failed to serialize trace: {}", e)))
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new(crate::DEFAULT_AGENT_NAME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_trace_events() {
+        let mut timeline = Timeline::new("Planner");
+        timeline.record(SpanCategory::Model, "model.generate", Instant::now(), json!({"round": 1}));
+        timeline.record(SpanCategory::Tool, "search", Instant::now(), json!({"is_error": false}));
+        timeline.record_from_origin(SpanCategory::Run, "agent.run", Value::Null);
+
+        let trace: Value = serde_json::from_str(&timeline.to_trace_json().unwrap()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events[0]["args"]["name"], "Planner");
+
+        let spans: Vec<&Value> = events.iter().filter(|event| event["ph"] == "X").collect();
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1]["name"], "search");
+        assert_eq!(spans[1]["cat"], "tool");
+        assert_eq!(spans[1]["tid"], 3);
+        assert_eq!(spans[2]["ts"], 0);
+        assert!(spans[2]["dur"].as_u64().unwrap() >= spans[0]["dur"].as_u64().unwrap());
+
+        let threads = events.iter().filter(|event| event["name"] == "thread_name").count();
+        assert_eq!(threads, 3);
+    }
+}
@@ -0,0 +1,368 @@
+//! Chaos and fault injection for the SDK.
+//! 
+//! This module provides wrappers that inject configurable failures into
+//! models and tools: model timeouts, throttling (HTTP 429), malformed
+//! responses, tool panics and slow streams. Faults are drawn from a seeded
+//! generator so a failing scenario can be reproduced exactly.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio_stream::StreamExt;
+
+use crate::models::model::{Model, ModelConfig, ModelResponse, ModelStreamResponse};
+use crate::tools::registry::{AsyncToolFunction, Tool};
+use crate::types::{IndubitablyError, IndubitablyResult, Messages, ModelError, ToolSpec};
+
+/// Configuration for fault injection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    /// The probability that a model call times out.
+    pub timeout_probability: f64,
+    /// How long a model call waits before reporting an injected timeout.
+    pub timeout_delay: Duration,
+    /// The probability that a model call is throttled with a 429.
+    pub throttle_probability: f64,
+    /// The probability that a model call returns a malformed response.
+    pub malformed_probability: f64,
+    /// The probability that a tool call panics.
+    pub tool_panic_probability: f64,
+    /// The probability that a model stream is slowed down.
+    pub slow_stream_probability: f64,
+    /// The delay added before each event of a slowed stream.
+    pub slow_stream_delay: Duration,
+    /// The seed for the fault generator.
+    pub seed: u64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            timeout_probability: 0.0,
+            timeout_delay: Duration::ZERO,
+            throttle_probability: 0.0,
+            malformed_probability: 0.0,
+            tool_panic_probability: 0.0,
+            slow_stream_probability: 0.0,
+            slow_stream_delay: Duration::from_millis(500),
+            seed: 0x5eed_c4a0_5eed_c4a0,
+        }
+    }
+}
+
+impl ChaosConfig {
+    /// Create a new chaos configuration with no faults enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the model timeout probability and delay.
+    pub fn with_timeouts(mut self, probability: f64, delay: Duration) -> Self {
+        self.timeout_probability = probability;
+        self.timeout_delay = delay;
+        self
+    }
+
+    /// Set the throttling probability.
+    pub fn with_throttling(mut self, probability: f64) -> Self {
+        self.throttle_probability = probability;
+        self
+    }
+
+    /// Set the malformed response probability.
+    pub fn with_malformed_responses(mut self, probability: f64) -> Self {
+        self.malformed_probability = probability;
+        self
+    }
+
+    /// Set the tool panic probability.
+    pub fn with_tool_panics(mut self, probability: f64) -> Self {
+        self.tool_panic_probability = probability;
+        self
+    }
+
+    /// Set the slow stream probability and per-event delay.
+    pub fn with_slow_streams(mut self, probability: f64, delay: Duration) -> Self {
+        self.slow_stream_probability = probability;
+        self.slow_stream_delay = delay;
+        self
+    }
+
+    /// Set the seed for the fault generator.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// Counts of the faults injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    /// The number of injected model timeouts.
+    pub timeouts: u64,
+    /// The number of injected throttling errors.
+    pub throttles: u64,
+    /// The number of injected malformed responses.
+    pub malformed: u64,
+    /// The number of injected tool panics.
+    pub tool_panics: u64,
+    /// The number of slowed streams.
+    pub slow_streams: u64,
+}
+
+/// A shared source of injected faults.
+#[derive(Debug)]
+pub struct ChaosInjector {
+    config: ChaosConfig,
+    state: Mutex<u64>,
+    timeouts: AtomicU64,
+    throttles: AtomicU64,
+    malformed: AtomicU64,
+    tool_panics: AtomicU64,
+    slow_streams: AtomicU64,
+}
+
+impl ChaosInjector {
+    /// Create a new injector with the given configuration.
+    pub fn new(config: ChaosConfig) -> Arc<Self> {
+        // xorshift has a fixed point at zero, so never start there.
+        let state = Mutex::new(config.seed.max(1));
+        Arc::new(Self {
+            config,
+            state,
+            timeouts: AtomicU64::new(0),
+            throttles: AtomicU64::new(0),
+            malformed: AtomicU64::new(0),
+            tool_panics: AtomicU64::new(0),
+            slow_streams: AtomicU64::new(0),
+        })
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    /// Get the counts of faults injected so far.
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            throttles: self.throttles.load(Ordering::Relaxed),
+            malformed: self.malformed.load(Ordering::Relaxed),
+            tool_panics: self.tool_panics.load(Ordering::Relaxed),
+            slow_streams: self.slow_streams.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Wrap a model so its calls are subject to fault injection.
+    pub fn wrap_model(self: &Arc<Self>, model: Box<dyn Model>) -> ChaosModel {
+        ChaosModel {
+            inner: model,
+            injector: Arc::clone(self),
+        }
+    }
+
+    /// Wrap a tool so its calls may panic.
+    pub fn wrap_tool(self: &Arc<Self>, tool: Tool) -> Tool {
+        let injector = Arc::clone(self);
+        let inner = Arc::clone(&tool.function);
+        let name = tool.name.clone();
+        let function = move |input: serde_json::Value| {
+            injector.tool_fault(&name);
+            inner(input)
+        };
+        let async_function = tool.async_function.clone().map(|inner| {
+            let injector = Arc::clone(self);
+            let name = tool.name.clone();
+            let function: AsyncToolFunction = Arc::new(move |input| {
+                injector.tool_fault(&name);
+                inner(input)
+            });
+            function
+        });
+        Tool {
+            function: Arc::new(function),
+            async_function,
+            ..tool
+        }
+    }
+
+    /// Panic in a tool call if a fault is injected.
+    fn tool_fault(&self, name: &str) {
+        if self.roll(self.config.tool_panic_probability) {
+            self.tool_panics.fetch_add(1, Ordering::Relaxed);
+            panic!("chaos: injected panic in tool '{}'", name);
+        }
+    }
+
+    /// Draw the next value from the generator and test it against a probability.
+    fn roll(&self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        let sample = (*state >> 11) as f64 / (1u64 << 53) as f64;
+        sample < probability
+    }
+
+    /// Pick the fault, if any, to inject into a model call.
+    async fn model_fault(&self, model_id: &str) -> IndubitablyResult<()> {
+        if self.roll(self.config.timeout_probability) {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(self.config.timeout_delay).await;
+            return Err(IndubitablyError::TimeoutError(format!(
+                "chaos: injected timeout for model '{}'",
+                model_id
+            )));
+        }
+        if self.roll(self.config.throttle_probability) {
+            self.throttles.fetch_add(1, Ordering::Relaxed);
+            return Err(ModelError::ModelThrottled(format!(
+                "chaos: injected 429 Too Many Requests for model '{}'",
+                model_id
+            ))
+            .into());
+        }
+        if self.roll(self.config.malformed_probability) {
+            self.malformed.fetch_add(1, Ordering::Relaxed);
+            return Err(ModelError::InvalidResponseFormat(format!(
+                "chaos: injected malformed JSON from model '{}': {{\"content\": [",
+                model_id
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// A model wrapper that injects faults before delegating to the inner model.
+pub struct ChaosModel {
+    inner: Box<dyn Model>,
+    injector: Arc<ChaosInjector>,
+}
+
+impl ChaosModel {
+    /// Get the injector driving this model.
+    pub fn injector(&self) -> &Arc<ChaosInjector> {
+        &self.injector
+    }
+}
+
+#[async_trait]
+impl Model for ChaosModel {
+    fn config(&self) -> &ModelConfig {
+        self.inner.config()
+    }
+
+    fn update_config(&mut self, config: ModelConfig) {
+        self.inner.update_config(config);
+    }
+
+    fn config_mut(&mut self) -> &mut ModelConfig {
+        self.inner.config_mut()
+    }
+
+    async fn generate(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelResponse> {
+        self.injector.model_fault(self.inner.model_id()).await?;
+        self.inner.generate(messages, tool_specs, system_prompt).await
+    }
+
+    async fn stream(
+        &self,
+        messages: &Messages,
+        tool_specs: Option<&[ToolSpec]>,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<ModelStreamResponse> {
+        self.injector.model_fault(self.inner.model_id()).await?;
+        let stream = self.inner.stream(messages, tool_specs, system_prompt).await?;
+
+        if !self.injector.roll(self.injector.config.slow_stream_probability) {
+            return Ok(stream);
+        }
+        self.injector.slow_streams.fetch_add(1, Ordering::Relaxed);
+
+        let delay = self.injector.config.slow_stream_delay;
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut stream = stream;
+            while let Some(event) = stream.next().await {
+                tokio::time::sleep(delay).await;
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+
+    async fn structured_output(
+        &self,
+        output_model: &str,
+        messages: &Messages,
+        system_prompt: Option<&str>,
+    ) -> IndubitablyResult<serde_json::Value> {
+        self.injector.model_fault(self.inner.model_id()).await?;
+        self.inner.structured_output(output_model, messages, system_prompt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::MockModel;
+    use crate::types::Message;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_throttling_always_injected() {
+        let injector = ChaosInjector::new(ChaosConfig::new().with_throttling(1.0));
+        let model = injector.wrap_model(Box::new(MockModel::new()));
+
+        let result = model.generate(&vec![Message::user("Hi")], None, None).await;
+        assert!(matches!(
+            result,
+            Err(IndubitablyError::ModelError(ModelError::ModelThrottled(_)))
+        ));
+        assert_eq!(injector.stats().throttles, 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_faults_by_default() {
+        let injector = ChaosInjector::new(ChaosConfig::new());
+        let model = injector.wrap_model(Box::new(MockModel::new()));
+
+        assert!(model.generate(&vec![Message::user("Hi")], None, None).await.is_ok());
+        assert_eq!(injector.stats(), ChaosStats::default());
+    }
+
+    #[test]
+    fn test_seeded_faults_are_reproducible() {
+        let pattern = |seed| {
+            let injector = ChaosInjector::new(ChaosConfig::new().with_seed(seed));
+            (0..64).map(|_| injector.roll(0.3)).collect::<Vec<_>>()
+        };
+        let hits = pattern(42).iter().filter(|hit| **hit).count();
+
+        assert_eq!(pattern(42), pattern(42));
+        assert!(hits > 5 && hits < 40);
+    }
+
+    #[test]
+    fn test_tool_panic_injected() {
+        let injector = ChaosInjector::new(ChaosConfig::new().with_tool_panics(1.0));
+        let tool = injector.wrap_tool(Tool::new("echo", "Echo", Arc::new(Ok)));
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| tool.execute(json!("x"))));
+        assert!(result.is_err());
+        assert_eq!(injector.stats().tool_panics, 1);
+    }
+}
@@ -0,0 +1,8 @@
+//! Testing utilities for the SDK.
+//! 
+//! This module provides helpers for exercising agents under adverse
+//! conditions before they reach production.
+
+pub mod chaos;
+
+pub use chaos::{ChaosConfig, ChaosInjector, ChaosModel, ChaosStats};
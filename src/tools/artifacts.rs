@@ -0,0 +1,531 @@
+//! Artifact storage for large tool outputs.
+//! 
+//! This module provides the `ArtifactStore` trait and built-in memory, file
+//! and S3 stores used to spill oversized tool outputs out of the model
+//! context, along with the `read_artifact` tool that lets the model page
+//! through stored artifacts. Pages end on character boundaries, so text
+//! split across pages reads back intact.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use serde_json::{json, Value};
+
+use crate::crypto::{hex, sha256};
+use crate::models::http::{HttpClient, HttpRequest, HttpResponse};
+use crate::models::signing::{uri_encode, AwsCredentials, SigV4Signer};
+use crate::types::{IndubitablyResult, IndubitablyError, ToolError};
+use super::decorator::block_on_tool;
+use super::registry::{Tool, ToolMetadata};
+
+/// The name of the built-in artifact reading tool.
+pub const READ_ARTIFACT_TOOL_NAME: &str = "read_artifact";
+
+/// The default number of bytes returned by a single `read_artifact` call.
+pub const DEFAULT_ARTIFACT_PAGE_BYTES: usize = 16 * 1024;
+
+/// A handle identifying a stored artifact.
+pub type ArtifactHandle = String;
+
+/// A trait for storing large payloads outside the model context.
+///
+/// Stores are synchronous so they can be used from tool functions; a store
+/// backed by remote object storage can block on its client internally.
+pub trait ArtifactStore: Send + Sync + fmt::Debug {
+    /// Store a payload and return its handle.
+    fn put(&self, data: &[u8], media_type: &str) -> IndubitablyResult<ArtifactHandle>;
+
+    /// Read up to `length` bytes starting at `offset`.
+    fn read(&self, handle: &str, offset: usize, length: usize) -> IndubitablyResult<Vec<u8>>;
+
+    /// Get the total size of an artifact in bytes.
+    fn size(&self, handle: &str) -> IndubitablyResult<usize>;
+
+    /// Delete an artifact.
+    fn delete(&self, handle: &str) -> IndubitablyResult<()>;
+}
+
+fn artifact_not_found(handle: &str) -> IndubitablyError {
+    IndubitablyError::ToolError(ToolError::ArtifactNotFound(handle.to_string()))
+}
+
+fn storage_error(error: impl fmt::Display) -> IndubitablyError {
+    IndubitablyError::ToolError(ToolError::ExecutionFailed(format!("Artifact storage failed: {}", error)))
+}
+
+fn new_handle() -> ArtifactHandle {
+    format!("artifact-{}", uuid::Uuid::new_v4().simple())
+}
+
+/// Reject handles that could name something other than an artifact the store created.
+fn check_handle(handle: &str) -> IndubitablyResult<()> {
+    if handle.is_empty() || !handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(artifact_not_found(handle));
+    }
+    Ok(())
+}
+
+/// An artifact store that keeps payloads in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryArtifactStore {
+    artifacts: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl InMemoryArtifactStore {
+    /// Create a new in-memory artifact store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ArtifactStore for InMemoryArtifactStore {
+    fn put(&self, data: &[u8], _media_type: &str) -> IndubitablyResult<ArtifactHandle> {
+        let handle = new_handle();
+        self.artifacts
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(handle.clone(), data.to_vec());
+        Ok(handle)
+    }
+
+    fn read(&self, handle: &str, offset: usize, length: usize) -> IndubitablyResult<Vec<u8>> {
+        let artifacts = self.artifacts.read().unwrap_or_else(|e| e.into_inner());
+        let data = artifacts.get(handle).ok_or_else(|| artifact_not_found(handle))?;
+        let start = offset.min(data.len());
+        let end = start.saturating_add(length).min(data.len());
+        Ok(data[start..end].to_vec())
+    }
+
+    fn size(&self, handle: &str) -> IndubitablyResult<usize> {
+        let artifacts = self.artifacts.read().unwrap_or_else(|e| e.into_inner());
+        artifacts.get(handle).map(|data| data.len()).ok_or_else(|| artifact_not_found(handle))
+    }
+
+    fn delete(&self, handle: &str) -> IndubitablyResult<()> {
+        self.artifacts.write().unwrap_or_else(|e| e.into_inner()).remove(handle);
+        Ok(())
+    }
+}
+
+/// An artifact store that writes payloads to files in a directory.
+#[derive(Debug, Clone)]
+pub struct FileArtifactStore {
+    root: PathBuf,
+}
+
+impl FileArtifactStore {
+    /// Create a new file artifact store rooted at the given directory.
+    pub fn new(root: impl Into<PathBuf>) -> IndubitablyResult<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root).map_err(storage_error)?;
+        Ok(Self { root })
+    }
+
+    /// Resolve the path for a handle, rejecting handles that could escape the root.
+    fn path(&self, handle: &str) -> IndubitablyResult<PathBuf> {
+        check_handle(handle)?;
+        Ok(self.root.join(handle))
+    }
+}
+
+impl ArtifactStore for FileArtifactStore {
+    fn put(&self, data: &[u8], _media_type: &str) -> IndubitablyResult<ArtifactHandle> {
+        let handle = new_handle();
+        fs::write(self.path(&handle)?, data).map_err(storage_error)?;
+        Ok(handle)
+    }
+
+    fn read(&self, handle: &str, offset: usize, length: usize) -> IndubitablyResult<Vec<u8>> {
+        let mut file = fs::File::open(self.path(handle)?).map_err(|_| artifact_not_found(handle))?;
+        file.seek(SeekFrom::Start(offset as u64)).map_err(storage_error)?;
+        let mut buffer = Vec::new();
+        file.take(length as u64).read_to_end(&mut buffer).map_err(storage_error)?;
+        Ok(buffer)
+    }
+
+    fn size(&self, handle: &str) -> IndubitablyResult<usize> {
+        let metadata = fs::metadata(self.path(handle)?).map_err(|_| artifact_not_found(handle))?;
+        Ok(metadata.len() as usize)
+    }
+
+    fn delete(&self, handle: &str) -> IndubitablyResult<()> {
+        match fs::remove_file(self.path(handle)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(storage_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The signing service name of S3.
+const S3_SIGNING_SERVICE: &str = "s3";
+
+/// An artifact store that keeps payloads as objects in an S3 bucket.
+///
+/// Requests are signed with SigV4 and sent through the caller's `HttpClient`,
+/// blocking the calling thread until they complete. Reads fetch only the
+/// requested byte range.
+#[derive(Clone)]
+pub struct S3ArtifactStore {
+    client: Arc<dyn HttpClient>,
+    credentials: AwsCredentials,
+    region: String,
+    bucket: String,
+    prefix: String,
+}
+
+impl fmt::Debug for S3ArtifactStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3ArtifactStore")
+            .field("credentials", &self.credentials)
+            .field("region", &self.region)
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3ArtifactStore {
+    /// Create a store that writes objects under `artifacts/` in a bucket.
+    pub fn new(client: Arc<dyn HttpClient>, credentials: AwsCredentials, region: &str, bucket: &str) -> Self {
+        Self {
+            client,
+            credentials,
+            region: region.to_string(),
+            bucket: bucket.to_string(),
+            prefix: "artifacts/".to_string(),
+        }
+    }
+
+    /// Set the key prefix objects are written under.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Sign and send a request for the object of a handle.
+    fn send(
+        &self,
+        method: &str,
+        handle: &str,
+        request: impl FnOnce(HttpRequest) -> HttpRequest,
+    ) -> IndubitablyResult<HttpResponse> {
+        check_handle(handle)?;
+        let url = format!(
+            "https://{}.s3.{}.amazonaws.com/{}",
+            self.bucket,
+            self.region,
+            uri_encode(&format!("{}{}", self.prefix, handle), false)
+        );
+        let mut request = request(HttpRequest::new(method, &url));
+        let payload_hash = hex::encode(&sha256(&request.body));
+        request = request.with_header("x-amz-content-sha256", &payload_hash);
+        SigV4Signer::new(self.credentials.clone(), &self.region, S3_SIGNING_SERVICE)
+            .sign_at(&mut request, chrono::Utc::now())?;
+        let response = block_on_tool(self.client.send(request))??;
+        match response.status {
+            404 => Err(artifact_not_found(handle)),
+            // The range of a read starts past the end of the object
+            416 if method == "GET" => Ok(response),
+            status if !response.is_success() => {
+                Err(storage_error(format!("S3 {} of '{}' returned status {}", method, handle, status)))
+            }
+            _ => Ok(response),
+        }
+    }
+}
+
+impl ArtifactStore for S3ArtifactStore {
+    fn put(&self, data: &[u8], media_type: &str) -> IndubitablyResult<ArtifactHandle> {
+        let handle = new_handle();
+        self.send("PUT", &handle, |request| {
+            request.with_header("content-type", media_type).with_body(data.to_vec())
+        })?;
+        tracing::debug!(
+            "bucket=<{}>, handle=<{}>, bytes=<{}> | stored artifact in s3",
+            self.bucket,
+            handle,
+            data.len()
+        );
+        Ok(handle)
+    }
+
+    fn read(&self, handle: &str, offset: usize, length: usize) -> IndubitablyResult<Vec<u8>> {
+        if length == 0 {
+            return Ok(Vec::new());
+        }
+        let range = format!("bytes={}-{}", offset, offset.saturating_add(length - 1));
+        let response = self.send("GET", handle, |request| request.with_header("range", &range))?;
+        if response.status == 416 {
+            return Ok(Vec::new());
+        }
+        Ok(response.body)
+    }
+
+    fn size(&self, handle: &str) -> IndubitablyResult<usize> {
+        let response = self.send("HEAD", handle, |request| request)?;
+        response
+            .header("content-length")
+            .and_then(|length| length.trim().parse().ok())
+            .ok_or_else(|| storage_error(format!("S3 HEAD of '{}' returned no content length", handle)))
+    }
+
+    fn delete(&self, handle: &str) -> IndubitablyResult<()> {
+        match self.send("DELETE", handle, |request| request) {
+            Err(IndubitablyError::ToolError(ToolError::ArtifactNotFound(_))) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+}
+
+/// When and how tool outputs are spilled to an artifact store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpilloverPolicy {
+    /// Outputs larger than this many bytes are spilled.
+    pub threshold_bytes: usize,
+    /// The number of bytes kept inline as a preview.
+    pub preview_bytes: usize,
+}
+
+impl Default for SpilloverPolicy {
+    fn default() -> Self {
+        Self {
+            threshold_bytes: 64 * 1024,
+            preview_bytes: 2 * 1024,
+        }
+    }
+}
+
+impl SpilloverPolicy {
+    /// Create a new spillover policy.
+    pub fn new(threshold_bytes: usize, preview_bytes: usize) -> Self {
+        Self {
+            threshold_bytes,
+            preview_bytes,
+        }
+    }
+}
+
+/// Store an oversized tool output and build the preview placed in context.
+///
+/// Returns `None` when the output is within the policy threshold.
+pub fn spill_output(
+    store: &dyn ArtifactStore,
+    policy: &SpilloverPolicy,
+    output: &Value,
+) -> IndubitablyResult<Option<(Value, ArtifactHandle)>> {
+    let (text, media_type) = match output {
+        Value::String(text) => (text.clone(), "text/plain"),
+        other => (serde_json::to_string_pretty(other).map_err(storage_error)?, "application/json"),
+    };
+
+    if text.len() <= policy.threshold_bytes {
+        return Ok(None);
+    }
+
+    let handle = store.put(text.as_bytes(), media_type)?;
+
+    let mut cut = policy.preview_bytes.min(text.len());
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+
+    let preview = format!(
+        "{}\n[output truncated: {} bytes stored as artifact '{}'; call {} with this handle, an offset and a length to read more]",
+        &text[..cut],
+        text.len(),
+        handle,
+        READ_ARTIFACT_TOOL_NAME
+    );
+    Ok(Some((Value::String(preview), handle)))
+}
+
+/// Find the part of a page made of whole UTF-8 characters.
+///
+/// Continuation bytes at the start belong to a character the offset cut into,
+/// and an incomplete character at the end is left for the next page. Pages
+/// that are not text, or too short to hold a whole character, are kept as
+/// they are.
+fn char_boundaries(data: &[u8], at_end: bool) -> (usize, usize) {
+    let first = data.iter().take(3).take_while(|byte| *byte & 0xC0 == 0x80).count();
+    let mut last = data.len();
+    if !at_end {
+        if let Err(e) = std::str::from_utf8(&data[first..]) {
+            if e.error_len().is_none() {
+                last = first + e.valid_up_to();
+            }
+        }
+    }
+    if last <= first {
+        return (0, data.len());
+    }
+    (first, last)
+}
+
+/// Create the built-in `read_artifact` tool for the given store.
+pub fn create_read_artifact_tool(store: Arc<dyn ArtifactStore>) -> Tool {
+    let function = move |input: Value| {
+        let handle = input.get("handle").and_then(Value::as_str).ok_or_else(|| {
+            IndubitablyError::ToolError(ToolError::InvalidInput("Expected 'handle' string".to_string()))
+        })?;
+        let offset = input.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let length = input
+            .get("length")
+            .and_then(Value::as_u64)
+            .map(|length| length as usize)
+            .unwrap_or(DEFAULT_ARTIFACT_PAGE_BYTES);
+
+        let total = store.size(handle)?;
+        let data = store.read(handle, offset, length)?;
+        let start = offset.min(total);
+        let (first, last) = char_boundaries(&data, start + data.len() >= total);
+        let end = start + last;
+
+        Ok(json!({
+            "handle": handle,
+            "offset": start + first,
+            "next_offset": if end < total { Value::from(end) } else { Value::Null },
+            "total_bytes": total,
+            "content": String::from_utf8_lossy(&data[first..last]),
+        }))
+    };
+
+    Tool::new(
+        READ_ARTIFACT_TOOL_NAME,
+        "Read a range of bytes from a stored artifact produced by a previous tool call",
+        Arc::new(function),
+    )
+    .with_metadata(ToolMetadata::new().with_input_schema(json!({
+        "type": "object",
+        "properties": {
+            "handle": {"type": "string", "description": "The artifact handle"},
+            "offset": {"type": "integer", "minimum": 0, "description": "The byte offset to start reading from"},
+            "length": {"type": "integer", "minimum": 1, "description": "The maximum number of bytes to read"}
+        },
+        "required": ["handle"]
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_and_read_back() {
+        let store: Arc<dyn ArtifactStore> = Arc::new(InMemoryArtifactStore::new());
+        let policy = SpilloverPolicy::new(16, 4);
+        let output = Value::String("0123456789".repeat(10));
+
+        let (preview, handle) = spill_output(store.as_ref(), &policy, &output).unwrap().unwrap();
+        assert!(preview.as_str().unwrap().starts_with("0123\n[output truncated: 100 bytes"));
+
+        let tool = create_read_artifact_tool(Arc::clone(&store));
+        let page = tool.execute(json!({"handle": handle, "offset": 95, "length": 10})).unwrap();
+        assert_eq!(page["content"], json!("56789"));
+        assert_eq!(page["next_offset"], Value::Null);
+        assert_eq!(page["total_bytes"], json!(100));
+    }
+
+    #[test]
+    fn test_read_artifact_pages_on_char_boundaries() {
+        let store: Arc<dyn ArtifactStore> = Arc::new(InMemoryArtifactStore::new());
+        let handle = store.put("héllo wörld".as_bytes(), "text/plain").unwrap();
+        let tool = create_read_artifact_tool(Arc::clone(&store));
+
+        let mut offset = Value::from(0);
+        let mut text = String::new();
+        while !offset.is_null() {
+            let page = tool.execute(json!({"handle": handle, "offset": offset, "length": 2})).unwrap();
+            text.push_str(page["content"].as_str().unwrap());
+            offset = page["next_offset"].clone();
+        }
+        assert_eq!(text, "héllo wörld");
+
+        // An offset inside a character starts the page at the next one
+        let page = tool.execute(json!({"handle": handle, "offset": 2, "length": 4})).unwrap();
+        assert_eq!(page["offset"], json!(3));
+        assert_eq!(page["content"], json!("llo"));
+    }
+
+    #[derive(Debug, Default)]
+    struct FakeS3 {
+        objects: RwLock<HashMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for FakeS3 {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            assert!(request.header("authorization").unwrap().starts_with("AWS4-HMAC-SHA256"));
+            let mut objects = self.objects.write().unwrap();
+            let object = objects.get(&request.url).cloned();
+            Ok(match (request.method.as_str(), object) {
+                ("PUT", _) => {
+                    objects.insert(request.url.clone(), request.body.clone());
+                    HttpResponse::new(200, Vec::new())
+                }
+                (_, None) => HttpResponse::new(404, Vec::new()),
+                ("HEAD", Some(data)) => {
+                    HttpResponse::new(200, Vec::new()).with_header("content-length", &data.len().to_string())
+                }
+                ("GET", Some(data)) => {
+                    let range = request.header("range").unwrap().trim_start_matches("bytes=");
+                    let (start, end) = range.split_once('-').unwrap();
+                    let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+                    if start >= data.len() {
+                        HttpResponse::new(416, Vec::new())
+                    } else {
+                        HttpResponse::new(206, data[start..(end + 1).min(data.len())].to_vec())
+                    }
+                }
+                ("DELETE", Some(_)) => {
+                    objects.remove(&request.url);
+                    HttpResponse::new(204, Vec::new())
+                }
+                _ => HttpResponse::new(405, Vec::new()),
+            })
+        }
+    }
+
+    #[test]
+    fn test_s3_artifact_store() {
+        let s3 = Arc::new(FakeS3::default());
+        let credentials = AwsCredentials::new("AKID", "secret");
+        let store = S3ArtifactStore::new(s3.clone(), credentials, "eu-west-1", "tool-output").with_prefix("runs/");
+
+        let handle = store.put(b"hello world", "text/plain").unwrap();
+        let url = format!("https://tool-output.s3.eu-west-1.amazonaws.com/runs/{}", handle);
+        assert!(s3.objects.read().unwrap().contains_key(&url));
+        assert_eq!(store.size(&handle).unwrap(), 11);
+        assert_eq!(store.read(&handle, 6, 100).unwrap(), b"world");
+        assert!(store.read(&handle, 20, 5).unwrap().is_empty());
+        assert!(store.read("../escape", 0, 1).is_err());
+
+        store.delete(&handle).unwrap();
+        assert!(store.size(&handle).is_err());
+        store.delete(&handle).unwrap();
+    }
+
+    #[test]
+    fn test_small_output_not_spilled() {
+        let store = InMemoryArtifactStore::new();
+        let result = spill_output(&store, &SpilloverPolicy::default(), &json!({"ok": true})).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_file_artifact_store() {
+        let dir = std::env::temp_dir().join(format!("indubitably-artifacts-{}", uuid::Uuid::new_v4()));
+        let store = FileArtifactStore::new(&dir).unwrap();
+
+        let handle = store.put(b"hello world", "text/plain").unwrap();
+        assert_eq!(store.size(&handle).unwrap(), 11);
+        assert_eq!(store.read(&handle, 6, 100).unwrap(), b"world");
+        assert!(store.read("../escape", 0, 1).is_err());
+
+        store.delete(&handle).unwrap();
+        assert!(store.size(&handle).is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
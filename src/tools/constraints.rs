@@ -0,0 +1,318 @@
+//! Declarative argument constraints for tools.
+//! 
+//! This module provides constraints that tools declare on their arguments
+//! beyond what a JSON schema expresses, such as allowed URL hosts, path
+//! prefixes, numeric ranges and collection sizes. The executor checks them
+//! before running a tool and reports every violation with the exact
+//! argument path, so the model can correct its call.
+
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A constraint on the value of a tool argument.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArgumentConstraint {
+    /// A URL whose host is one of the given hosts. `*.example.com` matches subdomains.
+    AllowedHosts {
+        /// The allowed host patterns.
+        hosts: Vec<String>,
+    },
+    /// A file path inside one of the given directories, without `..` segments
+    /// and after following symbolic links.
+    PathPrefix {
+        /// The allowed path prefixes.
+        prefixes: Vec<String>,
+    },
+    /// A number within an inclusive range.
+    Range {
+        /// The minimum allowed value.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        /// The maximum allowed value.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+    },
+    /// An array with at most the given number of items.
+    MaxItems {
+        /// The maximum number of items.
+        max: usize,
+    },
+    /// A string with at most the given number of characters.
+    MaxLength {
+        /// The maximum number of characters.
+        max: usize,
+    },
+}
+
+impl ArgumentConstraint {
+    /// Create a constraint allowing URLs on the given hosts.
+    pub fn allowed_hosts(hosts: &[&str]) -> Self {
+        Self::AllowedHosts { hosts: hosts.iter().map(|host| host.to_lowercase()).collect() }
+    }
+
+    /// Create a constraint allowing paths under the given prefixes.
+    pub fn path_prefix(prefixes: &[&str]) -> Self {
+        Self::PathPrefix { prefixes: prefixes.iter().map(|prefix| prefix.to_string()).collect() }
+    }
+
+    /// Create a constraint allowing numbers in an inclusive range.
+    pub fn range(min: Option<f64>, max: Option<f64>) -> Self {
+        Self::Range { min, max }
+    }
+
+    /// Create a constraint limiting array length.
+    pub fn max_items(max: usize) -> Self {
+        Self::MaxItems { max }
+    }
+
+    /// Create a constraint limiting string length.
+    pub fn max_length(max: usize) -> Self {
+        Self::MaxLength { max }
+    }
+
+    /// Check a value, returning a description of the problem if it is not allowed.
+    pub fn check(&self, value: &Value) -> Option<String> {
+        match self {
+            Self::AllowedHosts { hosts } => {
+                let Some(url) = value.as_str() else {
+                    return Some("must be a URL string".to_string());
+                };
+                let Some(host) = url_host(url) else {
+                    return Some(format!("'{}' is not a valid URL", url));
+                };
+                if hosts.iter().any(|pattern| host_matches(pattern, &host)) {
+                    None
+                } else {
+                    Some(format!("host '{}' is not allowed; allowed hosts: {}", host, hosts.join(", ")))
+                }
+            }
+            Self::PathPrefix { prefixes } => {
+                let Some(path) = value.as_str() else {
+                    return Some("must be a path string".to_string());
+                };
+                let path = Path::new(path);
+                if path.components().any(|component| component == Component::ParentDir) {
+                    return Some(format!("path '{}' must not contain '..'", path.display()));
+                }
+                let resolved = resolve_path(path);
+                if prefixes.iter().any(|prefix| resolved.starts_with(resolve_path(Path::new(prefix)))) {
+                    None
+                } else {
+                    Some(format!(
+                        "path '{}' is outside the allowed directories: {}",
+                        path.display(),
+                        prefixes.join(", ")
+                    ))
+                }
+            }
+            Self::Range { min, max } => {
+                let Some(number) = value.as_f64() else {
+                    return Some("must be a number".to_string());
+                };
+                match (min, max) {
+                    (Some(min), _) if number < *min => Some(format!("{} is below the minimum of {}", number, min)),
+                    (_, Some(max)) if number > *max => Some(format!("{} is above the maximum of {}", number, max)),
+                    _ => None,
+                }
+            }
+            Self::MaxItems { max } => {
+                let Some(items) = value.as_array() else {
+                    return Some("must be an array".to_string());
+                };
+                (items.len() > *max).then(|| format!("has {} items but at most {} are allowed", items.len(), max))
+            }
+            Self::MaxLength { max } => {
+                let Some(text) = value.as_str() else {
+                    return Some("must be a string".to_string());
+                };
+                let length = text.chars().count();
+                (length > *max).then(|| format!("has {} characters but at most {} are allowed", length, max))
+            }
+        }
+    }
+}
+
+/// A constraint applied to the argument at a path.
+///
+/// Paths are dot-separated keys, and a key ending in `[]` applies the rest of
+/// the path to every array element, as in `recipients[]` or `files[].path`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArgumentGuard {
+    /// The argument path.
+    pub path: String,
+    /// The constraint applied to the argument.
+    pub constraint: ArgumentConstraint,
+}
+
+impl ArgumentGuard {
+    /// Create a new guard.
+    pub fn new(path: &str, constraint: ArgumentConstraint) -> Self {
+        Self {
+            path: path.to_string(),
+            constraint,
+        }
+    }
+
+    /// Check the guarded arguments of a tool input, appending any violations.
+    pub fn check(&self, input: &Value, violations: &mut Vec<ConstraintViolation>) {
+        let segments: Vec<&str> = self.path.split('.').filter(|segment| !segment.is_empty()).collect();
+        self.check_at(input, &segments, String::new(), violations);
+    }
+
+    fn check_at(&self, value: &Value, segments: &[&str], location: String, violations: &mut Vec<ConstraintViolation>) {
+        let Some((segment, rest)) = segments.split_first() else {
+            if let Some(message) = self.constraint.check(value) {
+                violations.push(ConstraintViolation { path: location, message });
+            }
+            return;
+        };
+
+        let (key, each) = match segment.strip_suffix("[]") {
+            Some(key) => (key, true),
+            None => (*segment, false),
+        };
+        let location = if location.is_empty() { key.to_string() } else { format!("{}.{}", location, key) };
+
+        // Missing arguments are left to the schema's required list.
+        let Some(child) = value.get(key) else {
+            return;
+        };
+        if !each {
+            return self.check_at(child, rest, location, violations);
+        }
+        match child.as_array() {
+            Some(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.check_at(item, rest, format!("{}[{}]", location, index), violations);
+                }
+            }
+            None => violations.push(ConstraintViolation { path: location, message: "must be an array".to_string() }),
+        }
+    }
+}
+
+/// A violated argument constraint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstraintViolation {
+    /// The concrete path of the offending argument, such as `files[2].path`.
+    pub path: String,
+    /// What is wrong with the argument.
+    pub message: String,
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "argument '{}' {}", self.path, self.message)
+    }
+}
+
+/// Check a tool input against guards, returning every violation.
+pub fn check_arguments(guards: &[ArgumentGuard], input: &Value) -> Vec<ConstraintViolation> {
+    let mut violations = Vec::new();
+    for guard in guards {
+        guard.check(input, &mut violations);
+    }
+    violations
+}
+
+/// Resolve a path through the symbolic links of its longest existing ancestor.
+///
+/// The rest of the path, which does not exist yet, is appended unchanged.
+pub(crate) fn resolve_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    for ancestor in absolute.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            return match absolute.strip_prefix(ancestor) {
+                Ok(rest) if !rest.as_os_str().is_empty() => canonical.join(rest),
+                _ => canonical,
+            };
+        }
+    }
+    absolute
+}
+
+/// Extract the lowercase host of an absolute URL.
+///
+/// URLs with credentials are rejected, and `\` ends the host as it does in
+/// browsers, so `https://evil.com\@allowed.com` is seen as going to `evil.com`.
+fn url_host(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let host_port = rest.split(['/', '\\', '?', '#']).next()?;
+    if host_port.contains('@') {
+        return None;
+    }
+    let host = match host_port.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next()?,
+        None => host_port.split(':').next()?,
+    };
+    (!host.is_empty()).then(|| host.trim_end_matches('.').to_lowercase())
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.len() > domain.len() && host.ends_with(&format!(".{}", domain)),
+        None => host == pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_arguments() {
+        let guards = vec![
+            ArgumentGuard::new("url", ArgumentConstraint::allowed_hosts(&["api.example.com", "*.internal.dev"])),
+            ArgumentGuard::new("files[].path", ArgumentConstraint::path_prefix(&["/workspace"])),
+            ArgumentGuard::new("limit", ArgumentConstraint::range(Some(1.0), Some(100.0))),
+            ArgumentGuard::new("files", ArgumentConstraint::max_items(2)),
+            ArgumentGuard::new("note", ArgumentConstraint::max_length(5)),
+        ];
+
+        let allowed = json!({
+            "url": "https://docs.internal.dev:8443/path?q=1",
+            "files": [{"path": "/workspace/a.txt"}],
+            "limit": 10
+        });
+        assert!(check_arguments(&guards, &allowed).is_empty());
+
+        let denied = json!({
+            "url": "https://evil.com/api.example.com",
+            "files": [{"path": "/workspace/ok"}, {"path": "/workspace/../etc/passwd"}, {"path": "/workspacex/b"}],
+            "limit": 500,
+            "note": "too long"
+        });
+        let violations = check_arguments(&guards, &denied);
+        let paths: Vec<&str> = violations.iter().map(|violation| violation.path.as_str()).collect();
+        assert_eq!(paths, vec!["url", "files[1].path", "files[2].path", "limit", "files", "note"]);
+        assert_eq!(
+            violations[0].to_string(),
+            "argument 'url' host 'evil.com' is not allowed; allowed hosts: api.example.com, *.internal.dev"
+        );
+        assert!(violations[3].message.contains("above the maximum of 100"));
+
+        assert!(!host_matches("*.internal.dev", "internal.dev"));
+        assert_eq!(url_host("not a url"), None);
+        assert_eq!(url_host("https://api.example.com@evil.com/"), None);
+        assert_eq!(url_host("https://evil.com\\@api.example.com/").as_deref(), Some("evil.com"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_prefix_follows_symlinks() {
+        let root = tempfile::tempdir().unwrap();
+        let allowed = root.path().join("allowed");
+        std::fs::create_dir(&allowed).unwrap();
+        std::os::unix::fs::symlink("/etc", allowed.join("escape")).unwrap();
+        let constraint = ArgumentConstraint::path_prefix(&[allowed.to_str().unwrap()]);
+
+        assert_eq!(constraint.check(&json!(allowed.join("new/file.txt").to_str().unwrap())), None);
+        assert!(constraint.check(&json!(allowed.join("escape/passwd").to_str().unwrap())).is_some());
+    }
+}
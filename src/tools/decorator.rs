@@ -1,30 +1,45 @@
 //! Tool decorator for easily creating tools from functions.
 //! 
-//! This module provides a decorator macro and utilities for
+//! This module provides the `#[tool]` attribute and utilities for
 //! converting Rust functions into tools that agents can use.
 
 use std::sync::Arc;
 use serde_json::Value;
 
-use crate::types::IndubitablyResult;
+use crate::types::{IndubitablyError, IndubitablyResult};
 use super::registry::Tool;
 
-/// Create a tool from a function.
-/// 
-/// This macro creates a tool that can be registered with the tool registry.
-/// The function should take a `serde_json::Value` and return a `IndubitablyResult<serde_json::Value>`.
-/// 
+/// Define a tool from a function with typed arguments.
+///
+/// The attribute keeps the function and adds a `create_<name>_tool()`
+/// function returning the `Tool`, with an input schema built from the
+/// argument types and descriptions taken from the doc comment.
+///
 /// # Example
-/// 
+///
 /// ```rust
 /// use indubitably_rust_agent_sdk::tools::tool;
-/// 
-/// #[tool("calculator", "A simple calculator tool")]
-/// fn calculator(input: Value) -> IndubitablyResult<Value> {
-///     // Tool implementation
-///     Ok(Value::String("result".to_string()))
+///
+/// /// Convert a temperature between Celsius and Fahrenheit.
+/// ///
+/// /// # Arguments
+/// /// * `degrees` - The temperature to convert.
+/// /// * `to_fahrenheit` - Whether to convert to Fahrenheit; Celsius if omitted.
+/// #[tool]
+/// fn convert_temperature(degrees: f64, to_fahrenheit: Option<bool>) -> f64 {
+///     if to_fahrenheit.unwrap_or(false) { degrees * 9.0 / 5.0 + 32.0 } else { (degrees - 32.0) * 5.0 / 9.0 }
 /// }
+///
+/// let tool = create_convert_temperature_tool();
+/// assert_eq!(tool.name, "convert_temperature");
 /// ```
+pub use indubitably_rust_agent_sdk_macros::tool;
+
+/// Create a tool from a function.
+///
+/// Superseded by the `#[tool]` attribute, which derives the input schema
+/// from the argument types.
+#[deprecated(note = "use the `#[tool]` attribute from `tools::tool`")]
 #[macro_export]
 macro_rules! tool {
     ($name:expr, $description:expr) => {
@@ -45,6 +60,27 @@ macro_rules! tool {
     };
 }
 
+/// Run a future to completion from a synchronous caller.
+///
+/// This is how `Tool::execute` runs tools created with `Tool::new_async`. It
+/// starts a thread and runtime per call, so the executor awaits such tools
+/// through `Tool::execute_async` instead.
+pub fn block_on_tool<F>(future: F) -> IndubitablyResult<F::Output>
+where
+    F: std::future::Future + Send,
+    F::Output: Send,
+{
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+                Ok(runtime.block_on(future))
+            })
+            .join()
+            .unwrap_or_else(|_| Err(IndubitablyError::InternalError("Tool thread panicked".to_string())))
+    })
+}
+
 /// Create a tool from a function with custom metadata.
 pub fn create_tool<F>(name: &str, description: &str, function: F) -> Tool
 where
@@ -172,4 +208,34 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), json!("hello world"));
     }
+
+    /// Look up the forecast for a city.
+    ///
+    /// # Arguments
+    /// * `city` - The city to look up.
+    /// * `days` - How many days ahead to look.
+    #[tool]
+    async fn forecast(city: &str, days: Option<u32>, units: Vec<String>) -> IndubitablyResult<String> {
+        if city.is_empty() {
+            return Err(IndubitablyError::ValidationError("no city".to_string()));
+        }
+        Ok(format!("{} for {} day(s) in {}", city, days.unwrap_or(1), units.join("/")))
+    }
+
+    #[test]
+    fn test_tool_attribute_builds_schema_and_calls_function() {
+        let tool = create_forecast_tool();
+        assert_eq!(tool.name, "forecast");
+        assert_eq!(tool.description, "Look up the forecast for a city.");
+        let schema = tool.metadata.input_schema.as_ref().unwrap();
+        assert_eq!(schema["properties"]["city"], json!({ "type": "string", "description": "The city to look up." }));
+        assert_eq!(schema["properties"]["days"]["type"], "integer");
+        assert_eq!(schema["properties"]["units"], json!({ "type": "array", "items": { "type": "string" } }));
+        assert_eq!(schema["required"], json!(["city", "units"]));
+
+        let output = tool.execute(json!({ "city": "Oslo", "units": ["C"] })).unwrap();
+        assert_eq!(output, json!("Oslo for 1 day(s) in C"));
+        assert!(tool.execute(json!({ "city": "Oslo" })).is_err());
+        assert!(tool.execute(json!({ "city": "", "units": [] })).is_err());
+    }
 }
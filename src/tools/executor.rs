@@ -3,15 +3,24 @@
 //! This module provides functionality for executing tools with
 //! proper context, error handling, and result management.
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use std::collections::HashMap;
 use async_trait::async_trait;
 use serde_json::Value;
+use tokio::sync::Semaphore;
 use tokio::time::timeout;
 
 use crate::types::{IndubitablyResult, IndubitablyError, ToolError};
 use super::registry::Tool;
+use super::artifacts::{spill_output, ArtifactStore, SpilloverPolicy};
+use super::summarize::ToolOutputSummarizer;
+use super::result_processor::{ProcessAction, ToolResultProcessor};
+use super::constraints::check_arguments;
+use super::policy::ToolPolicy;
+use super::schema::validate_input;
+use super::workspace::in_working_directory;
 
 /// The result of a tool execution.
 #[derive(Debug, Clone)]
@@ -84,6 +93,8 @@ pub struct ToolExecutionContext {
     pub timeout: Duration,
     /// Additional context data.
     pub context: HashMap<String, Value>,
+    /// The working directory of the run, visible to the tool through `current_working_directory`.
+    pub working_directory: Option<PathBuf>,
 }
 
 impl ToolExecutionContext {
@@ -94,6 +105,7 @@ impl ToolExecutionContext {
             input,
             timeout: Duration::from_secs(30), // Default 30 second timeout
             context: HashMap::new(),
+            working_directory: None,
         }
     }
 
@@ -109,6 +121,12 @@ impl ToolExecutionContext {
         self
     }
 
+    /// Set the working directory of the run.
+    pub fn with_working_directory(mut self, working_directory: &Path) -> Self {
+        self.working_directory = Some(working_directory.to_path_buf());
+        self
+    }
+
     /// Get context data by key.
     pub fn get_context(&self, key: &str) -> Option<&Value> {
         self.context.get(key)
@@ -122,6 +140,18 @@ pub struct ToolExecutor {
     default_timeout: Duration,
     /// Whether to enable detailed logging.
     enable_logging: bool,
+    /// The maximum serialized output size in bytes, if bounded.
+    max_output_bytes: Option<usize>,
+    /// The store oversized outputs are spilled to, with its policy.
+    artifact_store: Option<(Arc<dyn ArtifactStore>, SpilloverPolicy)>,
+    /// The stage replacing large outputs with summaries.
+    summarizer: Option<ToolOutputSummarizer>,
+    /// The stage truncating or summarizing outputs over their limits.
+    result_processor: Option<ToolResultProcessor>,
+    /// The policy deciding which tool calls may run.
+    policy: Option<ToolPolicy>,
+    /// The maximum number of tools run at once by `execute_parallel`, if bounded.
+    max_concurrency: Option<usize>,
 }
 
 impl ToolExecutor {
@@ -130,6 +160,12 @@ impl ToolExecutor {
         Self {
             default_timeout: Duration::from_secs(30),
             enable_logging: false,
+            max_output_bytes: None,
+            artifact_store: None,
+            summarizer: None,
+            result_processor: None,
+            policy: None,
+            max_concurrency: None,
         }
     }
 
@@ -138,6 +174,12 @@ impl ToolExecutor {
         Self {
             default_timeout,
             enable_logging,
+            max_output_bytes: None,
+            artifact_store: None,
+            summarizer: None,
+            result_processor: None,
+            policy: None,
+            max_concurrency: None,
         }
     }
 
@@ -153,6 +195,65 @@ impl ToolExecutor {
         self
     }
 
+    /// Set the maximum serialized output size retained from a tool.
+    pub fn with_max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Spill outputs larger than the policy threshold to an artifact store.
+    ///
+    /// Spilled outputs are replaced with a preview and an artifact handle that
+    /// can be read back with the `read_artifact` tool.
+    pub fn with_artifact_store(mut self, store: Arc<dyn ArtifactStore>, policy: SpilloverPolicy) -> Self {
+        self.artifact_store = Some((store, policy));
+        self
+    }
+
+    /// Summarize outputs above the summarizer's token threshold before they enter the conversation.
+    ///
+    /// The full output is kept in the summarizer's artifact store; register
+    /// `ToolOutputSummarizer::retrieval_tool` so the model can read it.
+    pub fn with_summarizer(mut self, summarizer: ToolOutputSummarizer) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// Truncate or summarize outputs over the processor's limits before they enter the conversation.
+    pub fn with_result_processor(mut self, processor: ToolResultProcessor) -> Self {
+        self.result_processor = Some(processor);
+        self
+    }
+
+    /// Consult a policy before each tool call; refused calls fail without running.
+    pub fn with_policy(mut self, policy: ToolPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Limit how many tools `execute_parallel` runs at once; 1 runs them one after another.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency.max(1));
+        self
+    }
+
+    /// Get the limit on tools run at once, if one is set.
+    pub fn max_concurrency(&self) -> Option<usize> {
+        self.max_concurrency
+    }
+
+    /// Get the tool policy, if one is set.
+    pub fn policy(&self) -> Option<&ToolPolicy> {
+        self.policy.as_ref()
+    }
+
+    /// Start a new agent run, resetting the policy's per-run invocation counts.
+    pub fn begin_run(&self) {
+        if let Some(ref policy) = self.policy {
+            policy.reset();
+        }
+    }
+
     /// Execute a tool with the given context.
     pub async fn execute(
         &self,
@@ -170,9 +271,62 @@ impl ToolExecutor {
             );
         }
 
+        // Input that does not match the schema never reaches the tool
+        if let Some(ref schema) = tool.metadata.input_schema {
+            let violations = validate_input(schema, &context.input);
+            if !violations.is_empty() {
+                let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                tracing::warn!(
+                    "tool_name=<{}>, violations=<{}> | rejected tool call with input not matching its schema",
+                    context.tool_name,
+                    violations.len()
+                );
+                let error = ToolError::InvalidInput(format!(
+                    "Tool '{}' was not run: {}",
+                    context.tool_name,
+                    details.join("; ")
+                ));
+                return ToolExecutionResult::failure(error.to_string(), start_time.elapsed().as_millis() as u64)
+                    .with_metadata("tool_name", Value::String(context.tool_name))
+                    .with_metadata("schema_violations", serde_json::to_value(&violations).unwrap_or_default());
+            }
+        }
+
+        let violations = check_arguments(&tool.metadata.argument_constraints, &context.input);
+        if !violations.is_empty() {
+            let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+            tracing::warn!(
+                "tool_name=<{}>, violations=<{}> | rejected tool call with disallowed arguments",
+                context.tool_name,
+                violations.len()
+            );
+            return ToolExecutionResult::failure(
+                format!("Tool '{}' was not run: {}", context.tool_name, details.join("; ")),
+                start_time.elapsed().as_millis() as u64,
+            )
+            .with_metadata("tool_name", Value::String(context.tool_name))
+            .with_metadata("constraint_violations", serde_json::to_value(&violations).unwrap_or_default());
+        }
+
+        // Checked last so that approval is only asked for calls that would run
+        if let Some(ref policy) = self.policy {
+            if let Err(denial) = policy.check(tool, &context.input).await {
+                tracing::info!(
+                    "tool_name=<{}>, reason=<{}> | tool call refused by policy",
+                    context.tool_name,
+                    denial.reason.as_str()
+                );
+                return ToolExecutionResult::failure(denial.message, start_time.elapsed().as_millis() as u64)
+                    .with_metadata("tool_name", Value::String(context.tool_name))
+                    .with_metadata("policy_denial", Value::String(denial.reason.as_str().to_string()));
+            }
+        }
+
+        // Synchronous tools run on the blocking pool, so the timeout fires even while they block
+        let working_directory = context.working_directory.as_deref();
+        let execution = in_working_directory(working_directory, tool.execute_async(context.input.clone()));
         let execution_result = timeout(timeout_duration, async {
-            let result = tool.execute(context.input.clone());
-            match result {
+            match execution.await {
                 Ok(output) => Ok(output),
                 Err(e) => Err(e.to_string()),
             }
@@ -192,9 +346,13 @@ impl ToolExecutor {
                     );
                 }
 
-                ToolExecutionResult::success(output, execution_time_ms)
-                    .with_metadata("tool_name", Value::String(context.tool_name))
-                    .with_metadata("execution_time", Value::Number(execution_time_ms.into()))
+                let (output, stages) = self.apply_output_stages(&context.tool_name, output).await;
+                stages.into_iter().fold(
+                    ToolExecutionResult::success(output, execution_time_ms)
+                        .with_metadata("tool_name", Value::String(context.tool_name))
+                        .with_metadata("execution_time", Value::Number(execution_time_ms.into())),
+                    |result, (key, value)| result.with_metadata(key, value),
+                )
             }
             Ok(Err(error)) => {
                 if self.enable_logging {
@@ -235,28 +393,89 @@ impl ToolExecutor {
         input: Value,
         registry: &super::registry::ToolRegistry,
     ) -> IndubitablyResult<ToolExecutionResult> {
-        let tool = registry.get(tool_name).await.ok_or_else(|| {
+        self.execute_by_name_in(tool_name, input, registry, None).await
+    }
+
+    /// Execute a tool by name from a registry inside a run's working directory.
+    pub async fn execute_by_name_in(
+        &self,
+        tool_name: &str,
+        input: Value,
+        registry: &super::registry::ToolRegistry,
+        working_directory: Option<&Path>,
+    ) -> IndubitablyResult<ToolExecutionResult> {
+        let tool = Self::resolve(tool_name, registry).await?;
+        Ok(self.execute(&tool, self.context_for(tool_name, input, working_directory)).await)
+    }
+
+    /// Execute tools by name concurrently inside a run's working directory.
+    ///
+    /// Results are returned in the order of the calls; a tool missing from
+    /// the registry fails its own call without affecting the others.
+    pub async fn execute_parallel_by_name_in(
+        &self,
+        calls: Vec<(String, Value)>,
+        registry: &super::registry::ToolRegistry,
+        working_directory: Option<&Path>,
+    ) -> Vec<IndubitablyResult<ToolExecutionResult>> {
+        let mut results = Vec::with_capacity(calls.len());
+        let mut executions = Vec::new();
+        for (tool_name, input) in calls {
+            match Self::resolve(&tool_name, registry).await {
+                Ok(tool) => {
+                    executions.push((Tool::clone(&tool), self.context_for(&tool_name, input, working_directory)));
+                    results.push(None);
+                }
+                Err(e) => results.push(Some(Err(e))),
+            }
+        }
+
+        let mut executed = self.execute_parallel(executions).await.into_iter();
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| {
+                    Ok(executed
+                        .next()
+                        .unwrap_or_else(|| ToolExecutionResult::failure("Tool execution failed".to_string(), 0)))
+                })
+            })
+            .collect()
+    }
+
+    async fn resolve(tool_name: &str, registry: &super::registry::ToolRegistry) -> IndubitablyResult<Arc<Tool>> {
+        registry.get(tool_name).await.ok_or_else(|| {
             IndubitablyError::ToolError(ToolError::ToolNotFound(
                 format!("Tool '{}' not found", tool_name),
             ))
-        })?;
+        })
+    }
 
-        let context = ToolExecutionContext::new(tool_name, input)
+    fn context_for(&self, tool_name: &str, input: Value, working_directory: Option<&Path>) -> ToolExecutionContext {
+        let mut context = ToolExecutionContext::new(tool_name, input)
             .with_timeout(self.default_timeout);
-
-        Ok(self.execute(&tool, context).await)
+        if let Some(working_directory) = working_directory {
+            context = context.with_working_directory(working_directory);
+        }
+        context
     }
 
-    /// Execute multiple tools in parallel.
+    /// Execute multiple tools in parallel, up to the concurrency limit, returning results in order.
     pub async fn execute_parallel(
         &self,
         executions: Vec<(Tool, ToolExecutionContext)>,
     ) -> Vec<ToolExecutionResult> {
         let mut handles = Vec::new();
+        let semaphore = self.max_concurrency.map(|limit| Arc::new(Semaphore::new(limit)));
 
         for (tool, context) in executions {
             let executor = self.clone();
+            let semaphore = semaphore.clone();
             let handle = tokio::spawn(async move {
+                let _permit = match semaphore {
+                    Some(ref semaphore) => semaphore.acquire().await.ok(),
+                    None => None,
+                };
                 executor.execute(&tool, context).await
             });
             handles.push(handle);
@@ -278,6 +497,82 @@ impl ToolExecutor {
 
         results
     }
+
+    /// Run a successful output through the summarize, spill, process and truncate stages.
+    ///
+    /// The first stage that cuts the output down ends the pipeline. Returns the output and the
+    /// metadata recording what the stage did.
+    async fn apply_output_stages(&self, tool_name: &str, output: Value) -> (Value, Vec<(&'static str, Value)>) {
+        let original_size = ("original_size_bytes", Value::Number(output_size_bytes(&output).into()));
+
+        if let Some(ref summarizer) = self.summarizer {
+            match summarizer.summarize(tool_name, &output).await {
+                Ok(Some((summary, handle, original_tokens))) => {
+                    return (summary, vec![
+                        ("summarized", Value::Bool(true)),
+                        original_size,
+                        ("original_tokens", Value::Number(original_tokens.into())),
+                        ("artifact_handle", Value::String(handle)),
+                    ]);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("tool_name=<{}>, error=<{}> | failed to summarize tool output", tool_name, e);
+                }
+            }
+        }
+
+        if let Some((store, policy)) = &self.artifact_store {
+            match spill_output(store.as_ref(), policy, &output) {
+                Ok(Some((preview, handle))) => {
+                    return (preview, vec![
+                        ("truncated", Value::Bool(true)),
+                        original_size,
+                        ("artifact_handle", Value::String(handle)),
+                    ]);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!(
+                        "tool_name=<{}>, error=<{}> | failed to spill tool output to artifact store",
+                        tool_name,
+                        e
+                    );
+                }
+            }
+        }
+
+        if let Some(ref processor) = self.result_processor {
+            let processed = processor.process(tool_name, output).await;
+            let flag = match processed.action {
+                ProcessAction::Unchanged => return self.truncate_stage(processed.output, original_size),
+                ProcessAction::Truncated => "truncated",
+                ProcessAction::Summarized => "summarized",
+            };
+            return (processed.output, vec![
+                (flag, Value::Bool(true)),
+                original_size,
+                ("original_tokens", Value::Number(processed.original_tokens.into())),
+            ]);
+        }
+
+        self.truncate_stage(output, original_size)
+    }
+
+    /// Truncate an output over the byte limit, the last output stage.
+    fn truncate_stage(
+        &self,
+        output: Value,
+        original_size: (&'static str, Value),
+    ) -> (Value, Vec<(&'static str, Value)>) {
+        match self.max_output_bytes {
+            Some(max_bytes) => match truncate_output(output, max_bytes) {
+                (output, true) => (output, vec![("truncated", Value::Bool(true)), original_size]),
+                (output, false) => (output, Vec::new()),
+            },
+            None => (output, Vec::new()),
+        }
+    }
 }
 
 impl Default for ToolExecutor {
@@ -291,10 +586,51 @@ impl Clone for ToolExecutor {
         Self {
             default_timeout: self.default_timeout,
             enable_logging: self.enable_logging,
+            max_output_bytes: self.max_output_bytes,
+            artifact_store: self.artifact_store.clone(),
+            summarizer: self.summarizer.clone(),
+            result_processor: self.result_processor.clone(),
+            policy: self.policy.clone(),
+            max_concurrency: self.max_concurrency,
         }
     }
 }
 
+/// Get the size of a tool output in bytes, counting strings by their text.
+pub fn output_size_bytes(output: &Value) -> usize {
+    match output {
+        Value::String(text) => text.len(),
+        other => other.to_string().len(),
+    }
+}
+
+/// Truncate a tool output to at most `max_bytes` bytes of content.
+///
+/// Oversized outputs are replaced by a text preview followed by a marker
+/// noting the original size. Returns the output and whether it was truncated.
+pub fn truncate_output(output: Value, max_bytes: usize) -> (Value, bool) {
+    if output_size_bytes(&output) <= max_bytes {
+        return (output, false);
+    }
+
+    let text = match output {
+        Value::String(text) => text,
+        other => other.to_string(),
+    };
+
+    let mut cut = max_bytes;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+
+    (Value::String(truncation_note(&text[..cut], text.len())), true)
+}
+
+/// Follow the text kept from a longer output with a note of how much was kept.
+pub(crate) fn truncation_note(kept: &str, original_bytes: usize) -> String {
+    format!("{}\n[truncated: showing {} of {} bytes]", kept, kept.len(), original_bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,26 +663,146 @@ mod tests {
 
     #[tokio::test]
     async fn test_tool_execution_timeout() {
-        // Skip this test for now since the current architecture doesn't support
-        // timeouts for synchronous tools. The timeout mechanism only works with
-        // async operations, but our ToolFunction type is synchronous.
-        
-        // TODO: Implement proper timeout support for synchronous tools
-        // This would require either:
-        // 1. Making ToolFunction async
-        // 2. Using a different timeout mechanism that can interrupt blocking operations
-        
-        // For now, just verify that the executor works without timeout
         let executor = ToolExecutor::new();
         let tool = Tool::new(
-            "test_tool",
-            "A test tool",
-            Arc::new(|_| Ok(json!("done"))),
+            "slow_tool",
+            "A tool that blocks",
+            Arc::new(|_| {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok(json!("done"))
+            }),
+        );
+        let context = ToolExecutionContext::new("slow_tool", json!(null)).with_timeout(Duration::from_millis(50));
+
+        let started = std::time::Instant::now();
+        let result = executor.execute(&tool, context).await;
+        assert!(!result.is_success());
+        assert!(result.error.unwrap().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_tool_output_truncation() {
+        let executor = ToolExecutor::new().with_max_output_bytes(Some(8));
+        let tool = Tool::new(
+            "verbose_tool",
+            "A verbose tool",
+            Arc::new(|_| Ok(json!("x".repeat(64)))),
+        );
+        let context = ToolExecutionContext::new("verbose_tool", json!(null));
+
+        let result = executor.execute(&tool, context).await;
+
+        assert!(result.is_success());
+        assert!(result.output().as_str().unwrap().starts_with("xxxxxxxx\n[truncated"));
+        assert_eq!(result.metadata.get("truncated"), Some(&json!(true)));
+        assert_eq!(result.metadata.get("original_size_bytes"), Some(&json!(64)));
+    }
+
+    #[tokio::test]
+    async fn test_tool_output_stages_compose() {
+        use super::super::result_processor::OutputLimits;
+
+        let processor = ToolResultProcessor::new(OutputLimits::new(Some(64), None))
+            .with_tool_limits("verbose_tool", OutputLimits::new(Some(16), None));
+        let executor = ToolExecutor::new().with_result_processor(processor).with_max_output_bytes(Some(8));
+        let tool = Tool::new("verbose_tool", "A verbose tool", Arc::new(|_| Ok(json!("x".repeat(32)))));
+
+        let result = executor.execute(&tool, ToolExecutionContext::new("verbose_tool", json!(null))).await;
+        assert!(result.output().as_str().unwrap().starts_with("xxxxxxxxxxxxxxxx\n[truncated: showing 16 of 32"));
+        assert!(result.metadata.contains_key("original_tokens"));
+
+        // Outputs the processor leaves alone still go through the byte limit
+        let tool = Tool::new("quiet_tool", "A quieter tool", Arc::new(|_| Ok(json!("y".repeat(12)))));
+        let result = executor.execute(&tool, ToolExecutionContext::new("quiet_tool", json!(null))).await;
+        assert!(result.output().as_str().unwrap().starts_with("yyyyyyyy\n[truncated: showing 8 of 12"));
+        assert_eq!(result.metadata.get("original_size_bytes"), Some(&json!(12)));
+    }
+
+    #[tokio::test]
+    async fn test_tool_output_spillover() {
+        let store: Arc<dyn ArtifactStore> = Arc::new(crate::tools::InMemoryArtifactStore::new());
+        let executor = ToolExecutor::new()
+            .with_artifact_store(Arc::clone(&store), SpilloverPolicy::new(32, 8));
+        let tool = Tool::new(
+            "dump_tool",
+            "A tool with large output",
+            Arc::new(|_| Ok(json!("y".repeat(1024)))),
         );
-        let context = ToolExecutionContext::new("test_tool", json!(null));
+        let context = ToolExecutionContext::new("dump_tool", json!(null));
 
         let result = executor.execute(&tool, context).await;
+
         assert!(result.is_success());
+        assert!(result.output().as_str().unwrap().starts_with("yyyyyyyy\n[output truncated"));
+        let handle = result.metadata.get("artifact_handle").and_then(Value::as_str).unwrap();
+        assert_eq!(store.size(handle).unwrap(), 1024);
+    }
+
+    #[tokio::test]
+    async fn test_argument_constraints_reject_before_running() {
+        use super::super::constraints::ArgumentConstraint;
+        use super::super::registry::ToolMetadata;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = ran.clone();
+        let tool = Tool::new(
+            "fetch",
+            "Fetch a URL",
+            Arc::new(move |_| {
+                flag.store(true, Ordering::SeqCst);
+                Ok(json!("fetched"))
+            }),
+        )
+        .with_metadata(
+            ToolMetadata::new()
+                .with_argument_constraint("url", ArgumentConstraint::allowed_hosts(&["api.example.com"]))
+                .with_argument_constraint("retries", ArgumentConstraint::range(Some(0.0), Some(3.0))),
+        );
+        let executor = ToolExecutor::new();
+
+        let context = ToolExecutionContext::new("fetch", json!({"url": "https://evil.com/x", "retries": 9}));
+        let result = executor.execute(&tool, context).await;
+
+        assert!(!result.is_success());
+        assert!(!ran.load(Ordering::SeqCst));
+        assert_eq!(
+            result.error(),
+            Some("Tool 'fetch' was not run: argument 'url' host 'evil.com' is not allowed; allowed hosts: api.example.com; argument 'retries' 9 is above the maximum of 3")
+        );
+        assert_eq!(result.metadata["constraint_violations"].as_array().unwrap().len(), 2);
+
+        let context = ToolExecutionContext::new("fetch", json!({"url": "https://api.example.com/v1", "retries": 1}));
+        assert!(executor.execute(&tool, context).await.is_success());
+        assert!(ran.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_input_schema_rejects_before_running() {
+        use super::super::registry::ToolMetadata;
+
+        let tool = Tool::new("forecast", "Get a forecast", Arc::new(|_| Ok(json!("sunny")))).with_metadata(
+            ToolMetadata::new().with_input_schema(json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}, "days": {"type": "integer", "maximum": 14}},
+                "required": ["city"]
+            })),
+        );
+        let executor = ToolExecutor::new();
+
+        let context = ToolExecutionContext::new("forecast", json!({"days": 30}));
+        let result = executor.execute(&tool, context).await;
+
+        assert!(!result.is_success());
+        assert_eq!(
+            result.error(),
+            Some("Invalid tool input: Tool 'forecast' was not run: argument 'city' is required; argument 'days' must be at most 14, got 30")
+        );
+        assert_eq!(result.metadata["schema_violations"][0]["path"], "city");
+
+        let context = ToolExecutionContext::new("forecast", json!({"city": "Oslo", "days": 3}));
+        assert!(executor.execute(&tool, context).await.is_success());
     }
 
     #[tokio::test]
@@ -365,4 +821,32 @@ mod tests {
         assert_eq!(results.len(), 2);
         assert!(results.iter().all(|r| r.is_success()));
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_parallel_execution_respects_concurrency_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (running_in, peak_in) = (running.clone(), peak.clone());
+        let tool = Tool::new("slow", "Sleeps", Arc::new(move |input| {
+            let now = running_in.fetch_add(1, Ordering::SeqCst) + 1;
+            peak_in.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            running_in.fetch_sub(1, Ordering::SeqCst);
+            Ok(input)
+        }));
+        let registry = super::super::registry::ToolRegistry::new();
+        registry.register(tool).await.unwrap();
+        let executor = ToolExecutor::new().with_max_concurrency(2);
+
+        let mut calls: Vec<(String, Value)> = (0..5).map(|i| ("slow".to_string(), json!(i))).collect();
+        calls.insert(2, ("missing".to_string(), json!(null)));
+        let results = executor.execute_parallel_by_name_in(calls, &registry, None).await;
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert!(results[2].is_err());
+        let outputs: Vec<Value> = results.iter().flatten().map(|r| r.output.clone()).collect();
+        assert_eq!(outputs, vec![json!(0), json!(1), json!(2), json!(3), json!(4)]);
+    }
 }
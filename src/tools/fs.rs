@@ -0,0 +1,252 @@
+//! Built-in file system tools for the SDK.
+//! 
+//! This module provides `create_fs_tools`, the `read_file`, `write_file`
+//! and `list_files` tools scoped to a root directory, such as a repository
+//! checkout. Paths are relative to the root and may not escape it. Every
+//! file the tools change is first recorded in a `FileJournal`, which can
+//! list the changed files and roll them back to their original contents.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use super::constraints::resolve_path;
+use super::registry::{Tool, ToolEffect, ToolMetadata};
+use crate::types::{IndubitablyError, IndubitablyResult, ToolError};
+
+/// The name of the file reading tool.
+pub const READ_FILE_TOOL_NAME: &str = "read_file";
+
+/// The name of the file writing tool.
+pub const WRITE_FILE_TOOL_NAME: &str = "write_file";
+
+/// The name of the file listing tool.
+pub const LIST_FILES_TOOL_NAME: &str = "list_files";
+
+/// The most files `list_files` returns.
+pub const MAX_LISTED_FILES: usize = 500;
+
+/// Directories `list_files` does not descend into.
+const SKIPPED_DIRECTORIES: &[&str] = &[".git", "target", "node_modules"];
+
+fn fs_error(path: &Path, e: impl std::fmt::Display) -> IndubitablyError {
+    IndubitablyError::ToolError(ToolError::ExecutionFailed(format!("{}: {}", path.display(), e)))
+}
+
+/// Resolve a relative path inside a root directory, rejecting paths that escape it.
+///
+/// Symbolic links are followed, so a link inside the root that points out of it is rejected too.
+/// The returned path has its links resolved.
+pub fn resolve_in(root: &Path, relative: &str) -> IndubitablyResult<PathBuf> {
+    let relative = Path::new(relative);
+    let outside = || {
+        IndubitablyError::ToolError(ToolError::InvalidInput(format!(
+            "Path '{}' is outside {}",
+            relative.display(),
+            root.display()
+        )))
+    };
+    let escapes = relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if escapes {
+        return Err(outside());
+    }
+    let path = resolve_path(&root.join(relative));
+    if !path.starts_with(resolve_path(root)) {
+        return Err(outside());
+    }
+    Ok(path)
+}
+
+/// The original contents of the files changed through the tools, for rolling them back.
+#[derive(Debug, Default)]
+pub struct FileJournal {
+    originals: Mutex<BTreeMap<PathBuf, Option<Vec<u8>>>>,
+}
+
+impl FileJournal {
+    /// Create an empty journal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a file's contents before its first change; later changes keep the first record.
+    pub fn record(&self, path: &Path) {
+        let mut originals = self.originals.lock().unwrap_or_else(|e| e.into_inner());
+        if !originals.contains_key(path) {
+            originals.insert(path.to_path_buf(), fs::read(path).ok());
+        }
+    }
+
+    /// Get the files changed since the journal was created or cleared.
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        self.originals.lock().unwrap_or_else(|e| e.into_inner()).keys().cloned().collect()
+    }
+
+    /// Restore every changed file to its original contents, removing files that did not exist.
+    ///
+    /// Returns the number of files restored and clears the journal.
+    pub fn rollback(&self) -> IndubitablyResult<usize> {
+        let originals = std::mem::take(&mut *self.originals.lock().unwrap_or_else(|e| e.into_inner()));
+        for (path, original) in &originals {
+            match original {
+                Some(contents) => fs::write(path, contents).map_err(|e| fs_error(path, e))?,
+                None if path.exists() => fs::remove_file(path).map_err(|e| fs_error(path, e))?,
+                None => {}
+            }
+        }
+        tracing::debug!("files=<{}> | rolled back file changes", originals.len());
+        Ok(originals.len())
+    }
+
+    /// Forget the recorded originals, keeping the changes.
+    pub fn clear(&self) {
+        self.originals.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Get a string argument from a tool input.
+fn string_arg<'a>(input: &'a Value, name: &str) -> IndubitablyResult<&'a str> {
+    input.get(name).and_then(Value::as_str).ok_or_else(|| {
+        IndubitablyError::ToolError(ToolError::InvalidInput(format!("Expected '{}' string", name)))
+    })
+}
+
+/// Create the `read_file`, `write_file` and `list_files` tools scoped to a root directory.
+pub fn create_fs_tools(root: impl Into<PathBuf>, journal: Arc<FileJournal>) -> Vec<Tool> {
+    let root: PathBuf = root.into();
+    vec![
+        create_read_file_tool(root.clone()),
+        create_write_file_tool(root.clone(), journal),
+        create_list_files_tool(root),
+    ]
+}
+
+fn create_read_file_tool(root: PathBuf) -> Tool {
+    let function = move |input: Value| {
+        let path = resolve_in(&root, string_arg(&input, "path")?)?;
+        let text = fs::read_to_string(&path).map_err(|e| fs_error(&path, e))?;
+        let lines: Vec<&str> = text.lines().collect();
+        let start = input.get("start_line").and_then(Value::as_u64).unwrap_or(1).max(1) as usize;
+        let end = input.get("end_line").and_then(Value::as_u64).map_or(lines.len(), |end| end as usize);
+        let end = end.min(lines.len());
+        let content = lines.get(start - 1..end).map(|range| range.join("\n")).unwrap_or_default();
+        Ok(json!({ "content": content, "total_lines": lines.len() }))
+    };
+    let description = "Read a text file, optionally a range of its lines";
+    Tool::new(READ_FILE_TOOL_NAME, description, Arc::new(function)).with_metadata(ToolMetadata::new().with_input_schema(
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string", "description": "The file path, relative to the repository root"},
+                "start_line": {"type": "integer", "minimum": 1, "description": "The first line to read"},
+                "end_line": {"type": "integer", "minimum": 1, "description": "The last line to read"}
+            },
+            "required": ["path"]
+        }),
+    ))
+}
+
+fn create_write_file_tool(root: PathBuf, journal: Arc<FileJournal>) -> Tool {
+    let function = move |input: Value| {
+        let path = resolve_in(&root, string_arg(&input, "path")?)?;
+        let content = string_arg(&input, "content")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| fs_error(parent, e))?;
+        }
+        journal.record(&path);
+        fs::write(&path, content).map_err(|e| fs_error(&path, e))?;
+        Ok(json!({ "written": content.len() }))
+    };
+    Tool::new(WRITE_FILE_TOOL_NAME, "Create or replace a text file", Arc::new(function)).with_metadata(
+        ToolMetadata::new()
+            .with_input_schema(json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "The file path, relative to the repository root"},
+                    "content": {"type": "string", "description": "The full new contents of the file"}
+                },
+                "required": ["path", "content"]
+            }))
+            .with_effect(ToolEffect::Write),
+    )
+}
+
+fn create_list_files_tool(root: PathBuf) -> Tool {
+    let function = move |input: Value| {
+        // Listed paths are resolved, so they are made relative to the resolved root
+        let root = resolve_path(&root);
+        let directory = resolve_in(&root, input.get("path").and_then(Value::as_str).unwrap_or("."))?;
+        let mut files = Vec::new();
+        let mut pending = vec![directory];
+        while let Some(directory) = pending.pop() {
+            for entry in fs::read_dir(&directory).map_err(|e| fs_error(&directory, e))?.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if path.is_dir() {
+                    if !SKIPPED_DIRECTORIES.contains(&name.as_str()) {
+                        pending.push(path);
+                    }
+                } else if let Ok(relative) = path.strip_prefix(&root) {
+                    files.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+        files.sort();
+        let truncated = files.len() > MAX_LISTED_FILES;
+        files.truncate(MAX_LISTED_FILES);
+        Ok(json!({ "files": files, "truncated": truncated }))
+    };
+    Tool::new(LIST_FILES_TOOL_NAME, "List the files under a directory", Arc::new(function)).with_metadata(
+        ToolMetadata::new().with_input_schema(json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string", "description": "The directory relative to the repository root, or the root"}
+            }
+        })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fs_tools_write_within_root_and_roll_back() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("lib.rs"), "fn one() {}\nfn two() {}\n").unwrap();
+        let journal = Arc::new(FileJournal::new());
+        let tools = create_fs_tools(root.path(), Arc::clone(&journal));
+        let (read, write, list) = (&tools[0], &tools[1], &tools[2]);
+
+        let output = read.execute(json!({ "path": "lib.rs", "start_line": 2 })).unwrap();
+        assert_eq!(output["content"], "fn two() {}");
+        assert!(read.execute(json!({ "path": "../secret" })).is_err());
+
+        write.execute(json!({ "path": "lib.rs", "content": "fn three() {}\n" })).unwrap();
+        write.execute(json!({ "path": "src/new.rs", "content": "// new\n" })).unwrap();
+        assert_eq!(list.execute(json!({})).unwrap()["files"], json!(["lib.rs", "src/new.rs"]));
+        assert_eq!(journal.changed_files().len(), 2);
+
+        assert_eq!(journal.rollback().unwrap(), 2);
+        assert_eq!(fs::read_to_string(root.path().join("lib.rs")).unwrap(), "fn one() {}\nfn two() {}\n");
+        assert!(!root.path().join("src/new.rs").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_in_rejects_symlinks_out_of_root() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret"), "key").unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link")).unwrap();
+
+        assert!(resolve_in(root.path(), "link/secret").is_err());
+        assert!(resolve_in(root.path(), "link/new.rs").is_err());
+        assert!(resolve_in(root.path(), "src/new.rs").is_ok());
+    }
+}
@@ -0,0 +1,307 @@
+//! Declarative HTTP tools for the SDK.
+//! 
+//! This module turns tool definitions written in a TOML, YAML or JSON file
+//! into live tools that call an HTTP API, so simple integrations need no
+//! Rust. A definition names the tool, describes it, gives its input schema,
+//! and says how to call the API:
+//! 
+//! ```toml
+//! [[tools]]
+//! name = "get_issue"
+//! description = "Get an issue from the tracker"
+//! method = "GET"
+//! url = "https://tracker.example.com/issues/{id}"
+//! auth = { value = "Bearer ${TRACKER_TOKEN}" }
+//! input_schema = { type = "object", properties = { id = { type = "string" } }, required = ["id"] }
+//! ```
+//! 
+//! `{param}` placeholders in the URL are filled from the tool input; the
+//! remaining input is sent as the query string of GET, HEAD and DELETE
+//! requests and as the JSON body of the others. `${VAR}` references in the
+//! URL, headers and auth value are read from the environment at call time,
+//! so secrets stay out of the file.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::manifest::parse_manifest_text;
+use super::registry::{Tool, ToolEffect, ToolMetadata};
+use crate::models::signing::uri_encode;
+use crate::models::{HttpClient, HttpRequest};
+use crate::types::{IndubitablyError, IndubitablyResult, ToolError};
+
+/// The header credentials are sent in unless a definition names another.
+pub const DEFAULT_AUTH_HEADER: &str = "Authorization";
+
+/// The HTTP methods a definition may use.
+pub const HTTP_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS", "POST", "PUT", "PATCH", "DELETE"];
+
+/// The credentials an HTTP tool sends with every request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpToolAuth {
+    /// The header carrying the credentials.
+    #[serde(default = "default_auth_header")]
+    pub header: String,
+    /// The header value, usually referencing an environment variable such as `Bearer ${API_TOKEN}`.
+    pub value: String,
+}
+
+/// A tool calling an HTTP API, as defined in a tool file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpToolDefinition {
+    /// The tool name.
+    pub name: String,
+    /// The description shown to the model.
+    pub description: String,
+    /// The JSON schema of the tool input.
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+    /// The HTTP method.
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// The URL, with `{param}` placeholders filled from the input.
+    pub url: String,
+    /// Extra headers sent with every request.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The credentials sent with every request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<HttpToolAuth>,
+}
+
+/// A tool file holding several definitions.
+#[derive(Debug, Deserialize)]
+struct HttpToolFile {
+    tools: Vec<HttpToolDefinition>,
+}
+
+fn default_auth_header() -> String {
+    DEFAULT_AUTH_HEADER.to_string()
+}
+
+fn default_input_schema() -> Value {
+    serde_json::json!({"type": "object"})
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl HttpToolDefinition {
+    /// Get the effect of calling the tool, from its method.
+    pub fn effect(&self) -> Option<ToolEffect> {
+        match self.method.as_str() {
+            "GET" | "HEAD" | "OPTIONS" => None,
+            "DELETE" => Some(ToolEffect::Delete),
+            _ => Some(ToolEffect::Write),
+        }
+    }
+
+    /// Build the request for a tool input.
+    pub fn build_request(&self, input: Value) -> IndubitablyResult<HttpRequest> {
+        let mut arguments = match input {
+            Value::Object(arguments) => arguments,
+            Value::Null => Map::new(),
+            other => return Err(ToolError::InvalidInput(format!("Expected an object, got {}", other)).into()),
+        };
+
+        let mut url = fill_placeholders(&expand_env(&self.url)?, &mut arguments)?;
+        let sends_query = matches!(self.method.as_str(), "GET" | "HEAD" | "DELETE");
+        if sends_query && !arguments.is_empty() {
+            let query: Vec<String> = arguments
+                .iter()
+                .map(|(key, value)| format!("{}={}", uri_encode(key, true), uri_encode(&argument_text(value), true)))
+                .collect();
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&query.join("&"));
+        }
+
+        let mut request = HttpRequest::new(&self.method, &url).with_header("accept", "application/json");
+        for (name, value) in &self.headers {
+            request = request.with_header(name, &expand_env(value)?);
+        }
+        if let Some(auth) = &self.auth {
+            request = request.with_header(&auth.header, &expand_env(&auth.value)?);
+        }
+        if !sends_query {
+            request = request.with_json_body(&arguments)?;
+        }
+        Ok(request)
+    }
+
+    /// Create a live tool sending its requests with the given client.
+    pub fn into_tool(self, client: Arc<dyn HttpClient>) -> Tool {
+        let mut metadata = ToolMetadata::new()
+            .with_input_schema(self.input_schema.clone())
+            .with_extra("http", serde_json::json!({"method": self.method, "url": self.url}));
+        if let Some(effect) = self.effect() {
+            metadata = metadata.with_effect(effect);
+        }
+        let name = self.name.clone();
+        let description = self.description.clone();
+        let definition = Arc::new(self);
+        Tool::new_async(&name, &description, move |input| {
+            let definition = Arc::clone(&definition);
+            let client = Arc::clone(&client);
+            async move {
+                let request = definition.build_request(input)?;
+                let response = client.send(request).await?;
+                if !response.is_success() {
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "{} returned HTTP {}: {}",
+                        definition.name,
+                        response.status,
+                        response.text()
+                    ))
+                    .into());
+                }
+                Ok(response.json().unwrap_or_else(|_| Value::String(response.text())))
+            }
+        })
+        .with_metadata(metadata)
+    }
+}
+
+/// Read HTTP tool definitions from a parsed tool file, either a single definition or a `tools` list.
+pub fn parse_http_tools(value: Value) -> IndubitablyResult<Vec<HttpToolDefinition>> {
+    let definitions = if value.get("tools").is_some() {
+        serde_json::from_value::<HttpToolFile>(value).map(|file| file.tools)
+    } else {
+        serde_json::from_value(value).map(|definition| vec![definition])
+    };
+    let mut definitions =
+        definitions.map_err(|e| IndubitablyError::ValidationError(format!("Invalid HTTP tool: {}", e)))?;
+    for definition in &mut definitions {
+        definition.method = definition.method.to_ascii_uppercase();
+        if !HTTP_METHODS.contains(&definition.method.as_str()) {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Unsupported HTTP method '{}' for tool '{}'",
+                definition.method, definition.name
+            )));
+        }
+    }
+    Ok(definitions)
+}
+
+/// Load the HTTP tools defined in a TOML, YAML or JSON file.
+pub fn load_http_tools(path: &Path, client: Arc<dyn HttpClient>) -> IndubitablyResult<Vec<Tool>> {
+    load_http_tools_from_text(path, &std::fs::read_to_string(path)?, client)
+}
+
+/// Create live HTTP tools from the text of a TOML, YAML or JSON tool file read from `path`.
+pub fn load_http_tools_from_text(path: &Path, text: &str, client: Arc<dyn HttpClient>) -> IndubitablyResult<Vec<Tool>> {
+    let definitions = parse_http_tools(parse_manifest_text(path, text)?)?;
+    tracing::debug!("path=<{:?}>, tools=<{}> | loaded http tools", path, definitions.len());
+    Ok(definitions.into_iter().map(|definition| definition.into_tool(Arc::clone(&client))).collect())
+}
+
+/// Replace `${VAR}` references with environment variables.
+fn expand_env(text: &str) -> IndubitablyResult<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| ToolError::ToolNotAvailable(format!("Unclosed variable reference in '{}'", text)))?;
+        let name = &rest[start + 2..start + end];
+        let value = std::env::var(name)
+            .map_err(|_| ToolError::ToolNotAvailable(format!("Environment variable '{}' is not set", name)))?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Fill `{param}` placeholders from the arguments, removing the arguments used.
+fn fill_placeholders(url: &str, arguments: &mut Map<String, Value>) -> IndubitablyResult<String> {
+    let mut filled = String::with_capacity(url.len());
+    let mut rest = url;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + end];
+        let value = arguments
+            .remove(name)
+            .ok_or_else(|| ToolError::InvalidInput(format!("Missing URL parameter '{}'", name)))?;
+        filled.push_str(&rest[..start]);
+        filled.push_str(&uri_encode(&argument_text(&value), true));
+        rest = &rest[start + end + 1..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+fn argument_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HttpResponse;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    struct RecordingClient {
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for RecordingClient {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            self.requests.lock().unwrap().push(request);
+            Ok(HttpResponse::new(200, br#"{"ok": true}"#.to_vec()))
+        }
+    }
+
+    #[test]
+    fn test_http_tools_from_toml_call_the_api() {
+        std::env::set_var("HTTP_TOOL_TEST_TOKEN", "secret");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tracker.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[tools]]
+name = "get_issue"
+description = "Get an issue"
+url = "https://tracker.example.com/issues/{id}"
+auth = { value = "Bearer ${HTTP_TOOL_TEST_TOKEN}" }
+
+[[tools]]
+name = "create_issue"
+description = "Create an issue"
+method = "post"
+url = "https://tracker.example.com/issues"
+headers = { "x-team" = "sdk" }
+"#,
+        )
+        .unwrap();
+        let client = Arc::new(RecordingClient { requests: Mutex::new(Vec::new()) });
+        let tools = load_http_tools(&path, client.clone()).unwrap();
+
+        assert_eq!(tools[0].metadata.effects, Vec::<ToolEffect>::new());
+        assert_eq!(tools[1].metadata.effects, vec![ToolEffect::Write]);
+        let output = tools[0].execute(serde_json::json!({"id": "a b", "fields": "title"})).unwrap();
+        assert_eq!(output, serde_json::json!({"ok": true}));
+        tools[1].execute(serde_json::json!({"title": "Crash"})).unwrap();
+        assert!(tools[0].execute(serde_json::json!({})).is_err());
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests[0].url, "https://tracker.example.com/issues/a%20b?fields=title");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer secret"));
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].header("x-team"), Some("sdk"));
+        assert_eq!(requests[1].body, br#"{"title":"Crash"}"#);
+    }
+}
@@ -0,0 +1,124 @@
+//! The built-in image generation tool for the SDK.
+//! 
+//! This module provides the `generate_image` tool, which lets the model ask an
+//! `ImageGenerationModel` for images. The images are returned under the
+//! `images` key of the tool output; the agent takes them out before the
+//! output reaches the model, so the conversation only sees a short note, and
+//! returns them in the `AgentResult`.
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use super::registry::{Tool, ToolMetadata};
+use crate::models::image::{ImageGenerationModel, ImageRequest};
+use crate::types::{ImageContent, IndubitablyError, IndubitablyResult, ToolError};
+
+/// The name of the built-in image generation tool.
+pub const GENERATE_IMAGE_TOOL_NAME: &str = "generate_image";
+
+/// The tool output key holding generated images.
+pub const IMAGES_OUTPUT_KEY: &str = "images";
+
+/// The most images the model may ask for in one call.
+pub const MAX_IMAGES_PER_CALL: u64 = 4;
+
+/// Remove the images from a tool output and return them.
+///
+/// Entries that do not parse as images are dropped.
+pub fn take_images(output: &mut Value) -> Vec<ImageContent> {
+    output
+        .as_object_mut()
+        .and_then(|fields| fields.remove(IMAGES_OUTPUT_KEY))
+        .and_then(|images| match images {
+            Value::Array(entries) => Some(entries),
+            _ => None,
+        })
+        .map(|entries| entries.into_iter().filter_map(|entry| serde_json::from_value(entry).ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Read the image request from the tool input.
+fn image_request(input: &Value) -> IndubitablyResult<ImageRequest> {
+    let prompt = input.get("prompt").and_then(Value::as_str).ok_or_else(|| {
+        IndubitablyError::ToolError(ToolError::InvalidInput("Expected 'prompt' string".to_string()))
+    })?;
+    let mut request = ImageRequest::new(prompt);
+    if let Some(negative_prompt) = input.get("negative_prompt").and_then(Value::as_str) {
+        request = request.with_negative_prompt(negative_prompt);
+    }
+    if let Some(count) = input.get("count").and_then(Value::as_u64) {
+        request = request.with_count(count.min(MAX_IMAGES_PER_CALL) as u32);
+    }
+    if let (Some(width), Some(height)) = (
+        input.get("width").and_then(Value::as_u64),
+        input.get("height").and_then(Value::as_u64),
+    ) {
+        request = request.with_size(width as u32, height as u32);
+    }
+    Ok(request)
+}
+
+/// Create the `generate_image` tool backed by the given model.
+pub fn create_generate_image_tool(model: Arc<dyn ImageGenerationModel>) -> Tool {
+    let function = move |input: Value| {
+        let request = image_request(&input);
+        let model = Arc::clone(&model);
+        async move {
+            let images = model.generate_images(&request?).await?;
+            Ok(json!({
+                "message": format!(
+                    "Generated {} image(s). They are shown to the user with your reply; describe them rather than repeating their data.",
+                    images.len()
+                ),
+                IMAGES_OUTPUT_KEY: images,
+            }))
+        }
+    };
+
+    Tool::new_async(GENERATE_IMAGE_TOOL_NAME, "Generate images from a text description", function).with_metadata(
+        ToolMetadata::new().with_input_schema(json!({
+            "type": "object",
+            "properties": {
+                "prompt": {"type": "string", "description": "A detailed description of the image"},
+                "negative_prompt": {"type": "string", "description": "What to keep out of the image"},
+                "count": {"type": "integer", "minimum": 1, "maximum": MAX_IMAGES_PER_CALL, "description": "The number of images"},
+                "width": {"type": "integer", "minimum": 256, "description": "The width in pixels"},
+                "height": {"type": "integer", "minimum": 256, "description": "The height in pixels"}
+            },
+            "required": ["prompt"]
+        })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::types::IndubitablyResult;
+
+    struct Sketcher;
+
+    #[async_trait]
+    impl ImageGenerationModel for Sketcher {
+        fn model_id(&self) -> &str {
+            "sketcher"
+        }
+
+        async fn generate_images(&self, request: &ImageRequest) -> IndubitablyResult<Vec<ImageContent>> {
+            Ok((0..request.count).map(|_| ImageContent::base64("c2tldGNo", "image/png")).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_image_tool() {
+        let tool = create_generate_image_tool(Arc::new(Sketcher));
+        let mut output = tool.execute(json!({ "prompt": "a cat", "count": 9 })).unwrap();
+        let images = take_images(&mut output);
+        assert_eq!(images.len(), MAX_IMAGES_PER_CALL as usize);
+        assert_eq!(images[0].source.data.base64.as_deref(), Some("c2tldGNo"));
+        assert!(output.get(IMAGES_OUTPUT_KEY).is_none());
+        assert!(output["message"].as_str().unwrap().starts_with("Generated 4 image(s)."));
+        assert!(tool.execute(json!({})).is_err());
+    }
+}
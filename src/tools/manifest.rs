@@ -0,0 +1,757 @@
+//! Tool manifest parsing for the SDK.
+//! 
+//! This module reads the TOML and YAML files that declarative tools are
+//! defined in into JSON values, which are then deserialized with serde. It
+//! covers the parts of both formats that tool definitions use: TOML tables,
+//! arrays of tables, dotted keys, strings, numbers, booleans, arrays and
+//! inline tables; YAML block mappings and sequences, plain and quoted
+//! scalars, flow collections and `|`/`>` block scalars. Anchors, tags,
+//! multiple documents and TOML dates are not supported.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::types::{IndubitablyError, IndubitablyResult};
+
+fn invalid(format: &str, line: usize, message: &str) -> IndubitablyError {
+    IndubitablyError::ValidationError(format!("Invalid {} at line {}: {}", format, line, message))
+}
+
+/// Parse a manifest file as TOML, YAML or JSON according to its extension.
+pub fn parse_manifest_file(path: &Path) -> IndubitablyResult<Value> {
+    parse_manifest_text(path, &std::fs::read_to_string(path)?)
+}
+
+/// Parse the text of a manifest file as TOML, YAML or JSON according to the file's extension.
+pub fn parse_manifest_text(path: &Path, text: &str) -> IndubitablyResult<Value> {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or_default() {
+        "toml" => parse_toml(text),
+        "yaml" | "yml" => parse_yaml(text),
+        "json" => Ok(serde_json::from_str(text)?),
+        other => Err(IndubitablyError::ValidationError(format!(
+            "Unsupported manifest format '{}' for {}",
+            other,
+            path.display()
+        ))),
+    }
+}
+
+/// Parse a TOML document.
+pub fn parse_toml(text: &str) -> IndubitablyResult<Value> {
+    TomlParser { chars: text.chars().collect(), pos: 0, line: 1 }.document()
+}
+
+struct TomlParser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl TomlParser {
+    fn error(&self, message: &str) -> IndubitablyError {
+        invalid("TOML", self.line, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, c: char) -> IndubitablyResult<()> {
+        if self.bump() == Some(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    /// Skip spaces and tabs, and a comment ending the line.
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skip whitespace, newlines and comments.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some('\n' | '\r') => {
+                    self.bump();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> IndubitablyResult<()> {
+        self.skip_spaces();
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some(c) => Err(self.error(&format!("unexpected '{}' after value", c))),
+        }
+    }
+
+    fn document(mut self) -> IndubitablyResult<Value> {
+        let mut root = Map::new();
+        let mut current: Vec<String> = Vec::new();
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => return Ok(Value::Object(root)),
+                Some('[') if self.starts_with("[[") => {
+                    self.pos += 2;
+                    let path = self.dotted_key()?;
+                    self.expect(']')?;
+                    self.expect(']')?;
+                    let (last, parent) = path.split_last().ok_or_else(|| self.error("empty table name"))?;
+                    let table = table_at(&mut root, parent).map_err(|e| self.error(&e))?;
+                    match table.entry(last.clone()).or_insert_with(|| Value::Array(Vec::new())) {
+                        Value::Array(items) => items.push(Value::Object(Map::new())),
+                        _ => return Err(self.error(&format!("'{}' is not an array of tables", last))),
+                    }
+                    current = path;
+                }
+                Some('[') => {
+                    self.bump();
+                    let path = self.dotted_key()?;
+                    self.expect(']')?;
+                    table_at(&mut root, &path).map_err(|e| self.error(&e))?;
+                    current = path;
+                }
+                Some(_) => {
+                    let table = table_at(&mut root, &current).map_err(|e| self.error(&e))?;
+                    let (key, value) = self.key_value()?;
+                    insert_dotted(table, &key, value).map_err(|e| self.error(&e))?;
+                }
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    fn key_value(&mut self) -> IndubitablyResult<(Vec<String>, Value)> {
+        let key = self.dotted_key()?;
+        self.expect('=')?;
+        self.skip_spaces();
+        Ok((key, self.value()?))
+    }
+
+    fn dotted_key(&mut self) -> IndubitablyResult<Vec<String>> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_spaces();
+            let part = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                        self.bump();
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected a key"));
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            parts.push(part);
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return Ok(parts);
+            }
+            self.bump();
+        }
+    }
+
+    fn value(&mut self) -> IndubitablyResult<Value> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_blank();
+                    if self.peek() == Some(']') {
+                        self.bump();
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip_blank();
+                    match self.bump() {
+                        Some(',') => {}
+                        Some(']') => return Ok(Value::Array(items)),
+                        _ => return Err(self.error("expected ',' or ']' in array")),
+                    }
+                }
+            }
+            Some('{') => {
+                self.bump();
+                let mut table = Map::new();
+                self.skip_spaces();
+                if self.peek() == Some('}') {
+                    self.bump();
+                    return Ok(Value::Object(table));
+                }
+                loop {
+                    let (key, value) = self.key_value()?;
+                    insert_dotted(&mut table, &key, value).map_err(|e| self.error(&e))?;
+                    self.skip_spaces();
+                    match self.bump() {
+                        Some(',') => {}
+                        Some('}') => return Ok(Value::Object(table)),
+                        _ => return Err(self.error("expected ',' or '}' in inline table")),
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || "+-._".contains(c)) {
+                    self.bump();
+                }
+                let token: String = self.chars[start..self.pos].iter().filter(|c| **c != '_').collect();
+                match token.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => parse_number(&token).ok_or_else(|| self.error(&format!("unsupported value '{}'", token))),
+                }
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> IndubitablyResult<String> {
+        let multiline = self.starts_with("\"\"\"");
+        self.pos += if multiline { 3 } else { 1 };
+        if multiline && self.peek() == Some('\n') {
+            self.bump();
+        }
+        let mut text = String::new();
+        loop {
+            if multiline && self.starts_with("\"\"\"") {
+                self.pos += 3;
+                return Ok(text);
+            }
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some('\n') if !multiline => return Err(self.error("unterminated string")),
+                Some('"') if !multiline => return Ok(text),
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('b') => text.push('\u{8}'),
+                    Some('f') => text.push('\u{c}'),
+                    Some('"') => text.push('"'),
+                    Some('\\') => text.push('\\'),
+                    Some(kind @ ('u' | 'U')) => {
+                        let digits = if kind == 'u' { 4 } else { 8 };
+                        let code: String = (0..digits).filter_map(|_| self.bump()).collect();
+                        let c = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32);
+                        text.push(c.ok_or_else(|| self.error("invalid unicode escape"))?);
+                    }
+                    Some('\n') if multiline => {
+                        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+                            self.bump();
+                        }
+                    }
+                    _ => return Err(self.error("invalid escape")),
+                },
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> IndubitablyResult<String> {
+        let multiline = self.starts_with("'''");
+        self.pos += if multiline { 3 } else { 1 };
+        if multiline && self.peek() == Some('\n') {
+            self.bump();
+        }
+        let mut text = String::new();
+        loop {
+            if multiline && self.starts_with("'''") {
+                self.pos += 3;
+                return Ok(text);
+            }
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some('\n') if !multiline => return Err(self.error("unterminated string")),
+                Some('\'') if !multiline => return Ok(text),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+}
+
+/// Get the table at a path, creating missing tables and taking the last table of arrays of tables.
+fn table_at<'m>(root: &'m mut Map<String, Value>, path: &[String]) -> Result<&'m mut Map<String, Value>, String> {
+    let mut table = root;
+    for key in path {
+        let entry = table.entry(key.clone()).or_insert_with(|| Value::Object(Map::new()));
+        table = match entry {
+            Value::Object(next) => next,
+            Value::Array(items) => match items.last_mut() {
+                Some(Value::Object(next)) => next,
+                _ => return Err(format!("'{}' is not a table", key)),
+            },
+            _ => return Err(format!("'{}' is not a table", key)),
+        };
+    }
+    Ok(table)
+}
+
+fn insert_dotted(table: &mut Map<String, Value>, key: &[String], value: Value) -> Result<(), String> {
+    let (last, parent) = key.split_last().ok_or_else(|| "empty key".to_string())?;
+    let table = table_at(table, parent)?;
+    if table.contains_key(last) {
+        return Err(format!("duplicate key '{}'", last));
+    }
+    table.insert(last.clone(), value);
+    Ok(())
+}
+
+fn parse_number(token: &str) -> Option<Value> {
+    if let Ok(integer) = token.parse::<i64>() {
+        return Some(Value::from(integer));
+    }
+    if token.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+        return token.parse::<f64>().ok().filter(|f| f.is_finite()).map(Value::from);
+    }
+    None
+}
+
+/// Parse a YAML document.
+pub fn parse_yaml(text: &str) -> IndubitablyResult<Value> {
+    let mut lines: Vec<YamlLine> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, raw)| raw.trim() != "---")
+        .map(|(index, raw)| {
+            let indent = raw.len() - raw.trim_start_matches(' ').len();
+            YamlLine { number: index + 1, indent, text: strip_comment(raw[indent..].trim_end()).to_string(), raw }
+        })
+        .collect();
+    let mut parser = YamlParser { lines: &mut lines, pos: 0 };
+    parser.skip_blank();
+    if parser.pos >= parser.lines.len() {
+        return Ok(Value::Null);
+    }
+    let indent = parser.lines[parser.pos].indent;
+    let value = parser.node(indent)?;
+    parser.skip_blank();
+    if let Some(line) = parser.lines.get(parser.pos) {
+        return Err(invalid("YAML", line.number, "unexpected indentation"));
+    }
+    Ok(value)
+}
+
+struct YamlLine<'a> {
+    number: usize,
+    indent: usize,
+    text: String,
+    raw: &'a str,
+}
+
+struct YamlParser<'l, 'a> {
+    lines: &'l mut Vec<YamlLine<'a>>,
+    pos: usize,
+}
+
+impl YamlParser<'_, '_> {
+    fn skip_blank(&mut self) {
+        while self.lines.get(self.pos).is_some_and(|line| line.text.is_empty()) {
+            self.pos += 1;
+        }
+    }
+
+    fn current(&mut self) -> Option<&YamlLine<'_>> {
+        self.skip_blank();
+        self.lines.get(self.pos)
+    }
+
+    /// Parse the block node starting at the current line, which is indented by `indent`.
+    fn node(&mut self, indent: usize) -> IndubitablyResult<Value> {
+        let line = &self.lines[self.pos];
+        if is_sequence_item(&line.text) {
+            self.sequence(indent)
+        } else if split_key(&line.text).is_some() {
+            self.mapping(indent)
+        } else {
+            let (number, text) = (line.number, line.text.clone());
+            self.pos += 1;
+            parse_yaml_scalar(&text).map_err(|e| invalid("YAML", number, &e))
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> IndubitablyResult<Value> {
+        let mut items = Vec::new();
+        while let Some(line) = self.current() {
+            if line.indent != indent || !is_sequence_item(&line.text) {
+                break;
+            }
+            let rest = line.text[1..].trim_start().to_string();
+            let offset = line.text.len() - rest.len();
+            if rest.is_empty() {
+                self.pos += 1;
+                items.push(self.child(indent)?);
+            } else if split_key(&rest).is_some() || is_sequence_item(&rest) {
+                // An item opening a mapping or sequence continues at the column of its content
+                let line = &mut self.lines[self.pos];
+                line.indent += offset;
+                line.text = rest;
+                let indent = line.indent;
+                items.push(self.node(indent)?);
+            } else {
+                items.push(self.value_after(indent, &rest)?);
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> IndubitablyResult<Value> {
+        let mut map = Map::new();
+        while let Some(line) = self.current() {
+            if line.indent != indent || is_sequence_item(&line.text) {
+                break;
+            }
+            let number = line.number;
+            let (key, rest) = split_key(&line.text).ok_or_else(|| invalid("YAML", number, "expected 'key: value'"))?;
+            let key = match parse_yaml_scalar(&key).map_err(|e| invalid("YAML", number, &e))? {
+                Value::String(key) => key,
+                other => other.to_string(),
+            };
+            let value = if rest.is_empty() {
+                self.pos += 1;
+                // A sequence may sit at the same indentation as its key
+                match self.current() {
+                    Some(next) if next.indent == indent && is_sequence_item(&next.text) => self.sequence(indent)?,
+                    _ => self.child(indent)?,
+                }
+            } else {
+                self.value_after(indent, &rest)?
+            };
+            if map.insert(key.clone(), value).is_some() {
+                return Err(invalid("YAML", number, &format!("duplicate key '{}'", key)));
+            }
+        }
+        Ok(Value::Object(map))
+    }
+
+    /// Parse the node nested below a line indented by `indent`, or null if there is none.
+    fn child(&mut self, indent: usize) -> IndubitablyResult<Value> {
+        match self.current() {
+            Some(next) if next.indent > indent => {
+                let indent = next.indent;
+                self.node(indent)
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+
+    /// Parse an inline value, or a block scalar starting on the following lines.
+    fn value_after(&mut self, indent: usize, text: &str) -> IndubitablyResult<Value> {
+        let number = self.lines[self.pos].number;
+        self.pos += 1;
+        let Some(style) = text.chars().next().filter(|c| *c == '|' || *c == '>') else {
+            return parse_yaml_scalar(text).map_err(|e| invalid("YAML", number, &e));
+        };
+        let chomp = text[1..].trim();
+        let mut body: Vec<&str> = Vec::new();
+        let mut block_indent = None;
+        while let Some(line) = self.lines.get(self.pos) {
+            if !line.raw.trim().is_empty() {
+                if line.indent <= indent {
+                    break;
+                }
+                block_indent.get_or_insert(line.indent);
+            }
+            body.push(line.raw);
+            self.pos += 1;
+        }
+        while body.last().is_some_and(|line| line.trim().is_empty()) {
+            body.pop();
+        }
+        let block_indent = block_indent.unwrap_or(0);
+        let lines: Vec<&str> = body.iter().map(|line| line.get(block_indent..).unwrap_or("")).collect();
+        let mut text = if style == '|' {
+            lines.join("\n")
+        } else {
+            let mut folded = String::new();
+            for line in &lines {
+                if line.is_empty() {
+                    folded.push('\n');
+                } else {
+                    if !folded.is_empty() && !folded.ends_with('\n') {
+                        folded.push(' ');
+                    }
+                    folded.push_str(line);
+                }
+            }
+            folded
+        };
+        if chomp != "-" && !text.is_empty() {
+            text.push('\n');
+        }
+        Ok(Value::String(text))
+    }
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Remove a trailing comment, which starts with a `#` at the start or after whitespace, outside quotes.
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return text[..index].trim_end(),
+            None => {}
+        }
+        previous = c;
+    }
+    text
+}
+
+/// Split `key: value` at the first colon outside quotes and brackets that ends the text or precedes a space.
+fn split_key(text: &str) -> Option<(String, String)> {
+    let mut quote = None;
+    let mut depth = 0usize;
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    for (i, &(index, c)) in chars.iter().enumerate() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' if index == 0 => quote = Some(c),
+                '[' | '{' if index == 0 => return None,
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth = depth.saturating_sub(1),
+                ':' if depth == 0 && chars.get(i + 1).is_none_or(|(_, next)| *next == ' ') => {
+                    return Some((text[..index].trim().to_string(), text[index + 1..].trim().to_string()));
+                }
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+/// Parse a YAML scalar or flow collection.
+fn parse_yaml_scalar(text: &str) -> Result<Value, String> {
+    let chars: Vec<char> = text.trim().chars().collect();
+    let mut pos = 0;
+    let value = flow_value(&chars, &mut pos, false)?;
+    skip_flow_spaces(&chars, &mut pos);
+    if pos < chars.len() {
+        return Err(format!("unexpected '{}'", chars[pos..].iter().collect::<String>()));
+    }
+    Ok(value)
+}
+
+fn skip_flow_spaces(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+fn flow_value(chars: &[char], pos: &mut usize, in_flow: bool) -> Result<Value, String> {
+    skip_flow_spaces(chars, pos);
+    match chars.get(*pos) {
+        Some('"') => {
+            let start = *pos;
+            *pos += 1;
+            while let Some(c) = chars.get(*pos) {
+                *pos += 1;
+                match c {
+                    '\\' => *pos += 1,
+                    '"' => {
+                        let quoted: String = chars[start..*pos].iter().collect();
+                        return serde_json::from_str(&quoted).map_err(|e| format!("invalid quoted string: {}", e));
+                    }
+                    _ => {}
+                }
+            }
+            Err("unterminated string".to_string())
+        }
+        Some('\'') => {
+            *pos += 1;
+            let mut text = String::new();
+            while let Some(&c) = chars.get(*pos) {
+                *pos += 1;
+                if c == '\'' {
+                    if chars.get(*pos) == Some(&'\'') {
+                        *pos += 1;
+                    } else {
+                        return Ok(Value::String(text));
+                    }
+                }
+                text.push(c);
+            }
+            Err("unterminated string".to_string())
+        }
+        Some('[') => {
+            *pos += 1;
+            let mut items = Vec::new();
+            loop {
+                skip_flow_spaces(chars, pos);
+                if chars.get(*pos) == Some(&']') {
+                    *pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                items.push(flow_value(chars, pos, true)?);
+                skip_flow_spaces(chars, pos);
+                match chars.get(*pos) {
+                    Some(',') => *pos += 1,
+                    Some(']') => {}
+                    _ => return Err("expected ',' or ']'".to_string()),
+                }
+            }
+        }
+        Some('{') => {
+            *pos += 1;
+            let mut map = Map::new();
+            loop {
+                skip_flow_spaces(chars, pos);
+                if chars.get(*pos) == Some(&'}') {
+                    *pos += 1;
+                    return Ok(Value::Object(map));
+                }
+                let key = match flow_value(chars, pos, true)? {
+                    Value::String(key) => key,
+                    other => other.to_string(),
+                };
+                skip_flow_spaces(chars, pos);
+                if chars.get(*pos) != Some(&':') {
+                    return Err("expected ':' in flow mapping".to_string());
+                }
+                *pos += 1;
+                map.insert(key, flow_value(chars, pos, true)?);
+                skip_flow_spaces(chars, pos);
+                match chars.get(*pos) {
+                    Some(',') => *pos += 1,
+                    Some('}') => {}
+                    _ => return Err("expected ',' or '}'".to_string()),
+                }
+            }
+        }
+        _ => {
+            let start = *pos;
+            while let Some(&c) = chars.get(*pos) {
+                let ends_key = c == ':' && chars.get(*pos + 1).is_none_or(|next| next.is_whitespace());
+                if in_flow && (matches!(c, ',' | ']' | '}') || ends_key) {
+                    break;
+                }
+                *pos += 1;
+            }
+            let plain: String = chars[start..*pos].iter().collect();
+            Ok(plain_scalar(plain.trim()))
+        }
+    }
+}
+
+fn plain_scalar(text: &str) -> Value {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        _ => parse_number(text).unwrap_or_else(|| Value::String(text.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_toml_and_yaml_manifests_parse_alike() {
+        let toml = r#"
+# Weather tools
+[[tools]]
+name = "get_weather"
+description = """
+Get the weather for a city."""
+method = "GET"
+url = "https://api.example.com/weather/{city}"
+headers = { Accept = "application/json" }
+
+[tools.auth]
+header = "Authorization"
+value = "Bearer ${WEATHER_API_KEY}"
+
+[tools.input_schema]
+type = "object"
+required = [
+  "city",  # the only required field
+]
+properties.city = { type = "string" }
+properties.days = { type = "integer", maximum = 1_0 }
+"#;
+        let yaml = r#"
+# Weather tools
+tools:
+- name: get_weather
+  description: |
+    Get the weather for a city.
+  method: GET
+  url: https://api.example.com/weather/{city}
+  headers: {Accept: application/json}
+  auth:
+    header: Authorization
+    value: 'Bearer ${WEATHER_API_KEY}'
+  input_schema:
+    type: object
+    required: [city]  # the only required field
+    properties:
+      city:
+        type: string
+      days: {type: integer, maximum: 10}
+"#;
+        let expected = json!({"tools": [{
+            "name": "get_weather",
+            "description": "Get the weather for a city.",
+            "method": "GET",
+            "url": "https://api.example.com/weather/{city}",
+            "headers": {"Accept": "application/json"},
+            "auth": {"header": "Authorization", "value": "Bearer ${WEATHER_API_KEY}"},
+            "input_schema": {
+                "type": "object",
+                "required": ["city"],
+                "properties": {"city": {"type": "string"}, "days": {"type": "integer", "maximum": 10}}
+            }
+        }]});
+        assert_eq!(parse_toml(toml).unwrap(), expected);
+        let mut from_yaml = parse_yaml(yaml).unwrap();
+        // The literal block keeps its final newline
+        assert_eq!(from_yaml["tools"][0]["description"], "Get the weather for a city.\n");
+        from_yaml["tools"][0]["description"] = json!("Get the weather for a city.");
+        assert_eq!(from_yaml, expected);
+
+        assert!(parse_toml("name = \"a\"\nname = \"b\"").is_err());
+        assert!(parse_yaml("a: 1\n  b: 2").is_err());
+    }
+}
@@ -1,17 +1,80 @@
 //! MCP (Model Context Protocol) client for the SDK.
 //! 
 //! This module provides functionality for connecting to MCP servers
-//! and using their tools.
+//! and using their tools. The client speaks JSON-RPC with the server over
+//! one of three transports: the stdio of a child process, one message per
+//! line; the SSE transport, which reads messages from an event stream and
+//! posts requests to the endpoint the stream announces; or streamable HTTP,
+//! which posts each message and reads the response as JSON or an event
+//! stream. Over any of them it performs the `initialize` handshake, discovers
+//! tools with `tools/list`, and runs them through `tools/call`. HTTP
+//! transports send the configured headers, such as `Authorization`, and
+//! re-establish a lost connection or expired session before failing a
+//! request.
 
 use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
+use serde_json::{json, Value};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+
+use crate::models::{HttpClient, HttpRequest, HttpResponse};
+use crate::types::{IndubitablyResult, IndubitablyError, McpError, ToolError, ToolSpec};
+use super::registry::{Tool, ToolMetadata};
+
+/// The MCP protocol version the client asks the server for.
+pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// The MCP protocol version the client asks for over streamable HTTP, which it introduced.
+pub const MCP_STREAMABLE_HTTP_PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// The header carrying the session a streamable HTTP server assigned.
+pub const MCP_SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+
+/// The header carrying the negotiated protocol version on HTTP requests.
+pub const MCP_PROTOCOL_VERSION_HEADER: &str = "MCP-Protocol-Version";
+
+/// The default number of times a lost HTTP connection is re-established before a request fails.
+pub const DEFAULT_MAX_RECONNECTS: u32 = 3;
+
+/// The wait before the first reconnection attempt, doubled for each further attempt.
+const RECONNECT_BACKOFF: Duration = Duration::from_millis(200);
 
-use crate::types::{IndubitablyResult, IndubitablyError, ToolSpec};
-use super::registry::Tool;
+/// The JSON-RPC error code for a method the client does not implement.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+fn default_max_reconnects() -> u32 {
+    DEFAULT_MAX_RECONNECTS
+}
+
+/// How the client reaches an MCP server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MCPTransport {
+    /// Start the configured command and exchange messages over its stdin and stdout.
+    #[default]
+    Stdio,
+    /// Read messages from the event stream at `url` and post requests to the endpoint it announces.
+    Sse {
+        /// The URL of the event stream.
+        url: String,
+    },
+    /// Post each message to `url` and read the response as JSON or an event stream.
+    StreamableHttp {
+        /// The URL of the MCP endpoint.
+        url: String,
+    },
+}
 
 /// Configuration for an MCP client.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MCPClientConfig {
     /// The command to run the MCP server.
     pub command: String,
@@ -21,8 +84,34 @@ pub struct MCPClientConfig {
     pub working_directory: Option<String>,
     /// Environment variables for the server.
     pub environment: HashMap<String, String>,
-    /// Connection timeout in seconds.
+    /// Connection and request timeout in seconds.
     pub timeout_seconds: u64,
+    /// How the client reaches the server.
+    #[serde(default)]
+    pub transport: MCPTransport,
+    /// Headers sent with every HTTP request, such as `Authorization`.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// How many times a lost HTTP connection is re-established before a request fails.
+    #[serde(default = "default_max_reconnects")]
+    pub max_reconnects: u32,
+}
+
+impl std::fmt::Debug for MCPClientConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Header values often hold credentials
+        let headers: HashMap<&str, &str> = self.headers.keys().map(|name| (name.as_str(), "[REDACTED]")).collect();
+        f.debug_struct("MCPClientConfig")
+            .field("command", &self.command)
+            .field("args", &self.args)
+            .field("working_directory", &self.working_directory)
+            .field("environment", &self.environment)
+            .field("timeout_seconds", &self.timeout_seconds)
+            .field("transport", &self.transport)
+            .field("headers", &headers)
+            .field("max_reconnects", &self.max_reconnects)
+            .finish()
+    }
 }
 
 impl Default for MCPClientConfig {
@@ -33,6 +122,9 @@ impl Default for MCPClientConfig {
             working_directory: None,
             environment: HashMap::new(),
             timeout_seconds: 30,
+            transport: MCPTransport::Stdio,
+            headers: HashMap::new(),
+            max_reconnects: DEFAULT_MAX_RECONNECTS,
         }
     }
 }
@@ -72,154 +164,839 @@ impl MCPClientConfig {
         self.timeout_seconds = timeout_seconds;
         self
     }
+
+    /// Set how the client reaches the server.
+    pub fn with_transport(mut self, transport: MCPTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Add a header sent with every HTTP request.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Authenticate HTTP requests with a bearer token.
+    pub fn with_bearer_token(self, token: &str) -> Self {
+        self.with_header("Authorization", &format!("Bearer {}", token))
+    }
+
+    /// Set how many times a lost HTTP connection is re-established before a request fails.
+    pub fn with_max_reconnects(mut self, max_reconnects: u32) -> Self {
+        self.max_reconnects = max_reconnects;
+        self
+    }
 }
 
-/// An MCP client that can connect to MCP servers.
+/// Responses awaited from the server, keyed by request ID.
+type PendingRequests = Mutex<HashMap<u64, oneshot::Sender<Result<Value, McpError>>>>;
+
+/// The state of the event stream of the SSE transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum StreamState {
+    /// The stream is open but has not announced where to post messages yet.
+    Connecting,
+    /// The stream is open and messages are posted to this URL.
+    Ready(String),
+    /// The stream ended and must be reopened.
+    Closed,
+}
+
+/// An HTTP transport to a remote MCP server.
+struct HttpLink {
+    client: Arc<dyn HttpClient>,
+    url: String,
+    headers: Vec<(String, String)>,
+    /// Whether this is streamable HTTP rather than the SSE transport.
+    streamable: bool,
+    /// The event stream of the SSE transport.
+    stream: watch::Sender<StreamState>,
+    /// The task reading the event stream.
+    reader: Mutex<Option<JoinHandle<()>>>,
+    /// The number of event streams opened, so a replaced stream's reader cannot close its successor.
+    generation: AtomicU64,
+    /// The session a streamable HTTP server assigned.
+    session_id: Mutex<Option<String>>,
+    /// Whether the server forgot the session, so the client must initialize again.
+    session_expired: AtomicBool,
+    /// The protocol version the server agreed to.
+    protocol_version: Mutex<Option<String>>,
+}
+
+impl HttpLink {
+    fn request(&self, method: &str, url: &str) -> HttpRequest {
+        let mut request = HttpRequest::new(method, url);
+        for (name, value) in &self.headers {
+            request = request.with_header(name, value);
+        }
+        if let Some(version) = self.protocol_version.lock().unwrap_or_else(|e| e.into_inner()).as_deref() {
+            request = request.with_header(MCP_PROTOCOL_VERSION_HEADER, version);
+        }
+        if let Some(session_id) = self.session_id.lock().unwrap_or_else(|e| e.into_inner()).as_deref() {
+            request = request.with_header(MCP_SESSION_ID_HEADER, session_id);
+        }
+        request
+    }
+
+    /// Get the URL to post messages to, waiting for the event stream to announce it.
+    async fn endpoint(&self, timeout: Duration) -> IndubitablyResult<String> {
+        if self.streamable {
+            return Ok(self.url.clone());
+        }
+        let mut receiver = self.stream.subscribe();
+        let state = tokio::time::timeout(timeout, receiver.wait_for(|state| *state != StreamState::Connecting))
+            .await
+            .map_err(|_| McpError::ConnectionFailed("MCP server did not announce its message endpoint".to_string()))?
+            .map(|state| state.clone())
+            .unwrap_or(StreamState::Closed);
+        match state {
+            StreamState::Ready(endpoint) => Ok(endpoint),
+            _ => Err(McpError::ConnectionFailed("MCP event stream is closed".to_string()).into()),
+        }
+    }
+
+    /// Post a message, mapping failed statuses to errors.
+    async fn post(&self, message: &Value, timeout: Duration) -> IndubitablyResult<HttpResponse> {
+        let endpoint = self.endpoint(timeout).await?;
+        let request = self
+            .request("POST", &endpoint)
+            .with_header("accept", "application/json, text/event-stream")
+            .with_json_body(message)?;
+        let had_session = request.header(MCP_SESSION_ID_HEADER).is_some();
+        let response = tokio::time::timeout(timeout, self.client.send(request))
+            .await
+            .map_err(|_| {
+                McpError::ConnectionFailed(format!("No response from {} after {}s", endpoint, timeout.as_secs()))
+            })?
+            .map_err(|e| McpError::ConnectionFailed(e.to_string()))?;
+        if let Some(session_id) = response.header(MCP_SESSION_ID_HEADER) {
+            *self.session_id.lock().unwrap_or_else(|e| e.into_inner()) = Some(session_id.to_string());
+        }
+        match response.status {
+            _ if response.is_success() => Ok(response),
+            404 if had_session => {
+                *self.session_id.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                self.session_expired.store(true, Ordering::SeqCst);
+                Err(McpError::ConnectionFailed("MCP session expired".to_string()).into())
+            }
+            401 | 403 => Err(McpError::ClientFailed(format!(
+                "MCP server rejected the credentials (status {})",
+                response.status
+            ))
+            .into()),
+            502..=504 => {
+                Err(McpError::ConnectionFailed(format!("MCP server unavailable (status {})", response.status)).into())
+            }
+            status => Err(McpError::ServerFailed(format!("HTTP {}: {}", status, response.text())).into()),
+        }
+    }
+}
+
+impl Drop for HttpLink {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            reader.abort();
+        }
+    }
+}
+
+/// The channel messages travel over.
+enum Link {
+    /// The stdin of a server process; its stdout is read on a dedicated thread.
+    Stdio(Mutex<ChildStdin>),
+    /// A remote server reached over HTTP.
+    Http(HttpLink),
+}
+
+impl std::fmt::Debug for Link {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Link::Stdio(_) => f.write_str("Stdio"),
+            Link::Http(http) => {
+                f.debug_struct("Http").field("url", &http.url).field("streamable", &http.streamable).finish()
+            }
+        }
+    }
+}
+
+/// A JSON-RPC connection to an MCP server.
+///
+/// The server's output is read on a dedicated thread or task rather than by
+/// the caller, so requests can be made from synchronous tool functions on any
+/// runtime.
 #[derive(Debug)]
+struct McpConnection {
+    link: Link,
+    pending: PendingRequests,
+    next_id: AtomicU64,
+    timeout: Duration,
+    max_reconnects: u32,
+    /// The connection itself, for the task reading an event stream.
+    this: Weak<McpConnection>,
+}
+
+impl McpConnection {
+    /// Take over the stdio of a server process.
+    fn open(process: &mut Child, timeout: Duration) -> IndubitablyResult<Arc<Self>> {
+        let missing = || McpError::ClientFailed("MCP server stdio is not piped".to_string());
+        let stdin = process.stdin.take().ok_or_else(missing)?;
+        let stdout = process.stdout.take().ok_or_else(missing)?;
+        let connection = Self::new(Link::Stdio(Mutex::new(stdin)), timeout, 0);
+
+        let reader = Arc::downgrade(&connection);
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                let Some(connection) = reader.upgrade() else {
+                    return;
+                };
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    tracing::debug!("line=<{}> | ignoring non-JSON output from mcp server", line);
+                    continue;
+                };
+                if let (Some(reply), Link::Stdio(stdin)) = (connection.dispatch(message), &connection.link) {
+                    let _ = write_line(stdin, &reply);
+                }
+            }
+            if let Some(connection) = reader.upgrade() {
+                connection.fail_pending(|| McpError::ServerFailed("MCP server closed its output".to_string()));
+            }
+        });
+        if let Some(stderr) = process.stderr.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    tracing::debug!("stderr=<{}> | mcp server log", line);
+                }
+            });
+        }
+        Ok(connection)
+    }
+
+    /// Connect to a remote server, opening the event stream of the SSE transport.
+    async fn open_http(
+        client: Arc<dyn HttpClient>,
+        url: &str,
+        streamable: bool,
+        config: &MCPClientConfig,
+    ) -> IndubitablyResult<Arc<Self>> {
+        let mut headers: Vec<(String, String)> = config.headers.clone().into_iter().collect();
+        headers.sort();
+        let link = HttpLink {
+            client,
+            url: url.to_string(),
+            headers,
+            streamable,
+            stream: watch::Sender::new(StreamState::Closed),
+            reader: Mutex::new(None),
+            generation: AtomicU64::new(0),
+            session_id: Mutex::new(None),
+            session_expired: AtomicBool::new(false),
+            protocol_version: Mutex::new(None),
+        };
+        let timeout = Duration::from_secs(config.timeout_seconds);
+        let connection = Self::new(Link::Http(link), timeout, config.max_reconnects);
+        if !streamable {
+            connection.open_stream().await?;
+        }
+        Ok(connection)
+    }
+
+    fn new(link: Link, timeout: Duration, max_reconnects: u32) -> Arc<Self> {
+        Arc::new_cyclic(|this| Self {
+            link,
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            timeout,
+            max_reconnects,
+            this: this.clone(),
+        })
+    }
+
+    /// Open the event stream of the SSE transport and read it on a task, replacing any earlier stream.
+    async fn open_stream(&self) -> IndubitablyResult<()> {
+        let Link::Http(http) = &self.link else {
+            return Ok(());
+        };
+        if let Some(reader) = http.reader.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            reader.abort();
+        }
+        let generation = http.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        http.stream.send_replace(StreamState::Connecting);
+        let request = http.request("GET", &http.url).with_header("accept", "text/event-stream");
+        let response = tokio::time::timeout(self.timeout, http.client.send_streaming(request)).await;
+        let response = match response {
+            Ok(Ok(response)) if response.is_success() => response,
+            failed => {
+                http.stream.send_replace(StreamState::Closed);
+                return Err(match failed {
+                    Ok(Ok(response)) if matches!(response.status, 401 | 403) => McpError::ClientFailed(format!(
+                        "MCP server rejected the credentials (status {})",
+                        response.status
+                    )),
+                    Ok(Ok(response)) => {
+                        McpError::ConnectionFailed(format!("HTTP {} opening event stream", response.status))
+                    }
+                    Ok(Err(e)) => McpError::ConnectionFailed(e.to_string()),
+                    Err(_) => McpError::ConnectionFailed("Timed out opening event stream".to_string()),
+                }
+                .into());
+            }
+        };
+
+        let reader = self.this.clone();
+        let base = http.url.clone();
+        let task = tokio::spawn(async move {
+            let mut body = response.body;
+            let mut parser = SseParser::default();
+            while let Some(Ok(chunk)) = body.next().await {
+                for (event, data) in parser.push(&chunk) {
+                    let Some(connection) = reader.upgrade() else {
+                        return;
+                    };
+                    connection.handle_stream_event(&base, &event, &data).await;
+                }
+            }
+            let Some(connection) = reader.upgrade() else {
+                return;
+            };
+            if let Link::Http(http) = &connection.link {
+                if http.generation.load(Ordering::SeqCst) == generation {
+                    tracing::debug!("url=<{}> | mcp event stream closed", base);
+                    http.stream.send_replace(StreamState::Closed);
+                    connection.fail_pending(|| McpError::ConnectionFailed("MCP event stream closed".to_string()));
+                }
+            }
+        });
+        *http.reader.lock().unwrap_or_else(|e| e.into_inner()) = Some(task);
+        Ok(())
+    }
+
+    /// Handle an event of the SSE transport's stream.
+    async fn handle_stream_event(&self, base: &str, event: &str, data: &str) {
+        let Link::Http(http) = &self.link else {
+            return;
+        };
+        match event {
+            "endpoint" => {
+                let endpoint = resolve_url(base, data.trim());
+                tracing::debug!("endpoint=<{}> | mcp server announced its message endpoint", endpoint);
+                http.stream.send_replace(StreamState::Ready(endpoint));
+            }
+            "message" => match serde_json::from_str::<Value>(data) {
+                Ok(message) => {
+                    if let Some(reply) = self.dispatch(message) {
+                        let _ = http.post(&reply, self.timeout).await;
+                    }
+                }
+                Err(_) => tracing::debug!("data=<{}> | ignoring non-JSON event from mcp server", data),
+            },
+            _ => {}
+        }
+    }
+
+    /// Write one message to the server.
+    async fn send(&self, message: &Value) -> IndubitablyResult<()> {
+        let http = match &self.link {
+            Link::Stdio(stdin) => return write_line(stdin, message),
+            Link::Http(http) => http,
+        };
+        let response = http.post(message, self.timeout).await?;
+        if !http.streamable || response.body.is_empty() {
+            return Ok(());
+        }
+        // Streamable HTTP answers a request in the body of its post, as JSON or as an event stream
+        let is_stream = response.header("content-type").is_some_and(|kind| kind.starts_with("text/event-stream"));
+        let messages: Vec<Value> = if is_stream {
+            let mut parser = SseParser::default();
+            let mut events = parser.push(&response.body);
+            events.extend(parser.push(b"\n\n"));
+            events.into_iter().filter_map(|(_, data)| serde_json::from_str(&data).ok()).collect()
+        } else {
+            match response.json::<Value>() {
+                Ok(Value::Array(batch)) => batch,
+                Ok(message) => vec![message],
+                Err(_) => {
+                    return Err(McpError::ProtocolError(format!("Invalid response body: {}", response.text())).into())
+                }
+            }
+        };
+        for message in messages {
+            if let Some(reply) = self.dispatch(message) {
+                let _ = http.post(&reply, self.timeout).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a request and wait for its result, reconnecting a lost HTTP connection between attempts.
+    async fn request(&self, method: &str, params: Value) -> IndubitablyResult<Value> {
+        let mut attempt = 0;
+        loop {
+            match self.request_once(method, params.clone()).await {
+                Err(IndubitablyError::McpError(McpError::ConnectionFailed(reason)))
+                    if attempt < self.max_reconnects && matches!(self.link, Link::Http(_)) =>
+                {
+                    attempt += 1;
+                    tracing::warn!(
+                        "method=<{}>, attempt=<{}>, reason=<{}> | mcp connection lost, reconnecting",
+                        method,
+                        attempt,
+                        reason
+                    );
+                    tokio::time::sleep(RECONNECT_BACKOFF * 2u32.pow(attempt - 1)).await;
+                    if let Err(e) = Box::pin(self.reconnect(method)).await {
+                        tracing::debug!("method=<{}>, error=<{}> | mcp reconnection failed", method, e);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn request_once(&self, method: &str, params: Value) -> IndubitablyResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(id, sender);
+        let sent = self.send(&json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})).await;
+        if let Err(e) = sent {
+            self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+            return Err(e);
+        }
+        match tokio::time::timeout(self.timeout, receiver).await {
+            Ok(Ok(result)) => result.map_err(IndubitablyError::from),
+            Ok(Err(_)) => Err(McpError::ServerFailed("MCP server dropped the request".to_string()).into()),
+            Err(_) => {
+                self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+                Err(McpError::ClientFailed(format!(
+                    "MCP request '{}' timed out after {}s",
+                    method,
+                    self.timeout.as_secs()
+                ))
+                .into())
+            }
+        }
+    }
+
+    /// Re-establish a lost HTTP connection, starting a new session unless the failed request was the handshake.
+    async fn reconnect(&self, method: &str) -> IndubitablyResult<()> {
+        let Link::Http(http) = &self.link else {
+            return Ok(());
+        };
+        let new_session = if http.streamable {
+            http.session_expired.swap(false, Ordering::SeqCst)
+        } else {
+            self.open_stream().await?;
+            true
+        };
+        if new_session && method != "initialize" {
+            self.initialize().await?;
+        }
+        Ok(())
+    }
+
+    /// Send a notification, which has no response.
+    async fn notify(&self, method: &str, params: Value) -> IndubitablyResult<()> {
+        self.send(&json!({"jsonrpc": "2.0", "method": method, "params": params})).await
+    }
+
+    /// Perform the protocol handshake, returning the server's `initialize` result.
+    async fn initialize(&self) -> IndubitablyResult<Value> {
+        let version = match &self.link {
+            Link::Http(http) if http.streamable => MCP_STREAMABLE_HTTP_PROTOCOL_VERSION,
+            _ => MCP_PROTOCOL_VERSION,
+        };
+        let initialized = self
+            .request("initialize", json!({
+                "protocolVersion": version,
+                "capabilities": {},
+                "clientInfo": {"name": env!("CARGO_PKG_NAME"), "version": crate::VERSION},
+            }))
+            .await?;
+        let agreed = initialized.get("protocolVersion").and_then(Value::as_str);
+        if let (Link::Http(http), Some(agreed)) = (&self.link, agreed) {
+            *http.protocol_version.lock().unwrap_or_else(|e| e.into_inner()) = Some(agreed.to_string());
+        }
+        self.notify("notifications/initialized", json!({})).await?;
+        Ok(initialized)
+    }
+
+    /// Route a message from the server to its waiting request, returning the reply to a request from the server.
+    fn dispatch(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        if let Some(method) = message.get("method").and_then(Value::as_str) {
+            // Servers may ping the client; other requests are not supported
+            return id.map(|id| match method {
+                "ping" => json!({"jsonrpc": "2.0", "id": id, "result": {}}),
+                _ => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": METHOD_NOT_FOUND, "message": format!("Method '{}' not supported", method)},
+                }),
+            });
+        }
+        let id = id.as_ref().and_then(Value::as_u64)?;
+        let sender = self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id)?;
+        let result = match (message.get("result"), message.get("error")) {
+            (_, Some(error)) => Err(McpError::ServerFailed(format!(
+                "{} (code {})",
+                error.get("message").and_then(Value::as_str).unwrap_or("unknown error"),
+                error.get("code").and_then(Value::as_i64).unwrap_or_default()
+            ))),
+            (Some(result), None) => Ok(result.clone()),
+            (None, None) => Err(McpError::ProtocolError(format!("Response {} has no result or error", id))),
+        };
+        let _ = sender.send(result);
+        None
+    }
+
+    /// Fail every waiting request, such as when the server exits.
+    fn fail_pending(&self, error: impl Fn() -> McpError) {
+        for (_, sender) in self.pending.lock().unwrap_or_else(|e| e.into_inner()).drain() {
+            let _ = sender.send(Err(error()));
+        }
+    }
+
+    /// End the session of a streamable HTTP server.
+    async fn close(&self) {
+        let Link::Http(http) = &self.link else {
+            return;
+        };
+        if http.streamable && http.session_id.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+            let request = http.request("DELETE", &http.url);
+            let _ = tokio::time::timeout(self.timeout, http.client.send(request)).await;
+        }
+    }
+
+    /// Call a remote tool, mapping a result flagged as an error to a failed tool call.
+    async fn call_tool(&self, name: &str, arguments: Value) -> IndubitablyResult<Value> {
+        let arguments = if arguments.is_null() { json!({}) } else { arguments };
+        let result = self.request("tools/call", json!({"name": name, "arguments": arguments})).await?;
+        let content = result.get("content").and_then(Value::as_array).cloned().unwrap_or_default();
+        let text: Vec<&str> = content
+            .iter()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect();
+        if result.get("isError").and_then(Value::as_bool) == Some(true) {
+            return Err(ToolError::ExecutionFailed(format!("MCP tool '{}' failed: {}", name, text.join("\n"))).into());
+        }
+        Ok(match result.get("structuredContent") {
+            Some(structured) => structured.clone(),
+            None if text.len() == content.len() => Value::String(text.join("\n")),
+            None => Value::Array(content),
+        })
+    }
+}
+
+/// Write one line-delimited message to a server's stdin.
+fn write_line(stdin: &Mutex<ChildStdin>, message: &Value) -> IndubitablyResult<()> {
+    let mut stdin = stdin.lock().unwrap_or_else(|e| e.into_inner());
+    writeln!(stdin, "{}", message)
+        .and_then(|_| stdin.flush())
+        .map_err(|e| McpError::ClientFailed(format!("Failed to write to MCP server: {}", e)).into())
+}
+
+/// Resolve a URL announced by a server against the URL of its event stream.
+fn resolve_url(base: &str, reference: &str) -> String {
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        return reference.to_string();
+    }
+    let origin_end = base.find("://").map_or(0, |scheme| {
+        base[scheme + 3..].find('/').map_or(base.len(), |path| scheme + 3 + path)
+    });
+    if reference.starts_with('/') {
+        return format!("{}{}", &base[..origin_end], reference);
+    }
+    let directory_end = base[origin_end..].rfind('/').map_or(base.len(), |slash| origin_end + slash);
+    format!("{}/{}", &base[..directory_end], reference)
+}
+
+/// An incremental parser of server-sent events.
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Add received bytes, returning the name and data of each event they complete.
+    fn push(&mut self, chunk: &[u8]) -> Vec<(String, String)> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    let event = self.event.take().unwrap_or_else(|| "message".to_string());
+                    events.push((event, self.data.join("\n")));
+                }
+                self.event = None;
+                self.data.clear();
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+/// An MCP client that can connect to MCP servers.
 pub struct MCPClient {
     config: MCPClientConfig,
-    server_process: Option<tokio::process::Child>,
+    http_client: Option<Arc<dyn HttpClient>>,
+    server_process: Option<Child>,
+    connection: Option<Arc<McpConnection>>,
+    server_info: Option<MCPServerInfo>,
     tools: Vec<Tool>,
 }
 
+impl std::fmt::Debug for MCPClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MCPClient")
+            .field("config", &self.config)
+            .field("http_client", &self.http_client.is_some())
+            .field("connected", &self.is_connected())
+            .field("server_info", &self.server_info)
+            .field("tools", &self.tools.iter().map(|tool| tool.name.as_str()).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
 impl MCPClient {
     /// Create a new MCP client.
     pub fn new() -> Self {
-        Self {
-            config: MCPClientConfig::default(),
-            server_process: None,
-            tools: Vec::new(),
-        }
+        Self::with_config(MCPClientConfig::default())
     }
 
     /// Create a new MCP client with the given configuration.
     pub fn with_config(config: MCPClientConfig) -> Self {
         Self {
             config,
+            http_client: None,
             server_process: None,
+            connection: None,
+            server_info: None,
             tools: Vec::new(),
         }
     }
 
-    /// Connect to the MCP server.
+    /// Set the client used to reach servers over the SSE and streamable HTTP transports.
+    pub fn with_http_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Connect to the MCP server, perform the protocol handshake and discover its tools.
+    ///
+    /// Over stdio this starts the server process.
     pub async fn connect(&mut self) -> IndubitablyResult<()> {
-        // For now, this is a placeholder implementation
-        // In a real implementation, you would:
-        // 1. Start the server process
-        // 2. Establish communication (stdio, TCP, etc.)
-        // 3. Perform handshake
-        // 4. Discover available tools
-        
-        tracing::info!("Connecting to MCP server: {} {:?}", self.config.command, self.config.args);
-        
-        // Simulate connection delay
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        
-        // For now, just create some placeholder tools
-        self.tools = vec![
-            Tool::new(
-                "mcp_placeholder_1",
-                "Placeholder MCP tool 1",
-                Arc::new(|_| Ok(serde_json::Value::String("MCP tool 1 result".to_string()))),
-            ),
-            Tool::new(
-                "mcp_placeholder_2",
-                "Placeholder MCP tool 2",
-                Arc::new(|_| Ok(serde_json::Value::String("MCP tool 2 result".to_string()))),
-            ),
-        ];
-        
-        Ok(())
+        self.disconnect().await?;
+        let connection = match self.config.transport.clone() {
+            MCPTransport::Stdio => self.spawn_server()?,
+            MCPTransport::Sse { url } => self.open_remote(&url, false).await?,
+            MCPTransport::StreamableHttp { url } => self.open_remote(&url, true).await?,
+        };
+
+        match Self::handshake(&connection).await {
+            Ok((server_info, specs)) => {
+                tracing::info!(
+                    "server=<{}>, version=<{}>, tools=<{}> | connected to mcp server",
+                    server_info.name,
+                    server_info.version,
+                    specs.len()
+                );
+                self.tools = specs.into_iter().map(|spec| remote_tool(&connection, spec)).collect();
+                self.server_info = Some(server_info);
+                self.connection = Some(connection);
+                Ok(())
+            }
+            Err(e) => {
+                self.disconnect().await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Start the server process and take over its stdio.
+    fn spawn_server(&mut self) -> IndubitablyResult<Arc<McpConnection>> {
+        tracing::info!("command=<{}>, args=<{:?}> | connecting to mcp server", self.config.command, self.config.args);
+        let mut command = Command::new(&self.config.command);
+        command
+            .args(&self.config.args)
+            .envs(&self.config.environment)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(ref directory) = self.config.working_directory {
+            command.current_dir(directory);
+        }
+        let mut process = command.spawn().map_err(|e| {
+            McpError::ClientFailed(format!("Failed to start MCP server '{}': {}", self.config.command, e))
+        })?;
+        let connection = McpConnection::open(&mut process, Duration::from_secs(self.config.timeout_seconds));
+        if connection.is_err() {
+            let _ = process.kill();
+            let _ = process.wait();
+        } else {
+            self.server_process = Some(process);
+        }
+        connection
+    }
+
+    /// Open a connection to a remote server.
+    async fn open_remote(&self, url: &str, streamable: bool) -> IndubitablyResult<Arc<McpConnection>> {
+        tracing::info!("url=<{}>, streamable=<{}> | connecting to mcp server", url, streamable);
+        let client = self.http_client.clone().ok_or_else(|| {
+            McpError::ClientFailed("Connecting to a remote MCP server requires an HTTP client".to_string())
+        })?;
+        McpConnection::open_http(client, url, streamable, &self.config).await
+    }
+
+    /// Initialize the session and list every tool, following pagination cursors.
+    async fn handshake(connection: &McpConnection) -> IndubitablyResult<(MCPServerInfo, Vec<ToolSpec>)> {
+        let initialized = connection.initialize().await?;
+        let server = initialized.get("serverInfo").cloned().unwrap_or_default();
+        let text = |value: &Value, key: &str| value.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+        let server_info = MCPServerInfo {
+            name: text(&server, "name"),
+            version: text(&server, "version"),
+            description: text(&initialized, "instructions"),
+            capabilities: initialized
+                .get("capabilities")
+                .and_then(Value::as_object)
+                .map(|capabilities| capabilities.keys().cloned().collect())
+                .unwrap_or_default(),
+        };
+
+        let mut specs = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match cursor {
+                Some(ref cursor) => json!({"cursor": cursor}),
+                None => json!({}),
+            };
+            let page = connection.request("tools/list", params).await?;
+            let tools = page
+                .get("tools")
+                .and_then(Value::as_array)
+                .ok_or_else(|| McpError::ProtocolError("tools/list result has no tools".to_string()))?;
+            for tool in tools {
+                let schema = tool.get("inputSchema").cloned().unwrap_or_else(|| json!({"type": "object"}));
+                let name = tool
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| McpError::ProtocolError("Listed tool has no name".to_string()))?;
+                specs.push(
+                    ToolSpec::new(name, tool.get("description").and_then(Value::as_str).unwrap_or_default())
+                        .with_input_schema(schema),
+                );
+            }
+            cursor = page.get("nextCursor").and_then(Value::as_str).map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok((server_info, specs))
     }
 
     /// Disconnect from the MCP server.
     pub async fn disconnect(&mut self) -> IndubitablyResult<()> {
+        if let Some(connection) = self.connection.take() {
+            connection.close().await;
+        }
         if let Some(mut process) = self.server_process.take() {
-            let _ = process.kill().await;
+            let _ = process.kill();
+            let _ = process.wait();
         }
-        
+        self.server_info = None;
         self.tools.clear();
         Ok(())
     }
 
     /// Check if the client is connected.
     pub fn is_connected(&self) -> bool {
-        !self.tools.is_empty()
+        self.connection.is_some()
+    }
+
+    fn connection(&self) -> IndubitablyResult<&Arc<McpConnection>> {
+        self.connection
+            .as_ref()
+            .ok_or_else(|| McpError::ClientFailed("MCP client not connected".to_string()).into())
     }
 
     /// Get the available tools from the MCP server.
     pub async fn list_tools(&self) -> IndubitablyResult<Vec<ToolSpec>> {
-        if !self.is_connected() {
-            return Err(IndubitablyError::McpError(
-                crate::types::McpError::ClientFailed(
-                    "MCP client not connected".to_string(),
-                ),
-            ));
-        }
-        
+        self.connection()?;
         let specs: Vec<ToolSpec> = self.tools.iter().map(|tool| tool.spec()).collect();
         Ok(specs)
     }
 
-    /// Get the tools as Tool objects.
+    /// Get the tools as Tool objects, each calling the server when executed.
     pub async fn get_tools(&self) -> IndubitablyResult<Vec<Tool>> {
-        if !self.is_connected() {
-            return Err(IndubitablyError::McpError(
-                crate::types::McpError::ClientFailed(
-                    "MCP client not connected".to_string(),
-                ),
-            ));
-        }
-        
+        self.connection()?;
         Ok(self.tools.clone())
     }
 
     /// Execute a tool by name.
     pub async fn execute_tool(&self, tool_name: &str, input: serde_json::Value) -> IndubitablyResult<serde_json::Value> {
-        if !self.is_connected() {
-            return Err(IndubitablyError::McpError(
-                crate::types::McpError::ClientFailed(
-                    "MCP client not connected".to_string(),
-                ),
-            ));
-        }
-        
-        let tool = self.tools.iter().find(|t| t.name == tool_name).ok_or_else(|| {
-            IndubitablyError::McpError(
-                crate::types::McpError::ClientFailed(
-                    format!("Tool '{}' not found", tool_name),
-                ),
-            )
-        })?;
-        
-        tool.execute(input)
+        let connection = self.connection()?;
+        if !self.tools.iter().any(|t| t.name == tool_name) {
+            return Err(McpError::ClientFailed(format!("Tool '{}' not found", tool_name)).into());
+        }
+        connection.call_tool(tool_name, input).await
     }
 
     /// Get information about the MCP server.
     pub async fn get_server_info(&self) -> IndubitablyResult<MCPServerInfo> {
-        if !self.is_connected() {
-            return Err(IndubitablyError::McpError(
-                crate::types::McpError::ClientFailed(
-                    "MCP client not connected".to_string(),
-                ),
-            ));
-        }
-        
-        // For now, return placeholder info
-        Ok(MCPServerInfo {
-            name: "Placeholder MCP Server".to_string(),
-            version: "1.0.0".to_string(),
-            description: "A placeholder MCP server for testing".to_string(),
-            capabilities: vec!["tools".to_string(), "resources".to_string()],
-        })
+        self.connection()?;
+        self.server_info
+            .clone()
+            .ok_or_else(|| McpError::ClientFailed("MCP server info is unavailable".to_string()).into())
+    }
+}
+
+impl Default for MCPClient {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Wrap a listed tool so that executing it calls the server.
+fn remote_tool(connection: &Arc<McpConnection>, spec: ToolSpec) -> Tool {
+    let caller = Arc::downgrade(connection);
+    let name = spec.name.clone();
+    let function = move |input: Value| {
+        let connection = caller.upgrade();
+        let name = name.clone();
+        async move {
+            let connection = connection
+                .ok_or_else(|| McpError::ClientFailed(format!("MCP tool '{}' used after disconnecting", name)))?;
+            connection.call_tool(&name, input).await
+        }
+    };
+    let schema = spec.input_schema.unwrap_or_else(|| json!({"type": "object"}));
+    Tool::new_async(&spec.name, &spec.description, function)
+        .with_metadata(ToolMetadata::new().with_input_schema(schema))
+}
+
 impl Drop for MCPClient {
     fn drop(&mut self) {
         // Ensure we clean up the server process
         if let Some(mut process) = self.server_process.take() {
             let _ = process.kill();
+            let _ = process.wait();
         }
     }
 }
@@ -240,6 +1017,7 @@ pub struct MCPServerInfo {
 /// A builder for creating MCP clients with common configurations.
 pub struct MCPClientBuilder {
     config: MCPClientConfig,
+    http_client: Option<Arc<dyn HttpClient>>,
 }
 
 impl MCPClientBuilder {
@@ -247,6 +1025,7 @@ impl MCPClientBuilder {
     pub fn new() -> Self {
         Self {
             config: MCPClientConfig::default(),
+            http_client: None,
         }
     }
 
@@ -280,9 +1059,49 @@ impl MCPClientBuilder {
         self
     }
 
+    /// Connect to a remote server over the SSE transport.
+    pub fn sse(mut self, url: &str) -> Self {
+        self.config.transport = MCPTransport::Sse { url: url.to_string() };
+        self
+    }
+
+    /// Connect to a remote server over streamable HTTP.
+    pub fn streamable_http(mut self, url: &str) -> Self {
+        self.config.transport = MCPTransport::StreamableHttp { url: url.to_string() };
+        self
+    }
+
+    /// Add a header sent with every HTTP request.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.config.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Authenticate HTTP requests with a bearer token.
+    pub fn bearer_token(mut self, token: &str) -> Self {
+        self.config = self.config.with_bearer_token(token);
+        self
+    }
+
+    /// Set how many times a lost HTTP connection is re-established before a request fails.
+    pub fn max_reconnects(mut self, max_reconnects: u32) -> Self {
+        self.config.max_reconnects = max_reconnects;
+        self
+    }
+
+    /// Set the client used to reach remote servers.
+    pub fn http_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
     /// Build the MCP client.
     pub fn build(self) -> MCPClient {
-        MCPClient::with_config(self.config)
+        let client = MCPClient::with_config(self.config);
+        match self.http_client {
+            Some(http_client) => client.with_http_client(http_client),
+            None => client,
+        }
     }
 }
 
@@ -292,12 +1111,10 @@ impl Default for MCPClientBuilder {
     }
 }
 
-// Import Arc for the placeholder tools
-use std::sync::Arc;
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::HttpStreamingResponse;
 
     #[tokio::test]
     async fn test_mcp_client_config() {
@@ -332,44 +1149,226 @@ mod tests {
         assert_eq!(client.config.timeout_seconds, 120);
     }
 
+    /// A shell MCP server with an `echo` tool, a `fail` tool, and a paginated tool list.
+    #[cfg(unix)]
+    const TEST_SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s\n' "$line" | sed -n 's/^{"id":\([0-9]*\),.*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"echo-server","version":"0.1.0"},"instructions":"Test server"}}' ;;
+    *'"method":"tools/list"'*'"cursor":"2"'*)
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"tools":[{"name":"fail","description":"Always fails","inputSchema":{"type":"object"}}]}}' ;;
+    *'"method":"tools/list"'*)
+      echo '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info"}}'
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"tools":[{"name":"echo","description":"Echo text","inputSchema":{"type":"object","properties":{"text":{"type":"string"}}}}],"nextCursor":"2"}}' ;;
+    *'"method":"tools/call"'*'"name":"fail"'*)
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"content":[{"type":"text","text":"bad input"}],"isError":true}}' ;;
+    *'"method":"tools/call"'*)
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"content":[{"type":"text","text":"pong"}]}}' ;;
+  esac
+done
+"#;
+
+    #[cfg(unix)]
     #[tokio::test]
     async fn test_mcp_client_lifecycle() {
-        let mut client = MCPClient::new();
-        
+        let mut client = MCPClientBuilder::new()
+            .command("sh")
+            .args(vec!["-c".to_string(), TEST_SERVER.to_string()])
+            .timeout(5)
+            .build();
+
         // Initially not connected
         assert!(!client.is_connected());
-        
-        // Connect
-        let result = client.connect().await;
-        assert!(result.is_ok());
+        assert!(client.list_tools().await.is_err());
+
+        client.connect().await.unwrap();
         assert!(client.is_connected());
-        
-        // List tools
-        let tools = client.list_tools().await;
-        assert!(tools.is_ok());
-        assert!(!tools.unwrap().is_empty());
-        
-        // Execute tool
-        let result = client.execute_tool("mcp_placeholder_1", serde_json::Value::Null).await;
-        assert!(result.is_ok());
-        
-        // Disconnect
-        let result = client.disconnect().await;
-        assert!(result.is_ok());
+        let info = client.get_server_info().await.unwrap();
+        assert_eq!(info.name, "echo-server");
+        assert_eq!(info.version, "0.1.0");
+        assert_eq!(info.description, "Test server");
+        assert_eq!(info.capabilities, vec!["tools"]);
+
+        // Both pages of tools are discovered, with their schemas
+        let specs = client.list_tools().await.unwrap();
+        let names: Vec<&str> = specs.iter().map(|spec| spec.name.as_str()).collect();
+        assert_eq!(names, vec!["echo", "fail"]);
+        assert_eq!(specs[0].input_schema.as_ref().unwrap()["properties"]["text"]["type"], "string");
+
+        let output = client.execute_tool("echo", serde_json::json!({"text": "ping"})).await.unwrap();
+        assert_eq!(output, "pong");
+        let error = client.execute_tool("fail", serde_json::Value::Null).await.unwrap_err();
+        assert!(matches!(
+            error,
+            IndubitablyError::ToolError(ToolError::ExecutionFailed(ref message)) if message.contains("bad input")
+        ));
+        assert!(client.execute_tool("missing", serde_json::Value::Null).await.is_err());
+
+        // Tools call the server from synchronous tool functions
+        let tools = client.get_tools().await.unwrap();
+        assert_eq!(tools[0].execute(serde_json::json!({"text": "ping"})).unwrap(), "pong");
+
+        client.disconnect().await.unwrap();
+        assert!(!client.is_connected());
+        assert!(tools[0].execute(serde_json::json!({})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mcp_client_connect_fails_without_server() {
+        let mut client = MCPClientBuilder::new().command("indubitably-missing-mcp-server").build();
+        assert!(matches!(
+            client.connect().await,
+            Err(IndubitablyError::McpError(McpError::ClientFailed(_)))
+        ));
         assert!(!client.is_connected());
     }
 
+    /// Answer a request the way a remote server with an `echo` tool would.
+    fn answer(message: &Value) -> Option<Value> {
+        let id = message.get("id")?;
+        let result = match message.get("method")?.as_str()? {
+            "initialize" => json!({
+                "protocolVersion": message["params"]["protocolVersion"],
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "hosted", "version": "1.0.0"},
+            }),
+            "tools/list" => json!({"tools": [{"name": "echo", "inputSchema": {"type": "object"}}]}),
+            _ => json!({"content": [{"type": "text", "text": message["params"]["arguments"]["text"]}]}),
+        };
+        Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    }
+
+    /// A streamable HTTP server requiring a bearer token, which can forget its session once.
+    #[derive(Default)]
+    struct HostedServer {
+        sessions: Mutex<u32>,
+        forget_session: AtomicBool,
+        deleted: AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for HostedServer {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            if request.header("authorization") != Some("Bearer secret") {
+                return Ok(HttpResponse::new(401, Vec::new()));
+            }
+            if request.method == "DELETE" {
+                self.deleted.store(true, Ordering::SeqCst);
+                return Ok(HttpResponse::new(200, Vec::new()));
+            }
+            let message: Value = serde_json::from_slice(&request.body).unwrap();
+            let mut sessions = self.sessions.lock().unwrap();
+            if message["method"] == "initialize" {
+                *sessions += 1;
+                let body = serde_json::to_vec(&answer(&message)).unwrap();
+                return Ok(HttpResponse::new(200, body).with_header("mcp-session-id", &format!("s{}", sessions)));
+            }
+            if request.header(MCP_SESSION_ID_HEADER) != Some(format!("s{}", sessions).as_str()) {
+                return Ok(HttpResponse::new(400, Vec::new()));
+            }
+            if self.forget_session.swap(false, Ordering::SeqCst) {
+                return Ok(HttpResponse::new(404, Vec::new()));
+            }
+            match answer(&message) {
+                // Calls are answered as an event stream, after pinging the client
+                Some(response) if message["method"] == "tools/call" => {
+                    let ping = json!({"jsonrpc": "2.0", "id": "ping-1", "method": "ping"});
+                    let body = format!("event: message\ndata: {}\n\nevent: message\ndata: {}\n\n", ping, response);
+                    Ok(HttpResponse::new(200, body.into_bytes()).with_header("content-type", "text/event-stream"))
+                }
+                Some(response) => Ok(HttpResponse::new(200, serde_json::to_vec(&response).unwrap())
+                    .with_header("content-type", "application/json")),
+                None => Ok(HttpResponse::new(202, Vec::new())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mcp_client_streamable_http_renews_expired_session() {
+        let server = Arc::new(HostedServer::default());
+        let mut client = MCPClientBuilder::new()
+            .streamable_http("https://mcp.example.com/mcp")
+            .bearer_token("secret")
+            .http_client(server.clone())
+            .build();
+        client.connect().await.unwrap();
+        assert_eq!(client.get_server_info().await.unwrap().name, "hosted");
+        assert_eq!(client.execute_tool("echo", json!({"text": "one"})).await.unwrap(), "one");
+
+        server.forget_session.store(true, Ordering::SeqCst);
+        assert_eq!(client.execute_tool("echo", json!({"text": "two"})).await.unwrap(), "two");
+        assert_eq!(*server.sessions.lock().unwrap(), 2);
+
+        client.disconnect().await.unwrap();
+        assert!(server.deleted.load(Ordering::SeqCst));
+        assert!(!format!("{:?}", client).contains("secret"));
+
+        let mut unauthorized = MCPClientBuilder::new()
+            .streamable_http("https://mcp.example.com/mcp")
+            .http_client(server.clone())
+            .build();
+        assert!(matches!(
+            unauthorized.connect().await,
+            Err(IndubitablyError::McpError(McpError::ClientFailed(_)))
+        ));
+        let mut without_client = MCPClientBuilder::new().sse("https://mcp.example.com/sse").build();
+        assert!(without_client.connect().await.is_err());
+    }
+
+    /// A server on the SSE transport whose event stream can be cut.
+    #[derive(Default)]
+    struct LegacyServer {
+        streams: Mutex<Vec<tokio::sync::mpsc::UnboundedSender<IndubitablyResult<Vec<u8>>>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for LegacyServer {
+        async fn send(&self, request: HttpRequest) -> IndubitablyResult<HttpResponse> {
+            let streams = self.streams.lock().unwrap();
+            let session = format!("session={}", streams.len());
+            let Some(stream) = streams.last().filter(|_| request.url.ends_with(&session)) else {
+                return Ok(HttpResponse::new(503, Vec::new()));
+            };
+            let message: Value = serde_json::from_slice(&request.body).unwrap();
+            if let Some(response) = answer(&message) {
+                let _ = stream.send(Ok(format!("event: message\ndata: {}\n\n", response).into_bytes()));
+            }
+            Ok(HttpResponse::new(202, Vec::new()))
+        }
+
+        async fn send_streaming(&self, request: HttpRequest) -> IndubitablyResult<HttpStreamingResponse> {
+            assert_eq!(request.url, "https://mcp.example.com/sse");
+            let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            let mut streams = self.streams.lock().unwrap();
+            let endpoint = format!("event: endpoint\ndata: /messages?session={}\n\n", streams.len() + 1);
+            sender.send(Ok(endpoint.into_bytes())).unwrap();
+            streams.push(sender);
+            Ok(HttpStreamingResponse {
+                status: 200,
+                headers: Vec::new(),
+                body: Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(receiver)),
+            })
+        }
+    }
+
     #[tokio::test]
-    async fn test_mcp_client_server_info() {
-        let mut client = MCPClient::new();
+    async fn test_mcp_client_sse_reconnects_after_stream_loss() {
+        let server = Arc::new(LegacyServer::default());
+        let mut client = MCPClientBuilder::new()
+            .sse("https://mcp.example.com/sse")
+            .http_client(server.clone())
+            .timeout(5)
+            .build();
         client.connect().await.unwrap();
-        
-        let info = client.get_server_info().await;
-        assert!(info.is_ok());
-        
-        let info = info.unwrap();
-        assert_eq!(info.name, "Placeholder MCP Server");
-        assert_eq!(info.version, "1.0.0");
-        assert!(!info.capabilities.is_empty());
+        assert_eq!(client.execute_tool("echo", json!({"text": "one"})).await.unwrap(), "one");
+
+        // Cutting the stream ends the session; the client opens a new one and retries
+        server.streams.lock().unwrap().clear();
+        let tools = client.get_tools().await.unwrap();
+        let output = tokio::task::spawn_blocking(move || tools[0].execute(json!({"text": "two"}))).await.unwrap();
+        assert_eq!(output.unwrap(), "two");
+        assert_eq!(server.streams.lock().unwrap().len(), 1);
     }
 }
@@ -0,0 +1,417 @@
+//! MCP server for the SDK.
+//! 
+//! This module provides `MCPServer`, which serves the tools of a
+//! `ToolRegistry` over the Model Context Protocol so that Claude Desktop and
+//! other MCP clients can call them. The server speaks newline-delimited
+//! JSON-RPC on stdio, or streamable HTTP through `handle_http`, which servers
+//! mount by converting their requests to `HttpRequest`. A whole agent can be
+//! exposed as one more tool that takes a prompt and returns the agent's answer.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::executor::{ToolExecutionContext, ToolExecutor};
+use super::mcp::{MCP_PROTOCOL_VERSION, MCP_SESSION_ID_HEADER, MCP_STREAMABLE_HTTP_PROTOCOL_VERSION};
+use super::registry::ToolRegistry;
+use crate::agent::Agent;
+use crate::models::{HttpRequest, HttpResponse};
+use crate::types::{IndubitablyError, IndubitablyResult, McpError};
+
+/// The protocol versions the server accepts, newest first.
+pub const MCP_SERVER_PROTOCOL_VERSIONS: &[&str] = &[MCP_STREAMABLE_HTTP_PROTOCOL_VERSION, MCP_PROTOCOL_VERSION];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Numbers HTTP sessions so their ids are unique within the process.
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(1);
+
+/// An agent served as a tool.
+struct AgentTool {
+    name: String,
+    description: String,
+    agent: Arc<tokio::sync::Mutex<Agent>>,
+}
+
+/// Serves the tools of a registry to MCP clients.
+pub struct MCPServer {
+    name: String,
+    version: String,
+    instructions: Option<String>,
+    registry: Arc<ToolRegistry>,
+    executor: ToolExecutor,
+    agent: Option<AgentTool>,
+    /// The ids of the open streamable HTTP sessions.
+    sessions: Mutex<HashSet<String>>,
+}
+
+impl std::fmt::Debug for MCPServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MCPServer")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("agent", &self.agent.as_ref().map(|agent| &agent.name))
+            .finish()
+    }
+}
+
+impl MCPServer {
+    /// Create a server for the tools of a registry.
+    pub fn new(name: &str, registry: Arc<ToolRegistry>) -> Self {
+        Self {
+            name: name.to_string(),
+            version: crate::VERSION.to_string(),
+            instructions: None,
+            registry,
+            executor: ToolExecutor::new(),
+            agent: None,
+            sessions: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Set the server version reported to clients.
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Set the instructions reported to clients on initialization.
+    pub fn with_instructions(mut self, instructions: &str) -> Self {
+        self.instructions = Some(instructions.to_string());
+        self
+    }
+
+    /// Set the executor that runs tool calls.
+    pub fn with_executor(mut self, executor: ToolExecutor) -> Self {
+        self.executor = executor;
+        self
+    }
+
+    /// Serve an agent as a tool that takes a `prompt` and returns the agent's answer.
+    ///
+    /// Calls are run one at a time, each continuing the agent's conversation.
+    pub fn with_agent(mut self, name: &str, description: &str, agent: Arc<tokio::sync::Mutex<Agent>>) -> Self {
+        self.agent = Some(AgentTool {
+            name: name.to_string(),
+            description: description.to_string(),
+            agent,
+        });
+        self
+    }
+
+    /// Handle a JSON-RPC message or batch, returning the response if one is due.
+    ///
+    /// Notifications and responses from the client get no response.
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        match message {
+            Value::Array(batch) if !batch.is_empty() => {
+                let mut responses = Vec::new();
+                for message in batch {
+                    responses.extend(Box::pin(self.handle_message(message)).await);
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            Value::Object(ref object) => {
+                let id = object.get("id").cloned();
+                let Some(method) = object.get("method").and_then(Value::as_str) else {
+                    // A response to a request the server never sends, or a malformed message
+                    return id.filter(|_| !object.contains_key("result") && !object.contains_key("error")).map(
+                        |id| error_message(id, INVALID_REQUEST, "Message has no method"),
+                    );
+                };
+                let params = object.get("params").cloned().unwrap_or_else(|| json!({}));
+                let outcome = self.dispatch(method, params).await;
+                let id = id?;
+                Some(match outcome {
+                    Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                    Err((code, message)) => error_message(id, code, &message),
+                })
+            }
+            _ => Some(error_message(Value::Null, INVALID_REQUEST, "Message is not a JSON-RPC object")),
+        }
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools().await),
+            "tools/call" => {
+                let Some(name) = params.get("name").and_then(Value::as_str) else {
+                    return Err((INVALID_PARAMS, "Missing tool name".to_string()));
+                };
+                let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                self.call_tool(name, arguments).await
+            }
+            method if method.starts_with("notifications/") => Ok(Value::Null),
+            method => {
+                tracing::debug!("method=<{}> | mcp server received unsupported method", method);
+                Err((METHOD_NOT_FOUND, format!("Method '{}' not found", method)))
+            }
+        }
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params.get("protocolVersion").and_then(Value::as_str);
+        let version = requested
+            .filter(|version| MCP_SERVER_PROTOCOL_VERSIONS.contains(version))
+            .unwrap_or(MCP_SERVER_PROTOCOL_VERSIONS[0]);
+        let client = params.pointer("/clientInfo/name").and_then(Value::as_str).unwrap_or("unknown");
+        tracing::debug!("client=<{}>, protocol_version=<{}> | mcp client initialized", client, version);
+        let mut result = json!({
+            "protocolVersion": version,
+            "capabilities": {"tools": {"listChanged": false}},
+            "serverInfo": {"name": self.name, "version": self.version},
+        });
+        if let Some(ref instructions) = self.instructions {
+            result["instructions"] = json!(instructions);
+        }
+        result
+    }
+
+    async fn list_tools(&self) -> Value {
+        let mut tools: Vec<Value> = self
+            .registry
+            .list_tools()
+            .await
+            .iter()
+            .map(|tool| {
+                let mut entry = json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": tool.metadata.input_schema.clone().unwrap_or_else(|| json!({"type": "object"})),
+                });
+                if let Some(ref schema) = tool.metadata.output_schema {
+                    entry["outputSchema"] = schema.clone();
+                }
+                entry
+            })
+            .collect();
+        tools.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+        if let Some(ref agent) = self.agent {
+            tools.push(json!({
+                "name": agent.name,
+                "description": agent.description,
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {"prompt": {"type": "string", "description": "The message for the agent"}},
+                    "required": ["prompt"],
+                },
+            }));
+        }
+        json!({"tools": tools})
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, (i64, String)> {
+        if let Some(agent) = self.agent.as_ref().filter(|agent| agent.name == name) {
+            let Some(prompt) = arguments.get("prompt").and_then(Value::as_str) else {
+                return Ok(tool_result(json!("Missing 'prompt' argument"), true));
+            };
+            return Ok(match agent.agent.lock().await.run(prompt).await {
+                Ok(result) => tool_result(json!(result.response), false),
+                Err(e) => tool_result(json!(e.to_string()), true),
+            });
+        }
+        let Some(tool) = self.registry.get(name).await else {
+            return Err((INVALID_PARAMS, format!("Unknown tool '{}'", name)));
+        };
+        let result = self.executor.execute(&tool, ToolExecutionContext::new(name, arguments)).await;
+        tracing::debug!("tool_name=<{}>, success=<{}> | mcp server ran tool", name, result.is_success());
+        Ok(match result.error() {
+            Some(error) if !result.is_success() => tool_result(json!(error), true),
+            _ => tool_result(result.output().clone(), false),
+        })
+    }
+
+    /// Serve newline-delimited JSON-RPC from a reader to a writer until the reader closes.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> IndubitablyResult<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await.map_err(server_error)? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => self.handle_message(message).await,
+                Err(e) => Some(error_message(Value::Null, PARSE_ERROR, &format!("Invalid JSON: {}", e))),
+            };
+            if let Some(response) = response {
+                let mut line = response.to_string();
+                line.push('\n');
+                writer.write_all(line.as_bytes()).await.map_err(server_error)?;
+                writer.flush().await.map_err(server_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serve on the process's stdin and stdout, as MCP clients expect of a launched server.
+    ///
+    /// Nothing else may be written to stdout while the server runs.
+    pub async fn serve_stdio(&self) -> IndubitablyResult<()> {
+        tracing::debug!("server=<{}> | serving mcp on stdio", self.name);
+        self.serve(tokio::io::BufReader::new(tokio::io::stdin()), tokio::io::stdout()).await
+    }
+
+    /// Handle a streamable HTTP request, returning a JSON response.
+    ///
+    /// `POST` carries JSON-RPC messages, `initialize` opens a session whose id
+    /// is returned in the `Mcp-Session-Id` header, and `DELETE` ends it.
+    pub async fn handle_http(&self, request: &HttpRequest) -> HttpResponse {
+        let session_id = request.header(MCP_SESSION_ID_HEADER).map(str::to_string);
+        let method = request.method.to_ascii_uppercase();
+        if method == "DELETE" {
+            return match session_id {
+                Some(id) if self.lock_sessions().remove(&id) => HttpResponse::new(200, Vec::new()),
+                _ => json_response(404, json!({"error": "unknown session"})),
+            };
+        }
+        if method != "POST" {
+            return json_response(405, json!({"error": "method not allowed"})).with_header("allow", "POST, DELETE");
+        }
+
+        let message: Value = match serde_json::from_slice(&request.body) {
+            Ok(message) => message,
+            Err(e) => {
+                let error = error_message(Value::Null, PARSE_ERROR, &format!("Invalid JSON: {}", e));
+                return json_response(400, error);
+            }
+        };
+        let initializes = |message: &Value| message.get("method").and_then(Value::as_str) == Some("initialize");
+        let opens_session = match message {
+            Value::Array(ref batch) => batch.iter().any(initializes),
+            ref message => initializes(message),
+        };
+        let session_id = if opens_session {
+            let id = format!("mcp-{}-{}", std::process::id(), NEXT_SESSION.fetch_add(1, Ordering::Relaxed));
+            self.lock_sessions().insert(id.clone());
+            id
+        } else {
+            match session_id {
+                Some(id) if self.lock_sessions().contains(&id) => id,
+                Some(_) => return json_response(404, json!({"error": "unknown session"})),
+                None => {
+                    let error = format!("missing {} header", MCP_SESSION_ID_HEADER);
+                    return json_response(400, json!({"error": error}));
+                }
+            }
+        };
+
+        match self.handle_message(message).await {
+            Some(response) => json_response(200, response).with_header(MCP_SESSION_ID_HEADER, &session_id),
+            None => HttpResponse::new(202, Vec::new()).with_header(MCP_SESSION_ID_HEADER, &session_id),
+        }
+    }
+
+    fn lock_sessions(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Build the result of a tool call, with the output as text and, for objects, as structured content.
+fn tool_result(output: Value, is_error: bool) -> Value {
+    let text = match output {
+        Value::String(ref text) => text.clone(),
+        ref output => output.to_string(),
+    };
+    let mut result = json!({"content": [{"type": "text", "text": text}], "isError": is_error});
+    if output.is_object() && !is_error {
+        result["structuredContent"] = output;
+    }
+    result
+}
+
+fn error_message(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn json_response(status: u16, body: Value) -> HttpResponse {
+    HttpResponse::new(status, body.to_string().into_bytes()).with_header("content-type", "application/json")
+}
+
+fn server_error(error: std::io::Error) -> IndubitablyError {
+    McpError::ServerFailed(error.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::tools::registry::{Tool, ToolMetadata};
+    use crate::models::model::{MockModel, ModelResponse};
+
+    #[tokio::test]
+    async fn test_mcp_server_serves_registry_and_agent() {
+        let registry = Arc::new(ToolRegistry::new());
+        let add = Tool::new(
+            "add",
+            "Add two numbers",
+            Arc::new(|input: Value| Ok(json!({"sum": input["a"].as_i64().unwrap() + input["b"].as_i64().unwrap()}))),
+        )
+        .with_metadata(ToolMetadata::new().with_input_schema(json!({
+            "type": "object",
+            "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+            "required": ["a", "b"],
+        })));
+        registry.register(add).await.unwrap();
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("Paris")]);
+        let agent = AgentBuilder::new().model(Box::new(model)).build().unwrap();
+        let server = MCPServer::new("calculator", registry)
+            .with_version("1.2.0")
+            .with_agent("ask_agent", "Ask the agent", Arc::new(tokio::sync::Mutex::new(agent)));
+
+        let call = |id: u64, name: &str, arguments: Value| {
+            let params = json!({"name": name, "arguments": arguments});
+            json!({"jsonrpc": "2.0", "id": id, "method": "tools/call", "params": params})
+        };
+        let input = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocolVersion": "2024-11-05"}}),
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+            call(3, "add", json!({"a": 2, "b": 3})),
+            call(4, "add", json!({"a": "x"})),
+            call(5, "ask_agent", json!({"prompt": "Capital of France?"})),
+            json!({"jsonrpc": "2.0", "id": 6, "method": "resources/list"}),
+        ];
+        let mut lines: String = input.iter().map(|message| format!("{}\n", message)).collect();
+        lines.push_str("not json\n");
+        let mut output = Vec::new();
+        server.serve(lines.as_bytes(), &mut output).await.unwrap();
+        let responses: Vec<Value> =
+            String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+        assert_eq!(responses.len(), 7);
+        assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(responses[0]["result"]["serverInfo"]["version"], "1.2.0");
+        let names: Vec<&str> =
+            responses[1]["result"]["tools"].as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["add", "ask_agent"]);
+        assert_eq!(responses[2]["result"]["structuredContent"]["sum"], 5);
+        assert_eq!(responses[3]["result"]["isError"], true);
+        assert_eq!(responses[4]["result"]["content"][0]["text"], "Paris");
+        assert_eq!(responses[5]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[6]["error"]["code"], PARSE_ERROR);
+
+        // Streamable HTTP: initialize opens a session that later requests must carry
+        let post = |body: Value| HttpRequest::post("http://localhost/mcp").with_json_body(&body).unwrap();
+        let opened = server.handle_http(&post(input[0].clone())).await;
+        let session = opened.header(MCP_SESSION_ID_HEADER).unwrap().to_string();
+        assert_eq!(server.handle_http(&post(input[2].clone())).await.status, 400);
+        let listed = server.handle_http(&post(input[2].clone()).with_header(MCP_SESSION_ID_HEADER, &session)).await;
+        assert_eq!(listed.json::<Value>().unwrap()["result"]["tools"].as_array().unwrap().len(), 2);
+        let notified = server.handle_http(&post(input[1].clone()).with_header(MCP_SESSION_ID_HEADER, &session)).await;
+        assert_eq!(notified.status, 202);
+        let closed = HttpRequest::new("DELETE", "http://localhost/mcp").with_header(MCP_SESSION_ID_HEADER, &session);
+        assert_eq!(server.handle_http(&closed).await.status, 200);
+        assert_eq!(server.handle_http(&closed).await.status, 404);
+    }
+}
@@ -6,9 +6,58 @@
 pub mod registry;
 pub mod decorator;
 pub mod executor;
+pub mod artifacts;
+pub mod constraints;
+pub mod policy;
+pub mod schema;
+pub mod strict;
+pub mod selector;
+pub mod summarize;
+pub mod repair;
+pub mod workspace;
+pub mod image_generation;
+pub mod fs;
+pub mod patch;
+pub mod test_runner;
+pub mod mcp;
+pub mod mcp_server;
+pub mod render;
+pub mod manifest;
+pub mod http_tool;
+pub mod result_processor;
+pub mod wasm;
 
-pub use registry::{Tool, ToolFunction, ToolMetadata};
+pub use registry::{
+    AsyncToolFunction, Tool, ToolEffect, ToolFilter, ToolFunction, ToolFuture, ToolMetadata, TOOL_NAMESPACE_SEPARATOR,
+};
 pub use executor::ToolExecutionResult;
+pub use artifacts::{ArtifactStore, FileArtifactStore, InMemoryArtifactStore, S3ArtifactStore, SpilloverPolicy};
+pub use constraints::{ArgumentConstraint, ArgumentGuard, ConstraintViolation};
+pub use policy::{ApprovalCallback, ApprovalDecision, ApprovalRequest, DenialReason, PolicyDenial, ToolPolicy};
+pub use schema::validate_input;
+pub use strict::{strict_tool_schema, to_strict_schema, StrictSchema};
+pub use selector::{Embedder, EmbeddingToolSelector, KeywordToolSelector, ToolSelector};
+pub use summarize::ToolOutputSummarizer;
+pub use decorator::tool;
+pub use image_generation::create_generate_image_tool;
+pub use fs::{create_fs_tools, FileJournal};
+pub use patch::{apply_unified_diff, create_apply_patch_tool, parse_unified_diff};
+pub use test_runner::{create_run_tests_tool, TestCommand, TestOutcome};
+pub use mcp::{MCPClient, MCPClientBuilder, MCPClientConfig, MCPServerInfo, MCPTransport};
+pub use mcp_server::{MCPServer, MCP_SERVER_PROTOCOL_VERSIONS};
+pub use http_tool::{
+    load_http_tools, load_http_tools_from_text, parse_http_tools, HttpToolAuth, HttpToolDefinition, HTTP_METHODS,
+};
+pub use manifest::{parse_manifest_file, parse_manifest_text, parse_toml, parse_yaml};
+pub use render::{
+    DiffRenderer, ImageLinkRenderer, RenderedResult, RendererRegistry, ResultRenderer, TableRenderer, MAX_TABLE_ROWS,
+};
+pub use result_processor::{
+    OutputLimits, OversizeStrategy, ProcessAction, ProcessedOutput, ToolResultProcessor,
+    DEFAULT_MAX_SUMMARY_INPUT_BYTES,
+};
+pub use wasm::WasmToolLoader;
+pub use workspace::{current_working_directory, Workspace, WorkspaceCleanup, WorkspaceConfig, WorkspaceFile};
 
 // Re-export commonly used types
 pub use registry::ToolRegistry;
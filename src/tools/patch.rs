@@ -0,0 +1,333 @@
+//! The built-in patch tool for the SDK.
+//! 
+//! This module parses unified diffs, such as those `git diff` prints, and
+//! applies them to files under a root directory. A patch is validated in
+//! full before anything is written: every hunk's context and removed lines
+//! must match the file, allowing the hunk to have moved a few lines, and
+//! the line counts must agree with the hunk headers. A patch that does not
+//! apply cleanly changes nothing. Files the patch changes are recorded in a
+//! `FileJournal` first, so they can be rolled back.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use super::fs::{resolve_in, FileJournal};
+use super::registry::{Tool, ToolEffect, ToolMetadata};
+use crate::types::{IndubitablyError, IndubitablyResult, ToolError};
+
+/// The name of the patch tool.
+pub const APPLY_PATCH_TOOL_NAME: &str = "apply_patch";
+
+/// The most lines a hunk may have moved from where its header places it.
+pub const MAX_HUNK_OFFSET: usize = 50;
+
+/// The path of a missing file in a diff header.
+const DEV_NULL: &str = "/dev/null";
+
+fn patch_error(message: impl std::fmt::Display) -> IndubitablyError {
+    IndubitablyError::ToolError(ToolError::InvalidInput(format!("Patch does not apply: {}", message)))
+}
+
+/// A line of a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    /// A line kept as is.
+    Context(String),
+    /// A line removed.
+    Remove(String),
+    /// A line added.
+    Add(String),
+}
+
+/// A hunk of changes to one region of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The first line of the region in the original file, starting at 1.
+    pub old_start: usize,
+    /// The lines of the hunk, in order.
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// Get the lines the hunk expects in the original file.
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Get the lines the hunk leaves in the new file.
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// The changes a diff makes to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// The original path, or `None` for a new file.
+    pub old_path: Option<String>,
+    /// The new path, or `None` for a deleted file.
+    pub new_path: Option<String>,
+    /// The hunks, in file order.
+    pub hunks: Vec<Hunk>,
+    /// Whether the new file ends without a newline.
+    pub no_newline_at_end: bool,
+}
+
+impl FilePatch {
+    /// Get the path the patch changes, relative to the root.
+    pub fn path(&self) -> &str {
+        self.new_path.as_deref().or(self.old_path.as_deref()).unwrap_or_default()
+    }
+}
+
+/// Parse a header path, dropping the `a/` or `b/` prefix and any timestamp.
+fn header_path(rest: &str) -> Option<String> {
+    let path = rest.split('\t').next().unwrap_or(rest).trim();
+    if path == DEV_NULL {
+        return None;
+    }
+    let path = path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")).unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Parse a hunk range such as `12,3` into its start and length.
+fn hunk_range(range: &str) -> Option<(usize, usize)> {
+    let range = &range[1..];
+    match range.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Parse a unified diff into the changes it makes to each file.
+///
+/// Hunk line counts are checked against their headers.
+pub fn parse_unified_diff(diff: &str) -> IndubitablyResult<Vec<FilePatch>> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut lines = diff.lines().peekable();
+    while let Some(line) = lines.next() {
+        if let Some(rest) = line.strip_prefix("--- ") {
+            let Some(new_header) = lines.next().and_then(|next| next.strip_prefix("+++ ")) else {
+                return Err(patch_error(format!("'{}' is not followed by a '+++' line", line)));
+            };
+            patches.push(FilePatch {
+                old_path: header_path(rest),
+                new_path: header_path(new_header),
+                hunks: Vec::new(),
+                no_newline_at_end: false,
+            });
+        } else if line.starts_with("@@") {
+            let patch = patches.last_mut().ok_or_else(|| patch_error("hunk before any file header"))?;
+            let ranges: Vec<&str> = line.split_whitespace().skip(1).take(2).collect();
+            let (Some((old_start, old_len)), Some((_, new_len))) = (
+                ranges.first().filter(|range| range.starts_with('-')).and_then(|range| hunk_range(range)),
+                ranges.get(1).filter(|range| range.starts_with('+')).and_then(|range| hunk_range(range)),
+            ) else {
+                return Err(patch_error(format!("malformed hunk header '{}'", line)));
+            };
+            let mut hunk = Hunk { old_start, lines: Vec::new() };
+            let (mut old_seen, mut new_seen) = (0, 0);
+            while old_seen < old_len || new_seen < new_len {
+                let Some(body) = lines.next() else {
+                    break;
+                };
+                let text = body.get(1..).unwrap_or_default().to_string();
+                match body.chars().next() {
+                    Some('+') => {
+                        new_seen += 1;
+                        hunk.lines.push(HunkLine::Add(text));
+                    }
+                    Some('-') => {
+                        old_seen += 1;
+                        hunk.lines.push(HunkLine::Remove(text));
+                    }
+                    Some(' ') | None => {
+                        old_seen += 1;
+                        new_seen += 1;
+                        hunk.lines.push(HunkLine::Context(text));
+                    }
+                    Some('\\') => {}
+                    _ => return Err(patch_error(format!("unexpected line '{}' in hunk", body))),
+                }
+            }
+            if old_seen != old_len || new_seen != new_len {
+                return Err(patch_error(format!(
+                    "hunk '{}' has {} original and {} new lines",
+                    line, old_seen, new_seen
+                )));
+            }
+            if lines.peek().is_some_and(|next| next.starts_with("\\ No newline")) {
+                lines.next();
+                patch.no_newline_at_end = hunk.lines.last().is_some_and(|last| !matches!(last, HunkLine::Remove(_)));
+            }
+            patch.hunks.push(hunk);
+        }
+    }
+    if patches.is_empty() {
+        return Err(patch_error("no file headers found"));
+    }
+    if let Some(empty) = patches.iter().find(|patch| patch.hunks.is_empty() && patch.new_path.is_some()) {
+        return Err(patch_error(format!("{} has no hunks", empty.path())));
+    }
+    Ok(patches)
+}
+
+/// Find where a hunk's original lines start, nearest the header's position first.
+fn locate_hunk(lines: &[String], expected: &[&str], hint: usize) -> Option<usize> {
+    let matches_at = |start: usize| {
+        lines.get(start..start + expected.len()).is_some_and(|window| window.iter().zip(expected).all(|(a, b)| a == b))
+    };
+    (0..=MAX_HUNK_OFFSET).find_map(|offset| {
+        [hint.checked_add(offset), hint.checked_sub(offset)]
+            .into_iter()
+            .flatten()
+            .find(|&start| matches_at(start))
+    })
+}
+
+/// Compute the new contents of a file, or `None` if the patch deletes it.
+fn patched_contents(original: Option<&str>, patch: &FilePatch) -> IndubitablyResult<Option<String>> {
+    if patch.new_path.is_none() {
+        return Ok(None);
+    }
+    let original = original.unwrap_or_default();
+    let mut lines: Vec<String> = original.lines().map(str::to_string).collect();
+    let mut shift: isize = 0;
+    for hunk in &patch.hunks {
+        let expected = hunk.old_lines();
+        let hint = (hunk.old_start.saturating_sub(1) as isize + shift).max(0) as usize;
+        let hint = if expected.is_empty() && hunk.old_start == 0 { 0 } else { hint };
+        let start = locate_hunk(&lines, &expected, hint).ok_or_else(|| {
+            patch_error(format!("hunk at line {} of {} does not match the file", hunk.old_start, patch.path()))
+        })?;
+        let replacement: Vec<String> = hunk.new_lines().into_iter().map(str::to_string).collect();
+        shift += replacement.len() as isize - expected.len() as isize;
+        lines.splice(start..start + expected.len(), replacement);
+    }
+    let mut contents = lines.join("\n");
+    let ends_with_newline = if patch.hunks.is_empty() { original.ends_with('\n') } else { !patch.no_newline_at_end };
+    if ends_with_newline && !lines.is_empty() {
+        contents.push('\n');
+    }
+    Ok(Some(contents))
+}
+
+/// Apply a unified diff to the files under a root directory.
+///
+/// The whole diff is checked before any file is written, and changed files
+/// are recorded in the journal first. Returns the changed paths.
+pub fn apply_unified_diff(root: &Path, diff: &str, journal: &FileJournal) -> IndubitablyResult<Vec<PathBuf>> {
+    let patches = parse_unified_diff(diff)?;
+    let mut planned: Vec<(PathBuf, Option<String>)> = Vec::new();
+    for patch in &patches {
+        let source = match patch.old_path {
+            Some(ref old_path) => {
+                let path = resolve_in(root, old_path)?;
+                Some(fs::read_to_string(&path).map_err(|e| patch_error(format!("cannot read {}: {}", old_path, e)))?)
+            }
+            None => None,
+        };
+        let target = resolve_in(root, patch.path())?;
+        if patch.old_path.is_none() && target.exists() {
+            return Err(patch_error(format!("{} already exists", patch.path())));
+        }
+        if let (Some(old_path), Some(new_path)) = (&patch.old_path, &patch.new_path) {
+            if old_path != new_path {
+                planned.push((resolve_in(root, old_path)?, None));
+            }
+        }
+        planned.push((target, patched_contents(source.as_deref(), patch)?));
+    }
+
+    for (path, contents) in &planned {
+        journal.record(path);
+        match contents {
+            Some(contents) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(patch_error)?;
+                }
+                fs::write(path, contents).map_err(|e| patch_error(format!("cannot write {}: {}", path.display(), e)))?;
+            }
+            None => fs::remove_file(path).map_err(|e| patch_error(format!("cannot remove {}: {}", path.display(), e)))?,
+        }
+    }
+    tracing::debug!("root=<{}>, files=<{}> | applied patch", root.display(), planned.len());
+    Ok(planned.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Create the `apply_patch` tool applying unified diffs under a root directory.
+pub fn create_apply_patch_tool(root: impl Into<PathBuf>, journal: Arc<FileJournal>) -> Tool {
+    let root: PathBuf = root.into();
+    let function = move |input: Value| {
+        let diff = input.get("patch").and_then(Value::as_str).ok_or_else(|| {
+            IndubitablyError::ToolError(ToolError::InvalidInput("Expected 'patch' string".to_string()))
+        })?;
+        let changed = apply_unified_diff(&root, diff, &journal)?;
+        let files: Vec<String> = changed
+            .iter()
+            .filter_map(|path| path.strip_prefix(&root).ok())
+            .map(|path| path.to_string_lossy().replace('\\', "/"))
+            .collect();
+        Ok(json!({ "changed_files": files }))
+    };
+    Tool::new(
+        APPLY_PATCH_TOOL_NAME,
+        "Apply a unified diff to the repository; a patch that does not apply cleanly changes nothing",
+        Arc::new(function),
+    )
+    .with_metadata(
+        ToolMetadata::new()
+            .with_input_schema(json!({
+                "type": "object",
+                "properties": {
+                    "patch": {"type": "string", "description": "A unified diff with '---', '+++' and '@@' lines"}
+                },
+                "required": ["patch"]
+            }))
+            .with_effect(ToolEffect::Write),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_unified_diff_validates_before_writing() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("math.rs"), "fn add(a: i32, b: i32) -> i32 {\n    a - b\n}\n").unwrap();
+        let journal = FileJournal::new();
+
+        let bad = "--- a/math.rs\n+++ b/math.rs\n@@ -1,3 +1,3 @@\n fn add(a: i32, b: i32) -> i32 {\n\
+                   -    a * b\n+    a + b\n }\n--- /dev/null\n+++ b/notes.md\n@@ -0,0 +1 @@\n+notes\n";
+        assert!(apply_unified_diff(root.path(), bad, &journal).is_err());
+        assert!(!root.path().join("notes.md").exists());
+
+        let good = "--- a/math.rs\n+++ b/math.rs\n@@ -1,3 +1,3 @@\n fn add(a: i32, b: i32) -> i32 {\n\
+                    -    a - b\n+    a + b\n }\n--- /dev/null\n+++ b/notes.md\n@@ -0,0 +1 @@\n+notes\n";
+        let changed = apply_unified_diff(root.path(), good, &journal).unwrap();
+        assert_eq!(changed.len(), 2);
+        let math = fs::read_to_string(root.path().join("math.rs")).unwrap();
+        assert_eq!(math, "fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n");
+        assert_eq!(fs::read_to_string(root.path().join("notes.md")).unwrap(), "notes\n");
+
+        journal.rollback().unwrap();
+        assert!(fs::read_to_string(root.path().join("math.rs")).unwrap().contains("a - b"));
+        assert!(!root.path().join("notes.md").exists());
+    }
+}
@@ -0,0 +1,304 @@
+//! Tool permission policies for the SDK.
+//! 
+//! This module provides `ToolPolicy`, which the `ToolExecutor` consults
+//! before running a tool. A policy can restrict which tools run with allow
+//! and deny lists, cap how often each tool runs per agent run, and require a
+//! human to confirm dangerous tools, such as ones that write files or run
+//! shell commands, through an `ApprovalCallback`. A refused call is returned
+//! as a failed execution, which the agent passes to the model as a tool error
+//! so it can continue without the tool.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::registry::{Tool, ToolEffect};
+
+/// A tool call waiting for approval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// The tool name.
+    pub tool_name: String,
+    /// The tool input.
+    pub input: Value,
+    /// The side effects the tool declares.
+    pub effects: Vec<ToolEffect>,
+    /// How many times the tool already ran in this run.
+    pub previous_invocations: usize,
+}
+
+/// The answer to an approval request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    /// Run the tool.
+    Approve,
+    /// Do not run the tool, with the reason shown to the model.
+    Deny(String),
+}
+
+/// Asks a human, or another system, whether a tool call may run.
+#[async_trait]
+pub trait ApprovalCallback: Send + Sync {
+    /// Decide whether a tool call may run.
+    async fn approve(&self, request: &ApprovalRequest) -> ApprovalDecision;
+}
+
+#[async_trait]
+impl<F> ApprovalCallback for F
+where
+    F: Fn(&ApprovalRequest) -> ApprovalDecision + Send + Sync,
+{
+    async fn approve(&self, request: &ApprovalRequest) -> ApprovalDecision {
+        self(request)
+    }
+}
+
+/// Why a policy refused a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DenialReason {
+    /// The tool is on the deny list.
+    Denied,
+    /// The tool is not on the allow list.
+    NotAllowed,
+    /// The tool already ran as often as allowed in this run.
+    LimitReached,
+    /// The approval callback refused the call, or none was set.
+    ApprovalDenied,
+}
+
+impl DenialReason {
+    /// Get the reason name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Denied => "denied",
+            Self::NotAllowed => "not_allowed",
+            Self::LimitReached => "limit_reached",
+            Self::ApprovalDenied => "approval_denied",
+        }
+    }
+}
+
+/// A tool call refused by a policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDenial {
+    /// Why the call was refused.
+    pub reason: DenialReason,
+    /// The message for the model.
+    pub message: String,
+}
+
+impl fmt::Display for PolicyDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Decides which tool calls the executor may run.
+///
+/// Clones share the invocation counts.
+#[derive(Clone, Default)]
+pub struct ToolPolicy {
+    allow: Option<HashSet<String>>,
+    deny: HashSet<String>,
+    max_invocations: HashMap<String, usize>,
+    default_max_invocations: Option<usize>,
+    require_approval: HashSet<String>,
+    approve_mutating: bool,
+    approval: Option<Arc<dyn ApprovalCallback>>,
+    /// The number of times each tool ran since the last `reset`.
+    invocations: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl fmt::Debug for ToolPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolPolicy")
+            .field("allow", &self.allow)
+            .field("deny", &self.deny)
+            .field("max_invocations", &self.max_invocations)
+            .field("default_max_invocations", &self.default_max_invocations)
+            .field("require_approval", &self.require_approval)
+            .field("approve_mutating", &self.approve_mutating)
+            .field("approval", &self.approval.is_some())
+            .finish()
+    }
+}
+
+impl ToolPolicy {
+    /// Create a policy that lets every tool run.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let only the listed tools run; may be called repeatedly to extend the list.
+    pub fn with_allowed<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow.get_or_insert_with(HashSet::new).extend(tools.into_iter().map(Into::into));
+        self
+    }
+
+    /// Never let a tool run, even if it is allowed.
+    pub fn with_denied(mut self, tool_name: &str) -> Self {
+        self.deny.insert(tool_name.to_string());
+        self
+    }
+
+    /// Cap how many times a tool runs per agent run.
+    pub fn with_max_invocations(mut self, tool_name: &str, max: usize) -> Self {
+        self.max_invocations.insert(tool_name.to_string(), max);
+        self
+    }
+
+    /// Cap how many times each tool without its own cap runs per agent run.
+    pub fn with_default_max_invocations(mut self, max: usize) -> Self {
+        self.default_max_invocations = Some(max);
+        self
+    }
+
+    /// Ask the approval callback before a tool runs.
+    pub fn with_approval_required(mut self, tool_name: &str) -> Self {
+        self.require_approval.insert(tool_name.to_string());
+        self
+    }
+
+    /// Ask the approval callback before any tool that writes, sends or deletes runs.
+    pub fn with_approval_for_mutating(mut self) -> Self {
+        self.approve_mutating = true;
+        self
+    }
+
+    /// Set the callback asked to approve tool calls; without one, such calls are refused.
+    pub fn with_approval_callback(mut self, callback: Arc<dyn ApprovalCallback>) -> Self {
+        self.approval = Some(callback);
+        self
+    }
+
+    fn lock_invocations(&self) -> std::sync::MutexGuard<'_, HashMap<String, usize>> {
+        self.invocations.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get how many times a tool ran since the last reset.
+    pub fn invocations(&self, tool_name: &str) -> usize {
+        self.lock_invocations().get(tool_name).copied().unwrap_or(0)
+    }
+
+    /// Forget the invocation counts, as at the start of an agent run.
+    pub fn reset(&self) {
+        self.lock_invocations().clear();
+    }
+
+    /// Check whether a tool call may run, counting it if so.
+    pub async fn check(&self, tool: &Tool, input: &Value) -> Result<(), PolicyDenial> {
+        let name = tool.name.as_str();
+        let deny = |reason, message: String| Err(PolicyDenial { reason, message });
+        if self.deny.contains(name) {
+            return deny(DenialReason::Denied, format!("Tool '{}' was not run because it is denied by policy", name));
+        }
+        if self.allow.as_ref().is_some_and(|allow| !allow.contains(name)) {
+            return deny(
+                DenialReason::NotAllowed,
+                format!("Tool '{}' was not run because it is not on the allowed list", name),
+            );
+        }
+        let previous_invocations = self.invocations(name);
+        if let Some(max) = self.max_invocations.get(name).copied().or(self.default_max_invocations) {
+            if previous_invocations >= max {
+                return deny(
+                    DenialReason::LimitReached,
+                    format!("Tool '{}' was not run because it may only run {} time(s) per run", name, max),
+                );
+            }
+        }
+
+        let needs_approval =
+            self.require_approval.contains(name) || (self.approve_mutating && tool.metadata.is_mutating());
+        if needs_approval {
+            let Some(ref approval) = self.approval else {
+                return deny(
+                    DenialReason::ApprovalDenied,
+                    format!("Tool '{}' was not run because it needs approval and none can be asked", name),
+                );
+            };
+            let request = ApprovalRequest {
+                tool_name: name.to_string(),
+                input: input.clone(),
+                effects: tool.metadata.effects.clone(),
+                previous_invocations,
+            };
+            if let ApprovalDecision::Deny(reason) = approval.approve(&request).await {
+                tracing::info!("tool_name=<{}>, reason=<{}> | tool call was not approved", name, reason);
+                return deny(
+                    DenialReason::ApprovalDenied,
+                    format!("Tool '{}' was not run because the call was not approved: {}", name, reason),
+                );
+            }
+        }
+
+        *self.lock_invocations().entry(name.to_string()).or_insert(0) += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::registry::ToolMetadata;
+    use crate::tools::{ToolExecutionContext, ToolExecutor};
+    use serde_json::json;
+
+    fn tool(name: &str, effects: Vec<ToolEffect>) -> Tool {
+        let mut metadata = ToolMetadata::new();
+        for effect in effects {
+            metadata = metadata.with_effect(effect);
+        }
+        Tool::new(name, "Test tool", Arc::new(|_| Ok(json!("done")))).with_metadata(metadata)
+    }
+
+    #[tokio::test]
+    async fn test_tool_policy_limits_and_approvals() {
+        let approval = |request: &ApprovalRequest| match request.input["path"].as_str() {
+            Some(path) if path.starts_with("/tmp/") => ApprovalDecision::Approve,
+            _ => ApprovalDecision::Deny("outside the scratch directory".to_string()),
+        };
+        let policy = ToolPolicy::new()
+            .with_allowed(["search", "write_file"])
+            .with_denied("write_file_unsafe")
+            .with_max_invocations("search", 2)
+            .with_approval_for_mutating()
+            .with_approval_callback(Arc::new(approval));
+        let executor = ToolExecutor::new().with_policy(policy.clone());
+        let run = |tool: Tool, input: Value| {
+            let executor = &executor;
+            async move {
+                let name = tool.name.clone();
+                executor.execute(&tool, ToolExecutionContext::new(&name, input)).await
+            }
+        };
+
+        let search = tool("search", Vec::new());
+        assert!(run(search.clone(), json!({})).await.is_success());
+        assert!(run(search.clone(), json!({})).await.is_success());
+        let limited = run(search.clone(), json!({})).await;
+        assert!(limited.error().unwrap().contains("may only run 2 time(s)"));
+        assert_eq!(limited.metadata["policy_denial"], "limit_reached");
+
+        let write = tool("write_file", vec![ToolEffect::Write]);
+        assert!(run(write.clone(), json!({"path": "/tmp/notes.txt"})).await.is_success());
+        let refused = run(write, json!({"path": "/etc/passwd"})).await;
+        assert!(refused.error().unwrap().contains("not approved: outside the scratch directory"));
+        assert_eq!(policy.invocations("write_file"), 1);
+
+        let other = run(tool("shell", Vec::new()), json!({})).await;
+        assert_eq!(other.metadata["policy_denial"], "not_allowed");
+
+        executor.begin_run();
+        assert!(run(search, json!({})).await.is_success());
+    }
+}
@@ -4,11 +4,17 @@
 //! and managing tools that agents can use.
 
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 
-use crate::types::{ToolSpec, IndubitablyResult, IndubitablyError};
+use crate::types::{ToolSpec, IndubitablyResult, IndubitablyError, ToolError};
+use super::constraints::{ArgumentConstraint, ArgumentGuard};
+use super::decorator::block_on_tool;
+use super::http_tool::load_http_tools;
+use super::workspace::{current_working_directory, with_working_directory};
+use crate::models::HttpClient;
 
 /// A tool that can be executed by an agent.
 #[derive(Clone)]
@@ -19,6 +25,8 @@ pub struct Tool {
     pub description: String,
     /// The function that implements the tool.
     pub function: ToolFunction,
+    /// The asynchronous function that implements the tool, for tools doing I/O.
+    pub async_function: Option<AsyncToolFunction>,
     /// Metadata about the tool.
     pub metadata: ToolMetadata,
 }
@@ -26,10 +34,39 @@ pub struct Tool {
 /// A function that implements a tool.
 pub type ToolFunction = Arc<dyn Fn(serde_json::Value) -> IndubitablyResult<serde_json::Value> + Send + Sync>;
 
+/// The future an asynchronous tool function returns.
+pub type ToolFuture = Pin<Box<dyn Future<Output = IndubitablyResult<serde_json::Value>> + Send>>;
+
+/// An asynchronous function that implements a tool.
+pub type AsyncToolFunction = Arc<dyn Fn(serde_json::Value) -> ToolFuture + Send + Sync>;
+
+/// A side effect a tool may have outside the conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolEffect {
+    /// The tool creates or modifies data.
+    Write,
+    /// The tool sends messages or requests to other parties.
+    Send,
+    /// The tool deletes data.
+    Delete,
+}
+
+impl ToolEffect {
+    /// Get the effect name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Write => "write",
+            Self::Send => "send",
+            Self::Delete => "delete",
+        }
+    }
+}
+
 /// Metadata about a tool.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolMetadata {
-    /// The input schema for the tool.
+    /// The input schema for the tool, checked by the executor before the tool runs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub input_schema: Option<serde_json::Value>,
     /// The output schema for the tool.
@@ -38,6 +75,18 @@ pub struct ToolMetadata {
     /// Additional metadata.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub extra: Option<HashMap<String, serde_json::Value>>,
+    /// Constraints on argument values enforced before the tool runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub argument_constraints: Vec<ArgumentGuard>,
+    /// The side effects of the tool; tools without effects are read-only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<ToolEffect>,
+    /// The namespace grouping the tool, such as `fs` or `tenant-a/crm`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Free-form tags, such as `dangerous`, used to filter tools.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl Default for ToolMetadata {
@@ -46,6 +95,10 @@ impl Default for ToolMetadata {
             input_schema: None,
             output_schema: None,
             extra: None,
+            argument_constraints: Vec::new(),
+            effects: Vec::new(),
+            namespace: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -78,6 +131,44 @@ impl ToolMetadata {
         }
         self
     }
+
+    /// Constrain the argument at a path, such as `url` or `files[].path`.
+    pub fn with_argument_constraint(mut self, path: &str, constraint: ArgumentConstraint) -> Self {
+        self.argument_constraints.push(ArgumentGuard::new(path, constraint));
+        self
+    }
+
+    /// Declare a side effect of the tool.
+    pub fn with_effect(mut self, effect: ToolEffect) -> Self {
+        if !self.effects.contains(&effect) {
+            self.effects.push(effect);
+        }
+        self
+    }
+
+    /// Put the tool in a namespace; nested namespaces are separated by `/`.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.trim_matches(TOOL_NAMESPACE_SEPARATOR).to_string());
+        self
+    }
+
+    /// Tag the tool.
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+        self
+    }
+
+    /// Check whether the tool has a tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing == tag)
+    }
+
+    /// Check whether the tool writes, sends or deletes anything.
+    pub fn is_mutating(&self) -> bool {
+        !self.effects.is_empty()
+    }
 }
 
 impl Tool {
@@ -87,10 +178,27 @@ impl Tool {
             name: name.to_string(),
             description: description.to_string(),
             function,
+            async_function: None,
             metadata: ToolMetadata::default(),
         }
     }
 
+    /// Create a tool implemented by an asynchronous function.
+    ///
+    /// The executor awaits the function on the agent's runtime. `execute`
+    /// still works for synchronous callers by running it on its own thread.
+    pub fn new_async<F, Fut>(name: &str, description: &str, function: F) -> Self
+    where
+        F: Fn(serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = IndubitablyResult<serde_json::Value>> + Send + 'static,
+    {
+        let async_function: AsyncToolFunction = Arc::new(move |input| Box::pin(function(input)));
+        let blocking = Arc::clone(&async_function);
+        let mut tool = Self::new(name, description, Arc::new(move |input| block_on_tool(blocking(input))?));
+        tool.async_function = Some(async_function);
+        tool
+    }
+
     /// Set the metadata for the tool.
     pub fn with_metadata(mut self, metadata: ToolMetadata) -> Self {
         self.metadata = metadata;
@@ -102,6 +210,37 @@ impl Tool {
         (self.function)(input)
     }
 
+    /// Check whether the tool is implemented by an asynchronous function.
+    pub fn is_async(&self) -> bool {
+        self.async_function.is_some()
+    }
+
+    /// Execute the tool without blocking the runtime.
+    ///
+    /// Asynchronous tools are awaited in place. Synchronous tools run on the
+    /// blocking thread pool, seeing the same working directory, so a timeout
+    /// around this call fires even while the tool blocks.
+    pub async fn execute_async(&self, input: serde_json::Value) -> IndubitablyResult<serde_json::Value> {
+        if let Some(ref function) = self.async_function {
+            return function(input).await;
+        }
+        let function = Arc::clone(&self.function);
+        let working_directory = current_working_directory();
+        tokio::task::spawn_blocking(move || with_working_directory(working_directory.as_deref(), || function(input)))
+            .await
+            .unwrap_or_else(|e| {
+                Err(ToolError::ExecutionFailed(format!("Tool '{}' did not complete: {}", self.name, e)).into())
+            })
+    }
+
+    /// Get the name qualified by the tool's namespace, such as `fs/read`.
+    pub fn qualified_name(&self) -> String {
+        match self.metadata.namespace {
+            Some(ref namespace) => format!("{}{}{}", namespace, TOOL_NAMESPACE_SEPARATOR, self.name),
+            None => self.name.clone(),
+        }
+    }
+
     /// Get the tool specification.
     pub fn spec(&self) -> ToolSpec {
         ToolSpec::new(&self.name, &self.description)
@@ -110,73 +249,209 @@ impl Tool {
     }
 }
 
+/// Separates nested namespaces and a namespace from a tool name.
+pub const TOOL_NAMESPACE_SEPARATOR: char = '/';
+
+/// A filter choosing which tools are visible, for example to one tenant's requests.
+///
+/// A tool is visible when it is in one of the namespaces, if any are given,
+/// has one of the tags, if any are given, and has none of the excluded tags.
+/// A namespace includes the namespaces nested in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolFilter {
+    /// The namespaces whose tools are visible; all are when empty.
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// The tags of which a visible tool has at least one; any are when empty.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The tags hiding a tool.
+    #[serde(default)]
+    pub excluded_tags: Vec<String>,
+}
+
+impl ToolFilter {
+    /// Create a filter letting every tool through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let the tools in a namespace through.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespaces.push(namespace.trim_matches(TOOL_NAMESPACE_SEPARATOR).to_string());
+        self
+    }
+
+    /// Let the tools with a tag through.
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Hide the tools with a tag, such as `dangerous`.
+    pub fn without_tag(mut self, tag: &str) -> Self {
+        self.excluded_tags.push(tag.to_string());
+        self
+    }
+
+    /// Check whether a tool is visible.
+    pub fn matches(&self, tool: &Tool) -> bool {
+        self.matches_parts(tool.metadata.namespace.as_deref(), &tool.metadata.tags)
+    }
+
+    /// Check whether a tool in a namespace, with tags, is visible.
+    pub fn matches_parts(&self, namespace: Option<&str>, tags: &[String]) -> bool {
+        let in_namespace = self.namespaces.is_empty()
+            || namespace.is_some_and(|namespace| {
+                self.namespaces.iter().any(|allowed| {
+                    namespace
+                        .strip_prefix(allowed.as_str())
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with(TOOL_NAMESPACE_SEPARATOR))
+                })
+            });
+        in_namespace
+            && (self.tags.is_empty() || tags.iter().any(|tag| self.tags.contains(tag)))
+            && !tags.iter().any(|tag| self.excluded_tags.contains(tag))
+    }
+}
+
+/// A snapshot of the registered tools.
+pub type ToolSnapshot = Arc<HashMap<String, Arc<Tool>>>;
+
 /// A registry for managing tools.
+///
+/// The registry stores tools in a copy-on-write snapshot. Lookups clone the
+/// current snapshot pointer and never wait on writers or deep-clone tools;
+/// registrations build a new snapshot and swap it in.
 pub struct ToolRegistry {
-    tools: Arc<RwLock<HashMap<String, Tool>>>,
+    inner: Arc<ToolRegistryInner>,
+}
+
+struct ToolRegistryInner {
+    /// The current snapshot of registered tools.
+    snapshot: RwLock<ToolSnapshot>,
+    /// Serializes writers so concurrent registrations are not lost.
+    write_lock: Mutex<()>,
 }
 
 impl ToolRegistry {
     /// Create a new tool registry.
     pub fn new() -> Self {
         Self {
-            tools: Arc::new(RwLock::new(HashMap::new())),
+            inner: Arc::new(ToolRegistryInner {
+                snapshot: RwLock::new(Arc::new(HashMap::new())),
+                write_lock: Mutex::new(()),
+            }),
         }
     }
 
+    /// Get the current snapshot of registered tools.
+    ///
+    /// The snapshot is immutable and is not affected by later registrations.
+    pub fn snapshot(&self) -> ToolSnapshot {
+        Arc::clone(&self.inner.snapshot.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Apply a modification to a copy of the current snapshot and publish it.
+    fn update<F>(&self, modify: F)
+    where
+        F: FnOnce(&mut HashMap<String, Arc<Tool>>),
+    {
+        let _guard = self.inner.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tools = HashMap::clone(&self.snapshot());
+        modify(&mut tools);
+        *self.inner.snapshot.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(tools);
+    }
+
     /// Register a tool in the registry.
     pub async fn register(&self, tool: Tool) -> Result<(), IndubitablyError> {
-        let mut tools = self.tools.write().await;
-        tools.insert(tool.name.clone(), tool);
+        self.update(|tools| {
+            tools.insert(tool.name.clone(), Arc::new(tool));
+        });
         Ok(())
     }
 
+    /// Load the declarative HTTP tools in a TOML, YAML or JSON file and register them, returning their names.
+    pub async fn register_http_tools(
+        &self,
+        path: &std::path::Path,
+        client: Arc<dyn HttpClient>,
+    ) -> IndubitablyResult<Vec<String>> {
+        let tools = load_http_tools(path, client)?;
+        let names = tools.iter().map(|tool| tool.name.clone()).collect();
+        self.update(|registered| {
+            for tool in tools {
+                registered.insert(tool.name.clone(), Arc::new(tool));
+            }
+        });
+        Ok(names)
+    }
+
     /// Unregister a tool from the registry.
     pub async fn unregister(&self, name: &str) -> Result<(), IndubitablyError> {
-        let mut tools = self.tools.write().await;
-        tools.remove(name);
+        self.update(|tools| {
+            tools.remove(name);
+        });
         Ok(())
     }
 
     /// Get a tool by name.
-    pub async fn get(&self, name: &str) -> Option<Tool> {
-        let tools = self.tools.read().await;
-        tools.get(name).cloned()
+    pub async fn get(&self, name: &str) -> Option<Arc<Tool>> {
+        self.snapshot().get(name).cloned()
     }
 
     /// Get all tool names.
     pub async fn list_names(&self) -> Vec<String> {
-        let tools = self.tools.read().await;
-        tools.keys().cloned().collect()
+        self.snapshot().keys().cloned().collect()
     }
 
     /// Get all tools.
-    pub async fn list_tools(&self) -> Vec<Tool> {
-        let tools = self.tools.read().await;
-        tools.values().cloned().collect()
+    pub async fn list_tools(&self) -> Vec<Arc<Tool>> {
+        self.snapshot().values().cloned().collect()
     }
 
     /// Get tool specifications for all tools.
     pub async fn list_specs(&self) -> Vec<ToolSpec> {
-        let tools = self.tools.read().await;
-        tools.values().map(|tool| tool.spec()).collect()
+        self.snapshot().values().map(|tool| tool.spec()).collect()
+    }
+
+    /// Get the namespaces of the registered tools, sorted.
+    pub async fn list_namespaces(&self) -> Vec<String> {
+        let mut namespaces: Vec<String> =
+            self.snapshot().values().filter_map(|tool| tool.metadata.namespace.clone()).collect();
+        namespaces.sort();
+        namespaces.dedup();
+        namespaces
+    }
+
+    /// Get the tools in a namespace, including those in namespaces nested in it.
+    pub async fn list_in_namespace(&self, namespace: &str) -> Vec<Arc<Tool>> {
+        self.list_filtered(&ToolFilter::new().with_namespace(namespace)).await
+    }
+
+    /// Get the tools with a tag.
+    pub async fn list_by_tag(&self, tag: &str) -> Vec<Arc<Tool>> {
+        self.list_filtered(&ToolFilter::new().with_tag(tag)).await
+    }
+
+    /// Get the tools a filter lets through.
+    pub async fn list_filtered(&self, filter: &ToolFilter) -> Vec<Arc<Tool>> {
+        self.snapshot().values().filter(|tool| filter.matches(tool)).cloned().collect()
     }
 
     /// Check if a tool exists.
     pub async fn exists(&self, name: &str) -> bool {
-        let tools = self.tools.read().await;
-        tools.contains_key(name)
+        self.snapshot().contains_key(name)
     }
 
     /// Get the number of tools in the registry.
     pub async fn count(&self) -> usize {
-        let tools = self.tools.read().await;
-        tools.len()
+        self.snapshot().len()
     }
 
     /// Clear all tools from the registry.
     pub async fn clear(&self) -> Result<(), IndubitablyError> {
-        let mut tools = self.tools.write().await;
-        tools.clear();
+        self.update(|tools| tools.clear());
         Ok(())
     }
 }
@@ -190,7 +465,7 @@ impl Default for ToolRegistry {
 impl Clone for ToolRegistry {
     fn clone(&self) -> Self {
         Self {
-            tools: Arc::clone(&self.tools),
+            inner: Arc::clone(&self.inner),
         }
     }
 }
@@ -227,6 +502,12 @@ mod tests {
         
         assert_eq!(result.as_str().unwrap(), "Processed: hello");
         
+        // Snapshots taken before a change are unaffected by it
+        let snapshot = registry.snapshot();
+        registry.unregister("test_tool").await.unwrap();
+        assert!(snapshot.contains_key("test_tool"));
+        registry.register((*tool).clone()).await.unwrap();
+
         // Test tool unregistration
         registry.unregister("test_tool").await.unwrap();
         assert_eq!(registry.count().await, 0);
@@ -266,4 +547,45 @@ mod tests {
         let result = registry.get("nonexistent_tool").await;
         assert!(result.is_none());
     }
+
+    #[tokio::test]
+    async fn test_namespaces_and_tags() {
+        let registry = ToolRegistry::new();
+        let tool = |name: &str, metadata: ToolMetadata| {
+            Tool::new(name, name, Arc::new(|_| Ok(serde_json::Value::Null))).with_metadata(metadata)
+        };
+        registry.register(tool("read", ToolMetadata::new().with_namespace("fs"))).await.unwrap();
+        let delete = ToolMetadata::new().with_namespace("fs").with_tag("dangerous");
+        registry.register(tool("delete", delete)).await.unwrap();
+        registry.register(tool("lookup", ToolMetadata::new().with_namespace("crm/contacts"))).await.unwrap();
+        registry.register(tool("clock", ToolMetadata::new())).await.unwrap();
+
+        assert_eq!(registry.list_namespaces().await, ["crm/contacts", "fs"]);
+        assert_eq!(registry.get("delete").await.unwrap().qualified_name(), "fs/delete");
+        assert_eq!(registry.list_by_tag("dangerous").await.len(), 1);
+        assert_eq!(registry.list_in_namespace("crm").await[0].name, "lookup");
+        assert!(registry.list_in_namespace("cr").await.is_empty());
+
+        let filter = ToolFilter::new().with_namespace("fs").without_tag("dangerous");
+        let visible: Vec<String> = registry.list_filtered(&filter).await.iter().map(|t| t.name.clone()).collect();
+        assert_eq!(visible, ["read"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_async() {
+        let echo = Tool::new_async("echo", "Echo the input", |input| async move {
+            tokio::task::yield_now().await;
+            Ok(input)
+        });
+        assert!(echo.is_async());
+        assert_eq!(echo.execute_async(serde_json::json!("hi")).await.unwrap(), "hi");
+        // Synchronous callers still get a result
+        assert_eq!(echo.execute(serde_json::json!("hi")).unwrap(), "hi");
+
+        let blocking = Tool::new("upper", "Uppercase the input", Arc::new(|input| {
+            Ok(serde_json::json!(input.as_str().unwrap_or_default().to_uppercase()))
+        }));
+        assert!(!blocking.is_async());
+        assert_eq!(blocking.execute_async(serde_json::json!("hi")).await.unwrap(), "HI");
+    }
 }
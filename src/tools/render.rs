@@ -0,0 +1,376 @@
+//! Tool result renderers for the SDK.
+//! 
+//! Tool outputs reach the model as JSON, which is rarely the best way to
+//! show them to a person. This module provides `ResultRenderer`s that turn
+//! the output of a tool call into terminal text and a UI hint for front-ends:
+//! a table for query results, links for images and a diff viewer for
+//! patches. A `RendererRegistry` maps tool names to renderers; the CLI prints
+//! the terminal text and the agent attaches the UI hint to `toolCompleted`
+//! stream events, which the SSE adapter forwards as `ui`. Tools without a
+//! renderer fall back to pretty-printed JSON.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::patch::{parse_unified_diff, HunkLine, APPLY_PATCH_TOOL_NAME};
+
+/// The most rows a table shows in the terminal.
+pub const MAX_TABLE_ROWS: usize = 50;
+
+/// The widest a table cell is shown in the terminal, in characters.
+const MAX_CELL_WIDTH: usize = 40;
+
+/// The output keys that may hold an image link.
+const IMAGE_LINK_KEYS: &[&str] = &["url", "image_url", "uri", "src", "href"];
+
+/// The rendering of one tool call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderedResult {
+    /// The renderer kind, such as `table`, `image` or `diff`.
+    pub kind: String,
+    /// The text shown in a terminal.
+    pub text: String,
+    /// The hint front-ends use to render the output; it carries the kind under `kind`.
+    pub hint: Value,
+}
+
+/// Renders the output of a tool call for people.
+pub trait ResultRenderer: Send + Sync {
+    /// Get the renderer kind, such as `table`.
+    fn kind(&self) -> &str;
+
+    /// Render a tool call, or return `None` if the output is not in a shape the renderer understands.
+    fn render(&self, input: &Value, output: &Value) -> Option<RenderedResult>;
+}
+
+/// Renders query results, given as rows of objects or as columns and rows, as a table.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TableRenderer;
+
+impl TableRenderer {
+    /// Get the columns and rows of an output shaped like a table.
+    fn table(output: &Value) -> Option<(Vec<String>, Vec<Vec<Value>>)> {
+        let output = match output.get("rows") {
+            Some(rows) if output.get("columns").is_none() => rows,
+            _ => output,
+        };
+        if let (Some(columns), Some(rows)) = (output.get("columns"), output.get("rows")) {
+            let columns: Vec<String> = columns.as_array()?.iter().map(cell_text).collect();
+            let rows = rows.as_array()?.iter().map(|row| row.as_array().cloned()).collect::<Option<_>>()?;
+            return Some((columns, rows));
+        }
+
+        let records: Vec<&Map<String, Value>> = output.as_array()?.iter().map(Value::as_object).collect::<Option<_>>()?;
+        let mut columns: Vec<String> = Vec::new();
+        for record in &records {
+            for key in record.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+        let rows = records
+            .iter()
+            .map(|record| columns.iter().map(|column| record.get(column).cloned().unwrap_or(Value::Null)).collect())
+            .collect();
+        Some((columns, rows))
+    }
+}
+
+impl ResultRenderer for TableRenderer {
+    fn kind(&self) -> &str {
+        "table"
+    }
+
+    fn render(&self, _input: &Value, output: &Value) -> Option<RenderedResult> {
+        let (columns, rows) = Self::table(output)?;
+        if columns.is_empty() {
+            return None;
+        }
+        let shown: Vec<Vec<String>> = rows
+            .iter()
+            .take(MAX_TABLE_ROWS)
+            .map(|row| (0..columns.len()).map(|i| truncate(&row.get(i).map(cell_text).unwrap_or_default())).collect())
+            .collect();
+        let widths: Vec<usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| shown.iter().map(|row| row[i].chars().count()).fold(column.chars().count(), usize::max))
+            .collect();
+        let line = |cells: &[String]| {
+            let cells: Vec<String> =
+                cells.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+        let mut text = vec![line(&columns), format!("|-{}-|", separator.join("-|-"))];
+        text.extend(shown.iter().map(|row| line(row)));
+        if rows.len() > MAX_TABLE_ROWS {
+            text.push(format!("... {} more rows", rows.len() - MAX_TABLE_ROWS));
+        }
+        text.push(format!("({} rows)", rows.len()));
+        Some(RenderedResult {
+            kind: self.kind().to_string(),
+            text: text.join("\n"),
+            hint: json!({"kind": self.kind(), "columns": columns, "rows": rows}),
+        })
+    }
+}
+
+/// Renders image links in a tool output as links a front-end can preview.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImageLinkRenderer;
+
+impl ImageLinkRenderer {
+    fn collect_links(value: &Value, links: &mut Vec<String>) {
+        match value {
+            Value::String(text) if is_image_link(text) => links.push(text.clone()),
+            Value::Array(items) => items.iter().for_each(|item| Self::collect_links(item, links)),
+            Value::Object(fields) => {
+                for (key, field) in fields {
+                    match field {
+                        Value::String(text) if IMAGE_LINK_KEYS.contains(&key.as_str()) && is_link(text) => {
+                            links.push(text.clone())
+                        }
+                        _ => Self::collect_links(field, links),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ResultRenderer for ImageLinkRenderer {
+    fn kind(&self) -> &str {
+        "image"
+    }
+
+    fn render(&self, _input: &Value, output: &Value) -> Option<RenderedResult> {
+        let mut links = Vec::new();
+        Self::collect_links(output, &mut links);
+        links.dedup();
+        if links.is_empty() {
+            return None;
+        }
+        Some(RenderedResult {
+            kind: self.kind().to_string(),
+            text: links.iter().map(|link| format!("[image] {}", link)).collect::<Vec<_>>().join("\n"),
+            hint: json!({"kind": self.kind(), "urls": links}),
+        })
+    }
+}
+
+/// Renders unified diffs, from the output or the `patch` input of a tool such as `apply_patch`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffRenderer {
+    color: bool,
+}
+
+impl DiffRenderer {
+    /// Create a renderer that prints diffs without color.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Color added and removed lines with ANSI escapes in the terminal text.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn find_diff(input: &Value, output: &Value) -> Option<String> {
+        let is_diff = |text: &str| text.starts_with("diff --git") || text.starts_with("--- ");
+        [output.as_str(), output.get("diff").and_then(Value::as_str), output.get("patch").and_then(Value::as_str)]
+            .into_iter()
+            .chain([input.get("patch").and_then(Value::as_str), input.get("diff").and_then(Value::as_str)])
+            .flatten()
+            .find(|text| is_diff(text.trim_start()))
+            .map(str::to_string)
+    }
+}
+
+impl ResultRenderer for DiffRenderer {
+    fn kind(&self) -> &str {
+        "diff"
+    }
+
+    fn render(&self, input: &Value, output: &Value) -> Option<RenderedResult> {
+        let diff = Self::find_diff(input, output)?;
+        let files: Vec<Value> = parse_unified_diff(&diff)
+            .ok()?
+            .iter()
+            .map(|file| {
+                let lines = file.hunks.iter().flat_map(|hunk| hunk.lines.iter());
+                let (additions, deletions) = lines.fold((0, 0), |(added, removed), line| match line {
+                    HunkLine::Add(_) => (added + 1, removed),
+                    HunkLine::Remove(_) => (added, removed + 1),
+                    HunkLine::Context(_) => (added, removed),
+                });
+                json!({"path": file.path(), "additions": additions, "deletions": deletions})
+            })
+            .collect();
+        let text = if self.color {
+            diff.lines().map(colorize).collect::<Vec<_>>().join("\n")
+        } else {
+            diff.trim_end().to_string()
+        };
+        Some(RenderedResult {
+            kind: self.kind().to_string(),
+            text,
+            hint: json!({"kind": self.kind(), "files": files, "patch": diff}),
+        })
+    }
+}
+
+/// Color a diff line for the terminal.
+fn colorize(line: &str) -> String {
+    if line.starts_with("+++") || line.starts_with("---") {
+        format!("\x1b[1m{}\x1b[0m", line)
+    } else if line.starts_with('+') {
+        format!("\x1b[32m{}\x1b[0m", line)
+    } else if line.starts_with('-') {
+        format!("\x1b[31m{}\x1b[0m", line)
+    } else if line.starts_with("@@") {
+        format!("\x1b[36m{}\x1b[0m", line)
+    } else {
+        line.to_string()
+    }
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    if text.chars().count() <= MAX_CELL_WIDTH {
+        return text;
+    }
+    let kept: String = text.chars().take(MAX_CELL_WIDTH - 1).collect();
+    format!("{}…", kept)
+}
+
+fn is_link(text: &str) -> bool {
+    text.starts_with("https://") || text.starts_with("http://") || text.starts_with("data:image/")
+}
+
+fn is_image_link(text: &str) -> bool {
+    if text.starts_with("data:image/") {
+        return true;
+    }
+    let path = text.split(['?', '#']).next().unwrap_or(text).to_ascii_lowercase();
+    is_link(text) && [".png", ".jpg", ".jpeg", ".gif", ".webp", ".svg"].iter().any(|ext| path.ends_with(ext))
+}
+
+/// Maps tool names to the renderers used for their results.
+#[derive(Clone, Default)]
+pub struct RendererRegistry {
+    renderers: HashMap<String, Arc<dyn ResultRenderer>>,
+}
+
+impl fmt::Debug for RendererRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut renderers: Vec<(&String, &str)> =
+            self.renderers.iter().map(|(name, renderer)| (name, renderer.kind())).collect();
+        renderers.sort();
+        f.debug_struct("RendererRegistry").field("renderers", &renderers).finish()
+    }
+}
+
+impl RendererRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with renderers for the built-in tools, such as a diff viewer for `apply_patch`.
+    pub fn with_defaults() -> Self {
+        Self::new().with_renderer(APPLY_PATCH_TOOL_NAME, Arc::new(DiffRenderer::new()))
+    }
+
+    /// Render the results of a tool with a renderer.
+    pub fn with_renderer(mut self, tool_name: &str, renderer: Arc<dyn ResultRenderer>) -> Self {
+        self.register(tool_name, renderer);
+        self
+    }
+
+    /// Render the results of a tool with a renderer, replacing any previous one.
+    pub fn register(&mut self, tool_name: &str, renderer: Arc<dyn ResultRenderer>) {
+        self.renderers.insert(tool_name.to_string(), renderer);
+    }
+
+    /// Get the renderer of a tool, if one is registered.
+    pub fn get(&self, tool_name: &str) -> Option<&Arc<dyn ResultRenderer>> {
+        self.renderers.get(tool_name)
+    }
+
+    /// Render a tool call with the tool's renderer, if it has one that understands the output.
+    pub fn render_with(&self, tool_name: &str, input: &Value, output: &Value) -> Option<RenderedResult> {
+        self.renderers.get(tool_name)?.render(input, output)
+    }
+
+    /// Render a tool call, falling back to pretty-printed JSON.
+    pub fn render(&self, tool_name: &str, input: &Value, output: &Value) -> RenderedResult {
+        self.render_with(tool_name, input, output).unwrap_or_else(|| {
+            let text = match output {
+                Value::String(text) => text.clone(),
+                other => serde_json::to_string_pretty(other).unwrap_or_default(),
+            };
+            RenderedResult {
+                kind: "json".to_string(),
+                text,
+                hint: json!({"kind": "json"}),
+            }
+        })
+    }
+
+    /// Render a tool call whose output is the text returned to the model, parsing it as JSON if it is JSON.
+    pub fn render_text(&self, tool_name: &str, input: &Value, output: &str) -> RenderedResult {
+        let output = serde_json::from_str(output).unwrap_or_else(|_| Value::String(output.to_string()));
+        self.render(tool_name, input, &output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_renders_tables_images_and_diffs() {
+        let renderers = RendererRegistry::with_defaults()
+            .with_renderer("sql", Arc::new(TableRenderer))
+            .with_renderer("render_chart", Arc::new(ImageLinkRenderer));
+
+        let rows = r#"[{"id": 1, "name": "Ada"}, {"id": 2, "name": "Grace", "team": "Navy"}]"#;
+        let table = renderers.render_text("sql", &json!({}), rows);
+        assert_eq!(table.kind, "table");
+        assert_eq!(
+            table.text,
+            "| id | name  | team |\n|----|-------|------|\n| 1  | Ada   |      |\n| 2  | Grace | Navy |\n(2 rows)"
+        );
+        assert_eq!(table.hint["columns"], json!(["id", "name", "team"]));
+
+        let chart = json!({"chart": {"url": "https://cdn.example.com/c/42"}, "thumb": "https://x.io/t.png?s=1"});
+        let image = renderers.render("render_chart", &json!({}), &chart);
+        assert_eq!(image.hint["urls"], json!(["https://cdn.example.com/c/42", "https://x.io/t.png?s=1"]));
+
+        let patch = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n fn main() {\n-    old();\n+    new();\n";
+        let diff = renderers.render_text(APPLY_PATCH_TOOL_NAME, &json!({"patch": patch}), r#"{"changed_files": []}"#);
+        assert_eq!(diff.kind, "diff");
+        assert_eq!(diff.hint["files"], json!([{"path": "src/lib.rs", "additions": 1, "deletions": 1}]));
+
+        // Outputs a renderer does not understand, and tools without one, fall back to JSON
+        assert_eq!(renderers.render("sql", &json!({}), &json!("3 rows updated")).kind, "json");
+        assert_eq!(renderers.render_text("search", &json!({}), r#"{"a":1}"#).text, "{\n  \"a\": 1\n}");
+    }
+}
@@ -0,0 +1,176 @@
+//! Tool argument repair for the SDK.
+//! 
+//! Providers occasionally emit tool arguments that are not valid JSON:
+//! wrapped in a code fence, left with a trailing comma or unquoted keys, or
+//! truncated by the output limit. This module repairs the syntax only;
+//! truncated arguments are never completed with guessed values. When
+//! repair fails, the provider records the parse error in the response
+//! metadata under `MALFORMED_TOOL_CALLS_METADATA_KEY`, and the agent sends
+//! it back to the model as a tool error asking for the call to be
+//! re-emitted, up to a bounded number of retries.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// The response metadata key mapping tool use ids to the parse errors of their arguments.
+pub const MALFORMED_TOOL_CALLS_METADATA_KEY: &str = "malformed_tool_calls";
+
+/// The default number of times the model may be asked to re-emit malformed arguments in a run.
+pub const DEFAULT_MAX_ARGUMENT_RETRIES: usize = 2;
+
+/// Why tool arguments could not be repaired.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RepairError {
+    /// The JSON ended early, usually because the output limit cut the call off.
+    #[error("the arguments were cut off before the JSON ended")]
+    Truncated,
+    /// The JSON is malformed in a way repair does not fix.
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// Parse tool arguments, repairing them if needed, or return the parse error.
+pub fn parse_arguments(raw: &str) -> Result<Value, String> {
+    if raw.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    match serde_json::from_str(raw) {
+        Ok(value) => Ok(value),
+        Err(e) => match repair_json(raw) {
+            Ok(value) => {
+                tracing::debug!("error=<{}> | repaired malformed tool arguments", e);
+                Ok(value)
+            }
+            Err(RepairError::Truncated) => Err(format!("{} ({})", RepairError::Truncated, e)),
+            Err(RepairError::Invalid(_)) => Err(e.to_string()),
+        },
+    }
+}
+
+/// Repair a JSON object or array that is fenced, surrounded by prose, has trailing commas or unquoted keys.
+///
+/// Only syntax is repaired. JSON that ends early is reported as truncated
+/// rather than completed, since the missing values cannot be recovered.
+pub fn repair_json(raw: &str) -> Result<Value, RepairError> {
+    let text = raw.trim();
+    let text = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .map(|rest| rest.trim_end().trim_end_matches("```"))
+        .unwrap_or(text);
+    let start = text.find(['{', '[']).ok_or_else(|| RepairError::Invalid("no JSON object or array".to_string()))?;
+
+    let mut out = String::with_capacity(text.len() + 8);
+    let mut closers = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut expect_key = false;
+    let mut chars = text[start..].chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        if expect_key && (c.is_ascii_alphabetic() || c == '_' || c == '$') {
+            let mut key = c.to_string();
+            while let Some(next) = chars.next_if(|next| next.is_ascii_alphanumeric() || matches!(next, '_' | '$')) {
+                key.push(next);
+            }
+            out.push_str(&serde_json::to_string(&key).unwrap_or_default());
+            expect_key = false;
+            continue;
+        }
+        if !c.is_whitespace() {
+            expect_key = false;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' => {
+                closers.push('}');
+                expect_key = true;
+                out.push(c);
+            }
+            '[' => {
+                closers.push(']');
+                out.push(c);
+            }
+            ',' => {
+                expect_key = closers.last() == Some(&'}');
+                out.push(c);
+            }
+            '}' | ']' => {
+                let trimmed = out.trim_end().len();
+                out.truncate(trimmed);
+                if out.ends_with(',') {
+                    out.pop();
+                }
+                if closers.pop() != Some(c) {
+                    return Err(RepairError::Invalid(format!("unexpected '{}'", c)));
+                }
+                out.push(c);
+                if closers.is_empty() {
+                    break;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    if in_string || !closers.is_empty() {
+        return Err(RepairError::Truncated);
+    }
+    serde_json::from_str(&out).map_err(|e| RepairError::Invalid(e.to_string()))
+}
+
+/// Get the parse errors of the tool uses in response metadata, keyed by tool use id.
+pub fn malformed_tool_calls(metadata: &HashMap<String, Value>) -> HashMap<String, String> {
+    metadata
+        .get(MALFORMED_TOOL_CALLS_METADATA_KEY)
+        .and_then(Value::as_object)
+        .map(|calls| {
+            calls
+                .iter()
+                .map(|(id, error)| (id.clone(), error.as_str().unwrap_or_default().to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build the tool error asking the model to re-emit a call with valid arguments.
+pub fn retry_message(tool_name: &str, error: &str) -> String {
+    format!(
+        "The arguments for '{}' were not valid JSON ({}). The tool was not run. Call it again with the complete arguments as a single valid JSON object.",
+        tool_name, error
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_repair_common_malformations() {
+        assert_eq!(parse_arguments("").unwrap(), json!({}));
+        assert_eq!(parse_arguments("{\"a\": 1,}").unwrap(), json!({"a": 1}));
+        assert_eq!(parse_arguments("```json\n{\"a\": [1, 2,]}\n```").unwrap(), json!({"a": [1, 2]}));
+        assert_eq!(parse_arguments("{path: \"a\", max_depth: 2}").unwrap(), json!({"path": "a", "max_depth": 2}));
+        assert_eq!(parse_arguments("Sure: {\"a\": \"}\"} trailing").unwrap(), json!({"a": "}"}));
+        assert_eq!(repair_json("{\"path\": \"src/ma"), Err(RepairError::Truncated));
+        assert_eq!(repair_json("{\"q\": {\"k\":"), Err(RepairError::Truncated));
+        assert!(parse_arguments("{\"path\": \"src/ma").unwrap_err().contains("cut off"));
+        assert!(parse_arguments("{\"a\": tru").is_err());
+        assert!(parse_arguments("not json").is_err());
+    }
+}
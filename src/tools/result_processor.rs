@@ -0,0 +1,287 @@
+//! Tool result processing for the SDK.
+//! 
+//! This module provides `ToolResultProcessor`, which keeps oversized tool
+//! outputs, such as scraped web pages or long logs, from blowing up the
+//! context. Outputs over a byte or token limit are truncated, keeping their
+//! start or their start and end, or summarized by a cheap model, falling back
+//! to truncation when no model is set or summarization fails. Limits can be
+//! set per tool. Unlike `ToolOutputSummarizer`, the processor keeps nothing
+//! of what it removes; use that when the model should be able to read the
+//! full output back.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::executor::truncation_note;
+use super::summarize::{summarize_output, DEFAULT_SUMMARY_PROMPT};
+use crate::models::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::models::Model;
+
+/// The most bytes of an output sent to the summary model.
+pub const DEFAULT_MAX_SUMMARY_INPUT_BYTES: usize = 200_000;
+
+/// How an oversized output is cut down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizeStrategy {
+    /// Keep the start of the output.
+    Truncate,
+    /// Keep the start and the end of the output, as for logs whose errors come last.
+    HeadAndTail,
+    /// Have the summary model summarize the output.
+    Summarize,
+}
+
+/// The byte and token limits of a tool output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputLimits {
+    /// The most bytes of output kept.
+    pub max_bytes: Option<usize>,
+    /// The most tokens of output kept.
+    pub max_tokens: Option<usize>,
+}
+
+impl OutputLimits {
+    /// Create limits from a byte limit, a token limit, or both.
+    pub fn new(max_bytes: Option<usize>, max_tokens: Option<usize>) -> Self {
+        Self { max_bytes, max_tokens }
+    }
+}
+
+/// What the processor did to an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessAction {
+    /// The output was within its limits.
+    Unchanged,
+    /// The output was truncated.
+    Truncated,
+    /// The output was replaced by a summary.
+    Summarized,
+}
+
+/// A processed tool output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessedOutput {
+    /// The output to put in the conversation.
+    pub output: Value,
+    /// What was done to the output.
+    pub action: ProcessAction,
+    /// The size of the original output in bytes.
+    pub original_bytes: usize,
+    /// The size of the original output in tokens.
+    pub original_tokens: usize,
+}
+
+/// A stage truncating or summarizing tool outputs over their limits.
+#[derive(Clone)]
+pub struct ToolResultProcessor {
+    limits: OutputLimits,
+    tool_limits: HashMap<String, OutputLimits>,
+    strategy: OversizeStrategy,
+    summary_model: Option<Arc<dyn Model>>,
+    tokenizer: Arc<dyn Tokenizer>,
+    prompt: String,
+}
+
+impl fmt::Debug for ToolResultProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolResultProcessor")
+            .field("limits", &self.limits)
+            .field("tool_limits", &self.tool_limits)
+            .field("strategy", &self.strategy)
+            .field("summary_model", &self.summary_model.as_ref().map(|model| model.model_id().to_string()))
+            .field("tokenizer", &self.tokenizer.name())
+            .finish()
+    }
+}
+
+impl ToolResultProcessor {
+    /// Create a processor truncating outputs over the given limits.
+    pub fn new(limits: OutputLimits) -> Self {
+        Self {
+            limits,
+            tool_limits: HashMap::new(),
+            strategy: OversizeStrategy::Truncate,
+            summary_model: None,
+            tokenizer: Arc::new(HeuristicTokenizer::default()),
+            prompt: DEFAULT_SUMMARY_PROMPT.to_string(),
+        }
+    }
+
+    /// Set how oversized outputs are cut down.
+    pub fn with_strategy(mut self, strategy: OversizeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Summarize oversized outputs with the given model, ideally a cheap one.
+    pub fn with_summary_model(mut self, model: Arc<dyn Model>) -> Self {
+        self.summary_model = Some(model);
+        self.strategy = OversizeStrategy::Summarize;
+        self
+    }
+
+    /// Set the prompt asking the summary model to summarize an output.
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = prompt.to_string();
+        self
+    }
+
+    /// Set the tokenizer used to measure outputs.
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Use different limits for one tool, such as tighter ones for a web scraper.
+    pub fn with_tool_limits(mut self, tool_name: &str, limits: OutputLimits) -> Self {
+        self.tool_limits.insert(tool_name.to_string(), limits);
+        self
+    }
+
+    /// Get the limits that apply to a tool.
+    pub fn limits_for(&self, tool_name: &str) -> OutputLimits {
+        self.tool_limits.get(tool_name).copied().unwrap_or(self.limits)
+    }
+
+    /// Truncate or summarize an output over the tool's limits.
+    pub async fn process(&self, tool_name: &str, output: Value) -> ProcessedOutput {
+        let limits = self.limits_for(tool_name);
+        let text = match output {
+            Value::String(ref text) => text.clone(),
+            ref other => other.to_string(),
+        };
+        let original_bytes = text.len();
+        let original_tokens = self.tokenizer.count_tokens(&text);
+        if self.fits(&text, limits) {
+            return ProcessedOutput {
+                output,
+                action: ProcessAction::Unchanged,
+                original_bytes,
+                original_tokens,
+            };
+        }
+
+        if self.strategy == OversizeStrategy::Summarize {
+            match self.summarize(tool_name, &text, original_tokens).await {
+                Some(summary) => {
+                    // A summary over the limits is cut down like any other output
+                    let summary = if self.fits(&summary, limits) {
+                        summary
+                    } else {
+                        self.truncate(&summary, limits, OversizeStrategy::Truncate)
+                    };
+                    return ProcessedOutput {
+                        output: Value::String(summary),
+                        action: ProcessAction::Summarized,
+                        original_bytes,
+                        original_tokens,
+                    };
+                }
+                None => tracing::debug!("tool_name=<{}> | truncating tool output instead of summarizing", tool_name),
+            }
+        }
+
+        ProcessedOutput {
+            output: Value::String(self.truncate(&text, limits, self.strategy)),
+            action: ProcessAction::Truncated,
+            original_bytes,
+            original_tokens,
+        }
+    }
+
+    fn fits(&self, text: &str, limits: OutputLimits) -> bool {
+        limits.max_bytes.is_none_or(|max_bytes| text.len() <= max_bytes)
+            && limits.max_tokens.is_none_or(|max_tokens| self.tokenizer.count_tokens(text) <= max_tokens)
+    }
+
+    async fn summarize(&self, tool_name: &str, text: &str, tokens: usize) -> Option<String> {
+        let model = self.summary_model.as_ref()?;
+        let input = &text[..floor_char_boundary(text, DEFAULT_MAX_SUMMARY_INPUT_BYTES)];
+        match summarize_output(model.as_ref(), &self.prompt, tool_name, input).await {
+            Ok(summary) => Some(format!("{}\n[summary of {} tokens of output from '{}']", summary, tokens, tool_name)),
+            Err(e) => {
+                tracing::warn!("tool_name=<{}>, error=<{}> | failed to summarize tool output", tool_name, e);
+                None
+            }
+        }
+    }
+
+    /// Cut text down to its limits, shrinking the byte budget until the token limit is met too.
+    fn truncate(&self, text: &str, limits: OutputLimits, strategy: OversizeStrategy) -> String {
+        let mut budget = limits.max_bytes.unwrap_or(text.len()).min(text.len());
+        if let Some(max_tokens) = limits.max_tokens {
+            let tokens = self.tokenizer.count_tokens(text).max(1);
+            budget = budget.min(text.len() * max_tokens / tokens);
+        }
+        loop {
+            let kept = cut(text, budget, strategy);
+            let fits = limits.max_tokens.is_none_or(|max_tokens| self.tokenizer.count_tokens(&kept) <= max_tokens);
+            if fits || budget == 0 {
+                return truncation_note(&kept, text.len());
+            }
+            budget = budget * 9 / 10;
+        }
+    }
+}
+
+/// Keep up to `budget` bytes of text, from its start or from its start and end.
+fn cut(text: &str, budget: usize, strategy: OversizeStrategy) -> String {
+    if strategy != OversizeStrategy::HeadAndTail {
+        return text[..floor_char_boundary(text, budget)].to_string();
+    }
+    let head = floor_char_boundary(text, budget * 2 / 3);
+    let mut tail = text.len() - (budget - head);
+    while !text.is_char_boundary(tail) {
+        tail += 1;
+    }
+    format!("{}\n[... {} bytes omitted ...]\n{}", &text[..head], tail - head, &text[tail..])
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::{MockModel, ModelResponse};
+
+    #[tokio::test]
+    async fn test_process_truncates_or_summarizes_oversized_outputs() {
+        let page = format!("<html>{}</html>", "lorem ipsum ".repeat(500));
+        let processor = ToolResultProcessor::new(OutputLimits::new(Some(4_000), None))
+            .with_tool_limits("scrape", OutputLimits::new(None, Some(50)));
+
+        let small = processor.process("search", Value::String("three results".to_string())).await;
+        assert_eq!(small.action, ProcessAction::Unchanged);
+
+        let scraped = processor.process("scrape", Value::String(page.clone())).await;
+        assert_eq!(scraped.action, ProcessAction::Truncated);
+        assert_eq!(scraped.original_bytes, page.len());
+        let text = scraped.output.as_str().unwrap();
+        assert!(text.starts_with("<html>lorem ipsum"));
+        assert!(text.ends_with(&format!("of {} bytes]", page.len())));
+
+        let log = format!("{}ERROR: disk full", "ok\n".repeat(3_000));
+        let processor = processor.with_strategy(OversizeStrategy::HeadAndTail);
+        let tail = processor.process("run_tests", Value::String(log)).await;
+        assert!(tail.output.as_str().unwrap().contains("bytes omitted ...]\n"));
+        assert!(tail.output.as_str().unwrap().contains("ERROR: disk full\n[truncated"));
+
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("A page of placeholder text.")]);
+        let summarizing = processor.with_summary_model(Arc::new(model));
+        let summarized = summarizing.process("scrape", Value::String(page)).await;
+        assert_eq!(summarized.action, ProcessAction::Summarized);
+        assert!(summarized.output.as_str().unwrap().starts_with("A page of placeholder text.\n[summary of"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Validation of tool input against its JSON schema.
+//! 
+//! This module checks a tool call's input against `ToolMetadata::input_schema`
+//! before the tool runs, and reports every violation with the exact argument
+//! path so the model can correct its call. It covers the keywords tools
+//! declare in practice: types, required and additional properties, array
+//! items, enums, numeric and length bounds, patterns and `anyOf`/`oneOf`/
+//! `allOf`. Unknown keywords and `$ref` are ignored.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use super::constraints::ConstraintViolation;
+
+/// The path reported for a violation of the input as a whole.
+pub const ROOT_PATH: &str = "$";
+
+/// Check a tool input against its JSON schema, returning every violation.
+///
+/// A `null` value for an optional property counts as absent, as models often
+/// send one instead of leaving the property out.
+pub fn validate_input(schema: &Value, input: &Value) -> Vec<ConstraintViolation> {
+    let mut violations = Vec::new();
+    validate_at(schema, input, "", &mut violations);
+    violations
+}
+
+fn violation(path: &str, message: String) -> ConstraintViolation {
+    let path = if path.is_empty() { ROOT_PATH } else { path };
+    ConstraintViolation { path: path.to_string(), message }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, violations: &mut Vec<ConstraintViolation>) {
+    let Some(schema) = schema.as_object() else {
+        if schema == &Value::Bool(false) {
+            violations.push(violation(path, "is not allowed".to_string()));
+        }
+        return;
+    };
+    if schema.contains_key("$ref") {
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            let message = format!("must be of type {}, got {}", types.join(" or "), type_name(value));
+            violations.push(violation(path, message));
+            return;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            let options: Vec<String> = options.iter().map(Value::to_string).collect();
+            violations.push(violation(path, format!("must be one of {}", options.join(", "))));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            violations.push(violation(path, format!("must be {}", expected)));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => validate_object(schema, fields, path, violations),
+        Value::Array(items) => validate_array(schema, items, path, violations),
+        Value::String(text) => validate_string(schema, text, path, violations),
+        Value::Number(number) => {
+            if let Some(number) = number.as_f64() {
+                validate_number(schema, number, path, violations);
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for subschema in all {
+            validate_at(subschema, value, path, violations);
+        }
+    }
+    for (keyword, exactly_one) in [("anyOf", false), ("oneOf", true)] {
+        let Some(options) = schema.get(keyword).and_then(Value::as_array) else {
+            continue;
+        };
+        let matched = options.iter().filter(|option| validate_input(option, value).is_empty()).count();
+        if matched == 0 || (exactly_one && matched > 1) {
+            let expected = if exactly_one { "exactly one" } else { "at least one" };
+            let message = format!("must match {} of the {} allowed schemas", expected, options.len());
+            violations.push(violation(path, message));
+        }
+    }
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    fields: &Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<ConstraintViolation>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for name in &required {
+        if !fields.contains_key(*name) {
+            violations.push(violation(&child_path(path, name), "is required".to_string()));
+        }
+    }
+    for (name, value) in fields {
+        let field_path = child_path(path, name);
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(_) if value.is_null() && !required.contains(&name.as_str()) => {}
+            Some(property) => validate_at(property, value, &field_path, violations),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    violations.push(violation(&field_path, "is not an allowed property".to_string()))
+                }
+                Some(additional @ Value::Object(_)) => validate_at(additional, value, &field_path, violations),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn validate_array(schema: &Map<String, Value>, items: &[Value], path: &str, violations: &mut Vec<ConstraintViolation>) {
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+        if (items.len() as u64) < min {
+            violations.push(violation(path, format!("must have at least {} items, got {}", min, items.len())));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+        if items.len() as u64 > max {
+            violations.push(violation(path, format!("must have at most {} items, got {}", max, items.len())));
+        }
+    }
+    if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+        for (index, item) in items.iter().enumerate() {
+            if items[..index].contains(item) {
+                violations.push(violation(&format!("{}[{}]", path, index), "duplicates an earlier item".to_string()));
+            }
+        }
+    }
+    if let Some(item_schema) = schema.get("items").filter(|items| items.is_object()) {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(item_schema, item, &format!("{}[{}]", path, index), violations);
+        }
+    }
+}
+
+fn validate_string(schema: &Map<String, Value>, text: &str, path: &str, violations: &mut Vec<ConstraintViolation>) {
+    let length = text.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if length < min {
+            violations.push(violation(path, format!("must be at least {} characters, got {}", min, length)));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        if length > max {
+            violations.push(violation(path, format!("must be at most {} characters, got {}", max, length)));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        // An invalid pattern is the tool's fault, not the model's, so it is not reported
+        if let Ok(regex) = Regex::new(pattern) {
+            if !regex.is_match(text) {
+                violations.push(violation(path, format!("must match the pattern '{}'", pattern)));
+            }
+        }
+    }
+}
+
+fn validate_number(schema: &Map<String, Value>, number: f64, path: &str, violations: &mut Vec<ConstraintViolation>) {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    if let Some(min) = bound("minimum").filter(|min| number < *min) {
+        violations.push(violation(path, format!("must be at least {}, got {}", min, number)));
+    }
+    if let Some(max) = bound("maximum").filter(|max| number > *max) {
+        violations.push(violation(path, format!("must be at most {}, got {}", max, number)));
+    }
+    if let Some(min) = bound("exclusiveMinimum").filter(|min| number <= *min) {
+        violations.push(violation(path, format!("must be greater than {}, got {}", min, number)));
+    }
+    if let Some(max) = bound("exclusiveMaximum").filter(|max| number >= *max) {
+        violations.push(violation(path, format!("must be less than {}, got {}", max, number)));
+    }
+    if let Some(step) = bound("multipleOf").filter(|step| *step > 0.0) {
+        if ((number / step).round() * step - number).abs() > f64::EPSILON * number.abs().max(1.0) {
+            violations.push(violation(path, format!("must be a multiple of {}", step)));
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_input_reports_paths() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "city": {"type": "string", "minLength": 2},
+                "days": {"type": "integer", "minimum": 1, "maximum": 14},
+                "units": {"type": "string", "enum": ["metric", "imperial"]},
+                "stops": {"type": "array", "items": {
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}},
+                    "required": ["name"]
+                }}
+            },
+            "required": ["city", "days"],
+            "additionalProperties": false
+        });
+
+        let valid = json!({"city": "Oslo", "days": 3, "units": null, "stops": [{"name": "Bergen"}]});
+        assert!(validate_input(&schema, &valid).is_empty());
+
+        let invalid = json!({"days": 30.5, "units": "kelvin", "stops": [{"name": 4}, {}], "extra": true});
+        let violations: Vec<String> = validate_input(&schema, &invalid).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            violations,
+            vec![
+                "argument 'city' is required",
+                "argument 'days' must be of type integer, got number",
+                "argument 'extra' is not an allowed property",
+                "argument 'stops[0].name' must be of type string, got number",
+                "argument 'stops[1].name' is required",
+                "argument 'units' must be one of \"metric\", \"imperial\"",
+            ]
+        );
+
+        let violations = validate_input(&schema, &json!("Oslo"));
+        assert_eq!(violations[0].path, ROOT_PATH);
+    }
+}
@@ -0,0 +1,267 @@
+//! Per-turn tool selection for the SDK.
+//! 
+//! This module provides the `ToolSelector` stage, which picks the tools
+//! most relevant to the current turn so an agent with many registered
+//! tools sends only a handful of specs to the model. `KeywordToolSelector`
+//! ranks tools by word overlap with their names and descriptions, and
+//! `EmbeddingToolSelector` ranks them by cosine similarity using a
+//! caller-supplied `Embedder`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::types::{IndubitablyResult, ToolSpec};
+
+/// The default number of tools sent to the model per turn.
+pub const DEFAULT_TOP_K: usize = 8;
+
+/// Picks the tool specifications to send to the model for a turn.
+#[async_trait]
+pub trait ToolSelector: Send + Sync {
+    /// Select the specs relevant to the query, in the order they were given.
+    async fn select(&self, query: &str, specs: &[ToolSpec]) -> IndubitablyResult<Vec<ToolSpec>>;
+
+    /// Export the selector's cached state, such as tool embeddings, for an agent snapshot.
+    fn export_cache(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Import cached state exported by `export_cache`.
+    fn import_cache(&self, _cache: serde_json::Value) -> IndubitablyResult<()> {
+        Ok(())
+    }
+}
+
+/// Computes vector embeddings for text.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Embed each text, returning one vector per input.
+    async fn embed(&self, texts: &[String]) -> IndubitablyResult<Vec<Vec<f32>>>;
+}
+
+/// Selects tools by word overlap between the query and each tool's name and description.
+#[derive(Debug, Clone)]
+pub struct KeywordToolSelector {
+    /// The maximum number of tools to select.
+    pub top_k: usize,
+    /// Tools that are always selected.
+    pub always_include: HashSet<String>,
+}
+
+impl Default for KeywordToolSelector {
+    fn default() -> Self {
+        Self {
+            top_k: DEFAULT_TOP_K,
+            always_include: HashSet::new(),
+        }
+    }
+}
+
+impl KeywordToolSelector {
+    /// Create a new keyword selector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of tools to select.
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Always select the named tool.
+    pub fn with_always_include(mut self, tool_name: &str) -> Self {
+        self.always_include.insert(tool_name.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl ToolSelector for KeywordToolSelector {
+    async fn select(&self, query: &str, specs: &[ToolSpec]) -> IndubitablyResult<Vec<ToolSpec>> {
+        let query_words = words(query);
+        // Weight rare words higher so a shared word like "file" does not outrank a specific match.
+        let documents: Vec<HashSet<String>> = specs.iter().map(|spec| words(&spec_text(spec))).collect();
+        let document_frequency = |word: &String| documents.iter().filter(|doc| doc.contains(word)).count();
+        let scores: Vec<f32> = documents
+            .iter()
+            .map(|doc| {
+                query_words
+                    .iter()
+                    .filter(|word| doc.contains(*word))
+                    .map(|word| (1.0 + specs.len() as f32 / document_frequency(word) as f32).ln())
+                    .sum()
+            })
+            .collect();
+        Ok(top_k(specs, &scores, self.top_k, &self.always_include))
+    }
+}
+
+/// Selects tools by embedding similarity between the query and each tool's name and description.
+pub struct EmbeddingToolSelector {
+    embedder: Arc<dyn Embedder>,
+    /// The maximum number of tools to select.
+    pub top_k: usize,
+    /// Tools that are always selected.
+    pub always_include: HashSet<String>,
+    /// Cached tool embeddings keyed by the embedded text.
+    cache: Mutex<HashMap<String, Vec<f32>>>,
+}
+
+impl EmbeddingToolSelector {
+    /// Create a new embedding selector.
+    pub fn new(embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            embedder,
+            top_k: DEFAULT_TOP_K,
+            always_include: HashSet::new(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the maximum number of tools to select.
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Always select the named tool.
+    pub fn with_always_include(mut self, tool_name: &str) -> Self {
+        self.always_include.insert(tool_name.to_string());
+        self
+    }
+}
+
+impl std::fmt::Debug for EmbeddingToolSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddingToolSelector")
+            .field("top_k", &self.top_k)
+            .field("always_include", &self.always_include)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ToolSelector for EmbeddingToolSelector {
+    async fn select(&self, query: &str, specs: &[ToolSpec]) -> IndubitablyResult<Vec<ToolSpec>> {
+        let texts: Vec<String> = specs.iter().map(spec_text).collect();
+        let missing: Vec<String> = {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            texts.iter().filter(|text| !cache.contains_key(*text)).cloned().collect()
+        };
+
+        // Embed the query together with any tools not seen before in a single call.
+        let mut inputs = vec![query.to_string()];
+        inputs.extend(missing.iter().cloned());
+        let mut vectors = self.embedder.embed(&inputs).await?.into_iter();
+        let query_vector = vectors.next().unwrap_or_default();
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        for (text, vector) in missing.into_iter().zip(vectors) {
+            cache.insert(text, vector);
+        }
+        let scores: Vec<f32> = texts
+            .iter()
+            .map(|text| cache.get(text).map_or(0.0, |vector| cosine_similarity(&query_vector, vector)))
+            .collect();
+        Ok(top_k(specs, &scores, self.top_k, &self.always_include))
+    }
+
+    fn export_cache(&self) -> Option<serde_json::Value> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        serde_json::to_value(&*cache).ok()
+    }
+
+    fn import_cache(&self, cache: serde_json::Value) -> IndubitablyResult<()> {
+        let imported: HashMap<String, Vec<f32>> = serde_json::from_value(cache)?;
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).extend(imported);
+        Ok(())
+    }
+}
+
+/// Keep the always-included specs and the highest scoring ones, preserving the original order.
+fn top_k(specs: &[ToolSpec], scores: &[f32], k: usize, always_include: &HashSet<String>) -> Vec<ToolSpec> {
+    let mut ranked: Vec<usize> = (0..specs.len())
+        .filter(|&index| !always_include.contains(&specs[index].name))
+        .collect();
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+    let mut chosen: HashSet<usize> = ranked.into_iter().take(k).collect();
+    chosen.extend((0..specs.len()).filter(|&index| always_include.contains(&specs[index].name)));
+
+    specs
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| chosen.contains(index))
+        .map(|(_, spec)| spec.clone())
+        .collect()
+}
+
+/// The text a tool is matched on.
+fn spec_text(spec: &ToolSpec) -> String {
+    format!("{} {}", spec.name.replace(['_', '-', '.'], " "), spec.description)
+}
+
+/// Split text into lowercase alphanumeric words.
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 1)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Get the cosine similarity of two vectors, or 0 if either is zero.
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs() -> Vec<ToolSpec> {
+        vec![
+            ToolSpec::new("send_email", "Send an email message to a recipient"),
+            ToolSpec::new("get_weather", "Get the weather forecast for a city"),
+            ToolSpec::new("read_file", "Read a file from disk"),
+            ToolSpec::new("search_docs", "Search the documentation"),
+        ]
+    }
+
+    struct LetterEmbedder;
+
+    #[async_trait]
+    impl Embedder for LetterEmbedder {
+        async fn embed(&self, texts: &[String]) -> IndubitablyResult<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    ["weather", "email", "file"].iter().map(|w| text.matches(w).count() as f32).collect()
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_selectors() {
+        let keyword = KeywordToolSelector::new().with_top_k(1).with_always_include("search_docs");
+        let selected = keyword.select("What's the weather in Paris?", &specs()).await.unwrap();
+        let names: Vec<&str> = selected.iter().map(|spec| spec.name.as_str()).collect();
+        assert_eq!(names, vec!["get_weather", "search_docs"]);
+
+        let embedding = EmbeddingToolSelector::new(Arc::new(LetterEmbedder)).with_top_k(2);
+        let selected = embedding.select("email the file to Bob", &specs()).await.unwrap();
+        let names: Vec<&str> = selected.iter().map(|spec| spec.name.as_str()).collect();
+        assert_eq!(names, vec!["send_email", "read_file"]);
+        assert_eq!(embedding.cache.lock().unwrap().len(), 4);
+    }
+}
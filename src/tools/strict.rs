@@ -0,0 +1,234 @@
+//! Strict tool schemas for the SDK.
+//! 
+//! Providers with strict function calling, such as OpenAI's `strict` tools,
+//! guarantee that tool call arguments match the schema, but only accept a
+//! subset of JSON Schema: every object must forbid additional properties and
+//! list all of its properties as required, and keywords such as `minLength`
+//! or `pattern` are rejected. This module rewrites a tool's input schema into
+//! that subset where it can, recording each fix, and reports what it cannot
+//! fix so the tool can be sent without strict mode instead.
+//! 
+//! Optional properties are made required and nullable. Tool input validation
+//! treats `null` for an optional property as absent, so tools see the same
+//! input as before. Dropped keywords are still enforced by that validation.
+
+use serde_json::{json, Map, Value};
+
+use crate::types::ToolSpec;
+
+/// The keywords strict mode rejects; they are removed from strict schemas.
+pub const STRICT_UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "minLength",
+    "maxLength",
+    "pattern",
+    "format",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "patternProperties",
+    "unevaluatedProperties",
+    "propertyNames",
+    "minProperties",
+    "maxProperties",
+    "unevaluatedItems",
+    "contains",
+    "minContains",
+    "maxContains",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+];
+
+/// A tool input schema rewritten for strict mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrictSchema {
+    /// The rewritten schema.
+    pub schema: Value,
+    /// The changes made to the original schema.
+    pub fixes: Vec<String>,
+    /// The problems that keep the schema from strict mode.
+    pub violations: Vec<String>,
+}
+
+impl StrictSchema {
+    /// Check whether the schema can be sent in strict mode.
+    pub fn is_strict(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Rewrite a tool input schema for strict mode; a missing schema becomes an empty object.
+pub fn to_strict_schema(schema: Option<&Value>) -> StrictSchema {
+    let mut strict = StrictSchema {
+        schema: schema.filter(|schema| !schema.is_null()).cloned().unwrap_or_else(|| json!({})),
+        fixes: Vec::new(),
+        violations: Vec::new(),
+    };
+    let Some(root) = strict.schema.as_object_mut() else {
+        strict.violations.push("the schema is not an object".to_string());
+        return strict;
+    };
+    if root.is_empty() {
+        root.insert("type".to_string(), json!("object"));
+        root.insert("properties".to_string(), json!({}));
+    }
+    if root.get("type").and_then(Value::as_str) != Some("object") {
+        strict.violations.push("the root schema must have type 'object'".to_string());
+        return strict;
+    }
+    let mut schema = std::mem::take(&mut strict.schema);
+    rewrite(&mut schema, "$", &mut strict.fixes, &mut strict.violations);
+    strict.schema = schema;
+    strict
+}
+
+/// Get a tool's input schema rewritten for strict mode, or `None` if it cannot be made strict.
+pub fn strict_tool_schema(spec: &ToolSpec) -> Option<Value> {
+    let strict = to_strict_schema(spec.input_schema.as_ref());
+    if !strict.is_strict() {
+        tracing::debug!(
+            "tool_name=<{}>, violations=<{}> | sending tool without strict mode",
+            spec.name,
+            strict.violations.join("; ")
+        );
+        return None;
+    }
+    Some(strict.schema)
+}
+
+fn rewrite(node: &mut Value, path: &str, fixes: &mut Vec<String>, violations: &mut Vec<String>) {
+    let Some(object) = node.as_object_mut() else {
+        return;
+    };
+    for keyword in STRICT_UNSUPPORTED_KEYWORDS {
+        if object.remove(*keyword).is_some() {
+            fixes.push(format!("removed '{}' at {}", keyword, path));
+        }
+    }
+
+    let is_object = object.get("type").and_then(Value::as_str) == Some("object") || object.contains_key("properties");
+    if is_object {
+        rewrite_object(object, path, fixes, violations);
+    }
+    if let Some(items) = object.get_mut("items") {
+        rewrite(items, &format!("{}[]", path), fixes, violations);
+    }
+    for keyword in ["anyOf", "oneOf", "allOf"] {
+        if let Some(Value::Array(branches)) = object.get_mut(keyword) {
+            for (index, branch) in branches.iter_mut().enumerate() {
+                rewrite(branch, &format!("{}.{}[{}]", path, keyword, index), fixes, violations);
+            }
+        }
+    }
+    for keyword in ["$defs", "definitions"] {
+        if let Some(Value::Object(definitions)) = object.get_mut(keyword) {
+            for (name, definition) in definitions.iter_mut() {
+                rewrite(definition, &format!("{}.{}.{}", path, keyword, name), fixes, violations);
+            }
+        }
+    }
+}
+
+fn rewrite_object(
+    object: &mut Map<String, Value>,
+    path: &str,
+    fixes: &mut Vec<String>,
+    violations: &mut Vec<String>,
+) {
+    match object.get("additionalProperties") {
+        None => {
+            object.insert("additionalProperties".to_string(), json!(false));
+            fixes.push(format!("set additionalProperties to false at {}", path));
+        }
+        Some(Value::Bool(false)) => {}
+        Some(_) => violations.push(format!("{} allows additional properties", path)),
+    }
+
+    let required: Vec<String> = object
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default();
+    let mut all_required = required.clone();
+    if let Some(Value::Object(properties)) = object.get_mut("properties") {
+        for (name, property) in properties.iter_mut() {
+            let property_path = format!("{}.{}", path, name);
+            rewrite(property, &property_path, fixes, violations);
+            if !required.contains(name) {
+                make_nullable(property);
+                all_required.push(name.clone());
+                fixes.push(format!("made optional property {} required and nullable", property_path));
+            }
+        }
+    }
+    object.insert("required".to_string(), json!(all_required));
+}
+
+/// Let a property schema also accept `null`.
+fn make_nullable(property: &mut Value) {
+    let Some(schema) = property.as_object_mut() else {
+        return;
+    };
+    if let Some(Value::Array(values)) = schema.get_mut("enum") {
+        if !values.contains(&Value::Null) {
+            values.push(Value::Null);
+        }
+    }
+    match schema.get_mut("type") {
+        Some(Value::String(kind)) => {
+            let kind = std::mem::take(kind);
+            schema.insert("type".to_string(), json!([kind, "null"]));
+        }
+        Some(Value::Array(kinds)) => {
+            if !kinds.contains(&json!("null")) {
+                kinds.push(json!("null"));
+            }
+        }
+        _ => {
+            let original = std::mem::take(schema);
+            *property = json!({"anyOf": [Value::Object(original), {"type": "null"}]});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::schema::validate_input;
+
+    #[test]
+    fn test_to_strict_schema_fixes_what_it_can() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "minLength": 1},
+                "limit": {"type": "integer", "maximum": 50},
+                "filters": {
+                    "type": "object",
+                    "properties": {"site": {"type": "string"}},
+                    "required": ["site"],
+                },
+            },
+            "required": ["query"],
+        });
+        let strict = to_strict_schema(Some(&schema));
+        assert!(strict.is_strict());
+        assert_eq!(strict.schema["additionalProperties"], false);
+        assert_eq!(strict.schema["required"], json!(["query", "filters", "limit"]));
+        assert_eq!(strict.schema["properties"]["limit"]["type"], json!(["integer", "null"]));
+        assert_eq!(strict.schema["properties"]["filters"]["additionalProperties"], false);
+        assert!(strict.schema["properties"]["query"].get("minLength").is_none());
+        assert_eq!(strict.fixes.len(), 6);
+
+        // Arguments that fill optional properties with null still validate against the original schema
+        let input = json!({"query": "rust", "limit": null, "filters": null});
+        assert!(validate_input(&schema, &input).is_empty());
+
+        let open = json!({"type": "object", "additionalProperties": true});
+        assert_eq!(to_strict_schema(Some(&open)).violations, vec!["$ allows additional properties"]);
+        assert!(!to_strict_schema(Some(&json!({"type": "string"}))).is_strict());
+        assert_eq!(to_strict_schema(None).schema["required"], json!([]));
+    }
+}
@@ -0,0 +1,160 @@
+//! Tool output summarization for the SDK.
+//! 
+//! This module provides `ToolOutputSummarizer`, which keeps large tool
+//! outputs from filling the context. Outputs above a token threshold are
+//! stored in full in an artifact store and replaced in the conversation by
+//! a summary written by a cheap model, with a note pointing at the
+//! `read_artifact` tool for the full text.
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::artifacts::{create_read_artifact_tool, ArtifactHandle, ArtifactStore, READ_ARTIFACT_TOOL_NAME};
+use super::registry::Tool;
+use crate::models::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::models::Model;
+use crate::types::{IndubitablyResult, Message};
+
+/// The default number of tokens above which tool outputs are summarized.
+pub const DEFAULT_SUMMARY_THRESHOLD_TOKENS: usize = 2000;
+
+/// The default prompt asking the model to summarize a tool output.
+pub const DEFAULT_SUMMARY_PROMPT: &str = "Summarize the following tool output for another assistant that \
+will use it to answer the user. Keep every identifier, number, name and error message that could matter, \
+drop boilerplate and repetition, and do not add anything that is not in the output.";
+
+/// A stage replacing large tool outputs with model-written summaries.
+#[derive(Clone)]
+pub struct ToolOutputSummarizer {
+    model: Arc<dyn Model>,
+    store: Arc<dyn ArtifactStore>,
+    tokenizer: Arc<dyn Tokenizer>,
+    threshold_tokens: usize,
+    prompt: String,
+}
+
+impl fmt::Debug for ToolOutputSummarizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolOutputSummarizer")
+            .field("model", &self.model.model_id())
+            .field("store", &self.store)
+            .field("tokenizer", &self.tokenizer.name())
+            .field("threshold_tokens", &self.threshold_tokens)
+            .finish()
+    }
+}
+
+impl ToolOutputSummarizer {
+    /// Create a summarizer using the given model and keeping full outputs in the given store.
+    pub fn new(model: Arc<dyn Model>, store: Arc<dyn ArtifactStore>) -> Self {
+        Self {
+            model,
+            store,
+            tokenizer: Arc::new(HeuristicTokenizer::default()),
+            threshold_tokens: DEFAULT_SUMMARY_THRESHOLD_TOKENS,
+            prompt: DEFAULT_SUMMARY_PROMPT.to_string(),
+        }
+    }
+
+    /// Set the number of tokens above which outputs are summarized.
+    pub fn with_threshold_tokens(mut self, threshold_tokens: usize) -> Self {
+        self.threshold_tokens = threshold_tokens;
+        self
+    }
+
+    /// Set the tokenizer used to measure outputs.
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Set the prompt asking the model to summarize an output.
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = prompt.to_string();
+        self
+    }
+
+    /// Get the store holding full outputs.
+    pub fn store(&self) -> &Arc<dyn ArtifactStore> {
+        &self.store
+    }
+
+    /// Create the `read_artifact` tool the model uses to read full outputs.
+    pub fn retrieval_tool(&self) -> Tool {
+        create_read_artifact_tool(Arc::clone(&self.store))
+    }
+
+    /// Summarize an output over the threshold, returning the summary, the full output's handle and its tokens.
+    ///
+    /// Returns `None` when the output is within the threshold.
+    pub async fn summarize(
+        &self,
+        tool_name: &str,
+        output: &Value,
+    ) -> IndubitablyResult<Option<(Value, ArtifactHandle, usize)>> {
+        let (text, media_type) = match output {
+            Value::String(text) => (text.clone(), "text/plain"),
+            other => (serde_json::to_string_pretty(other)?, "application/json"),
+        };
+        let tokens = self.tokenizer.count_tokens(&text);
+        if tokens <= self.threshold_tokens {
+            return Ok(None);
+        }
+
+        let summary = summarize_output(self.model.as_ref(), &self.prompt, tool_name, &text).await?;
+        let handle = self.store.put(text.as_bytes(), media_type)?;
+        tracing::debug!(
+            "tool_name=<{}>, tokens=<{}>, handle=<{}> | summarized tool output",
+            tool_name,
+            tokens,
+            handle
+        );
+
+        let text = format!(
+            "{}\n[summary of {} tokens of output; the full output is stored as artifact '{}'; call {} with this handle, an offset and a length to read it]",
+            summary,
+            tokens,
+            handle,
+            READ_ARTIFACT_TOOL_NAME
+        );
+        Ok(Some((Value::String(text), handle, tokens)))
+    }
+}
+
+/// Ask a model to summarize the output of a tool.
+pub(crate) async fn summarize_output(
+    model: &dyn Model,
+    prompt: &str,
+    tool_name: &str,
+    text: &str,
+) -> IndubitablyResult<String> {
+    let request = vec![Message::user(&format!("Output of the '{}' tool:\n\n{}", tool_name, text))];
+    let summary = model.generate(&request, None, Some(prompt)).await?;
+    Ok(summary.content.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::model::{MockModel, ModelResponse};
+    use crate::tools::artifacts::InMemoryArtifactStore;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_summarize_large_output() {
+        let store: Arc<dyn ArtifactStore> = Arc::new(InMemoryArtifactStore::new());
+        let model = MockModel::new().with_responses(vec![ModelResponse::new("42 rows; row 17 failed validation.")]);
+        let summarizer = ToolOutputSummarizer::new(Arc::new(model), Arc::clone(&store)).with_threshold_tokens(10);
+
+        assert!(summarizer.summarize("query", &json!("short")).await.unwrap().is_none());
+
+        let output = Value::String("row ok\n".repeat(42));
+        let (summary, handle, tokens) = summarizer.summarize("query", &output).await.unwrap().unwrap();
+        assert!(summary.as_str().unwrap().starts_with("42 rows; row 17 failed validation.\n[summary of"));
+        assert!(tokens > 10);
+        let page = summarizer.retrieval_tool().execute(json!({ "handle": handle, "length": 7 })).unwrap();
+        assert_eq!(page["content"], json!("row ok\n"));
+    }
+}
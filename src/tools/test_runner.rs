@@ -0,0 +1,173 @@
+//! The built-in test runner tool for the SDK.
+//! 
+//! This module provides `TestCommand`, a test command such as `cargo test`
+//! run in a directory with a time limit, and the `run_tests` tool that lets
+//! the model run it. Output is captured through files rather than pipes, so
+//! a command producing a lot of output cannot stall, and only its tail is
+//! kept, where test failures are summarized.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use super::registry::{Tool, ToolMetadata};
+use crate::types::{IndubitablyError, IndubitablyResult, ToolError};
+
+/// The name of the test runner tool.
+pub const RUN_TESTS_TOOL_NAME: &str = "run_tests";
+
+/// The default time limit of a test run.
+pub const DEFAULT_TEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The most characters of output kept from a test run.
+pub const MAX_TEST_OUTPUT_CHARS: usize = 8_000;
+
+fn runner_error(message: impl std::fmt::Display) -> IndubitablyError {
+    IndubitablyError::ToolError(ToolError::ExecutionFailed(message.to_string()))
+}
+
+/// The outcome of a test run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestOutcome {
+    /// Whether the command exited successfully within the time limit.
+    pub passed: bool,
+    /// The exit code, or `None` if the command was killed.
+    pub exit_code: Option<i32>,
+    /// Whether the time limit stopped the command.
+    pub timed_out: bool,
+    /// The tail of the command's standard output and error.
+    pub output: String,
+}
+
+/// A test command run in a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCommand {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl TestCommand {
+    /// Create a test command running the given program.
+    pub fn new(program: &str) -> Self {
+        Self {
+            program: program.to_string(),
+            args: Vec::new(),
+            timeout: DEFAULT_TEST_TIMEOUT,
+        }
+    }
+
+    /// Create the `cargo test` command.
+    pub fn cargo() -> Self {
+        Self::new("cargo").with_args(&["test"])
+    }
+
+    /// Add arguments to the command.
+    pub fn with_args(mut self, args: &[&str]) -> Self {
+        self.args.extend(args.iter().map(|arg| arg.to_string()));
+        self
+    }
+
+    /// Set the time limit, after which the command is killed.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get the command line, for messages.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program.as_str()).chain(self.args.iter().map(String::as_str)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Run the command in a directory and wait for it, up to the time limit.
+    pub async fn run(&self, directory: &Path) -> IndubitablyResult<TestOutcome> {
+        let log = std::env::temp_dir().join(format!("indubitably-tests-{}.log", uuid::Uuid::new_v4().simple()));
+        let stdout = File::create(&log).map_err(runner_error)?;
+        let stderr = stdout.try_clone().map_err(runner_error)?;
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .current_dir(directory)
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| runner_error(format!("could not run {}: {}", self.command_line(), e)))?;
+
+        let (status, timed_out) = match tokio::time::timeout(self.timeout, child.wait()).await {
+            Ok(status) => (Some(status.map_err(runner_error)?), false),
+            Err(_) => {
+                let _ = child.kill().await;
+                (None, true)
+            }
+        };
+        let output = std::fs::read(&log).map(|bytes| String::from_utf8_lossy(&bytes).into_owned()).unwrap_or_default();
+        let _ = std::fs::remove_file(&log);
+
+        let exit_code = status.and_then(|status| status.code());
+        let passed = status.is_some_and(|status| status.success());
+        tracing::debug!(
+            "command=<{}>, passed=<{}>, exit_code=<{:?}>, timed_out=<{}> | ran tests",
+            self.command_line(),
+            passed,
+            exit_code,
+            timed_out
+        );
+        Ok(TestOutcome { passed, exit_code, timed_out, output: tail(&output, MAX_TEST_OUTPUT_CHARS) })
+    }
+}
+
+/// Keep the last characters of a text.
+fn tail(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - max_chars).collect();
+    format!("[{} earlier characters omitted]\n{}", count - max_chars, kept)
+}
+
+/// Create the `run_tests` tool running a test command in a directory.
+pub fn create_run_tests_tool(directory: impl Into<PathBuf>, command: TestCommand) -> Tool {
+    let directory: PathBuf = directory.into();
+    let description = format!("Run the test suite with `{}` and report whether it passed", command.command_line());
+    let command = Arc::new(command);
+    let directory = Arc::new(directory);
+    let function = move |_input: Value| {
+        let command = Arc::clone(&command);
+        let directory = Arc::clone(&directory);
+        async move { Ok(serde_json::to_value(command.run(&directory).await?)?) }
+    };
+    Tool::new_async(RUN_TESTS_TOOL_NAME, &description, function)
+        .with_metadata(ToolMetadata::new().with_input_schema(json!({ "type": "object", "properties": {} })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_tests_tool_reports_exit_status() {
+        let directory = tempfile::tempdir().unwrap();
+        let passing = create_run_tests_tool(directory.path(), TestCommand::new("sh").with_args(&["-c", "echo ok"]));
+        let output = passing.execute_async(json!({})).await.unwrap();
+        assert_eq!(output["passed"], true);
+        assert_eq!(output["output"], "ok\n");
+
+        let failing = TestCommand::new("sh").with_args(&["-c", "echo broken >&2; exit 3"]);
+        let failing = failing.run(directory.path()).await.unwrap();
+        assert!(!failing.passed);
+        assert_eq!(failing.exit_code, Some(3));
+        assert_eq!(failing.output, "broken\n");
+
+        let slow = TestCommand::new("sleep").with_args(&["5"]).with_timeout(Duration::from_millis(100));
+        assert!(slow.run(directory.path()).await.unwrap().timed_out);
+        assert_eq!(tail("abcdef", 2), "[4 earlier characters omitted]\nef");
+    }
+}
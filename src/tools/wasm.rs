@@ -0,0 +1,126 @@
+//! WebAssembly plugin tools for the SDK.
+//! 
+//! This module provides `WasmToolLoader`, which turns a compiled WebAssembly
+//! module into a `Tool` so that the `ToolWatcher` can hot-reload real
+//! plugins. Modules are instantiated through the same `WasmRuntime` skills
+//! use: `WasmtimeCliRuntime` with the `wasmtime-cli` feature, or one the
+//! application provides with an embedded WebAssembly engine. A plugin exports
+//! `call`, which receives the tool input and returns its output, and may
+//! export `describe`, which returns the tool's `name`, `description` and
+//! `input_schema`.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::{Tool, ToolMetadata};
+use crate::skills::WasmRuntime;
+use crate::types::IndubitablyResult;
+
+/// The export a plugin runs tool calls with.
+pub const CALL_EXPORT: &str = "call";
+
+/// The optional export a plugin describes itself with.
+pub const DESCRIBE_EXPORT: &str = "describe";
+
+/// The description a plugin returns from `describe`.
+#[derive(Debug, Default, Deserialize)]
+struct PluginDescription {
+    name: Option<String>,
+    description: Option<String>,
+    input_schema: Option<Value>,
+}
+
+/// Loads compiled WebAssembly modules as tools.
+#[derive(Clone)]
+pub struct WasmToolLoader {
+    runtime: Arc<dyn WasmRuntime>,
+}
+
+impl fmt::Debug for WasmToolLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmToolLoader").finish_non_exhaustive()
+    }
+}
+
+impl WasmToolLoader {
+    /// Create a loader instantiating modules with the given runtime.
+    pub fn new(runtime: Arc<dyn WasmRuntime>) -> Self {
+        Self { runtime }
+    }
+
+    /// Load a `.wasm` file as a tool named after the file unless the module describes itself.
+    pub fn load_file(&self, path: &Path) -> IndubitablyResult<Tool> {
+        let bytes = std::fs::read(path)?;
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+        self.load_bytes(stem, &bytes)
+    }
+
+    /// Instantiate a module and wrap it in a tool.
+    pub fn load_bytes(&self, default_name: &str, bytes: &[u8]) -> IndubitablyResult<Tool> {
+        let instance = self.runtime.instantiate(bytes)?;
+        let description = match instance.call(DESCRIBE_EXPORT, Value::Null) {
+            Ok(description) => serde_json::from_value(description).unwrap_or_else(|e| {
+                tracing::warn!("tool_name=<{}>, error=<{}> | ignoring invalid plugin description", default_name, e);
+                PluginDescription::default()
+            }),
+            Err(e) => {
+                tracing::debug!("tool_name=<{}>, error=<{}> | plugin does not describe itself", default_name, e);
+                PluginDescription::default()
+            }
+        };
+
+        let name = description.name.unwrap_or_else(|| default_name.to_string());
+        let text = description.description.unwrap_or_else(|| format!("WebAssembly plugin '{}'", name));
+        let schema = description.input_schema.unwrap_or_else(|| serde_json::json!({"type": "object"}));
+        tracing::debug!("tool_name=<{}> | loaded webassembly plugin", name);
+        Ok(Tool::new(&name, &text, Arc::new(move |input| instance.call(CALL_EXPORT, input)))
+            .with_metadata(ToolMetadata::new().with_input_schema(schema)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skills::WasmInstance;
+    use crate::types::ToolError;
+
+    /// A runtime whose modules echo their input and take their description from the module bytes.
+    struct EchoRuntime;
+
+    struct EchoInstance {
+        description: Option<Value>,
+    }
+
+    impl WasmRuntime for EchoRuntime {
+        fn instantiate(&self, module: &[u8]) -> IndubitablyResult<Arc<dyn WasmInstance>> {
+            Ok(Arc::new(EchoInstance { description: serde_json::from_slice(module).ok() }))
+        }
+    }
+
+    impl WasmInstance for EchoInstance {
+        fn call(&self, export: &str, input: Value) -> IndubitablyResult<Value> {
+            match (export, &self.description) {
+                (CALL_EXPORT, _) => Ok(input),
+                (DESCRIBE_EXPORT, Some(description)) => Ok(description.clone()),
+                _ => Err(ToolError::ExecutionFailed(format!("No export '{}'", export)).into()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_wasm_plugins_through_runtime() {
+        let loader = WasmToolLoader::new(Arc::new(EchoRuntime));
+        let tool = loader.load_bytes("fallback", br#"{"name": "echo", "description": "Echo the input"}"#).unwrap();
+        assert_eq!(tool.name, "echo");
+        assert_eq!(tool.description, "Echo the input");
+        assert_eq!(tool.execute(serde_json::json!({"text": "hi"})).unwrap()["text"], "hi");
+
+        let plain = loader.load_bytes("convert", b"\0asm").unwrap();
+        assert_eq!(plain.name, "convert");
+        assert_eq!(plain.metadata.input_schema, Some(serde_json::json!({"type": "object"})));
+    }
+}
@@ -1,17 +1,29 @@
 //! Tool watcher for monitoring tool directories and hot-reloading.
 //! 
 //! This module provides functionality for watching tool directories
-//! and automatically reloading tools when they change.
+//! and automatically reloading tools when they change. Each tool file must
+//! have a detached `<file>.sig` signature from a publisher in the watcher's
+//! trust store; unsigned or untrusted files are refused by default. A file
+//! is read once, and the bytes that were verified are the bytes loaded.
+//! Changing or removing a signature re-verifies its tool file.
+//! WebAssembly modules (`.wasm`) are loaded as executable plugins when the
+//! watcher has a `WasmToolLoader`, and TOML or YAML files as declarative HTTP
+//! tools when it has an HTTP client; other files are registered as placeholders.
 
 use std::collections::HashMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use notify::{Watcher, RecursiveMode, WatcherKind};
 use serde::{Deserialize, Serialize};
 
-use crate::types::IndubitablyResult;
+use crate::models::HttpClient;
+use crate::skills::TrustStore;
+use crate::types::{IndubitablyResult, ToolError};
+use super::http_tool::load_http_tools_from_text;
 use super::registry::{Tool, ToolRegistry};
+use super::wasm::WasmToolLoader;
 
 /// Configuration for the tool watcher.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,7 +45,7 @@ impl Default for ToolWatcherConfig {
         Self {
             watch_directory: PathBuf::from("./tools"),
             recursive: true,
-            file_extensions: vec!["rs".to_string(), "toml".to_string()],
+            file_extensions: ["rs", "toml", "yaml", "yml", "wasm"].iter().map(|e| e.to_string()).collect(),
             debounce_ms: 1000,
             enable_hot_reload: true,
         }
@@ -90,10 +102,56 @@ pub enum ToolWatcherEvent {
     ToolLoaded(String),
     /// A tool was unloaded.
     ToolUnloaded(String),
+    /// A tool file failed signature verification and was not loaded.
+    VerificationFailed(PathBuf, String),
     /// An error occurred during watching.
     Error(String),
 }
 
+/// The loaders turning tool files into executable tools.
+#[derive(Clone, Default)]
+struct ToolFileLoaders {
+    wasm_loader: Option<Arc<WasmToolLoader>>,
+    http_client: Option<Arc<dyn HttpClient>>,
+}
+
+impl fmt::Debug for ToolFileLoaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolFileLoaders")
+            .field("wasm_loader", &self.wasm_loader)
+            .field("http_client", &self.http_client.is_some())
+            .finish()
+    }
+}
+
+impl ToolFileLoaders {
+    /// Load the tools defined in a file from its verified contents.
+    fn load(&self, path: &Path, bytes: &[u8]) -> IndubitablyResult<Vec<Tool>> {
+        match (path.extension().and_then(|e| e.to_str()), &self.http_client) {
+            (Some("wasm"), _) => {
+                let loader = self.wasm_loader.as_ref().ok_or_else(|| {
+                    ToolError::ToolNotAvailable(format!("No WASM runtime is configured to load {:?}", path))
+                })?;
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+                Ok(vec![loader.load_bytes(stem, bytes)?])
+            }
+            (Some("toml" | "yaml" | "yml"), Some(client)) => {
+                let text = std::str::from_utf8(bytes)
+                    .map_err(|e| ToolError::InvalidInput(format!("Tool file {:?} is not UTF-8: {}", path, e)))?;
+                load_http_tools_from_text(path, text, Arc::clone(client))
+            }
+            _ => {
+                let tool_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+                Ok(vec![Tool::new(
+                    tool_name,
+                    &format!("Tool loaded from {:?}", path),
+                    Arc::new(|_| Ok(serde_json::Value::String("placeholder".to_string()))),
+                )])
+            }
+        }
+    }
+}
+
 /// A watcher for monitoring tool directories and hot-reloading tools.
 #[derive(Debug)]
 pub struct ToolWatcher {
@@ -102,7 +160,9 @@ pub struct ToolWatcher {
     watcher: Option<notify::RecommendedWatcher>,
     event_sender: mpsc::Sender<ToolWatcherEvent>,
     event_receiver: mpsc::Receiver<ToolWatcherEvent>,
-    loaded_tools: Arc<RwLock<HashMap<PathBuf, String>>>,
+    loaded_tools: Arc<RwLock<HashMap<PathBuf, Vec<String>>>>,
+    trust_store: Arc<TrustStore>,
+    loaders: ToolFileLoaders,
 }
 
 impl ToolWatcher {
@@ -118,9 +178,29 @@ impl ToolWatcher {
             event_sender,
             event_receiver,
             loaded_tools,
+            trust_store: Arc::new(TrustStore::new()),
+            loaders: ToolFileLoaders::default(),
         })
     }
 
+    /// Set the trust store used to verify tool files before loading them.
+    pub fn with_trust_store(mut self, store: TrustStore) -> Self {
+        self.trust_store = Arc::new(store);
+        self
+    }
+
+    /// Set the loader used for WebAssembly plugins; without one, `.wasm` files fail to load.
+    pub fn with_wasm_loader(mut self, loader: WasmToolLoader) -> Self {
+        self.loaders.wasm_loader = Some(Arc::new(loader));
+        self
+    }
+
+    /// Set the client used by declarative HTTP tools; without one, TOML and YAML files load as placeholders.
+    pub fn with_http_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.loaders.http_client = Some(client);
+        self
+    }
+
     /// Start watching the tool directory.
     pub async fn start(&mut self) -> IndubitablyResult<()> {
         if !self.config.enable_hot_reload {
@@ -150,10 +230,12 @@ impl ToolWatcher {
         let event_sender = self.event_sender.clone();
         let registry = Arc::clone(&self.registry);
         let loaded_tools = Arc::clone(&self.loaded_tools);
+        let trust_store = Arc::clone(&self.trust_store);
+        let loaders = self.loaders.clone();
         let config = self.config.clone();
 
         tokio::spawn(async move {
-            Self::process_events(rx, event_sender, registry, loaded_tools, config).await;
+            Self::process_events(rx, event_sender, registry, loaded_tools, trust_store, loaders, config).await;
         });
 
         // Load existing tools
@@ -210,38 +292,15 @@ impl ToolWatcher {
 
     /// Load a tool from a file.
     async fn load_tool_file(&self, path: &Path) -> IndubitablyResult<()> {
-        // For now, this is a placeholder implementation
-        // In a real implementation, you would:
-        // 1. Parse the file (Rust source, TOML config, etc.)
-        // 2. Extract tool definitions
-        // 3. Register them with the registry
-        
-        let tool_name = path.file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        // Create a placeholder tool
-        let tool = Tool::new(
-            &tool_name,
-            &format!("Tool loaded from {:?}", path),
-            Arc::new(|_| Ok(serde_json::Value::String("placeholder".to_string()))),
-        );
-
-        self.registry.register(tool).await?;
-        
-        // Record the loaded tool
-        let mut loaded_tools = self.loaded_tools.write().await;
-        loaded_tools.insert(path.to_path_buf(), tool_name);
-
-        Ok(())
+        let bytes = Self::read_verified(&self.trust_store, path).await?;
+        Self::load_tool_file_static(&self.registry, &self.loaded_tools, &self.loaders, path, &bytes).await
     }
 
     /// Unload a tool from a file.
     async fn unload_tool_file(&self, path: &Path) -> IndubitablyResult<()> {
         let mut loaded_tools = self.loaded_tools.write().await;
         
-        if let Some(tool_name) = loaded_tools.remove(path) {
+        for tool_name in loaded_tools.remove(path).unwrap_or_default() {
             self.registry.unregister(&tool_name).await?;
         }
 
@@ -253,46 +312,60 @@ impl ToolWatcher {
         rx: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
         event_sender: mpsc::Sender<ToolWatcherEvent>,
         registry: Arc<ToolRegistry>,
-        loaded_tools: Arc<RwLock<HashMap<PathBuf, String>>>,
+        loaded_tools: Arc<RwLock<HashMap<PathBuf, Vec<String>>>>,
+        trust_store: Arc<TrustStore>,
+        loaders: ToolFileLoaders,
         config: ToolWatcherConfig,
     ) {
         for res in rx {
             match res {
                 Ok(event) => {
                     for kind in event.kinds {
-                        match kind {
-                            notify::EventKind::Create(_) => {
-                                for path in &event.paths {
-                                    if Self::should_watch_file_static(&config, path) {
-                                        if let Err(e) = Self::load_tool_file_static(&registry, &loaded_tools, path).await {
-                                            let _ = event_sender.send(ToolWatcherEvent::Error(e.to_string())).await;
-                                        } else {
-                                            let _ = event_sender.send(ToolWatcherEvent::ToolCreated(path.clone())).await;
-                                        }
-                                    }
+                        for path in &event.paths {
+                            // A changed or removed signature re-verifies the tool file it signs
+                            if let Some(tool_path) = Self::signed_tool_path(path) {
+                                if Self::should_watch_file_static(&config, &tool_path) && tool_path.exists() {
+                                    let event = Self::refresh_tool_file(
+                                        &registry,
+                                        &loaded_tools,
+                                        &trust_store,
+                                        &loaders,
+                                        &tool_path,
+                                    )
+                                    .await
+                                    .unwrap_or(ToolWatcherEvent::ToolModified(tool_path));
+                                    let _ = event_sender.send(event).await;
                                 }
+                                continue;
                             }
-                            notify::EventKind::Modify(_) => {
-                                for path in &event.paths {
+                            match kind {
+                                notify::EventKind::Create(_) | notify::EventKind::Modify(_) => {
                                     if Self::should_watch_file_static(&config, path) {
-                                        if let Err(e) = Self::reload_tool_file_static(&registry, &loaded_tools, path).await {
-                                            let _ = event_sender.send(ToolWatcherEvent::Error(e.to_string())).await;
-                                        } else {
-                                            let _ = event_sender.send(ToolWatcherEvent::ToolModified(path.clone())).await;
-                                        }
+                                        let changed = match kind {
+                                            notify::EventKind::Create(_) => ToolWatcherEvent::ToolCreated(path.clone()),
+                                            _ => ToolWatcherEvent::ToolModified(path.clone()),
+                                        };
+                                        let event = Self::refresh_tool_file(
+                                            &registry,
+                                            &loaded_tools,
+                                            &trust_store,
+                                            &loaders,
+                                            path,
+                                        )
+                                        .await
+                                        .unwrap_or(changed);
+                                        let _ = event_sender.send(event).await;
                                     }
                                 }
-                            }
-                            notify::EventKind::Remove(_) => {
-                                for path in &event.paths {
+                                notify::EventKind::Remove(_) => {
                                     if let Err(e) = Self::unload_tool_file_static(&registry, &loaded_tools, path).await {
                                         let _ = event_sender.send(ToolWatcherEvent::Error(e.to_string())).await;
                                     } else {
                                         let _ = event_sender.send(ToolWatcherEvent::ToolDeleted(path.clone())).await;
                                     }
                                 }
+                                _ => {}
                             }
-                            _ => {}
                         }
                     }
                 }
@@ -303,6 +376,49 @@ impl ToolWatcher {
         }
     }
 
+    /// Verify a tool file and load its tools in place of the previous ones.
+    ///
+    /// Returns the failure event, or `None` when the file was loaded. A file
+    /// that no longer verifies has its tools unloaded.
+    async fn refresh_tool_file(
+        registry: &ToolRegistry,
+        loaded_tools: &Arc<RwLock<HashMap<PathBuf, Vec<String>>>>,
+        trust_store: &TrustStore,
+        loaders: &ToolFileLoaders,
+        path: &Path,
+    ) -> Option<ToolWatcherEvent> {
+        let bytes = match Self::read_verified(trust_store, path).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let _ = Self::unload_tool_file_static(registry, loaded_tools, path).await;
+                return Some(ToolWatcherEvent::VerificationFailed(path.to_path_buf(), e.to_string()));
+            }
+        };
+        match Self::reload_tool_file_static(registry, loaded_tools, loaders, path, &bytes).await {
+            Ok(()) => None,
+            Err(e) => Some(ToolWatcherEvent::Error(e.to_string())),
+        }
+    }
+
+    /// Get the tool file a detached `<file>.sig` signature belongs to.
+    fn signed_tool_path(path: &Path) -> Option<PathBuf> {
+        (path.extension()? == "sig").then(|| path.with_extension(""))
+    }
+
+    /// Read a tool file and verify it against its detached `<file>.sig` signature.
+    ///
+    /// The returned bytes are the ones verified, so the file cannot be swapped
+    /// between the check and loading.
+    async fn read_verified(trust_store: &TrustStore, path: &Path) -> IndubitablyResult<Vec<u8>> {
+        let bytes = std::fs::read(path)?;
+        let mut signature_path = path.as_os_str().to_owned();
+        signature_path.push(".sig");
+        let signature = std::fs::read(PathBuf::from(signature_path)).ok();
+        let artifact = format!("tool:{}", path.display());
+        trust_store.verify(&artifact, &bytes, signature.as_deref(), None).await?;
+        Ok(bytes)
+    }
+
     /// Static version of should_watch_file for use in async context.
     fn should_watch_file_static(config: &ToolWatcherConfig, path: &Path) -> bool {
         if let Some(extension) = path.extension() {
@@ -316,24 +432,19 @@ impl ToolWatcher {
     /// Static version of load_tool_file for use in async context.
     async fn load_tool_file_static(
         registry: &ToolRegistry,
-        loaded_tools: &Arc<RwLock<HashMap<PathBuf, String>>>,
+        loaded_tools: &Arc<RwLock<HashMap<PathBuf, Vec<String>>>>,
+        loaders: &ToolFileLoaders,
         path: &Path,
+        bytes: &[u8],
     ) -> IndubitablyResult<()> {
-        let tool_name = path.file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let tool = Tool::new(
-            &tool_name,
-            &format!("Tool loaded from {:?}", path),
-            Arc::new(|_| Ok(serde_json::Value::String("placeholder".to_string()))),
-        );
-
-        registry.register(tool).await?;
+        let tools = loaders.load(path, bytes)?;
+        let tool_names = tools.iter().map(|tool| tool.name.clone()).collect();
+        for tool in tools {
+            registry.register(tool).await?;
+        }
         
         let mut loaded_tools = loaded_tools.write().await;
-        loaded_tools.insert(path.to_path_buf(), tool_name);
+        loaded_tools.insert(path.to_path_buf(), tool_names);
 
         Ok(())
     }
@@ -341,25 +452,27 @@ impl ToolWatcher {
     /// Static version of reload_tool_file for use in async context.
     async fn reload_tool_file_static(
         registry: &ToolRegistry,
-        loaded_tools: &Arc<RwLock<HashMap<PathBuf, String>>>,
+        loaded_tools: &Arc<RwLock<HashMap<PathBuf, Vec<String>>>>,
+        loaders: &ToolFileLoaders,
         path: &Path,
+        bytes: &[u8],
     ) -> IndubitablyResult<()> {
         // First unload the existing tool
         Self::unload_tool_file_static(registry, loaded_tools, path).await?;
         
         // Then load the new version
-        Self::load_tool_file_static(registry, loaded_tools, path).await
+        Self::load_tool_file_static(registry, loaded_tools, loaders, path, bytes).await
     }
 
     /// Static version of unload_tool_file for use in async context.
     async fn unload_tool_file_static(
         registry: &ToolRegistry,
-        loaded_tools: &Arc<RwLock<HashMap<PathBuf, String>>>,
+        loaded_tools: &Arc<RwLock<HashMap<PathBuf, Vec<String>>>>,
         path: &Path,
     ) -> IndubitablyResult<()> {
         let mut loaded_tools = loaded_tools.write().await;
         
-        if let Some(tool_name) = loaded_tools.remove(path) {
+        for tool_name in loaded_tools.remove(path).unwrap_or_default() {
             registry.unregister(&tool_name).await?;
         }
 
@@ -406,6 +519,13 @@ mod tests {
         assert!(watcher.is_ok());
     }
 
+    #[test]
+    fn test_signed_tool_path() {
+        let signature = Path::new("tools/echo.wasm.sig");
+        assert_eq!(ToolWatcher::signed_tool_path(signature), Some(PathBuf::from("tools/echo.wasm")));
+        assert_eq!(ToolWatcher::signed_tool_path(Path::new("tools/echo.wasm")), None);
+    }
+
     #[test]
     fn test_should_watch_file() {
         let config = ToolWatcherConfig::new()
@@ -0,0 +1,340 @@
+//! Per-run working directories for the SDK.
+//! 
+//! This module provides `Workspace`, a scratch directory scoped to one agent
+//! run. It is created under the system temp directory or a configured root,
+//! exposed to tools through the execution context and
+//! `current_working_directory`, and removed afterwards according to its
+//! cleanup policy. Files the run created or changed are returned in the
+//! `AgentResult` as `WorkspaceFile` artifacts with their media types.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{IndubitablyError, IndubitablyResult, ToolError};
+
+tokio::task_local! {
+    static WORKING_DIRECTORY: PathBuf;
+}
+
+/// Get the working directory of the run executing the current tool, if it has one.
+pub fn current_working_directory() -> Option<PathBuf> {
+    WORKING_DIRECTORY.try_with(Clone::clone).ok()
+}
+
+/// Run a tool function with the given working directory visible to it.
+pub(crate) fn with_working_directory<R>(directory: Option<&Path>, f: impl FnOnce() -> R) -> R {
+    match directory {
+        Some(directory) => WORKING_DIRECTORY.sync_scope(directory.to_path_buf(), f),
+        None => f(),
+    }
+}
+
+/// Await a tool future with the given working directory visible to it.
+pub(crate) async fn in_working_directory<F: std::future::Future>(directory: Option<&Path>, future: F) -> F::Output {
+    match directory {
+        Some(directory) => WORKING_DIRECTORY.scope(directory.to_path_buf(), future).await,
+        None => future.await,
+    }
+}
+
+/// When a workspace is removed at the end of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceCleanup {
+    /// Remove the workspace after every run.
+    Always,
+    /// Remove the workspace after successful runs and keep it for inspection after failures.
+    OnSuccess,
+    /// Keep the workspace.
+    Never,
+}
+
+/// Where per-run workspaces are created and when they are removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// The directory workspaces are created in; the system temp directory when unset.
+    pub root: Option<PathBuf>,
+    /// When workspaces are removed.
+    pub cleanup: WorkspaceCleanup,
+    /// Whether files the run created or changed are returned as artifacts.
+    pub collect_artifacts: bool,
+}
+
+impl WorkspaceConfig {
+    /// Create workspaces in the system temp directory, removed after every run.
+    pub fn temp() -> Self {
+        Self {
+            root: None,
+            cleanup: WorkspaceCleanup::Always,
+            collect_artifacts: true,
+        }
+    }
+
+    /// Create workspaces under the given root, kept after the run.
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: Some(root.into()),
+            cleanup: WorkspaceCleanup::Never,
+            collect_artifacts: true,
+        }
+    }
+
+    /// Set when workspaces are removed.
+    pub fn with_cleanup(mut self, cleanup: WorkspaceCleanup) -> Self {
+        self.cleanup = cleanup;
+        self
+    }
+
+    /// Set whether files the run created or changed are returned as artifacts.
+    pub fn with_collect_artifacts(mut self, collect_artifacts: bool) -> Self {
+        self.collect_artifacts = collect_artifacts;
+        self
+    }
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        Self::temp()
+    }
+}
+
+/// A file in a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceFile {
+    /// The path relative to the workspace, with `/` separators.
+    pub name: String,
+    /// The absolute path of the file.
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size_bytes: u64,
+    /// The media type guessed from the file extension.
+    pub media_type: String,
+    /// The file contents, captured when the workspace was removed at the end of the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<u8>>,
+}
+
+/// A working directory scoped to one agent run.
+#[derive(Debug)]
+pub struct Workspace {
+    path: PathBuf,
+    config: WorkspaceConfig,
+    baseline: HashMap<PathBuf, (u64, Option<SystemTime>)>,
+    finished: bool,
+}
+
+fn workspace_error(path: &Path, error: impl std::fmt::Display) -> IndubitablyError {
+    IndubitablyError::ToolError(ToolError::ExecutionFailed(format!(
+        "Workspace {} failed: {}",
+        path.display(),
+        error
+    )))
+}
+
+impl Workspace {
+    /// Create a workspace for a run.
+    pub fn create(config: &WorkspaceConfig, run_id: &str) -> IndubitablyResult<Self> {
+        let root = config.root.clone().unwrap_or_else(std::env::temp_dir);
+        let path = root.join(format!("indubitably-run-{}", run_id));
+        fs::create_dir_all(&path).map_err(|e| workspace_error(&path, e))?;
+        let mut workspace = Self {
+            path,
+            config: config.clone(),
+            baseline: HashMap::new(),
+            finished: false,
+        };
+        workspace.baseline = workspace
+            .list()?
+            .into_iter()
+            .map(|file| {
+                let modified = fs::metadata(&file.path).and_then(|metadata| metadata.modified()).ok();
+                (file.path, (file.size_bytes, modified))
+            })
+            .collect();
+        tracing::debug!("path=<{}> | created run workspace", workspace.path.display());
+        Ok(workspace)
+    }
+
+    /// Get the workspace directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resolve a relative path inside the workspace, rejecting paths that escape it.
+    pub fn resolve(&self, relative: &str) -> IndubitablyResult<PathBuf> {
+        let relative = Path::new(relative);
+        let escapes = relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(IndubitablyError::ValidationError(format!(
+                "Path '{}' is outside the workspace",
+                relative.display()
+            )));
+        }
+        Ok(self.path.join(relative))
+    }
+
+    /// List the files in the workspace, sorted by name.
+    pub fn list(&self) -> IndubitablyResult<Vec<WorkspaceFile>> {
+        let mut files = Vec::new();
+        let mut pending = vec![self.path.clone()];
+        while let Some(directory) = pending.pop() {
+            for entry in fs::read_dir(&directory).map_err(|e| workspace_error(&self.path, e))? {
+                let entry = entry.map_err(|e| workspace_error(&self.path, e))?;
+                let metadata = entry.metadata().map_err(|e| workspace_error(&self.path, e))?;
+                let path = entry.path();
+                if metadata.is_dir() {
+                    pending.push(path);
+                } else if metadata.is_file() {
+                    files.push(self.file(path, metadata.len()));
+                }
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    /// List the files created or changed since the workspace was created.
+    pub fn artifacts(&self) -> IndubitablyResult<Vec<WorkspaceFile>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|file| {
+                let modified = fs::metadata(&file.path).and_then(|metadata| metadata.modified()).ok();
+                self.baseline.get(&file.path) != Some(&(file.size_bytes, modified))
+            })
+            .collect())
+    }
+
+    fn file(&self, path: PathBuf, size_bytes: u64) -> WorkspaceFile {
+        let name = path
+            .strip_prefix(&self.path)
+            .unwrap_or(&path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        WorkspaceFile {
+            media_type: media_type_for(&path).to_string(),
+            name,
+            path,
+            size_bytes,
+            content: None,
+        }
+    }
+
+    /// Finish the run, returning its artifacts and applying the cleanup policy.
+    ///
+    /// Artifacts of a removed workspace carry their contents, since their paths no longer exist.
+    pub fn finish(mut self, succeeded: bool) -> Vec<WorkspaceFile> {
+        self.finished = true;
+        let mut artifacts = if self.config.collect_artifacts {
+            self.artifacts().unwrap_or_else(|e| {
+                tracing::warn!("path=<{}>, error=<{}> | failed to collect workspace artifacts", self.path.display(), e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        if self.should_remove(succeeded) {
+            for artifact in &mut artifacts {
+                artifact.content = fs::read(&artifact.path).ok();
+            }
+            self.remove();
+        }
+        artifacts
+    }
+
+    fn should_remove(&self, succeeded: bool) -> bool {
+        match self.config.cleanup {
+            WorkspaceCleanup::Always => true,
+            WorkspaceCleanup::OnSuccess => succeeded,
+            WorkspaceCleanup::Never => false,
+        }
+    }
+
+    fn remove(&self) {
+        match fs::remove_dir_all(&self.path) {
+            Ok(()) => tracing::debug!("path=<{}> | removed run workspace", self.path.display()),
+            Err(e) => tracing::warn!("path=<{}>, error=<{}> | failed to remove run workspace", self.path.display(), e),
+        }
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        // A workspace dropped without `finish` belongs to a run that failed
+        if !self.finished && self.should_remove(false) {
+            self.remove();
+        }
+    }
+}
+
+/// Guess the media type of a file from its extension.
+pub fn media_type_for(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_artifacts_and_cleanup() {
+        let root = tempfile::tempdir().unwrap();
+        let config = WorkspaceConfig::at(root.path()).with_cleanup(WorkspaceCleanup::OnSuccess);
+        let first = Workspace::create(&config, "run-1").unwrap();
+        fs::write(first.path().join("existing.txt"), "before").unwrap();
+        drop(first);
+        let workspace = Workspace::create(&config, "run-1").unwrap();
+
+        fs::create_dir(workspace.path().join("out")).unwrap();
+        fs::write(workspace.resolve("out/chart.png").unwrap(), [0x89, b'P', b'N', b'G']).unwrap();
+        assert!(workspace.resolve("../escape.txt").is_err());
+        let names: Vec<String> = workspace.list().unwrap().into_iter().map(|file| file.name).collect();
+        assert_eq!(names, vec!["existing.txt", "out/chart.png"]);
+
+        let path = workspace.path().to_path_buf();
+        let artifacts = workspace.finish(true);
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].media_type, "image/png");
+        assert_eq!(artifacts[0].content.as_deref(), Some(&[0x89, b'P', b'N', b'G'][..]));
+        assert!(!path.exists());
+
+        let failed = Workspace::create(&config, "run-2").unwrap();
+        let path = failed.path().to_path_buf();
+        drop(failed);
+        assert!(path.exists());
+    }
+}
@@ -0,0 +1,176 @@
+//! Feedback HTTP route for the SDK.
+//! 
+//! This module provides `FeedbackRoute`, a framework-agnostic handler for
+//! `/sessions/{session_id}/feedback`. `POST` records a rating, comment or
+//! correction on a message of the session and publishes a
+//! `FeedbackReceived` event; `GET` lists the feedback recorded so far.
+//! Servers mount it by converting their requests to `HttpRequest`.
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::agent::experiments::EXPERIMENTS_METADATA_KEY;
+use crate::models::{HttpRequest, HttpResponse};
+use crate::session::SessionManager;
+use crate::telemetry::events::{EventBus, LifecycleEvent, LifecycleEventKind};
+use crate::types::{Feedback, FeedbackRating, IndubitablyError, SessionError};
+
+/// The path the route serves, with `{session_id}` as the placeholder.
+pub const FEEDBACK_ROUTE_PATH: &str = "/sessions/{session_id}/feedback";
+
+/// The JSON body of a feedback `POST`.
+#[derive(Debug, Clone, Deserialize)]
+struct FeedbackBody {
+    message_id: String,
+    rating: FeedbackRating,
+    #[serde(default)]
+    comment: Option<String>,
+    #[serde(default)]
+    correction: Option<String>,
+}
+
+/// Handles feedback requests against a session manager.
+pub struct FeedbackRoute<M: SessionManager> {
+    sessions: Arc<Mutex<M>>,
+    events: Option<EventBus>,
+}
+
+impl<M: SessionManager> FeedbackRoute<M> {
+    /// Create a route storing feedback through the given session manager.
+    pub fn new(sessions: Arc<Mutex<M>>) -> Self {
+        Self { sessions, events: None }
+    }
+
+    /// Publish a `FeedbackReceived` event to the bus for each recorded rating.
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Handle a request, returning a JSON response.
+    pub async fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        let Some(session_id) = session_id_from_url(&request.url) else {
+            return json_response(404, json!({"error": "not found"}));
+        };
+        match request.method.to_ascii_uppercase().as_str() {
+            "POST" => self.post(session_id, &request.body).await,
+            "GET" => match self.sessions.lock().await.list_feedback(session_id).await {
+                Ok(feedback) => json_response(200, json!({"feedback": feedback})),
+                Err(e) => error_response(e),
+            },
+            _ => json_response(405, json!({"error": "method not allowed"})).with_header("allow", "GET, POST"),
+        }
+    }
+
+    async fn post(&self, session_id: &str, body: &[u8]) -> HttpResponse {
+        let body: FeedbackBody = match serde_json::from_slice(body) {
+            Ok(body) => body,
+            Err(e) => return json_response(400, json!({"error": format!("Invalid feedback body: {}", e)})),
+        };
+        let mut feedback = Feedback::new(session_id, &body.message_id, body.rating);
+        feedback.comment = body.comment;
+        feedback.correction = body.correction;
+
+        let mut sessions = self.sessions.lock().await;
+        let feedback = match sessions.record_feedback(feedback).await {
+            Ok(feedback) => feedback,
+            Err(e) => return error_response(e),
+        };
+        if let Some(ref events) = self.events {
+            let mut data = json!({
+                "session_id": feedback.session_id,
+                "message_id": feedback.message_id,
+                "rating": feedback.rating.as_str(),
+                "has_comment": feedback.comment.is_some(),
+                "has_correction": feedback.correction.is_some(),
+            });
+            // Ratings of a session in an experiment are attributed to its variants
+            let labels = sessions.get_session(session_id).await.ok().flatten().and_then(|session| {
+                session.metadata?.get(EXPERIMENTS_METADATA_KEY).cloned()
+            });
+            drop(sessions);
+            if let Some(labels) = labels {
+                data[EXPERIMENTS_METADATA_KEY] = labels;
+            }
+            events.publish(LifecycleEvent::new(LifecycleEventKind::FeedbackReceived, "feedback", data)).await;
+        }
+        json_response(201, json!(feedback))
+    }
+}
+
+/// Extract the session ID from a URL or path matching `FEEDBACK_ROUTE_PATH`.
+fn session_id_from_url(url: &str) -> Option<&str> {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => &rest[rest.find('/')?..],
+        None => url,
+    };
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let session_id = path.strip_prefix("/sessions/")?.strip_suffix("/feedback")?;
+    (!session_id.is_empty() && !session_id.contains('/')).then_some(session_id)
+}
+
+fn json_response(status: u16, body: serde_json::Value) -> HttpResponse {
+    HttpResponse::new(status, body.to_string().into_bytes()).with_header("content-type", "application/json")
+}
+
+fn error_response(error: IndubitablyError) -> HttpResponse {
+    let status = match error {
+        IndubitablyError::SessionError(SessionError::SessionNotFound(_)) => 404,
+        IndubitablyError::ValidationError(_) => 422,
+        _ => 500,
+    };
+    json_response(status, json!({"error": error.to_string()}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::experiments::ExperimentOutcomes;
+    use crate::session::FileSessionManager;
+    use crate::telemetry::events::{MetricsSubscriber, METRIC_FEEDBACK_THUMBS_UP_RATE};
+    use crate::types::{Session, SessionAgent, SessionMessage, SessionType};
+
+    #[tokio::test]
+    async fn test_feedback_route() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = FileSessionManager::new(dir.path().to_str().unwrap());
+        let mut session = Session::new("s1", SessionType::Conversation, SessionAgent::new("a", "Agent"));
+        session.add_message(SessionMessage::new("m1", "user", "Hi"));
+        session.add_message(SessionMessage::new("m2", "assistant", "Hello!"));
+        session.add_metadata(EXPERIMENTS_METADATA_KEY, json!({"prompt_v2": "concise"}));
+        manager.create_session(session).await.unwrap();
+
+        let events = EventBus::new();
+        let metrics = MetricsSubscriber::new();
+        let outcomes = ExperimentOutcomes::new();
+        events.subscribe(Arc::new(metrics.clone()));
+        events.subscribe(Arc::new(outcomes.clone()));
+        let route = FeedbackRoute::new(Arc::new(Mutex::new(manager))).with_events(events);
+
+        let post = |body: serde_json::Value| {
+            HttpRequest::post("https://api.example.com/sessions/s1/feedback").with_body(body.to_string().into_bytes())
+        };
+        let response = route.handle(&post(json!({"message_id": "m2", "rating": "thumbs_up", "comment": "great"}))).await;
+        assert_eq!(response.status, 201);
+        let response = route.handle(&post(json!({"message_id": "m2", "rating": "thumbs_down", "correction": "Hi there!"}))).await;
+        assert_eq!(response.status, 201);
+        assert_eq!(route.handle(&post(json!({"message_id": "missing", "rating": "thumbs_up"}))).await.status, 422);
+        assert_eq!(route.handle(&post(json!({"rating": "meh"}))).await.status, 400);
+        assert_eq!(route.handle(&HttpRequest::get("/sessions/other/feedback")).await.status, 404);
+        assert_eq!(route.handle(&HttpRequest::new("DELETE", "/sessions/s1/feedback")).await.status, 405);
+
+        let listed = route.handle(&HttpRequest::get("/sessions/s1/feedback?limit=10")).await;
+        let body: serde_json::Value = listed.json().unwrap();
+        assert_eq!(body["feedback"].as_array().unwrap().len(), 2);
+        assert_eq!(body["feedback"][1]["correction"], "Hi there!");
+
+        let stored = route.sessions.lock().await.get_session("s1").await.unwrap().unwrap();
+        assert_eq!(stored.rating(), Some(FeedbackRating::ThumbsDown));
+        assert_eq!(metrics.snapshot().get(METRIC_FEEDBACK_THUMBS_UP_RATE), Some(0.5));
+        let concise = outcomes.variant("prompt_v2", "concise").unwrap();
+        assert_eq!((concise.thumbs_up, concise.thumbs_down), (1, 1));
+    }
+}
@@ -0,0 +1,17 @@
+//! Transport adapters for the SDK.
+//! 
+//! This module provides adapters for delivering agent stream events to
+//! front-ends over common wire protocols, buffering for clients that resume
+//! after a dropped connection, and routes front-ends call back
+//! into, such as recording feedback.
+
+pub mod sse;
+pub mod feedback;
+pub mod resume;
+
+pub use sse::{SseEncoder, WireEvent};
+pub use feedback::{FeedbackRoute, FEEDBACK_ROUTE_PATH};
+pub use resume::{
+    parse_last_event_id, ResumedStream, SequencedEvent, StreamHub, WebSocketFrame, WebSocketResume,
+    DEFAULT_REPLAY_CAPACITY, LAST_EVENT_ID_HEADER,
+};
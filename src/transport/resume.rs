@@ -0,0 +1,315 @@
+//! Resumable event streams for the SDK.
+//! 
+//! This module provides `StreamHub`, which numbers each run's stream events
+//! with increasing ids and keeps the last N of them. A client that loses its
+//! connection reconnects with the id of the last event it received, such as
+//! the SSE `Last-Event-ID` header or a `WebSocketResume` message, and gets
+//! the events it missed followed by the live tail of the run.
+
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+
+use super::sse::{SseEncoder, WireEvent};
+use crate::types::{IndubitablyResult, StreamEvent, StreamingError};
+
+/// The default number of events kept per run for clients that reconnect.
+pub const DEFAULT_REPLAY_CAPACITY: usize = 256;
+
+/// The default number of runs whose events are kept.
+pub const DEFAULT_MAX_RUNS: usize = 1024;
+
+/// The header an SSE client sends with the id of the last event it received.
+pub const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
+/// A wire event with its position in the run's stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequencedEvent {
+    /// The id of the event, increasing by one within a run.
+    pub id: u64,
+    /// The event payload.
+    pub event: WireEvent,
+}
+
+/// The WebSocket text message carrying an event; the same shape as `SequencedEvent`.
+pub type WebSocketFrame = SequencedEvent;
+
+impl SequencedEvent {
+    /// Encode the event as an SSE frame.
+    pub fn to_sse(&self) -> String {
+        SseEncoder::frame(self.id, &self.event)
+    }
+
+    /// Encode the event as a WebSocket text message.
+    pub fn to_websocket(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// The message a WebSocket client sends when it connects or reconnects to a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSocketResume {
+    /// The run to stream.
+    pub run_id: String,
+    /// The id of the last event the client received, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_event_id: Option<u64>,
+}
+
+/// A stream of a run's events, replayed and then live.
+pub type ResumedStream = Pin<Box<dyn Stream<Item = SequencedEvent> + Send>>;
+
+/// Parse the value of a `Last-Event-ID` header.
+pub fn parse_last_event_id(value: &str) -> Option<u64> {
+    value.trim().parse().ok()
+}
+
+/// The buffered events and live subscribers of one run.
+struct RunBuffer {
+    events: VecDeque<SequencedEvent>,
+    next_id: u64,
+    finished: bool,
+    subscribers: Vec<UnboundedSender<SequencedEvent>>,
+}
+
+#[derive(Default)]
+struct HubState {
+    runs: HashMap<String, RunBuffer>,
+    /// Run ids in creation order, for evicting the oldest.
+    order: VecDeque<String>,
+}
+
+/// Buffers the recent events of each run so that clients can resume after a disconnect.
+///
+/// Clones share the same buffers.
+#[derive(Clone)]
+pub struct StreamHub {
+    state: Arc<Mutex<HubState>>,
+    capacity: usize,
+    max_runs: usize,
+}
+
+impl std::fmt::Debug for StreamHub {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamHub")
+            .field("capacity", &self.capacity)
+            .field("max_runs", &self.max_runs)
+            .field("runs", &self.lock().runs.len())
+            .finish()
+    }
+}
+
+impl Default for StreamHub {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(HubState::default())),
+            capacity: DEFAULT_REPLAY_CAPACITY,
+            max_runs: DEFAULT_MAX_RUNS,
+        }
+    }
+}
+
+impl StreamHub {
+    /// Create a hub keeping the default number of events per run.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of events kept per run.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Set the number of runs kept; the oldest finished runs are dropped first.
+    pub fn with_max_runs(mut self, max_runs: usize) -> Self {
+        self.max_runs = max_runs.max(1);
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HubState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add an event to a run's stream, returning its id.
+    pub fn publish(&self, run_id: &str, event: &StreamEvent) -> u64 {
+        let mut state = self.lock();
+        if !state.runs.contains_key(run_id) {
+            state.order.push_back(run_id.to_string());
+            self.evict(&mut state);
+        }
+        let run = state.runs.entry(run_id.to_string()).or_insert_with(|| RunBuffer {
+            events: VecDeque::new(),
+            next_id: 0,
+            finished: false,
+            subscribers: Vec::new(),
+        });
+        let event = SequencedEvent {
+            id: run.next_id,
+            event: WireEvent::from_event(event),
+        };
+        run.next_id += 1;
+        run.subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        if run.events.len() == self.capacity {
+            run.events.pop_front();
+        }
+        run.events.push_back(event.clone());
+        event.id
+    }
+
+    /// Drop the oldest runs beyond the limit, preferring finished ones.
+    fn evict(&self, state: &mut HubState) {
+        while state.order.len() > self.max_runs {
+            let position = state
+                .order
+                .iter()
+                .position(|run_id| state.runs.get(run_id).is_none_or(|run| run.finished))
+                .unwrap_or(0);
+            if let Some(run_id) = state.order.remove(position) {
+                state.runs.remove(&run_id);
+            }
+        }
+    }
+
+    /// Mark a run as finished, ending its live streams once they have delivered every event.
+    pub fn finish(&self, run_id: &str) {
+        if let Some(run) = self.lock().runs.get_mut(run_id) {
+            run.finished = true;
+            run.subscribers.clear();
+        }
+    }
+
+    /// Forget a run and its buffered events.
+    pub fn remove(&self, run_id: &str) {
+        let mut state = self.lock();
+        state.runs.remove(run_id);
+        state.order.retain(|id| id != run_id);
+    }
+
+    /// Publish every event of a stream to a run, finishing it when the stream ends.
+    ///
+    /// Errors in the source stream are published as `error` events.
+    pub fn attach<S>(&self, run_id: &str, stream: S) -> JoinHandle<()>
+    where
+        S: Stream<Item = IndubitablyResult<StreamEvent>> + Send + 'static,
+    {
+        let hub = self.clone();
+        let run_id = run_id.to_string();
+        tokio::spawn(async move {
+            tokio::pin!(stream);
+            while let Some(event) = stream.next().await {
+                match event {
+                    Ok(event) => hub.publish(&run_id, &event),
+                    Err(e) => hub.publish(&run_id, &StreamEvent::error(&e.to_string())),
+                };
+            }
+            hub.finish(&run_id);
+        })
+    }
+
+    /// Stream a run's events after the given id, then its live events until it finishes.
+    ///
+    /// Without an id, every buffered event is replayed. Fails if the run is
+    /// unknown, or if events after the id are no longer buffered, in which
+    /// case the client should start over rather than show a partial answer.
+    pub fn resume(&self, run_id: &str, last_event_id: Option<u64>) -> IndubitablyResult<ResumedStream> {
+        let mut state = self.lock();
+        let run = state
+            .runs
+            .get_mut(run_id)
+            .ok_or_else(|| StreamingError::StreamInterrupted(format!("No buffered stream for run '{}'", run_id)))?;
+        let first_missed = last_event_id.map_or(0, |id| id.saturating_add(1));
+        let oldest = run.events.front().map_or(run.next_id, |event| event.id);
+        if first_missed < oldest {
+            return Err(StreamingError::BufferOverflow(format!(
+                "Events of run '{}' before {} are no longer buffered",
+                run_id, oldest
+            ))
+            .into());
+        }
+
+        let (sender, receiver) = unbounded_channel();
+        for event in run.events.iter().filter(|event| event.id >= first_missed) {
+            let _ = sender.send(event.clone());
+        }
+        if !run.finished {
+            run.subscribers.push(sender);
+        }
+        tracing::debug!(
+            "run_id=<{}>, last_event_id=<{:?}>, next_id=<{}> | resuming stream",
+            run_id,
+            last_event_id,
+            run.next_id
+        );
+        Ok(Box::pin(UnboundedReceiverStream::new(receiver)))
+    }
+
+    /// Resume a run's stream as SSE frames, given the client's `Last-Event-ID` header.
+    pub fn resume_sse(
+        &self,
+        run_id: &str,
+        last_event_id: Option<&str>,
+    ) -> IndubitablyResult<Pin<Box<dyn Stream<Item = String> + Send>>> {
+        let stream = self.resume(run_id, last_event_id.and_then(parse_last_event_id))?;
+        Ok(Box::pin(stream.map(|event| event.to_sse())))
+    }
+
+    /// Resume a run's stream as WebSocket text messages, given the client's resume message.
+    pub fn resume_websocket(
+        &self,
+        resume: &WebSocketResume,
+    ) -> IndubitablyResult<Pin<Box<dyn Stream<Item = String> + Send>>> {
+        let stream = self.resume(&resume.run_id, resume.last_event_id)?;
+        Ok(Box::pin(stream.map(|event| event.to_websocket())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{IndubitablyError, StreamContent};
+
+    fn delta(text: &str) -> StreamEvent {
+        StreamEvent::content_block_delta(vec![StreamContent::text(text)])
+    }
+
+    #[tokio::test]
+    async fn test_resume_replays_missed_tail_then_live_events() {
+        let hub = StreamHub::new().with_capacity(3);
+        for text in ["a", "b", "c", "d"] {
+            hub.publish("run-1", &delta(text));
+        }
+
+        // The client saw event 1 before its connection dropped
+        let mut stream = hub.resume_sse("run-1", Some("1")).unwrap();
+        hub.publish("run-1", &delta("e"));
+        hub.finish("run-1");
+        let mut frames = Vec::new();
+        while let Some(frame) = stream.next().await {
+            frames.push(frame);
+        }
+        let ids: Vec<&str> = frames.iter().map(|frame| frame.lines().next().unwrap()).collect();
+        assert_eq!(ids, vec!["id: 2", "id: 3", "id: 4"]);
+        assert!(frames[2].contains("\"text\":\"e\""));
+
+        // Event 1 was evicted, so resuming from 0 would leave a gap
+        assert!(matches!(
+            hub.resume("run-1", Some(0)),
+            Err(IndubitablyError::StreamingError(StreamingError::BufferOverflow(_)))
+        ));
+        assert!(hub.resume("run-2", None).is_err());
+
+        let resume: WebSocketResume = serde_json::from_str(r#"{"run_id": "run-1", "last_event_id": 3}"#).unwrap();
+        let messages: Vec<String> = hub.resume_websocket(&resume).unwrap().collect().await;
+        let frame: WebSocketFrame = serde_json::from_str(&messages[0]).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(frame.id, 4);
+        assert_eq!(frame.event.text.as_deref(), Some("e"));
+    }
+}
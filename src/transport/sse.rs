@@ -0,0 +1,234 @@
+//! Server-sent events adapter for the SDK.
+//! 
+//! This module encodes `StreamEvent`s as server-sent events carrying a
+//! compact JSON payload (`WireEvent`). The payload uses short keys and omits
+//! empty fields so front-ends can render text deltas and tool activity
+//! chips with minimal parsing; `wire_schema` describes it as JSON Schema.
+//! Tool completions carry the UI hint of the tool's result renderer as `ui`.
+
+use std::pin::Pin;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::types::{IndubitablyResult, StreamEvent, StreamEventPool, StreamEventType};
+
+/// The compact JSON payload of a server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WireEvent {
+    /// The event type, matching the SSE event name.
+    pub t: String,
+    /// The stable identifier of the tool call or plan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The tool name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The text delta.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// A short summary of a tool outcome.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// The number of tool calls in a plan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    /// Whether the event reports a failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub err: Option<bool>,
+    /// The error message of an error event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msg: Option<String>,
+    /// A hint for rendering a tool output, such as a table or a diff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ui: Option<Value>,
+}
+
+impl WireEvent {
+    /// Build the wire payload for a stream event.
+    pub fn from_event(event: &StreamEvent) -> Self {
+        let mut wire = WireEvent {
+            t: event_name(&event.event_type),
+            ..Default::default()
+        };
+
+        if let Some(ref marker) = event.tool_marker {
+            wire.id = Some(marker.id.clone());
+            wire.name = marker.name.clone();
+            wire.summary = marker.summary.clone();
+            wire.count = marker.tool_count;
+            wire.err = marker.is_error;
+            wire.ui = marker.ui_hint.clone();
+        }
+
+        if let Some(ref tool_use) = event.tool_use {
+            wire.id.get_or_insert_with(|| tool_use.tool_use_id.clone());
+            wire.name.get_or_insert_with(|| tool_use.name.clone());
+        }
+
+        if let Some(ref content) = event.content {
+            let text: String = content.iter().filter_map(|item| item.text.as_deref()).collect();
+            if !text.is_empty() {
+                wire.text = Some(text);
+            }
+        }
+
+        if matches!(event.event_type, StreamEventType::Error) {
+            wire.err = Some(true);
+            wire.msg = event
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("error"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+        }
+
+        wire
+    }
+}
+
+/// Get the SSE event name for an event type.
+fn event_name(event_type: &StreamEventType) -> String {
+    serde_json::to_value(event_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// An encoder that turns stream events into server-sent event frames.
+///
+/// Each frame carries an increasing `id` so clients can resume with the
+/// `Last-Event-ID` header.
+#[derive(Debug, Default)]
+pub struct SseEncoder {
+    next_id: u64,
+}
+
+impl SseEncoder {
+    /// Create a new encoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encode an event as an SSE frame.
+    pub fn encode(&mut self, event: &StreamEvent) -> String {
+        let frame = Self::frame(self.next_id, &WireEvent::from_event(event));
+        self.next_id += 1;
+        frame
+    }
+
+    /// Encode a wire event as an SSE frame with the given id.
+    pub fn frame(id: u64, wire: &WireEvent) -> String {
+        let data = serde_json::to_string(wire).unwrap_or_else(|_| "{}".to_string());
+        format!("id: {}\nevent: {}\ndata: {}\n\n", id, wire.t, data)
+    }
+
+    /// Encode a stream of events as a stream of SSE frames.
+    ///
+    /// Errors in the source stream are encoded as `error` events. Encoded
+    /// events are returned to the shared `StreamEventPool`.
+    pub fn encode_stream<S>(stream: S) -> Pin<Box<dyn Stream<Item = String> + Send>>
+    where
+        S: Stream<Item = IndubitablyResult<StreamEvent>> + Send + 'static,
+    {
+        let mut encoder = SseEncoder::new();
+        Box::pin(stream.map(move |event| match event {
+            Ok(event) => {
+                let frame = encoder.encode(&event);
+                StreamEventPool::shared().recycle(event);
+                frame
+            }
+            Err(e) => encoder.encode(&StreamEvent::error(&e.to_string())),
+        }))
+    }
+}
+
+/// Get the JSON Schema describing the `WireEvent` payload.
+pub fn wire_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "WireEvent",
+        "type": "object",
+        "required": ["t"],
+        "properties": {
+            "t": {
+                "type": "string",
+                "description": "The event type, matching the SSE event name",
+                "enum": [
+                    "messageStart", "contentBlockStart", "contentBlockDelta", "contentBlockStop",
+                    "toolUseStart", "toolUseDelta", "toolUseStop",
+                    "toolResultStart", "toolResultDelta", "toolResultStop",
+                    "messageDelta", "messageStop",
+                    "toolPlanStarted", "toolExecuting", "toolCompleted", "error"
+                ]
+            },
+            "id": {"type": "string", "description": "Stable identifier of the tool call or plan"},
+            "name": {"type": "string", "description": "Tool name"},
+            "text": {"type": "string", "description": "Text delta"},
+            "summary": {"type": "string", "description": "Short summary of a tool outcome"},
+            "count": {"type": "integer", "minimum": 0, "description": "Number of tool calls in a plan"},
+            "err": {"type": "boolean", "description": "Whether the event reports a failure"},
+            "msg": {"type": "string", "description": "Error message"},
+            "ui": {
+                "type": "object",
+                "description": "Hint for rendering a tool output, such as a table, image links or a diff",
+                "required": ["kind"],
+                "properties": {"kind": {"type": "string"}}
+            }
+        },
+        "additionalProperties": false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{IndubitablyError, StreamContent};
+
+    #[test]
+    fn test_encode_tool_markers() {
+        let mut encoder = SseEncoder::new();
+
+        let frame = encoder.encode(&StreamEvent::tool_executing("call-1", "calculator"));
+        assert_eq!(
+            frame,
+            "id: 0\nevent: toolExecuting\ndata: {\"t\":\"toolExecuting\",\"id\":\"call-1\",\"name\":\"calculator\"}\n\n"
+        );
+
+        let frame = encoder.encode(&StreamEvent::tool_completed("call-1", "calculator", "42", false));
+        assert!(frame.starts_with("id: 1\nevent: toolCompleted\n"));
+        assert!(frame.contains("\"summary\":\"42\",\"err\":false"));
+
+        let hint = json!({"kind": "table", "columns": ["n"], "rows": [[42]]});
+        let event = StreamEvent::tool_completed("call-2", "sql", "1 row", false).with_ui_hint(hint.clone());
+        assert_eq!(WireEvent::from_event(&event).ui, Some(hint));
+    }
+
+    #[test]
+    fn test_wire_event_text_and_errors() {
+        let delta = WireEvent::from_event(&StreamEvent::content_block_delta(vec![StreamContent::text("Hi")]));
+        assert_eq!(delta.t, "contentBlockDelta");
+        assert_eq!(delta.text.as_deref(), Some("Hi"));
+
+        let error = WireEvent::from_event(&StreamEvent::error("boom"));
+        assert_eq!(error.err, Some(true));
+        assert_eq!(error.msg.as_deref(), Some("boom"));
+
+        let names = wire_schema()["properties"]["t"]["enum"].as_array().unwrap().clone();
+        assert!(names.contains(&json!(delta.t)));
+        assert!(names.contains(&json!(error.t)));
+    }
+
+    #[tokio::test]
+    async fn test_encode_stream() {
+        let events: Vec<IndubitablyResult<StreamEvent>> = vec![
+            Ok(StreamEvent::tool_plan_started("plan-1", 2)),
+            Err(IndubitablyError::NetworkError("reset".to_string())),
+        ];
+        let frames: Vec<String> = SseEncoder::encode_stream(tokio_stream::iter(events)).collect().await;
+
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].contains("\"count\":2"));
+        assert!(frames[1].starts_with("id: 1\nevent: error\n"));
+    }
+}
@@ -0,0 +1,122 @@
+//! Citation type definitions for the SDK.
+//! 
+//! This module defines the types used to attach sources to generated
+//! content. Citations are populated by retrieval and web tools and by
+//! providers that return grounding information, so front-ends can render
+//! footnotes without parsing free text.
+
+use serde::{Deserialize, Serialize};
+
+/// A byte range within a piece of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CitationSpan {
+    /// The start offset, inclusive.
+    pub start: usize,
+    /// The end offset, exclusive.
+    pub end: usize,
+}
+
+impl CitationSpan {
+    /// Create a new span.
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+/// A source supporting part of a response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// The identifier of the source, such as a document or chunk id.
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    /// The location of the source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// The title of the source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The span of the response text supported by the source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<CitationSpan>,
+    /// The quoted text from the source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+impl Citation {
+    /// Create a new citation for the given source.
+    pub fn new(source_id: &str) -> Self {
+        Self {
+            source_id: source_id.to_string(),
+            uri: None,
+            title: None,
+            span: None,
+            snippet: None,
+        }
+    }
+
+    /// Set the source location.
+    pub fn with_uri(mut self, uri: &str) -> Self {
+        self.uri = Some(uri.to_string());
+        self
+    }
+
+    /// Set the source title.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Set the span of the response text supported by the source.
+    pub fn with_span(mut self, start: usize, end: usize) -> Self {
+        self.span = Some(CitationSpan::new(start, end));
+        self
+    }
+
+    /// Set the quoted text from the source.
+    pub fn with_snippet(mut self, snippet: &str) -> Self {
+        self.snippet = Some(snippet.to_string());
+        self
+    }
+
+    /// Extract citations from a tool output carrying a `citations` array.
+    ///
+    /// Entries that do not parse as citations are skipped.
+    pub fn from_tool_output(output: &serde_json::Value) -> Vec<Citation> {
+        output
+            .get("citations")
+            .and_then(serde_json::Value::as_array)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_citations_from_tool_output() {
+        let output = json!({
+            "results": ["..."],
+            "citations": [
+                {"sourceId": "doc-1", "uri": "https://example.com/a", "span": {"start": 0, "end": 12}},
+                {"uri": "missing source id"},
+            ]
+        });
+
+        let citations = Citation::from_tool_output(&output);
+        assert_eq!(citations.len(), 1);
+        assert_eq!(
+            citations[0],
+            Citation::new("doc-1").with_uri("https://example.com/a").with_span(0, 12)
+        );
+        assert!(Citation::from_tool_output(&json!("plain text")).is_empty());
+    }
+}
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::tools::{ToolResult, ToolUse};
+use super::citations::Citation;
 use super::media::{DocumentContent, ImageContent, VideoContent};
 
 /// Text content to be evaluated by guardrails.
@@ -56,6 +57,19 @@ pub struct ReasoningContentBlock {
     pub redacted_content: Vec<u8>,
 }
 
+impl ReasoningContentBlock {
+    /// Create a reasoning block from plain reasoning text.
+    pub fn new(text: &str) -> Self {
+        Self {
+            reasoning_text: ReasoningTextBlock {
+                signature: None,
+                text: text.to_string(),
+            },
+            redacted_content: Vec::new(),
+        }
+    }
+}
+
 /// A cache point configuration for optimizing conversation history.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CachePoint {
@@ -93,6 +107,9 @@ pub struct ContentBlock {
     /// Video to include in the message.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub video: Option<VideoContent>,
+    /// The sources supporting the text of this block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub citations: Option<Vec<Citation>>,
 }
 
 /// Contains configurations for instructions to provide the model for how to handle input.
@@ -127,6 +144,18 @@ pub enum MessageRole {
 /// A collection of messages.
 pub type Messages = Vec<Message>;
 
+/// The metadata key holding a message's identifier.
+pub const MESSAGE_ID_KEY: &str = "message_id";
+
+/// The metadata key holding the RFC 3339 time after which a message ages out.
+pub const MESSAGE_EXPIRES_AT_KEY: &str = "expires_at";
+
+/// The metadata key holding the RFC 3339 time a message's content was removed when it aged out.
+pub const MESSAGE_EXPIRED_AT_KEY: &str = "expired_at";
+
+/// The text replacing the content of a message that aged out.
+pub const EXPIRED_CONTENT_PLACEHOLDER: &str = "[This content expired and was removed]";
+
 impl Message {
     /// Create a new message with the given role and content.
     pub fn new(role: MessageRole, content: Vec<ContentBlock>) -> Self {
@@ -170,6 +199,132 @@ impl Message {
         )
     }
 
+    /// Create a new assistant message with text and tool use requests.
+    pub fn assistant_with_tool_uses(text: &str, tool_uses: Vec<ToolUse>) -> Self {
+        let mut content = Vec::new();
+        if !text.is_empty() {
+            content.push(ContentBlock {
+                text: Some(text.to_string()),
+                ..Default::default()
+            });
+        }
+        content.extend(tool_uses.into_iter().map(|tool_use| ContentBlock {
+            tool_use: Some(tool_use),
+            ..Default::default()
+        }));
+        Self::new(MessageRole::Assistant, content)
+    }
+
+    /// Create a new user message carrying tool results.
+    pub fn tool_results(results: Vec<ToolResult>) -> Self {
+        Self::new(
+            MessageRole::User,
+            results
+                .into_iter()
+                .map(|result| ContentBlock {
+                    tool_result: Some(result),
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the tool use requests in the message.
+    pub fn tool_uses(&self) -> Vec<&ToolUse> {
+        self.content.iter().filter_map(|block| block.tool_use.as_ref()).collect()
+    }
+
+    /// Get the tool results in the message.
+    pub fn tool_result_blocks(&self) -> Vec<&ToolResult> {
+        self.content.iter().filter_map(|block| block.tool_result.as_ref()).collect()
+    }
+
+    /// Set the message identifier.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.metadata
+            .get_or_insert_with(HashMap::new)
+            .insert(MESSAGE_ID_KEY.to_string(), serde_json::Value::String(id.to_string()));
+        self
+    }
+
+    /// Get the message identifier, if one was assigned.
+    pub fn id(&self) -> Option<&str> {
+        self.metadata.as_ref()?.get(MESSAGE_ID_KEY)?.as_str()
+    }
+
+    /// Age the message out of the conversation once the given time has passed after now.
+    pub fn with_ttl(self, ttl: std::time::Duration) -> Self {
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let now = chrono::Utc::now();
+        self.with_expires_at(now.checked_add_signed(ttl).unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC))
+    }
+
+    /// Age the message out of the conversation at the given time.
+    pub fn with_expires_at(mut self, expires_at: chrono::DateTime<chrono::Utc>) -> Self {
+        self.metadata
+            .get_or_insert_with(HashMap::new)
+            .insert(MESSAGE_EXPIRES_AT_KEY.to_string(), serde_json::Value::String(expires_at.to_rfc3339()));
+        self
+    }
+
+    /// Get the time the message ages out, if it has a TTL.
+    pub fn expires_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let expires_at = self.metadata.as_ref()?.get(MESSAGE_EXPIRES_AT_KEY)?.as_str()?;
+        chrono::DateTime::parse_from_rfc3339(expires_at).ok().map(|time| time.with_timezone(&chrono::Utc))
+    }
+
+    /// Check whether the message has aged out at the given time.
+    pub fn is_expired_at(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expires_at().is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Check if this is a user message typed by the user rather than one carrying tool results.
+    pub fn is_user_turn(&self) -> bool {
+        self.role == MessageRole::User && self.content.iter().all(|block| block.tool_result.is_none())
+    }
+
+    /// Attach citations to the first text block of the message.
+    pub fn with_citations(mut self, citations: Vec<Citation>) -> Self {
+        if citations.is_empty() {
+            return self;
+        }
+        match self.content.iter_mut().find(|block| block.text.is_some()) {
+            Some(block) => block.citations.get_or_insert_with(Vec::new).extend(citations),
+            None => self.content.push(ContentBlock {
+                citations: Some(citations),
+                ..Default::default()
+            }),
+        }
+        self
+    }
+
+    /// Put reasoning blocks ahead of the message content.
+    pub fn with_reasoning(mut self, reasoning: Vec<ReasoningContentBlock>) -> Self {
+        let blocks = reasoning.into_iter().map(|reasoning| ContentBlock {
+            reasoning_content: Some(reasoning),
+            ..Default::default()
+        });
+        self.content.splice(0..0, blocks);
+        self
+    }
+
+    /// Get the reasoning blocks of the message.
+    pub fn reasoning(&self) -> Vec<&ReasoningContentBlock> {
+        self.content
+            .iter()
+            .filter_map(|block| block.reasoning_content.as_ref())
+            .collect()
+    }
+
+    /// Get all citations attached to the message.
+    pub fn citations(&self) -> Vec<&Citation> {
+        self.content
+            .iter()
+            .filter_map(|block| block.citations.as_ref())
+            .flatten()
+            .collect()
+    }
+
     /// Get the text content from the message.
     pub fn text(&self) -> Option<&str> {
         self.content
@@ -188,6 +343,32 @@ impl Message {
     }
 }
 
+impl ContentBlock {
+    /// Create a text block.
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            text: Some(text.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Create an image block.
+    pub fn from_image(image: ImageContent) -> Self {
+        Self {
+            image: Some(image),
+            ..Default::default()
+        }
+    }
+
+    /// Create a document block.
+    pub fn from_document(document: DocumentContent) -> Self {
+        Self {
+            document: Some(document),
+            ..Default::default()
+        }
+    }
+}
+
 impl Default for ContentBlock {
     fn default() -> Self {
         Self {
@@ -200,6 +381,7 @@ impl Default for ContentBlock {
             tool_result: None,
             tool_use: None,
             video: None,
+            citations: None,
         }
     }
 }
@@ -0,0 +1,309 @@
+//! Conversation diff and merge utilities for the SDK.
+//! 
+//! This module compares conversations turn by turn. `MessagesDiff::diff`
+//! produces a structured diff of added, removed and edited turns, and
+//! `merge_conversations` performs a three-way merge of two branches forked
+//! from a common ancestor, reporting the spans both branches changed
+//! differently as conflicts. Turns are compared by role and content;
+//! metadata such as message IDs is ignored.
+
+use serde::{Deserialize, Serialize};
+
+use super::content::{Message, Messages};
+
+/// One change between two conversations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TurnChange {
+    /// A turn present only in the new conversation.
+    Added {
+        /// The index of the turn in the new conversation.
+        index: usize,
+        /// The added turn.
+        message: Message,
+    },
+    /// A turn present only in the old conversation.
+    Removed {
+        /// The index of the turn in the old conversation.
+        index: usize,
+        /// The removed turn.
+        message: Message,
+    },
+    /// A turn whose content changed while its role stayed the same.
+    Edited {
+        /// The index of the turn in the old conversation.
+        old_index: usize,
+        /// The index of the turn in the new conversation.
+        new_index: usize,
+        /// The turn before the edit.
+        before: Message,
+        /// The turn after the edit.
+        after: Message,
+    },
+}
+
+/// The structured difference between two conversations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationDiff {
+    /// The changes, in conversation order.
+    pub changes: Vec<TurnChange>,
+    /// The number of turns present unchanged in both conversations.
+    pub unchanged: usize,
+}
+
+impl ConversationDiff {
+    /// Check whether the conversations are the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Get the number of added turns.
+    pub fn added(&self) -> usize {
+        self.count(|change| matches!(change, TurnChange::Added { .. }))
+    }
+
+    /// Get the number of removed turns.
+    pub fn removed(&self) -> usize {
+        self.count(|change| matches!(change, TurnChange::Removed { .. }))
+    }
+
+    /// Get the number of edited turns.
+    pub fn edited(&self) -> usize {
+        self.count(|change| matches!(change, TurnChange::Edited { .. }))
+    }
+
+    fn count(&self, predicate: impl Fn(&TurnChange) -> bool) -> usize {
+        self.changes.iter().filter(|change| predicate(change)).count()
+    }
+
+    /// Render the diff as text, one line per changed turn, for test failure output.
+    pub fn render(&self) -> String {
+        let line = |message: &Message| format!("{}: {}", role_name(message), message.all_text());
+        self.changes
+            .iter()
+            .map(|change| match change {
+                TurnChange::Added { index, message } => format!("+ [{}] {}", index, line(message)),
+                TurnChange::Removed { index, message } => format!("- [{}] {}", index, line(message)),
+                TurnChange::Edited { old_index, new_index, before, after } => {
+                    format!("~ [{}->{}] {} => {}", old_index, new_index, line(before), after.all_text())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Diffing for conversations.
+pub trait MessagesDiff {
+    /// Compute the changes that turn this conversation into `other`.
+    fn diff(&self, other: &[Message]) -> ConversationDiff;
+}
+
+impl MessagesDiff for [Message] {
+    fn diff(&self, other: &[Message]) -> ConversationDiff {
+        let mut diff = ConversationDiff::default();
+        let (mut old_index, mut new_index) = (0, 0);
+        for (old_end, new_end) in matching_turns(self, other).into_iter().chain([(self.len(), other.len())]) {
+            push_hunk(&mut diff, self, other, old_index..old_end, new_index..new_end);
+            if old_end < self.len() {
+                diff.unchanged += 1;
+            }
+            (old_index, new_index) = (old_end + 1, new_end + 1);
+        }
+        diff
+    }
+}
+
+/// A span both branches changed differently.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeConflict {
+    /// The index in the merged conversation where branch A's version starts.
+    pub index: usize,
+    /// The turns of the common ancestor.
+    pub base: Messages,
+    /// The turns of branch A, which the merged conversation keeps.
+    pub ours: Messages,
+    /// The turns of branch B.
+    pub theirs: Messages,
+}
+
+/// The result of a three-way merge.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationMerge {
+    /// The merged conversation, keeping branch A's turns where the branches conflict.
+    pub messages: Messages,
+    /// The spans both branches changed differently.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl ConversationMerge {
+    /// Check whether the merge had no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merge two branches forked from a common ancestor.
+///
+/// Spans changed on only one branch take that branch's turns. Spans both
+/// branches changed identically are taken once; spans they changed
+/// differently keep branch A's turns and are reported as conflicts.
+pub fn merge_conversations(base: &[Message], ours: &[Message], theirs: &[Message]) -> ConversationMerge {
+    let ours_matches = matching_turns(base, ours);
+    let theirs_matches = matching_turns(base, theirs);
+    let ours_of = |index: usize| ours_matches.iter().find(|(b, _)| *b == index).map(|(_, o)| *o);
+    let theirs_of = |index: usize| theirs_matches.iter().find(|(b, _)| *b == index).map(|(_, t)| *t);
+
+    // Base turns kept by both branches anchor the merge; changes happen between them.
+    let mut anchors: Vec<(usize, usize, usize)> = (0..base.len())
+        .filter_map(|index| Some((index, ours_of(index)?, theirs_of(index)?)))
+        .collect();
+    anchors.push((base.len(), ours.len(), theirs.len()));
+
+    let mut merge = ConversationMerge::default();
+    let (mut base_start, mut ours_start, mut theirs_start) = (0, 0, 0);
+    for (base_end, ours_end, theirs_end) in anchors {
+        let base_span = &base[base_start..base_end];
+        let ours_span = &ours[ours_start..ours_end];
+        let theirs_span = &theirs[theirs_start..theirs_end];
+
+        if same_turns(ours_span, base_span) {
+            merge.messages.extend_from_slice(theirs_span);
+        } else if same_turns(theirs_span, base_span) || same_turns(ours_span, theirs_span) {
+            merge.messages.extend_from_slice(ours_span);
+        } else {
+            merge.conflicts.push(MergeConflict {
+                index: merge.messages.len(),
+                base: base_span.to_vec(),
+                ours: ours_span.to_vec(),
+                theirs: theirs_span.to_vec(),
+            });
+            merge.messages.extend_from_slice(ours_span);
+        }
+
+        if base_end < base.len() {
+            merge.messages.push(ours[ours_end].clone());
+        }
+        (base_start, ours_start, theirs_start) = (base_end + 1, ours_end + 1, theirs_end + 1);
+    }
+    merge
+}
+
+/// Check whether two turns have the same role and content.
+fn same_turn(a: &Message, b: &Message) -> bool {
+    a.role == b.role && a.content == b.content
+}
+
+fn same_turns(a: &[Message], b: &[Message]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_turn(a, b))
+}
+
+/// Find the index pairs of a longest common subsequence of turns.
+fn matching_turns(old: &[Message], new: &[Message]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if same_turn(&old[i], &new[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::with_capacity(lengths[0][0]);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if same_turn(&old[i], &new[j]) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Record the changes between two unmatched spans, pairing turns of the same role as edits.
+fn push_hunk(
+    diff: &mut ConversationDiff,
+    old: &[Message],
+    new: &[Message],
+    old_span: std::ops::Range<usize>,
+    new_span: std::ops::Range<usize>,
+) {
+    let mut new_indices = new_span.peekable();
+    for old_index in old_span {
+        match new_indices.next_if(|&new_index| old[old_index].role == new[new_index].role) {
+            Some(new_index) => diff.changes.push(TurnChange::Edited {
+                old_index,
+                new_index,
+                before: old[old_index].clone(),
+                after: new[new_index].clone(),
+            }),
+            None => diff.changes.push(TurnChange::Removed {
+                index: old_index,
+                message: old[old_index].clone(),
+            }),
+        }
+    }
+    for index in new_indices {
+        diff.changes.push(TurnChange::Added {
+            index,
+            message: new[index].clone(),
+        });
+    }
+}
+
+fn role_name(message: &Message) -> &'static str {
+    match message.role {
+        super::content::MessageRole::User => "user",
+        super::content::MessageRole::Assistant => "assistant",
+        super::content::MessageRole::System => "system",
+        super::content::MessageRole::Tool => "tool",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_and_merge_conversations() {
+        let base = vec![Message::user("Hi"), Message::assistant("Hello!")];
+        let edited = vec![
+            Message::user("Hi").with_id("m1"),
+            Message::assistant("Hello there!"),
+            Message::user("Weather?"),
+            Message::assistant("Sunny."),
+        ];
+
+        let diff = base.diff(&edited);
+        assert_eq!((diff.unchanged, diff.edited(), diff.added(), diff.removed()), (1, 1, 2, 0));
+        assert_eq!(
+            diff.render(),
+            "~ [1->1] assistant: Hello! => Hello there!\n+ [2] user: Weather?\n+ [3] assistant: Sunny."
+        );
+        assert!(edited.diff(&edited).is_empty());
+        assert_eq!(edited[..2].diff(&base[..1]).removed(), 1);
+
+        // Branch A continues the conversation; branch B rewrites the greeting.
+        let ours = vec![Message::user("Hi"), Message::assistant("Hello!"), Message::user("Bye")];
+        let theirs = vec![Message::user("Hey"), Message::assistant("Hello!")];
+        let merged = merge_conversations(&base, &ours, &theirs);
+        assert!(merged.is_clean());
+        assert_eq!(merged.messages, vec![Message::user("Hey"), Message::assistant("Hello!"), Message::user("Bye")]);
+
+        // Both branches continue differently from the same point.
+        let theirs = vec![Message::user("Hi"), Message::assistant("Hello!"), Message::user("Thanks")];
+        let merged = merge_conversations(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].index, 2);
+        assert_eq!(merged.conflicts[0].theirs, vec![Message::user("Thanks")]);
+        assert_eq!(merged.messages, ours);
+    }
+}
@@ -64,6 +64,14 @@ pub enum IndubitablyError {
     #[error("MCP error: {0}")]
     McpError(#[from] McpError),
 
+    /// An error occurred while loading a skill package.
+    #[error("Skill error: {0}")]
+    SkillError(#[from] SkillError),
+
+    /// An error occurred while parsing a document.
+    #[error("Document error: {0}")]
+    DocumentError(#[from] DocumentError),
+
     /// A validation error occurred.
     #[error("Validation error: {0}")]
     ValidationError(String),
@@ -72,6 +80,10 @@ pub enum IndubitablyError {
     #[error("Configuration error: {0}")]
     ConfigurationError(String),
 
+    /// An agent configuration failed validation.
+    #[error("Invalid agent configuration: {0}")]
+    InvalidConfiguration(crate::agent::ConfigReport),
+
     /// An authentication error occurred.
     #[error("Authentication error: {0}")]
     AuthenticationError(String),
@@ -84,6 +96,10 @@ pub enum IndubitablyError {
     #[error("Timeout error: {0}")]
     TimeoutError(String),
 
+    /// A configured memory limit was exceeded.
+    #[error("Memory limit exceeded: {0}")]
+    MemoryLimitExceeded(String),
+
     /// An internal error occurred.
     #[error("Internal error: {0}")]
     InternalError(String),
@@ -147,6 +163,10 @@ pub enum ToolError {
     /// The tool timed out.
     #[error("Tool timeout: {0}")]
     Timeout(String),
+
+    /// The requested artifact was not found.
+    #[error("Artifact not found: {0}")]
+    ArtifactNotFound(String),
 }
 
 /// Errors that can occur during session management.
@@ -171,6 +191,10 @@ pub enum SessionError {
     /// The session storage failed.
     #[error("Session storage failed: {0}")]
     StorageFailed(String),
+
+    /// Encrypting or decrypting session content failed.
+    #[error("Session encryption failed: {0}")]
+    EncryptionFailed(String),
 }
 
 /// Errors that can occur during streaming.
@@ -211,6 +235,10 @@ pub enum EventLoopError {
     /// The event loop exceeded maximum iterations.
     #[error("Maximum iterations exceeded: {0}")]
     MaxIterationsExceeded(String),
+
+    /// The liveness watchdog aborted a run that exceeded its hard ceiling.
+    #[error("Run aborted by watchdog: {0}")]
+    WatchdogTimeout(String),
 }
 
 /// Errors that can occur during conversation management.
@@ -231,6 +259,10 @@ pub enum ConversationError {
     /// The conversation summarization failed.
     #[error("Summarization failed: {0}")]
     SummarizationFailed(String),
+
+    /// The requested message was not found.
+    #[error("Message not found: {0}")]
+    MessageNotFound(String),
 }
 
 /// Errors that can occur during telemetry operations.
@@ -247,6 +279,10 @@ pub enum TelemetryError {
     /// The telemetry configuration is invalid.
     #[error("Invalid telemetry configuration: {0}")]
     InvalidConfiguration(String),
+
+    /// Exporting telemetry to an external system failed.
+    #[error("Telemetry export failed: {0}")]
+    ExportFailed(String),
 }
 
 /// Errors that can occur during hook execution.
@@ -285,6 +321,54 @@ pub enum McpError {
     ConnectionFailed(String),
 }
 
+/// Errors that can occur while loading skill packages.
+#[derive(Error, Debug)]
+pub enum SkillError {
+    /// The skill manifest is missing or invalid.
+    #[error("Invalid skill manifest: {0}")]
+    InvalidManifest(String),
+
+    /// The skill signature is missing or does not verify.
+    #[error("Signature verification failed: {0}")]
+    SignatureInvalid(String),
+
+    /// A packaged file does not match its recorded digest.
+    #[error("Integrity check failed: {0}")]
+    IntegrityMismatch(String),
+
+    /// The skill version does not satisfy the pinned requirement.
+    #[error("Version mismatch: {0}")]
+    VersionMismatch(String),
+
+    /// The skill uses a feature this loader cannot provide.
+    #[error("Unsupported skill feature: {0}")]
+    Unsupported(String),
+}
+
+/// Errors that can occur while parsing documents.
+#[derive(Error, Debug)]
+pub enum DocumentError {
+    /// The document format is not supported.
+    #[error("Unsupported document format: {0}")]
+    UnsupportedFormat(String),
+
+    /// The document is damaged or does not follow its format.
+    #[error("Malformed document: {0}")]
+    Malformed(String),
+
+    /// The document is encrypted.
+    #[error("Encrypted document: {0}")]
+    Encrypted(String),
+
+    /// Optical character recognition failed.
+    #[error("OCR failed: {0}")]
+    OcrFailed(String),
+
+    /// Speech transcription failed.
+    #[error("Transcription failed: {0}")]
+    TranscriptionFailed(String),
+}
+
 impl From<String> for IndubitablyError {
     fn from(err: String) -> Self {
         IndubitablyError::InternalError(err)
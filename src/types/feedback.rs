@@ -0,0 +1,129 @@
+//! Feedback type definitions for the SDK.
+//! 
+//! This module defines the ratings, comments and corrections users attach
+//! to agent responses. Feedback is stored in the session metadata, so it
+//! travels with the session through every session manager and can be read
+//! back for evaluation and dataset export.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::session::Session;
+
+/// The session metadata key holding the feedback records.
+pub const FEEDBACK_METADATA_KEY: &str = "feedback";
+
+/// The session metadata key holding the rating of the most recent feedback.
+pub const RATING_METADATA_KEY: &str = "rating";
+
+/// A user's rating of an agent response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackRating {
+    /// The response was good.
+    ThumbsUp,
+    /// The response was bad.
+    ThumbsDown,
+}
+
+impl FeedbackRating {
+    /// Get the rating name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackRating::ThumbsUp => "thumbs_up",
+            FeedbackRating::ThumbsDown => "thumbs_down",
+        }
+    }
+
+    /// Check whether the rating is positive.
+    pub fn is_positive(&self) -> bool {
+        *self == FeedbackRating::ThumbsUp
+    }
+}
+
+/// Feedback on one message of a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feedback {
+    /// The unique identifier for the feedback.
+    pub id: String,
+    /// The session the rated message belongs to.
+    pub session_id: String,
+    /// The rated message.
+    pub message_id: String,
+    /// The rating.
+    pub rating: FeedbackRating,
+    /// A free-form comment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The response the user expected instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correction: Option<String>,
+    /// When the feedback was given.
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Feedback {
+    /// Create new feedback on a message.
+    pub fn new(session_id: &str, message_id: &str, rating: FeedbackRating) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            message_id: message_id.to_string(),
+            rating,
+            comment: None,
+            correction: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Set the comment.
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Set the corrected response.
+    pub fn with_correction(mut self, correction: &str) -> Self {
+        self.correction = Some(correction.to_string());
+        self
+    }
+}
+
+impl Session {
+    /// Get the feedback recorded on this session, oldest first.
+    pub fn feedback(&self) -> Vec<Feedback> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(FEEDBACK_METADATA_KEY))
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record feedback on this session and make its rating the session's rating.
+    pub fn add_feedback(&mut self, feedback: Feedback) {
+        let rating = feedback.rating;
+        let mut records = self.feedback();
+        records.push(feedback);
+        self.add_metadata(FEEDBACK_METADATA_KEY, serde_json::json!(records));
+        self.add_metadata(RATING_METADATA_KEY, serde_json::json!(rating));
+        self.updated_at = Utc::now();
+    }
+
+    /// Get the rating of the most recent feedback.
+    pub fn rating(&self) -> Option<FeedbackRating> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(RATING_METADATA_KEY))
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+/// Get the share of feedback that is positive, or `None` if there is none.
+pub fn thumbs_up_rate(feedback: &[Feedback]) -> Option<f64> {
+    if feedback.is_empty() {
+        return None;
+    }
+    let positive = feedback.iter().filter(|record| record.rating.is_positive()).count();
+    Some(positive as f64 / feedback.len() as f64)
+}
@@ -1,10 +1,15 @@
 //! Media-related type definitions for the SDK.
 //! 
 //! This module defines the types used to represent different media types
-//! including documents, images, and videos.
+//! including documents, images, videos, and audio.
+
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
+use super::exceptions::{DocumentError, IndubitablyError, IndubitablyResult};
+use crate::crypto::base64;
+
 /// Document content to include in a message.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DocumentContent {
@@ -188,7 +193,189 @@ pub struct VideoData {
     pub file_path: Option<String>,
 }
 
+/// Audio content to transcribe for a message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioContent {
+    /// The type of audio.
+    #[serde(rename = "type")]
+    pub content_type: AudioType,
+    /// The source of the audio.
+    pub source: AudioSource,
+}
+
+/// The type of audio content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioType {
+    Audio,
+    Speech,
+    Music,
+    Recording,
+}
+
+/// The source of audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioSource {
+    /// The type of source.
+    #[serde(rename = "type")]
+    pub source_type: AudioSourceType,
+    /// The media type of the audio.
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
+    /// The data of the audio.
+    pub data: AudioData,
+}
+
+/// The type of audio source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioSourceType {
+    Base64,
+    S3,
+    Http,
+    File,
+}
+
+/// The data of audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioData {
+    /// The base64 encoded content of the audio.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base64: Option<String>,
+    /// The URL of the audio.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The file path of the audio.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+}
+
+/// Read a media file, naming the path in the error.
+fn read_media(path: &Path) -> IndubitablyResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| {
+        IndubitablyError::DocumentError(DocumentError::Malformed(format!("Cannot read {}: {}", path.display(), e)))
+    })
+}
+
+/// Read a media file base64 encoded, if it can be read.
+fn read_base64(path: &str) -> Option<String> {
+    std::fs::read(path).ok().map(|bytes| base64::encode(&bytes))
+}
+
+/// Detect the media type of image data from its signature.
+pub fn sniff_image_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Detect the media type of audio data from its signature.
+pub fn sniff_audio_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+        Some("audio/wav")
+    } else if bytes.starts_with(b"ID3") || (bytes.len() >= 2 && bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0) {
+        Some("audio/mpeg")
+    } else if bytes.starts_with(b"OggS") {
+        Some("audio/ogg")
+    } else if bytes.starts_with(b"fLaC") {
+        Some("audio/flac")
+    } else if bytes.len() >= 8 && &bytes[4..8] == b"ftyp" {
+        Some("audio/mp4")
+    } else if bytes.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some("audio/webm")
+    } else {
+        None
+    }
+}
+
 impl DocumentContent {
+    /// Create a document from a file, typed by its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        let path = path.as_ref();
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        Self::from_bytes(&read_media(path)?, &name)
+    }
+
+    /// Create a document from file data, typed by the extension of its file name.
+    ///
+    /// Text formats keep their text; other formats are base64 encoded.
+    pub fn from_bytes(bytes: &[u8], file_name: &str) -> IndubitablyResult<Self> {
+        let extension = Path::new(file_name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let (content_type, media_type) = match extension.as_str() {
+            "txt" => (DocumentType::Text, "text/plain"),
+            "md" | "markdown" => (DocumentType::Markdown, "text/markdown"),
+            "html" | "htm" => (DocumentType::Html, "text/html"),
+            "csv" => (DocumentType::Csv, "text/csv"),
+            "json" => (DocumentType::Json, "application/json"),
+            "xml" => (DocumentType::Xml, "application/xml"),
+            "pdf" => (DocumentType::Pdf, "application/pdf"),
+            "doc" => (DocumentType::Word, "application/msword"),
+            "docx" => (DocumentType::Word, "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+            "xls" => (DocumentType::Excel, "application/vnd.ms-excel"),
+            "xlsx" => (DocumentType::Excel, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            _ => {
+                return Err(IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(file_name.to_string())));
+            }
+        };
+        let text = media_type.starts_with("text/") || matches!(content_type, DocumentType::Json | DocumentType::Xml);
+        let data = if text {
+            let text = String::from_utf8(bytes.to_vec()).map_err(|_| {
+                IndubitablyError::DocumentError(DocumentError::Malformed(format!("{} is not UTF-8 text", file_name)))
+            })?;
+            DocumentData { text: Some(text), base64: None, url: None, file_path: None }
+        } else {
+            DocumentData { text: None, base64: Some(base64::encode(bytes)), url: None, file_path: None }
+        };
+        Ok(Self {
+            content_type,
+            source: DocumentSource {
+                source_type: DocumentSourceType::Base64,
+                media_type: media_type.to_string(),
+                data,
+            },
+        })
+    }
+
+    /// Get the short format name providers use, such as `pdf` or `docx`.
+    pub fn format(&self) -> &'static str {
+        match self.source.media_type.as_str() {
+            "application/pdf" => "pdf",
+            "text/csv" => "csv",
+            "text/html" => "html",
+            "text/markdown" => "md",
+            "application/msword" => "doc",
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => "docx",
+            "application/vnd.ms-excel" => "xls",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => "xlsx",
+            _ => "txt",
+        }
+    }
+
+    /// Get the document's text, if it is held as text.
+    pub fn text_data(&self) -> Option<&str> {
+        self.source.data.text.as_deref()
+    }
+
+    /// Get the document's data base64 encoded, reading a file source if needed.
+    pub fn base64_data(&self) -> Option<String> {
+        let data = &self.source.data;
+        data.base64
+            .clone()
+            .or_else(|| data.text.as_ref().map(|text| base64::encode(text.as_bytes())))
+            .or_else(|| data.file_path.as_deref().and_then(read_base64))
+    }
+
     /// Create a new text document.
     pub fn text(text: &str) -> Self {
         Self {
@@ -225,6 +412,39 @@ impl DocumentContent {
 }
 
 impl ImageContent {
+    /// Create an image from file data, detecting its media type.
+    pub fn from_bytes(bytes: &[u8]) -> IndubitablyResult<Self> {
+        let media_type = sniff_image_media_type(bytes).ok_or_else(|| {
+            IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(
+                "Image is not PNG, JPEG, GIF or WebP".to_string(),
+            ))
+        })?;
+        Ok(Self::base64(&base64::encode(bytes), media_type))
+    }
+
+    /// Create an image from a file, detecting its media type.
+    pub fn from_path(path: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        Self::from_bytes(&read_media(path.as_ref())?)
+    }
+
+    /// Get the short format name providers use, such as `png` or `jpeg`.
+    pub fn format(&self) -> &str {
+        self.source.media_type.strip_prefix("image/").unwrap_or(&self.source.media_type)
+    }
+
+    /// Get the image's data base64 encoded, reading a file source if needed.
+    pub fn base64_data(&self) -> Option<String> {
+        let data = &self.source.data;
+        data.base64
+            .clone()
+            .or_else(|| data.file_path.as_deref().and_then(read_base64))
+    }
+
+    /// Get the image's URL, if it is fetched by the provider.
+    pub fn url_data(&self) -> Option<&str> {
+        self.source.data.url.as_deref()
+    }
+
     /// Create a new image from base64 data.
     pub fn base64(base64: &str, media_type: &str) -> Self {
         Self {
@@ -291,3 +511,93 @@ impl VideoContent {
         }
     }
 }
+
+impl AudioContent {
+    /// Create audio from a file, typed by its signature or extension.
+    pub fn from_path(path: impl AsRef<Path>) -> IndubitablyResult<Self> {
+        let path = path.as_ref();
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        Self::from_bytes(&read_media(path)?, &name)
+    }
+
+    /// Create audio from file data, typed by its signature, then the extension of its file name.
+    pub fn from_bytes(bytes: &[u8], file_name: &str) -> IndubitablyResult<Self> {
+        let extension = Path::new(file_name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let by_extension = match extension.as_str() {
+            "wav" => Some("audio/wav"),
+            "mp3" | "mpga" | "mpeg" => Some("audio/mpeg"),
+            "ogg" | "oga" | "opus" => Some("audio/ogg"),
+            "flac" => Some("audio/flac"),
+            "m4a" | "mp4" => Some("audio/mp4"),
+            "webm" => Some("audio/webm"),
+            _ => None,
+        };
+        let media_type = sniff_audio_media_type(bytes).or(by_extension).ok_or_else(|| {
+            IndubitablyError::DocumentError(DocumentError::UnsupportedFormat(format!(
+                "{} is not WAV, MP3, Ogg, FLAC, M4A or WebM audio",
+                file_name
+            )))
+        })?;
+        Ok(Self::base64(&base64::encode(bytes), media_type))
+    }
+
+    /// Get the short format name transcription services use, such as `wav` or `mp3`.
+    pub fn format(&self) -> &'static str {
+        match self.source.media_type.as_str() {
+            "audio/mpeg" | "audio/mp3" => "mp3",
+            "audio/ogg" => "ogg",
+            "audio/flac" => "flac",
+            "audio/mp4" | "audio/m4a" => "m4a",
+            "audio/webm" => "webm",
+            _ => "wav",
+        }
+    }
+
+    /// Get the audio's data base64 encoded, reading a file source if needed.
+    pub fn base64_data(&self) -> Option<String> {
+        let data = &self.source.data;
+        data.base64
+            .clone()
+            .or_else(|| data.file_path.as_deref().and_then(read_base64))
+    }
+
+    /// Get the audio's URL, if it is held as one.
+    pub fn url_data(&self) -> Option<&str> {
+        self.source.data.url.as_deref()
+    }
+
+    /// Create new audio from base64 data.
+    pub fn base64(base64: &str, media_type: &str) -> Self {
+        Self {
+            content_type: AudioType::Audio,
+            source: AudioSource {
+                source_type: AudioSourceType::Base64,
+                media_type: media_type.to_string(),
+                data: AudioData {
+                    base64: Some(base64.to_string()),
+                    url: None,
+                    file_path: None,
+                },
+            },
+        }
+    }
+
+    /// Create new audio from a URL.
+    pub fn url(url: &str, media_type: &str) -> Self {
+        Self {
+            content_type: AudioType::Audio,
+            source: AudioSource {
+                source_type: AudioSourceType::Http,
+                media_type: media_type.to_string(),
+                data: AudioData {
+                    base64: None,
+                    url: Some(url.to_string()),
+                    file_path: None,
+                },
+            },
+        }
+    }
+}
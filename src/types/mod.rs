@@ -13,6 +13,9 @@ pub mod traces;
 pub mod collections;
 pub mod event_loop;
 pub mod session;
+pub mod citations;
+pub mod feedback;
+pub mod diff;
 
 pub use content::*;
 pub use tools::*;
@@ -24,6 +27,9 @@ pub use traces::*;
 pub use collections::*;
 pub use event_loop::*;
 pub use session::*;
+pub use citations::*;
+pub use feedback::*;
+pub use diff::*;
 
 // Re-export commonly used types
 pub use content::{Message, Messages, ContentBlock, SystemContentBlock};
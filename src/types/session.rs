@@ -7,7 +7,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 
-use super::content::Message;
+use super::content::{Message, MESSAGE_EXPIRES_AT_KEY};
+
+/// The session metadata key holding the id of the user the session belongs to.
+pub const USER_ID_METADATA_KEY: &str = "user_id";
 
 /// A session represents a conversation or interaction with an agent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +124,17 @@ impl Session {
             metadata.insert(key.to_string(), value);
         }
     }
+
+    /// Record the user the session belongs to.
+    pub fn with_user_id(mut self, user_id: &str) -> Self {
+        self.add_metadata(USER_ID_METADATA_KEY, serde_json::json!(user_id));
+        self
+    }
+
+    /// Get the user the session belongs to, if recorded.
+    pub fn user_id(&self) -> Option<&str> {
+        self.metadata.as_ref()?.get(USER_ID_METADATA_KEY)?.as_str()
+    }
 }
 
 impl SessionAgent {
@@ -183,10 +197,21 @@ impl SessionMessage {
             },
             content: message.all_text(),
             created_at: Utc::now(),
-            metadata: None,
+            // The expiry travels with the message so the retention janitor can find it
+            metadata: message
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get(MESSAGE_EXPIRES_AT_KEY))
+                .map(|expires_at| HashMap::from([(MESSAGE_EXPIRES_AT_KEY.to_string(), expires_at.clone())])),
         }
     }
 
+    /// Get the time the message ages out, if it has a TTL.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let expires_at = self.metadata.as_ref()?.get(MESSAGE_EXPIRES_AT_KEY)?.as_str()?;
+        DateTime::parse_from_rfc3339(expires_at).ok().map(|time| time.with_timezone(&Utc))
+    }
+
     /// Add metadata to the message.
     pub fn add_metadata(&mut self, key: &str, value: serde_json::Value) {
         if self.metadata.is_none() {
@@ -5,6 +5,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 use super::tools::ToolUse;
 
@@ -26,11 +28,36 @@ pub struct StreamEvent {
     /// The message delta information.
     #[serde(rename = "messageDelta", skip_serializing_if = "Option::is_none")]
     pub message_delta: Option<MessageDelta>,
+    /// The tool-call boundary marker information.
+    #[serde(rename = "toolMarker", skip_serializing_if = "Option::is_none")]
+    pub tool_marker: Option<ToolMarker>,
     /// Additional metadata for the event.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// A marker for a tool-call boundary, used by front-ends to render tool activity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolMarker {
+    /// The stable identifier of the plan or tool call the marker belongs to.
+    pub id: String,
+    /// The name of the tool.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// A short summary of the tool outcome.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// The number of tool calls in a plan.
+    #[serde(rename = "toolCount", skip_serializing_if = "Option::is_none")]
+    pub tool_count: Option<usize>,
+    /// Whether the tool call failed.
+    #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    /// A hint for rendering the tool output, from the tool's result renderer.
+    #[serde(rename = "uiHint", default, skip_serializing_if = "Option::is_none")]
+    pub ui_hint: Option<serde_json::Value>,
+}
+
 /// The type of stream event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -47,6 +74,9 @@ pub enum StreamEventType {
     ToolResultStop,
     MessageDelta,
     MessageStop,
+    ToolPlanStarted,
+    ToolExecuting,
+    ToolCompleted,
     Error,
 }
 
@@ -119,6 +149,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -131,6 +162,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -143,6 +175,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -155,6 +188,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -167,6 +201,7 @@ impl StreamEvent {
             tool_use: Some(tool_use),
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -179,6 +214,7 @@ impl StreamEvent {
             tool_use: Some(tool_use),
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -191,6 +227,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -203,6 +240,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: Some(tool_result),
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -215,6 +253,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: Some(tool_result),
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -227,6 +266,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -239,6 +279,7 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: Some(message_delta),
+            tool_marker: None,
             metadata: None,
         }
     }
@@ -251,6 +292,63 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
+            tool_marker: None,
+            metadata: None,
+        }
+    }
+
+    /// Create a marker event for the model planning one or more tool calls.
+    pub fn tool_plan_started(plan_id: &str, tool_count: usize) -> Self {
+        Self::tool_marker_event(StreamEventType::ToolPlanStarted, ToolMarker {
+            id: plan_id.to_string(),
+            name: None,
+            summary: None,
+            tool_count: Some(tool_count),
+            is_error: None,
+            ui_hint: None,
+        })
+    }
+
+    /// Create a marker event for a tool call starting to execute.
+    pub fn tool_executing(tool_use_id: &str, name: &str) -> Self {
+        Self::tool_marker_event(StreamEventType::ToolExecuting, ToolMarker {
+            id: tool_use_id.to_string(),
+            name: Some(name.to_string()),
+            summary: None,
+            tool_count: None,
+            is_error: None,
+            ui_hint: None,
+        })
+    }
+
+    /// Create a marker event for a tool call that finished.
+    pub fn tool_completed(tool_use_id: &str, name: &str, summary: &str, is_error: bool) -> Self {
+        Self::tool_marker_event(StreamEventType::ToolCompleted, ToolMarker {
+            id: tool_use_id.to_string(),
+            name: Some(name.to_string()),
+            summary: Some(summary.to_string()),
+            tool_count: None,
+            is_error: Some(is_error),
+            ui_hint: None,
+        })
+    }
+
+    /// Attach a rendering hint to a tool marker event; other events are returned unchanged.
+    pub fn with_ui_hint(mut self, hint: serde_json::Value) -> Self {
+        if let Some(ref mut marker) = self.tool_marker {
+            marker.ui_hint = Some(hint);
+        }
+        self
+    }
+
+    fn tool_marker_event(event_type: StreamEventType, marker: ToolMarker) -> Self {
+        Self {
+            event_type,
+            content: None,
+            tool_use: None,
+            tool_result: None,
+            message_delta: None,
+            tool_marker: Some(marker),
             metadata: None,
         }
     }
@@ -263,21 +361,45 @@ impl StreamEvent {
             tool_use: None,
             tool_result: None,
             message_delta: None,
-            metadata: Some({
-                let mut map = HashMap::new();
-                map.insert("error".to_string(), serde_json::Value::String(error_message.to_string()));
-                map
-            }),
+            tool_marker: None,
+            metadata: None,
         }
+        .with_metadata("error", serde_json::Value::String(error_message.to_string()))
+    }
+
+    /// Add metadata to the event, allocating the map only on first use.
+    pub fn with_metadata(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.metadata
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value);
+        self
+    }
+
+    /// Clear the event for reuse, keeping the content buffers allocated.
+    fn reset(&mut self) {
+        if let Some(ref mut content) = self.content {
+            for item in content.iter_mut() {
+                if let Some(ref mut text) = item.text {
+                    text.clear();
+                }
+                item.image = None;
+                item.document = None;
+            }
+        }
+        self.tool_use = None;
+        self.tool_result = None;
+        self.message_delta = None;
+        self.tool_marker = None;
+        self.metadata = None;
     }
 }
 
 impl StreamContent {
     /// Create a new text content block.
-    pub fn text(text: &str) -> Self {
+    pub fn text(text: impl Into<String>) -> Self {
         Self {
             content_type: StreamContentType::Text,
-            text: Some(text.to_string()),
+            text: Some(text.into()),
             image: None,
             document: None,
         }
@@ -303,3 +425,153 @@ impl StreamContent {
         }
     }
 }
+
+/// The default number of idle events retained by a [`StreamEventPool`].
+pub const DEFAULT_STREAM_EVENT_POOL_CAPACITY: usize = 1024;
+
+/// A pool of reusable stream events for the delta hot path.
+///
+/// Providers emit one event per token delta. Acquiring delta events from a
+/// pool and recycling them once consumed reuses the event, its content vector
+/// and its text buffer instead of allocating all three for every delta.
+#[derive(Debug)]
+pub struct StreamEventPool {
+    /// The idle events available for reuse.
+    free: Mutex<Vec<StreamEvent>>,
+    /// The maximum number of idle events to retain.
+    capacity: usize,
+    /// The number of acquisitions served from the pool.
+    hits: AtomicU64,
+    /// The number of acquisitions that had to allocate.
+    misses: AtomicU64,
+}
+
+/// Usage statistics for a [`StreamEventPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamEventPoolStats {
+    /// The number of acquisitions served from the pool.
+    pub hits: u64,
+    /// The number of acquisitions that had to allocate.
+    pub misses: u64,
+    /// The number of idle events currently held.
+    pub available: usize,
+}
+
+impl StreamEventPool {
+    /// Create a new pool with the default capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_STREAM_EVENT_POOL_CAPACITY)
+    }
+
+    /// Create a new pool retaining at most `capacity` idle events.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            free: Mutex::new(Vec::new()),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the process-wide pool shared by the model providers.
+    pub fn shared() -> &'static StreamEventPool {
+        static SHARED: OnceLock<StreamEventPool> = OnceLock::new();
+        SHARED.get_or_init(StreamEventPool::new)
+    }
+
+    /// Acquire a text delta event, reusing a recycled event when available.
+    pub fn text_delta(&self, text: &str) -> StreamEvent {
+        let recycled = self.free.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let mut event = match recycled {
+            Some(event) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                event
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                StreamEvent::content_block_delta(Vec::with_capacity(1))
+            }
+        };
+
+        event.event_type = StreamEventType::ContentBlockDelta;
+        let content = event.content.get_or_insert_with(Vec::new);
+        content.truncate(1);
+        match content.first_mut() {
+            Some(item) => {
+                item.content_type = StreamContentType::Text;
+                item.text.get_or_insert_with(String::new).push_str(text);
+            }
+            None => content.push(StreamContent::text(text)),
+        }
+        event
+    }
+
+    /// Return a consumed event to the pool.
+    ///
+    /// Events beyond the pool capacity are dropped.
+    pub fn recycle(&self, mut event: StreamEvent) {
+        event.reset();
+        let mut free = self.free.lock().unwrap_or_else(|e| e.into_inner());
+        if free.len() < self.capacity {
+            free.push(event);
+        }
+    }
+
+    /// Get the pool usage statistics.
+    pub fn stats(&self) -> StreamEventPoolStats {
+        StreamEventPoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            available: self.free.lock().unwrap_or_else(|e| e.into_inner()).len(),
+        }
+    }
+}
+
+impl Default for StreamEventPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_reuses_recycled_events() {
+        let pool = StreamEventPool::new();
+
+        let event = pool.text_delta("Hello");
+        assert_eq!(event.content.as_ref().unwrap()[0].text.as_deref(), Some("Hello"));
+        pool.recycle(event);
+
+        let event = pool.text_delta(" world");
+        let content = event.content.as_ref().unwrap();
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0].text.as_deref(), Some(" world"));
+        assert!(event.metadata.is_none());
+
+        let stats = pool.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[test]
+    fn test_pool_capacity_is_bounded() {
+        let pool = StreamEventPool::with_capacity(1);
+
+        pool.recycle(pool.text_delta("a"));
+        pool.recycle(StreamEvent::error("boom"));
+
+        assert_eq!(pool.stats().available, 1);
+    }
+
+    #[test]
+    fn test_error_event_metadata() {
+        let event = StreamEvent::error("boom");
+        assert_eq!(
+            event.metadata.unwrap().get("error"),
+            Some(&serde_json::Value::String("boom".to_string()))
+        );
+    }
+}